@@ -17,6 +17,7 @@
 use clap::Parser;
 
 use super::*;
+use utils::pass_instrument::PassInstrument;
 
 #[derive(Parser, Debug)]
 #[command(version,about,long_about=None)]
@@ -30,6 +31,36 @@ pub struct Cli {
     pub output: String,
     #[arg(short, long, value_name = "llvm_path")]
     pub ll: Option<String>,
+    /// Directories searched by `#include`
+    #[arg(short = 'I', value_name = "include_dir")]
+    pub include: Vec<String>,
+    /// Comma-separated middle-end passes to run instead of the default pipeline
+    #[arg(long, value_name = "passes")]
+    pub passes: Option<String>,
+    /// Dump IR before these passes (comma-separated, or `all`) into `--print-dir`
+    #[arg(long, value_name = "passes", value_delimiter = ',')]
+    pub print_before: Vec<String>,
+    /// Dump IR after these passes (comma-separated, or `all`) into `--print-dir`
+    #[arg(long, value_name = "passes", value_delimiter = ',')]
+    pub print_after: Vec<String>,
+    /// Directory to write IR dumps into
+    #[arg(long, value_name = "dir", default_value = "pass_dumps")]
+    pub print_dir: String,
+    /// Print wall time and changed flag of each pass to stderr
+    #[arg(long)]
+    pub time_passes: bool,
+}
+
+impl Cli {
+    /// Get pass instrumentation requested by command line.
+    pub fn pass_instrument(&self) -> PassInstrument {
+        PassInstrument::new(
+            self.print_before.clone(),
+            self.print_after.clone(),
+            PathBuf::from(&self.print_dir),
+            self.time_passes,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -67,4 +98,40 @@ mod tests {
         assert!(cli.asm);
         assert_eq!(cli.ll, Some("1.ll".to_string()));
     }
+
+    #[test]
+    fn test_include() {
+        let cli = super::Cli::parse_from([BIN, "1.sy", "-S", "-o", "1.s", "-Iinc", "-I", "lib"]);
+        assert_eq!(cli.include, ["inc", "lib"]);
+    }
+
+    #[test]
+    fn test_passes() {
+        let cli = super::Cli::parse_from([BIN, "1.sy", "-S", "-o", "1.s", "--passes=mem2reg,licm"]);
+        assert_eq!(cli.passes, Some("mem2reg,licm".to_string()));
+        let cli = super::Cli::parse_from([BIN, "1.sy", "-S", "-o", "1.s"]);
+        assert_eq!(cli.passes, None);
+    }
+
+    #[test]
+    fn test_pass_instrument() {
+        let cli = super::Cli::parse_from([
+            BIN,
+            "1.sy",
+            "-S",
+            "-o",
+            "1.s",
+            "--print-after=mem2reg,licm",
+            "--print-before",
+            "all",
+            "--time-passes",
+        ]);
+        assert_eq!(cli.print_after, ["mem2reg", "licm"]);
+        assert_eq!(cli.print_before, ["all"]);
+        assert_eq!(cli.print_dir, "pass_dumps");
+        assert!(cli.time_passes);
+        assert!(!cli.pass_instrument().is_empty());
+        let cli = super::Cli::parse_from([BIN, "1.sy", "-S", "-o", "1.s"]);
+        assert!(cli.pass_instrument().is_empty());
+    }
 }
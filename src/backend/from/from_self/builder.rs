@@ -94,6 +94,7 @@ impl IRBuilder {
         let mut reg_gener = RegGenerator::new();
         let mut regs: HashMap<Address, Reg> = HashMap::new();
         let mut insert_back_for_remove_phi = HashMap::new();
+        let mut switch_bbs = Vec::new();
 
         /* ---------- 根据 entry 创建 func ---------- */
         let (entry, caller_reg_stack) = Self::build_entry(
@@ -104,6 +105,7 @@ impl IRBuilder {
             &mut regs,
             fmms,
             &mut insert_back_for_remove_phi,
+            &mut switch_bbs,
         )?;
         let params: Vec<_> = self_func.params.iter().map(|p| p.name.clone()).collect();
         let mut m_f = Func::new(self_func.name.clone(), params, entry);
@@ -113,11 +115,15 @@ impl IRBuilder {
         match &self_func.return_type {
             middle::ir::ValueType::Void => { /* do nothing */ }
             middle::ir::ValueType::Int
+            | middle::ir::ValueType::UnsignedInt
+            | middle::ir::ValueType::Long
+            | middle::ir::ValueType::UnsignedLong
             | middle::ir::ValueType::Bool
+            | middle::ir::ValueType::SignedChar
             | middle::ir::ValueType::Pointer(_) => {
                 m_f.ret_mut().replace(REG_A0);
             }
-            middle::ir::ValueType::Float => {
+            middle::ir::ValueType::Float | middle::ir::ValueType::Double => {
                 m_f.ret_mut().replace(REG_FA0);
             }
             middle::ir::ValueType::Array(_, _) => todo!(),
@@ -133,10 +139,16 @@ impl IRBuilder {
             &mut regs,
             fmms,
             &mut insert_back_for_remove_phi,
+            &mut switch_bbs,
         )? {
             m_f.push_bb(bb);
         }
 
+        /* ---------- switch 生成的额外 bb ---------- */
+        for bb in switch_bbs {
+            m_f.push_bb(bb);
+        }
+
         /* ---------- phi ---------- */
         let mut bbs_mut = m_f // insert back to bbs to process phi
             .iter_bbs_mut()
@@ -148,6 +160,15 @@ impl IRBuilder {
                 .ok_or_else(|| anyhow!("{:?} not found", &&bb_name))
                 .with_context(|| context!())?;
             for (from, phi_dst) in insert_back {
+                if let Some(d) = Self::double_const_from(&from) {
+                    let (v_reg, prepare) = Self::_prepare_dmm(d, &mut reg_gener);
+                    for inst in prepare {
+                        bb.insert_before_term(inst)?;
+                    }
+                    let mv = MvInst::new(phi_dst.into(), v_reg.into());
+                    bb.insert_before_term(mv.into())?;
+                    continue;
+                }
                 let from = Self::no_load_from(&from, &regs)?;
                 match from {
                     Operand::Reg(_) => {
@@ -315,6 +336,7 @@ impl IRBuilder {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_other_bbs(
         func: &middle::ir::Function,
         stack_allocator: &mut StackAllocator,
@@ -323,6 +345,7 @@ impl IRBuilder {
         regs: &mut HashMap<Address, Reg>,
         fmms: &mut HashMap<Fmm, FloatVar>,
         insert_back_for_remove_phi: &mut HashMap<String, Vec<(middle::ir::Operand, Reg)>>,
+        switch_bbs: &mut Vec<Block>,
     ) -> Result<Vec<Block>> {
         func.bfs_iter()
             .skip(1)
@@ -335,11 +358,13 @@ impl IRBuilder {
                     regs,
                     fmms,
                     insert_back_for_remove_phi,
+                    switch_bbs,
                 )
             })
             .collect()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_bb(
         bb: &ObjPtr<middle::ir::BasicBlock>,
         stack_allocator: &mut StackAllocator,
@@ -348,10 +373,49 @@ impl IRBuilder {
         regs: &mut HashMap<Address, Reg>,
         fmms: &mut HashMap<Fmm, FloatVar>,
         insert_back_for_remove_phi: &mut HashMap<String, Vec<(middle::ir::Operand, Reg)>>,
+        switch_bbs: &mut Vec<Block>,
     ) -> Result<Block> {
         // basic 的 label 注意一下
         let mut m_bb = Block::new(Self::label_name_from(bb));
+        let gen_insts = Self::build_bb_insts(
+            bb,
+            stack_allocator,
+            stack_slots,
+            reg_gener,
+            regs,
+            fmms,
+            insert_back_for_remove_phi,
+            switch_bbs,
+        )?;
+        m_bb.extend_insts(gen_insts);
+        m_bb.depth = bb.depth;
+        Ok(m_bb)
+    }
+
+    /// 对 bb 中的指令做指令选择, switch 生成的额外 bb 放入 switch_bbs
+    #[allow(clippy::too_many_arguments)]
+    fn build_bb_insts(
+        bb: &ObjPtr<middle::ir::BasicBlock>,
+        stack_allocator: &mut StackAllocator,
+        stack_slots: &mut HashMap<Address, StackSlot>,
+        reg_gener: &mut RegGenerator,
+        regs: &mut HashMap<Address, Reg>,
+        fmms: &mut HashMap<Fmm, FloatVar>,
+        insert_back_for_remove_phi: &mut HashMap<String, Vec<(middle::ir::Operand, Reg)>>,
+        switch_bbs: &mut Vec<Block>,
+    ) -> Result<Vec<Inst>> {
+        let mut insts = Vec::new();
         for inst in bb.iter() {
+            if inst.get_type() == middle::ir::instruction::InstType::Switch {
+                let switch = downcast_ref::<middle::ir::instruction::terminator_inst::Switch>(
+                    inst.as_ref().as_ref(),
+                );
+                let (gen_insts, gen_bbs) =
+                    Self::build_switch_inst(switch, reg_gener, regs).with_context(|| context!())?;
+                insts.extend(gen_insts);
+                switch_bbs.extend(gen_bbs);
+                continue;
+            }
             let gen_insts = Self::build_instruction(
                 &inst,
                 stack_allocator,
@@ -362,12 +426,12 @@ impl IRBuilder {
                 insert_back_for_remove_phi,
             )
             .with_context(|| context!())?;
-            m_bb.extend_insts(gen_insts);
+            insts.extend(gen_insts);
         }
-        m_bb.depth = bb.depth;
-        Ok(m_bb)
+        Ok(insts)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_entry(
         func: &middle::ir::Function,
         stack_allocator: &mut StackAllocator,
@@ -376,6 +440,7 @@ impl IRBuilder {
         regs: &mut HashMap<Address, Reg>,
         fmms: &mut HashMap<Fmm, FloatVar>,
         insert_back_for_remove_phi: &mut HashMap<String, Vec<(middle::ir::Operand, Reg)>>,
+        switch_bbs: &mut Vec<Block>,
     ) -> Result<(Block, usize)> {
         /* ---------- 初始化 ---------- */
         let mut insts: Vec<Inst> = Vec::new();
@@ -386,10 +451,14 @@ impl IRBuilder {
         let mut usual_idx = 0;
         for param in func.params.iter() {
             let is_usual: bool = match &param.value_type {
-                middle::ir::ValueType::Float => false,
+                middle::ir::ValueType::Float | middle::ir::ValueType::Double => false,
                 middle::ir::ValueType::Pointer(_)
                 | middle::ir::ValueType::Bool
-                | middle::ir::ValueType::Int => true,
+                | middle::ir::ValueType::SignedChar
+                | middle::ir::ValueType::Int
+                | middle::ir::ValueType::UnsignedInt
+                | middle::ir::ValueType::Long
+                | middle::ir::ValueType::UnsignedLong => true,
                 middle::ir::ValueType::Void => {
                     return Err(anyhow!(
                         "it is impossible to receive void-type parameter: {}",
@@ -399,7 +468,9 @@ impl IRBuilder {
                 middle::ir::ValueType::Array(_, _) => {
                     return Err(anyhow!("array should be pointer {}", param))
                 }
-                middle::ir::ValueType::SignedChar => todo!(),
+                middle::ir::ValueType::Struct(_) | middle::ir::ValueType::Union(_) => {
+                    return Err(anyhow!("struct should be passed by pointer {}", param))
+                }
             };
             let v_reg = reg_gener.gen_virtual_reg(is_usual);
             regs.insert(param.as_ref() as *const _ as Address, v_reg); // 参数绑定寄存器
@@ -422,19 +493,16 @@ impl IRBuilder {
 
         /* ---------- 指令选择 ---------- */
         let bb = func.entry.with_context(|| context!())?;
-        for inst in bb.iter() {
-            let gen_insts = Self::build_instruction(
-                &inst,
-                stack_allocator,
-                stack_slots,
-                reg_gener,
-                regs,
-                fmms,
-                insert_back_for_remove_phi,
-            )
-            .with_context(|| context!())?;
-            insts.extend(gen_insts);
-        }
+        insts.extend(Self::build_bb_insts(
+            &bb,
+            stack_allocator,
+            stack_slots,
+            reg_gener,
+            regs,
+            fmms,
+            insert_back_for_remove_phi,
+            switch_bbs,
+        )?);
 
         /* ---------- 后端的 entry bb ---------- */
         let label = Self::label_name_from(&bb);
@@ -39,7 +39,13 @@ impl IRBuilder {
         let mut phisic_arg_regs: Vec<Reg> = Vec::new();
         let arguments = call.get_operand(); // 参数列表, 这个可以类比成 llvm_ir::call::arguments
         for arg in arguments {
-            let ope = Self::no_load_from(arg, regs).with_context(|| context!())?;
+            let ope = if let Some(d) = Self::double_const_from(arg) {
+                let (v_reg, prepare) = Self::_prepare_dmm(d, reg_gener);
+                ret_insts.extend(prepare);
+                v_reg.into()
+            } else {
+                Self::no_load_from(arg, regs).with_context(|| context!())?
+            };
             match ope {
                 Operand::Reg(r) => {
                     if r.is_usual() && i_arg_num < 8 {
@@ -151,12 +157,14 @@ impl IRBuilder {
                 ret_insts.push(call_inst.into());
             }
             | middle::ir::ValueType::Int
+            | middle::ir::ValueType::UnsignedInt
+            | middle::ir::ValueType::Long
+            | middle::ir::ValueType::UnsignedLong
             | middle::ir::ValueType::Float
-            | middle::ir::ValueType::Bool => {
-                let (dst, ret_a0) = if
-                    func.return_type == middle::ir::ValueType::Int ||
-                    func.return_type == middle::ir::ValueType::Bool
-                {
+            | middle::ir::ValueType::Double
+            | middle::ir::ValueType::Bool
+            | middle::ir::ValueType::SignedChar => {
+                let (dst, ret_a0) = if !func.return_type.is_float() {
                     (reg_gener.gen_virtual_usual_reg(), REG_A0)
                 } else {
                     (reg_gener.gen_virtual_float_reg(), REG_FA0)
@@ -169,7 +177,7 @@ impl IRBuilder {
             }
             _ => {
                 return Err(
-                    anyhow!("sysy only return: void | float | int".to_string())
+                    anyhow!("only scalar return type is supported".to_string())
                 ).with_context(|| context!());
             }
         }
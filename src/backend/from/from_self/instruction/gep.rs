@@ -31,17 +31,12 @@ impl IRBuilder {
         fprintln!("log/build_gep_inst.log";'a';"gep:{}",gep);
 
         let idxes = gep.get_index();
-        let ty = gep.get_ptr().get_type();
-        let (_, ofst, prepare) =
-            Self::_cal_offset(&ty, idxes, reg_gener, regs).with_context(|| context!())?;
+        let (ofst, prepare) = Self::_cal_offset(&gep.element_type, idxes, reg_gener, regs)
+            .with_context(|| context!())?;
         ret.extend(prepare);
-        let _mid = reg_gener.gen_virtual_usual_reg();
 
         fprintln!("log/build_gep_inst.log";'a';"ofst:{:?}",ofst);
 
-        let slli = SllInst::new(_mid.into(), ofst.into(), (2).into()).with_8byte(); // sysy 的数据都是 4Byte
-        ret.push(slli.into());
-
         // /* ---------- base ---------- */
         let ptr = gep.get_ptr();
         let base: Reg =
@@ -65,7 +60,7 @@ impl IRBuilder {
         fprintln!("log/build_gep_inst.log";'a';"base:{:?}", base);
 
         let dst = reg_gener.gen_virtual_usual_reg();
-        let add = AddInst::new(dst.into(), base.into(), _mid.into()).with_8byte();
+        let add = AddInst::new(dst.into(), base.into(), ofst.into()).with_8byte();
 
         fprintln!("log/build_gep_inst.log";'a';"final:{:?}", dst);
 
@@ -74,81 +69,71 @@ impl IRBuilder {
         Ok(ret)
     }
 
+    /// Calculate offset of `getelementptr` in bytes.
+    /// The first index strides over `ty`,
+    /// and each later index selects an array element or a struct field inside.
     fn _cal_offset(
         ty: &middle::ir::ValueType,
         idxes: &[middle::ir::Operand],
         reg_gener: &mut RegGenerator,
         regs: &HashMap<Address, Reg>,
-    ) -> Result<(
-        usize, /* 部分阶乘 */
-        Reg,   /* 部分结果 */
-        Vec<Inst>,
-    )> {
+    ) -> Result<(Reg, Vec<Inst>)> {
         let mut ret = Vec::new();
-        if idxes.is_empty() {
-            let factor = Self::_cal_capas_factor(ty).with_context(|| context!())?;
-            // println!("ty={}, factor={}", ty, factor);
-            return Ok((factor, REG_ZERO, ret));
-        }
-        match ty {
-            middle::ir::ValueType::Void => {
-                Err(anyhow!("gep can't be void: {}", ty)).with_context(|| context!())
-            }
-            middle::ir::ValueType::SignedChar
-            | middle::ir::ValueType::Int
-            | middle::ir::ValueType::Bool
-            | middle::ir::ValueType::Float => {
-                assert!(idxes.len() == 1); // 这种情况是 idxes 和 types 同时耗尽
-                let (idx, prepare) =
-                    Self::prepare_rs1_i(&idxes[0], reg_gener, regs).with_context(|| context!())?;
-                ret.extend(prepare);
-                // println!("{}", ty);
-                Ok((1, idx, ret))
-            }
-            middle::ir::ValueType::Array(ty, sz) => {
-                // 这里 ty 是拿到当前数组的类型
-                let (idx, prepare) =
-                    Self::prepare_rs1_i(&idxes[0], reg_gener, regs).with_context(|| context!())?;
-                ret.extend(prepare);
-                let (_factor, _acc, prepare) = Self::_cal_offset(ty, &idxes[1..], reg_gener, regs)
-                    .with_context(|| context!())?;
-                ret.extend(prepare);
-                let factor = sz * _factor; // 当前类型的 sizeof
-                let part = reg_gener.gen_virtual_usual_reg(); // 部分积
-                let mul =
-                    MulInst::new(part.into(), idx.into(), (factor as i64).into()).with_8byte();
-                ret.push(mul.into());
-                let acc = reg_gener.gen_virtual_usual_reg(); // 部分结果
-                let add = AddInst::new(acc.into(), _acc.into(), part.into()).with_8byte();
-                ret.push(add.into());
-                Ok((factor, acc, ret))
-            }
-            // %getelementptr_85 = getelementptr i32, ptr %getelementptr_84, i32 0
-            // %getelementptr_57 = getelementptr [2 x i32], ptr %getelementptr_38, i32 3
-            // %getelementptr_58 = getelementptr [2 x i32], ptr %getelementptr_57, i32 0, i32 0
-            // 应该这么说, gep 的第一层永远是 ptr
-            middle::ir::ValueType::Pointer(poi) => Self::_cal_offset(poi, idxes, reg_gener, regs),
+        let Some((first, rest)) = idxes.split_first() else {
+            return Ok((REG_ZERO, ret));
+        };
+        let mut acc = Self::_cal_stride(first, ty.size(), REG_ZERO, reg_gener, regs, &mut ret)
+            .with_context(|| context!())?;
+        let mut ty = ty;
+        for idx in rest {
+            let sub_ty = ty
+                .get_indexed_type(idx)
+                .ok_or_else(|| anyhow!("gep can't index into {} with {}", ty, idx))
+                .with_context(|| context!())?;
+            acc = match ty {
+                middle::ir::ValueType::Struct(_) => {
+                    // 结构体的字段下标一定是常量, 偏移在编译期确定
+                    let middle::ir::Operand::Constant(middle::ir::Constant::Int(field)) = idx
+                    else {
+                        return Err(anyhow!("field index must be constant: {}", idx))
+                            .with_context(|| context!());
+                    };
+                    let offset = ty
+                        .field_offset(*field as usize)
+                        .ok_or_else(|| anyhow!("field {} not found in {}", field, ty))
+                        .with_context(|| context!())?;
+                    let dst = reg_gener.gen_virtual_usual_reg();
+                    let add =
+                        AddInst::new(dst.into(), acc.into(), (offset as i64).into()).with_8byte();
+                    ret.push(add.into());
+                    dst
+                }
+                _ => Self::_cal_stride(idx, sub_ty.size(), acc, reg_gener, regs, &mut ret)
+                    .with_context(|| context!())?,
+            };
+            ty = sub_ty;
         }
+        Ok((acc, ret))
     }
-}
 
-/** @brief 一些针对数组的 辅助函数
- */
-impl IRBuilder {
-    pub fn _cal_capas_factor(ty: &middle::ir::ValueType) -> Result<usize> {
-        match ty {
-            middle::ir::ValueType::Void => {
-                Err(anyhow!("gep can't be void: {}", ty)).with_context(|| context!())
-            }
-            middle::ir::ValueType::Pointer(_) => todo!(),
-            middle::ir::ValueType::SignedChar
-            | middle::ir::ValueType::Int
-            | middle::ir::ValueType::Float
-            | middle::ir::ValueType::Bool => Ok(1),
-            middle::ir::ValueType::Array(ty, sz) => {
-                let ty = ty.as_ref();
-                Ok(sz * Self::_cal_capas_factor(ty).with_context(|| context!())?)
-            }
-        }
+    /// Add `idx * size` to `acc`, returns the register of result.
+    fn _cal_stride(
+        idx: &middle::ir::Operand,
+        size: usize,
+        acc: Reg,
+        reg_gener: &mut RegGenerator,
+        regs: &HashMap<Address, Reg>,
+        ret: &mut Vec<Inst>,
+    ) -> Result<Reg> {
+        let (idx, prepare) =
+            Self::prepare_rs1_i(idx, reg_gener, regs).with_context(|| context!())?;
+        ret.extend(prepare);
+        let part = reg_gener.gen_virtual_usual_reg(); // 部分积
+        let mul = MulInst::new(part.into(), idx.into(), (size as i64).into()).with_8byte();
+        ret.push(mul.into());
+        let dst = reg_gener.gen_virtual_usual_reg(); // 部分结果
+        let add = AddInst::new(dst.into(), acc.into(), part.into()).with_8byte();
+        ret.push(add.into());
+        Ok(dst)
     }
 }
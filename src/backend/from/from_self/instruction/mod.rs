@@ -17,6 +17,7 @@
 mod call;
 mod gep;
 mod normal;
+mod switch;
 
 use std::collections::HashMap;
 
@@ -83,8 +83,12 @@ impl IRBuilder {
             middle::ir::instruction::InstType::SRem => {
                 ssa2tac_three_usual_Itype!(RemInst, SRem, inst, regs, reg_gener)
             }
-            middle::ir::instruction::InstType::UDiv => todo!(), // TODO 目前还没有 udiv 和 urem
-            middle::ir::instruction::InstType::URem => todo!(),
+            middle::ir::instruction::InstType::UDiv => {
+                ssa2tac_three_usual_Itype!(UdivInst, UDiv, inst, regs, reg_gener)
+            }
+            middle::ir::instruction::InstType::URem => {
+                ssa2tac_three_usual_Itype!(UremInst, URem, inst, regs, reg_gener)
+            }
             middle::ir::instruction::InstType::FDiv => {
                 ssa2tac_three_float!(DivInst, FDiv, inst, regs, reg_gener, fmms)
             }
@@ -120,6 +124,9 @@ impl IRBuilder {
                 );
                 Self::build_br_inst(br, regs)
             }
+            middle::ir::instruction::InstType::Switch => {
+                Err(anyhow!("switch should be built with its blocks")).with_context(|| context!())
+            } // switch 会生成额外的块, 由 build_bb 处理
             middle::ir::instruction::InstType::Load => {
                 let load = downcast_ref::<middle::ir::instruction::memory_op_inst::Load>(
                     inst.as_ref().as_ref()
@@ -138,7 +145,31 @@ impl IRBuilder {
                 );
                 Self::build_zext_inst(zext, reg_gener, regs)
             }
-            middle::ir::instruction::InstType::SextTo => todo!(),
+            middle::ir::instruction::InstType::SextTo => {
+                let sext = downcast_ref::<middle::ir::instruction::extend_inst::SextTo>(
+                    inst.as_ref().as_ref()
+                );
+                Self::build_sext_inst(sext, reg_gener, regs)
+            }
+            middle::ir::instruction::InstType::Trunc => {
+                let trunc = downcast_ref::<middle::ir::instruction::extend_inst::Trunc>(
+                    inst.as_ref().as_ref()
+                );
+                Self::build_trunc_inst(trunc, reg_gener, regs)
+            }
+            middle::ir::instruction::InstType::BitCast => {
+                let bitcast = downcast_ref::<middle::ir::instruction::extend_inst::BitCast>(
+                    inst.as_ref().as_ref()
+                );
+                // 只有同宽度整数之间的 bitcast, 寄存器中的位模式不变
+                let (src, prepare) = Self::prepare_rs1_i(
+                    bitcast.get_src(),
+                    reg_gener,
+                    regs
+                ).with_context(|| context!())?;
+                regs.insert(bitcast as *const _ as Address, src);
+                Ok(prepare)
+            }
             middle::ir::instruction::InstType::ItoFp => {
                 let i2fp = downcast_ref::<middle::ir::instruction::extend_inst::ItoFp>(
                     inst.as_ref().as_ref()
@@ -151,7 +182,17 @@ impl IRBuilder {
                 ).with_context(|| context!())?;
                 ret.extend(prepare);
                 let dst = reg_gener.gen_virtual_float_reg();
-                let fcvtsw = I2fInst::new(dst.into(), src.into());
+                let src_ty = i2fp.get_src().get_type();
+                let mut fcvtsw = I2fInst::new(dst.into(), src.into());
+                if src_ty.size() == 8 {
+                    fcvtsw = fcvtsw.with_8byte();
+                }
+                if src_ty.is_unsigned() {
+                    fcvtsw = fcvtsw.with_unsigned();
+                }
+                if i2fp.get_value_type() == middle::ir::ValueType::Double {
+                    fcvtsw = fcvtsw.with_double();
+                }
                 ret.push(fcvtsw.into());
                 regs.insert(i2fp as *const _ as Address, dst);
                 Ok(ret)
@@ -160,11 +201,78 @@ impl IRBuilder {
                 let fptoi = downcast_ref::<middle::ir::instruction::extend_inst::FpToI>(
                     inst.as_ref().as_ref()
                 );
-                let src = Self::no_load_from(fptoi.get_src(), regs).with_context(|| context!())?;
+                let mut ret = Vec::new();
+                let (src, prepare) = Self::prepare_f(
+                    fptoi.get_src(),
+                    reg_gener,
+                    regs,
+                    fmms
+                ).with_context(|| context!())?;
+                ret.extend(prepare);
                 let dst = reg_gener.gen_virtual_usual_reg();
-                let fcvtws = F2iInst::new(dst.into(), src);
+                let dst_ty = fptoi.get_value_type();
+                let mut fcvtws = F2iInst::new(dst.into(), src);
+                if dst_ty.size() == 8 {
+                    fcvtws = fcvtws.with_8byte();
+                }
+                if dst_ty.is_unsigned() {
+                    fcvtws = fcvtws.with_unsigned();
+                }
+                if fptoi.get_src().get_type() == middle::ir::ValueType::Double {
+                    fcvtws = fcvtws.with_double();
+                }
+                ret.push(fcvtws.into());
                 regs.insert(fptoi as *const _ as Address, dst);
-                Ok(vec![fcvtws.into()])
+                Ok(ret)
+            }
+            middle::ir::instruction::InstType::FpExt => {
+                let fpext = downcast_ref::<middle::ir::instruction::extend_inst::FpExt>(
+                    inst.as_ref().as_ref()
+                );
+                let mut ret = Vec::new();
+                let (src, prepare) = Self::prepare_f(
+                    fpext.get_src(),
+                    reg_gener,
+                    regs,
+                    fmms
+                ).with_context(|| context!())?;
+                ret.extend(prepare);
+                let dst = reg_gener.gen_virtual_float_reg();
+                let fcvtds = F2dInst::new(dst.into(), src);
+                ret.push(fcvtds.into());
+                regs.insert(fpext as *const _ as Address, dst);
+                Ok(ret)
+            }
+            middle::ir::instruction::InstType::FpTrunc => {
+                let fptrunc = downcast_ref::<middle::ir::instruction::extend_inst::FpTrunc>(
+                    inst.as_ref().as_ref()
+                );
+                let mut ret = Vec::new();
+                let (src, prepare) = Self::prepare_f(
+                    fptrunc.get_src(),
+                    reg_gener,
+                    regs,
+                    fmms
+                ).with_context(|| context!())?;
+                ret.extend(prepare);
+                let dst = reg_gener.gen_virtual_float_reg();
+                let fcvtsd = D2fInst::new(dst.into(), src);
+                ret.push(fcvtsd.into());
+                regs.insert(fptrunc as *const _ as Address, dst);
+                Ok(ret)
+            }
+            middle::ir::instruction::InstType::PtrToI => {
+                let ptrtoi = downcast_ref::<middle::ir::instruction::extend_inst::PtrToI>(
+                    inst.as_ref().as_ref()
+                );
+                // 指针本身就在寄存器中, 直接复用
+                let Operand::Reg(src) = Self::no_load_from(ptrtoi.get_src(), regs).with_context(
+                    || context!()
+                )? else {
+                    return Err(anyhow!("ptrtoint from non-reg")).with_context(|| context!());
+                };
+                regs.insert(ptrtoi as *const _ as Address, src);
+                Ok(vec![])
             }
             middle::ir::instruction::InstType::ICmp => {
                 let icmp = downcast_ref::<middle::ir::instruction::misc_inst::ICmp>(
@@ -232,55 +340,73 @@ impl IRBuilder {
             Ok((op0, op1))
         }
 
+        /// double 的比较使用 .d 后缀的指令
+        fn __feq(dst: Operand, op0: Operand, op1: Operand, is_double: bool) -> Inst {
+            if is_double {
+                FeqdInst::new(dst, op0, op1).into()
+            } else {
+                FeqsInst::new(dst, op0, op1).into()
+            }
+        }
+        fn __flt(dst: Operand, op0: Operand, op1: Operand, is_double: bool) -> Inst {
+            if is_double {
+                FltdInst::new(dst, op0, op1).into()
+            } else {
+                FltsInst::new(dst, op0, op1).into()
+            }
+        }
+        fn __fle(dst: Operand, op0: Operand, op1: Operand, is_double: bool) -> Inst {
+            if is_double {
+                FledInst::new(dst, op0, op1).into()
+            } else {
+                FlesInst::new(dst, op0, op1).into()
+            }
+        }
+
         /* ---------- 正文 ---------- */
 
         let flag = reg_gener.gen_virtual_usual_reg();
         regs.insert(fcmp as *const _ as Address, flag);
 
         let mut ret = Vec::new();
+        let is_double = fcmp.get_lhs().get_type() == middle::ir::ValueType::Double;
 
         match fcmp.op {
             | middle::ir::instruction::misc_inst::FCmpOp::Oeq
             | middle::ir::instruction::misc_inst::FCmpOp::Ueq => {
                 let (op0, op1) = __prepare_normal_op0_op1(fcmp, reg_gener, regs, &mut ret, fmms)?;
-                let feqs = FeqsInst::new(flag.into(), op0, op1);
-                ret.push(feqs.into());
+                ret.push(__feq(flag.into(), op0, op1, is_double));
             }
             | middle::ir::instruction::misc_inst::FCmpOp::One
             | middle::ir::instruction::misc_inst::FCmpOp::Une => {
                 // a != b <=> !(a == b) <=> (a == b) == 0
                 let (op0, op1) = __prepare_normal_op0_op1(fcmp, reg_gener, regs, &mut ret, fmms)?;
                 let _mid = reg_gener.gen_virtual_usual_reg();
-                let feqs = FeqsInst::new(_mid.into(), op0, op1);
-                ret.push(feqs.into());
+                ret.push(__feq(_mid.into(), op0, op1, is_double));
                 let seqz = SeqzInst::new(flag.into(), _mid.into());
                 ret.push(seqz.into());
             }
             | middle::ir::instruction::misc_inst::FCmpOp::Olt
             | middle::ir::instruction::misc_inst::FCmpOp::Ult => {
                 let (op0, op1) = __prepare_normal_op0_op1(fcmp, reg_gener, regs, &mut ret, fmms)?;
-                let flts = FltsInst::new(flag.into(), op0, op1);
-                ret.push(flts.into());
+                ret.push(__flt(flag.into(), op0, op1, is_double));
             }
             | middle::ir::instruction::misc_inst::FCmpOp::Ole
             | middle::ir::instruction::misc_inst::FCmpOp::Ule => {
                 let (op0, op1) = __prepare_normal_op0_op1(fcmp, reg_gener, regs, &mut ret, fmms)?;
-                let fles = FlesInst::new(flag.into(), op0, op1);
-                ret.push(fles.into());
+                ret.push(__fle(flag.into(), op0, op1, is_double));
             }
             | middle::ir::instruction::misc_inst::FCmpOp::Ogt
             | middle::ir::instruction::misc_inst::FCmpOp::Ugt => {
                 // a > b <=> b < a <=> op0 < op1
                 let (op0, op1) = __prepare_rev_op0_op1(fcmp, reg_gener, regs, &mut ret, fmms)?;
-                let flts = FltsInst::new(flag.into(), op0, op1);
-                ret.push(flts.into());
+                ret.push(__flt(flag.into(), op0, op1, is_double));
             }
             | middle::ir::instruction::misc_inst::FCmpOp::Oge
             | middle::ir::instruction::misc_inst::FCmpOp::Uge => {
                 // a >= b <=> b <= a
                 let (op0, op1) = __prepare_rev_op0_op1(fcmp, reg_gener, regs, &mut ret, fmms)?;
-                let fles = FlesInst::new(flag.into(), op0, op1);
-                ret.push(fles.into());
+                ret.push(__fle(flag.into(), op0, op1, is_double));
             }
             middle::ir::instruction::misc_inst::FCmpOp::Ord => todo!(),
             middle::ir::instruction::misc_inst::FCmpOp::Uno => todo!(),
@@ -318,6 +444,7 @@ impl IRBuilder {
         Ok(vec![])
     }
 
+    /// bool in register is already zero extended, 32-bit value is zero extended by `slli` and `srli`
     fn build_zext_inst(
         zext: &middle::ir::instruction::extend_inst::ZextTo,
         reg_gener: &mut RegGenerator,
@@ -328,7 +455,64 @@ impl IRBuilder {
             || context!()
         )?;
         ret.extend(prepare);
-        regs.insert(zext as *const _ as Address, src);
+        if zext.get_src().get_type().size() == 4 && zext.get_value_type().size() == 8 {
+            let dst = reg_gener.gen_virtual_usual_reg();
+            let slli = SllInst::new(dst.into(), src.into(), (32).into()).with_8byte();
+            let srli = SrlInst::new(dst.into(), dst.into(), (32).into()).with_8byte();
+            ret.push(slli.into());
+            ret.push(srli.into());
+            regs.insert(zext as *const _ as Address, dst);
+        } else {
+            regs.insert(zext as *const _ as Address, src);
+        }
+        Ok(ret)
+    }
+
+    /// char in register is already sign extended, bool is negated to all ones
+    fn build_sext_inst(
+        sext: &middle::ir::instruction::extend_inst::SextTo,
+        reg_gener: &mut RegGenerator,
+        regs: &mut HashMap<Address, Reg>
+    ) -> Result<Vec<Inst>> {
+        let mut ret = Vec::new();
+        let (src, prepare) = Self::prepare_rs1_i(sext.get_src(), reg_gener, regs).with_context(
+            || context!()
+        )?;
+        ret.extend(prepare);
+        if sext.get_src().get_type() == middle::ir::ValueType::Bool {
+            let dst = reg_gener.gen_virtual_usual_reg();
+            let neg = NegInst::new(dst.into(), src.into());
+            ret.push(neg.into());
+            regs.insert(sext as *const _ as Address, dst);
+        } else {
+            regs.insert(sext as *const _ as Address, src);
+        }
+        Ok(ret)
+    }
+
+    /// truncate to 32-bit by `addiw`, to char by keeping the lowest byte and sign extending it
+    /// to the whole register, by `slli` and `srai`
+    fn build_trunc_inst(
+        trunc: &middle::ir::instruction::extend_inst::Trunc,
+        reg_gener: &mut RegGenerator,
+        regs: &mut HashMap<Address, Reg>
+    ) -> Result<Vec<Inst>> {
+        let mut ret = Vec::new();
+        let (src, prepare) = Self::prepare_rs1_i(trunc.get_src(), reg_gener, regs).with_context(
+            || context!()
+        )?;
+        ret.extend(prepare);
+        let dst = reg_gener.gen_virtual_usual_reg();
+        if trunc.get_value_type().size() == 4 {
+            let addiw = AddInst::new(dst.into(), src.into(), (0).into());
+            ret.push(addiw.into());
+        } else {
+            let slli = SllInst::new(dst.into(), src.into(), (56).into()).with_8byte();
+            let srai = SraInst::new(dst.into(), dst.into(), (56).into());
+            ret.push(slli.into());
+            ret.push(srai.into());
+        }
+        regs.insert(trunc as *const _ as Address, dst);
         Ok(ret)
     }
 
@@ -424,10 +608,33 @@ impl IRBuilder {
                 ret.push(slt.into());
                 ret.push(seqz.into());
             }
-            middle::ir::instruction::misc_inst::ICmpOp::Ult => todo!(),
-            middle::ir::instruction::misc_inst::ICmpOp::Ule => todo!(),
-            middle::ir::instruction::misc_inst::ICmpOp::Ugt => todo!(),
-            middle::ir::instruction::misc_inst::ICmpOp::Uge => todo!(),
+            // 32 位数在寄存器中是符号扩展的, 符号扩展不改变无符号数之间的大小关系
+            middle::ir::instruction::misc_inst::ICmpOp::Ult => {
+                let (op0, op1) = __prepare_normal_op0_op1(icmp, reg_gener, regs, &mut ret)?;
+                let sltu = SltuInst::new(flag.into(), op0, op1);
+                ret.push(sltu.into());
+            }
+            middle::ir::instruction::misc_inst::ICmpOp::Ule => {
+                let (op0, op1) = __prepare_rev_op0_op1(icmp, reg_gener, regs, &mut ret)?;
+                let _mid = reg_gener.gen_virtual_usual_reg();
+                let sltu = SltuInst::new(_mid.into(), op0, op1);
+                let seqz = SeqzInst::new(flag.into(), _mid.into());
+                ret.push(sltu.into());
+                ret.push(seqz.into());
+            }
+            middle::ir::instruction::misc_inst::ICmpOp::Ugt => {
+                let (op0, op1) = __prepare_rev_op0_op1(icmp, reg_gener, regs, &mut ret)?;
+                let sltu = SltuInst::new(flag.into(), op0, op1);
+                ret.push(sltu.into());
+            }
+            middle::ir::instruction::misc_inst::ICmpOp::Uge => {
+                let (op0, op1) = __prepare_normal_op0_op1(icmp, reg_gener, regs, &mut ret)?;
+                let _mid = reg_gener.gen_virtual_usual_reg();
+                let sltu = SltuInst::new(_mid.into(), op0, op1);
+                let seqz = SeqzInst::new(flag.into(), _mid.into());
+                ret.push(sltu.into());
+                ret.push(seqz.into());
+            }
         }
         Ok(ret)
     }
@@ -441,16 +648,22 @@ impl IRBuilder {
         let ty = alloca.value_type.clone();
         let bytes: u32 = match ty {
             | middle::ir::ValueType::Int
+            | middle::ir::ValueType::UnsignedInt
+            | middle::ir::ValueType::Long
+            | middle::ir::ValueType::UnsignedLong
             | middle::ir::ValueType::Float
+            | middle::ir::ValueType::Double
             | middle::ir::ValueType::Bool
             | middle::ir::ValueType::SignedChar
             | middle::ir::ValueType::Pointer(_) => 8,
             middle::ir::ValueType::Void => {
                 return Err(anyhow!("it can't alloca void")).with_context(|| context!());
             }
-            middle::ir::ValueType::Array(_, _) => {
-                let cap = Self::_cal_capas_factor(&ty).with_context(|| context!())?;
-                (cap << 2) as u32
+            | middle::ir::ValueType::Array(_, _)
+            | middle::ir::ValueType::Struct(_)
+            | middle::ir::ValueType::Union(_) => {
+                // 按 8 字节对齐, 保证结构体中的指针字段对齐
+                ty.size().next_multiple_of(8) as u32
             }
         };
         let ss = stack_allocator.alloc(bytes);
@@ -486,23 +699,41 @@ impl IRBuilder {
 
         let mut ret: Vec<Inst> = Vec::new();
         ret.extend(prepare);
+        let is_8byte = store.get_value().get_type().size() == 8;
+        let is_byte = store.get_value().get_type() == middle::ir::ValueType::SignedChar;
         match addr {
             Operand::Reg(base) => {
                 // load/store Reg 的来源只能是 gep ->
-                let sw = SwInst::new(val, (0).into(), base);
-                ret.push(sw.into());
+                if is_8byte {
+                    let sd = SdInst::new(val, (0).into(), base);
+                    ret.push(sd.into());
+                } else if is_byte {
+                    let sb = SbInst::new(val, (0).into(), base);
+                    ret.push(sb.into());
+                } else {
+                    let sw = SwInst::new(val, (0).into(), base);
+                    ret.push(sw.into());
+                }
             }
             Operand::StackSlot(slot) => {
                 let sd = StoreInst::new(slot, val).with_8byte();
                 ret.push(sd.into());
             }
             Operand::Label(label) => {
-                // load/store label 只能是全局数组/变量, 全局数组一定只能是 int/float -> 4Byte -> sw/lw
+                // load/store label 只能是全局数组/变量, char -> 1Byte -> sb/lb, 8Byte -> sd/ld, 其余 -> 4Byte -> sw/lw
                 let addr = reg_gener.gen_virtual_usual_reg();
                 let lla = LlaInst::new(addr, label);
                 ret.push(lla.into());
-                let sw = SwInst::new(val, (0).into(), addr);
-                ret.push(sw.into());
+                if is_8byte {
+                    let sd = SdInst::new(val, (0).into(), addr);
+                    ret.push(sd.into());
+                } else if is_byte {
+                    let sb = SbInst::new(val, (0).into(), addr);
+                    ret.push(sb.into());
+                } else {
+                    let sw = SwInst::new(val, (0).into(), addr);
+                    ret.push(sw.into());
+                }
             }
             _ => {
                 return Err(anyhow!("impossible to store from imm/fmm")).with_context(|| context!());
@@ -522,24 +753,28 @@ impl IRBuilder {
         // if regs.contains_key(&(load as *const _ as Address)) {
         //     unimplemented!() // 已经 load 过一次了
         // }
-        let dst_reg = match load.get_value_type() {
-            middle::ir::ValueType::Float => reg_gener.gen_virtual_float_reg(),
-            | middle::ir::ValueType::Int
-            | middle::ir::ValueType::Bool
-            | middle::ir::ValueType::Pointer(_) => reg_gener.gen_virtual_usual_reg(),
-            _ => {
-                return Err(anyhow!("load instruction to array/void")).with_context(|| context!());
-            }
-        };
+        let dst_reg = Self::new_var(&load.get_value_type(), reg_gener).with_context(
+            || context!()
+        )?;
         regs.insert(load as *const _ as Address, dst_reg);
         // 两种情况: 1. 从栈上获取(之前 alloca 过一次), 2. 从非栈上获取(parameter-pointer, global)
         let addr = Self::address_from(load.get_ptr(), regs, stack_slots).with_context(
             || context!()
         )?;
+        let is_8byte = load.get_value_type().size() == 8;
+        let is_byte = load.get_value_type() == middle::ir::ValueType::SignedChar;
         match addr {
             Operand::Reg(base) => {
-                let lw = LwInst::new(dst_reg, (0).into(), base);
-                ret.push(lw.into());
+                if is_8byte {
+                    let ld = LdInst::new(dst_reg, (0).into(), base);
+                    ret.push(ld.into());
+                } else if is_byte {
+                    let lb = LbInst::new(dst_reg, (0).into(), base);
+                    ret.push(lb.into());
+                } else {
+                    let lw = LwInst::new(dst_reg, (0).into(), base);
+                    ret.push(lw.into());
+                }
             }
             Operand::StackSlot(slot) => {
                 let ld = LoadInst::new(dst_reg, slot).with_8byte(); // 对于 stack, 就是使用的 ld
@@ -549,8 +784,16 @@ impl IRBuilder {
                 let addr = reg_gener.gen_virtual_usual_reg();
                 let lla = LlaInst::new(addr, label);
                 ret.push(lla.into());
-                let lw = LwInst::new(dst_reg, (0).into(), addr);
-                ret.push(lw.into());
+                if is_8byte {
+                    let ld = LdInst::new(dst_reg, (0).into(), addr);
+                    ret.push(ld.into());
+                } else if is_byte {
+                    let lb = LbInst::new(dst_reg, (0).into(), addr);
+                    ret.push(lb.into());
+                } else {
+                    let lw = LwInst::new(dst_reg, (0).into(), addr);
+                    ret.push(lw.into());
+                }
             }
             _ => {
                 return Err(anyhow!("impossible to load from imm/fmm")).with_context(|| context!());
@@ -588,6 +831,19 @@ impl IRBuilder {
                             let li = AddInst::new(REG_A0.into(), REG_ZERO.into(), imm);
                             ret_insts.push(li.into());
                         }
+                        | middle::ir::Constant::UnsignedInt(_)
+                        | middle::ir::Constant::Long(_)
+                        | middle::ir::Constant::UnsignedLong(_) => {
+                            let imm = Self::const_except_arr_from(c)?;
+                            let li = LiInst::new(REG_A0.into(), imm);
+                            ret_insts.push(li.into());
+                        }
+                        middle::ir::Constant::Double(d) => {
+                            let (dst, prepare) = Self::_prepare_dmm(*d, reg_gener);
+                            ret_insts.extend(prepare);
+                            let mv = MvInst::new(REG_FA0.into(), dst.into());
+                            ret_insts.push(mv.into());
+                        }
                         middle::ir::Constant::Float(f) => {
                             let fmm: Fmm = f.into();
                             let n = if let Some(f_var) = fmms.get(&fmm) {
@@ -608,7 +864,9 @@ impl IRBuilder {
                             let loadf = LwInst::new(REG_FA0, (0).into(), addr);
                             ret_insts.push(loadf.into());
                         }
-                        middle::ir::Constant::Array(_) | middle::ir::Constant::Zero(_) => {
+                        | middle::ir::Constant::Array(_)
+                        | middle::ir::Constant::Struct(..)
+                        | middle::ir::Constant::Zero(_) => {
                             return Err(anyhow!("return array is not allow:{}", op)).with_context(
                                 || context!()
                             );
@@ -625,11 +883,15 @@ impl IRBuilder {
                     )?;
                     let mv_inst = match instr.get_value_type() {
                         | middle::ir::ValueType::Int
+                        | middle::ir::ValueType::UnsignedInt
+                        | middle::ir::ValueType::Long
+                        | middle::ir::ValueType::UnsignedLong
                         | middle::ir::ValueType::Bool
                         | middle::ir::ValueType::SignedChar => {
                             MvInst::new(REG_A0.into(), reg.into())
                         }
-                        middle::ir::ValueType::Float => MvInst::new(REG_FA0.into(), reg.into()),
+                        | middle::ir::ValueType::Float
+                        | middle::ir::ValueType::Double => MvInst::new(REG_FA0.into(), reg.into()),
                         middle::ir::ValueType::Void => {
                             return Err(
                                 anyhow!("return not is_void, but get void type")
@@ -640,6 +902,11 @@ impl IRBuilder {
                                 || context!()
                             );
                         }
+                        middle::ir::ValueType::Struct(_) | middle::ir::ValueType::Union(_) => {
+                            return Err(anyhow!("return struct is not supported")).with_context(
+                                || context!()
+                            );
+                        }
                         middle::ir::ValueType::Pointer(_) => {
                             // NOTE 注意一下这里 可能可以返回指针
                             return Err(
@@ -664,16 +931,27 @@ impl IRBuilder {
                             ).with_context(|| context!());
                         }
                         | middle::ir::ValueType::Int
+                        | middle::ir::ValueType::UnsignedInt
+                        | middle::ir::ValueType::Long
+                        | middle::ir::ValueType::UnsignedLong
                         | middle::ir::ValueType::Bool
                         | middle::ir::ValueType::SignedChar => {
                             MvInst::new(REG_A0.into(), (*reg).into())
                         }
-                        middle::ir::ValueType::Float => MvInst::new(REG_FA0.into(), (*reg).into()),
+                        | middle::ir::ValueType::Float
+                        | middle::ir::ValueType::Double => {
+                            MvInst::new(REG_FA0.into(), (*reg).into())
+                        }
                         middle::ir::ValueType::Array(_, _) => {
                             return Err(anyhow!("return array is not allow for sysy")).with_context(
                                 || context!()
                             );
                         }
+                        middle::ir::ValueType::Struct(_) | middle::ir::ValueType::Union(_) => {
+                            return Err(anyhow!("return struct is not supported")).with_context(
+                                || context!()
+                            );
+                        }
                         middle::ir::ValueType::Pointer(_) => {
                             return Err(
                                 anyhow!("return pointer is not allow for sysy")
@@ -708,6 +986,9 @@ impl IRBuilder {
                     middle::ir::Constant::Bool(bo) => *bo,
                     middle::ir::Constant::SignedChar(ch) => *ch != 0,
                     middle::ir::Constant::Int(i) => *i != 0,
+                    middle::ir::Constant::UnsignedInt(i) => *i != 0,
+                    middle::ir::Constant::Long(i) => *i != 0,
+                    middle::ir::Constant::UnsignedLong(i) => *i != 0,
                     _ =>
                         todo!() /* middle::ir::Constant::Float(_) middle::ir::Constant::Array(_) middle::ir::Constant::Zero(_)  */,
                 };
@@ -0,0 +1,231 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+/// Minimum number of cases to use a jump table.
+const JUMP_TABLE_MIN_CASES: usize = 4;
+
+/// Maximum ratio of table size to number of cases to use a jump table.
+const JUMP_TABLE_MAX_SPARSITY: i64 = 3;
+
+/// Maximum number of cases to compare one by one in binary search.
+const COMPARE_CHAIN_MAX_CASES: usize = 3;
+
+/// Blocks generated for a switch instruction.
+/// Blocks are labelled after the block of switch, so that they can be renamed together.
+struct SwitchBlocks<'a> {
+    head: String,
+    cond: Reg,
+    default: String,
+    reg_gener: &'a mut RegGenerator,
+    blocks: Vec<(String, Vec<Inst>)>,
+}
+
+impl SwitchBlocks<'_> {
+    /// Allocate a new block, the first allocated block is the block of switch itself.
+    fn new_block(&mut self) -> usize {
+        let label = if self.blocks.is_empty() {
+            self.head.clone()
+        } else {
+            format!("{}_switch{}", self.head, self.blocks.len())
+        };
+        self.blocks.push((label, vec![]));
+        self.blocks.len() - 1
+    }
+
+    fn label(&self, block: usize) -> String {
+        self.blocks[block].0.clone()
+    }
+
+    fn push_inst(&mut self, block: usize, inst: Inst) {
+        self.blocks[block].1.push(inst);
+    }
+
+    /// Load an immediate into a new register.
+    fn load_imm(&mut self, block: usize, imm: i64) -> Reg {
+        let dst = self.reg_gener.gen_virtual_usual_reg();
+        self.push_inst(block, LiInst::new(dst.into(), imm.into()).into());
+        dst
+    }
+
+    /// Dispatch with a jump table, cases should be sorted and dense.
+    /// ```text
+    ///     sub idx, cond, min
+    ///     sltu ok, idx, len
+    ///     beq ok, zero, default
+    /// table:
+    ///     lla base, table
+    ///     lw offset, 4 * idx(base)
+    ///     add target, base, offset
+    ///     jr target
+    /// ```
+    fn build_jump_table(&mut self, block: usize, cases: &[(i64, String)]) {
+        let min = cases.first().map_or(0, |(x, _)| *x);
+        let max = cases.last().map_or(0, |(x, _)| *x);
+
+        // Jump to default if index is out of range, unsigned compare handles negative index
+        let idx = if min == 0 {
+            self.cond
+        } else {
+            let min = self.load_imm(block, min);
+            let idx = self.reg_gener.gen_virtual_usual_reg();
+            let sub = SubInst::new(idx.into(), self.cond.into(), min.into()).with_8byte();
+            self.push_inst(block, sub.into());
+            idx
+        };
+        let len = self.load_imm(block, max - min + 1);
+        let ok = self.reg_gener.gen_virtual_usual_reg();
+        self.push_inst(
+            block,
+            SltuInst::new(ok.into(), idx.into(), len.into()).into(),
+        );
+        let table_block = self.new_block();
+        let default = self.default.clone();
+        self.push_inst(block, BeqInst::new(ok, REG_ZERO, default.into()).into());
+        let table_label = self.label(table_block);
+        self.push_inst(block, JmpInst::new(table_label.clone().into()).into());
+        let table: Label = format!("{table_label}_table").into();
+
+        // Load offset of target relative to table, and jump to it
+        let offset = self.reg_gener.gen_virtual_usual_reg();
+        let slli = SllInst::new(offset.into(), idx.into(), (2).into()).with_8byte();
+        self.push_inst(table_block, slli.into());
+        let base = self.reg_gener.gen_virtual_usual_reg();
+        self.push_inst(table_block, LlaInst::new(base, table.clone()).into());
+        let entry = self.reg_gener.gen_virtual_usual_reg();
+        let add = AddInst::new(entry.into(), base.into(), offset.into()).with_8byte();
+        self.push_inst(table_block, add.into());
+        let relative = self.reg_gener.gen_virtual_usual_reg();
+        self.push_inst(table_block, LwInst::new(relative, 0.into(), entry).into());
+        let target = self.reg_gener.gen_virtual_usual_reg();
+        let add = AddInst::new(target.into(), base.into(), relative.into()).with_8byte();
+        self.push_inst(table_block, add.into());
+
+        // Fill holes in the table with default
+        let mut targets = Vec::new();
+        let mut cases = cases.iter().peekable();
+        for x in min..=max {
+            match cases.next_if(|(case, _)| *case == x) {
+                Some((_, label)) => targets.push(label.into()),
+                None => targets.push(self.default.clone().into()),
+            }
+        }
+        self.push_inst(table_block, JrInst::new(target, table, targets).into());
+    }
+
+    /// Dispatch with a binary search tree, cases should be sorted.
+    /// Leaves of the tree compare cases one by one.
+    fn build_search(&mut self, block: usize, cases: &[(i64, String)]) {
+        if cases.len() <= COMPARE_CHAIN_MAX_CASES {
+            let mut block = block;
+            for (ix, (case, label)) in cases.iter().enumerate() {
+                let case = self.load_imm(block, *case);
+                let beq = BeqInst::new(self.cond, case, label.into());
+                self.push_inst(block, beq.into());
+                let next = if ix + 1 < cases.len() {
+                    let next = self.new_block();
+                    self.label(next)
+                } else {
+                    self.default.clone()
+                };
+                self.push_inst(block, JmpInst::new(next.into()).into());
+                block = self.blocks.len() - 1;
+            }
+            if cases.is_empty() {
+                let default = self.default.clone();
+                self.push_inst(block, JmpInst::new(default.into()).into());
+            }
+            return;
+        }
+
+        // Cases less than pivot goes to left subtree
+        let (left_cases, right_cases) = cases.split_at(cases.len() / 2);
+        let pivot = self.load_imm(block, right_cases[0].0);
+        let left = self.new_block();
+        let right = self.new_block();
+        let blt = BltInst::new(self.cond, pivot, self.label(left).into());
+        self.push_inst(block, blt.into());
+        self.push_inst(block, JmpInst::new(self.label(right).into()).into());
+        self.build_search(left, left_cases);
+        self.build_search(right, right_cases);
+    }
+}
+
+impl IRBuilder {
+    /// Switch is built into instructions appended to its block, and some extra blocks.
+    /// Dense cases are dispatched with a jump table in `.rodata`,
+    /// and sparse cases with a binary search tree of compares.
+    pub fn build_switch_inst(
+        switch: &middle::ir::instruction::terminator_inst::Switch,
+        reg_gener: &mut RegGenerator,
+        regs: &HashMap<Address, Reg>,
+    ) -> Result<(Vec<Inst>, Vec<Block>)> {
+        let parent_bb = switch
+            .get_parent_bb()
+            .ok_or(anyhow!("get parent bb failed"))
+            .with_context(|| context!())?;
+        let succ_bb = parent_bb.get_succ_bb();
+        let (default_bb, case_bbs) = succ_bb
+            .split_first()
+            .ok_or(anyhow!("get default bb failed"))
+            .with_context(|| context!())?;
+
+        // Sort cases by value
+        let mut cases: Vec<(i64, String)> = switch
+            .get_cases()
+            .iter()
+            .zip(case_bbs.iter())
+            .map(|(case, bb)| (*case as i64, Self::label_name_from(bb)))
+            .collect();
+        cases.sort_by_key(|(case, _)| *case);
+
+        let (cond, prepare) = Self::prepare_rs1_i(switch.get_cond(), reg_gener, regs)?;
+        let mut blocks = SwitchBlocks {
+            head: Self::label_name_from(&parent_bb),
+            cond,
+            default: Self::label_name_from(default_bb),
+            reg_gener,
+            blocks: vec![],
+        };
+        let head = blocks.new_block();
+        blocks.blocks[head].1.extend(prepare);
+        let span = match (cases.first(), cases.last()) {
+            (Some((min, _)), Some((max, _))) => max - min + 1,
+            _ => 0,
+        };
+        if cases.len() >= JUMP_TABLE_MIN_CASES
+            && span <= JUMP_TABLE_MAX_SPARSITY * cases.len() as i64
+        {
+            blocks.build_jump_table(head, &cases);
+        } else {
+            blocks.build_search(head, &cases);
+        }
+
+        // Extra blocks are in the same loop as switch
+        let mut blocks = blocks.blocks.into_iter();
+        let (_, insts) = blocks.next().unwrap();
+        let blocks = blocks
+            .map(|(label, insts)| {
+                let mut bb = Block::new(label);
+                bb.extend_insts(insts);
+                bb.depth = parent_bb.depth;
+                bb
+            })
+            .collect();
+        Ok((insts, blocks))
+    }
+}
@@ -27,7 +27,10 @@ macro_rules! ssa2tac_three_usual_Itype {
         insts.extend(prepare);
         let dst = $reg_gener.gen_virtual_usual_reg();
         $regs.insert(addi as *const _ as Address, dst);
-        let addi_inst = $tac_inst_ty::new(dst.into(), op0.into(), op1);
+        let mut addi_inst = $tac_inst_ty::new(dst.into(), op0.into(), op1);
+        if addi.get_value_type().size() == 8 {
+            addi_inst = addi_inst.with_8byte();
+        }
         insts.push(addi_inst.into());
         Ok(insts)
     }};
@@ -45,7 +48,10 @@ macro_rules! ssa2tac_three_float {
         let (op1, prepare) = Self::prepare_f(fadd.get_rhs(), $reg_gener, $regs, $fmms)?;
         insts.extend(prepare);
         let dst0 = $reg_gener.gen_virtual_float_reg();
-        let fadd_inst = $tac_inst_ty::new(dst0.into(), op0, op1);
+        let mut fadd_inst = $tac_inst_ty::new(dst0.into(), op0, op1);
+        if fadd.get_value_type() == middle::ir::ValueType::Double {
+            fadd_inst = fadd_inst.with_8byte();
+        }
         $regs.insert(fadd as *const _ as Address, dst0);
         insts.push(fadd_inst.into());
         Ok(insts)
@@ -32,9 +32,15 @@ impl IRBuilder {
     pub fn new_var(ty: &middle::ir::ValueType, reg_gener: &mut RegGenerator) -> Result<Reg> {
         let dst_reg = match ty {
             | middle::ir::ValueType::Int
+            | middle::ir::ValueType::UnsignedInt
+            | middle::ir::ValueType::Long
+            | middle::ir::ValueType::UnsignedLong
             | middle::ir::ValueType::Bool
+            | middle::ir::ValueType::SignedChar
             | middle::ir::ValueType::Pointer(_) => reg_gener.gen_virtual_usual_reg(),
-            middle::ir::ValueType::Float => reg_gener.gen_virtual_float_reg(),
+            middle::ir::ValueType::Float | middle::ir::ValueType::Double => {
+                reg_gener.gen_virtual_float_reg()
+            }
             _ => {
                 return Err(anyhow!("phi can't be void/array")).with_context(|| context!());
             }
@@ -76,18 +82,37 @@ impl IRBuilder {
     pub fn const_except_arr_from(con: &middle::ir::Constant) -> Result<Operand> {
         Ok(match con {
             middle::ir::Constant::Int(val) => Operand::Imm((*val as i64).into()),
+            // 寄存器中的 32 位数都是符号扩展的, unsigned 也不例外
+            middle::ir::Constant::UnsignedInt(val) => Operand::Imm((*val as i32 as i64).into()),
+            middle::ir::Constant::Long(val) => Operand::Imm((*val).into()),
+            middle::ir::Constant::UnsignedLong(val) => Operand::Imm((*val as i64).into()),
             middle::ir::Constant::Float(fla) => Operand::Fmm((*fla as f64).into()),
             middle::ir::Constant::Bool(boo) => Operand::Imm((*boo as i64).into()),
             middle::ir::Constant::SignedChar(sig) => Operand::Imm((*sig as i64).into()),
-            middle::ir::Constant::Array(_) => {
+            middle::ir::Constant::Array(_) | middle::ir::Constant::Struct(..) => {
                 return Err(anyhow!("const_from operand can't not be array:{}", con)).with_context(
                     || context!()
                 );
             }
+            middle::ir::Constant::Double(_) => {
+                return Err(anyhow!("double should be prepared by _prepare_dmm:{}", con)).with_context(
+                    || context!()
+                );
+            }
             middle::ir::Constant::Zero(_) => todo!(),
         })
     }
 
+    /// 如果 value 是 double 常量, 则返回 Some(double)
+    #[inline]
+    pub fn double_const_from(value: &middle::ir::Operand) -> Option<f64> {
+        if let middle::ir::Operand::Constant(middle::ir::Constant::Double(d)) = value {
+            Some(*d)
+        } else {
+            None
+        }
+    }
+
     /// 这里不包含有 函数的形参。local_var_from 返回 Reg
     #[inline]
     pub fn local_var_except_param_from(
@@ -145,6 +170,10 @@ impl IRBuilder {
         regs: &HashMap<Address, Reg>,
         fmms: &mut HashMap<Fmm, FloatVar>
     ) -> Result<(Operand, Vec<Inst>)> {
+        if let Some(d) = Self::double_const_from(value) {
+            let (dst, insts) = Self::_prepare_dmm(d, reg_gener);
+            return Ok((dst.into(), insts));
+        }
         let value = IRBuilder::no_load_from(value, regs)?;
         match &value {
             Operand::Imm(imm) => {
@@ -191,6 +220,16 @@ impl IRBuilder {
         Ok((dst, insts))
     }
 
+    /// double 常量不放到 rodata 中, 而是 li 其位模式, 再 fmv.d.x 到浮点寄存器
+    #[inline]
+    pub fn _prepare_dmm(dmm: f64, reg_gener: &mut RegGenerator) -> (Reg, Vec<Inst>) {
+        let bits = reg_gener.gen_virtual_usual_reg();
+        let li = LiInst::new(bits.into(), (dmm.to_bits() as i64).into());
+        let dst = reg_gener.gen_virtual_float_reg();
+        let fmv = FmvdxInst::new(dst.into(), bits.into());
+        (dst, vec![li.into(), fmv.into()])
+    }
+
     /// 如果value是个寄存器,直接返回,
     /// 如果是个常数,如果超出范围,则需要用一个寄存器来存储,并且需要生成一条指令
     /// 如果是不超出范围的常数,则直接返回
@@ -224,6 +263,10 @@ impl IRBuilder {
         regs: &HashMap<Address, Reg>,
         fmms: &mut HashMap<Fmm, FloatVar>
     ) -> Result<(Operand, Vec<Inst>)> {
+        if let Some(d) = Self::double_const_from(value) {
+            let (dst, insts) = Self::_prepare_dmm(d, reg_gener);
+            return Ok((dst.into(), insts));
+        }
         let value = IRBuilder::no_load_from(value, regs)?;
         match value {
             Operand::Reg(_) => Ok((value, vec![])),
@@ -40,7 +40,7 @@ impl IRBuilder {
     // }
 
     pub fn label_rename_func(backend: &mut Func, middle: &middle::ir::Function) -> Result<()> {
-        let label_map: HashMap<String, String> = middle
+        let mut label_map: HashMap<String, String> = middle
             .bfs_iter()
             .map(|bb| {
                 let key = Self::label_name_from(&bb);
@@ -48,12 +48,25 @@ impl IRBuilder {
                 (key, val)
             })
             .collect();
+        // switch 生成的 bb 形如 {head}_switch{n}, 跟随 head 改名
+        for bb in backend.iter_bbs() {
+            if let Some((head, suffix)) = bb.label().split_once("_switch") {
+                let head = label_map
+                    .get(head)
+                    .ok_or(anyhow!("label not found"))?
+                    .clone();
+                label_map.insert(bb.label().to_string(), format!("{head}_switch{suffix}"));
+            }
+        }
         for bb in backend.iter_bbs_mut() {
             // 改 bb 的 label
             let new_label = label_map
                 .get(bb.label())
                 .ok_or(anyhow!("label not found"))?
                 .clone();
+            // 跳转表以所在 bb 命名, 跟随 bb 改名
+            let old_table = format!("{}_table", bb.label());
+            let new_table = format!("{new_label}_table");
             bb.set_label(&new_label);
             for inst in bb.insts_mut() {
                 match inst {
@@ -82,6 +95,19 @@ impl IRBuilder {
                     Inst::Bge(bge) => {
                         branch!(bge, label_map);
                     }
+                    Inst::Lla(lla) if lla.label().as_str() == old_table => {
+                        *lla.label_mut() = new_table.clone().into();
+                    }
+                    Inst::Jr(jr) => {
+                        *jr.table_mut() = new_table.clone().into();
+                        for target in jr.targets_mut() {
+                            *target = label_map
+                                .get(target.as_str())
+                                .ok_or(anyhow!("not found label"))?
+                                .clone()
+                                .into();
+                        }
+                    }
                     _ => { /* do nothing */ }
                 }
             }
@@ -29,9 +29,29 @@ impl IRBuilder {
         let mut global_vars = Vec::new();
         for global_var in self_global_vars {
             let name = &global_var.name;
+            let ty = &global_var.value_type;
             let new_var = match &global_var.initializer {
-                middle::ir::Constant::SignedChar(_) => unimplemented!(),
+                init if ty.has_record() => Self::build_record_var(name, ty, init)?,
+                init if ty.is_array()
+                    && ty.get_base_type() == middle::ir::ValueType::SignedChar =>
+                {
+                    Self::build_char_arr_var(name, ty, init, !global_var.variable_or_constant)?
+                }
+                // 8 字节的数组同样按字节布局
+                init if ty.is_array() && ty.get_base_type().size() == 8 => {
+                    Self::build_record_var(name, ty, init)?
+                }
+                middle::ir::Constant::Struct(..) => {
+                    return Err(anyhow!("struct initializer for {}", ty))
+                        .with_context(|| context!())
+                }
+                // char 占一个字, lb/sb 只访问其最低字节 (小端序)
+                middle::ir::Constant::SignedChar(c) => Self::build_int_var(name, *c as i32)?,
                 middle::ir::Constant::Int(i) => Self::build_int_var(name, *i)?,
+                middle::ir::Constant::UnsignedInt(u) => Self::build_int_var(name, *u as i32)?,
+                middle::ir::Constant::Long(l) => Self::build_dword_var(name, *l as u64)?,
+                middle::ir::Constant::UnsignedLong(u) => Self::build_dword_var(name, *u)?,
+                middle::ir::Constant::Double(d) => Self::build_double_var(name, *d)?,
                 middle::ir::Constant::Float(f) => Self::build_float_var(name, *f)?,
                 middle::ir::Constant::Bool(b) => Self::build_bool_var(name, *b)?,
                 middle::ir::Constant::Array(arr) => Self::build_arr_var(name, arr)?,
@@ -44,10 +64,14 @@ impl IRBuilder {
 
     fn build_zero_initializer(name: &str, ty: &middle::ir::ValueType) -> Result<Var> {
         match ty.get_base_type() {
-            middle::ir::ValueType::Int => {
+            middle::ir::ValueType::Int
+            | middle::ir::ValueType::UnsignedInt
+            | middle::ir::ValueType::Long
+            | middle::ir::ValueType::UnsignedLong
+            | middle::ir::ValueType::Double => {
                 let var: ArrVar<u32> = ArrVar {
                     name: name.to_string(),
-                    capacity: ty.size(),
+                    capacity: ty.size() / 4,
                     init: vec![],
                     is_const: false,
                 };
@@ -56,7 +80,7 @@ impl IRBuilder {
             middle::ir::ValueType::Float => {
                 let var: ArrVar<f32> = ArrVar {
                     name: name.to_string(),
-                    capacity: ty.size(),
+                    capacity: ty.size() / 4,
                     init: vec![],
                     is_const: false,
                 };
@@ -102,6 +126,102 @@ impl IRBuilder {
         }
     }
 
+    /// Struct, union or char array (or array of them) is laid out as words,
+    /// fields of different types are written to their offsets.
+    fn build_record_var(
+        name: &str,
+        ty: &middle::ir::ValueType,
+        con: &middle::ir::Constant,
+    ) -> Result<Var> {
+        let mut bytes = vec![0u8; ty.size().next_multiple_of(4)];
+        Self::_init_record_bytes(con, 0, &mut bytes)?;
+        let init = bytes
+            .chunks(4)
+            .enumerate()
+            .map(|(i, word)| (i, u32::from_le_bytes([word[0], word[1], word[2], word[3]])))
+            .filter(|(_, word)| *word != 0)
+            .collect();
+        let var: ArrVar<u32> = ArrVar {
+            name: name.to_string(),
+            capacity: bytes.len() / 4,
+            init,
+            is_const: false,
+        };
+        Ok(var.into())
+    }
+
+    /// Char array is laid out as bytes,
+    /// read-only ASCII string with trailing zero is placed in `.rodata`.
+    fn build_char_arr_var(
+        name: &str,
+        ty: &middle::ir::ValueType,
+        con: &middle::ir::Constant,
+        is_const: bool,
+    ) -> Result<Var> {
+        let mut bytes = vec![0u8; ty.size()];
+        Self::_init_record_bytes(con, 0, &mut bytes)?;
+        if let (true, Some((0, content))) = (is_const, bytes.split_last()) {
+            if content.is_ascii() {
+                let var = var::Str {
+                    name: name.to_string(),
+                    init: Some(String::from_utf8(content.to_vec())?),
+                    is_const: true,
+                };
+                return Ok(var.into());
+            }
+        }
+        Self::build_record_var(name, ty, con)
+    }
+
+    fn _init_record_bytes(
+        con: &middle::ir::Constant,
+        offset: usize,
+        bytes: &mut [u8],
+    ) -> Result<()> {
+        match con {
+            middle::ir::Constant::SignedChar(c) => bytes[offset] = *c as u8,
+            middle::ir::Constant::Int(i) => {
+                bytes[offset..offset + 4].copy_from_slice(&i.to_le_bytes());
+            }
+            middle::ir::Constant::UnsignedInt(u) => {
+                bytes[offset..offset + 4].copy_from_slice(&u.to_le_bytes());
+            }
+            middle::ir::Constant::Long(l) => {
+                bytes[offset..offset + 8].copy_from_slice(&l.to_le_bytes());
+            }
+            middle::ir::Constant::UnsignedLong(u) => {
+                bytes[offset..offset + 8].copy_from_slice(&u.to_le_bytes());
+            }
+            middle::ir::Constant::Float(f) => {
+                bytes[offset..offset + 4].copy_from_slice(&f.to_le_bytes());
+            }
+            middle::ir::Constant::Double(d) => {
+                bytes[offset..offset + 8].copy_from_slice(&d.to_le_bytes());
+            }
+            middle::ir::Constant::Bool(b) => {
+                bytes[offset..offset + 4].copy_from_slice(&(*b as u32).to_le_bytes());
+            }
+            middle::ir::Constant::Array(arr) => {
+                for (i, item) in arr.iter().enumerate() {
+                    let size = item.get_type().size();
+                    Self::_init_record_bytes(item, offset + i * size, bytes)?;
+                }
+            }
+            middle::ir::Constant::Struct(_, fields) => {
+                let ty = con.get_type();
+                for (i, item) in fields.iter().enumerate() {
+                    let field_offset = ty
+                        .field_offset(i)
+                        .ok_or_else(|| anyhow!("field {} not found in {}", i, ty))
+                        .with_context(|| context!())?;
+                    Self::_init_record_bytes(item, offset + field_offset, bytes)?;
+                }
+            }
+            middle::ir::Constant::Zero(_) => {}
+        }
+        Ok(())
+    }
+
     fn _init_arr_i(
         arr: &[middle::ir::Constant],
     ) -> Result<Vec<(usize /* 大小, 而不是下标 */, u32)>> {
@@ -109,17 +229,26 @@ impl IRBuilder {
         for item in arr {
             match item {
                 middle::ir::Constant::Int(i) => init.push((1, *i as u32)),
+                middle::ir::Constant::UnsignedInt(u) => init.push((1, *u)),
                 middle::ir::Constant::SignedChar(c) => init.push((1, *c as u32)),
                 middle::ir::Constant::Bool(b) => init.push((1, *b as u32)),
                 middle::ir::Constant::Float(_) => {
                     return Err(anyhow!("float in int arr")).with_context(|| context!())
                 }
+                middle::ir::Constant::Long(_)
+                | middle::ir::Constant::UnsignedLong(_)
+                | middle::ir::Constant::Double(_) => {
+                    return Err(anyhow!("8-byte value in int arr")).with_context(|| context!())
+                }
                 middle::ir::Constant::Array(arr) => {
                     let sub_init = Self::_init_arr_i(arr)?;
                     init.extend(sub_init);
                 }
                 middle::ir::Constant::Zero(zero) => {
-                    init.push((zero.size(), 0));
+                    init.push((zero.size() / 4, 0));
+                }
+                middle::ir::Constant::Struct(..) => {
+                    return Err(anyhow!("struct in int arr")).with_context(|| context!())
                 }
             }
         }
@@ -134,16 +263,25 @@ impl IRBuilder {
             match item {
                 middle::ir::Constant::SignedChar(_)
                 | middle::ir::Constant::Int(_)
+                | middle::ir::Constant::UnsignedInt(_)
                 | middle::ir::Constant::Bool(_) => {
                     return Err(anyhow!("int in float arr")).with_context(|| context!())
                 }
+                middle::ir::Constant::Long(_)
+                | middle::ir::Constant::UnsignedLong(_)
+                | middle::ir::Constant::Double(_) => {
+                    return Err(anyhow!("8-byte value in float arr")).with_context(|| context!())
+                }
                 middle::ir::Constant::Float(f) => init.push((1, *f)),
                 middle::ir::Constant::Array(arr) => {
                     let sub_init = Self::_init_arr_f(arr)?;
                     init.extend(sub_init);
                 }
                 middle::ir::Constant::Zero(zero) => {
-                    init.push((zero.size(), 0 as f32));
+                    init.push((zero.size() / 4, 0 as f32));
+                }
+                middle::ir::Constant::Struct(..) => {
+                    return Err(anyhow!("struct in float arr")).with_context(|| context!())
                 }
             }
         }
@@ -154,6 +292,10 @@ impl IRBuilder {
     fn __is_int(con: &middle::ir::Constant) -> Option<bool> {
         match con {
             middle::ir::Constant::Int(_) => Some(true),
+            middle::ir::Constant::UnsignedInt(_) => Some(true),
+            middle::ir::Constant::Long(_) => Some(true),
+            middle::ir::Constant::UnsignedLong(_) => Some(true),
+            middle::ir::Constant::Double(_) => Some(false),
             middle::ir::Constant::SignedChar(_) => Some(true),
             middle::ir::Constant::Bool(_) => Some(true),
             middle::ir::Constant::Float(_) => Some(false),
@@ -166,6 +308,7 @@ impl IRBuilder {
                 None // 这种情况是: 全部是 Zero 的情况, 按道理来说应该走 build_zero_initializer 这条分支
             }
             middle::ir::Constant::Zero(_) => None,
+            middle::ir::Constant::Struct(..) => None,
         }
     }
 
@@ -205,4 +348,22 @@ impl IRBuilder {
         }));
         Ok(var)
     }
+
+    fn build_dword_var(name: &str, value: u64) -> Result<Var> {
+        let var = var::Var::Prim(var::PrimVar::DwordVar(var::DwordVar {
+            name: name.to_string(),
+            init: Some(value),
+            is_const: false,
+        }));
+        Ok(var)
+    }
+
+    fn build_double_var(name: &str, d: f64) -> Result<Var> {
+        let var = var::Var::Prim(var::PrimVar::DoubleVar(var::DoubleVar {
+            name: name.to_string(),
+            init: Some(d),
+            is_const: false,
+        }));
+        Ok(var)
+    }
 }
@@ -221,6 +221,7 @@ impl GenTool {
         );
         ret
     }
+    /// String in `.rodata`, `val` is escaped here and `.string` appends the trailing zero.
     #[inline]
     pub fn gen_const_str(name: &str, val: &str) -> String {
         let mut ret = String::with_capacity(32 + val.len());
@@ -232,10 +233,33 @@ impl GenTool {
         ret.push_str(name);
         ret.push_str(":\n");
         ret.push_str(".string \"");
-        ret.push_str(val);
+        for c in val.chars() {
+            match c {
+                '"' | '\\' => {
+                    ret.push('\\');
+                    ret.push(c);
+                }
+                ' '..='~' => ret.push(c),
+                _ => ret.push_str(&format!("\\{:03o}", c as u32)),
+            }
+        }
         ret.push_str("\"\n");
         ret
     }
+    /// Jump table in `.rodata`, each entry is the offset of target label relative to the table,
+    /// so that it needs no relocation in position-independent code.
+    #[inline]
+    pub fn gen_jump_table(name: &str, targets: &[&str]) -> String {
+        let mut ret = String::with_capacity(32 + targets.len() * 32);
+        ret.push_str(".section\t.rodata\n");
+        ret.push_str(".align\t2\n");
+        ret.push_str(name);
+        ret.push(':');
+        for target in targets {
+            ret.push_str(format!("\n.word\t{}-{}", target, name).as_str());
+        }
+        ret
+    }
     #[inline]
     pub fn gen_array<T: Data>(name: &str, num_elems: usize, init: &[(usize, T)]) -> String {
         let mut ret = String::with_capacity(128);
@@ -308,6 +332,11 @@ hello:
         assert_eq!(s, raw_match);
     }
     #[test]
+    fn test_gen_const_str_escape() {
+        let s = super::GenTool::gen_const_str("s", "say \"hi\"\\\n");
+        assert!(s.ends_with(".string \"say \\\"hi\\\"\\\\\\012\"\n"));
+    }
+    #[test]
     fn test_gen_word() {
         let s = super::GenTool::gen_word("hello", 0x12345678);
         println!("{}", s);
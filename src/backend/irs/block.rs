@@ -83,6 +83,7 @@ impl Block {
             matches!(
                 inst,
                 Inst::Jmp(_)
+                    | Inst::Jr(_)
                     | Inst::Tail(_)
                     | Inst::Ret
                     | Inst::Beq(_)
@@ -95,6 +95,8 @@ impl InstChecker for Riscv {
             Inst::Sd(sd) => self.check_sd(sd),
             Inst::Sw(sw) => self.check_sw(sw),
             Inst::Lw(lw) => self.check_lw(lw),
+            Inst::Sb(sb) => self.check_sb(sb),
+            Inst::Lb(lb) => self.check_lb(lb),
             Inst::Lla(_) => true,
             // special inst to temporary express the load and store operation ,should not to keep in the final ir
             Inst::Load(_) => false,
@@ -103,6 +105,7 @@ impl InstChecker for Riscv {
             Inst::LocalAddr(_) => false,
 
             Inst::Jmp(_) => true,
+            Inst::Jr(_) => true,
             Inst::Beq(_) => true,
             Inst::Bne(_) => true,
             Inst::Bge(_) => true,
@@ -114,13 +117,20 @@ impl InstChecker for Riscv {
             Inst::Li(li) => self.check_li(li),
             Inst::F2i(f2i) => self.check_f2i(f2i),
             Inst::I2f(i2f) => self.check_i2f(i2f),
+            Inst::F2d(f2d) => self.check_f2d(f2d),
+            Inst::D2f(d2f) => self.check_d2f(d2f),
+            Inst::Fmvdx(fmvdx) => self.check_fmvdx(fmvdx),
             Inst::Ret => true,
             Inst::Sltu(sltu) => self.check_sltu(sltu),
             Inst::Sgtu(sgut) => self.check_sgtu(sgut),
             Inst::UDiv(udiv) => self.check_udiv(udiv),
+            Inst::URem(urem) => self.check_urem(urem),
             Inst::Feqs(feqs) => self.check_feqs(feqs),
             Inst::Fles(fles) => self.check_fles(fles),
             Inst::Flts(flts) => self.check_flts(flts),
+            Inst::Feqd(feqd) => self.check_feqd(feqd),
+            Inst::Fled(fled) => self.check_fled(fled),
+            Inst::Fltd(fltd) => self.check_fltd(fltd),
             Inst::Lui(lui) => self.check_lui(lui),
         }
     }
@@ -149,12 +159,36 @@ impl Riscv {
             && matches!(flts.rhs(), Operand::Reg(_))
     }
 
+    fn check_feqd(&self, feqd: &FeqdInst) -> bool {
+        matches!(feqd.dst(), Operand::Reg(_))
+            && matches!(feqd.lhs(), Operand::Reg(_))
+            && matches!(feqd.rhs(), Operand::Reg(_))
+    }
+
+    fn check_fled(&self, fled: &FledInst) -> bool {
+        matches!(fled.dst(), Operand::Reg(_))
+            && matches!(fled.lhs(), Operand::Reg(_))
+            && matches!(fled.rhs(), Operand::Reg(_))
+    }
+
+    fn check_fltd(&self, fltd: &FltdInst) -> bool {
+        matches!(fltd.dst(), Operand::Reg(_))
+            && matches!(fltd.lhs(), Operand::Reg(_))
+            && matches!(fltd.rhs(), Operand::Reg(_))
+    }
+
     fn check_udiv(&self, udiv: &UdivInst) -> bool {
         matches!(udiv.dst(), Operand::Reg(_))
             && matches!(udiv.lhs(), Operand::Reg(_))
             && matches!(udiv.rhs(), Operand::Reg(_))
     }
 
+    fn check_urem(&self, urem: &UremInst) -> bool {
+        matches!(urem.dst(), Operand::Reg(_))
+            && matches!(urem.lhs(), Operand::Reg(_))
+            && matches!(urem.rhs(), Operand::Reg(_))
+    }
+
     fn check_add(&self, add: &AddInst) -> bool {
         matches!(add.dst(), Operand::Reg(_))
             && matches!(add.lhs(), Operand::Reg(_))
@@ -205,6 +239,21 @@ impl Riscv {
         })
     }
 
+    fn check_f2d(&self, f2d: &F2dInst) -> bool {
+        matches!(f2d.dst(), Operand::Reg(r) if r.is_float())
+            && matches!(f2d.src(), Operand::Reg(r) if r.is_float())
+    }
+
+    fn check_d2f(&self, d2f: &D2fInst) -> bool {
+        matches!(d2f.dst(), Operand::Reg(r) if r.is_float())
+            && matches!(d2f.src(), Operand::Reg(r) if r.is_float())
+    }
+
+    fn check_fmvdx(&self, fmvdx: &FmvdxInst) -> bool {
+        matches!(fmvdx.dst(), Operand::Reg(r) if r.is_float())
+            && matches!(fmvdx.src(), Operand::Reg(r) if r.is_usual())
+    }
+
     fn check_li(&self, li: &LiInst) -> bool {
         matches!(li.dst(), Operand::Reg(_)) && matches!(li.src(), Operand::Imm(_))
     }
@@ -245,6 +294,12 @@ impl Riscv {
     fn check_sw(&self, sw: &SwInst) -> bool {
         Self::check_imm_in_i_type_inst(sw.offset())
     }
+    fn check_lb(&self, lb: &LbInst) -> bool {
+        Self::check_imm_in_i_type_inst(lb.offset())
+    }
+    fn check_sb(&self, sb: &SbInst) -> bool {
+        Self::check_imm_in_i_type_inst(sb.offset())
+    }
     fn check_imm_in_i_type_inst(imm: &Imm) -> bool {
         imm.in_limit(12)
     }
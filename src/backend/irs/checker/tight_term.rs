@@ -17,7 +17,7 @@
 use super::*;
 
 /// 该检查器用于检查是否每个块的几位的1-2条指令为term类型指令，且块内无其他term类型指令
-/// 这要求块必须结尾是 {b,j},{ret},{j} 这三种指令组合中的一种
+/// 这要求块必须结尾是 {b,j},{ret},{j},{jr} 这四种指令组合中的一种
 pub struct TightTerm;
 
 impl IRChecker for TightTerm {}
@@ -46,7 +46,7 @@ impl BBChecker for TightTerm {
         // 最后一条指令是ret/jmp
         if terms.len() == 1 {
             if let Some((last, inst)) = terms.last() {
-                if (*last == insts.len() - 1 && matches!(inst, Inst::Ret))
+                if (*last == insts.len() - 1 && matches!(inst, Inst::Ret | Inst::Jr(_)))
                     || matches!(inst, Inst::Jmp(_))
                 {
                     return true;
@@ -60,7 +60,8 @@ impl Func {
                 bbs_asm.push_str(bb.gen_asm().as_str());
                 bbs_asm.push('\n');
             }
-            return GenTool::gen_func(self.name.as_str(), bbs_asm.as_str());
+            return GenTool::gen_func(self.name.as_str(), bbs_asm.as_str())
+                + &self.gen_tables_asm();
         }
         let thread_pool = rayon::ThreadPoolBuilder::new()
             .num_threads(CONFIG.num_parallel_for_block_gen_asm)
@@ -73,7 +74,21 @@ impl Func {
                 .collect::<Vec<String>>()
                 .join("\n")
         });
-        GenTool::gen_func(self.name.as_str(), &bbs_asm)
+        GenTool::gen_func(self.name.as_str(), &bbs_asm) + &self.gen_tables_asm()
+    }
+
+    /// Jump tables used by the function, placed after it.
+    fn gen_tables_asm(&self) -> String {
+        let mut tables_asm = String::new();
+        for bb in self.iter_bbs() {
+            for inst in bb.insts() {
+                if let Inst::Jr(jr) = inst {
+                    tables_asm.push('\n');
+                    tables_asm.push_str(jr.gen_table_asm().as_str());
+                }
+            }
+        }
+        tables_asm
     }
 }
 
@@ -328,6 +343,13 @@ impl Func {
                             *jmp.dst_mut() = to.into();
                         }
                     }
+                    Inst::Jr(jr) => {
+                        for target in jr.targets_mut() {
+                            if target.as_str() == from {
+                                *target = to.into();
+                            }
+                        }
+                    }
                     _ => {
                         continue;
                     }
@@ -22,13 +22,14 @@ impl_three_op_inst_with_dstmem!(MulInst, "mul");
 impl_three_op_inst!(RemInst, "rem");
 impl_three_op_inst!(DivInst, "div");
 impl_three_op_inst_with_dstmem!(SllInst, "sll");
-impl_three_op_inst!(SrlInst, "srl");
+impl_three_op_inst_with_dstmem!(SrlInst, "srl");
 impl_three_op_inst!(SraInst, "sra");
 impl_three_op_inst!(AndInst, "and");
 impl_two_op_inst!(NotInst, "not");
 impl_three_op_inst!(OrInst, "or");
 impl_three_op_inst!(XorInst, "xor");
-impl_three_op_inst!(UdivInst, "divuw");
+impl_three_op_inst_with_dstmem!(UdivInst, "divu");
+impl_three_op_inst_with_dstmem!(UremInst, "remu");
 
 // 实现比较指令
 impl_three_op_inst!(SltInst, "slt");
@@ -40,9 +41,13 @@ impl_two_op_inst!(SeqzInst, "seqz");
 impl_three_op_inst!(FeqsInst, "feq.s");
 impl_three_op_inst!(FlesInst, "fle.s");
 impl_three_op_inst!(FltsInst, "flt.s");
+impl_three_op_inst!(FeqdInst, "feq.d");
+impl_three_op_inst!(FledInst, "fle.d");
+impl_three_op_inst!(FltdInst, "flt.d");
 
 impl_two_op_inst!(NegInst, "neg");
-impl_two_op_inst!(MvInst, "mv");
+// float register is moved as a whole, so that double is preserved
+impl_two_op_inst!(MvInst, "mv", "d");
 
 ////////////////////////////////////////////////////////////////////////
 /// 以下是具体指令类型 与 Inst 的转换
@@ -55,6 +60,8 @@ mod c {
     impl_inst_convert!(MulInst, Mul);
     impl_inst_convert!(RemInst, Rem);
     impl_inst_convert!(DivInst, Div);
+    impl_inst_convert!(UdivInst, UDiv);
+    impl_inst_convert!(UremInst, URem);
     impl_inst_convert!(NegInst, Neg);
 
     // for bit count operation
@@ -75,6 +82,9 @@ mod c {
     impl_inst_convert!(FeqsInst, Feqs);
     impl_inst_convert!(FlesInst, Fles);
     impl_inst_convert!(FltsInst, Flts);
+    impl_inst_convert!(FeqdInst, Feqd);
+    impl_inst_convert!(FledInst, Fled);
+    impl_inst_convert!(FltdInst, Fltd);
 }
 
 #[cfg(test)]
@@ -104,4 +114,17 @@ mod tests {
         let fsub = SubInst::new(REG_FA0.into(), REG_FA1.into(), REG_FA2.into());
         assert_eq!(fsub.gen_asm(), "fsub.s fa0,fa1,fa2");
     }
+    #[test]
+    fn test_gem_asm_8byte() {
+        let mul = MulInst::new(REG_A0.into(), REG_A1.into(), REG_A2.into()).with_8byte();
+        assert_eq!(mul.gen_asm(), "mul a0,a1,a2");
+        let fadd = AddInst::new(REG_FA0.into(), REG_FA1.into(), REG_FA2.into()).with_8byte();
+        assert_eq!(fadd.gen_asm(), "fadd.d fa0,fa1,fa2");
+        let fdiv = DivInst::new(REG_FA0.into(), REG_FA1.into(), REG_FA2.into()).with_8byte();
+        assert_eq!(fdiv.gen_asm(), "fdiv.d fa0,fa1,fa2");
+        let srl = SrlInst::new(REG_A0.into(), REG_A1.into(), REG_A2.into());
+        assert_eq!(srl.gen_asm(), "srlw a0,a1,a2");
+        let remu = UremInst::new(REG_A0.into(), REG_A1.into(), REG_A2.into()).with_8byte();
+        assert_eq!(remu.gen_asm(), "remu a0,a1,a2");
+    }
 }
@@ -162,6 +162,61 @@ impl RegReplace for JmpInst {
     }
 }
 
+/// Indirect jump through a jump table.
+/// Entries of the table are offsets of `targets` relative to `table`,
+/// they are emitted to `.rodata` together with the function.
+#[derive(Clone, Debug)]
+pub struct JrInst {
+    src: Reg,
+    table: Label,
+    targets: Vec<Label>,
+}
+impl JrInst {
+    pub fn new(src: Reg, table: Label, targets: Vec<Label>) -> Self {
+        Self {
+            src,
+            table,
+            targets,
+        }
+    }
+    pub fn src(&self) -> &Reg {
+        &self.src
+    }
+    pub fn table(&self) -> &Label {
+        &self.table
+    }
+    pub fn table_mut(&mut self) -> &mut Label {
+        &mut self.table
+    }
+    pub fn targets(&self) -> &Vec<Label> {
+        &self.targets
+    }
+    pub fn targets_mut(&mut self) -> &mut Vec<Label> {
+        &mut self.targets
+    }
+    pub fn gen_asm(&self) -> String {
+        format!("jr {}", self.src.gen_asm())
+    }
+    pub fn gen_table_asm(&self) -> String {
+        let targets: Vec<&str> = self.targets.iter().map(|l| l.as_str()).collect();
+        gen_asm::GenTool::gen_jump_table(self.table.as_str(), &targets)
+    }
+}
+impl RegDefs for JrInst {}
+impl RegUses for JrInst {
+    fn uses(&self) -> Vec<&Reg> {
+        vec![&self.src]
+    }
+}
+impl RegReplace for JrInst {
+    fn replace_use(&mut self, from: Reg, to: Reg) -> Result<()> {
+        if self.src == from {
+            self.src = to;
+        }
+        Ok(())
+    }
+}
+
 impl_unary_inst!(TailInst, "tail");
 
 impl_branch_inst!(BeqInst, "beq");
@@ -198,6 +253,7 @@ mod convert_to_inst {
     use super::*;
     // inst for control flow
     impl_inst_convert!(JmpInst, Jmp);
+    impl_inst_convert!(JrInst, Jr);
     impl_inst_convert!(CallInst, Call);
     impl_inst_convert!(TailInst, Tail);
     impl_inst_convert!(BeqInst, Beq);
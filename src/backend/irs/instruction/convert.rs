@@ -18,10 +18,17 @@ use super::*;
 
 impl_conversion_inst!(I2fInst, "fcvt.s.w");
 impl_conversion_inst!(F2iInst, "fcvt.w.s", "rtz");
+impl_conversion_inst!(F2dInst, "fcvt.d.s");
+impl_conversion_inst!(D2fInst, "fcvt.s.d");
+// move bits of an integer register into a double register
+impl_conversion_inst!(FmvdxInst, "fmv.d.x");
 
 // impl conversion to Inst
 impl_inst_convert!(I2fInst, I2f);
 impl_inst_convert!(F2iInst, F2i);
+impl_inst_convert!(F2dInst, F2d);
+impl_inst_convert!(D2fInst, D2f);
+impl_inst_convert!(FmvdxInst, Fmvdx);
 
 #[cfg(test)]
 mod tests {
@@ -36,4 +43,22 @@ mod tests {
         let inst = F2iInst::new(REG_A0.into(), REG_FA0.into());
         assert_eq!(inst.gen_asm(), "fcvt.w.s a0,fa0,rtz");
     }
+    #[test]
+    fn test_conversion_formats() {
+        let inst = I2fInst::new(REG_FA0.into(), REG_A0.into())
+            .with_8byte()
+            .with_double();
+        assert_eq!(inst.gen_asm(), "fcvt.d.l fa0,a0");
+        let inst = I2fInst::new(REG_FA0.into(), REG_A0.into()).with_unsigned();
+        assert_eq!(inst.gen_asm(), "fcvt.s.wu fa0,a0");
+        let inst = F2iInst::new(REG_A0.into(), REG_FA0.into())
+            .with_8byte()
+            .with_unsigned()
+            .with_double();
+        assert_eq!(inst.gen_asm(), "fcvt.lu.d a0,fa0,rtz");
+        let inst = F2dInst::new(REG_FA0.into(), REG_FA1.into());
+        assert_eq!(inst.gen_asm(), "fcvt.d.s fa0,fa1");
+        let inst = FmvdxInst::new(REG_FA0.into(), REG_A0.into());
+        assert_eq!(inst.gen_asm(), "fmv.d.x fa0,a0");
+    }
 }
@@ -157,6 +157,8 @@ impl_mem_inst!(LdInst, "ld");
 impl_mem_inst!(SdInst, "sd");
 impl_mem_inst!(SwInst, "sw");
 impl_mem_inst!(LwInst, "lw");
+impl_mem_inst!(SbInst, "sb");
+impl_mem_inst!(LbInst, "lb");
 impl_two_op_inst!(LiInst, "li");
 impl_two_op_inst!(LuiInst, "lui");
 
@@ -288,6 +290,32 @@ impl RegReplace for LwInst {
     }
 }
 
+impl RegReplace for SbInst {
+    fn replace_use(&mut self, from: Reg, to: Reg) -> Result<()> {
+        if self.base() == &from {
+            *self.base_mut() = to;
+        }
+        if self.dst() == &from {
+            *self.dst_mut() = to;
+        }
+        Ok(())
+    }
+}
+impl RegReplace for LbInst {
+    fn replace_def(&mut self, from: Reg, to: Reg) -> Result<()> {
+        if self.dst() == &from {
+            *self.dst_mut() = to;
+        }
+        Ok(())
+    }
+    fn replace_use(&mut self, from: Reg, to: Reg) -> Result<()> {
+        if self.base() == &from {
+            *self.base_mut() = to;
+        }
+        Ok(())
+    }
+}
+
 impl RegReplace for LlaInst {
     fn replace_def(&mut self, from: Reg, to: Reg) -> Result<()> {
         if self.0 == from {
@@ -318,6 +346,8 @@ mod c {
     impl_inst_convert!(LdInst, Ld);
     impl_inst_convert!(LwInst, Lw);
     impl_inst_convert!(SwInst, Sw);
+    impl_inst_convert!(LbInst, Lb);
+    impl_inst_convert!(SbInst, Sb);
     impl_inst_convert!(LiInst, Li);
     impl_inst_convert!(LoadInst, Load);
     impl_inst_convert!(StoreInst, Store);
@@ -333,7 +363,7 @@ mod tests {
         let mv = MvInst::new(REG_A0.into(), REG_A1.into());
         assert_eq!(mv.gen_asm(), "mv a0,a1");
         let mv = MvInst::new(REG_FA0.into(), REG_FA1.into());
-        assert_eq!(mv.gen_asm(), "fmv.s fa0,fa1");
+        assert_eq!(mv.gen_asm(), "fmv.d fa0,fa1");
     }
     #[test]
     fn test_gen_asm_neg() {
@@ -58,6 +58,7 @@ pub enum Inst {
     Div(DivInst),
     UDiv(UdivInst),
     Rem(RemInst),
+    URem(UremInst),
 
     // bit count operation
     // xor
@@ -81,6 +82,9 @@ pub enum Inst {
     Feqs(FeqsInst),
     Fles(FlesInst),
     Flts(FltsInst),
+    Feqd(FeqdInst),
+    Fled(FledInst),
+    Fltd(FltdInst),
 
     // data transfer operation
     Mv(MvInst),
@@ -90,6 +94,8 @@ pub enum Inst {
     Sd(SdInst),
     Lw(LwInst),
     Sw(SwInst),
+    Lb(LbInst),
+    Sb(SbInst),
     Lla(LlaInst),
     // special load and store
     Load(LoadInst),
@@ -100,9 +106,13 @@ pub enum Inst {
     // conversion operation
     I2f(I2fInst),
     F2i(F2iInst),
+    F2d(F2dInst),
+    D2f(D2fInst),
+    Fmvdx(FmvdxInst),
 
     // control flow operation
     Jmp(JmpInst),
+    Jr(JrInst),
 
     Beq(BeqInst),
     Bne(BneInst),
@@ -161,6 +171,8 @@ impl Inst {
             Inst::Sd(inst) => inst.gen_asm(),
             Inst::Sw(inst) => inst.gen_asm(),
             Inst::Lw(inst) => inst.gen_asm(),
+            Inst::Sb(inst) => inst.gen_asm(),
+            Inst::Lb(inst) => inst.gen_asm(),
             Inst::Lla(inst) => inst.gen_asm(),
             Inst::Li(inst) => inst.gen_asm(),
             Inst::Load(inst) => inst.gen_asm(),
@@ -179,18 +191,26 @@ impl Inst {
             Inst::Or(inst) => inst.gen_asm(),
             Inst::Xor(inst) => inst.gen_asm(),
             Inst::Tail(inst) => inst.gen_asm(),
+            Inst::Jr(inst) => inst.gen_asm(),
             Inst::Seqz(inst) => inst.gen_asm(),
             Inst::I2f(i2f) => i2f.gen_asm(),
             Inst::F2i(f2i) => f2i.gen_asm(),
+            Inst::F2d(f2d) => f2d.gen_asm(),
+            Inst::D2f(d2f) => d2f.gen_asm(),
+            Inst::Fmvdx(fmvdx) => fmvdx.gen_asm(),
             Inst::Snez(snez) => snez.gen_asm(),
             Inst::Not(not) => not.gen_asm(),
             Inst::LocalAddr(local_addr) => local_addr.gen_asm(),
             Inst::Sltu(sltu) => sltu.gen_asm(),
             Inst::Sgtu(sgtu) => sgtu.gen_asm(),
             Inst::UDiv(udiv) => udiv.gen_asm(),
+            Inst::URem(urem) => urem.gen_asm(),
             Inst::Feqs(feqs) => feqs.gen_asm(),
             Inst::Fles(fles) => fles.gen_asm(),
             Inst::Flts(flts) => flts.gen_asm(),
+            Inst::Feqd(feqd) => feqd.gen_asm(),
+            Inst::Fled(fled) => fled.gen_asm(),
+            Inst::Fltd(fltd) => fltd.gen_asm(),
             Inst::Lui(lui) => lui.gen_asm(),
         }
     }
@@ -208,11 +228,12 @@ impl Inst {
                 | Inst::Call(_)
                 | Inst::Ret
                 | Inst::Tail(_)
+                | Inst::Jr(_)
         )
     }
 
     pub fn is_term(&self) -> bool {
-        matches!(self, Inst::Ret | Inst::Jmp(_) | Inst::Tail(_)) | self.is_branch()
+        matches!(self, Inst::Ret | Inst::Jmp(_) | Inst::Jr(_) | Inst::Tail(_)) | self.is_branch()
     }
 
     pub fn is_branch(&self) -> bool {
@@ -273,12 +294,17 @@ impl RegReplace for Inst {
             Inst::Sd(inst) => inst.replace_use(from, to),
             Inst::Lw(inst) => inst.replace_use(from, to),
             Inst::Sw(inst) => inst.replace_use(from, to),
+            Inst::Lb(inst) => inst.replace_use(from, to),
+            Inst::Sb(inst) => inst.replace_use(from, to),
             Inst::Load(inst) => inst.replace_use(from, to),
             Inst::Store(inst) => inst.replace_use(from, to),
             Inst::Lla(inst) => inst.replace_use(from, to),
             Inst::Li(inst) => inst.replace_use(from, to),
             Inst::I2f(i2f) => i2f.replace_use(from, to),
             Inst::F2i(f2i) => f2i.replace_use(from, to),
+            Inst::F2d(f2d) => f2d.replace_use(from, to),
+            Inst::D2f(d2f) => d2f.replace_use(from, to),
+            Inst::Fmvdx(fmvdx) => fmvdx.replace_use(from, to),
             Inst::Jmp(inst) => inst.replace_use(from, to),
             Inst::Beq(inst) => inst.replace_use(from, to),
             Inst::Bne(inst) => inst.replace_use(from, to),
@@ -293,6 +319,7 @@ impl RegReplace for Inst {
             Inst::Or(inst) => inst.replace_use(from, to),
             Inst::Xor(inst) => inst.replace_use(from, to),
             Inst::Tail(inst) => inst.replace_use(from, to),
+            Inst::Jr(inst) => inst.replace_use(from, to),
             Inst::Seqz(inst) => inst.replace_use(from, to),
             Inst::Snez(snez) => snez.replace_use(from, to),
             Inst::Not(not) => not.replace_use(from, to),
@@ -300,9 +327,13 @@ impl RegReplace for Inst {
             Inst::Sltu(sltu) => sltu.replace_use(from, to),
             Inst::Sgtu(sgtu) => sgtu.replace_use(from, to),
             Inst::UDiv(udiv) => udiv.replace_use(from, to),
+            Inst::URem(urem) => urem.replace_use(from, to),
             Inst::Feqs(feqs) => feqs.replace_use(from, to),
             Inst::Fles(fles) => fles.replace_use(from, to),
             Inst::Flts(flts) => flts.replace_use(from, to),
+            Inst::Feqd(feqd) => feqd.replace_use(from, to),
+            Inst::Fled(fled) => fled.replace_use(from, to),
+            Inst::Fltd(fltd) => fltd.replace_use(from, to),
             Inst::Lui(lui) => lui.replace_use(from, to),
         }
     }
@@ -323,12 +354,17 @@ impl RegReplace for Inst {
             Inst::Sd(inst) => inst.replace_def(from, to),
             Inst::Lw(inst) => inst.replace_def(from, to),
             Inst::Sw(inst) => inst.replace_def(from, to),
+            Inst::Lb(inst) => inst.replace_def(from, to),
+            Inst::Sb(inst) => inst.replace_def(from, to),
             Inst::Load(inst) => inst.replace_def(from, to),
             Inst::Store(inst) => inst.replace_def(from, to),
             Inst::Lla(inst) => inst.replace_def(from, to),
             Inst::Li(inst) => inst.replace_def(from, to),
             Inst::I2f(i2f) => i2f.replace_def(from, to),
             Inst::F2i(f2i) => f2i.replace_def(from, to),
+            Inst::F2d(f2d) => f2d.replace_def(from, to),
+            Inst::D2f(d2f) => d2f.replace_def(from, to),
+            Inst::Fmvdx(fmvdx) => fmvdx.replace_def(from, to),
             Inst::Jmp(inst) => inst.replace_def(from, to),
             Inst::Beq(inst) => inst.replace_def(from, to),
             Inst::Bne(inst) => inst.replace_def(from, to),
@@ -343,6 +379,7 @@ impl RegReplace for Inst {
             Inst::Or(inst) => inst.replace_def(from, to),
             Inst::Xor(inst) => inst.replace_def(from, to),
             Inst::Tail(inst) => inst.replace_def(from, to),
+            Inst::Jr(inst) => inst.replace_def(from, to),
             Inst::Seqz(inst) => inst.replace_def(from, to),
             Inst::Snez(snez) => snez.replace_def(from, to),
             Inst::Not(not) => not.replace_def(from, to),
@@ -350,9 +387,13 @@ impl RegReplace for Inst {
             Inst::Sltu(sltu) => sltu.replace_def(from, to),
             Inst::Sgtu(sgtu) => sgtu.replace_def(from, to),
             Inst::UDiv(udiv) => udiv.replace_def(from, to),
+            Inst::URem(urem) => urem.replace_def(from, to),
             Inst::Feqs(feqs) => feqs.replace_def(from, to),
             Inst::Fles(fles) => fles.replace_def(from, to),
             Inst::Flts(flts) => flts.replace_def(from, to),
+            Inst::Feqd(feqd) => feqd.replace_def(from, to),
+            Inst::Fled(fled) => fled.replace_def(from, to),
+            Inst::Fltd(fltd) => fltd.replace_def(from, to),
             Inst::Lui(lui) => lui.replace_def(from, to),
         }
     }
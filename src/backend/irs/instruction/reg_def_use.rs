@@ -45,6 +45,8 @@ impl RegUses for Inst {
             Inst::Sd(inst) => inst.uses(),
             Inst::Lw(inst) => inst.uses(),
             Inst::Sw(inst) => inst.uses(),
+            Inst::Lb(inst) => inst.uses(),
+            Inst::Sb(inst) => inst.uses(),
             Inst::Load(inst) => inst.uses(),
             Inst::Store(inst) => inst.uses(),
             Inst::Lla(inst) => inst.uses(),
@@ -62,19 +64,27 @@ impl RegUses for Inst {
             Inst::Xor(inst) => inst.uses(),
             Inst::Ret => vec![],
             Inst::Tail(tail) => tail.uses(),
+            Inst::Jr(jr) => jr.uses(),
             Inst::Li(inst) => inst.uses(),
             Inst::Seqz(inst) => inst.uses(),
             Inst::I2f(i2f) => i2f.uses(),
             Inst::F2i(f2i) => f2i.uses(),
+            Inst::F2d(f2d) => f2d.uses(),
+            Inst::D2f(d2f) => d2f.uses(),
+            Inst::Fmvdx(fmvdx) => fmvdx.uses(),
             Inst::Snez(snez) => snez.uses(),
             Inst::Not(not) => not.uses(),
             Inst::LocalAddr(laddr) => laddr.uses(),
             Inst::Sltu(sltu) => sltu.uses(),
             Inst::Sgtu(sgtu) => sgtu.uses(),
             Inst::UDiv(udiv) => udiv.uses(),
+            Inst::URem(urem) => urem.uses(),
             Inst::Feqs(feqs) => feqs.uses(),
             Inst::Fles(fles) => fles.uses(),
             Inst::Flts(flts) => flts.uses(),
+            Inst::Feqd(feqd) => feqd.uses(),
+            Inst::Fled(fled) => fled.uses(),
+            Inst::Fltd(fltd) => fltd.uses(),
             Inst::Lui(lui) => lui.uses(),
         }
     }
@@ -101,6 +111,8 @@ impl RegDefs for Inst {
             Inst::Store(inst) => inst.defs(),
             Inst::Lw(inst) => inst.defs(),
             Inst::Sw(inst) => inst.defs(),
+            Inst::Lb(inst) => inst.defs(),
+            Inst::Sb(inst) => inst.defs(),
             Inst::Lla(inst) => inst.defs(),
             Inst::Jmp(inst) => inst.defs(),
             Inst::Beq(inst) => inst.defs(),
@@ -112,20 +124,28 @@ impl RegDefs for Inst {
             Inst::Call(inst) => inst.defs(),
             Inst::Ret => vec![],
             Inst::Tail(tail) => tail.defs(),
+            Inst::Jr(jr) => jr.defs(),
             Inst::Slt(inst) => inst.defs(),
             Inst::Li(inst) => inst.defs(),
             Inst::Seqz(inst) => inst.defs(),
             Inst::I2f(i2f) => i2f.defs(),
             Inst::F2i(f2i) => f2i.defs(),
+            Inst::F2d(f2d) => f2d.defs(),
+            Inst::D2f(d2f) => d2f.defs(),
+            Inst::Fmvdx(fmvdx) => fmvdx.defs(),
             Inst::Snez(snez) => snez.defs(),
             Inst::Not(not) => not.defs(),
             Inst::LocalAddr(laddr) => laddr.defs(),
             Inst::Sltu(sltu) => sltu.defs(),
             Inst::Sgtu(sgtu) => sgtu.defs(),
             Inst::UDiv(udiv) => udiv.defs(),
+            Inst::URem(urem) => urem.defs(),
             Inst::Feqs(feqs) => feqs.defs(),
             Inst::Fles(fles) => fles.defs(),
             Inst::Flts(flts) => flts.defs(),
+            Inst::Feqd(feqd) => feqd.defs(),
+            Inst::Fled(fled) => fled.defs(),
+            Inst::Fltd(fltd) => fltd.defs(),
             Inst::Lui(lui) => lui.defs(),
         }
     }
@@ -172,6 +192,23 @@ impl RegUses for SwInst {
     }
 }
 impl RegDefs for SwInst {}
+impl RegUses for LbInst {
+    fn uses(&self) -> Vec<&Reg> {
+        vec![self.base()]
+    }
+}
+impl RegDefs for LbInst {
+    fn defs(&self) -> Vec<&Reg> {
+        vec![self.dst()]
+    }
+}
+
+impl RegUses for SbInst {
+    fn uses(&self) -> Vec<&Reg> {
+        vec![self.base(), self.dst()]
+    }
+}
+impl RegDefs for SbInst {}
 
 impl RegDefs for LoadInst {
     fn defs(&self) -> Vec<&Reg> {
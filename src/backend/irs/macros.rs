@@ -18,10 +18,20 @@
 macro_rules! impl_three_op_inst {
     ($ty_name:ident,$inst_name:expr) => {
         #[derive(Clone, Debug)]
-        pub struct $ty_name(Operand, Operand, Operand);
+        pub struct $ty_name(Operand, Operand, Operand, bool);
         impl $ty_name {
             pub fn new(dst: Operand, lhs: Operand, rhs: Operand) -> Self {
-                Self(dst, lhs, rhs)
+                Self(dst, lhs, rhs, false)
+            }
+
+            /// Operate on 8-byte values, float operation uses double precision
+            pub fn with_8byte(mut self) -> Self {
+                self.3 = true;
+                self
+            }
+
+            pub fn is_8byte(&self) -> bool {
+                self.3
             }
 
             pub fn dst(&self) -> &Operand {
@@ -55,6 +65,8 @@ macro_rules! impl_three_op_inst {
                         } else {
                             format!("{} {},{},{}", $inst_name, dst, lhs, rhs)
                         }
+                    } else if self.3 {
+                        format!("f{}.d {},{},{}", $inst_name, dst, lhs, rhs)
                     } else {
                         format!("f{}.s {},{},{}", $inst_name, dst, lhs, rhs)
                     }
@@ -123,11 +135,16 @@ macro_rules! impl_three_op_inst_with_dstmem {
                 Self(dst, lhs, rhs, false)
             }
 
+            /// Operate on 8-byte values, float operation uses double precision
             pub fn with_8byte(mut self) -> Self {
                 self.3 = true;
                 self
             }
 
+            pub fn is_8byte(&self) -> bool {
+                self.3
+            }
+
             pub fn dst(&self) -> &Operand {
                 &self.0
             }
@@ -168,6 +185,8 @@ macro_rules! impl_three_op_inst_with_dstmem {
                                 format!("{}w {},{},{}", $inst_name, dst, lhs, rhs)
                             }
                         }
+                    } else if self.3 {
+                        format!("f{}.d {},{},{}", $inst_name, dst, lhs, rhs)
                     } else {
                         format!("f{}.s {},{},{}", $inst_name, dst, lhs, rhs)
                     }
@@ -307,6 +326,9 @@ macro_rules! impl_branch_inst {
 #[macro_export]
 macro_rules! impl_two_op_inst {
     ($ty_name:ident,$inst_name:expr) => {
+        impl_two_op_inst!($ty_name, $inst_name, "s");
+    };
+    ($ty_name:ident,$inst_name:expr,$float_suffix:expr) => {
         #[derive(Clone, Debug)]
         pub struct $ty_name(Operand, Operand);
         impl $ty_name {
@@ -332,7 +354,7 @@ macro_rules! impl_two_op_inst {
                     if r.is_usual() {
                         format!("{} {},{}", $inst_name, dst, src)
                     } else {
-                        format!("f{}.s {},{}", $inst_name, dst, src)
+                        format!("f{}.{} {},{}", $inst_name, $float_suffix, dst, src)
                     }
                 } else {
                     unreachable!()
@@ -380,75 +402,29 @@ macro_rules! impl_two_op_inst {
 
 #[macro_export]
 /// create a new instruction type with two operands for conversion inst like fcvt.s.w and fcvt.w.s
+/// integer format `w` and float format `s` in the name are replaced according to the flags
 macro_rules! impl_conversion_inst {
-    ($ty_name:ident,$inst_name:expr) => {
+    ($ty_name:ident,$inst_name:expr $(,$inst_suffix:expr)?) => {
         #[derive(Clone, Debug)]
-        pub struct $ty_name(Operand, Operand);
+        pub struct $ty_name(Operand, Operand, bool, bool, bool);
         impl $ty_name {
             pub fn new(dst: Operand, src: Operand) -> Self {
-                Self(dst, src)
+                Self(dst, src, false, false, false)
             }
-            pub fn dst(&self) -> &Operand {
-                &self.0
-            }
-            pub fn src(&self) -> &Operand {
-                &self.1
-            }
-            pub fn dst_mut(&mut self) -> &mut Operand {
-                &mut self.0
-            }
-            pub fn src_mut(&mut self) -> &mut Operand {
-                &mut self.1
-            }
-            pub fn gen_asm(&self) -> String {
-                let dst = self.dst().gen_asm();
-                let src = self.src().gen_asm();
-                format!("{} {},{}", $inst_name, dst, src)
-            }
-        }
-        impl RegDefs for $ty_name {
-            fn defs(&self) -> Vec<&Reg> {
-                if let Operand::Reg(reg) = self.dst() {
-                    vec![reg]
-                } else {
-                    vec![]
-                }
-            }
-        }
-        impl RegUses for $ty_name {
-            fn uses(&self) -> Vec<&Reg> {
-                if let Operand::Reg(reg) = self.src() {
-                    vec![reg]
-                } else {
-                    vec![]
-                }
-            }
-        }
-        impl RegReplace for $ty_name {
-            fn replace_use(&mut self, from: Reg, to: Reg) -> Result<()> {
-                if let Operand::Reg(reg) = self.src_mut() {
-                    if *reg == from {
-                        *reg = to;
-                    }
-                }
-                Ok(())
+            /// Integer side is 8-byte, i.e. `l` instead of `w`
+            pub fn with_8byte(mut self) -> Self {
+                self.2 = true;
+                self
             }
-            fn replace_def(&mut self, from: Reg, to: Reg) -> Result<()> {
-                if let Operand::Reg(reg) = self.dst_mut() {
-                    if *reg == from {
-                        *reg = to;
-                    }
-                }
-                Ok(())
+            /// Integer side is unsigned, i.e. `wu` instead of `w`
+            pub fn with_unsigned(mut self) -> Self {
+                self.3 = true;
+                self
             }
-        }
-    };
-    ($ty_name:ident,$inst_name:expr,$inst_suffix:expr) => {
-        #[derive(Clone, Debug)]
-        pub struct $ty_name(Operand, Operand);
-        impl $ty_name {
-            pub fn new(dst: Operand, src: Operand) -> Self {
-                Self(dst, src)
+            /// Float side is double precision, i.e. `d` instead of `s`
+            pub fn with_double(mut self) -> Self {
+                self.4 = true;
+                self
             }
             pub fn dst(&self) -> &Operand {
                 &self.0
@@ -465,7 +441,26 @@ macro_rules! impl_conversion_inst {
             pub fn gen_asm(&self) -> String {
                 let dst = self.dst().gen_asm();
                 let src = self.src().gen_asm();
-                format!("{} {},{},{}", $inst_name, dst, src, $inst_suffix)
+                let int_format = match (self.2, self.3) {
+                    (false, false) => "w",
+                    (false, true) => "wu",
+                    (true, false) => "l",
+                    (true, true) => "lu",
+                };
+                let float_format = if self.4 { "d" } else { "s" };
+                let inst_name = $inst_name
+                    .split('.')
+                    .map(|part| match part {
+                        "w" => int_format,
+                        "s" => float_format,
+                        part => part,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(".");
+                #[allow(unused_mut)]
+                let mut asm = format!("{} {},{}", inst_name, dst, src);
+                $(asm = format!("{},{}", asm, $inst_suffix);)?
+                asm
             }
         }
         impl RegDefs for $ty_name {
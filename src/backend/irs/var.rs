@@ -31,6 +31,8 @@ pub enum Var {
 pub enum PrimVar {
     IntVar(IntVar),
     FloatVar(FloatVar),
+    DwordVar(DwordVar),
+    DoubleVar(DoubleVar),
 }
 #[derive(Clone, Debug)]
 pub struct IntVar {
@@ -56,6 +58,19 @@ pub struct FloatVar {
     pub init: Option<f32>,
     pub is_const: bool,
 }
+/// 8-byte integer, both `long long` and `unsigned long long`
+#[derive(Clone, Debug)]
+pub struct DwordVar {
+    pub name: String,
+    pub init: Option<u64>,
+    pub is_const: bool,
+}
+#[derive(Clone, Debug)]
+pub struct DoubleVar {
+    pub name: String,
+    pub init: Option<f64>,
+    pub is_const: bool,
+}
 #[derive(Clone, Debug)]
 pub struct ArrVar<T: Data> {
     pub name: String,
@@ -83,6 +98,8 @@ impl PrimVar {
         match self {
             PrimVar::IntVar(var) => var.gen_asm(),
             PrimVar::FloatVar(var) => var.gen_asm(),
+            PrimVar::DwordVar(var) => var.gen_asm(),
+            PrimVar::DoubleVar(var) => var.gen_asm(),
         }
     }
 }
@@ -96,6 +113,16 @@ impl FloatVar {
         GenTool::gen_float(&self.name, self.init.unwrap_or(0.0))
     }
 }
+impl DwordVar {
+    pub fn gen_asm(&self) -> String {
+        GenTool::gen_dword(&self.name, self.init.unwrap_or(0))
+    }
+}
+impl DoubleVar {
+    pub fn gen_asm(&self) -> String {
+        GenTool::gen_double(&self.name, self.init.unwrap_or(0.0))
+    }
+}
 
 impl Var {
     pub fn gen_asm(&self) -> String {
@@ -119,6 +146,21 @@ impl From<IntVar> for Var {
         Var::Prim(PrimVar::IntVar(value))
     }
 }
+impl From<DwordVar> for Var {
+    fn from(value: DwordVar) -> Self {
+        Var::Prim(PrimVar::DwordVar(value))
+    }
+}
+impl From<DoubleVar> for Var {
+    fn from(value: DoubleVar) -> Self {
+        Var::Prim(PrimVar::DoubleVar(value))
+    }
+}
+impl From<Str> for Var {
+    fn from(value: Str) -> Self {
+        Var::Str(value)
+    }
+}
 impl From<ArrVar<u32>> for Var {
     fn from(value: ArrVar<u32>) -> Self {
         Var::IntArr(value)
@@ -34,6 +34,10 @@ impl Block {
                     tos.push(label.to_string());
                     break;
                 }
+                Inst::Jr(jr) => {
+                    tos.extend(jr.targets().iter().map(|l| l.to_string()));
+                    break;
+                }
                 Inst::Tail(_) => break,
                 Inst::Ret => break,
                 _ => continue,
@@ -147,9 +147,14 @@ impl Block {
                     _ => unimplemented!(),
                 }
             }
-            Inst::Sltu(_) | Inst::Sgtu(_) | Inst::Feqs(_) | Inst::Fles(_) | Inst::Flts(_) => {
-                return Ok(())
-            }
+            Inst::Sltu(_)
+            | Inst::Sgtu(_)
+            | Inst::Feqs(_)
+            | Inst::Fles(_)
+            | Inst::Flts(_)
+            | Inst::Feqd(_)
+            | Inst::Fled(_)
+            | Inst::Fltd(_) => return Ok(()),
             _ => unimplemented!(),
         };
         Ok(())
@@ -215,7 +220,10 @@ impl Block {
                 | Inst::Snez(_)
                 | Inst::Feqs(_)
                 | Inst::Fles(_)
-                | Inst::Flts(_) => return Ok(()),
+                | Inst::Flts(_)
+                | Inst::Feqd(_)
+                | Inst::Fled(_)
+                | Inst::Fltd(_) => return Ok(()),
                 _ => unimplemented!(),
             }
         } else {
@@ -285,7 +293,10 @@ impl Block {
                 | Inst::Snez(_)
                 | Inst::Feqs(_)
                 | Inst::Fles(_)
-                | Inst::Flts(_) => return Ok(()),
+                | Inst::Flts(_)
+                | Inst::Feqd(_)
+                | Inst::Fled(_)
+                | Inst::Fltd(_) => return Ok(()),
                 _ => unimplemented!(),
             }
         } else {
@@ -468,6 +479,12 @@ impl Block {
                 Inst::Sw(sw) => {
                     opt_ls!(SwInst, sw, get_val, reg_vals);
                 }
+                Inst::Lb(lb) => {
+                    opt_ls!(LbInst, lb, get_val, reg_vals);
+                }
+                Inst::Sb(sb) => {
+                    opt_ls!(SbInst, sb, get_val, reg_vals);
+                }
                 Inst::Ld(ld) => {
                     opt_ls!(LdInst, ld, get_val, reg_vals);
                 }
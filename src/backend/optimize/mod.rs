@@ -14,7 +14,7 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{config::CONFIG, fprintln};
+use crate::{config::CONFIG, fprintln, utils::pass_instrument};
 
 use super::irs::*;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -75,33 +75,48 @@ fn test_symplify_and_desimplify_term(func: &mut Func) {
 
 #[allow(unused)]
 pub fn optimize_func(func: &mut Func) -> Result<()> {
-    block::handle_block_simplify(func)?;
+    run_stage(func, "block_simplify", block::handle_block_simplify)?;
 
     // inst combine? 匹配一些模式,将多条指令合并成一条
     fprintln!("log/before_inst_combine.s", "{}", func.gen_asm());
-    inst_combine::handle_inst_combine(func)?;
+    run_stage(func, "inst_combine", inst_combine::handle_inst_combine)?;
 
     // inst split? 将一条指令拆分成多条
-    pre_inst_split::handle_mul_div_opt(func)?;
+    run_stage(func, "mul_div_opt", pre_inst_split::handle_mul_div_opt)?;
 
-    phisicalize::handle_illegal_inst(func)?;
+    run_stage(func, "illegal_inst", phisicalize::handle_illegal_inst)?;
 
-    phisicalize::handle_long_jump(func, &REG_T0, 20_0000);
+    run_stage(func, "long_jump", |func| {
+        phisicalize::handle_long_jump(func, &REG_T0, 20_0000);
+        Ok(())
+    })?;
 
     fprintln!("log/after_inst_scheduling.s", "{}", func.gen_asm());
     // register allocation
-    reg_alloc::handle_reg_alloc(func)?;
+    run_stage(func, "reg_alloc", reg_alloc::handle_reg_alloc)?;
     fprintln!("log/after_reg_alloc.s", "{}", func.gen_asm());
 
     // processing caller-save and callee-save
-    caller_callee::handle_caller_callee(func)?;
+    run_stage(func, "caller_callee", caller_callee::handle_caller_callee)?;
 
     // processing stack frame's opening and closing
-    stack::handle_stack(func)?;
+    run_stage(func, "stack", stack::handle_stack)?;
 
     // inst scheduling
-    schedule::handle_inst_scheduling(func)?;
+    run_stage(func, "inst_scheduling", schedule::handle_inst_scheduling)?;
 
-    func.simplify_term();
+    run_stage(func, "simplify_term", |func| {
+        func.simplify_term();
+        Ok(())
+    })?;
     Ok(())
 }
+
+/// Run a stage of `optimize_func`, with IR dumps and timing if requested by command line.
+fn run_stage<F>(func: &mut Func, stage: &str, f: F) -> Result<()>
+where
+    F: FnOnce(&mut Func) -> Result<()>,
+{
+    let name = func.name().to_string();
+    pass_instrument::run_pass(stage, &name, "s", func, Func::gen_asm, f)
+}
@@ -252,6 +252,16 @@ impl Block {
             None
         }
 
+        /// 8 字节乘法拆分出的移位也必须是 8 字节的
+        fn _with_width(sll: SllInst, is_8byte: bool) -> SllInst {
+            if is_8byte {
+                sll.with_8byte()
+            } else {
+                sll
+            }
+        }
+
+        let is_8byte = mul.is_8byte();
         if let Operand::Imm(imm) = mul.rhs() {
             let num = **imm;
             let ones: Vec<u32> = (0..64).filter(|&i| (num >> i) & 1 == 1).collect();
@@ -259,12 +269,13 @@ impl Block {
                 let lhs = mul.lhs();
                 let sll_m = r_g.gen_virtual_usual_reg();
                 let sll = SllInst::new(sll_m.into(), lhs.clone(), (m as i64).into());
-                new_insts.push(sll.into());
+                new_insts.push(_with_width(sll, is_8byte).into());
                 let sll_n = r_g.gen_virtual_usual_reg();
                 let sll = SllInst::new(sll_n.into(), lhs.clone(), (n as i64).into());
-                new_insts.push(sll.into());
+                new_insts.push(_with_width(sll, is_8byte).into());
                 let dst = mul.dst();
                 let sub = SubInst::new(dst.clone(), sll_m.into(), sll_n.into());
+                let sub = if is_8byte { sub.with_8byte() } else { sub };
                 new_insts.push(sub.into());
             } else if num == 0 {
                 let dst = mul.dst();
@@ -276,13 +287,14 @@ impl Block {
                 let first = &ones[0]; // 不会出现 ones.len() == 0 的情况, 因为 ones.len() ==0 则 num == 0
                 let dst = mul.dst();
                 let sll = SllInst::new(dst.clone(), lhs.clone(), (*first as i64).into());
-                new_insts.push(sll.into());
+                new_insts.push(_with_width(sll, is_8byte).into());
                 let rest = &ones[1..];
                 for r in rest {
                     let _sll_r = r_g.gen_virtual_usual_reg();
                     let sll = SllInst::new(_sll_r.into(), lhs.clone(), (*r as i64).into());
-                    new_insts.push(sll.into());
+                    new_insts.push(_with_width(sll, is_8byte).into());
                     let add = AddInst::new(dst.clone(), dst.clone(), _sll_r.into());
+                    let add = if is_8byte { add.with_8byte() } else { add };
                     new_insts.push(add.into());
                 }
             } else {
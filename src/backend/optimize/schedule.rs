@@ -148,14 +148,24 @@ impl Inst {
             Inst::LocalAddr(_) => Ok((1, InstType::Integer)),
             Inst::Li(_) | Inst::Lla(_) | Inst::Lui(_) => Ok((1, InstType::Integer)),
             /* mem access */
-            Inst::F2i(_) | Inst::Fles(_) | Inst::Feqs(_) | Inst::Flts(_) | Inst::I2f(_) => {
-                Ok((4, InstType::FloatPoint))
-            }
+            Inst::F2i(_)
+            | Inst::Fles(_)
+            | Inst::Feqs(_)
+            | Inst::Flts(_)
+            | Inst::Fled(_)
+            | Inst::Feqd(_)
+            | Inst::Fltd(_)
+            | Inst::I2f(_)
+            | Inst::F2d(_)
+            | Inst::D2f(_)
+            | Inst::Fmvdx(_) => Ok((4, InstType::FloatPoint)),
             /* mem access */
             Inst::Ld(_)
             | Inst::Sd(_)
             | Inst::Lw(_)
             | Inst::Sw(_)
+            | Inst::Lb(_)
+            | Inst::Sb(_)
             | Inst::Load(_)
             | Inst::Store(_) => Ok((3, InstType::MemAccess)),
             /* jmp */
@@ -168,10 +178,13 @@ impl Inst {
             | Inst::Bge(_)
             | Inst::Call(_)
             | Inst::Ret
+            | Inst::Jr(_)
             | Inst::Tail(_) => Ok((1, InstType::Jmp)),
             /* div mul */
             Inst::Mul(_) => Ok((5, InstType::Mul)),
-            Inst::Div(_) | Inst::UDiv(_) | Inst::Rem(_) => Ok((6, InstType::DivRem)),
+            Inst::Div(_) | Inst::UDiv(_) | Inst::Rem(_) | Inst::URem(_) => {
+                Ok((6, InstType::DivRem))
+            }
         }
     }
 }
@@ -412,6 +425,7 @@ impl<'a> Graph<'a> {
                 | Inst::Tail(_)
                 | Inst::Ret
                 | Inst::Jmp(_)
+                | Inst::Jr(_)
                 | Inst::Call(_) => { /* 对于 bucket 啥也不干, 后面再单独处理 */ }
                 Inst::Ld(ld) => {
                     let base = ld.base();
@@ -469,6 +483,34 @@ impl<'a> Graph<'a> {
                             .push((id, true));
                     }
                 }
+                Inst::Lb(lb) => {
+                    let base = lb.base();
+                    if let Some(label) = reg_label.get(base) {
+                        bucket
+                            .entry(WrapOperand::Label(label.clone()))
+                            .or_default()
+                            .push((id, false));
+                    } else {
+                        bucket
+                            .entry(WrapOperand::Global)
+                            .or_default()
+                            .push((id, false));
+                    }
+                }
+                Inst::Sb(sb) => {
+                    let base = sb.base();
+                    if let Some(label) = reg_label.get(base) {
+                        bucket
+                            .entry(WrapOperand::Label(label.clone()))
+                            .or_default()
+                            .push((id, true));
+                    } else {
+                        bucket
+                            .entry(WrapOperand::Global)
+                            .or_default()
+                            .push((id, true));
+                    }
+                }
                 Inst::Store(sd) => {
                     bucket
                         .entry(WrapOperand::Stack(*sd.dst()))
@@ -113,8 +113,9 @@ pub fn handle_illegal_inst(func: &mut Func) -> Result<()> {
                 Inst::Mul(mul) => process_rhs_imm!(mul, r_g, new_insts),
                 Inst::Div(div) => process_rhs_imm!(div, r_g, new_insts),
                 Inst::Rem(rem) => process_rhs_imm!(rem, r_g, new_insts),
+                Inst::UDiv(udiv) => process_rhs_imm!(udiv, r_g, new_insts),
+                Inst::URem(urem) => process_rhs_imm!(urem, r_g, new_insts),
                 Inst::Sub(sub) => process_rhs_imm!(sub, r_g, new_insts),
-                // TODO, divu
                 _ => {
                     new_insts.push(inst.clone());
                 }
@@ -498,6 +499,8 @@ pub fn handle_offset_overflows(func: &mut Func) -> Result<()> {
                 Inst::Sd(sd) => handle_offset_overflow!(sd, SdInst, new_insts),
                 Inst::Lw(lw) => handle_offset_overflow!(lw, LwInst, new_insts),
                 Inst::Sw(sw) => handle_offset_overflow!(sw, SwInst, new_insts),
+                Inst::Lb(lb) => handle_offset_overflow!(lb, LbInst, new_insts),
+                Inst::Sb(sb) => handle_offset_overflow!(sb, SbInst, new_insts),
                 Inst::Add(add) => {
                     let rhs = add.rhs();
                     if let Operand::Imm(imm) = rhs {
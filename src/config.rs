@@ -24,6 +24,54 @@ pub struct Config {
     pub num_parallel_for_block_gen_asm: usize,
     pub reg_alloc_algo: String,
     pub open_auto_parallel: bool,
+    /// Run the middle IR verifier after every transform pass.
+    #[serde(default)]
+    pub verify_each_pass: bool,
+    /// Max number of instructions a loop body can grow to by unrolling.
+    #[serde(default = "default_loop_unroll_budget")]
+    pub loop_unroll_budget: usize,
+    /// Unroll factor for loops that can't be fully unrolled.
+    #[serde(default = "default_loop_unroll_factor")]
+    pub loop_unroll_factor: usize,
+    /// Max cost of a call site to be inlined, doubled for each surrounding loop.
+    #[serde(default = "default_inline_threshold")]
+    pub inline_threshold: usize,
+    /// Max number of instructions inlining can add to the program.
+    #[serde(default = "default_inline_growth_budget")]
+    pub inline_growth_budget: usize,
+    /// Max number of instructions cloning functions with constant arguments can add.
+    #[serde(default = "default_ipccp_clone_budget")]
+    pub ipccp_clone_budget: usize,
+    /// Max number of instructions unswitching can clone in a function.
+    #[serde(default = "default_loop_unswitch_budget")]
+    pub loop_unswitch_budget: usize,
+    /// Number of inner loop iterations in a tile, 0 disables loop tiling.
+    #[serde(default)]
+    pub loop_tile_size: usize,
+}
+
+fn default_loop_unroll_budget() -> usize {
+    256
+}
+
+fn default_loop_unroll_factor() -> usize {
+    4
+}
+
+fn default_inline_threshold() -> usize {
+    64
+}
+
+fn default_inline_growth_budget() -> usize {
+    4096
+}
+
+fn default_ipccp_clone_budget() -> usize {
+    1024
+}
+
+fn default_loop_unswitch_budget() -> usize {
+    128
 }
 
 lazy_static! {
@@ -52,6 +100,38 @@ lazy_static! {
                     .unwrap_or_else(|_| "true".to_string())
                     .parse()
                     .unwrap_or(false),
+                verify_each_pass: env::var("VERIFY_EACH_PASS")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap_or(false),
+                loop_unroll_budget: env::var("LOOP_UNROLL_BUDGET")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(default_loop_unroll_budget),
+                loop_unroll_factor: env::var("LOOP_UNROLL_FACTOR")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(default_loop_unroll_factor),
+                inline_threshold: env::var("INLINE_THRESHOLD")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(default_inline_threshold),
+                inline_growth_budget: env::var("INLINE_GROWTH_BUDGET")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(default_inline_growth_budget),
+                ipccp_clone_budget: env::var("IPCCP_CLONE_BUDGET")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(default_ipccp_clone_budget),
+                loop_unswitch_budget: env::var("LOOP_UNSWITCH_BUDGET")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(default_loop_unswitch_budget),
+                loop_tile_size: env::var("LOOP_TILE_SIZE")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0),
             }
         }
     };
@@ -16,6 +16,8 @@
 
 use thiserror::Error;
 
+use crate::frontend::diagnostic::Diagnostic;
+
 // 全局error处理表
 #[derive(Debug, Error)]
 pub enum CompilerError {
@@ -41,10 +43,66 @@ pub enum CompilerError {
 pub enum FrontendError {
     // 解析错误
     #[error("parse error")]
-    ParseError(String),
+    ParseError(Diagnostic),
     // 优化错误
     #[error("optimize error")]
     OptimizeError,
+    // 未声明的标识符
+    #[error("undeclared identifier")]
+    UndeclaredIdentifier(String, Diagnostic),
+    // 函数参数数量不匹配, 依次为函数名, 形参数量, 实参数量
+    #[error("argument count mismatch")]
+    ArgumentCountMismatch(String, usize, usize, Diagnostic),
+    // 给常量赋值
+    #[error("assign to constant")]
+    AssignToConst(String, Diagnostic),
+    // 对右值取地址
+    #[error("address of rvalue")]
+    AddressOfRvalue(Diagnostic),
+    // 循环和 switch 外的 break
+    #[error("break outside loop or switch")]
+    BreakOutsideLoop(Diagnostic),
+    // 循环外的 continue
+    #[error("continue outside loop")]
+    ContinueOutsideLoop(Diagnostic),
+    // 预处理错误
+    #[error("preprocess error")]
+    PreprocessError(Diagnostic),
+    // 语义分析发现的所有错误
+    #[error("{} semantic error(s)", .0.len())]
+    SemanticErrors(Vec<FrontendError>),
+}
+
+impl FrontendError {
+    /// Diagnostic pointing to source code, if the error has one.
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            FrontendError::ParseError(diag)
+            | FrontendError::PreprocessError(diag)
+            | FrontendError::UndeclaredIdentifier(_, diag)
+            | FrontendError::ArgumentCountMismatch(_, _, _, diag)
+            | FrontendError::AssignToConst(_, diag)
+            | FrontendError::AddressOfRvalue(diag)
+            | FrontendError::BreakOutsideLoop(diag)
+            | FrontendError::ContinueOutsideLoop(diag) => Some(diag),
+            FrontendError::OptimizeError | FrontendError::SemanticErrors(_) => None,
+        }
+    }
+
+    /// Mutable diagnostic, used to remap it to original source.
+    pub fn diagnostic_mut(&mut self) -> Option<&mut Diagnostic> {
+        match self {
+            FrontendError::ParseError(diag)
+            | FrontendError::PreprocessError(diag)
+            | FrontendError::UndeclaredIdentifier(_, diag)
+            | FrontendError::ArgumentCountMismatch(_, _, _, diag)
+            | FrontendError::AssignToConst(_, diag)
+            | FrontendError::AddressOfRvalue(diag)
+            | FrontendError::BreakOutsideLoop(diag)
+            | FrontendError::ContinueOutsideLoop(diag) => Some(diag),
+            FrontendError::OptimizeError | FrontendError::SemanticErrors(_) => None,
+        }
+    }
 }
 
 // 中端错误
@@ -86,12 +144,19 @@ pub fn handle_error(err: &CompilerError) {
             eprintln!("err: {}", err);
         }
         CompilerError::FrontendError(err) => match err {
-            FrontendError::ParseError(msg) => {
-                eprintln!("msg: parse error: {}", msg);
-            }
             FrontendError::OptimizeError => {
                 eprintln!("msg: optimize error");
             }
+            FrontendError::SemanticErrors(errs) => {
+                for diag in errs.iter().filter_map(FrontendError::diagnostic) {
+                    eprintln!("{}\n", diag);
+                }
+            }
+            _ => {
+                if let Some(diag) = err.diagnostic() {
+                    eprintln!("{}", diag);
+                }
+            }
         },
         _ => (),
     }
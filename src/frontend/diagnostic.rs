@@ -0,0 +1,96 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Display;
+
+use super::Span;
+
+/// A message attached to a span of source code.
+/// Line and column are resolved on creation, so source is not needed to display it.
+///
+/// Displayed in rustc style:
+/// ```text
+/// error: expected `;`
+///  --> main.c:3:14
+///   |
+/// 3 |     int x = 1
+///   |              ^
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+
+    /// Source file, `None` if source is not read from a file.
+    pub file: Option<String>,
+
+    /// Line of span start, starting from 1.
+    pub line: usize,
+
+    /// Column of span start in characters, starting from 1.
+    pub column: usize,
+
+    /// The whole line where span starts, without line break.
+    pub source_line: String,
+}
+
+impl Diagnostic {
+    pub fn new(src: &str, span: Span, message: impl Into<String>) -> Self {
+        let (line, column) = span.line_col(src);
+        let source_line = src.split('\n').nth(line - 1).unwrap_or("");
+        Self {
+            message: message.into(),
+            span,
+            file: None,
+            line,
+            column,
+            source_line: source_line.trim_end_matches('\r').to_string(),
+        }
+    }
+
+    /// Count of carets under source line.
+    /// Covers the span until line end, ignoring trailing blanks, and is at least 1.
+    fn caret_width(&self) -> usize {
+        let rest: String = self.source_line.chars().skip(self.column - 1).collect();
+        let len = self
+            .span
+            .end
+            .saturating_sub(self.span.start)
+            .min(rest.len());
+        let covered = rest.get(..len).unwrap_or(&rest).trim_end();
+        covered.chars().count().max(1)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "error: {}", self.message)?;
+        match &self.file {
+            Some(file) => writeln!(f, "{}--> {}:{}:{}", gutter, file, self.line, self.column)?,
+            None => writeln!(f, "{}--> {}:{}", gutter, self.line, self.column)?,
+        }
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.column - 1),
+            "^".repeat(self.caret_width())
+        )
+    }
+}
@@ -17,6 +17,7 @@
 use super::*;
 
 /// A declaration.
+/// Every variant ends with its span in source code.
 /// Example: `int x = 4;`
 #[derive(Clone, PartialEq, Debug)]
 pub enum Decl {
@@ -24,22 +25,46 @@ pub enum Decl {
     /// Example:
     /// `const int x;` is `Const(Int, x, None)`
     /// `const int x = 4;` is `Const(Int, x, Some(Int(4)))`
-    Const(Type, String, Option<Expr>),
+    Const(Type, String, Option<Expr>, Span),
 
     /// A declaration of a variable, optionally with assignment.
     /// Example:
     /// `int x;` is `Var(Int, x, None)`
     /// `int x = 4;` is `Var(Int, x, Some(Int(4)))`
-    Var(Type, String, Option<Expr>),
+    Var(Type, String, Option<Expr>, Span),
 
     /// Stacked declarations.
     /// Example:
     /// `int x = 1, y = 2;` is `Stack([Var(Int, x, Some(Int(1))), Var(Int, y, Some(Int(2)))])`
-    Stack(Vec<Decl>),
+    Stack(Vec<Decl>, Span),
 
     /// A declaration of a function, optionally with implementation.
     /// Example:
     /// `void f(int x)` is `Func(Void, "f", [(Int, (Some("x"))], None)`
     /// `void f() { ... }` is `Func(Void, "f", [], Some(...))`
-    Func(Type, String, Option<Box<Stmt>>),
+    Func(Type, String, Option<Box<Stmt>>, Span),
+
+    /// A definition of a struct with its fields.
+    /// Example:
+    /// `struct S { int x; float y; };` is `Struct("S", [(Int, Some("x")), (Float, Some("y"))])`
+    Struct(String, Vec<TypedIdent>, Span),
+
+    /// A definition of a union with its members.
+    /// Example:
+    /// `union U { int x; float y; };` is `Union("U", [(Int, Some("x")), (Float, Some("y"))])`
+    Union(String, Vec<TypedIdent>, Span),
+}
+
+impl Decl {
+    /// Span of the declaration in source code.
+    pub fn span(&self) -> Span {
+        match self {
+            Decl::Const(_, _, _, span)
+            | Decl::Var(_, _, _, span)
+            | Decl::Stack(_, span)
+            | Decl::Func(_, _, _, span)
+            | Decl::Struct(_, _, span)
+            | Decl::Union(_, _, span) => *span,
+        }
+    }
 }
@@ -17,50 +17,86 @@
 use super::*;
 
 /// A term that can be evaluated.
+/// Every variant ends with its span in source code.
 /// Example: `f("224")`
 #[derive(Clone, PartialEq, Debug)]
 pub enum Expr {
     /// A single variable.
     /// Example: `x`
-    Var(String),
+    Var(String, Span),
 
     /// An array, union or struct.
     /// Example: `{ 1, 2, 3 }`
-    Array(Vec<Expr>),
+    Array(Vec<Expr>, Span),
 
     /// Array indexing.
     /// Example: `x[8]`
-    Index(Box<Expr>, Box<Expr>),
+    Index(Box<Expr>, Box<Expr>, Span),
+
+    /// Member access of a struct or union.
+    /// Example: `x.y`
+    Field(Box<Expr>, String, Span),
+
+    /// Member access through a pointer to struct or union.
+    /// Example: `x->y`
+    Arrow(Box<Expr>, String, Span),
 
     /// A single 32-bit integer.
     /// Example: `8`
-    Int(i32),
+    Int(i32, Span),
 
     /// A single-precision floating-point number.
     /// Example: `3.6`
-    Float(f32),
+    Float(f32, Span),
 
     /// A string literal.
     /// Example: `"good"`
-    String(String),
+    String(String, Span),
 
     /// A boolean literal.
     /// Example: `false`
-    Bool(bool),
+    Bool(bool, Span),
 
     /// A function call.
     /// Example: `f(x, y)`
-    Call(Box<Expr>, Vec<Expr>),
+    Call(Box<Expr>, Vec<Expr>, Span),
 
     /// Application of unary operator.
     /// Example: `!false`, `x++`
-    Unary(UnaryOp, Box<Expr>),
+    Unary(UnaryOp, Box<Expr>, Span),
 
     /// Application of binary operator.
     /// Example: `a + b`
-    Binary(Box<Expr>, Vec<(BinaryOp, Expr)>),
+    Binary(Box<Expr>, Vec<(BinaryOp, Expr)>, Span),
+
+    /// Assignment, with binary operator for compound assignment.
+    /// Value of assignment is the value stored.
+    /// Example: `x = 1`, `x += 1`
+    Assign(Box<Expr>, Option<BinaryOp>, Box<Expr>, Span),
 
     /// Zero initializer.
     /// Example: `zeroinitializer`
-    Zero(Box<Type>),
+    Zero(Box<Type>, Span),
+}
+
+impl Expr {
+    /// Span of the expression in source code.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Var(_, span)
+            | Expr::Array(_, span)
+            | Expr::Index(_, _, span)
+            | Expr::Field(_, _, span)
+            | Expr::Arrow(_, _, span)
+            | Expr::Int(_, span)
+            | Expr::Float(_, span)
+            | Expr::String(_, span)
+            | Expr::Bool(_, span)
+            | Expr::Call(_, _, span)
+            | Expr::Unary(_, _, span)
+            | Expr::Binary(_, _, span)
+            | Expr::Assign(_, _, _, span)
+            | Expr::Zero(_, span) => *span,
+        }
+    }
 }
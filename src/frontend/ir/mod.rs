@@ -19,6 +19,7 @@ pub mod expr;
 pub mod misc;
 pub mod oprt;
 pub mod program;
+pub mod span;
 pub mod stmt;
 pub mod typed;
 
@@ -28,5 +29,6 @@ pub use expr::*;
 pub use misc::*;
 pub use oprt::*;
 pub use program::*;
+pub use span::*;
 pub use stmt::*;
 pub use typed::*;
@@ -15,8 +15,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 /// Unary operator type.
-/// Unlike action, target of unary operator does not need to be a left value.
-/// Example: `!`, `~`
+/// Target of `&`, `++` and `--` should be a left value.
+/// Example: `!`, `x++`
 #[derive(Clone, PartialEq, Debug)]
 pub enum UnaryOp {
     /// `!`
@@ -25,6 +25,18 @@ pub enum UnaryOp {
     Neg,
     /// `+`
     Pos,
+    /// `&`, target should be a left value
+    AddrOf,
+    /// `*`
+    Deref,
+    /// `++x`
+    PreInc,
+    /// `--x`
+    PreDec,
+    /// `x++`
+    PostInc,
+    /// `x--`
+    PostDec,
 }
 
 /// Binry operator type.
@@ -67,4 +79,6 @@ pub enum BinaryOp {
     And,
     /// ||
     Or,
+    /// ,
+    Comma,
 }
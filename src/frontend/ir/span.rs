@@ -0,0 +1,68 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Debug;
+use std::ops::Range;
+
+/// Byte range of a syntax node in the source code.
+/// Example: `x` in `int x;` is `Span { start: 4, end: 5 }`
+///
+/// Spans are metadata: they never take part in equality,
+/// so that `int a[4]` and `int b[4]` still have the same type.
+#[derive(Clone, Copy, Default, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Merge with a later span, covering everything in between.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// Resolve the span to (line, column) in `src`, both starting from 1.
+    pub fn line_col(&self, src: &str) -> (usize, usize) {
+        let start = self.start.min(src.len());
+        let before = &src[..start];
+        let line = before.matches('\n').count() + 1;
+        let line_begin = before.rfind('\n').map_or(0, |ix| ix + 1);
+        let column = before[line_begin..].chars().count() + 1;
+        (line, column)
+    }
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
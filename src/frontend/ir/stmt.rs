@@ -18,52 +18,88 @@ use super::*;
 
 /// A statement.
 /// Statements can not appear at top level.
+/// Every variant ends with its span in source code.
 /// Example: `continue`
 #[derive(Clone, PartialEq, Debug)]
 pub enum Stmt {
     /// A statement of nothing.
     /// Example: `;`
-    Nothing,
+    Nothing(Span),
 
     /// A declaration as statement.
     /// Example:
     /// `int x;` is `Decl(Var(Int, "x"))`
-    Decl(Decl),
+    Decl(Decl, Span),
 
     /// An expression as statement.
     /// Example:
     /// `y = x++;` is `Expr(Var("y"), UnaryOperator(...))`
-    Expr(Option<Expr>, Expr),
+    Expr(Option<Expr>, Expr, Span),
 
     /// A conditional branch.
     /// If the third argument is None, it means there's no else block.
     /// Example:
     /// `if (x == 4) ... else ...` is `If(Binary(...), ..., ...)`
-    If(Expr, Box<Stmt>, Box<Stmt>),
+    If(Expr, Box<Stmt>, Box<Stmt>, Span),
 
     /// A while-loop.
     /// Example:
     /// `while (true) ...` is `While(True, ...)`
-    While(Expr, Box<Stmt>),
+    While(Expr, Box<Stmt>, Span),
 
     /// A do-while-loop.
     /// Example:
     /// `do ... while (true)` is `DoWhile(..., True)`
-    DoWhile(Box<Stmt>, Expr),
+    DoWhile(Box<Stmt>, Expr, Span),
+
+    /// A for-loop.
+    /// Initializer is a declaration, an expression statement or nothing.
+    /// If condition is None, the loop runs until break.
+    /// Step is an expression statement or nothing.
+    /// Example:
+    /// `for (int i = 0; i < n; i = i + 1) ...` is `For(Decl(...), Some(Binary(...)), Expr(...), ...)`
+    For(Box<Stmt>, Option<Expr>, Box<Stmt>, Box<Stmt>, Span),
+
+    /// A switch statement.
+    /// Each arm is a case label (None for `default`) followed by statements,
+    /// and control falls through to the next arm unless it breaks.
+    /// Example:
+    /// `switch (x) { case 1: ... default: ... }` is `Switch(Var("x"), [(Some(Int(1)), [...]), (None, [...])])`
+    Switch(Expr, Vec<(Option<Expr>, Vec<Stmt>)>, Span),
 
     /// A break statement.
-    Break,
+    Break(Span),
 
     /// A continue statement.
-    Continue,
+    Continue(Span),
 
     /// A return statement.
     /// Example:
     /// `return x` is `Return(x)`
-    Return(Option<Expr>),
+    Return(Option<Expr>, Span),
 
     /// A nested block.
     /// Example:
     /// `{ ... }` is `Vec<Statement>([...])`
-    Block(Vec<Stmt>),
+    Block(Vec<Stmt>, Span),
+}
+
+impl Stmt {
+    /// Span of the statement in source code.
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Nothing(span)
+            | Stmt::Decl(_, span)
+            | Stmt::Expr(_, _, span)
+            | Stmt::If(_, _, _, span)
+            | Stmt::While(_, _, span)
+            | Stmt::DoWhile(_, _, span)
+            | Stmt::For(_, _, _, _, span)
+            | Stmt::Switch(_, _, span)
+            | Stmt::Break(span)
+            | Stmt::Continue(span)
+            | Stmt::Return(_, span)
+            | Stmt::Block(_, span) => *span,
+        }
+    }
 }
@@ -30,9 +30,21 @@ pub enum Type {
     /// 32-bit integer.
     Int,
 
+    /// 32-bit unsigned integer.
+    UnsignedInt,
+
+    /// 64-bit integer.
+    Long,
+
+    /// 64-bit unsigned integer.
+    UnsignedLong,
+
     /// 32-bit floating-point number.
     Float,
 
+    /// 64-bit floating-point number.
+    Double,
+
     /// String.
     String,
 
@@ -77,15 +89,34 @@ pub enum Type {
 impl Type {
     pub fn default_initializer(&self) -> Result<Expr> {
         match self {
-            Type::Int => Ok(Expr::Int(0)),
-            Type::Float => Ok(Expr::Float(0.0)),
-            Type::Bool => Ok(Expr::Bool(false)),
-            Type::Array(_, _) => Ok(Expr::Zero(self.clone().into())),
+            Type::Int | Type::UnsignedInt | Type::Long | Type::UnsignedLong | Type::Char => {
+                Ok(Expr::Int(0, Span::default()))
+            }
+            Type::Float | Type::Double => Ok(Expr::Float(0.0, Span::default())),
+            Type::Bool => Ok(Expr::Bool(false, Span::default())),
+            Type::Array(_, _) | Type::Struct(_) | Type::Union(_) => {
+                Ok(Expr::Zero(self.clone().into(), Span::default()))
+            }
             _ => Err(anyhow!("Cannot initialize type {:?}", self)).with_context(|| context!()),
         }
     }
 }
 
+impl Type {
+    /// Check if this type is a struct or union.
+    pub fn is_record(&self) -> bool {
+        matches!(self, Type::Struct(_) | Type::Union(_))
+    }
+
+    /// Check if this type is a struct or union, or an array of them.
+    pub fn has_record(&self) -> bool {
+        match self {
+            Type::Array(ty, _) => ty.has_record(),
+            _ => self.is_record(),
+        }
+    }
+}
+
 /// A left value is an identifier with usage of its type.
 /// If identifier is not null, it can be assigned to.
 /// Example: `(*f)(int)` indicates that `f` should be used as `(*f)(some_int)`.
@@ -14,23 +14,40 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod diagnostic;
 pub mod ir;
 pub mod parse;
 pub mod preprocess;
+pub mod sema;
 pub mod transform;
 
+use std::path::{Path, PathBuf};
+
 pub use ir::*;
+use preprocess::LineMap;
 use transform::constant_fold;
 
 use crate::errors::FrontendError;
 
 #[allow(unused)]
 pub fn parse(src: &str) -> Result<Program, FrontendError> {
-    let preprocessed = preprocess::timing::process(src);
-    let mut program = parse::program::parse(&preprocessed)?;
+    let (preprocessed, line_map) = preprocess::preprocess(src)?;
+    parse_preprocessed(&preprocessed, &line_map)
+}
+
+/// Preprocess and parse a file, searching `include_dirs` for `#include`.
+pub fn parse_file(path: &Path, include_dirs: Vec<PathBuf>) -> Result<Program, FrontendError> {
+    let (preprocessed, line_map) = preprocess::preprocess_file(path, include_dirs)?;
+    parse_preprocessed(&preprocessed, &line_map)
+}
+
+/// Parse and check preprocessed source, with diagnostics pointing at original files.
+fn parse_preprocessed(src: &str, line_map: &LineMap) -> Result<Program, FrontendError> {
+    let mut program = parse::program::parse(src).map_err(|e| line_map.remap_error(e))?;
+    sema::check(&program, src).map_err(|e| line_map.remap_error(e))?;
     match constant_fold::optimize_program(&mut program) {
         Ok(_) => Ok(program),
-        Err(e) => Err(FrontendError::OptimizeError),
+        Err(_) => Err(FrontendError::OptimizeError),
     }
 }
 
@@ -20,21 +20,13 @@ use super::*;
 
 pub fn make_const(decl: Decl) -> Decl {
     match decl {
-        Decl::Var(ty, id, expr) => Decl::Const(ty, id, expr),
+        Decl::Var(ty, id, expr, span) => Decl::Const(ty, id, expr, span),
         _ => decl,
     }
 }
 
-pub fn decl(input: &mut &str) -> PResult<Decl> {
-    // Attempt to match a macro.
-    if input.starts_with('#') {
-        return alt((
-            (token("#include"), opt(take_until(0.., '\n')), blank).value(Decl::Stack(vec![])),
-            (token("#define"), pad(ident), expr)
-                .map(|(_, id, expr)| Decl::Const(Type::Int, id, Some(expr))),
-        ))
-        .parse_next(input);
-    }
+pub fn decl(input: &mut Input) -> PResult<Decl> {
+    let start = input.location();
 
     // Match const token.
     let is_const = opt(token("const")).parse_next(input)?.is_some();
@@ -42,22 +34,34 @@ pub fn decl(input: &mut &str) -> PResult<Decl> {
     // Consume extern token.
     opt(token("extern")).parse_next(input)?;
 
-    // Parse type.
-    let left_type = atom_type.parse_next(input)?;
+    // Parse struct or union definition, or a plain type.
+    let mut record = None;
+    let left_type = match opt(record_def).parse_next(input)? {
+        Some((def, ty)) => {
+            // Definition without declarators ends here
+            if opt(token(";")).parse_next(input)?.is_some() {
+                return Ok(def);
+            }
+            record = Some(def);
+            ty
+        }
+        None => atom_type.parse_next(input)?,
+    };
 
     // Parse lval and optional assignment expression.
     let mut decls: Vec<Decl> = separated(
         1..,
-        |input: &mut &str| assignment(input, left_type.clone()),
+        |input: &mut Input| assignment(input, left_type.clone()),
         token(","),
     )
     .parse_next(input)?;
 
     // Require semicolon if the last declaration is not function implementation
-    if let Some(Decl::Func(_, _, Some(_))) = decls.last() {
+    // Nothing else can follow declarators, so a missing semicolon is reported right here
+    if let Some(Decl::Func(_, _, Some(_), _)) = decls.last() {
         // Do nothing
     } else {
-        token(";").parse_next(input)?;
+        cut_err(token(";")).parse_next(input)?;
     }
 
     // Make constant if necessary
@@ -65,37 +69,82 @@ pub fn decl(input: &mut &str) -> PResult<Decl> {
         decls = decls.into_iter().map(make_const).collect();
     }
 
+    // Definition of struct or union comes before its declarators
+    if let Some(def) = record {
+        decls.insert(0, def);
+    }
+
     // Return declaration according to count
     match decls.len() {
         1 => Ok(decls.pop().unwrap()),
-        _ => Ok(Decl::Stack(decls)),
+        _ => {
+            let end = decls.last().map_or(start, |d| d.span().end);
+            Ok(Decl::Stack(decls, Span::new(start, end)))
+        }
     }
 }
 
-pub fn assignment(input: &mut &str, left_type: Type) -> PResult<Decl> {
-    let left_val = lval.parse_next(input)?;
+/// Parser of a struct or union definition,
+/// returns the definition along with the type it defines.
+/// Example: `struct S { int x; float y; }`
+pub fn record_def(input: &mut Input) -> PResult<(Decl, Type)> {
+    let kind = alt((token("struct").value(true), token("union").value(false)));
+    let ((is_struct, id, fields), span) =
+        spanned((kind, pad(ident), curly(record_fields))).parse_next(input)?;
+    if is_struct {
+        Ok((Decl::Struct(id.clone(), fields, span), Type::Struct(id)))
+    } else {
+        Ok((Decl::Union(id.clone(), fields, span), Type::Union(id)))
+    }
+}
+
+/// Parser of fields in struct or union body.
+/// Example: `int x, y[4]; float z;`
+pub fn record_fields(input: &mut Input) -> PResult<Vec<TypedIdent>> {
+    let field = (
+        atom_type,
+        separated(1.., lval, token(",")),
+        cut_err(token(";")),
+    )
+        .map(|(ty, lvals, _): (Type, Vec<LVal>, _)| {
+            lvals
+                .into_iter()
+                .map(|lval| acc_lval(ty.clone(), lval))
+                .collect::<Vec<_>>()
+        });
+    repeat(0.., field)
+        .map(|fields: Vec<Vec<TypedIdent>>| fields.into_iter().flatten().collect())
+        .parse_next(input)
+}
+
+pub fn assignment(input: &mut Input, left_type: Type) -> PResult<Decl> {
+    let (left_val, lval_span) = spanned(lval).parse_next(input)?;
     let typed_ident = acc_lval(left_type, left_val);
     let Some(id) = typed_ident.id else {
         return Err(ErrMode::from_error_kind(input, ErrorKind::Verify).cut());
     };
 
     // Parse optional assignment.
-    if let Some(expr) = opt(preceded(token("="), expr)).parse_next(input)? {
-        return Ok(Decl::Var(typed_ident.ty, id, Some(expr)));
+    if let Some((expr, expr_span)) =
+        opt(spanned(preceded(token("="), assign_expr))).parse_next(input)?
+    {
+        let span = lval_span.to(expr_span);
+        return Ok(Decl::Var(typed_ident.ty, id, Some(expr), span));
     };
 
     // Parse optional function implementation.
-    if let Some(body) = opt(curly(vec_stmt)).parse_next(input)? {
+    if let Some((body, body_span)) = opt(spanned(curly(vec_stmt))).parse_next(input)? {
         return Ok(Decl::Func(
             typed_ident.ty,
             id,
-            Some(Box::new(Stmt::Block(body))),
+            Some(Box::new(Stmt::Block(body, body_span))),
+            lval_span.to(body_span),
         ));
     };
 
     // Return declaration according to real type
     match typed_ident.ty {
-        Type::Function(_, _) => Ok(Decl::Func(typed_ident.ty, id, None)),
-        _ => Ok(Decl::Var(typed_ident.ty, id, None)),
+        Type::Function(_, _) => Ok(Decl::Func(typed_ident.ty, id, None, lval_span)),
+        _ => Ok(Decl::Var(typed_ident.ty, id, None, lval_span)),
     }
 }
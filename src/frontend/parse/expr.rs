@@ -18,42 +18,79 @@ use crate::gen_lrec_binary;
 
 use super::*;
 
-/// Parse a vector of Expr.
-pub fn vec_expr(input: &mut &str) -> PResult<Vec<Expr>> {
-    separated(0.., expr, token(",")).parse_next(input)
+/// Parse a vector of Expr separated by comma, so that they are not comma expressions.
+pub fn vec_expr(input: &mut Input) -> PResult<Vec<Expr>> {
+    separated(0.., assign_expr, token(",")).parse_next(input)
 }
 
 /// Parse a box of Expr.
 /// Like an `expr`, but returns the boxed version.
-pub fn box_expr(input: &mut &str) -> PResult<Box<Expr>> {
+pub fn box_expr(input: &mut Input) -> PResult<Box<Expr>> {
     expr.map(Box::new).parse_next(input)
 }
 
 /// Parse prefix expressions like `!x->y.z`.
-pub fn prefix(input: &mut &str) -> PResult<Expr> {
+pub fn prefix(input: &mut Input) -> PResult<Expr> {
     let disp = dispatch! { peek(any);
-        '{' => curly(separated(0.., expr, token(","))).map(Expr::Array),
+        '{' => spanned(curly(vec_expr)).map(|(x, s)| Expr::Array(x, s)),
         '.' | '0'..='9' => pad(constant_number),
-        '"' => pad(string_lit).map(Expr::String),
+        '"' => pad(spanned(string_lit)).map(|(x, s)| Expr::String(x, s)),
+        // Character constant has type `int` in C
+        '\'' => pad(spanned(char_lit)).map(|(x, s)| Expr::Int(x as i32, s)),
         '(' => paren(expr),
         _ => fail,
     };
-    let atom = alt((disp, pad(ident).map(Expr::Var)));
+    let atom = alt((disp, pad(spanned(ident)).map(|(x, s)| Expr::Var(x, s))));
 
-    // Postfix: `head[a].b(c)->d`.
+    // Postfix: `head[a].b(c)->d++`.
     // Tail parsers return mutation on `head`.
     // Closures should be wrapped in `BoxF` for equal sizes.
     // Wrapping all closures with `BoxF` can also fix type inference problems,
     // because all closures have unique types, making `alt` report errors.
     let postfix_tail = dispatch! { peek(any);
-        '[' => bracket(box_expr).map(|x| BoxF::new(|acc| Expr::Index(acc, x))),
-        '(' => paren(vec_expr).map(|x| BoxF::new(|acc| Expr::Call(acc, x))),
+        '[' => spanned(bracket(box_expr))
+            .map(|(x, s)| BoxF::new(move |acc: Box<Expr>| {
+                let span = acc.span().to(s);
+                Expr::Index(acc, x, span)
+            })),
+        '(' => spanned(paren(vec_expr))
+            .map(|(x, s)| BoxF::new(move |acc: Box<Expr>| {
+                let span = acc.span().to(s);
+                Expr::Call(acc, x, span)
+            })),
+        '.' => spanned(preceded(token("."), cut_err(pad(ident))))
+            .map(|(x, s)| BoxF::new(move |acc: Box<Expr>| {
+                let span = acc.span().to(s);
+                Expr::Field(acc, x, span)
+            })),
+        '-' => alt((
+            spanned(preceded(token("->"), cut_err(pad(ident))))
+                .map(|(x, s)| BoxF::new(move |acc: Box<Expr>| {
+                    let span = acc.span().to(s);
+                    Expr::Arrow(acc, x, span)
+                })),
+            spanned(token("--"))
+                .map(|(_, s)| BoxF::new(move |acc: Box<Expr>| {
+                    let span = acc.span().to(s);
+                    Expr::Unary(UnaryOp::PostDec, acc, span)
+                })),
+        )),
+        '+' => spanned(token("++"))
+            .map(|(_, s)| BoxF::new(move |acc: Box<Expr>| {
+                let span = acc.span().to(s);
+                Expr::Unary(UnaryOp::PostInc, acc, span)
+            })),
         _ => fail,
     };
     let postfix = lrec(atom, repeat(0.., postfix_tail));
 
     // Prefix unary operator.
-    let prefix_init = unary_op.map(|op| BoxF::new(|acc| Expr::Unary(op, acc)));
+    let prefix_init = spanned(unary_op).map(|(op, s)| {
+        BoxF::new(move |acc: Box<Expr>| {
+            let span = s.to(acc.span());
+            Expr::Unary(op, acc, span)
+        })
+    });
     rrec(repeat(0.., prefix_init), postfix).parse_next(input)
 }
 
@@ -70,7 +107,21 @@ gen_lrec_binary!(binary_lv7, binary_op_lv7, binary_lv6);
 gen_lrec_binary!(binary_lv8, binary_op_lv8, binary_lv7);
 gen_lrec_binary!(binary_lv9, binary_op_lv9, binary_lv8);
 
-/// Parse a conditional expression.
-pub fn expr(input: &mut &str) -> PResult<Expr> {
-    binary_lv9.parse_next(input)
+/// Parse an assignment expression, which is right associative.
+/// Example: `a = b += 1`
+pub fn assign_expr(input: &mut Input) -> PResult<Expr> {
+    let lhs = binary_lv9.parse_next(input)?;
+    let Some((op, rhs)) = opt((assign_op, assign_expr)).parse_next(input)? else {
+        return Ok(lhs);
+    };
+    let span = lhs.span().to(rhs.span());
+    Ok(Expr::Assign(Box::new(lhs), op, Box::new(rhs), span))
+}
+
+// Comma has the lowest precedence.
+gen_lrec_binary!(binary_lv10, binary_op_lv10, assign_expr);
+
+/// Parse an expression, possibly with comma.
+pub fn expr(input: &mut Input) -> PResult<Expr> {
+    binary_lv10.parse_next(input)
 }
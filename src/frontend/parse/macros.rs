@@ -17,13 +17,15 @@
 #[macro_export]
 macro_rules! gen_lrec_binary {
     ($fn_name:ident, $op_name:ident, $base:ident) => {
-        pub fn $fn_name(input: &mut &str) -> PResult<Expr> {
+        pub fn $fn_name(input: &mut Input) -> PResult<Expr> {
             let head = $base.parse_next(input)?;
             let tail: Vec<(BinaryOp, Expr)> = repeat(0.., ($op_name, $base)).parse_next(input)?;
-            Ok(if (tail.is_empty()) {
-                head
-            } else {
-                Expr::Binary(Box::new(head), tail)
+            Ok(match tail.last() {
+                None => head,
+                Some((_, last)) => {
+                    let span = head.span().to(last.span());
+                    Expr::Binary(Box::new(head), tail, span)
+                }
             })
         }
     };
@@ -16,7 +16,7 @@
 
 use super::*;
 
-pub fn map_entry(input: &mut &str) -> PResult<MapEntry> {
+pub fn map_entry(input: &mut Input) -> PResult<MapEntry> {
     (ident, token(":"), expr)
         .map(|(id, _, expr)| MapEntry::new(id, expr))
         .parse_next(input)
@@ -40,6 +40,7 @@ pub use super::ir::expr::*;
 pub use super::ir::misc::*;
 pub use super::ir::oprt::*;
 pub use super::ir::program::*;
+pub use super::ir::span::*;
 pub use super::ir::stmt::*;
 pub use super::ir::typed::*;
 
@@ -52,6 +53,7 @@ pub use winnow::combinator::alt;
 pub use winnow::combinator::cut_err;
 pub use winnow::combinator::empty;
 pub use winnow::combinator::fail;
+pub use winnow::combinator::not;
 pub use winnow::combinator::opt;
 pub use winnow::combinator::peek;
 pub use winnow::combinator::preceded;
@@ -63,8 +65,11 @@ pub use winnow::dispatch;
 pub use winnow::error::ContextError;
 pub use winnow::error::ParserError;
 pub use winnow::error::StrContext;
+pub use winnow::error::StrContextValue;
 pub use winnow::stream::AsChar;
 pub use winnow::stream::Compare;
+pub use winnow::stream::Located;
+pub use winnow::stream::Location;
 pub use winnow::stream::SliceLen;
 pub use winnow::stream::Stream;
 pub use winnow::stream::StreamIsPartial;
@@ -16,18 +16,26 @@
 
 use super::*;
 
-pub fn unary_op(input: &mut &str) -> PResult<UnaryOp> {
+pub fn unary_op(input: &mut Input) -> PResult<UnaryOp> {
     dispatch! { peek(any);
         '!' => token("!").value(UnaryOp::Not),
-        '-' => token("-").value(UnaryOp::Neg),
-        '+' => token("+").value(UnaryOp::Pos),
+        '-' => alt((
+            token("--").value(UnaryOp::PreDec),
+            token("-").value(UnaryOp::Neg),
+        )),
+        '+' => alt((
+            token("++").value(UnaryOp::PreInc),
+            token("+").value(UnaryOp::Pos),
+        )),
+        '&' => token("&").value(UnaryOp::AddrOf),
+        '*' => token("*").value(UnaryOp::Deref),
         _ => fail,
     }
     .parse_next(input)
 }
 
 /// Level 0 operators, left to right
-pub fn binary_op_lv0(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv0(input: &mut Input) -> PResult<BinaryOp> {
     dispatch! { peek(any);
         '*' => token("*").value(BinaryOp::Mul),
         '/' => token("/").value(BinaryOp::Div),
@@ -38,7 +46,7 @@ pub fn binary_op_lv0(input: &mut &str) -> PResult<BinaryOp> {
 }
 
 /// Level 1 operators, left to right
-pub fn binary_op_lv1(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv1(input: &mut Input) -> PResult<BinaryOp> {
     alt((
         token("+").value(BinaryOp::Add),
         token("-").value(BinaryOp::Sub),
@@ -47,7 +55,7 @@ pub fn binary_op_lv1(input: &mut &str) -> PResult<BinaryOp> {
 }
 
 /// Level 2 operators, left to right
-pub fn binary_op_lv2(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv2(input: &mut Input) -> PResult<BinaryOp> {
     alt((
         token(">>").value(BinaryOp::Shr),
         token("<<").value(BinaryOp::Shl),
@@ -56,7 +64,7 @@ pub fn binary_op_lv2(input: &mut &str) -> PResult<BinaryOp> {
 }
 
 /// Level 3 operators, left to right
-pub fn binary_op_lv3(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv3(input: &mut Input) -> PResult<BinaryOp> {
     dispatch! { peek(any);
         '>' => alt((
             token(">=").value(BinaryOp::Ge),
@@ -72,7 +80,7 @@ pub fn binary_op_lv3(input: &mut &str) -> PResult<BinaryOp> {
 }
 
 /// Level 4 operators, left to right
-pub fn binary_op_lv4(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv4(input: &mut Input) -> PResult<BinaryOp> {
     alt((
         token("==").value(BinaryOp::Eq),
         token("!=").value(BinaryOp::Ne),
@@ -81,26 +89,51 @@ pub fn binary_op_lv4(input: &mut &str) -> PResult<BinaryOp> {
 }
 
 /// Level 5 operators, left to right
-pub fn binary_op_lv5(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv5(input: &mut Input) -> PResult<BinaryOp> {
     token("&").value(BinaryOp::BitAnd).parse_next(input)
 }
 
 /// Level 6 operators, left to right
-pub fn binary_op_lv6(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv6(input: &mut Input) -> PResult<BinaryOp> {
     token("^").value(BinaryOp::BitXor).parse_next(input)
 }
 
 /// Level 7 operators, left to right
-pub fn binary_op_lv7(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv7(input: &mut Input) -> PResult<BinaryOp> {
     token("|").value(BinaryOp::BitOr).parse_next(input)
 }
 
 /// Level 8 operators, left to right
-pub fn binary_op_lv8(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv8(input: &mut Input) -> PResult<BinaryOp> {
     token("&&").value(BinaryOp::And).parse_next(input)
 }
 
 /// Level 9 operators, left to right
-pub fn binary_op_lv9(input: &mut &str) -> PResult<BinaryOp> {
+pub fn binary_op_lv9(input: &mut Input) -> PResult<BinaryOp> {
     token("||").value(BinaryOp::Or).parse_next(input)
 }
+
+/// Assignment operators, right to left.
+/// Compound assignment comes with its binary operator.
+pub fn assign_op(input: &mut Input) -> PResult<Option<BinaryOp>> {
+    dispatch! { peek(any);
+        '=' => token("=").value(None),
+        '+' => token("+=").value(Some(BinaryOp::Add)),
+        '-' => token("-=").value(Some(BinaryOp::Sub)),
+        '*' => token("*=").value(Some(BinaryOp::Mul)),
+        '/' => token("/=").value(Some(BinaryOp::Div)),
+        '%' => token("%=").value(Some(BinaryOp::Mod)),
+        '<' => token("<<=").value(Some(BinaryOp::Shl)),
+        '>' => token(">>=").value(Some(BinaryOp::Shr)),
+        '&' => token("&=").value(Some(BinaryOp::BitAnd)),
+        '^' => token("^=").value(Some(BinaryOp::BitXor)),
+        '|' => token("|=").value(Some(BinaryOp::BitOr)),
+        _ => fail,
+    }
+    .parse_next(input)
+}
+
+/// Level 10 operators, left to right
+pub fn binary_op_lv10(input: &mut Input) -> PResult<BinaryOp> {
+    token(",").value(BinaryOp::Comma).parse_next(input)
+}
@@ -19,27 +19,52 @@ use winnow::error::{ErrMode, ErrorKind};
 
 use super::*;
 
+/// Input of parsers, a string slice that remembers its offset in source code.
+pub type Input<'s> = Located<&'s str>;
+
+/// Run `parser` and return its output along with its span in source code.
+/// Trailing blanks consumed by `parser` are not part of the span.
+pub fn spanned<'s, Output, InnerParser>(
+    parser: InnerParser,
+) -> impl Parser<Input<'s>, (Output, Span), ContextError>
+where
+    InnerParser: Parser<Input<'s>, Output, ContextError>,
+{
+    parser
+        .with_recognized()
+        .with_span()
+        .map(|((output, text), range)| {
+            let end = range.start + text.trim_end().len();
+            (output, Span::new(range.start, end))
+        })
+}
+
 /// Parser of a word that begins with letter,
 /// and continues with letters or numbers.
 /// For example, `int`, `x114ee`
-pub fn word(input: &mut &str) -> PResult<String> {
+pub fn word(input: &mut Input) -> PResult<String> {
     let head = one_of(('A'..='Z', 'a'..='z', '_')).parse_next(input)?;
     let rest = take_while(0.., ('A'..='Z', 'a'..='z', '0'..='9', '_')).parse_next(input)?;
     Ok(format!("{}{}", head, rest))
 }
 
 /// List of all keywords.
-const KEYWORDS: [&str; 10] = [
-    "void", "int", "float", "break", "continue", "return", "if", "else", "do", "while",
+const KEYWORDS: [&str; 19] = [
+    "void", "int", "float", "break", "continue", "return", "if", "else", "do", "while", "for",
+    "switch", "case", "default", "struct", "union", "unsigned", "long", "double",
 ];
 
 /// Parser of an identifier, a word which is not a keyword.
-pub fn ident(input: &mut &str) -> PResult<String> {
+pub fn ident(input: &mut Input) -> PResult<String> {
     word.verify(|x| !KEYWORDS.contains(&x)).parse_next(input)
 }
 
 /// Match decimal or hexadecimal numbers.
-pub fn match_numbers<'a>(input: &mut &'a str, is_hex: bool, min_count: usize) -> PResult<&'a str> {
+pub fn match_numbers<'a>(
+    input: &mut Input<'a>,
+    is_hex: bool,
+    min_count: usize,
+) -> PResult<&'a str> {
     if is_hex {
         take_while(min_count.., ('0'..='9', 'a'..='f', 'A'..='F')).parse_next(input)
     } else {
@@ -48,7 +73,7 @@ pub fn match_numbers<'a>(input: &mut &'a str, is_hex: bool, min_count: usize) ->
 }
 
 /// Parser of a usize.
-pub fn usize(input: &mut &str) -> PResult<usize> {
+pub fn usize(input: &mut Input) -> PResult<usize> {
     let is_oct_or_hex = opt("0").parse_next(input)?.is_some();
     let is_hex = opt(alt(("x", "X"))).parse_next(input)?.is_some();
     let radix = if is_oct_or_hex {
@@ -67,7 +92,8 @@ pub fn usize(input: &mut &str) -> PResult<usize> {
 }
 
 /// Parser of a constant number.
-pub fn constant_number(input: &mut &str) -> PResult<Expr> {
+pub fn constant_number(input: &mut Input) -> PResult<Expr> {
+    let start = input.location();
     let hex_prefix = opt(alt(("0x", "0X"))).parse_next(input)?.unwrap_or("");
     let is_hex = !hex_prefix.is_empty();
     let exponent_charset: (&str, &str) = if is_hex { ("p", "P") } else { ("e", "E") };
@@ -89,9 +115,10 @@ pub fn constant_number(input: &mut &str) -> PResult<Expr> {
         } else {
             10
         };
+        let span = Span::new(start, input.location());
         return i32::from_str_radix(before_point, radix)
             .map_err(|_| ErrMode::from_error_kind(input, ErrorKind::Verify).cut())
-            .map(Expr::Int);
+            .map(|x| Expr::Int(x, span));
     }
 
     // Read exponent value only if there is exponent indicator ("e" | "E" | "p" | "P")
@@ -107,52 +134,77 @@ pub fn constant_number(input: &mut &str) -> PResult<Expr> {
     };
 
     // Parse the number as float
+    let span = Span::new(start, input.location());
     if is_hex {
         parse_hexf32(&number, false)
             .map_err(|_| ErrMode::from_error_kind(input, ErrorKind::Verify).cut())
-            .map(Expr::Float)
+            .map(|x| Expr::Float(x, span))
     } else {
         number
             .parse()
             .map_err(|_| ErrMode::from_error_kind(input, ErrorKind::Verify).cut())
-            .map(Expr::Float)
+            .map(|x| Expr::Float(x, span))
     }
 }
 
-/// Parser of a string literal.
-pub fn string_lit(input: &mut &str) -> PResult<String> {
-    // TODO escape
-    let _ = '"'.parse_next(input)?;
-    let content = take_until(0.., '"').parse_next(input)?;
+/// Parser of a string literal, with escape sequences replaced.
+pub fn string_lit(input: &mut Input) -> PResult<String> {
     let _ = '"'.parse_next(input)?;
-    Ok(content.to_string())
+    let content = repeat(0.., preceded(not('"'), literal_char)).parse_next(input)?;
+    let _ = cut_err('"').parse_next(input)?;
+    Ok(content)
 }
 
-/// Parser of a char literal.
-pub fn char_lit(input: &mut &str) -> PResult<char> {
-    // TODO escape
-    let _ = '\''.parse_next(input)?;
-    let content = any.parse_next(input)?;
+/// Parser of a char literal, with escape sequence replaced.
+pub fn char_lit(input: &mut Input) -> PResult<char> {
     let _ = '\''.parse_next(input)?;
+    let content = preceded(not('\''), literal_char).parse_next(input)?;
+    let _ = cut_err('\'').parse_next(input)?;
     Ok(content)
 }
 
+/// Parser of a character in string or char literal.
+/// Escaped character must be in ASCII range, because literals are stored as bytes.
+pub fn literal_char(input: &mut Input) -> PResult<char> {
+    if opt('\\').parse_next(input)?.is_none() {
+        return any.parse_next(input);
+    }
+    let code = dispatch! { any;
+        'n' => empty.value(0x0a),
+        't' => empty.value(0x09),
+        'r' => empty.value(0x0d),
+        'a' => empty.value(0x07),
+        'b' => empty.value(0x08),
+        'f' => empty.value(0x0c),
+        'v' => empty.value(0x0b),
+        'x' => cut_err(take_while(1.., AsChar::is_hex_digit))
+            .try_map(|digits| u32::from_str_radix(digits, 16)),
+        c @ '0'..='7' => take_while(0..=2, '0'..='7')
+            .try_map(move |digits: &str| u32::from_str_radix(&format!("{}{}", c, digits), 8)),
+        c => empty.value(c as u32),
+    }
+    .parse_next(input)?;
+    char::from_u32(code)
+        .filter(char::is_ascii)
+        .ok_or_else(|| ErrMode::from_error_kind(input, ErrorKind::Verify).cut())
+}
+
 /// Parser of blank.
-pub fn blank(input: &mut &str) -> PResult<()> {
+pub fn blank(input: &mut Input) -> PResult<()> {
     (multispace0, alt((line_comment, block_comment, empty)))
         .value(())
         .parse_next(input)
 }
 
 /// Parser of blank beginning with line comment.
-pub fn line_comment(input: &mut &str) -> PResult<()> {
+pub fn line_comment(input: &mut Input) -> PResult<()> {
     ("//", opt(take_until(0.., '\n')), blank)
         .value(())
         .parse_next(input)
 }
 
 /// Parser of blank beginning with block comment.
-pub fn block_comment(input: &mut &str) -> PResult<()> {
+pub fn block_comment(input: &mut Input) -> PResult<()> {
     ("/*", cut_err(take_until(0.., "*/")), "*/", blank)
         .value(())
         .parse_next(input)
@@ -161,11 +213,11 @@ pub fn block_comment(input: &mut &str) -> PResult<()> {
 /// Parser of something wrapped in `()`.
 pub fn paren<'s, Output, InnerParser>(
     mut parser: InnerParser,
-) -> impl Parser<&'s str, Output, ContextError>
+) -> impl Parser<Input<'s>, Output, ContextError>
 where
-    InnerParser: Parser<&'s str, Output, ContextError>,
+    InnerParser: Parser<Input<'s>, Output, ContextError>,
 {
-    trace("paren", move |input: &mut &'s str| {
+    trace("paren", move |input: &mut Input<'s>| {
         let _ = token("(").parse_next(input)?;
         let output = parser.parse_next(input)?;
         let _ = token(")").parse_next(input)?;
@@ -176,11 +228,11 @@ where
 /// Parser of something wrapped in `[]`.
 pub fn bracket<'s, Output, InnerParser>(
     mut parser: InnerParser,
-) -> impl Parser<&'s str, Output, ContextError>
+) -> impl Parser<Input<'s>, Output, ContextError>
 where
-    InnerParser: Parser<&'s str, Output, ContextError>,
+    InnerParser: Parser<Input<'s>, Output, ContextError>,
 {
-    trace("bracket", move |input: &mut &'s str| {
+    trace("bracket", move |input: &mut Input<'s>| {
         let _ = token("[").parse_next(input)?;
         let output = parser.parse_next(input)?;
         let _ = token("]").parse_next(input)?;
@@ -189,27 +241,31 @@ where
 }
 
 /// Parser of something wrapped in `{}`.
+/// Once `{` is matched, a missing `}` is reported instead of backtracking,
+/// so that errors inside a block point into the block.
 pub fn curly<'s, Output, InnerParser>(
     mut parser: InnerParser,
-) -> impl Parser<&'s str, Output, ContextError>
+) -> impl Parser<Input<'s>, Output, ContextError>
 where
-    InnerParser: Parser<&'s str, Output, ContextError>,
+    InnerParser: Parser<Input<'s>, Output, ContextError>,
 {
-    trace("curly", move |input: &mut &'s str| {
+    trace("curly", move |input: &mut Input<'s>| {
         let _ = token("{").parse_next(input)?;
         let output = parser.parse_next(input)?;
-        let _ = token("}").parse_next(input)?;
+        let _ = cut_err(token("}")).parse_next(input)?;
         Ok(output)
     })
 }
 
 /// Parser of a token.
-pub fn token<'s>(mut parser: &'static str) -> impl Parser<&'s str, &'s str, ContextError> {
+pub fn token<'s>(parser: &'static str) -> impl Parser<Input<'s>, &'s str, ContextError> {
     // Get the first character and length of the token
     let head = parser.chars().next().unwrap();
     let len = parser.chars().count();
-    trace("token", move |input: &mut &'s str| {
-        let output = parser.parse_next(input)?;
+    trace("token", move |input: &mut Input<'s>| {
+        let output = parser
+            .context(StrContext::Expected(StrContextValue::StringLiteral(parser)))
+            .parse_next(input)?;
 
         // The next character after a token can not connect with the token
         if head.is_alphanum() {
@@ -269,11 +325,11 @@ pub fn token<'s>(mut parser: &'static str) -> impl Parser<&'s str, &'s str, Cont
 /// Parser of something ending with zero or more spaces.
 pub fn pad<'s, Output, InnerParser>(
     mut parser: InnerParser,
-) -> impl Parser<&'s str, Output, ContextError>
+) -> impl Parser<Input<'s>, Output, ContextError>
 where
-    InnerParser: Parser<&'s str, Output, ContextError>,
+    InnerParser: Parser<Input<'s>, Output, ContextError>,
 {
-    trace("pad", move |input: &mut &'s str| {
+    trace("pad", move |input: &mut Input<'s>| {
         let output = parser.parse_next(input)?;
         blank(input)?;
         Ok(output)
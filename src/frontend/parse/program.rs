@@ -15,12 +15,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors::FrontendError;
+use crate::frontend::diagnostic::Diagnostic;
 
 use super::*;
 
 pub fn parse(src: &str) -> Result<Program, FrontendError> {
     preceded(blank, repeat(0.., decl))
         .map(Program::new)
-        .parse(src)
-        .map_err(|err| FrontendError::ParseError(err.to_string()))
+        .parse(Located::new(src))
+        .map_err(|err| {
+            // Report where parsing stopped, with expected tokens if known
+            let message = match err.inner().to_string() {
+                msg if msg.is_empty() => "unexpected token".to_string(),
+                msg => msg,
+            };
+            let span = Span::new(err.offset(), err.offset() + 1);
+            FrontendError::ParseError(Diagnostic::new(src, span, message))
+        })
 }
@@ -18,45 +18,102 @@ use rayon::iter::Either;
 
 use super::*;
 
-pub fn box_stmt(input: &mut &str) -> PResult<Box<Stmt>> {
+pub fn box_stmt(input: &mut Input) -> PResult<Box<Stmt>> {
     stmt.map(Box::new).parse_next(input)
 }
 
-pub fn vec_stmt(input: &mut &str) -> PResult<Vec<Stmt>> {
+pub fn vec_stmt(input: &mut Input) -> PResult<Vec<Stmt>> {
     repeat(0.., stmt).parse_next(input)
 }
 
 /// Expression with semicolon.
-pub fn expr_sc(input: &mut &str) -> PResult<Expr> {
+pub fn expr_sc(input: &mut Input) -> PResult<Expr> {
     (expr, cut_err(token(";")))
         .map(|(e, _)| e)
         .parse_next(input)
 }
 
 /// Decl or Expr.
-pub fn decl_or_expr(input: &mut &str) -> PResult<Either<Decl, Expr>> {
+pub fn decl_or_expr(input: &mut Input) -> PResult<Either<Decl, Expr>> {
     alt((decl.map(Either::Left), expr_sc.map(Either::Right))).parse_next(input)
 }
 
-pub fn stmt(input: &mut &str) -> PResult<Stmt> {
+/// Expression statement without semicolon, optionally assigning to a left value.
+/// Example: `x = x + 1`
+pub fn expr_stmt(input: &mut Input) -> PResult<Stmt> {
+    spanned(expr)
+        .map(|(expr, s)| make_expr_stmt(expr, s))
+        .parse_next(input)
+}
+
+/// Make statement from expression, plain assignment at top level assigns to a left value.
+fn make_expr_stmt(expr: Expr, span: Span) -> Stmt {
+    match expr {
+        Expr::Assign(lval, None, expr, _) => Stmt::Expr(Some(*lval), *expr, span),
+        expr => Stmt::Expr(None, expr, span),
+    }
+}
+
+/// Initializer of for-loop with semicolon.
+/// Example: `int i = 0;`, `i = 0;`, `;`
+pub fn for_init(input: &mut Input) -> PResult<Stmt> {
+    alt((
+        spanned(decl).map(|(x, s)| Stmt::Decl(x, s)),
+        terminated(expr_stmt, token(";")),
+        spanned(token(";")).map(|(_, s)| Stmt::Nothing(s)),
+    ))
+    .parse_next(input)
+}
+
+/// Step of for-loop, an expression statement or nothing.
+/// Example: `i = i + 1`
+pub fn for_step(input: &mut Input) -> PResult<Stmt> {
+    alt((expr_stmt, spanned(empty).map(|(_, s)| Stmt::Nothing(s)))).parse_next(input)
+}
+
+/// Label of switch arm, None for `default`.
+/// Example: `case 1:`, `default:`
+pub fn switch_label(input: &mut Input) -> PResult<Option<Expr>> {
+    alt((
+        preceded(token("case"), cut_err(terminated(expr, token(":")))).map(Some),
+        (token("default"), cut_err(token(":"))).map(|_| None),
+    ))
+    .parse_next(input)
+}
+
+/// Arm of switch statement, a label followed by statements.
+/// Example: `case 1: x = 1; break;`
+pub fn switch_arm(input: &mut Input) -> PResult<(Option<Expr>, Vec<Stmt>)> {
+    (switch_label, vec_stmt).parse_next(input)
+}
+
+pub fn stmt(input: &mut Input) -> PResult<Stmt> {
     let disp = dispatch! { peek(any);
-        'b' => (token("break"), cut_err(token(";"))).value(Stmt::Break),
-        'c' => (token("continue"), cut_err(token(";"))).value(Stmt::Continue),
-        'i' => (token("if"), cut_err((paren(expr), box_stmt, opt((token("else"), box_stmt)))))
-            .map(|(_, (cond, pass, fail))| Stmt::If(cond, pass, fail.map_or(Stmt::Block(vec![]).into(), |(_, s)| s))),
-        'w' => (token("while"), cut_err((paren(expr), box_stmt))).map(|(_, (cond, body))| Stmt::While(cond, body)),
-        'd' => (token("do"), cut_err((box_stmt, token("while"), paren(expr), token(";"))))
-            .map(|(_, (body, _, cond, _))| Stmt::DoWhile(body, cond)),
-        'r' => (token("return"), cut_err((opt(expr), token(";"))))
-            .map(|(_, (e, _))| Stmt::Return(e)),
-        '{' => curly(cut_err(vec_stmt)).map(Stmt::Block),
+        'b' => spanned((token("break"), cut_err(token(";")))).map(|(_, s)| Stmt::Break(s)),
+        'c' => spanned((token("continue"), cut_err(token(";")))).map(|(_, s)| Stmt::Continue(s)),
+        'i' => spanned((token("if"), cut_err((paren(expr), box_stmt, opt((token("else"), box_stmt))))))
+            .map(|((_, (cond, pass, fail)), s)| {
+                let fail = fail.map_or(Stmt::Block(vec![], s).into(), |(_, s)| s);
+                Stmt::If(cond, pass, fail, s)
+            }),
+        'w' => spanned((token("while"), cut_err((paren(expr), box_stmt))))
+            .map(|((_, (cond, body)), s)| Stmt::While(cond, body, s)),
+        'd' => spanned((token("do"), cut_err((box_stmt, token("while"), paren(expr), token(";")))))
+            .map(|((_, (body, _, cond, _)), s)| Stmt::DoWhile(body, cond, s)),
+        'f' => spanned((token("for"), cut_err((token("("), for_init, opt(expr), token(";"), for_step, token(")"), box_stmt))))
+            .map(|((_, (_, init, cond, _, step, _, body)), s)| Stmt::For(init.into(), cond, step.into(), body, s)),
+        's' => spanned((token("switch"), cut_err((paren(expr), curly(repeat(0.., switch_arm))))))
+            .map(|((_, (cond, arms)), s)| Stmt::Switch(cond, arms, s)),
+        'r' => spanned((token("return"), cut_err((opt(expr), token(";")))))
+            .map(|((_, (e, _)), s)| Stmt::Return(e, s)),
+        '{' => spanned(curly(cut_err(vec_stmt))).map(|(x, s)| Stmt::Block(x, s)),
         _ => fail
     };
     alt((
         disp,
-        decl.map(Stmt::Decl),
-        (opt(terminated(expr, token("="))), expr_sc).map(|(lval, expr)| Stmt::Expr(lval, expr)),
-        token(";").value(Stmt::Nothing),
+        spanned(decl).map(|(x, s)| Stmt::Decl(x, s)),
+        spanned(expr_sc).map(|(expr, s)| make_expr_stmt(expr, s)),
+        spanned(token(";")).map(|(_, s)| Stmt::Nothing(s)),
     ))
     .parse_next(input)
 }
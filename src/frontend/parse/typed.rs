@@ -16,23 +16,38 @@
 
 use super::*;
 
-pub fn atom_type(input: &mut &str) -> PResult<Type> {
+pub fn atom_type(input: &mut Input) -> PResult<Type> {
     alt((
         token("void").value(Type::Void),
+        multiword_type,
         token("int").value(Type::Int),
         token("float").value(Type::Float),
+        token("double").value(Type::Double),
         token("string").value(Type::String),
         token("char").value(Type::Char),
         token("bool").value(Type::Bool),
-        (token("enum"), ident).map(|(_, ty)| Type::Enum(ty)),
-        (token("union"), ident).map(|(_, ty)| Type::Union(ty)),
-        (token("struct"), ident).map(|(_, ty)| Type::Struct(ty)),
+        (token("enum"), pad(ident)).map(|(_, ty)| Type::Enum(ty)),
+        (token("union"), pad(ident)).map(|(_, ty)| Type::Union(ty)),
+        (token("struct"), pad(ident)).map(|(_, ty)| Type::Struct(ty)),
+    ))
+    .parse_next(input)
+}
+
+/// Parser of integer types with multiple specifiers, like `unsigned long long int`.
+/// `long` is 64-bit, the same as `long long`.
+pub fn multiword_type(input: &mut Input) -> PResult<Type> {
+    let long = || pad(token("long"));
+    let int = || opt(pad(token("int")));
+    alt((
+        (pad(token("unsigned")), long(), opt(long()), int()).value(Type::UnsignedLong),
+        (pad(token("unsigned")), int()).value(Type::UnsignedInt),
+        (long(), opt(long()), int()).value(Type::Long),
     ))
     .parse_next(input)
 }
 
 /// Parser of an left value.
-pub fn lval(input: &mut &str) -> PResult<LVal> {
+pub fn lval(input: &mut Input) -> PResult<LVal> {
     let atom = alt((
         pad(ident).map(LVal::Var),
         paren(lval),
@@ -66,24 +81,24 @@ pub fn acc_lval(ty: Type, usage: LVal) -> TypedIdent {
 }
 
 /// Parser of a TypedIdent.
-pub fn typed_ident(input: &mut &str) -> PResult<TypedIdent> {
+pub fn typed_ident(input: &mut Input) -> PResult<TypedIdent> {
     let ty = atom_type.parse_next(input)?;
     let us = lval.parse_next(input)?;
     Ok(acc_lval(ty, us))
 }
 
 /// Parser of a single type.
-pub fn single_type(input: &mut &str) -> PResult<Type> {
+pub fn single_type(input: &mut Input) -> PResult<Type> {
     typed_ident.map(|ti| ti.ty).parse_next(input)
 }
 
 /// Parser of a box of type.
-pub fn box_type(input: &mut &str) -> PResult<Box<Type>> {
+pub fn box_type(input: &mut Input) -> PResult<Box<Type>> {
     single_type.map(Box::new).parse_next(input)
 }
 
 /// Parser of a vector of type.
-pub fn vec_typed(input: &mut &str) -> PResult<Vec<TypedIdent>> {
+pub fn vec_typed(input: &mut Input) -> PResult<Vec<TypedIdent>> {
     separated(0.., typed_ident, token(",")).parse_next(input)
 }
 
@@ -95,7 +110,7 @@ pub mod tests_typed {
     #[test]
     fn test_atom() {
         let code = "int";
-        match atom_type.parse(code) {
+        match atom_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(result, Type::Int),
             Err(err) => panic!("failed to parse {}: {}", code, err),
         }
@@ -105,7 +120,7 @@ pub mod tests_typed {
     fn test_space() {
         // Pointer to a function.
         let code = "int  (  *  )  (  int  u  )";
-        match single_type.parse(code) {
+        match single_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 Type::Pointer(Box::new(Type::Function(
@@ -120,7 +135,7 @@ pub mod tests_typed {
     #[test]
     fn test_function_pointer() {
         let code = "int (*)(int)";
-        match single_type.parse(code) {
+        match single_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 Type::Pointer(Box::new(Type::Function(
@@ -136,7 +151,7 @@ pub mod tests_typed {
     fn test_name_pointer_function() {
         // Function that returns a pointer.
         let code = "int *u(int)";
-        match single_type.parse(code) {
+        match single_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 Type::Function(
@@ -151,7 +166,7 @@ pub mod tests_typed {
     #[test]
     fn test_argname_pointer_function() {
         let code = "int *(int u)";
-        match single_type.parse(code) {
+        match single_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 Type::Function(
@@ -166,7 +181,7 @@ pub mod tests_typed {
     #[test]
     fn test_usage() {
         let code = "*(int)";
-        match lval.parse(code) {
+        match lval.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 LVal::Pointer(Box::new(LVal::Call(
@@ -181,7 +196,7 @@ pub mod tests_typed {
     #[test]
     fn test_pointer_function() {
         let code = "int *(int)";
-        match single_type.parse(code) {
+        match single_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 Type::Function(
@@ -196,12 +211,12 @@ pub mod tests_typed {
     #[test]
     fn test_array_pointer() {
         let code = "int x[][4]";
-        match single_type.parse(code) {
+        match single_type.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 Type::Pointer(Box::new(Type::Array(
                     Box::new(Type::Int),
-                    Box::new(Expr::Int(4))
+                    Box::new(Expr::Int(4, Span::default()))
                 )))
             ),
             Err(err) => panic!("failed to parse {}: {}", code, err),
@@ -213,7 +228,7 @@ pub mod tests_typed {
         // (*(*app)(f))(x) === f(x)
         // app: *(*(int -> int) -> *(int -> int))
         let code = "int (*(*app)(int (*)(int)))(int)";
-        match typed_ident.parse(code) {
+        match typed_ident.parse(Located::new(code)) {
             Ok(result) => assert_eq!(
                 result,
                 TypedIdent::new(
@@ -0,0 +1,243 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use super::macros::{Location, MacroTable};
+use super::token::{Token, TokenKind};
+
+/// Evaluate controlling expression of `#if` or `#elif`, returning whether it is non-zero.
+pub fn evaluate(tokens: Vec<Token>, macros: &MacroTable, loc: &Location) -> Result<bool, String> {
+    let tokens = replace_defined(tokens, macros)?;
+    let tokens = macros
+        .expand(tokens, loc, true)
+        .map_err(|_| "invalid macro invocation in condition".to_string())?;
+
+    // Identifiers left after expansion are zero
+    let tokens: Vec<Token> = tokens
+        .into_iter()
+        .filter(|t| !t.is_space())
+        .map(|t| match t.kind {
+            TokenKind::Ident => Token::new(TokenKind::Number, "0"),
+            _ => t,
+        })
+        .collect();
+    if tokens.is_empty() {
+        return Err("condition expected".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let value = parser.conditional()?;
+    match parser.peek() {
+        Some(token) => Err(format!("unexpected `{}` in condition", token.text)),
+        None => Ok(value != 0),
+    }
+}
+
+/// Replace `defined X` and `defined(X)` with 1 or 0, which must happen before expansion.
+fn replace_defined(tokens: Vec<Token>, macros: &MacroTable) -> Result<Vec<Token>, String> {
+    let mut out = Vec::new();
+    let mut iter = tokens.into_iter().filter(|t| !t.is_space());
+    while let Some(token) = iter.next() {
+        if !(token.is_ident() && token.text == "defined") {
+            out.push(token);
+            continue;
+        }
+        let mut name = iter.next();
+        let parened = name.as_ref().is_some_and(|t| t.is_punct("("));
+        if parened {
+            name = iter.next();
+        }
+        let Some(name) = name.filter(Token::is_ident) else {
+            return Err("macro name expected after `defined`".to_string());
+        };
+        if parened && !iter.next().is_some_and(|t| t.is_punct(")")) {
+            return Err("`)` expected after `defined`".to_string());
+        }
+        let value = if macros.is_defined(&name.text) {
+            "1"
+        } else {
+            "0"
+        };
+        out.push(Token::new(TokenKind::Number, value));
+    }
+    Ok(out)
+}
+
+/// Binary operators with precedence, larger binds tighter.
+const BINARY_OPS: [(&str, u8); 18] = [
+    ("||", 1),
+    ("&&", 2),
+    ("|", 3),
+    ("^", 4),
+    ("&", 5),
+    ("==", 6),
+    ("!=", 6),
+    ("<", 7),
+    ("<=", 7),
+    (">", 7),
+    (">=", 7),
+    ("<<", 8),
+    (">>", 8),
+    ("+", 9),
+    ("-", 9),
+    ("*", 10),
+    ("/", 10),
+    ("%", 10),
+];
+
+/// Recursive descent parser evaluating integer constant expression on the fly.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let matched = self.peek().is_some_and(|t| t.is_punct(punct));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("`{}` expected in condition", punct))
+        }
+    }
+
+    /// `a ? b : c`, lowest precedence.
+    fn conditional(&mut self) -> Result<i64, String> {
+        let cond = self.binary(1)?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.conditional()?;
+        self.expect(":")?;
+        let alt = self.conditional()?;
+        Ok(if cond != 0 { then } else { alt })
+    }
+
+    fn binary(&mut self, min_prec: u8) -> Result<i64, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let Some((op, prec)) = self
+                .peek()
+                .and_then(|t| BINARY_OPS.iter().find(|(op, _)| t.is_punct(op)).copied())
+            else {
+                return Ok(lhs);
+            };
+            if prec < min_prec {
+                return Ok(lhs);
+            }
+            self.pos += 1;
+            let rhs = self.binary(prec + 1)?;
+            lhs = match op {
+                "||" => (lhs != 0 || rhs != 0) as i64,
+                "&&" => (lhs != 0 && rhs != 0) as i64,
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "==" => (lhs == rhs) as i64,
+                "!=" => (lhs != rhs) as i64,
+                "<" => (lhs < rhs) as i64,
+                "<=" => (lhs <= rhs) as i64,
+                ">" => (lhs > rhs) as i64,
+                ">=" => (lhs >= rhs) as i64,
+                "<<" => lhs.wrapping_shl(rhs as u32),
+                ">>" => lhs.wrapping_shr(rhs as u32),
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                "/" | "%" if rhs == 0 => return Err("division by zero in condition".to_string()),
+                "/" => lhs.wrapping_div(rhs),
+                _ => lhs.wrapping_rem(rhs),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        if self.eat("!") {
+            Ok((self.unary()? == 0) as i64)
+        } else if self.eat("~") {
+            Ok(!self.unary()?)
+        } else if self.eat("-") {
+            Ok(self.unary()?.wrapping_neg())
+        } else if self.eat("+") {
+            self.unary()
+        } else if self.eat("(") {
+            let value = self.conditional()?;
+            self.expect(")")?;
+            Ok(value)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<i64, String> {
+        let Some(token) = self.peek().cloned() else {
+            return Err("unexpected end of condition".to_string());
+        };
+        self.pos += 1;
+        let value = match token.kind {
+            TokenKind::Number => parse_int(&token.text),
+            TokenKind::Literal if token.text.starts_with('\'') => parse_char(&token.text),
+            _ => None,
+        };
+        value.ok_or_else(|| format!("unexpected `{}` in condition", token.text))
+    }
+}
+
+/// Parse integer literal in decimal, octal or hexadecimal, ignoring suffixes.
+fn parse_int(text: &str) -> Option<i64> {
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let (digits, radix) = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        (hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (&digits[1..], 8)
+    } else {
+        (digits, 10)
+    };
+    u64::from_str_radix(digits, radix).ok().map(|v| v as i64)
+}
+
+/// Value of character literal like `'a'` or `'\n'`.
+fn parse_char(text: &str) -> Option<i64> {
+    let inner = text.strip_prefix('\'')?.strip_suffix('\'')?;
+    let mut chars = inner.chars();
+    let value = match (chars.next()?, chars.next()) {
+        (c, None) => c as i64,
+        ('\\', Some(escape)) => match escape {
+            'n' => 10,
+            't' => 9,
+            'r' => 13,
+            '0' => 0,
+            '\\' => 92,
+            '\'' => 39,
+            '"' => 34,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(value)
+}
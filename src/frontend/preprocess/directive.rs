@@ -0,0 +1,485 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::errors::FrontendError;
+use crate::frontend::diagnostic::Diagnostic;
+use crate::frontend::Span;
+
+use super::condition;
+use super::line_map::LineMap;
+use super::macros::{ExpandError, Location, Macro, MacroTable};
+use super::token::{strip_comments, to_text, tokenize, trim, Token, TokenKind};
+
+/// Maximum depth of nested `#include`, to stop recursive inclusion.
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+/// Header of SysY runtime library.
+const RUNTIME_HEADER: &str = "sylib.h";
+
+/// State of an `#if` group.
+struct Cond {
+    /// Line of the opening directive, for unterminated group.
+    line: usize,
+
+    /// Enclosing group is active.
+    parent_active: bool,
+
+    /// Some branch has been taken.
+    taken: bool,
+
+    /// Current branch is active.
+    active: bool,
+
+    /// `#else` has been seen.
+    seen_else: bool,
+}
+
+/// Source being preprocessed, for diagnostics.
+struct Source<'a> {
+    /// Path of file, `None` for source not read from a file.
+    path: Option<&'a Path>,
+
+    /// Original text.
+    text: &'a str,
+
+    /// Byte offset of each line start.
+    line_starts: Vec<usize>,
+}
+
+impl Source<'_> {
+    fn name(&self) -> String {
+        self.path
+            .map_or("<input>".to_string(), |path| path.display().to_string())
+    }
+
+    /// Error pointing at the whole line, starting from 1.
+    fn error(&self, line: usize, message: impl Into<String>) -> FrontendError {
+        let start = self.line_starts[line - 1];
+        let end = self.text[start..]
+            .find('\n')
+            .map_or(self.text.len(), |ix| start + ix);
+        let indent = self.text[start..end].len() - self.text[start..end].trim_start().len();
+        let mut diag = Diagnostic::new(self.text, Span::new(start + indent, end), message);
+        diag.file = self.path.map(|path| path.display().to_string());
+        FrontendError::PreprocessError(diag)
+    }
+}
+
+/// C preprocessor handling `#include`, `#define`, conditional groups and line splicing.
+/// Each output line records where it comes from in `LineMap`.
+pub struct Preprocessor {
+    /// Directories searched by `#include`, after directory of current file for quoted names.
+    include_dirs: Vec<PathBuf>,
+
+    macros: MacroTable,
+
+    /// Files with `#pragma once`.
+    once: HashSet<PathBuf>,
+
+    /// Current depth of `#include`.
+    depth: usize,
+
+    output: Vec<String>,
+    line_map: LineMap,
+}
+
+impl Preprocessor {
+    pub fn new(include_dirs: Vec<PathBuf>) -> Self {
+        let mut macros = MacroTable::default();
+
+        // Timing functions of SysY runtime, same as definitions in `sylib.h`
+        for (name, func) in [
+            ("starttime", "_sysy_starttime"),
+            ("stoptime", "_sysy_stoptime"),
+        ] {
+            let body = tokenize(&format!("{}(__LINE__)", func));
+            macros.define(
+                name,
+                Macro::Function {
+                    params: vec![],
+                    variadic: false,
+                    body,
+                },
+            );
+        }
+        Self {
+            include_dirs,
+            macros,
+            once: HashSet::new(),
+            depth: 0,
+            output: Vec::new(),
+            line_map: LineMap::default(),
+        }
+    }
+
+    /// Preprocessed text and origin of its lines.
+    pub fn finish(self) -> (String, LineMap) {
+        (self.output.join("\n"), self.line_map)
+    }
+
+    /// Read and preprocess a file.
+    pub fn process_file(&mut self, path: &Path) -> Result<(), FrontendError> {
+        let text = std::fs::read_to_string(path).map_err(|err| {
+            let mut diag = Diagnostic::new("", Span::new(0, 0), err.to_string());
+            diag.file = Some(path.display().to_string());
+            FrontendError::PreprocessError(diag)
+        })?;
+        self.process(&text, Some(path))
+    }
+
+    /// Preprocess source text, `path` is used to resolve quoted `#include` and for diagnostics.
+    pub fn process(&mut self, text: &str, path: Option<&Path>) -> Result<(), FrontendError> {
+        let src = Source {
+            path,
+            text,
+            line_starts: std::iter::once(0)
+                .chain(text.match_indices('\n').map(|(ix, _)| ix + 1))
+                .collect(),
+        };
+        let file = self
+            .line_map
+            .add_file(path.map(|path| path.display().to_string()));
+        let stripped = strip_comments(text);
+        let lines: Vec<&str> = stripped.split('\n').collect();
+        let mut conds: Vec<Cond> = Vec::new();
+
+        let mut next = 0;
+        while next < lines.len() {
+            // Splice lines ending with backslash
+            let first = next;
+            let mut logical = String::new();
+            loop {
+                let line = lines[next].trim_end_matches('\r');
+                next += 1;
+                match line.strip_suffix('\\') {
+                    Some(line) if next < lines.len() => logical += line,
+                    _ => {
+                        logical += lines[next - 1];
+                        break;
+                    }
+                }
+            }
+            let active = conds.last().map_or(true, |cond| cond.active);
+            let line_no = first + 1;
+
+            if let Some(directive) = logical.trim_start().strip_prefix('#') {
+                self.directive(directive, &src, line_no, &mut conds, active)?;
+                self.emit_blank(file, first, next);
+            } else if !active {
+                self.emit_blank(file, first, next);
+            } else {
+                // Arguments of function-like macro may continue on following lines
+                let loc_name = src.name();
+                let loc = Location {
+                    file: &loc_name,
+                    line: line_no,
+                };
+                let expanded = loop {
+                    let more = next < lines.len() && !lines[next].trim_start().starts_with('#');
+                    match self.macros.expand(tokenize(&logical), &loc, !more) {
+                        Ok(tokens) => break to_text(&tokens),
+                        Err(ExpandError::Unterminated(_)) if more => {
+                            logical.push(' ');
+                            logical += lines[next].trim_end_matches('\r');
+                            next += 1;
+                        }
+                        Err(ExpandError::Unterminated(name)) => {
+                            return Err(src.error(
+                                line_no,
+                                format!("unterminated argument list invoking macro `{}`", name),
+                            ))
+                        }
+                        Err(ExpandError::Invalid(msg)) => return Err(src.error(line_no, msg)),
+                    }
+                };
+                self.output.push(expanded);
+                self.line_map.push(file, line_no);
+                self.emit_blank(file, first + 1, next);
+            }
+        }
+        match conds.last() {
+            Some(cond) => Err(src.error(cond.line, "unterminated conditional directive")),
+            None => Ok(()),
+        }
+    }
+
+    /// Emit empty lines for original lines `from..to` (starting from 0), keeping line count.
+    fn emit_blank(&mut self, file: usize, from: usize, to: usize) {
+        for line in from..to {
+            self.output.push(String::new());
+            self.line_map.push(file, line + 1);
+        }
+    }
+
+    /// Handle directive text after `#`.
+    fn directive(
+        &mut self,
+        directive: &str,
+        src: &Source,
+        line: usize,
+        conds: &mut Vec<Cond>,
+        active: bool,
+    ) -> Result<(), FrontendError> {
+        let tokens = tokenize(directive);
+        let tokens = trim(&tokens);
+        let Some((name, rest)) = tokens.split_first() else {
+            // Null directive
+            return Ok(());
+        };
+        let rest = trim(rest);
+        let src_name = src.name();
+        let loc = Location {
+            file: &src_name,
+            line,
+        };
+        match name.text.as_str() {
+            "if" | "ifdef" | "ifndef" => {
+                let value = active
+                    && match name.text.as_str() {
+                        "if" => self.condition(rest, src, &loc)?,
+                        "ifdef" => self.macros.is_defined(&macro_name(rest, src, line)?),
+                        _ => !self.macros.is_defined(&macro_name(rest, src, line)?),
+                    };
+                conds.push(Cond {
+                    line,
+                    parent_active: active,
+                    taken: value,
+                    active: value,
+                    seen_else: false,
+                });
+            }
+            "elif" => {
+                let Some(cond) = conds.last() else {
+                    return Err(src.error(line, "`#elif` without `#if`"));
+                };
+                if cond.seen_else {
+                    return Err(src.error(line, "`#elif` after `#else`"));
+                }
+                let value = cond.parent_active && !cond.taken && self.condition(rest, src, &loc)?;
+                let cond = conds.last_mut().unwrap();
+                cond.active = value;
+                cond.taken |= value;
+            }
+            "else" => {
+                let Some(cond) = conds.last_mut() else {
+                    return Err(src.error(line, "`#else` without `#if`"));
+                };
+                if cond.seen_else {
+                    return Err(src.error(line, "`#else` after `#else`"));
+                }
+                cond.active = cond.parent_active && !cond.taken;
+                cond.taken = true;
+                cond.seen_else = true;
+            }
+            "endif" => {
+                if conds.pop().is_none() {
+                    return Err(src.error(line, "`#endif` without `#if`"));
+                }
+            }
+
+            // Other directives are ignored in inactive groups
+            _ if !active => (),
+            "define" => self.define(rest, src, line)?,
+            "undef" => {
+                let name = macro_name(rest, src, line)?;
+                self.macros.undef(&name);
+            }
+            "include" => self.include(rest, src, line, &loc)?,
+            "pragma" => {
+                if rest.first().is_some_and(|t| t.text == "once") {
+                    if let Some(path) = src.path {
+                        self.once.insert(canonical(path));
+                    }
+                }
+            }
+            "error" => return Err(src.error(line, format!("#error {}", to_text(rest)))),
+            _ => {
+                return Err(src.error(
+                    line,
+                    format!("invalid preprocessing directive `#{}`", name.text),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn condition(
+        &self,
+        rest: &[Token],
+        src: &Source,
+        loc: &Location,
+    ) -> Result<bool, FrontendError> {
+        condition::evaluate(rest.to_vec(), &self.macros, loc)
+            .map_err(|msg| src.error(loc.line, msg))
+    }
+
+    /// Parse `#define` after directive name.
+    fn define(&mut self, rest: &[Token], src: &Source, line: usize) -> Result<(), FrontendError> {
+        let name = macro_name(rest, src, line)?;
+        let rest = &rest[1..];
+
+        // Function-like macro has `(` right after name, without blanks
+        if !rest.first().is_some_and(|t| t.is_punct("(")) {
+            self.macros.define(&name, Macro::Object(collapse(rest)));
+            return Ok(());
+        }
+        let mut params = Vec::new();
+        let mut variadic = false;
+        let mut solid = rest
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, t)| !t.is_space());
+        let mut next = || {
+            solid
+                .next()
+                .ok_or_else(|| src.error(line, "missing `)` in macro parameter list"))
+        };
+        let unexpected = |t: &Token| {
+            src.error(
+                line,
+                format!("unexpected `{}` in macro parameter list", t.text),
+            )
+        };
+        let mut close = next()?;
+        if !close.1.is_punct(")") {
+            // Parameters separated by comma, `...` must be the last one
+            loop {
+                match close.1 {
+                    t if t.is_ident() => params.push(t.text.clone()),
+                    t if t.is_punct("...") => variadic = true,
+                    t => return Err(unexpected(t)),
+                }
+                close = next()?;
+                match close.1 {
+                    t if t.is_punct(")") => break,
+                    t if t.is_punct(",") && !variadic => close = next()?,
+                    t => return Err(unexpected(t)),
+                }
+            }
+        }
+        let body = collapse(&rest[close.0 + 1..]);
+
+        // `#` must be followed by a parameter, and `##` can't be at either end
+        let solid: Vec<&Token> = body.iter().filter(|t| !t.is_space()).collect();
+        let is_param = |t: &Token| {
+            t.is_ident() && (params.contains(&t.text) || variadic && t.text == "__VA_ARGS__")
+        };
+        for (ix, token) in solid.iter().enumerate() {
+            if token.is_punct("#") && !solid.get(ix + 1).is_some_and(|t| is_param(t)) {
+                return Err(src.error(line, "`#` is not followed by a macro parameter"));
+            }
+        }
+        if solid.first().is_some_and(|t| t.is_punct("##"))
+            || solid.last().is_some_and(|t| t.is_punct("##"))
+        {
+            return Err(src.error(line, "`##` can't appear at either end of macro body"));
+        }
+        self.macros.define(
+            &name,
+            Macro::Function {
+                params,
+                variadic,
+                body,
+            },
+        );
+        Ok(())
+    }
+
+    /// Handle `#include "file"` or `#include <file>`.
+    fn include(
+        &mut self,
+        rest: &[Token],
+        src: &Source,
+        line: usize,
+        loc: &Location,
+    ) -> Result<(), FrontendError> {
+        // Name may come from macro expansion
+        let mut text = to_text(rest);
+        if !text.starts_with(['"', '<']) {
+            let expanded = self
+                .macros
+                .expand(rest.to_vec(), loc, true)
+                .map_err(|_| src.error(line, "invalid macro invocation in `#include`"))?;
+            text = to_text(trim(&expanded));
+        }
+        let (name, quoted) =
+            if let Some(name) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+                (name, true)
+            } else if let Some(name) = text.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+                (name, false)
+            } else {
+                return Err(src.error(line, "`#include` expects \"FILENAME\" or <FILENAME>"));
+            };
+
+        // Quoted name is searched in directory of current file first
+        let current_dir = src.path.map_or(PathBuf::from("."), |path| {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        });
+        let found = quoted
+            .then_some(&current_dir)
+            .into_iter()
+            .chain(self.include_dirs.iter())
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file());
+        let Some(path) = found else {
+            // Runtime functions are built in, so their header is optional
+            if Path::new(name).file_name() == Some(RUNTIME_HEADER.as_ref()) {
+                return Ok(());
+            }
+            return Err(src.error(line, format!("cannot find include file `{}`", name)));
+        };
+        if self.once.contains(&canonical(&path)) {
+            return Ok(());
+        }
+        if self.depth >= MAX_INCLUDE_DEPTH {
+            return Err(src.error(line, "`#include` nested too deeply"));
+        }
+        let text =
+            std::fs::read_to_string(&path).map_err(|err| src.error(line, err.to_string()))?;
+        self.depth += 1;
+        let result = self.process(&text, Some(&path));
+        self.depth -= 1;
+        result
+    }
+}
+
+/// Name of macro as the only token.
+fn macro_name(tokens: &[Token], src: &Source, line: usize) -> Result<String, FrontendError> {
+    match tokens {
+        [token, ..] if token.is_ident() => Ok(token.text.clone()),
+        _ => Err(src.error(line, "macro name expected")),
+    }
+}
+
+/// Macro body without leading or trailing blanks, other blanks collapsed into one space.
+fn collapse(tokens: &[Token]) -> Vec<Token> {
+    trim(tokens)
+        .iter()
+        .map(|t| match t.kind {
+            TokenKind::Space => Token::new(TokenKind::Space, " "),
+            _ => t.clone(),
+        })
+        .collect()
+}
+
+/// Canonical path for `#pragma once`, falling back to the path itself.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,69 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::errors::FrontendError;
+use crate::frontend::diagnostic::Diagnostic;
+
+/// Origin of each line in preprocessed output, so that diagnostics point at original files.
+#[derive(Clone, Default, Debug)]
+pub struct LineMap {
+    /// Names of source files, `None` for source not read from a file.
+    files: Vec<Option<String>>,
+
+    /// File index and original line (starting from 1) of each output line.
+    lines: Vec<(usize, usize)>,
+}
+
+impl LineMap {
+    /// Register a source file, returning its index.
+    pub fn add_file(&mut self, name: Option<String>) -> usize {
+        self.files.push(name);
+        self.files.len() - 1
+    }
+
+    /// Record origin of the next output line.
+    pub fn push(&mut self, file: usize, line: usize) {
+        self.lines.push((file, line));
+    }
+
+    /// File name and original line of output line, both lines starting from 1.
+    pub fn lookup(&self, line: usize) -> Option<(Option<&str>, usize)> {
+        let (file, line) = *self.lines.get(line.checked_sub(1)?)?;
+        Some((self.files[file].as_deref(), line))
+    }
+
+    /// Point diagnostic on preprocessed output at original source.
+    pub fn remap(&self, diag: &mut Diagnostic) {
+        if let Some((file, line)) = self.lookup(diag.line) {
+            diag.file = file.map(str::to_string);
+            diag.line = line;
+        }
+    }
+
+    /// Remap all diagnostics in error.
+    pub fn remap_error(&self, mut err: FrontendError) -> FrontendError {
+        if let FrontendError::SemanticErrors(errs) = &mut err {
+            for err in errs.iter_mut() {
+                if let Some(diag) = err.diagnostic_mut() {
+                    self.remap(diag);
+                }
+            }
+        } else if let Some(diag) = err.diagnostic_mut() {
+            self.remap(diag);
+        }
+        err
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, VecDeque};
+
+use super::token::{tokenize, trim, Token, TokenKind};
+
+/// Macro definition, with blanks in body collapsed.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Macro {
+    Object(Vec<Token>),
+    Function {
+        params: Vec<String>,
+        /// Last parameter is `...`, referred to as `__VA_ARGS__`.
+        variadic: bool,
+        body: Vec<Token>,
+    },
+}
+
+/// Reason why expansion fails.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ExpandError {
+    /// Argument list of macro is not closed before end of input, more lines are needed.
+    Unterminated(String),
+    /// Other errors with message.
+    Invalid(String),
+}
+
+/// Position of expansion, for `__LINE__` and `__FILE__`.
+pub struct Location<'a> {
+    pub file: &'a str,
+    pub line: usize,
+}
+
+/// All macros defined so far.
+#[derive(Default)]
+pub struct MacroTable {
+    macros: HashMap<String, Macro>,
+}
+
+impl MacroTable {
+    /// Define or redefine a macro.
+    pub fn define(&mut self, name: &str, def: Macro) {
+        self.macros.insert(name.to_string(), def);
+    }
+
+    pub fn undef(&mut self, name: &str) {
+        self.macros.remove(name);
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.macros.contains_key(name) || matches!(name, "__LINE__" | "__FILE__")
+    }
+
+    /// Expand all macros in tokens, rescanning results until nothing changes.
+    /// If `complete` is false, a function-like macro name at the end may take arguments from following lines.
+    pub fn expand(
+        &self,
+        tokens: Vec<Token>,
+        loc: &Location,
+        complete: bool,
+    ) -> Result<Vec<Token>, ExpandError> {
+        let mut input: VecDeque<Token> = tokens.into();
+        let mut output = Vec::new();
+        while let Some(token) = input.pop_front() {
+            if !token.is_ident() || token.hide.contains(&token.text) {
+                output.push(token);
+                continue;
+            }
+            let name = token.text.clone();
+            let expanded = match self.macros.get(&name) {
+                Some(Macro::Object(body)) => body.clone(),
+                Some(Macro::Function {
+                    params,
+                    variadic,
+                    body,
+                }) => {
+                    // Function-like macro without argument list is an ordinary identifier
+                    let open = input.iter().position(|t| !t.is_space());
+                    match open {
+                        None if !complete => return Err(ExpandError::Unterminated(name)),
+                        Some(open) if input[open].is_punct("(") => {
+                            input.drain(..=open);
+                        }
+                        _ => {
+                            output.push(token);
+                            continue;
+                        }
+                    }
+                    let args = collect_args(&mut input, &name)?;
+                    let args = match_args(args, params, *variadic, &name)?;
+                    self.substitute(body, params, *variadic, &args, loc)?
+                }
+                None => match name.as_str() {
+                    "__LINE__" => vec![Token::new(TokenKind::Number, loc.line.to_string())],
+                    "__FILE__" => vec![Token::new(TokenKind::Literal, quote(loc.file))],
+                    _ => {
+                        output.push(token);
+                        continue;
+                    }
+                },
+            };
+
+            // Result is hidden from this macro and everything hiding the macro name,
+            // and padded with blanks so that it never sticks to neighbouring tokens
+            let padding = Token::new(TokenKind::Space, " ");
+            input.push_front(padding.clone());
+            for mut result in expanded.into_iter().rev() {
+                for hidden in token.hide.iter().chain(Some(&name)) {
+                    result = result.hidden_from(hidden);
+                }
+                input.push_front(result);
+            }
+            input.push_front(padding);
+        }
+        Ok(output)
+    }
+
+    /// Replace parameters in macro body with arguments, handling `#` and `##`.
+    fn substitute(
+        &self,
+        body: &[Token],
+        params: &[String],
+        variadic: bool,
+        args: &[Vec<Token>],
+        loc: &Location,
+    ) -> Result<Vec<Token>, ExpandError> {
+        let param_index = |token: &Token| -> Option<usize> {
+            if !token.is_ident() {
+                return None;
+            }
+            if variadic && token.text == "__VA_ARGS__" {
+                return Some(params.len());
+            }
+            params.iter().position(|p| *p == token.text)
+        };
+        let next_solid = |ix: usize| {
+            body[ix..]
+                .iter()
+                .position(|t| !t.is_space())
+                .map(|n| ix + n)
+        };
+
+        let mut out: Vec<Token> = Vec::new();
+        let mut ix = 0;
+        while ix < body.len() {
+            let token = &body[ix];
+            if token.is_punct("#") {
+                // Stringify, definition guarantees a parameter follows
+                let param_ix = next_solid(ix + 1).unwrap();
+                let arg = &args[param_index(&body[param_ix]).unwrap()];
+                out.push(Token::new(TokenKind::Literal, stringify(arg)));
+                ix = param_ix + 1;
+            } else if token.is_punct("##") {
+                // Paste last token with the first token of right operand
+                while out.last().is_some_and(Token::is_space) {
+                    out.pop();
+                }
+                let rhs_ix = next_solid(ix + 1).unwrap();
+                let rhs = match param_index(&body[rhs_ix]) {
+                    Some(arg_ix) => trim(&args[arg_ix]).to_vec(),
+                    None => vec![body[rhs_ix].clone()],
+                };
+                match (out.pop(), rhs.split_first()) {
+                    (Some(lhs), Some((first, rest))) => {
+                        out.extend(tokenize(&format!("{}{}", lhs.text, first.text)));
+                        out.extend_from_slice(rest);
+                    }
+                    (lhs, _) => {
+                        out.extend(lhs);
+                        out.extend(rhs);
+                    }
+                }
+                ix = rhs_ix + 1;
+            } else if let Some(arg_ix) = param_index(token) {
+                // Operand of `##` is not expanded, otherwise argument is fully expanded first
+                let before_paste = next_solid(ix + 1).is_some_and(|n| body[n].is_punct("##"));
+                if before_paste {
+                    out.extend_from_slice(trim(&args[arg_ix]));
+                } else {
+                    out.extend(self.expand(args[arg_ix].clone(), loc, true)?);
+                }
+                ix += 1;
+            } else {
+                out.push(token.clone());
+                ix += 1;
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Collect arguments after `(` until the matching `)`, split by top-level commas.
+fn collect_args(input: &mut VecDeque<Token>, name: &str) -> Result<Vec<Vec<Token>>, ExpandError> {
+    let mut args = vec![Vec::new()];
+    let mut depth = 0;
+    while let Some(token) = input.pop_front() {
+        if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            if depth == 0 {
+                return Ok(args);
+            }
+            depth -= 1;
+        } else if token.is_punct(",") && depth == 0 {
+            args.push(Vec::new());
+            continue;
+        }
+        args.last_mut().unwrap().push(token);
+    }
+    Err(ExpandError::Unterminated(name.to_string()))
+}
+
+/// Check argument count, merging extra arguments into `__VA_ARGS__`.
+fn match_args(
+    mut args: Vec<Vec<Token>>,
+    params: &[String],
+    variadic: bool,
+    name: &str,
+) -> Result<Vec<Vec<Token>>, ExpandError> {
+    // `f()` passes no argument rather than one empty argument
+    if params.is_empty() && !variadic && args.len() == 1 && trim(&args[0]).is_empty() {
+        args.clear();
+    }
+    if variadic && args.len() > params.len() {
+        let rest = args.split_off(params.len());
+        let mut va_args = Vec::new();
+        for (ix, arg) in rest.into_iter().enumerate() {
+            if ix > 0 {
+                va_args.push(Token::new(TokenKind::Punct, ","));
+            }
+            va_args.extend(arg);
+        }
+        args.push(va_args);
+    } else if variadic && args.len() == params.len() {
+        args.push(Vec::new());
+    }
+    let expected = params.len() + variadic as usize;
+    if args.len() != expected {
+        return Err(ExpandError::Invalid(format!(
+            "macro `{}` takes {} argument(s) but {} were supplied",
+            name,
+            params.len(),
+            args.len()
+        )));
+    }
+    Ok(args)
+}
+
+/// Quote text as string literal.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Turn argument into string literal for `#`, blanks are collapsed into one space.
+fn stringify(arg: &[Token]) -> String {
+    let mut text = String::new();
+    for token in trim(arg) {
+        match token.kind {
+            TokenKind::Space => text.push(' '),
+            TokenKind::Literal => text += &token.text.replace('\\', "\\\\").replace('"', "\\\""),
+            _ => text += &token.text,
+        }
+    }
+    format!("\"{}\"", text)
+}
@@ -14,4 +14,33 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod condition;
+pub mod directive;
+pub mod line_map;
+pub mod macros;
 pub mod timing;
+pub mod token;
+
+use std::path::{Path, PathBuf};
+
+pub use directive::Preprocessor;
+pub use line_map::LineMap;
+
+use crate::errors::FrontendError;
+
+/// Preprocess source not read from a file, quoted `#include` is searched in current directory.
+pub fn preprocess(src: &str) -> Result<(String, LineMap), FrontendError> {
+    let mut preprocessor = Preprocessor::new(Vec::new());
+    preprocessor.process(src, None)?;
+    Ok(preprocessor.finish())
+}
+
+/// Preprocess a file, searching `include_dirs` for `#include`.
+pub fn preprocess_file(
+    path: &Path,
+    include_dirs: Vec<PathBuf>,
+) -> Result<(String, LineMap), FrontendError> {
+    let mut preprocessor = Preprocessor::new(include_dirs);
+    preprocessor.process_file(path)?;
+    Ok(preprocessor.finish())
+}
@@ -0,0 +1,189 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::rc::Rc;
+
+/// Kind of preprocessing token.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+    Ident,
+    Number,
+    /// String or character literal, with quotes.
+    Literal,
+    Punct,
+    /// Run of blanks, kept so that expanded text looks like the source.
+    Space,
+}
+
+/// Preprocessing token.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+
+    /// Macros that must not expand this token again, see "hide set" in Prosser's algorithm.
+    pub hide: Rc<Vec<String>>,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+            hide: Rc::default(),
+        }
+    }
+
+    pub fn is_space(&self) -> bool {
+        self.kind == TokenKind::Space
+    }
+
+    pub fn is_punct(&self, punct: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == punct
+    }
+
+    pub fn is_ident(&self) -> bool {
+        self.kind == TokenKind::Ident
+    }
+
+    /// Copy of the token that is additionally hidden from macro `name`.
+    pub fn hidden_from(&self, name: &str) -> Self {
+        let mut token = self.clone();
+        if !token.hide.iter().any(|hidden| hidden == name) {
+            let mut hide = token.hide.as_ref().clone();
+            hide.push(name.to_string());
+            token.hide = Rc::new(hide);
+        }
+        token
+    }
+}
+
+/// Punctuators longer than one character, longest first.
+const PUNCTS: [&str; 22] = [
+    "...", "<<=", ">>=", "##", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "++", "--", "->",
+    "+=", "-=", "*=", "/=", "%=", "&=", "|=",
+];
+
+/// Split one line into preprocessing tokens.
+/// Comments must have been removed, unknown characters become single punctuators.
+pub fn tokenize(line: &str) -> Vec<Token> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let kind = if c.is_whitespace() {
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            TokenKind::Space
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            TokenKind::Ident
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            // pp-number, exponent sign belongs to the number
+            i += 1;
+            while i < chars.len() {
+                if matches!(chars[i], 'e' | 'E' | 'p' | 'P')
+                    && matches!(chars.get(i + 1), Some('+' | '-'))
+                {
+                    i += 2;
+                } else if chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.' {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            TokenKind::Number
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            TokenKind::Literal
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            i += PUNCTS
+                .iter()
+                .find(|punct| rest.starts_with(**punct))
+                .map_or(1, |punct| punct.len());
+            TokenKind::Punct
+        };
+        tokens.push(Token::new(kind, chars[start..i].iter().collect::<String>()));
+    }
+    tokens
+}
+
+/// Concatenate tokens back into source text.
+pub fn to_text(tokens: &[Token]) -> String {
+    tokens.iter().map(|token| token.text.as_str()).collect()
+}
+
+/// Tokens without leading and trailing blanks.
+pub fn trim(tokens: &[Token]) -> &[Token] {
+    let start = tokens
+        .iter()
+        .position(|t| !t.is_space())
+        .unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(|t| !t.is_space())
+        .map_or(start, |ix| ix + 1);
+    &tokens[start..end]
+}
+
+/// Replace comments with blanks, keeping byte offsets and line breaks unchanged.
+pub fn strip_comments(src: &str) -> String {
+    let bytes = src.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                // Literals end at the closing quote or line end
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = src[i + 2..].find("*/").map_or(bytes.len(), |ix| i + ix + 4);
+                for b in out[i..end].iter_mut() {
+                    if *b != b'\n' {
+                        *b = b' ';
+                    }
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    // Only whole comments are replaced, and they are replaced byte by byte with ASCII
+    String::from_utf8(out).unwrap()
+}
@@ -0,0 +1,367 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use crate::errors::FrontendError;
+use crate::frontend::diagnostic::Diagnostic;
+use crate::frontend::{Decl, Expr, Program, Span, Stmt, Type, UnaryOp};
+use crate::utils::frame_map::FrameMap;
+
+use super::library::LIBRARY_FUNCTIONS;
+
+/// Environment of variables in scope, mapping name to whether it is constant.
+type VarEnv<'a> = FrameMap<'a, String, bool>;
+
+/// Jump statements allowed at current position.
+#[derive(Clone, Copy, Default)]
+struct JumpScope {
+    /// Inside a loop or switch.
+    can_break: bool,
+
+    /// Inside a loop.
+    can_continue: bool,
+}
+
+impl JumpScope {
+    const LOOP: JumpScope = JumpScope {
+        can_break: true,
+        can_continue: true,
+    };
+}
+
+/// Walks the program and collects semantic errors instead of stopping at the first one.
+pub struct Checker<'a> {
+    /// Source code for diagnostics.
+    src: &'a str,
+
+    /// Argument count of each function, `None` for variable arguments.
+    /// Functions are visible everywhere, like in `middle::irgen`.
+    funcs: HashMap<String, Option<usize>>,
+
+    /// Errors found so far.
+    errors: Vec<FrontendError>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(src: &'a str) -> Self {
+        let funcs = LIBRARY_FUNCTIONS
+            .iter()
+            .map(|(name, arity)| (name.to_string(), *arity))
+            .collect();
+        Self {
+            src,
+            funcs,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Check the whole program, returning all errors found.
+    pub fn check_program(mut self, program: &Program) -> Vec<FrontendError> {
+        // Declare all functions first, so that they can be called before definition
+        for decl in program.module.iter() {
+            self.declare_func(decl);
+        }
+
+        // Check declarations in order, global variables are visible after declaration
+        let mut env = VarEnv::new();
+        for decl in program.module.iter() {
+            self.check_decl(decl, &mut env);
+        }
+        self.errors
+    }
+
+    /// Record argument count of functions in declaration.
+    fn declare_func(&mut self, decl: &Decl) {
+        match decl {
+            Decl::Func(Type::Function(_, params), id, _, _) => {
+                self.funcs.insert(id.clone(), Some(params.len()));
+            }
+            Decl::Stack(decls, _) => {
+                for decl in decls {
+                    self.declare_func(decl);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Create a diagnostic pointing to `span`.
+    fn diagnose(&self, span: Span, message: String) -> Diagnostic {
+        Diagnostic::new(self.src, span, message)
+    }
+
+    fn check_decl(&mut self, decl: &Decl, env: &mut VarEnv) {
+        match decl {
+            Decl::Const(ty, id, expr, _) => {
+                // Constant is not visible in its own initializer
+                self.check_type(ty, env);
+                if let Some(expr) = expr {
+                    self.check_expr(expr, env);
+                }
+                env.insert(id.clone(), true);
+            }
+            Decl::Var(ty, id, expr, _) => {
+                // Variable is visible in its own initializer, like in C
+                self.check_type(ty, env);
+                env.insert(id.clone(), false);
+                if let Some(expr) = expr {
+                    self.check_expr(expr, env);
+                }
+            }
+            Decl::Stack(decls, _) => {
+                for decl in decls {
+                    self.check_decl(decl, env);
+                }
+            }
+            Decl::Func(ty, _, body, _) => {
+                // Parameters are in a scope enclosing function body
+                let mut inner_env = env.branch();
+                if let Type::Function(ret, params) = ty {
+                    self.check_type(ret, &inner_env);
+                    for param in params {
+                        self.check_type(&param.ty, &inner_env);
+                        if let Some(id) = &param.id {
+                            inner_env.insert(id.clone(), false);
+                        }
+                    }
+                }
+                if let Some(body) = body {
+                    self.check_stmt(body, &mut inner_env, JumpScope::default());
+                }
+            }
+            Decl::Struct(_, fields, _) | Decl::Union(_, fields, _) => {
+                for field in fields {
+                    self.check_type(&field.ty, env);
+                }
+            }
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt, env: &mut VarEnv, jump: JumpScope) {
+        match stmt {
+            Stmt::Nothing(_) => (),
+            Stmt::Decl(decl, _) => self.check_decl(decl, env),
+            Stmt::Expr(lhs, rhs, _) => {
+                if let Some(lhs) = lhs {
+                    self.check_expr(lhs, env);
+                    self.check_assignable(lhs, env);
+                }
+                self.check_expr(rhs, env);
+            }
+            Stmt::If(cond, then, alt, _) => {
+                self.check_expr(cond, env);
+                self.check_stmt(then, env, jump);
+                self.check_stmt(alt, env, jump);
+            }
+            Stmt::While(cond, body, _) => {
+                self.check_expr(cond, env);
+                self.check_stmt(body, env, JumpScope::LOOP);
+            }
+            Stmt::DoWhile(body, cond, _) => {
+                self.check_stmt(body, env, JumpScope::LOOP);
+                self.check_expr(cond, env);
+            }
+            Stmt::For(init, cond, step, body, _) => {
+                // Declaration in initializer is only visible in the loop
+                let mut inner_env = env.branch();
+                self.check_stmt(init, &mut inner_env, jump);
+                if let Some(cond) = cond {
+                    self.check_expr(cond, &inner_env);
+                }
+                self.check_stmt(step, &mut inner_env, jump);
+                self.check_stmt(body, &mut inner_env, JumpScope::LOOP);
+            }
+            Stmt::Switch(cond, arms, _) => {
+                // All arms share the same scope, like in C
+                self.check_expr(cond, env);
+                let mut inner_env = env.branch();
+                let jump = JumpScope {
+                    can_break: true,
+                    ..jump
+                };
+                for (label, stmts) in arms {
+                    if let Some(label) = label {
+                        self.check_expr(label, &inner_env);
+                    }
+                    for stmt in stmts {
+                        self.check_stmt(stmt, &mut inner_env, jump);
+                    }
+                }
+            }
+            Stmt::Break(span) => {
+                if !jump.can_break {
+                    let message = "`break` outside of a loop or switch".to_string();
+                    let diag = self.diagnose(*span, message);
+                    self.errors.push(FrontendError::BreakOutsideLoop(diag));
+                }
+            }
+            Stmt::Continue(span) => {
+                if !jump.can_continue {
+                    let diag = self.diagnose(*span, "`continue` outside of a loop".to_string());
+                    self.errors.push(FrontendError::ContinueOutsideLoop(diag));
+                }
+            }
+            Stmt::Return(expr, _) => {
+                if let Some(expr) = expr {
+                    self.check_expr(expr, env);
+                }
+            }
+            Stmt::Block(stmts, _) => {
+                let mut inner_env = env.branch();
+                for stmt in stmts {
+                    self.check_stmt(stmt, &mut inner_env, jump);
+                }
+            }
+        }
+    }
+
+    /// Report assignment to constant.
+    /// Undeclared variables are already reported when checking `lhs` as expression.
+    fn check_assignable(&mut self, lhs: &Expr, env: &VarEnv) {
+        let mut root = lhs;
+        while let Expr::Index(inner, _, _) | Expr::Field(inner, _, _) = root {
+            root = inner;
+        }
+        if let Expr::Var(id, _) = root {
+            if let Some(true) = env.get(id) {
+                let message = format!("cannot assign to constant `{}`", id);
+                let diag = self.diagnose(lhs.span(), message);
+                self.errors
+                    .push(FrontendError::AssignToConst(id.clone(), diag));
+            }
+        }
+    }
+
+    fn check_expr(&mut self, expr: &Expr, env: &VarEnv) {
+        match expr {
+            Expr::Var(id, span) => {
+                if env.get(id).is_none() {
+                    let message = format!("cannot find value `{}` in this scope", id);
+                    let diag = self.diagnose(*span, message);
+                    self.errors
+                        .push(FrontendError::UndeclaredIdentifier(id.clone(), diag));
+                }
+            }
+            Expr::Array(exprs, _) => {
+                for expr in exprs {
+                    self.check_expr(expr, env);
+                }
+            }
+            Expr::Index(arr, ix, _) => {
+                self.check_expr(arr, env);
+                self.check_expr(ix, env);
+            }
+            Expr::Field(inner, _, _) | Expr::Arrow(inner, _, _) => self.check_expr(inner, env),
+            Expr::Call(func, args, span) => {
+                if let Expr::Var(id, id_span) = func.as_ref() {
+                    self.check_call(id, args.len(), *id_span, *span);
+                } else {
+                    self.check_expr(func, env);
+                }
+                for arg in args {
+                    self.check_expr(arg, env);
+                }
+            }
+            Expr::Unary(UnaryOp::AddrOf, inner, span) => {
+                self.check_expr(inner, env);
+                if !is_lvalue(inner) {
+                    let message = "cannot take address of rvalue".to_string();
+                    let diag = self.diagnose(*span, message);
+                    self.errors.push(FrontendError::AddressOfRvalue(diag));
+                }
+            }
+            Expr::Unary(
+                UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec,
+                inner,
+                _,
+            ) => {
+                self.check_expr(inner, env);
+                self.check_assignable(inner, env);
+            }
+            Expr::Unary(_, expr, _) => self.check_expr(expr, env),
+            Expr::Binary(head, tail, _) => {
+                self.check_expr(head, env);
+                for (_, expr) in tail {
+                    self.check_expr(expr, env);
+                }
+            }
+            Expr::Assign(lhs, _, rhs, _) => {
+                self.check_expr(lhs, env);
+                self.check_assignable(lhs, env);
+                self.check_expr(rhs, env);
+            }
+            Expr::Zero(ty, _) => self.check_type(ty, env),
+            Expr::Int(..) | Expr::Float(..) | Expr::String(..) | Expr::Bool(..) => (),
+        }
+    }
+
+    /// Check that function `id` exists and accepts `count` arguments.
+    fn check_call(&mut self, id: &str, count: usize, id_span: Span, call_span: Span) {
+        match self.funcs.get(id) {
+            None => {
+                let message = format!("cannot find function `{}` in this scope", id);
+                let diag = self.diagnose(id_span, message);
+                self.errors
+                    .push(FrontendError::UndeclaredIdentifier(id.to_string(), diag));
+            }
+            Some(Some(expected)) if *expected != count => {
+                let message = format!(
+                    "function `{}` takes {} argument(s) but {} were supplied",
+                    id, expected, count
+                );
+                let diag = self.diagnose(call_span, message);
+                self.errors.push(FrontendError::ArgumentCountMismatch(
+                    id.to_string(),
+                    *expected,
+                    count,
+                    diag,
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    /// Check expressions in type, for example array size.
+    fn check_type(&mut self, ty: &Type, env: &VarEnv) {
+        match ty {
+            Type::Pointer(ty) => self.check_type(ty, env),
+            Type::Array(ty, size) => {
+                self.check_type(ty, env);
+                self.check_expr(size, env);
+            }
+            Type::Function(ret, params) => {
+                self.check_type(ret, env);
+                for param in params {
+                    self.check_type(&param.ty, env);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Check if expression refers to an object in memory.
+fn is_lvalue(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Var(..)
+            | Expr::Index(..)
+            | Expr::Field(..)
+            | Expr::Arrow(..)
+            | Expr::Unary(UnaryOp::Deref, _, _)
+    )
+}
@@ -0,0 +1,36 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+/// Library functions available to every program, with their argument count.
+/// `None` means the function takes variable arguments.
+/// Keep in sync with `middle::irgen::gen_library_function`.
+pub const LIBRARY_FUNCTIONS: [(&str, Option<usize>); 15] = [
+    ("getint", Some(0)),
+    ("getch", Some(0)),
+    ("getfloat", Some(0)),
+    ("putint", Some(1)),
+    ("putch", Some(1)),
+    ("putfloat", Some(1)),
+    ("getarray", Some(1)),
+    ("getfarray", Some(1)),
+    ("putarray", Some(2)),
+    ("putfarray", Some(2)),
+    ("_sysy_starttime", Some(1)),
+    ("_sysy_stoptime", Some(1)),
+    ("thrd_create", Some(1)),
+    ("thrd_join", Some(0)),
+    ("putf", None),
+];
@@ -0,0 +1,35 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod checker;
+pub mod library;
+
+use crate::errors::FrontendError;
+
+use super::Program;
+use checker::Checker;
+
+/// Check the program for semantic errors before it is translated to middle IR.
+/// All errors found are reported at once in `FrontendError::SemanticErrors`.
+/// `src` is the source code that `program` was parsed from, used for diagnostics.
+pub fn check(program: &Program, src: &str) -> Result<(), FrontendError> {
+    let errors = Checker::new(src).check_program(program);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(FrontendError::SemanticErrors(errors))
+    }
+}
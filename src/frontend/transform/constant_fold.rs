@@ -20,44 +20,41 @@ use anyhow::{anyhow, Context, Result};
 
 use crate::{
     context,
-    frontend::{BinaryOp, Decl, Expr, Program, Stmt, Type, TypedIdent, UnaryOp},
+    frontend::{BinaryOp, Decl, Expr, Program, Span, Stmt, Type, TypedIdent, UnaryOp},
     utils::frame_map::FrameMap,
 };
 
 use super::reshape_array::{reshape_array, reshape_const_array};
 
+/// Environment of struct and union definitions, mapping tag to fields.
+type RecordEnv<'a> = FrameMap<'a, String, Vec<TypedIdent>>;
+
 pub fn optimize_program(program: &mut Program) -> Result<()> {
     let mut env = FrameMap::new();
+    let mut records = RecordEnv::new();
     for decl in program.module.iter_mut() {
-        fold_decl(decl, &mut env, true)?;
+        fold_decl(decl, &mut env, &mut records, true)?;
     }
     Ok(())
 }
 
 /// Fold constant expression in declaration into constant.
-fn fold_decl(decl: &mut Decl, env: &mut FrameMap<String, Expr>, is_global: bool) -> Result<()> {
+fn fold_decl(
+    decl: &mut Decl,
+    env: &mut FrameMap<String, Expr>,
+    records: &mut RecordEnv,
+    is_global: bool,
+) -> Result<()> {
     match decl {
-        Decl::Const(ty, id, expr) => {
+        Decl::Const(ty, id, expr, _) => {
             // Fold type
             *ty = get_folded_type(ty, env)?;
 
-            // Calculate folded initializer
-            let mut folded: Expr;
-            match expr {
-                Some(expr) => {
-                    // Calculate from given initializer
-                    folded = get_folded_expr(expr, env, ty)?;
-
-                    // Constant array can be malformed, reshape it
-                    if let Expr::Array(arr) = folded {
-                        folded = reshape_const_array(&mut VecDeque::from(arr), ty)?;
-                    }
-                }
-                None => {
-                    // Use default initializer
-                    folded = ty.default_initializer()?;
-                }
-            }
+            // Calculate folded initializer, or use default initializer
+            let folded = match expr {
+                Some(expr) => get_folded_init(expr, env, records, ty)?,
+                None => ty.default_initializer()?,
+            };
 
             // Update expression to folded
             *expr = Some(folded.clone());
@@ -65,47 +62,42 @@ fn fold_decl(decl: &mut Decl, env: &mut FrameMap<String, Expr>, is_global: bool)
             // Insert folded expression to environment
             env.insert(id.clone(), folded);
         }
-        Decl::Var(ty, _, expr) => {
+        Decl::Var(ty, _, expr, _) => {
             // Fold type
             *ty = get_folded_type(ty, env)?;
 
             // If variable is global, initializer should be constant
             if is_global {
-                // Calculate folded initializer
-                let mut folded: Expr;
-                match expr {
-                    Some(expr) => {
-                        // Calculate from given initializer
-                        folded = get_folded_expr(expr, env, ty)?;
-
-                        // Constant array can be malformed, reshape it
-                        if let Expr::Array(arr) = folded {
-                            folded = reshape_const_array(&mut VecDeque::from(arr), ty)?;
-                        }
-                    }
-                    None => {
-                        // Use default initializer
-                        folded = ty.default_initializer()?;
-                    }
-                }
+                // Calculate folded initializer, or use default initializer
+                let folded = match expr {
+                    Some(expr) => get_folded_init(expr, env, records, ty)?,
+                    None => ty.default_initializer()?,
+                };
 
                 // Update expression to folded
                 *expr = Some(folded.clone());
-            } else {
+            } else if let Type::Array(..) = ty {
                 // Value array can be malformed, reshape it
-                if let Some(Expr::Array(arr)) = expr {
+                if let Some(Expr::Array(arr, _)) = expr {
                     *expr = Some(reshape_array(&mut VecDeque::from(arr.clone()), ty)?);
                 }
             }
         }
-        Decl::Stack(vec) => {
+        Decl::Stack(vec, _) => {
             for decl in vec {
-                fold_decl(decl, env, is_global)?;
+                fold_decl(decl, env, records, is_global)?;
             }
         }
-        Decl::Func(ty, _, Some(stmt)) => {
+        Decl::Func(ty, _, Some(stmt), _) => {
             *ty = get_folded_type(ty, env)?;
-            fold_stmt(stmt, &mut env.branch())?;
+            fold_stmt(stmt, &mut env.branch(), &mut records.branch())?;
+        }
+        Decl::Struct(id, fields, _) | Decl::Union(id, fields, _) => {
+            // Fold field types, and record fields for initializers
+            for field in fields.iter_mut() {
+                field.ty = get_folded_type(&field.ty, env)?;
+            }
+            records.insert(id.clone(), fields.clone());
         }
         _ => (),
     }
@@ -113,21 +105,46 @@ fn fold_decl(decl: &mut Decl, env: &mut FrameMap<String, Expr>, is_global: bool)
 }
 
 /// Fold constant expression in statement into constant.
-fn fold_stmt(stmt: &mut Stmt, env: &mut FrameMap<String, Expr>) -> Result<()> {
+fn fold_stmt(
+    stmt: &mut Stmt,
+    env: &mut FrameMap<String, Expr>,
+    records: &mut RecordEnv,
+) -> Result<()> {
     match stmt {
-        Stmt::Decl(decl) => fold_decl(decl, env, false)?,
-        Stmt::Block(vec) => {
+        Stmt::Decl(decl, _) => fold_decl(decl, env, records, false)?,
+        Stmt::Block(vec, _) => {
             let mut inner_env = env.branch();
+            let mut inner_records = records.branch();
             for stmt in vec {
-                fold_stmt(stmt, &mut inner_env)?;
+                fold_stmt(stmt, &mut inner_env, &mut inner_records)?;
             }
         }
-        Stmt::If(_, a, b) => {
-            fold_stmt(a, env)?;
-            fold_stmt(b, env)?;
+        Stmt::If(_, a, b, _) => {
+            fold_stmt(a, env, records)?;
+            fold_stmt(b, env, records)?;
+        }
+        Stmt::While(_, a, _) => fold_stmt(a, env, records)?,
+        Stmt::DoWhile(a, _, _) => fold_stmt(a, env, records)?,
+        Stmt::For(init, _, _, body, _) => {
+            // Declaration in initializer is only visible in the loop
+            let mut inner_env = env.branch();
+            let mut inner_records = records.branch();
+            fold_stmt(init, &mut inner_env, &mut inner_records)?;
+            fold_stmt(body, &mut inner_env, &mut inner_records)?;
+        }
+        Stmt::Switch(_, arms, _) => {
+            // All arms share the same scope, case labels must be constant
+            let mut inner_env = env.branch();
+            let mut inner_records = records.branch();
+            for (label, stmts) in arms {
+                if let Some(label) = label {
+                    *label = Expr::Int(get_folded_i32(label, &inner_env)?, label.span());
+                }
+                for stmt in stmts {
+                    fold_stmt(stmt, &mut inner_env, &mut inner_records)?;
+                }
+            }
         }
-        Stmt::While(_, a) => fold_stmt(a, env)?,
-        Stmt::DoWhile(a, _) => fold_stmt(a, env)?,
         _ => (),
     }
     Ok(())
@@ -136,16 +153,16 @@ fn fold_stmt(stmt: &mut Stmt, env: &mut FrameMap<String, Expr>) -> Result<()> {
 impl Expr {
     pub fn to_i32(&self) -> Result<i32> {
         match self {
-            Expr::Int(x) => Ok(*x),
-            Expr::Float(x) => Ok(*x as i32),
+            Expr::Int(x, _) => Ok(*x),
+            Expr::Float(x, _) => Ok(*x as i32),
             _ => Err(anyhow!("Cannot cast to i32")),
         }
     }
 
     pub fn to_f32(&self) -> Result<f32> {
         match self {
-            Expr::Int(x) => Ok(*x as f32),
-            Expr::Float(x) => Ok(*x),
+            Expr::Int(x, _) => Ok(*x as f32),
+            Expr::Float(x, _) => Ok(*x),
             _ => Err(anyhow!("Cannot cast to f32")),
         }
     }
@@ -153,19 +170,19 @@ impl Expr {
 
 impl From<i32> for Expr {
     fn from(i: i32) -> Self {
-        Self::Int(i)
+        Self::Int(i, Span::default())
     }
 }
 
 impl From<f32> for Expr {
     fn from(fl: f32) -> Self {
-        Self::Float(fl)
+        Self::Float(fl, Span::default())
     }
 }
 
 impl From<bool> for Expr {
     fn from(b: bool) -> Self {
-        Self::Bool(b)
+        Self::Bool(b, Span::default())
     }
 }
 
@@ -176,7 +193,7 @@ fn get_folded_type(ty: &Type, env: &FrameMap<String, Expr>) -> Result<Type> {
         Type::Array(element_type, size) => {
             let size = get_folded_i32(size, env)?;
             let element_type = get_folded_type(element_type, env)?;
-            Ok(Type::Array(element_type.into(), Expr::Int(size).into()))
+            Ok(Type::Array(element_type.into(), Expr::from(size).into()))
         }
         Type::Function(ret, params) => {
             // Fold return type
@@ -195,6 +212,57 @@ fn get_folded_type(ty: &Type, env: &FrameMap<String, Expr>) -> Result<Type> {
     }
 }
 
+/// Fold an initializer to constant.
+/// Arrays are reshaped, and struct or union fields are folded with their own types.
+fn get_folded_init(
+    expr: &Expr,
+    env: &FrameMap<String, Expr>,
+    records: &RecordEnv,
+    expr_type: &Type,
+) -> Result<Expr> {
+    match (expr_type, expr) {
+        (Type::Struct(id) | Type::Union(id), Expr::Array(arr, span)) => {
+            let Some(fields) = records.get(id) else {
+                return Err(anyhow!("`{}` is not defined", id)).with_context(|| context!());
+            };
+
+            // Union is initialized through its first member
+            let count = match expr_type {
+                Type::Union(_) => fields.len().min(1),
+                _ => fields.len(),
+            };
+            if arr.len() > count {
+                return Err(anyhow!("too many initializers for `{}`", id))
+                    .with_context(|| context!());
+            }
+            arr.iter()
+                .zip(fields.iter())
+                .map(|(x, field)| get_folded_init(x, env, records, &field.ty))
+                .collect::<Result<_>>()
+                .map(|arr| Expr::Array(arr, *span))
+        }
+        (Type::Array(element_type, _), Expr::String(..)) if **element_type == Type::Char => {
+            // String literal initializes a char array as a whole
+            Ok(expr.clone())
+        }
+        (Type::Array(element_type, _), Expr::Array(arr, _)) if element_type.has_record() => {
+            // Each element is initialized separately, braces can not be elided
+            let folded = arr
+                .iter()
+                .map(|x| get_folded_init(x, env, records, element_type))
+                .collect::<Result<Vec<_>>>()?;
+            reshape_const_array(&mut VecDeque::from(folded), expr_type)
+        }
+        _ => {
+            // Constant array can be malformed, reshape it
+            match get_folded_expr(expr, env, expr_type)? {
+                Expr::Array(arr, _) => reshape_const_array(&mut VecDeque::from(arr), expr_type),
+                folded => Ok(folded),
+            }
+        }
+    }
+}
+
 /// Fold an expression to constant.
 fn get_folded_expr(expr: &Expr, env: &FrameMap<String, Expr>, expr_type: &Type) -> Result<Expr> {
     match expr_type {
@@ -202,13 +270,19 @@ fn get_folded_expr(expr: &Expr, env: &FrameMap<String, Expr>, expr_type: &Type)
             let arr = get_folded_array(expr, env, element_type)?;
             Ok(arr)
         }
-        Type::Int => {
+        // Literals are 32-bit, they are widened when translated to IR constants
+        Type::Int | Type::UnsignedInt | Type::Long | Type::UnsignedLong => {
             let x = get_folded_i32(expr, env)?;
-            Ok(Expr::Int(x))
+            Ok(Expr::Int(x, expr.span()))
         }
-        Type::Float => {
+        Type::Float | Type::Double => {
             let x = get_folded_f32(expr, env)?;
-            Ok(Expr::Float(x))
+            Ok(Expr::Float(x, expr.span()))
+        }
+        Type::Char => {
+            // Char constant is folded as int, keeping the lowest byte
+            let x = get_folded_i32(expr, env)?;
+            Ok(Expr::Int(x as i8 as i32, expr.span()))
         }
         _ => Err(anyhow!("cannot fold an instance of {:?}", expr_type)).with_context(|| context!()),
     }
@@ -228,7 +302,7 @@ fn get_folded_indexed(
 
     // Expression is indexed
     match expr {
-        Expr::Var(id) => {
+        Expr::Var(id, _) => {
             let Some(val) = env.get(id) else {
                 return Err(anyhow!("Variable not found"));
             };
@@ -236,7 +310,7 @@ fn get_folded_indexed(
             // Although val is already folded, we still need to handle the indexes
             get_folded_indexed(val, env, indexes, expr_type)
         }
-        Expr::Array(arr) => {
+        Expr::Array(arr, _) => {
             // Get index
             let ix = indexes.pop().unwrap();
 
@@ -249,7 +323,7 @@ fn get_folded_indexed(
             // Index unfolded array and then fold the result, to save computation
             get_folded_indexed(&arr[ix], env, indexes, expr_type)
         }
-        Expr::Index(arr, ix) => {
+        Expr::Index(arr, ix, _) => {
             let ix = get_folded_i32(ix, env)?;
             indexes.push(ix as usize);
             get_folded_indexed(arr, env, indexes, expr_type)
@@ -265,7 +339,7 @@ fn get_folded_array(
     element_type: &Type,
 ) -> Result<Expr> {
     match expr {
-        Expr::Var(id) => {
+        Expr::Var(id, _) => {
             let Some(val) = env.get(id) else {
                 return Err(anyhow!("Variable not found"));
             };
@@ -273,18 +347,18 @@ fn get_folded_array(
             // Although val is already folded, we still need to handle the type
             get_folded_array(val, env, element_type)
         }
-        Expr::Array(arr) => arr
+        Expr::Array(arr, span) => arr
             .iter()
             .map(|x| get_folded_expr(x, env, element_type))
             .collect::<Result<_>>()
-            .map(Expr::Array),
-        Expr::Index(arr, ix) => {
+            .map(|arr| Expr::Array(arr, *span)),
+        Expr::Index(arr, ix, _) => {
             let ix = get_folded_i32(ix, env)?;
             get_folded_indexed(
                 arr,
                 env,
                 vec![ix as usize],
-                &Type::Array(element_type.clone().into(), Expr::Int(0).into()),
+                &Type::Array(element_type.clone().into(), Expr::from(0).into()),
             )
         }
         _ => get_folded_expr(expr, env, element_type),
@@ -294,7 +368,7 @@ fn get_folded_array(
 /// Fold an i32 to constant.
 fn get_folded_i32(expr: &Expr, env: &FrameMap<String, Expr>) -> Result<i32> {
     match expr {
-        Expr::Var(id) => {
+        Expr::Var(id, _) => {
             let Some(val) = env.get(id) else {
                 return Err(anyhow!("Variable not found"));
             };
@@ -302,22 +376,28 @@ fn get_folded_i32(expr: &Expr, env: &FrameMap<String, Expr>) -> Result<i32> {
             // Value in environment is already folded, no need to fold again
             val.to_i32()
         }
-        Expr::Index(arr, ix) => {
+        Expr::Index(arr, ix, _) => {
             let ix = get_folded_i32(ix, env)?;
             get_folded_indexed(arr, env, vec![ix as usize], &Type::Int)?.to_i32()
         }
-        Expr::Int(x) => Ok(*x),
-        Expr::Float(x) => Ok(*x as i32),
-        Expr::Bool(x) => Ok(*x as i32),
-        Expr::Unary(op, expr) => {
+        Expr::Int(x, _) => Ok(*x),
+        Expr::Float(x, _) => Ok(*x as i32),
+        Expr::Bool(x, _) => Ok(*x as i32),
+        Expr::Unary(op, expr, _) => {
             let x = get_folded_i32(expr, env)?;
             match op {
                 UnaryOp::Neg => Ok(-x),
                 UnaryOp::Pos => Ok(x),
                 UnaryOp::Not => Ok(if x == 0 { 1 } else { 0 }),
+                UnaryOp::AddrOf | UnaryOp::Deref => {
+                    Err(anyhow!("pointer can't be folded")).with_context(|| context!())
+                }
+                UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec => {
+                    Err(anyhow!("side effect can't be folded")).with_context(|| context!())
+                }
             }
         }
-        Expr::Binary(head, tail) => {
+        Expr::Binary(head, tail, _) => {
             let mut x = get_folded_i32(head, env)?;
             for (op, expr) in tail {
                 let y = get_folded_i32(expr, env)?;
@@ -340,6 +420,7 @@ fn get_folded_i32(expr: &Expr, env: &FrameMap<String, Expr>) -> Result<i32> {
                     BinaryOp::Ne => x = if x != y { 1 } else { 0 },
                     BinaryOp::And => x = if x != 0 && y != 0 { 1 } else { 0 },
                     BinaryOp::Or => x = if x != 0 || y != 0 { 1 } else { 0 },
+                    BinaryOp::Comma => x = y,
                 };
             }
             Ok(x)
@@ -351,28 +432,34 @@ fn get_folded_i32(expr: &Expr, env: &FrameMap<String, Expr>) -> Result<i32> {
 /// Fold an f32 to constant.
 fn get_folded_f32(expr: &Expr, env: &FrameMap<String, Expr>) -> Result<f32> {
     match expr {
-        Expr::Var(id) => {
+        Expr::Var(id, _) => {
             let Some(val) = env.get(id) else {
                 return Err(anyhow!("Variable not found"));
             };
             val.to_f32()
         }
-        Expr::Index(arr, ix) => {
+        Expr::Index(arr, ix, _) => {
             let ix = get_folded_i32(ix, env)?;
             get_folded_indexed(arr, env, vec![ix as usize], &Type::Float)?.to_f32()
         }
-        Expr::Int(x) => Ok(*x as f32),
-        Expr::Float(x) => Ok(*x),
-        Expr::Bool(x) => Ok(*x as i32 as f32),
-        Expr::Unary(op, expr) => {
+        Expr::Int(x, _) => Ok(*x as f32),
+        Expr::Float(x, _) => Ok(*x),
+        Expr::Bool(x, _) => Ok(*x as i32 as f32),
+        Expr::Unary(op, expr, _) => {
             let x = get_folded_f32(expr, env)?;
             match op {
                 UnaryOp::Neg => Ok(-x),
                 UnaryOp::Pos => Ok(x),
                 UnaryOp::Not => Ok(if x == 0.0 { 1.0 } else { 0.0 }),
+                UnaryOp::AddrOf | UnaryOp::Deref => {
+                    Err(anyhow!("pointer can't be folded")).with_context(|| context!())
+                }
+                UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec => {
+                    Err(anyhow!("side effect can't be folded")).with_context(|| context!())
+                }
             }
         }
-        Expr::Binary(head, tail) => {
+        Expr::Binary(head, tail, _) => {
             let mut x = get_folded_f32(head, env)?;
             for (op, expr) in tail {
                 let y = get_folded_f32(expr, env)?;
@@ -395,6 +482,7 @@ fn get_folded_f32(expr: &Expr, env: &FrameMap<String, Expr>) -> Result<f32> {
                     BinaryOp::Ne => x = if x != y { 1.0 } else { 0.0 },
                     BinaryOp::And => x = if x != 0.0 && y != 0.0 { 1.0 } else { 0.0 },
                     BinaryOp::Or => x = if x != 0.0 || y != 0.0 { 1.0 } else { 0.0 },
+                    BinaryOp::Comma => x = y,
                 };
             }
             Ok(x)
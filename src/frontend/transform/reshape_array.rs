@@ -18,7 +18,7 @@ use std::collections::VecDeque;
 
 use anyhow::Result;
 
-use crate::frontend::{Expr, Type};
+use crate::frontend::{Expr, Span, Type};
 
 /// Reshape a possibly flattened constant array to nested.
 ///
@@ -38,16 +38,23 @@ pub fn reshape_const_array(arr: &mut VecDeque<Expr>, ty: &Type) -> Result<Expr>
                 new_arr.push(element_ty.default_initializer()?);
                 continue;
             };
-            if let Expr::Array(arr) = first_item {
+            match first_item {
+                // Struct or union element keeps its own initializer
+                Expr::Array(..) if element_ty.is_record() => new_arr.push(first_item),
+
                 // First element is array, sub-array is nested
-                new_arr.push(reshape_const_array(&mut VecDeque::from(arr), element_ty)?);
-            } else {
+                Expr::Array(arr, _) => {
+                    new_arr.push(reshape_const_array(&mut VecDeque::from(arr), element_ty)?);
+                }
+
                 // First element is non-array, sub-array is flattened
-                arr.push_front(first_item);
-                new_arr.push(reshape_const_array(arr, element_ty)?);
+                _ => {
+                    arr.push_front(first_item);
+                    new_arr.push(reshape_const_array(arr, element_ty)?);
+                }
             }
         }
-        Ok(Expr::Array(new_arr))
+        Ok(Expr::Array(new_arr, Span::default()))
     } else {
         Ok(arr.pop_front().unwrap())
     }
@@ -65,16 +72,23 @@ pub fn reshape_array(arr: &mut VecDeque<Expr>, ty: &Type) -> Result<Expr> {
             let Some(first_item) = arr.pop_front() else {
                 break;
             };
-            if let Expr::Array(arr) = first_item {
+            match first_item {
+                // Struct or union element keeps its own initializer
+                Expr::Array(..) if element_ty.is_record() => new_arr.push(first_item),
+
                 // First element is array, sub-array is nested
-                new_arr.push(reshape_array(&mut VecDeque::from(arr), element_ty)?);
-            } else {
+                Expr::Array(arr, _) => {
+                    new_arr.push(reshape_array(&mut VecDeque::from(arr), element_ty)?);
+                }
+
                 // First element is non-array, sub-array is flattened
-                arr.push_front(first_item);
-                new_arr.push(reshape_array(arr, element_ty)?);
+                _ => {
+                    arr.push_front(first_item);
+                    new_arr.push(reshape_array(arr, element_ty)?);
+                }
             }
         }
-        Ok(Expr::Array(new_arr))
+        Ok(Expr::Array(new_arr, Span::default()))
     } else {
         Ok(arr.pop_front().unwrap())
     }
@@ -84,106 +98,165 @@ pub fn reshape_array(arr: &mut VecDeque<Expr>, ty: &Type) -> Result<Expr> {
 mod tests {
     use std::collections::VecDeque;
 
-    use crate::frontend::{transform::reshape_array::reshape_array, Expr, Type};
+    use crate::frontend::{transform::reshape_array::reshape_array, Expr, Span, Type};
 
     #[test]
     fn test_reshape_flattened_array() {
-        let arr = vec![Expr::Int(1), Expr::Int(2), Expr::Int(3), Expr::Int(4)];
+        let arr = vec![
+            Expr::Int(1, Span::default()),
+            Expr::Int(2, Span::default()),
+            Expr::Int(3, Span::default()),
+            Expr::Int(4, Span::default()),
+        ];
         let mut vec_deque = VecDeque::from(arr);
         let ty = Type::Array(
-            Type::Array(Type::Int.into(), Expr::Int(2).into()).into(),
-            Expr::Int(2).into(),
+            Type::Array(Type::Int.into(), Expr::Int(2, Span::default()).into()).into(),
+            Expr::Int(2, Span::default()).into(),
         );
         let res = reshape_array(&mut vec_deque, &ty).unwrap();
         assert_eq!(
             res,
-            Expr::Array(vec![
-                Expr::Array(vec![Expr::Int(1), Expr::Int(2)]),
-                Expr::Array(vec![Expr::Int(3), Expr::Int(4)]),
-            ])
+            Expr::Array(
+                vec![
+                    Expr::Array(
+                        vec![Expr::Int(1, Span::default()), Expr::Int(2, Span::default())],
+                        Span::default()
+                    ),
+                    Expr::Array(
+                        vec![Expr::Int(3, Span::default()), Expr::Int(4, Span::default())],
+                        Span::default()
+                    ),
+                ],
+                Span::default()
+            )
         );
     }
 
     #[test]
     fn test_reshape_nested_array() {
         let arr = vec![
-            Expr::Array(vec![Expr::Int(1), Expr::Int(2)]),
-            Expr::Array(vec![Expr::Int(3), Expr::Int(4)]),
+            Expr::Array(
+                vec![Expr::Int(1, Span::default()), Expr::Int(2, Span::default())],
+                Span::default(),
+            ),
+            Expr::Array(
+                vec![Expr::Int(3, Span::default()), Expr::Int(4, Span::default())],
+                Span::default(),
+            ),
         ];
         let mut vec_deque = VecDeque::from(arr);
         let ty = Type::Array(
-            Type::Array(Type::Int.into(), Expr::Int(2).into()).into(),
-            Expr::Int(2).into(),
+            Type::Array(Type::Int.into(), Expr::Int(2, Span::default()).into()).into(),
+            Expr::Int(2, Span::default()).into(),
         );
         let res = reshape_array(&mut vec_deque, &ty).unwrap();
         assert_eq!(
             res,
-            Expr::Array(vec![
-                Expr::Array(vec![Expr::Int(1), Expr::Int(2)]),
-                Expr::Array(vec![Expr::Int(3), Expr::Int(4)]),
-            ])
+            Expr::Array(
+                vec![
+                    Expr::Array(
+                        vec![Expr::Int(1, Span::default()), Expr::Int(2, Span::default())],
+                        Span::default()
+                    ),
+                    Expr::Array(
+                        vec![Expr::Int(3, Span::default()), Expr::Int(4, Span::default())],
+                        Span::default()
+                    ),
+                ],
+                Span::default()
+            )
         );
     }
 
     #[test]
     fn test_reshape_mixed_array() {
         let arr = vec![
-            Expr::Int(1),
-            Expr::Int(2),
-            Expr::Array(vec![Expr::Int(3), Expr::Int(4)]),
+            Expr::Int(1, Span::default()),
+            Expr::Int(2, Span::default()),
+            Expr::Array(
+                vec![Expr::Int(3, Span::default()), Expr::Int(4, Span::default())],
+                Span::default(),
+            ),
         ];
         let mut vec_deque = VecDeque::from(arr);
         let ty = Type::Array(
-            Type::Array(Type::Int.into(), Expr::Int(2).into()).into(),
-            Expr::Int(2).into(),
+            Type::Array(Type::Int.into(), Expr::Int(2, Span::default()).into()).into(),
+            Expr::Int(2, Span::default()).into(),
         );
         let res = reshape_array(&mut vec_deque, &ty).unwrap();
         assert_eq!(
             res,
-            Expr::Array(vec![
-                Expr::Array(vec![Expr::Int(1), Expr::Int(2)]),
-                Expr::Array(vec![Expr::Int(3), Expr::Int(4)]),
-            ])
+            Expr::Array(
+                vec![
+                    Expr::Array(
+                        vec![Expr::Int(1, Span::default()), Expr::Int(2, Span::default())],
+                        Span::default()
+                    ),
+                    Expr::Array(
+                        vec![Expr::Int(3, Span::default()), Expr::Int(4, Span::default())],
+                        Span::default()
+                    ),
+                ],
+                Span::default()
+            )
         );
     }
 
     #[test]
     fn test_reshape_mixed_array_2() {
         let arr = vec![
-            Expr::Array(vec![Expr::Int(1), Expr::Int(2)]),
-            Expr::Int(3),
-            Expr::Int(4),
+            Expr::Array(
+                vec![Expr::Int(1, Span::default()), Expr::Int(2, Span::default())],
+                Span::default(),
+            ),
+            Expr::Int(3, Span::default()),
+            Expr::Int(4, Span::default()),
         ];
         let mut vec_deque = VecDeque::from(arr);
         let ty = Type::Array(
-            Type::Array(Type::Int.into(), Expr::Int(2).into()).into(),
-            Expr::Int(2).into(),
+            Type::Array(Type::Int.into(), Expr::Int(2, Span::default()).into()).into(),
+            Expr::Int(2, Span::default()).into(),
         );
         let res = reshape_array(&mut vec_deque, &ty).unwrap();
         assert_eq!(
             res,
-            Expr::Array(vec![
-                Expr::Array(vec![Expr::Int(1), Expr::Int(2)]),
-                Expr::Array(vec![Expr::Int(3), Expr::Int(4)]),
-            ])
+            Expr::Array(
+                vec![
+                    Expr::Array(
+                        vec![Expr::Int(1, Span::default()), Expr::Int(2, Span::default())],
+                        Span::default()
+                    ),
+                    Expr::Array(
+                        vec![Expr::Int(3, Span::default()), Expr::Int(4, Span::default())],
+                        Span::default()
+                    ),
+                ],
+                Span::default()
+            )
         );
     }
 
     #[test]
     fn test_reshape_fractured_array() {
-        let arr = vec![Expr::Array(vec![Expr::Int(1)]), Expr::Int(3)];
+        let arr = vec![
+            Expr::Array(vec![Expr::Int(1, Span::default())], Span::default()),
+            Expr::Int(3, Span::default()),
+        ];
         let mut vec_deque = VecDeque::from(arr);
         let ty = Type::Array(
-            Type::Array(Type::Int.into(), Expr::Int(2).into()).into(),
-            Expr::Int(2).into(),
+            Type::Array(Type::Int.into(), Expr::Int(2, Span::default()).into()).into(),
+            Expr::Int(2, Span::default()).into(),
         );
         let res = reshape_array(&mut vec_deque, &ty).unwrap();
         assert_eq!(
             res,
-            Expr::Array(vec![
-                Expr::Array(vec![Expr::Int(1),]),
-                Expr::Array(vec![Expr::Int(3),]),
-            ])
+            Expr::Array(
+                vec![
+                    Expr::Array(vec![Expr::Int(1, Span::default()),], Span::default()),
+                    Expr::Array(vec![Expr::Int(3, Span::default()),], Span::default()),
+                ],
+                Span::default()
+            )
         );
     }
 }
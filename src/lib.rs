@@ -20,6 +20,7 @@ use anyhow::Context;
 use backend::irs::checker::ProgramChecker;
 use errors::CompilerError;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::os::unix::fs::PermissionsExt;
 pub mod args;
 pub mod backend;
@@ -37,20 +38,25 @@ pub mod utils;
 use clap::arg;
 
 /// compile sysy source code to rv64gc asm
+/// `passes` replaces the default middle-end pipeline when given
 pub fn compile(
     sy_path: &str,
     output_path: &str,
     opt_flag: bool,
     asm_flag: bool,
     ll_path: Option<String>,
+    include_dirs: &[String],
+    passes: Option<&str>,
 ) -> Result<(), CompilerError> {
-    let content = std::fs::read_to_string(sy_path).map_err(CompilerError::IOError)?;
-    let mut program = frontend::parse(&content)?;
+    let include_dirs = include_dirs.iter().map(PathBuf::from).collect();
+    let mut program = frontend::parse_file(Path::new(sy_path), include_dirs)?;
     if opt_flag {
         frontend::optimize(&mut program);
     }
     let mut program = middle::gen(&program)?;
-    if opt_flag {
+    if let Some(passes) = passes {
+        middle::optimize_with_passes(&mut program, passes)?;
+    } else if opt_flag {
         middle::optimize(&mut program);
     }
     if let Some(ll_path) = ll_path {
@@ -137,14 +143,18 @@ pub fn compile_self_llc(
     opt_flag: bool,
     asm_flag: bool,
     ll_path: Option<String>,
+    include_dirs: &[String],
+    passes: Option<&str>,
 ) -> Result<(), CompilerError> {
-    let content = std::fs::read_to_string(sy_path).map_err(CompilerError::IOError)?;
-    let mut program = frontend::parse(&content)?;
+    let include_dirs = include_dirs.iter().map(PathBuf::from).collect();
+    let mut program = frontend::parse_file(Path::new(sy_path), include_dirs)?;
     if opt_flag {
         frontend::optimize(&mut program);
     }
     let mut program = middle::gen(&program)?;
-    if opt_flag {
+    if let Some(passes) = passes {
+        middle::optimize_with_passes(&mut program, passes)?;
+    } else if opt_flag {
         middle::optimize(&mut program);
     }
     // 中端接clang
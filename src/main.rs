@@ -20,7 +20,7 @@ extern crate compiler;
 use std::borrow::Borrow;
 
 use clap::Parser;
-use compiler::{args::Cli, compile, errors::handle_error};
+use compiler::{args::Cli, compile, errors::handle_error, utils::pass_instrument};
 
 fn main() {
     let cli = Cli::parse();
@@ -28,14 +28,33 @@ fn main() {
 }
 
 fn start_compiler(cli: &Cli) {
-    let (sy_path, output_path, opt_flag, asm_flag, ll_path) = (
+    let (sy_path, output_path, opt_flag, asm_flag, ll_path, include_dirs, passes) = (
         &cli.sy,
         &cli.output,
         cli.optimize != 0,
         cli.asm,
         cli.ll.clone(),
+        &cli.include,
+        cli.passes.as_deref(),
     );
-    let result = compile(sy_path, output_path, opt_flag, asm_flag, ll_path);
+    let instrument = cli.pass_instrument();
+    if !instrument.is_empty() {
+        pass_instrument::install(instrument);
+    }
+    let result = compile(
+        sy_path,
+        output_path,
+        opt_flag,
+        asm_flag,
+        ll_path,
+        include_dirs,
+        passes,
+    );
+    if let Some(instrument) = pass_instrument::uninstall() {
+        if instrument.time_passes {
+            eprint!("{}", instrument.report());
+        }
+    }
     if let Err(err) = result.borrow() {
         handle_error(err);
     }
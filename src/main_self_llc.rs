@@ -30,15 +30,35 @@ fn main() {
 fn start_compiler_sc(cli: &compiler::args::Cli) {
     use compiler::compile_self_llc;
     use compiler::errors::handle_error;
+    use compiler::utils::pass_instrument;
     use std::borrow::Borrow;
-    let (sy_path, output_path, opt_flag, asm_flag, ll_path) = (
+    let (sy_path, output_path, opt_flag, asm_flag, ll_path, include_dirs, passes) = (
         &cli.sy,
         &cli.output,
         cli.optimize != 0,
         cli.asm,
         cli.ll.clone(),
+        &cli.include,
+        cli.passes.as_deref(),
     );
-    let result = compile_self_llc(sy_path, output_path, opt_flag, asm_flag, ll_path);
+    let instrument = cli.pass_instrument();
+    if !instrument.is_empty() {
+        pass_instrument::install(instrument);
+    }
+    let result = compile_self_llc(
+        sy_path,
+        output_path,
+        opt_flag,
+        asm_flag,
+        ll_path,
+        include_dirs,
+        passes,
+    );
+    if let Some(instrument) = pass_instrument::uninstall() {
+        if instrument.time_passes {
+            eprint!("{}", instrument.report());
+        }
+    }
     if let Err(err) = result.borrow() {
         handle_error(err);
     }
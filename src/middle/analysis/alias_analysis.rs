@@ -194,6 +194,18 @@ fn can_ptr_alias(a: &Operand, b: &Operand) -> bool {
         }
     }
 
+    // Pointer loaded from memory can point to anywhere
+    if let Operand::Instruction(a) = a {
+        if a.get_type() != InstType::Alloca {
+            return true;
+        }
+    }
+    if let Operand::Instruction(b) = b {
+        if b.get_type() != InstType::Alloca {
+            return true;
+        }
+    }
+
     // Alloc instruction alias only when they're the same
     if let Operand::Instruction(a) = a {
         if let Operand::Instruction(b) = b {
@@ -206,7 +218,9 @@ fn can_ptr_alias(a: &Operand, b: &Operand) -> bool {
 }
 
 /// Check if two sets of GEP offsets can overlap.
-fn can_offset_overlap(a: HashMap<ValueType, Operand>, b: HashMap<ValueType, Operand>) -> bool {
+// Hash of `Record` does not use its mutable fields, so it is a false positive
+#[allow(clippy::mutable_key_type)]
+fn can_offset_overlap(a: HashMap<OffsetKey, Operand>, b: HashMap<OffsetKey, Operand>) -> bool {
     for (key, a_op) in a.iter() {
         if let Some(b_op) = b.get(key) {
             if !can_equal(a_op.clone(), b_op.clone()) {
@@ -217,8 +231,19 @@ fn can_offset_overlap(a: HashMap<ValueType, Operand>, b: HashMap<ValueType, Oper
     true
 }
 
+/// Kind of a single GEP index, used to match indices of two GEPs.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum OffsetKey {
+    /// Index strides over elements of the type.
+    Stride(ValueType),
+    /// Index selects a field of the struct type.
+    Field(ValueType),
+}
+
 /// Split GEP instruction into base pointer and offset.
-fn split_gep(op: &Operand) -> (Operand, HashMap<ValueType, Operand>) {
+// Hash of `Record` does not use its mutable fields, so it is a false positive
+#[allow(clippy::mutable_key_type)]
+fn split_gep(op: &Operand) -> (Operand, HashMap<OffsetKey, Operand>) {
     let mut base = op.clone();
     let mut offset = HashMap::new();
     while let Operand::Instruction(inst) = base {
@@ -232,21 +257,34 @@ fn split_gep(op: &Operand) -> (Operand, HashMap<ValueType, Operand>) {
         // Update offset
         let gep = downcast_ref::<GetElementPtr>(inst.as_ref().as_ref());
         let mut element_type = gep.element_type.clone();
-        for op in inst.get_operand().iter().skip(1) {
-            if let Some(old_offset) = offset.get_mut(&element_type) {
-                // Handle only +0 or 0+ in this case, UB otherwise
-                if let Operand::Constant(Constant::Int(0)) = old_offset {
-                    *old_offset = op.clone();
-                } else if let Operand::Constant(Constant::Int(0)) = op {
-                    // Do nothing
-                } else {
-                    unimplemented!("Unsupported GEP offset: {} + {}", old_offset, op);
+        for (i, op) in inst.get_operand().iter().skip(1).enumerate() {
+            let key = if i == 0 {
+                OffsetKey::Stride(element_type.clone())
+            } else if let ValueType::Struct(_) = element_type {
+                let key = OffsetKey::Field(element_type.clone());
+                element_type = element_type
+                    .get_indexed_type(op)
+                    .cloned()
+                    .unwrap_or(ValueType::Void);
+                key
+            } else {
+                if let Some(subtype) = element_type.get_sub_type() {
+                    element_type = subtype.clone();
+                }
+                OffsetKey::Stride(element_type.clone())
+            };
+            if let Some(old_offset) = offset.get_mut(&key) {
+                // Handle only +0, 0+ or sum of constants, offset is unknown otherwise
+                match (&*old_offset, op) {
+                    (Operand::Constant(Constant::Int(0)), _) => *old_offset = op.clone(),
+                    (_, Operand::Constant(Constant::Int(0))) => (),
+                    (Operand::Constant(Constant::Int(a)), Operand::Constant(Constant::Int(b))) => {
+                        *old_offset = Constant::Int(a + b).into();
+                    }
+                    _ => return (base, HashMap::new()),
                 }
             } else {
-                offset.insert(element_type.clone(), op.clone());
-            }
-            if let Some(subtype) = element_type.get_sub_type() {
-                element_type = subtype.clone();
+                offset.insert(key, op.clone());
             }
         }
     }
@@ -0,0 +1,128 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, rc::Rc};
+
+use crate::middle::{ir::FunPtr, Program};
+
+use super::{
+    dominator_tree::DominatorTree, effect_analysis::EffectAnalysis, memory_ssa::MemorySSA,
+};
+
+/// Kinds of analysis cached by `AnalysisManager`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Analysis {
+    DominatorTree,
+    EffectAnalysis,
+    MemorySSA,
+}
+
+/// Cache of analysis results shared by consecutive passes.
+/// Results are built lazily on first request, and dropped by `invalidate`
+/// when a pass changes the program without preserving them.
+pub struct AnalysisManager {
+    memory_ssa: Option<MemorySSA<'static>>,
+    effect_analysis: Option<Rc<EffectAnalysis>>,
+    dom_trees: HashMap<FunPtr, DominatorTree>,
+    build_count: HashMap<Analysis, usize>,
+}
+
+impl Default for AnalysisManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisManager {
+    pub fn new() -> Self {
+        Self {
+            memory_ssa: None,
+            effect_analysis: None,
+            dom_trees: HashMap::new(),
+            build_count: HashMap::new(),
+        }
+    }
+
+    /// Get effect analysis of program, build it if not cached.
+    pub fn effect_analysis(&mut self, program: &Program) -> &EffectAnalysis {
+        self.shared_effect_analysis(program)
+    }
+
+    /// Get effect analysis with shared ownership, build it if not cached.
+    fn shared_effect_analysis(&mut self, program: &Program) -> &Rc<EffectAnalysis> {
+        if self.effect_analysis.is_none() {
+            self.effect_analysis = Some(Rc::new(EffectAnalysis::new(program)));
+            *self
+                .build_count
+                .entry(Analysis::EffectAnalysis)
+                .or_default() += 1;
+        }
+        self.effect_analysis.as_ref().unwrap()
+    }
+
+    /// Get MemorySSA of program, build it (and effect analysis) if not cached.
+    pub fn memory_ssa(&mut self, program: &Program) -> &mut MemorySSA<'static> {
+        if self.memory_ssa.is_none() {
+            let effect_analysis = self.shared_effect_analysis(program).clone();
+            self.memory_ssa = Some(MemorySSA::new_shared(program, effect_analysis));
+            *self.build_count.entry(Analysis::MemorySSA).or_default() += 1;
+        }
+        self.memory_ssa.as_mut().unwrap()
+    }
+
+    /// Get dominator trees of all functions, trees are built lazily by their users.
+    pub fn dom_trees(&mut self) -> &mut HashMap<FunPtr, DominatorTree> {
+        &mut self.dom_trees
+    }
+
+    /// Get dominator tree of function, build it if not cached.
+    pub fn dom_tree(&mut self, func: FunPtr) -> &mut DominatorTree {
+        self.dom_trees
+            .entry(func)
+            .or_insert_with(|| DominatorTree::new(func))
+    }
+
+    /// Drop all cached results except the preserved ones.
+    /// MemorySSA depends on effect analysis, so it's dropped together with it.
+    pub fn invalidate(&mut self, preserved: &[Analysis]) {
+        if !preserved.contains(&Analysis::MemorySSA)
+            || !preserved.contains(&Analysis::EffectAnalysis)
+        {
+            self.memory_ssa = None;
+        }
+        if !preserved.contains(&Analysis::EffectAnalysis) {
+            self.effect_analysis = None;
+        }
+        if !preserved.contains(&Analysis::DominatorTree) {
+            self.dom_trees.clear();
+        }
+    }
+
+    /// Check if result of an analysis is cached.
+    pub fn is_cached(&self, analysis: Analysis) -> bool {
+        match analysis {
+            Analysis::DominatorTree => !self.dom_trees.is_empty(),
+            Analysis::EffectAnalysis => self.effect_analysis.is_some(),
+            Analysis::MemorySSA => self.memory_ssa.is_some(),
+        }
+    }
+
+    /// Get how many times effect analysis or MemorySSA is built.
+    /// Dominator trees are built lazily by passes, so they're not counted.
+    pub fn build_count(&self, analysis: Analysis) -> usize {
+        self.build_count.get(&analysis).cloned().unwrap_or_default()
+    }
+}
@@ -37,6 +37,7 @@ pub struct CallEdge {
 #[allow(unused)]
 pub struct CallGraph {
     main: Option<FunPtr>,
+    funcs: Vec<FunPtr>,
     calls: HashMap<FunPtr, HashSet<CallEdge>>,
     called_by: HashMap<FunPtr, HashSet<CallEdge>>,
 }
@@ -46,6 +47,7 @@ impl CallGraph {
         let mut calls = HashMap::new();
         let mut called_by = HashMap::new();
         let mut main = None;
+        let mut funcs = Vec::new();
         for func in program.module.functions.clone() {
             if func.name == "main" {
                 main = Some(func);
@@ -55,6 +57,7 @@ impl CallGraph {
             if func.is_lib() {
                 continue;
             }
+            funcs.push(func);
 
             // Iterate all instructions
             for bb in func.dfs_iter() {
@@ -87,6 +90,7 @@ impl CallGraph {
         }
         CallGraph {
             main,
+            funcs,
             calls,
             called_by,
         }
@@ -103,13 +107,100 @@ impl CallGraph {
     pub fn remove(&mut self, func: FunPtr) {
         if let Some(calls) = self.calls.remove(&func) {
             for call in calls {
-                self.called_by.get_mut(&call.caller).unwrap().remove(&call);
+                if let Some(called_by) = self.called_by.get_mut(&call.callee) {
+                    called_by.remove(&call);
+                }
             }
         }
         if let Some(called_by) = self.called_by.remove(&func) {
             for call in called_by {
-                self.calls.get_mut(&call.caller).unwrap().remove(&call);
+                if let Some(calls) = self.calls.get_mut(&call.caller) {
+                    calls.remove(&call);
+                }
+            }
+        }
+        self.funcs.retain(|f| *f != func);
+    }
+
+    pub fn add_edge(&mut self, edge: CallEdge) {
+        self.calls.entry(edge.caller).or_default().insert(edge);
+        self.called_by.entry(edge.callee).or_default().insert(edge);
+    }
+
+    pub fn remove_edge(&mut self, edge: CallEdge) {
+        if let Some(calls) = self.calls.get_mut(&edge.caller) {
+            calls.remove(&edge);
+        }
+        if let Some(called_by) = self.called_by.get_mut(&edge.callee) {
+            called_by.remove(&edge);
+        }
+    }
+
+    /// Check if function calls itself, directly or through other functions.
+    pub fn is_recursive(&self, scc: &[FunPtr]) -> bool {
+        scc.len() > 1
+            || self
+                .get_calls(scc[0])
+                .iter()
+                .any(|call| call.callee == scc[0])
+    }
+
+    /// Get strongly connected components of call graph, with callee before caller.
+    pub fn get_sccs(&self) -> Vec<Vec<FunPtr>> {
+        let mut tarjan = Tarjan::default();
+        for func in self.funcs.iter() {
+            if !tarjan.index.contains_key(func) {
+                tarjan.visit(self, *func);
+            }
+        }
+        tarjan.sccs
+    }
+}
+
+/// State of Tarjan's algorithm, which finds SCCs in reverse topological order.
+#[derive(Default)]
+struct Tarjan {
+    index: HashMap<FunPtr, usize>,
+    low_link: HashMap<FunPtr, usize>,
+    stack: Vec<FunPtr>,
+    on_stack: HashSet<FunPtr>,
+    sccs: Vec<Vec<FunPtr>>,
+}
+
+impl Tarjan {
+    fn visit(&mut self, call_graph: &CallGraph, func: FunPtr) {
+        let id = self.index.len();
+        self.index.insert(func, id);
+        self.low_link.insert(func, id);
+        self.stack.push(func);
+        self.on_stack.insert(func);
+
+        // Visit callee in order of call instruction, to make result stable
+        let mut calls = call_graph.get_calls(func).into_iter().collect::<Vec<_>>();
+        calls.sort_by_key(|call| call.inst.get_id());
+        for call in calls {
+            let callee = call.callee;
+            if !self.index.contains_key(&callee) {
+                self.visit(call_graph, callee);
+                let low = self.low_link[&func].min(self.low_link[&callee]);
+                self.low_link.insert(func, low);
+            } else if self.on_stack.contains(&callee) {
+                let low = self.low_link[&func].min(self.index[&callee]);
+                self.low_link.insert(func, low);
+            }
+        }
+
+        // Pop SCC if `func` is its root
+        if self.low_link[&func] == self.index[&func] {
+            let mut scc = Vec::new();
+            while let Some(f) = self.stack.pop() {
+                self.on_stack.remove(&f);
+                scc.push(f);
+                if f == func {
+                    break;
+                }
             }
+            self.sccs.push(scc);
         }
     }
 }
@@ -20,8 +20,11 @@ use crate::{
     backend::from_self::downcast_ref,
     middle::{
         ir::{
-            instruction::{misc_inst::Call, InstType},
-            FunPtr, InstPtr, Operand,
+            instruction::{
+                misc_inst::{Call, Phi},
+                InstType,
+            },
+            Constant, FunPtr, InstPtr, Operand, ValueType,
         },
         Program,
     },
@@ -63,6 +66,8 @@ pub struct EffectAnalysis {
     pub has_io_output: HashSet<FunPtr>,
     pub has_mem_input: HashSet<FunPtr>,
     pub has_mem_output: HashSet<FunPtr>,
+    /// Functions that always return a non-negative integer
+    pub non_negative_ret: HashSet<FunPtr>,
     functions: Vec<FunPtr>,
 }
 
@@ -76,6 +81,7 @@ impl EffectAnalysis {
             has_io_output: HashSet::new(),
             has_mem_input: HashSet::new(),
             has_mem_output: HashSet::new(),
+            non_negative_ret: HashSet::new(),
             functions: program.module.functions.clone(),
         };
 
@@ -128,12 +134,51 @@ impl EffectAnalysis {
                     );
                 }
             }
+            if !changed {
+                break;
+            }
+        }
+
+        // Iterate return value facts until unchanged, recursion is assumed to return anything
+        loop {
+            let mut changed = false;
+            for func in program.module.functions.iter() {
+                if func.is_lib()
+                    || func.return_type != ValueType::Int
+                    || effect.non_negative_ret.contains(func)
+                {
+                    continue;
+                }
+                if func.entry.is_none() {
+                    continue;
+                }
+
+                // Look for returns in every block, so that an outdated exit doesn't matter
+                let non_negative = func
+                    .dfs_iter()
+                    .map(|bb| bb.get_last_inst())
+                    .filter(|inst| inst.get_type() == InstType::Ret)
+                    .all(|ret| {
+                        ret.get_operand()
+                            .first()
+                            .is_some_and(|op| effect.is_non_negative(op))
+                    });
+                if non_negative {
+                    changed |= effect.non_negative_ret.insert(*func);
+                }
+            }
             if !changed {
                 break effect;
             }
         }
     }
 
+    /// Get if operand is always a non-negative integer.
+    /// Addition and multiplication wrap, so they need bounded operands, see `get_bound`.
+    pub fn is_non_negative(&self, operand: &Operand) -> bool {
+        self.check_non_negative(operand, &mut HashSet::new())
+    }
+
     /// Get if instruction has IO.
     pub fn has_io(&self, inst: InstPtr) -> bool {
         if inst.get_type() == InstType::Call {
@@ -178,6 +223,71 @@ impl EffectAnalysis {
         res
     }
 
+    /// Check if operand is non-negative, `visited` breaks cycles of phi.
+    /// A revisited instruction is assumed non-negative, this holds because every rule is a conjunction,
+    /// so a negative operand anywhere in the cycle still fails the outermost check.
+    fn check_non_negative(&self, operand: &Operand, visited: &mut HashSet<InstPtr>) -> bool {
+        let inst = match operand {
+            Operand::Constant(Constant::Int(value)) => return *value >= 0,
+            Operand::Constant(Constant::Bool(_)) => return true,
+            Operand::Instruction(inst) => *inst,
+            _ => return false,
+        };
+        if !visited.insert(inst) {
+            return true;
+        }
+        match inst.get_type() {
+            InstType::Add | InstType::Mul => self.get_bound(operand, visited).is_some(),
+            InstType::SDiv => inst
+                .get_operand()
+                .iter()
+                .all(|op| self.check_non_negative(op, visited)),
+            InstType::SRem => self.check_non_negative(&inst.get_operand()[0], visited),
+            InstType::ZextTo => true,
+            InstType::Phi => {
+                let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+                phi.get_incoming_values()
+                    .iter()
+                    .all(|(op, _)| self.check_non_negative(op, visited))
+            }
+            InstType::Call => {
+                let call = downcast_ref::<Call>(inst.as_ref().as_ref());
+                self.non_negative_ret.contains(&call.func)
+            }
+            _ => false,
+        }
+    }
+
+    /// Get upper bound of operand if it's provably within `0..=bound`.
+    /// Add and mul wrap on overflow, so they only count when both sides are bounded,
+    /// for example `x % 8 * 3 + 1` with non-negative `x` is bounded by 22.
+    fn get_bound(&self, operand: &Operand, visited: &mut HashSet<InstPtr>) -> Option<i32> {
+        let inst = match operand {
+            Operand::Constant(Constant::Int(value)) => return (*value >= 0).then_some(*value),
+            Operand::Instruction(inst) => *inst,
+            _ => return None,
+        };
+        let ops = inst.get_operand();
+        match inst.get_type() {
+            InstType::Add => self
+                .get_bound(&ops[0], visited)?
+                .checked_add(self.get_bound(&ops[1], visited)?),
+            InstType::Mul => self
+                .get_bound(&ops[0], visited)?
+                .checked_mul(self.get_bound(&ops[1], visited)?),
+            InstType::SRem => match ops[1] {
+                Operand::Constant(Constant::Int(rhs))
+                    if rhs > 0 && self.check_non_negative(&ops[0], visited) =>
+                {
+                    Some(rhs - 1)
+                }
+                _ => None,
+            },
+            InstType::ZextTo => Some(1),
+            _ => None,
+        }
+    }
+
     /// Process function, return changed or not
     fn process_func(&mut self, func: FunPtr) -> bool {
         let mut changed = false;
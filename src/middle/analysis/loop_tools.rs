@@ -128,9 +128,14 @@ impl LoopForest {
                     }
                 }
             } else {
-                // 当前bb有双分支，且第二个分支还未访问
+                // 当前bb有多分支，且存在未访问的分支
                 stack.push(bb);
-                stack.push(bb.get_succ_bb()[1]);
+                let next = bb
+                    .get_succ_bb()
+                    .iter()
+                    .find(|next| !id_map.contains_key(next))
+                    .unwrap();
+                stack.push(*next);
             }
         }
 
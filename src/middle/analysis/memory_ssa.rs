@@ -14,7 +14,11 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    rc::Rc,
+};
 
 use anyhow::{anyhow, Context, Result};
 
@@ -46,12 +50,40 @@ pub struct MemorySSA<'a> {
     block_to_node: HashMap<BBPtr, NodePtr>,
     node_to_block: HashMap<NodePtr, BBPtr>,
     node_to_user: HashMap<NodePtr, HashSet<NodePtr>>,
-    pub effect_analysis: &'a EffectAnalysis,
+    pub effect_analysis: EffectAnalysisRef<'a>,
+}
+
+/// Effect analysis used by MemorySSA, either borrowed or shared with a cache.
+pub enum EffectAnalysisRef<'a> {
+    Borrowed(&'a EffectAnalysis),
+    Shared(Rc<EffectAnalysis>),
+}
+
+impl Deref for EffectAnalysisRef<'_> {
+    type Target = EffectAnalysis;
+
+    fn deref(&self) -> &EffectAnalysis {
+        match self {
+            EffectAnalysisRef::Borrowed(effect_analysis) => effect_analysis,
+            EffectAnalysisRef::Shared(effect_analysis) => effect_analysis,
+        }
+    }
+}
+
+impl MemorySSA<'static> {
+    /// Build MemorySSA for program, sharing ownership of effect analysis.
+    pub fn new_shared(program: &Program, effect_analysis: Rc<EffectAnalysis>) -> Self {
+        Self::build(program, EffectAnalysisRef::Shared(effect_analysis))
+    }
 }
 
 impl<'a> MemorySSA<'a> {
     /// Build MemorySSA for program.
     pub fn new(program: &Program, effect_analysis: &'a EffectAnalysis) -> Self {
+        Self::build(program, EffectAnalysisRef::Borrowed(effect_analysis))
+    }
+
+    fn build(program: &Program, effect_analysis: EffectAnalysisRef<'a>) -> Self {
         let mut memory_ssa = Self {
             builder: MemorySSABuilder {
                 node_pool: ObjPool::new(),
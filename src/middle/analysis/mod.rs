@@ -19,10 +19,13 @@ use crate::middle::ir::FunPtr;
 use std::collections::{HashMap, HashSet};
 
 pub mod alias_analysis;
+pub mod analysis_manager;
 pub mod call_graph;
 pub mod dominator_tree;
 pub mod effect_analysis;
 pub mod loop_tools;
 pub mod memory_ssa;
 pub mod reachability;
+pub mod scalar_evolution;
 pub mod simple_gvn;
+pub mod verifier;
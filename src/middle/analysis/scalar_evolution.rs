@@ -0,0 +1,464 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    backend::from_self::downcast_ref,
+    middle::ir::{
+        instruction::{
+            misc_inst::{ICmp, ICmpOp, Phi},
+            InstType,
+        },
+        BBPtr, Constant, InstPtr, Operand, ValueType,
+    },
+};
+
+use super::loop_tools::LoopPtr;
+
+/// Loop invariant affine expression `constant + sum(coef * value)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Affine {
+    pub constant: i64,
+    pub terms: Vec<(Operand, i64)>,
+}
+
+impl Affine {
+    pub fn constant(constant: i64) -> Self {
+        Self {
+            constant,
+            terms: Vec::new(),
+        }
+    }
+
+    pub fn value(op: Operand) -> Self {
+        if let Operand::Constant(Constant::Int(value)) = op {
+            return Self::constant(value as i64);
+        }
+        Self {
+            constant: 0,
+            terms: vec![(op, 1)],
+        }
+    }
+
+    /// Get value of expression if it has no variable term.
+    pub fn get_constant(&self) -> Option<i64> {
+        self.terms.is_empty().then_some(self.constant)
+    }
+
+    pub fn add(&self, another: &Affine) -> Affine {
+        let mut result = self.clone();
+        result.constant = result.constant.wrapping_add(another.constant);
+        for (op, coef) in another.terms.iter() {
+            match result.terms.iter_mut().find(|(term, _)| term == op) {
+                Some((_, c)) => *c = c.wrapping_add(*coef),
+                None => result.terms.push((op.clone(), *coef)),
+            }
+        }
+        result.terms.retain(|(_, coef)| *coef != 0);
+        result
+    }
+
+    pub fn scale(&self, factor: i64) -> Affine {
+        let mut result = Affine::constant(self.constant.wrapping_mul(factor));
+        if factor != 0 {
+            result.terms = self
+                .terms
+                .iter()
+                .map(|(op, coef)| (op.clone(), coef.wrapping_mul(factor)))
+                .collect();
+        }
+        result
+    }
+}
+
+impl Display for Affine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = self
+            .terms
+            .iter()
+            .map(|(op, coef)| {
+                // Parameter displays with its type, print its name only
+                let op = match op {
+                    Operand::Parameter(param) => format!("%{}", param.name),
+                    _ => op.to_string(),
+                };
+                if *coef == 1 {
+                    op
+                } else {
+                    format!("{} * {}", coef, op)
+                }
+            })
+            .collect::<Vec<_>>();
+        if self.constant != 0 || parts.is_empty() {
+            parts.push(self.constant.to_string());
+        }
+        write!(f, "{}", parts.join(" + "))
+    }
+}
+
+/// Evolution of an integer value in a loop.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scev {
+    /// Value that does not change in the loop
+    Invariant(Affine),
+    /// Add recurrence `{start,+,step}`, which is `start` at the first iteration
+    /// and increases by `step` at each iteration
+    AddRec { start: Affine, step: Affine },
+}
+
+impl Scev {
+    pub fn add(&self, another: &Scev) -> Scev {
+        match (self, another) {
+            (Scev::Invariant(a), Scev::Invariant(b)) => Scev::Invariant(a.add(b)),
+            (Scev::AddRec { start, step }, Scev::Invariant(a))
+            | (Scev::Invariant(a), Scev::AddRec { start, step }) => Scev::AddRec {
+                start: start.add(a),
+                step: step.clone(),
+            },
+            (
+                Scev::AddRec { start, step },
+                Scev::AddRec {
+                    start: start2,
+                    step: step2,
+                },
+            ) => Scev::AddRec {
+                start: start.add(start2),
+                step: step.add(step2),
+            },
+        }
+    }
+
+    pub fn scale(&self, factor: i64) -> Scev {
+        match self {
+            Scev::Invariant(a) => Scev::Invariant(a.scale(factor)),
+            Scev::AddRec { start, step } => Scev::AddRec {
+                start: start.scale(factor),
+                step: step.scale(factor),
+            },
+        }
+    }
+
+    /// Multiply two evolutions, only affine result is supported.
+    pub fn mul(&self, another: &Scev) -> Option<Scev> {
+        let get_constant = |scev: &Scev| match scev {
+            Scev::Invariant(a) => a.get_constant(),
+            Scev::AddRec { .. } => None,
+        };
+        if let Some(factor) = get_constant(another) {
+            Some(self.scale(factor))
+        } else {
+            get_constant(self).map(|factor| another.scale(factor))
+        }
+    }
+}
+
+impl Display for Scev {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scev::Invariant(a) => write!(f, "{}", a),
+            Scev::AddRec { start, step } => write!(f, "{{{},+,{}}}", start, step),
+        }
+    }
+}
+
+/// Scalar evolution analysis, which describes integer values in loops as add recurrences.
+/// Results are cached, so the analysis should be rebuilt after changing the loop.
+#[derive(Default)]
+pub struct ScalarEvolution {
+    cache: HashMap<(LoopPtr, InstPtr), Option<Scev>>,
+}
+
+impl ScalarEvolution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get evolution of integer operand in loop, return None if it's not affine.
+    pub fn get_scev(&mut self, lo: LoopPtr, op: &Operand) -> Option<Scev> {
+        self.compute(lo, op, None)
+    }
+
+    /// Check if operand does not change in loop.
+    pub fn is_invariant(&mut self, lo: LoopPtr, op: &Operand) -> bool {
+        match op {
+            Operand::Instruction(inst) if is_in_loop(lo, *inst) => {
+                matches!(self.get_scev(lo, op), Some(Scev::Invariant(_)))
+            }
+            _ => true,
+        }
+    }
+
+    /// Get number of iterations of loop, which is the number of times the back edge is taken.
+    /// Loop should only exit from its header, and exit condition should compare an add
+    /// recurrence with a loop invariant, both known as constants.
+    pub fn get_trip_count(&mut self, lo: LoopPtr) -> Option<usize> {
+        let header = lo.head;
+        for bb in get_loop_blocks(lo) {
+            if bb != header && bb.get_succ_bb().iter().any(|succ| !lo.is_in_loop(succ)) {
+                return None;
+            }
+        }
+
+        // Get exit condition from header
+        let br = header.get_last_inst();
+        let [true_bb, false_bb] = header.get_succ_bb()[..] else {
+            return None;
+        };
+        let Some(Operand::Instruction(cond)) = br.get_operand().first() else {
+            return None;
+        };
+        if cond.get_type() != InstType::ICmp {
+            return None;
+        }
+        let icmp = downcast_ref::<ICmp>(cond.as_ref().as_ref());
+        let lhs = self.get_scev(lo, icmp.get_lhs())?;
+        let rhs = self.get_scev(lo, icmp.get_rhs())?;
+
+        // Normalize to `{start,+,step} op bound`, where loop continues when condition holds
+        let (start, step, bound, op) = match (lhs, rhs) {
+            (Scev::AddRec { start, step }, Scev::Invariant(bound)) => (start, step, bound, icmp.op),
+            (Scev::Invariant(bound), Scev::AddRec { start, step }) => {
+                (start, step, bound, swap_op(icmp.op))
+            }
+            _ => return None,
+        };
+        let op = match (lo.is_in_loop(&true_bb), lo.is_in_loop(&false_bb)) {
+            (true, false) => op,
+            (false, true) => inverse_op(op),
+            _ => return None,
+        };
+        let start = start.get_constant()?;
+        let step = step.get_constant()?;
+        let bound = bound.get_constant()?;
+        count_iterations(op, start, step, bound).map(|count| count as usize)
+    }
+
+    /// Get value of operand when loop exits.
+    /// Only values in header are known, because other blocks don't run at the last iteration.
+    /// Add recurrences are only known when trip count is constant.
+    pub fn get_final_value(&mut self, lo: LoopPtr, op: &Operand) -> Option<Affine> {
+        match self.get_scev(lo, op)? {
+            Scev::Invariant(a) => Some(a),
+            Scev::AddRec { start, step } => {
+                let Operand::Instruction(inst) = op else {
+                    return None;
+                };
+                if inst.get_parent_bb()? != lo.head {
+                    return None;
+                }
+                let trip_count = self.get_trip_count(lo)? as i64;
+                Some(start.add(&step.scale(trip_count)))
+            }
+        }
+    }
+
+    /// Dump evolution of values, trip count and final values of header to string.
+    pub fn dump(&mut self, lo: LoopPtr) -> String {
+        let mut res = format!("loop {}:\n", lo.head.name);
+        let mut blocks = get_loop_blocks(lo);
+        blocks.sort();
+        for bb in blocks {
+            for inst in bb.iter() {
+                if let Some(scev) = self.get_scev(lo, &inst.into()) {
+                    res += &format!("    {} = {}\n", inst, scev);
+                }
+            }
+        }
+        match self.get_trip_count(lo) {
+            Some(trip_count) => res += &format!("    trip count: {}\n", trip_count),
+            None => res += "    trip count: unknown\n",
+        }
+        for inst in lo.head.iter() {
+            if let Some(value) = self.get_final_value(lo, &inst.into()) {
+                res += &format!("    final {} = {}\n", inst, value);
+            }
+        }
+        res
+    }
+
+    /// Compute evolution of operand. When solving recurrence of `symbol`,
+    /// the phi stands for itself, and results are not cached.
+    fn compute(&mut self, lo: LoopPtr, op: &Operand, symbol: Option<InstPtr>) -> Option<Scev> {
+        let inst = match op {
+            Operand::Constant(Constant::Int(value)) => {
+                return Some(Scev::Invariant(Affine::constant(*value as i64)))
+            }
+            Operand::Instruction(inst) => *inst,
+            _ if op.get_type() == ValueType::Int => {
+                return Some(Scev::Invariant(Affine::value(op.clone())))
+            }
+            _ => return None,
+        };
+        if inst.get_value_type() != ValueType::Int {
+            return None;
+        }
+        if Some(inst) == symbol || !is_in_loop(lo, inst) {
+            return Some(Scev::Invariant(Affine::value(op.clone())));
+        }
+        if symbol.is_none() {
+            if let Some(scev) = self.cache.get(&(lo, inst)) {
+                return scev.clone();
+            }
+        }
+        let scev = self.compute_inst(lo, inst, symbol);
+        if symbol.is_none() {
+            self.cache.insert((lo, inst), scev.clone());
+        }
+        scev
+    }
+
+    fn compute_inst(
+        &mut self,
+        lo: LoopPtr,
+        inst: InstPtr,
+        symbol: Option<InstPtr>,
+    ) -> Option<Scev> {
+        let operands = inst.get_operand();
+        match inst.get_type() {
+            InstType::Add => {
+                let lhs = self.compute(lo, &operands[0], symbol)?;
+                let rhs = self.compute(lo, &operands[1], symbol)?;
+                Some(lhs.add(&rhs))
+            }
+            InstType::Sub => {
+                let lhs = self.compute(lo, &operands[0], symbol)?;
+                let rhs = self.compute(lo, &operands[1], symbol)?;
+                Some(lhs.add(&rhs.scale(-1)))
+            }
+            InstType::Mul => {
+                let lhs = self.compute(lo, &operands[0], symbol)?;
+                let rhs = self.compute(lo, &operands[1], symbol)?;
+                lhs.mul(&rhs)
+            }
+            InstType::Shl => {
+                let lhs = self.compute(lo, &operands[0], symbol)?;
+                let Operand::Constant(Constant::Int(shift @ 0..=30)) = operands[1] else {
+                    return None;
+                };
+                Some(lhs.scale(1 << shift))
+            }
+            // Recurrence of another phi depending on `symbol` is not affine
+            InstType::Phi if symbol.is_none() && inst.get_parent_bb() == Some(lo.head) => {
+                self.solve_phi(lo, inst)
+            }
+            _ => None,
+        }
+    }
+
+    /// Solve header phi shaped like `phi = [start, outside], [phi + step, inside]`.
+    fn solve_phi(&mut self, lo: LoopPtr, inst: InstPtr) -> Option<Scev> {
+        let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+        let [(a, a_bb), (b, b_bb)] = phi.get_incoming_values() else {
+            return None;
+        };
+        let (start, next) = match (lo.is_in_loop(a_bb), lo.is_in_loop(b_bb)) {
+            (false, true) => (a, b),
+            (true, false) => (b, a),
+            _ => return None,
+        };
+        let Scev::Invariant(start) = self.compute(lo, start, None)? else {
+            return None;
+        };
+        let Scev::Invariant(next) = self.compute(lo, next, Some(inst))? else {
+            return None;
+        };
+
+        // Next value should contain phi exactly once, and the rest is step
+        let phi_op = Operand::Instruction(inst);
+        let (recur, terms): (Vec<_>, Vec<_>) =
+            next.terms.into_iter().partition(|(op, _)| *op == phi_op);
+        if recur != [(phi_op, 1)] {
+            return None;
+        }
+        let step = Affine {
+            constant: next.constant,
+            terms,
+        };
+        Some(Scev::AddRec { start, step })
+    }
+}
+
+fn is_in_loop(lo: LoopPtr, inst: InstPtr) -> bool {
+    inst.get_parent_bb().is_some_and(|bb| lo.is_in_loop(&bb))
+}
+
+/// Get blocks of loop, including blocks of sub loops.
+fn get_loop_blocks(lo: LoopPtr) -> Vec<BBPtr> {
+    let mut blocks = lo.blocks.iter().cloned().collect::<Vec<_>>();
+    for sub_loop in lo.sub_loops.iter() {
+        blocks.extend(get_loop_blocks(*sub_loop));
+    }
+    blocks
+}
+
+/// Get operator that gives the same result with swapped operands.
+fn swap_op(op: ICmpOp) -> ICmpOp {
+    match op {
+        ICmpOp::Slt => ICmpOp::Sgt,
+        ICmpOp::Sle => ICmpOp::Sge,
+        ICmpOp::Sgt => ICmpOp::Slt,
+        ICmpOp::Sge => ICmpOp::Sle,
+        ICmpOp::Ult => ICmpOp::Ugt,
+        ICmpOp::Ule => ICmpOp::Uge,
+        ICmpOp::Ugt => ICmpOp::Ult,
+        ICmpOp::Uge => ICmpOp::Ule,
+        ICmpOp::Eq | ICmpOp::Ne => op,
+    }
+}
+
+/// Get operator that gives the opposite result.
+fn inverse_op(op: ICmpOp) -> ICmpOp {
+    match op {
+        ICmpOp::Eq => ICmpOp::Ne,
+        ICmpOp::Ne => ICmpOp::Eq,
+        ICmpOp::Slt => ICmpOp::Sge,
+        ICmpOp::Sle => ICmpOp::Sgt,
+        ICmpOp::Sgt => ICmpOp::Sle,
+        ICmpOp::Sge => ICmpOp::Slt,
+        ICmpOp::Ult => ICmpOp::Uge,
+        ICmpOp::Ule => ICmpOp::Ugt,
+        ICmpOp::Ugt => ICmpOp::Ule,
+        ICmpOp::Uge => ICmpOp::Ult,
+    }
+}
+
+/// Count iterations `k` before `start + k * step op bound` first fails.
+fn count_iterations(op: ICmpOp, start: i64, step: i64, bound: i64) -> Option<i64> {
+    let holds = match op {
+        ICmpOp::Eq => start == bound,
+        ICmpOp::Ne => start != bound,
+        ICmpOp::Slt => start < bound,
+        ICmpOp::Sle => start <= bound,
+        ICmpOp::Sgt => start > bound,
+        ICmpOp::Sge => start >= bound,
+        _ => return None,
+    };
+    if !holds {
+        return Some(0);
+    }
+    match op {
+        ICmpOp::Slt if step > 0 => Some((bound - start + step - 1) / step),
+        ICmpOp::Sle if step > 0 => Some((bound - start) / step + 1),
+        ICmpOp::Sgt if step < 0 => Some((start - bound - step - 1) / -step),
+        ICmpOp::Sge if step < 0 => Some((start - bound) / -step + 1),
+        ICmpOp::Ne if step != 0 && (bound - start) % step == 0 && (bound - start) / step > 0 => {
+            Some((bound - start) / step)
+        }
+        ICmpOp::Eq if step != 0 => Some(1),
+        _ => None,
+    }
+}
@@ -23,6 +23,7 @@ use crate::{
     backend::from_self::downcast_ref,
     middle::ir::{
         instruction::{
+            memory_op_inst::GetElementPtr,
             misc_inst::{Call, FCmp, ICmp},
             InstType,
         },
@@ -119,8 +120,15 @@ impl<'a> SimpleGVN<'a> {
             cmp.op.hash(&mut hasher);
         }
 
-        // Hash instruction type
+        // Hash element type for GEP, union members share the same indices
+        if ty == InstType::GetElementPtr {
+            let gep = downcast_ref::<GetElementPtr>(inst.as_ref().as_ref());
+            gep.element_type.hash(&mut hasher);
+        }
+
+        // Hash instruction type and value type, casts to different types differ
         inst.get_type().hash(&mut hasher);
+        inst.get_value_type().hash(&mut hasher);
 
         // Hash number of operands in canonical order
         let mut numbers = inst
@@ -209,6 +217,15 @@ impl<'a> PartialEq for Expr<'a> {
                     }
                 }
 
+                // Compare element type for GEP
+                if ty == InstType::GetElementPtr {
+                    let gep1 = downcast_ref::<GetElementPtr>(inst1.as_ref().as_ref());
+                    let gep2 = downcast_ref::<GetElementPtr>(inst2.as_ref().as_ref());
+                    if gep1.element_type != gep2.element_type {
+                        return false;
+                    }
+                }
+
                 // If number of operands is not the same, their value is not the same
                 if inst1.get_operand().len() != inst2.get_operand().len() {
                     return false;
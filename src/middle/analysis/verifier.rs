@@ -0,0 +1,395 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    context,
+    middle::{
+        ir::{
+            instruction::{
+                downcast_ref,
+                memory_op_inst::{Load, Store},
+                misc_inst::{Call, Phi},
+                terminator_inst::{Br, Ret, Switch},
+                InstType,
+            },
+            BBPtr, FunPtr, InstPtr, Operand, ValueType,
+        },
+        Program,
+    },
+};
+
+use super::{dominator_tree::DominatorTree, reachability::Reachability};
+
+/// Verify every function of the program, see `verify_function`.
+pub fn verify_program(program: &Program) -> Result<()> {
+    let errors = program
+        .module
+        .functions
+        .iter()
+        .filter(|func| !func.is_lib())
+        .flat_map(|func| Verifier::new(*func).verify())
+        .collect::<Vec<_>>();
+    report(errors)
+}
+
+/// Verify structural invariants of a function:
+/// 1. every block is terminated by exactly one terminator, and phis come first
+/// 2. successor and predecessor lists agree with each other and with the terminator
+/// 3. incoming blocks of each phi are exactly the predecessors of its block
+/// 4. operands and users of each instruction are symmetric
+/// 5. every instruction operand dominates its use
+/// 6. operand types agree with the instruction
+/// 7. exit block is reachable and ends with the return
+pub fn verify_function(func: FunPtr) -> Result<()> {
+    report(Verifier::new(func).verify())
+}
+
+fn report(errors: Vec<String>) -> Result<()> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("{}", errors.join("\n"))).with_context(|| context!())
+    }
+}
+
+struct Verifier {
+    func: FunPtr,
+    dom_tree: DominatorTree,
+    reachability: Reachability,
+    /// Position of each instruction in the function, as (block, index in block).
+    position: HashMap<usize, (BBPtr, usize)>,
+    errors: Vec<String>,
+}
+
+impl Verifier {
+    fn new(func: FunPtr) -> Self {
+        let mut position = HashMap::new();
+        for bb in func.dfs_iter() {
+            for (index, inst) in bb.iter().enumerate() {
+                position.insert(inst.get_id(), (bb, index));
+            }
+        }
+        Self {
+            func,
+            dom_tree: DominatorTree::new(func),
+            reachability: Reachability::new(func),
+            position,
+            errors: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, msg: String) {
+        self.errors.push(format!("@{}: {}", self.func.name, msg));
+    }
+
+    fn verify(mut self) -> Vec<String> {
+        self.verify_exit();
+        for bb in self.func.dfs_iter() {
+            self.verify_block(bb);
+            for inst in bb.iter() {
+                self.verify_use_def(inst);
+                self.verify_dominance(inst);
+                self.verify_type(inst);
+            }
+        }
+        self.errors
+    }
+
+    fn verify_exit(&mut self) {
+        let Some(exit) = self.func.exit else {
+            self.error("function has no exit".to_string());
+            return;
+        };
+        if !self.reachability.is_reachable(exit) {
+            self.error(format!("exit %{} is not reachable", exit.name));
+            return;
+        }
+
+        // Blocks without terminator are reported by `verify_block`
+        let ty = exit.get_last_inst().get_type();
+        if !exit.is_empty() && is_terminator(ty) && ty != InstType::Ret {
+            self.error(format!("exit %{} ends with {} instead of ret", exit.name, ty));
+        }
+    }
+
+    fn verify_block(&mut self, bb: BBPtr) {
+        if bb.is_empty() {
+            self.error(format!("block %{} is empty", bb.name));
+            return;
+        }
+
+        // Check placement of phis and terminators
+        let mut seen_non_phi = false;
+        for inst in bb.iter() {
+            let ty = inst.get_type();
+            if ty == InstType::Phi && seen_non_phi {
+                self.error(format!("{} is not at the start of %{}", inst, bb.name));
+            }
+            seen_non_phi |= ty != InstType::Phi;
+            if is_terminator(ty) && !inst.is_last() {
+                self.error(format!("{} is not at the end of %{}", inst, bb.name));
+            }
+        }
+        let last = bb.get_last_inst();
+        if !is_terminator(last.get_type()) {
+            self.error(format!("block %{} is not terminated", bb.name));
+            return;
+        }
+
+        // Check number of successors against the terminator
+        let expected = match last.get_type() {
+            InstType::Ret => 0,
+            InstType::Br if downcast_ref::<Br>(last.as_ref().as_ref()).is_cond_br() => 2,
+            InstType::Br => 1,
+            _ => {
+                downcast_ref::<Switch>(last.as_ref().as_ref())
+                    .get_cases()
+                    .len()
+                    + 1
+            }
+        };
+        if bb.get_succ_bb().len() != expected {
+            self.error(format!(
+                "block %{} has {} successors, but {} expects {}",
+                bb.name,
+                bb.get_succ_bb().len(),
+                last.get_type(),
+                expected
+            ));
+        }
+
+        // Check symmetry of successors and predecessors
+        for succ in bb.get_succ_bb() {
+            if !succ.get_pred_bb().contains(&bb) {
+                self.error(format!(
+                    "%{} is a successor of %{}, but not the other way round",
+                    succ.name, bb.name
+                ));
+            }
+        }
+        for pred in bb.get_pred_bb() {
+            if !pred.get_succ_bb().contains(&bb) {
+                self.error(format!(
+                    "%{} is a predecessor of %{}, but not the other way round",
+                    pred.name, bb.name
+                ));
+            }
+        }
+
+        // Check incoming blocks of phis, unreachable predecessors may be omitted
+        let preds = bb.get_pred_bb().iter().copied().collect::<HashSet<_>>();
+        for inst in bb.iter() {
+            if inst.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+            let incoming = phi
+                .get_incoming_values()
+                .iter()
+                .map(|(_, bb)| *bb)
+                .collect::<HashSet<_>>();
+            let missing = preds
+                .iter()
+                .any(|pred| self.reachability.is_reachable(*pred) && !incoming.contains(pred));
+            if missing
+                || !incoming.is_subset(&preds)
+                || phi.get_incoming_values().len() != incoming.len()
+            {
+                self.error(format!(
+                    "incoming blocks of {} are [{}], but predecessors of %{} are [{}]",
+                    inst,
+                    block_names(phi.get_incoming_values().iter().map(|(_, bb)| *bb)),
+                    bb.name,
+                    block_names(bb.get_pred_bb().iter().copied())
+                ));
+            }
+        }
+    }
+
+    fn verify_use_def(&mut self, inst: InstPtr) {
+        let id = inst.get_id();
+        for op in inst.get_operand() {
+            let has_user = match op {
+                Operand::Instruction(def) => def.get_user().iter().any(|u| u.get_id() == id),
+                Operand::Parameter(param) => param.get_user().iter().any(|u| u.get_id() == id),
+                Operand::Global(gvar) => gvar.get_user().iter().any(|u| u.get_id() == id),
+                Operand::Constant(_) => true,
+            };
+            if !has_user {
+                self.error(format!("{} uses {}, but is not its user", inst, op));
+            }
+        }
+        for user in inst.get_user() {
+            if user.get_parent_bb().is_none() {
+                self.error(format!("{} is used by removed instruction {}", inst, user));
+            } else if !user.get_operand().contains(&Operand::Instruction(inst)) {
+                self.error(format!(
+                    "{} is a user of {}, but does not use it",
+                    user, inst
+                ));
+            }
+        }
+    }
+
+    fn verify_dominance(&mut self, inst: InstPtr) {
+        let (bb, index) = self.position[&inst.get_id()];
+        if !self.reachability.is_reachable(bb) {
+            return;
+        }
+        let incoming = if inst.get_type() == InstType::Phi {
+            let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+            phi.get_incoming_values()
+                .iter()
+                .map(|(_, bb)| Some(*bb))
+                .collect()
+        } else {
+            vec![None; inst.get_operand().len()]
+        };
+        for (op, pred) in inst.get_operand().iter().zip(incoming) {
+            let Operand::Instruction(def) = op else {
+                continue;
+            };
+            let Some(&(def_bb, def_index)) = self.position.get(&def.get_id()) else {
+                self.error(format!("{} uses {} outside of the function", inst, def));
+                continue;
+            };
+            let dominated = match pred {
+                // Operand of phi should dominate the end of incoming block
+                Some(pred) => {
+                    !self.reachability.is_reachable(pred) || self.dom_tree.is_dominate(def_bb, pred)
+                }
+                None if def_bb == bb => def_index < index,
+                None => self.dom_tree.is_dominate(def_bb, bb),
+            };
+            if !dominated {
+                self.error(format!("{} does not dominate its use in {}", def, inst));
+            }
+        }
+    }
+
+    fn verify_type(&mut self, inst: InstPtr) {
+        let ops = inst.get_operand();
+        let expect_same = |a: &ValueType, b: &ValueType| a.to_string() == b.to_string();
+        let mismatch = match inst.get_type() {
+            InstType::Add
+            | InstType::Sub
+            | InstType::Mul
+            | InstType::UDiv
+            | InstType::SDiv
+            | InstType::URem
+            | InstType::SRem
+            | InstType::Shl
+            | InstType::LShr
+            | InstType::AShr
+            | InstType::And
+            | InstType::Or
+            | InstType::Xor
+            | InstType::ICmp => {
+                let (lhs, rhs) = (ops[0].get_type(), ops[1].get_type());
+                (!expect_same(&lhs, &rhs) || lhs.is_float())
+                    .then(|| format!("operands have types {} and {}", lhs, rhs))
+            }
+            InstType::FAdd | InstType::FSub | InstType::FMul | InstType::FDiv | InstType::FCmp => {
+                let (lhs, rhs) = (ops[0].get_type(), ops[1].get_type());
+                (!expect_same(&lhs, &rhs) || !lhs.is_float())
+                    .then(|| format!("operands have types {} and {}", lhs, rhs))
+            }
+            InstType::Load => {
+                let load = downcast_ref::<Load>(inst.as_ref().as_ref());
+                let ptr = load.get_ptr().get_type();
+                match ptr.get_sub_type() {
+                    Some(ty) if ptr.is_pointer() && expect_same(ty, &inst.get_value_type()) => None,
+                    _ => Some(format!("loads {} from {}", inst.get_value_type(), ptr)),
+                }
+            }
+            InstType::Store => {
+                let store = downcast_ref::<Store>(inst.as_ref().as_ref());
+                let (value, ptr) = (store.get_value().get_type(), store.get_ptr().get_type());
+                match ptr.get_sub_type() {
+                    Some(ty) if ptr.is_pointer() && expect_same(ty, &value) => None,
+                    _ => Some(format!("stores {} to {}", value, ptr)),
+                }
+            }
+            InstType::Br if !ops.is_empty() => {
+                let cond = ops[0].get_type();
+                (cond != ValueType::Bool).then(|| format!("condition has type {}", cond))
+            }
+            InstType::Switch => {
+                let cond = ops[0].get_type();
+                (!cond.is_integer()).then(|| format!("condition has type {}", cond))
+            }
+            InstType::Ret => {
+                let ret = downcast_ref::<Ret>(inst.as_ref().as_ref());
+                let ty = if ret.is_void() {
+                    ValueType::Void
+                } else {
+                    ret.get_return_value().get_type()
+                };
+                (!expect_same(&ty, &self.func.return_type)).then(|| {
+                    format!(
+                        "returns {}, but function returns {}",
+                        ty, self.func.return_type
+                    )
+                })
+            }
+            InstType::Phi => {
+                let ty = inst.get_value_type();
+                ops.iter()
+                    .find(|op| !expect_same(&op.get_type(), &ty))
+                    .map(|op| format!("incoming value {} has type {}", op, op.get_type()))
+            }
+            InstType::Call => {
+                let call = downcast_ref::<Call>(inst.as_ref().as_ref());
+                let params = &call.func.params;
+                if call.func.is_lib() {
+                    None
+                } else if params.len() != ops.len() {
+                    Some(format!(
+                        "passes {} arguments to @{} with {} parameters",
+                        ops.len(),
+                        call.func.name,
+                        params.len()
+                    ))
+                } else {
+                    ops.iter()
+                        .zip(params.iter())
+                        .find(|(op, param)| !expect_same(&op.get_type(), &param.value_type))
+                        .map(|(op, param)| {
+                            format!("passes {} to {}", op.get_type(), param.as_ref())
+                        })
+                }
+            }
+            _ => None,
+        };
+        if let Some(msg) = mismatch {
+            self.error(format!("type mismatch in {}: {}", inst.gen_llvm_ir(), msg));
+        }
+    }
+}
+
+fn is_terminator(ty: InstType) -> bool {
+    matches!(ty, InstType::Ret | InstType::Br | InstType::Switch)
+}
+
+fn block_names(bbs: impl Iterator<Item = BBPtr>) -> String {
+    bbs.map(|bb| format!("%{}", bb.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -44,12 +44,15 @@ pub struct BasicBlock {
     pred_bbs: Vec<BBPtr>,
 
     /// The successor `BasicBlock` of the `BasicBlock`.
-    /// The number of successor `BasicBlocks` can theoretically be 0, 1, and 2:
+    /// The number of successor `BasicBlocks` can theoretically be 0, 1, 2, and more:
     /// 1. When the number of successor `BasicBlocks` is 0, the `BasicBlock` is the function exit `BasicBlock`.
     /// 2. When the number of successor `BasicBlocks` is 1, the last instruction of the `BasicBlock` is an unconditional jump instruction.
     /// 3. When the number of successor `BasicBlocks` is 2, the last instruction of the `BasicBlock` is a conditional jump instruction.
     ///    + The `BasicBlock` with index 0 is the `BasicBlock` to jump to when the condition is true.
     ///    + The `BasicBlock` with index 1 is the `BasicBlock` to jump to when the condition is false.
+    /// 4. When the last instruction of the `BasicBlock` is a switch instruction:
+    ///    + The `BasicBlock` with index 0 is the `BasicBlock` to jump to when no case matches.
+    ///    + The `BasicBlock` with index i + 1 is the `BasicBlock` to jump to when i-th case matches.
     succ_bbs: Vec<BBPtr>,
 }
 
@@ -273,6 +276,21 @@ impl BasicBlock {
         bb.pred_bbs.push(self_ptr);
     }
 
+    /// Appends a `BasicBlock` to jump to, used by instructions with arbitrary number of successors.
+    /// For switch instruction, the default `BasicBlock` should be appended first.
+    pub fn push_succ_bb(&mut self, mut bb: BBPtr) {
+        let self_ptr = ObjPtr::new(self);
+        self.succ_bbs.push(bb);
+        bb.pred_bbs.push(self_ptr);
+    }
+
+    /// Remove every edge from this block to `bb`, used by instructions with arbitrary number of successors.
+    pub fn remove_succ_bb(&mut self, mut bb: BBPtr) {
+        let self_ptr = ObjPtr::new(self);
+        bb.remove_pred_bb(self_ptr);
+        self.succ_bbs.retain(|x| x.id != bb.id);
+    }
+
     /// Remove basic block to jump to when the condition is false.
     /// This will only execute when false bb exists.
     pub fn remove_false_bb(&mut self) {
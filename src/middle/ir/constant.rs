@@ -28,9 +28,14 @@ use super::*;
 pub enum Constant {
     SignedChar(i8),
     Int(i32),
+    UnsignedInt(u32),
+    Long(i64),
+    UnsignedLong(u64),
     Float(f32),
+    Double(f64),
     Bool(bool),
     Array(Vec<Constant>),
+    Struct(Record, Vec<Constant>),
     Zero(ValueType),
 }
 
@@ -39,10 +44,15 @@ impl PartialEq for Constant {
         match (self, other) {
             (Constant::SignedChar(c1), Constant::SignedChar(c2)) => c1 == c2,
             (Constant::Int(i1), Constant::Int(i2)) => i1 == i2,
+            (Constant::UnsignedInt(u1), Constant::UnsignedInt(u2)) => u1 == u2,
+            (Constant::Long(l1), Constant::Long(l2)) => l1 == l2,
+            (Constant::UnsignedLong(u1), Constant::UnsignedLong(u2)) => u1 == u2,
             // Compare float in bits to have `Eq` trait implemented
             (Constant::Float(f1), Constant::Float(f2)) => f1.to_bits() == f2.to_bits(),
+            (Constant::Double(d1), Constant::Double(d2)) => d1.to_bits() == d2.to_bits(),
             (Constant::Bool(b1), Constant::Bool(b2)) => b1 == b2,
             (Constant::Array(arr1), Constant::Array(arr2)) => arr1 == arr2,
+            (Constant::Struct(r1, f1), Constant::Struct(r2, f2)) => r1 == r2 && f1 == f2,
             (Constant::Zero(t1), Constant::Zero(t2)) => t1 == t2,
             _ => false,
         }
@@ -56,9 +66,17 @@ impl Hash for Constant {
         match self {
             Constant::SignedChar(c) => c.hash(state),
             Constant::Int(i) => i.hash(state),
+            Constant::UnsignedInt(u) => u.hash(state),
+            Constant::Long(l) => l.hash(state),
+            Constant::UnsignedLong(u) => u.hash(state),
             Constant::Float(f) => f.to_bits().hash(state),
+            Constant::Double(d) => d.to_bits().hash(state),
             Constant::Bool(b) => b.hash(state),
             Constant::Array(arr) => arr.hash(state),
+            Constant::Struct(r, fields) => {
+                r.hash(state);
+                fields.hash(state);
+            }
             Constant::Zero(t) => t.hash(state),
         }
     }
@@ -69,6 +87,10 @@ impl std::fmt::Display for Constant {
         match self {
             Constant::SignedChar(c) => write!(f, "{}", c),
             Constant::Int(i) => write!(f, "{}", i),
+            // LLVM integers are signless, so unsigned values are written in two's complement
+            Constant::UnsignedInt(u) => write!(f, "{}", *u as i32),
+            Constant::Long(l) => write!(f, "{}", l),
+            Constant::UnsignedLong(u) => write!(f, "{}", *u as i64),
             Constant::Float(fl) => {
                 // write float in hexidemal form (IEEE-754) like 0x1234567800000000
                 let bytes = (*fl as f64).to_le_bytes();
@@ -78,6 +100,7 @@ impl std::fmt::Display for Constant {
                 }
                 Ok(())
             }
+            Constant::Double(d) => write!(f, "0x{:016x}", d.to_bits()),
             Constant::Bool(b) => write!(f, "{}", b),
             Constant::Array(arr) => {
                 write!(f, "[")?;
@@ -89,6 +112,16 @@ impl std::fmt::Display for Constant {
                 }
                 write!(f, "]")
             }
+            Constant::Struct(_, fields) => {
+                write!(f, "{{ ")?;
+                for (i, c) in fields.iter().enumerate() {
+                    write!(f, "{} {}", c.get_type(), c)?;
+                    if i != fields.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+                write!(f, " }}")
+            }
             Constant::Zero(_) => write!(f, "zeroinitializer"),
         }
     }
@@ -99,12 +132,17 @@ impl Constant {
         match self {
             Constant::SignedChar(_) => ValueType::SignedChar,
             Constant::Int(_) => ValueType::Int,
+            Constant::UnsignedInt(_) => ValueType::UnsignedInt,
+            Constant::Long(_) => ValueType::Long,
+            Constant::UnsignedLong(_) => ValueType::UnsignedLong,
             Constant::Float(_) => ValueType::Float,
+            Constant::Double(_) => ValueType::Double,
             Constant::Bool(_) => ValueType::Bool,
             Constant::Array(arr) => {
                 let sub_type = arr.first().unwrap().get_type();
                 ValueType::Array(Box::new(sub_type), arr.len())
             }
+            Constant::Struct(r, _) => ValueType::Struct(r.clone()),
             Constant::Zero(t) => t.clone(),
         }
     }
@@ -129,9 +167,27 @@ impl From<i32> for Constant {
     }
 }
 
+impl From<i8> for Constant {
+    fn from(c: i8) -> Self {
+        Self::SignedChar(c)
+    }
+}
+
 impl From<u32> for Constant {
     fn from(u: u32) -> Self {
-        Self::Int(u as i32)
+        Self::UnsignedInt(u)
+    }
+}
+
+impl From<i64> for Constant {
+    fn from(l: i64) -> Self {
+        Self::Long(l)
+    }
+}
+
+impl From<u64> for Constant {
+    fn from(u: u64) -> Self {
+        Self::UnsignedLong(u)
     }
 }
 
@@ -141,50 +197,56 @@ impl From<f32> for Constant {
     }
 }
 
-impl From<bool> for Constant {
-    fn from(b: bool) -> Self {
-        Self::Bool(b)
+impl From<f64> for Constant {
+    fn from(d: f64) -> Self {
+        Self::Double(d)
     }
 }
 
-impl From<Constant> for i32 {
-    fn from(val: Constant) -> Self {
-        match val {
-            Constant::Int(x) => x,
-            Constant::Float(x) => x as i32,
-            Constant::Bool(x) => x as i32,
-            _ => panic!("Cannot cast {} to i32", val),
-        }
+impl From<bool> for Constant {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
     }
 }
 
-impl From<Constant> for u32 {
-    fn from(val: Constant) -> Self {
-        match val {
-            Constant::Int(x) => x as u32,
-            Constant::Float(x) => x as u32,
-            Constant::Bool(x) => x as u32,
-            _ => panic!("Cannot cast {} to u32", val),
+/// Convert numeric constant to a primitive with `as` semantics.
+macro_rules! impl_from_constant {
+    ($ty:ty) => {
+        impl From<Constant> for $ty {
+            fn from(val: Constant) -> Self {
+                match val {
+                    Constant::SignedChar(x) => x as $ty,
+                    Constant::Int(x) => x as $ty,
+                    Constant::UnsignedInt(x) => x as $ty,
+                    Constant::Long(x) => x as $ty,
+                    Constant::UnsignedLong(x) => x as $ty,
+                    Constant::Float(x) => x as $ty,
+                    Constant::Double(x) => x as $ty,
+                    Constant::Bool(x) => x as i32 as $ty,
+                    _ => panic!("Cannot cast {} to {}", val, stringify!($ty)),
+                }
+            }
         }
-    }
+    };
 }
 
-impl From<Constant> for f32 {
-    fn from(val: Constant) -> Self {
-        match val {
-            Constant::Int(x) => x as f32,
-            Constant::Float(x) => x,
-            Constant::Bool(x) => x as i32 as f32,
-            _ => panic!("Cannot cast {} to f32", val),
-        }
-    }
-}
+impl_from_constant!(i32);
+impl_from_constant!(u32);
+impl_from_constant!(i64);
+impl_from_constant!(u64);
+impl_from_constant!(f32);
+impl_from_constant!(f64);
 
 impl From<Constant> for bool {
     fn from(val: Constant) -> Self {
         match val {
+            Constant::SignedChar(x) => x != 0,
             Constant::Int(x) => x != 0,
+            Constant::UnsignedInt(x) => x != 0,
+            Constant::Long(x) => x != 0,
+            Constant::UnsignedLong(x) => x != 0,
             Constant::Float(x) => x != 0.0,
+            Constant::Double(x) => x != 0.0,
             Constant::Bool(x) => x,
             _ => panic!("Cannot cast {} to bool", val),
         }
@@ -195,8 +257,13 @@ impl Constant {
     pub fn cast(self, ty: &ValueType) -> Self {
         match ty {
             ValueType::Int => Into::<i32>::into(self).into(),
+            ValueType::UnsignedInt => Into::<u32>::into(self).into(),
+            ValueType::Long => Into::<i64>::into(self).into(),
+            ValueType::UnsignedLong => Into::<u64>::into(self).into(),
             ValueType::Float => Into::<f32>::into(self).into(),
+            ValueType::Double => Into::<f64>::into(self).into(),
             ValueType::Bool => Into::<bool>::into(self).into(),
+            ValueType::SignedChar => (Into::<i32>::into(self) as i8).into(),
             ValueType::Array(element_ty, _) => {
                 let arr = match self {
                     Constant::Array(arr) => arr,
@@ -217,7 +284,11 @@ impl ops::Neg for Constant {
         let ty = self.get_type();
         match ty {
             ValueType::Float => (-Into::<f32>::into(self)).into(),
+            ValueType::Double => (-Into::<f64>::into(self)).into(),
             ValueType::Int | ValueType::Bool => (-Into::<i32>::into(self)).into(),
+            ValueType::UnsignedInt => Into::<u32>::into(self).wrapping_neg().into(),
+            ValueType::Long => Into::<i64>::into(self).wrapping_neg().into(),
+            ValueType::UnsignedLong => Into::<u64>::into(self).wrapping_neg().into(),
             _ => todo!(),
         }
     }
@@ -231,113 +302,89 @@ impl ops::Not for Constant {
     }
 }
 
-impl ops::Add for Constant {
-    type Output = Constant;
-
-    fn add(self, rhs: Constant) -> Self::Output {
-        let ty = self.get_type();
-        match ty {
-            ValueType::Float => (Into::<f32>::into(self) + Into::<f32>::into(rhs)).into(),
-            ValueType::Int | ValueType::Bool => {
-                (Into::<i32>::into(self).wrapping_add(Into::<i32>::into(rhs))).into()
-            }
-            _ => todo!(),
-        }
-    }
-}
-
-impl ops::Sub for Constant {
-    type Output = Constant;
-
-    fn sub(self, rhs: Constant) -> Self::Output {
-        let ty = self.get_type();
-        match ty {
-            ValueType::Float => (Into::<f32>::into(self) - Into::<f32>::into(rhs)).into(),
-            ValueType::Int | ValueType::Bool => {
-                (Into::<i32>::into(self).wrapping_sub(Into::<i32>::into(rhs))).into()
-            }
-            _ => todo!(),
-        }
-    }
-}
-
-impl ops::Mul for Constant {
-    type Output = Constant;
-
-    fn mul(self, rhs: Constant) -> Self::Output {
-        let ty = self.get_type();
-        match ty {
-            ValueType::Float => (Into::<f32>::into(self) * Into::<f32>::into(rhs)).into(),
-            ValueType::Int | ValueType::Bool => {
-                (Into::<i32>::into(self).wrapping_mul(Into::<i32>::into(rhs))).into()
+/// Implement an arithmetic operator, computed in the type of left operand.
+macro_rules! impl_num_op {
+    ($trait:ident, $func:ident, |$lhs:ident, $rhs:ident| $int_body:expr, $float_body:expr) => {
+        impl ops::$trait for Constant {
+            type Output = Constant;
+
+            fn $func(self, rhs: Constant) -> Self::Output {
+                let ty = self.get_type();
+                match ty {
+                    ValueType::Float => {
+                        let ($lhs, $rhs) = (Into::<f32>::into(self), Into::<f32>::into(rhs));
+                        ($float_body).into()
+                    }
+                    ValueType::Double => {
+                        let ($lhs, $rhs) = (Into::<f64>::into(self), Into::<f64>::into(rhs));
+                        ($float_body).into()
+                    }
+                    ValueType::Int | ValueType::Bool => {
+                        let ($lhs, $rhs) = (Into::<i32>::into(self), Into::<i32>::into(rhs));
+                        ($int_body).into()
+                    }
+                    ValueType::UnsignedInt => {
+                        let ($lhs, $rhs) = (Into::<u32>::into(self), Into::<u32>::into(rhs));
+                        ($int_body).into()
+                    }
+                    ValueType::Long => {
+                        let ($lhs, $rhs) = (Into::<i64>::into(self), Into::<i64>::into(rhs));
+                        ($int_body).into()
+                    }
+                    ValueType::UnsignedLong => {
+                        let ($lhs, $rhs) = (Into::<u64>::into(self), Into::<u64>::into(rhs));
+                        ($int_body).into()
+                    }
+                    _ => todo!(),
+                }
             }
-            _ => todo!(),
         }
-    }
+    };
 }
 
-impl ops::Div for Constant {
-    type Output = Constant;
-
-    fn div(self, rhs: Constant) -> Self::Output {
-        let ty = self.get_type();
-        match ty {
-            ValueType::Float => (Into::<f32>::into(self) / Into::<f32>::into(rhs)).into(),
-            ValueType::Int | ValueType::Bool => {
-                (Into::<i32>::into(self).wrapping_div(Into::<i32>::into(rhs))).into()
+/// Implement an integer operator, computed in the type of left operand.
+/// Types narrower than `i32` are computed as `i32`.
+macro_rules! impl_int_op {
+    ($trait:ident, $func:ident, |$lhs:ident, $rhs:ident| $body:expr) => {
+        impl ops::$trait for Constant {
+            type Output = Constant;
+
+            fn $func(self, rhs: Constant) -> Self::Output {
+                let ty = self.get_type();
+                match ty {
+                    ValueType::UnsignedInt => {
+                        let ($lhs, $rhs) = (Into::<u32>::into(self), Into::<u32>::into(rhs));
+                        ($body).into()
+                    }
+                    ValueType::Long => {
+                        let ($lhs, $rhs) = (Into::<i64>::into(self), Into::<i64>::into(rhs));
+                        ($body).into()
+                    }
+                    ValueType::UnsignedLong => {
+                        let ($lhs, $rhs) = (Into::<u64>::into(self), Into::<u64>::into(rhs));
+                        ($body).into()
+                    }
+                    _ => {
+                        let ($lhs, $rhs) = (Into::<i32>::into(self), Into::<i32>::into(rhs));
+                        ($body).into()
+                    }
+                }
             }
-            _ => todo!(),
         }
-    }
+    };
 }
 
-impl ops::Rem for Constant {
-    type Output = Constant;
-
-    fn rem(self, rhs: Constant) -> Self::Output {
-        (Into::<i32>::into(self).wrapping_rem(Into::<i32>::into(rhs))).into()
-    }
-}
-
-impl ops::Shl for Constant {
-    type Output = Constant;
-
-    fn shl(self, rhs: Constant) -> Self::Output {
-        (Into::<i32>::into(self).wrapping_shl(Into::<i32>::into(rhs) as u32)).into()
-    }
-}
-
-impl ops::Shr for Constant {
-    type Output = Constant;
-
-    fn shr(self, rhs: Constant) -> Self::Output {
-        (Into::<i32>::into(self).wrapping_shr(Into::<i32>::into(rhs) as u32)).into()
-    }
-}
-
-impl ops::BitAnd for Constant {
-    type Output = Constant;
-
-    fn bitand(self, rhs: Constant) -> Self::Output {
-        (Into::<i32>::into(self) & Into::<i32>::into(rhs)).into()
-    }
-}
-
-impl ops::BitOr for Constant {
-    type Output = Constant;
-
-    fn bitor(self, rhs: Constant) -> Self::Output {
-        (Into::<i32>::into(self) | Into::<i32>::into(rhs)).into()
-    }
-}
-
-impl ops::BitXor for Constant {
-    type Output = Constant;
-
-    fn bitxor(self, rhs: Constant) -> Self::Output {
-        (Into::<i32>::into(self) ^ Into::<i32>::into(rhs)).into()
-    }
-}
+impl_num_op!(Add, add, |lhs, rhs| lhs.wrapping_add(rhs), lhs + rhs);
+impl_num_op!(Sub, sub, |lhs, rhs| lhs.wrapping_sub(rhs), lhs - rhs);
+impl_num_op!(Mul, mul, |lhs, rhs| lhs.wrapping_mul(rhs), lhs * rhs);
+impl_num_op!(Div, div, |lhs, rhs| lhs.wrapping_div(rhs), lhs / rhs);
+impl_int_op!(Rem, rem, |lhs, rhs| lhs.wrapping_rem(rhs));
+impl_int_op!(Shl, shl, |lhs, rhs| lhs.wrapping_shl(rhs as u32));
+// Shift right is arithmetic for signed and logical for unsigned types
+impl_int_op!(Shr, shr, |lhs, rhs| lhs.wrapping_shr(rhs as u32));
+impl_int_op!(BitAnd, bitand, |lhs, rhs| lhs & rhs);
+impl_int_op!(BitOr, bitor, |lhs, rhs| lhs | rhs);
+impl_int_op!(BitXor, bitxor, |lhs, rhs| lhs ^ rhs);
 
 impl cmp::PartialOrd for Constant {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
@@ -346,9 +393,21 @@ impl cmp::PartialOrd for Constant {
             ValueType::Float => {
                 Into::<f32>::into(self.clone()).partial_cmp(&Into::<f32>::into(other.clone()))
             }
+            ValueType::Double => {
+                Into::<f64>::into(self.clone()).partial_cmp(&Into::<f64>::into(other.clone()))
+            }
             ValueType::Int => {
                 Into::<i32>::into(self.clone()).partial_cmp(&Into::<i32>::into(other.clone()))
             }
+            ValueType::UnsignedInt => {
+                Into::<u32>::into(self.clone()).partial_cmp(&Into::<u32>::into(other.clone()))
+            }
+            ValueType::Long => {
+                Into::<i64>::into(self.clone()).partial_cmp(&Into::<i64>::into(other.clone()))
+            }
+            ValueType::UnsignedLong => {
+                Into::<u64>::into(self.clone()).partial_cmp(&Into::<u64>::into(other.clone()))
+            }
             ValueType::Bool => {
                 Into::<bool>::into(self.clone()).partial_cmp(&Into::<bool>::into(other.clone()))
             }
@@ -26,20 +26,30 @@ pub trait BinaryInst {
     fn set_rhs(&mut self, rhs: Operand);
 }
 
-impl_binary_inst!(Add, "i32", get_add, lhs, rhs, Int);
-impl_binary_inst!(FAdd, "float", get_fadd, lhs, rhs, Float);
-impl_binary_inst!(Sub, "i32", get_sub, lhs, rhs, Int);
-impl_binary_inst!(FSub, "float", get_fsub, lhs, rhs, Float);
-impl_binary_inst!(Mul, "i32", get_mul, lhs, rhs, Int);
-impl_binary_inst!(FMul, "float", get_fmul, lhs, rhs, Float);
-impl_binary_inst!(UDiv, "i32", get_udiv, lhs, rhs, Int);
-impl_binary_inst!(SDiv, "i32", get_sdiv, lhs, rhs, Int);
-impl_binary_inst!(FDiv, "float", get_fdiv, lhs, rhs, Float);
-impl_binary_inst!(URem, "i32", get_urem, lhs, rhs, Int);
-impl_binary_inst!(SRem, "i32", get_srem, lhs, rhs, Int);
-impl_binary_inst!(Shl, "i32", get_shl, value, shiftamt, Int);
-impl_binary_inst!(LShr, "i32", get_lshr, value, shiftamt, Int);
-impl_binary_inst!(AShr, "i32", get_ashr, value, shiftamt, Int);
-impl_binary_inst!(And, "i1", get_and, lhs, rhs, Bool);
-impl_binary_inst!(Or, "i1", get_or, lhs, rhs, Bool);
-impl_binary_inst!(Xor, "i1", get_xor, lhs, rhs, Bool);
+/// Get result type of a binary instruction whose default type is `default`.
+/// `Int` is refined to the unsigned or 64-bit type of `lhs`, and `Float` to `Double`.
+fn binary_value_type(default: ValueType, lhs: &Operand) -> ValueType {
+    match (default, lhs.get_type()) {
+        (Int, ty @ (ValueType::UnsignedInt | ValueType::Long | ValueType::UnsignedLong)) => ty,
+        (Float, ValueType::Double) => ValueType::Double,
+        (default, _) => default,
+    }
+}
+
+impl_binary_inst!(Add, get_add, lhs, rhs, Int);
+impl_binary_inst!(FAdd, get_fadd, lhs, rhs, Float);
+impl_binary_inst!(Sub, get_sub, lhs, rhs, Int);
+impl_binary_inst!(FSub, get_fsub, lhs, rhs, Float);
+impl_binary_inst!(Mul, get_mul, lhs, rhs, Int);
+impl_binary_inst!(FMul, get_fmul, lhs, rhs, Float);
+impl_binary_inst!(UDiv, get_udiv, lhs, rhs, Int);
+impl_binary_inst!(SDiv, get_sdiv, lhs, rhs, Int);
+impl_binary_inst!(FDiv, get_fdiv, lhs, rhs, Float);
+impl_binary_inst!(URem, get_urem, lhs, rhs, Int);
+impl_binary_inst!(SRem, get_srem, lhs, rhs, Int);
+impl_binary_inst!(Shl, get_shl, value, shiftamt, Int);
+impl_binary_inst!(LShr, get_lshr, value, shiftamt, Int);
+impl_binary_inst!(AShr, get_ashr, value, shiftamt, Int);
+impl_binary_inst!(And, get_and, lhs, rhs, Bool);
+impl_binary_inst!(Or, get_or, lhs, rhs, Bool);
+impl_binary_inst!(Xor, get_xor, lhs, rhs, Bool);
@@ -19,9 +19,9 @@ use std::fmt::Formatter;
 use super::*;
 
 impl IRBuilder {
-    pub fn get_zext(&mut self, src: Operand) -> InstPtr {
+    pub fn get_zext(&mut self, src: Operand, ty: ValueType) -> InstPtr {
         let mut inst = self.new_instruction(Box::new(ZextTo {
-            manager: InstManager::new(ValueType::Int),
+            manager: InstManager::new(ty),
         }));
         unsafe {
             inst.get_manager_mut().add_operand(src);
@@ -29,9 +29,9 @@ impl IRBuilder {
         inst
     }
 
-    pub fn get_sext(&mut self, src: Operand) -> InstPtr {
+    pub fn get_sext(&mut self, src: Operand, ty: ValueType) -> InstPtr {
         let mut inst = self.new_instruction(Box::new(SextTo {
-            manager: InstManager::new(ValueType::Int),
+            manager: InstManager::new(ty),
         }));
         unsafe {
             inst.get_manager_mut().add_operand(src);
@@ -39,9 +39,9 @@ impl IRBuilder {
         inst
     }
 
-    pub fn get_itofp(&mut self, src: Operand) -> InstPtr {
+    pub fn get_itofp(&mut self, src: Operand, ty: ValueType) -> InstPtr {
         let mut inst = self.new_instruction(Box::new(ItoFp {
-            manager: InstManager::new(ValueType::Float),
+            manager: InstManager::new(ty),
         }));
         unsafe {
             inst.get_manager_mut().add_operand(src);
@@ -49,8 +49,28 @@ impl IRBuilder {
         inst
     }
 
-    pub fn get_fptoi(&mut self, src: Operand) -> InstPtr {
+    pub fn get_fptoi(&mut self, src: Operand, ty: ValueType) -> InstPtr {
         let mut inst = self.new_instruction(Box::new(FpToI {
+            manager: InstManager::new(ty),
+        }));
+        unsafe {
+            inst.get_manager_mut().add_operand(src);
+        }
+        inst
+    }
+
+    pub fn get_trunc(&mut self, src: Operand, ty: ValueType) -> InstPtr {
+        let mut inst = self.new_instruction(Box::new(Trunc {
+            manager: InstManager::new(ty),
+        }));
+        unsafe {
+            inst.get_manager_mut().add_operand(src);
+        }
+        inst
+    }
+
+    pub fn get_ptrtoi(&mut self, src: Operand) -> InstPtr {
+        let mut inst = self.new_instruction(Box::new(PtrToI {
             manager: InstManager::new(ValueType::Int),
         }));
         unsafe {
@@ -58,9 +78,39 @@ impl IRBuilder {
         }
         inst
     }
+
+    pub fn get_fpext(&mut self, src: Operand, ty: ValueType) -> InstPtr {
+        let mut inst = self.new_instruction(Box::new(FpExt {
+            manager: InstManager::new(ty),
+        }));
+        unsafe {
+            inst.get_manager_mut().add_operand(src);
+        }
+        inst
+    }
+
+    pub fn get_fptrunc(&mut self, src: Operand, ty: ValueType) -> InstPtr {
+        let mut inst = self.new_instruction(Box::new(FpTrunc {
+            manager: InstManager::new(ty),
+        }));
+        unsafe {
+            inst.get_manager_mut().add_operand(src);
+        }
+        inst
+    }
+
+    pub fn get_bitcast(&mut self, src: Operand, ty: ValueType) -> InstPtr {
+        let mut inst = self.new_instruction(Box::new(BitCast {
+            manager: InstManager::new(ty),
+        }));
+        unsafe {
+            inst.get_manager_mut().add_operand(src);
+        }
+        inst
+    }
 }
 
-/// zero extend  bool to  int
+/// Zero extend an integer to a wider integer type
 pub struct ZextTo {
     manager: InstManager,
 }
@@ -89,15 +139,22 @@ impl Instruction for ZextTo {
     gen_common_code!(ZextTo, ZextTo);
     fn copy_self(&self) -> Box<dyn Instruction> {
         Box::new(ZextTo {
-            manager: InstManager::new(ValueType::Int),
+            manager: InstManager::new(self.get_value_type()),
         })
     }
     fn gen_llvm_ir(&self) -> String {
-        format!("{} = zext i1 {} to i32", self, self.get_src())
+        let src = self.get_src();
+        format!(
+            "{} = zext {} {} to {}",
+            self,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
     }
 }
 
-/// Sign extend  bool to  int
+/// Sign extend an integer to a wider integer type
 pub struct SextTo {
     manager: InstManager,
 }
@@ -125,14 +182,22 @@ impl Instruction for SextTo {
     gen_common_code!(SextTo, SextTo);
     fn copy_self(&self) -> Box<dyn Instruction> {
         Box::new(SextTo {
-            manager: InstManager::new(ValueType::Int),
+            manager: InstManager::new(self.get_value_type()),
         })
     }
     fn gen_llvm_ir(&self) -> String {
-        format!("{} = sext i1 {} to i32", self, self.get_src())
+        let src = self.get_src();
+        format!(
+            "{} = sext {} {} to {}",
+            self,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
     }
 }
 
+/// Convert an integer to a floating point type, respecting signedness of the source
 pub struct ItoFp {
     manager: InstManager,
 }
@@ -160,14 +225,28 @@ impl Instruction for ItoFp {
     gen_common_code!(ItoFp, ItoFp);
     fn copy_self(&self) -> Box<dyn Instruction> {
         Box::new(ItoFp {
-            manager: InstManager::new(ValueType::Float),
+            manager: InstManager::new(self.get_value_type()),
         })
     }
     fn gen_llvm_ir(&self) -> String {
-        format!("{} = sitofp i32 {} to float", self, self.get_src())
+        let src = self.get_src();
+        let op = if src.get_type().is_unsigned() {
+            "uitofp"
+        } else {
+            "sitofp"
+        };
+        format!(
+            "{} = {} {} {} to {}",
+            self,
+            op,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
     }
 }
 
+/// Convert a floating point to an integer type, respecting signedness of the target
 pub struct FpToI {
     manager: InstManager,
 }
@@ -195,10 +274,233 @@ impl Instruction for FpToI {
     gen_common_code!(FpToI, FpToI);
     fn copy_self(&self) -> Box<dyn Instruction> {
         Box::new(FpToI {
+            manager: InstManager::new(self.get_value_type()),
+        })
+    }
+    fn gen_llvm_ir(&self) -> String {
+        let src = self.get_src();
+        let op = if self.get_value_type().is_unsigned() {
+            "fptoui"
+        } else {
+            "fptosi"
+        };
+        format!(
+            "{} = {} {} {} to {}",
+            self,
+            op,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
+    }
+}
+
+/// Convert pointer to int, used to calculate pointer difference
+pub struct PtrToI {
+    manager: InstManager,
+}
+
+impl PtrToI {
+    /// Get the operand which will be converted
+    pub fn get_src(&self) -> &Operand {
+        &self.get_operand()[0]
+    }
+    /// # Safety
+    ///
+    /// Set the operand which will be converted
+    pub unsafe fn set_src(&mut self, src: Operand) {
+        self.manager.set_operand(0, src);
+    }
+}
+
+impl Display for PtrToI {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%ptrtoi_{}", self.get_id())
+    }
+}
+
+impl Instruction for PtrToI {
+    gen_common_code!(PtrToI, PtrToI);
+    fn copy_self(&self) -> Box<dyn Instruction> {
+        Box::new(PtrToI {
             manager: InstManager::new(ValueType::Int),
         })
     }
     fn gen_llvm_ir(&self) -> String {
-        format!("{} = fptosi float {} to i32", self, self.get_src())
+        let src = self.get_src();
+        format!("{} = ptrtoint {} {} to i32", self, src.get_type(), src)
+    }
+}
+
+/// Truncate an integer to a narrower integer type, keeping the lowest bits
+pub struct Trunc {
+    manager: InstManager,
+}
+
+impl Trunc {
+    /// Get the operand which will be truncated
+    pub fn get_src(&self) -> &Operand {
+        &self.get_operand()[0]
+    }
+    /// # Safety
+    ///
+    /// Set the operand which will be truncated
+    pub unsafe fn set_src(&mut self, src: Operand) {
+        self.manager.set_operand(0, src);
+    }
+}
+
+impl Display for Trunc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%trunc_{}", self.get_id())
+    }
+}
+
+impl Instruction for Trunc {
+    gen_common_code!(Trunc, Trunc);
+    fn copy_self(&self) -> Box<dyn Instruction> {
+        Box::new(Trunc {
+            manager: InstManager::new(self.get_value_type()),
+        })
+    }
+    fn gen_llvm_ir(&self) -> String {
+        let src = self.get_src();
+        format!(
+            "{} = trunc {} {} to {}",
+            self,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
+    }
+}
+
+/// Extend float to double
+pub struct FpExt {
+    manager: InstManager,
+}
+
+impl FpExt {
+    /// Get the operand which will be extended
+    pub fn get_src(&self) -> &Operand {
+        &self.get_operand()[0]
+    }
+    /// # Safety
+    ///
+    /// Set the operand which will be extended
+    pub unsafe fn set_src(&mut self, src: Operand) {
+        self.manager.set_operand(0, src);
+    }
+}
+
+impl Display for FpExt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%fpext_{}", self.get_id())
+    }
+}
+
+impl Instruction for FpExt {
+    gen_common_code!(FpExt, FpExt);
+    fn copy_self(&self) -> Box<dyn Instruction> {
+        Box::new(FpExt {
+            manager: InstManager::new(self.get_value_type()),
+        })
+    }
+    fn gen_llvm_ir(&self) -> String {
+        let src = self.get_src();
+        format!(
+            "{} = fpext {} {} to {}",
+            self,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
+    }
+}
+
+/// Truncate double to float
+pub struct FpTrunc {
+    manager: InstManager,
+}
+
+impl FpTrunc {
+    /// Get the operand which will be truncated
+    pub fn get_src(&self) -> &Operand {
+        &self.get_operand()[0]
+    }
+    /// # Safety
+    ///
+    /// Set the operand which will be truncated
+    pub unsafe fn set_src(&mut self, src: Operand) {
+        self.manager.set_operand(0, src);
+    }
+}
+
+impl Display for FpTrunc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%fptrunc_{}", self.get_id())
+    }
+}
+
+impl Instruction for FpTrunc {
+    gen_common_code!(FpTrunc, FpTrunc);
+    fn copy_self(&self) -> Box<dyn Instruction> {
+        Box::new(FpTrunc {
+            manager: InstManager::new(self.get_value_type()),
+        })
+    }
+    fn gen_llvm_ir(&self) -> String {
+        let src = self.get_src();
+        format!(
+            "{} = fptrunc {} {} to {}",
+            self,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
+    }
+}
+
+/// Reinterpret an integer as another integer type of the same width,
+/// used to convert between signed and unsigned types
+pub struct BitCast {
+    manager: InstManager,
+}
+
+impl BitCast {
+    /// Get the operand which will be converted
+    pub fn get_src(&self) -> &Operand {
+        &self.get_operand()[0]
+    }
+    /// # Safety
+    ///
+    /// Set the operand which will be converted
+    pub unsafe fn set_src(&mut self, src: Operand) {
+        self.manager.set_operand(0, src);
+    }
+}
+
+impl Display for BitCast {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%bitcast_{}", self.get_id())
+    }
+}
+
+impl Instruction for BitCast {
+    gen_common_code!(BitCast, BitCast);
+    fn copy_self(&self) -> Box<dyn Instruction> {
+        Box::new(BitCast {
+            manager: InstManager::new(self.get_value_type()),
+        })
+    }
+    fn gen_llvm_ir(&self) -> String {
+        let src = self.get_src();
+        format!(
+            "{} = bitcast {} {} to {}",
+            self,
+            src.get_type(),
+            src,
+            self.get_value_type()
+        )
     }
 }
@@ -128,8 +128,8 @@ impl IRBuilder {
         // For example, in `getelementptr [2 x i32], ptr %alloca_0, i32 1, i32 1`
         // the second index shrinks the pointer type to `i32*`.
         let mut return_type = element_type.clone();
-        for _ in &index[1..] {
-            if let Some(sub_type) = return_type.get_sub_type() {
+        for i in &index[1..] {
+            if let Some(sub_type) = return_type.get_indexed_type(i) {
                 return_type = sub_type.clone();
             } else {
                 panic!("Invalid index for getelementptr instruction");
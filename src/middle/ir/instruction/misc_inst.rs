@@ -370,6 +370,17 @@ impl Instruction for Phi {
         self.incoming_values[index].0 = operand;
     }
 
+    fn replace_operand(&mut self, from: &Operand, to: &Operand) {
+        unsafe {
+            self.get_manager_mut().replace_operand(from, to);
+        }
+        for (op, _) in self.incoming_values.iter_mut() {
+            if op == from {
+                *op = to.clone();
+            }
+        }
+    }
+
     fn gen_llvm_ir(&self) -> String {
         let mut res = format!("{} = phi {} ", self, self.get_value_type());
         for (op, bb) in self.get_incoming_values() {
@@ -62,6 +62,7 @@ define_inst_type_enum!(
     // Terminator Instructions
     Ret,
     Br,
+    Switch,
     // Memory Access and Addressing Operations
     Alloca,
     Load,
@@ -72,6 +73,11 @@ define_inst_type_enum!(
     SextTo,
     ItoFp,
     FpToI,
+    PtrToI,
+    Trunc,
+    FpExt,
+    FpTrunc,
+    BitCast,
     // Other Operations
     ICmp,
     FCmp,
@@ -24,6 +24,13 @@ pub struct Br {
     manager: InstManager,
 }
 
+/// Multi-way branch on an integer condition.
+/// The default `BasicBlock` is successor 0, and the `BasicBlock` of i-th case is successor i + 1.
+pub struct Switch {
+    manager: InstManager,
+    cases: Vec<i32>,
+}
+
 impl IRBuilder {
     pub fn get_ret(&mut self, return_value: Option<Operand>) -> InstPtr {
         let mut ret = self.new_instruction(Box::new(Ret {
@@ -50,6 +57,17 @@ impl IRBuilder {
         }
         br
     }
+
+    pub fn get_switch(&mut self, cond: Operand, cases: Vec<i32>) -> InstPtr {
+        let mut switch = self.new_instruction(Box::new(Switch {
+            manager: InstManager::new(ValueType::Void),
+            cases,
+        }));
+        unsafe {
+            switch.get_manager_mut().add_operand(cond);
+        }
+        switch
+    }
 }
 
 impl Ret {
@@ -71,6 +89,17 @@ impl Br {
     }
 }
 
+impl Switch {
+    pub fn get_cond(&self) -> &Operand {
+        &self.manager.operand[0]
+    }
+
+    /// Returns case values, in the same order as successors except the default one.
+    pub fn get_cases(&self) -> &[i32] {
+        &self.cases
+    }
+}
+
 impl Display for Ret {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "%ret_{}", self.get_id())
@@ -83,6 +112,12 @@ impl Display for Br {
     }
 }
 
+impl Display for Switch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%switch_{}", self.get_id())
+    }
+}
+
 impl Instruction for Ret {
     gen_common_code!(Ret, Ret);
     #[inline]
@@ -131,3 +166,40 @@ impl Instruction for Br {
         })
     }
 }
+
+impl Instruction for Switch {
+    gen_common_code!(Switch, Switch);
+    #[inline]
+    fn gen_llvm_ir(&self) -> String {
+        let parent_bb = self.get_parent_bb().unwrap();
+        let next_bb = parent_bb.get_succ_bb();
+        if next_bb.len() != self.cases.len() + 1 {
+            panic!(
+                "basic block {} has {} successors but switch has {} cases",
+                parent_bb.name,
+                next_bb.len(),
+                self.cases.len()
+            );
+        }
+        let cases = self
+            .cases
+            .iter()
+            .zip(next_bb.iter().skip(1))
+            .map(|(case, bb)| format!("i32 {}, label %{}", case, bb.name))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "switch i32 {}, label %{} [ {} ]",
+            self.get_cond(),
+            next_bb[0].name,
+            cases
+        )
+    }
+
+    fn copy_self(&self) -> Box<dyn Instruction> {
+        Box::new(Switch {
+            manager: InstManager::new(self.get_value_type()),
+            cases: self.cases.clone(),
+        })
+    }
+}
@@ -114,7 +114,7 @@ macro_rules! define_inst_type_enum {
 /// impl BinaryInst trait automatically.
 #[macro_export]
 macro_rules! impl_binary_inst {
-    ($type:ident, $operand_type:expr,$func: ident, $lhs:ident, $rhs: ident, $value_type: ident) => {
+    ($type:ident, $func: ident, $lhs:ident, $rhs: ident, $value_type: ident) => {
         /// If you want to make a new binary inst,
         /// please use the IRBuilder to create it.
         pub struct $type {
@@ -146,7 +146,7 @@ macro_rules! impl_binary_inst {
             gen_common_code!($type, $type);
             fn copy_self(&self) -> Box<dyn Instruction> {
                 Box::new($type {
-                    manager: InstManager::new($value_type),
+                    manager: InstManager::new(self.get_value_type()),
                 })
             }
             #[inline]
@@ -155,7 +155,7 @@ macro_rules! impl_binary_inst {
                     "{} = {} {} {}, {}",
                     self,
                     self.get_type(),
-                    $operand_type,
+                    self.get_value_type(),
                     self.get_lhs(),
                     self.get_rhs()
                 )
@@ -170,9 +170,11 @@ macro_rules! impl_binary_inst {
 
         impl IRBuilder {
             /// Get a new inst instruction with operands.
+            /// Result type follows the left operand if it is a wider or unsigned variant.
             pub fn $func(&mut self, $lhs: Operand, $rhs: Operand) -> InstPtr {
+                let value_type = binary_value_type($value_type, &$lhs);
                 let mut inst = self.new_instruction(Box::new($type {
-                    manager: InstManager::new($value_type),
+                    manager: InstManager::new(value_type),
                 }));
                 unsafe {
                     inst.get_manager_mut().add_operand($lhs);
@@ -34,7 +34,7 @@ pub use constant::Constant;
 pub use global_variable::{GlobalPtr, GlobalVariable};
 pub use ir_builder::IRBuilder;
 pub use operand::Operand;
-pub use value_type::ValueType;
+pub use value_type::{Record, ValueType};
 
 use crate::utils::mem::{ObjPool, ObjPtr};
 use std::collections::{HashSet, VecDeque};
@@ -18,6 +18,9 @@ use super::*;
 
 /// one module is one file
 pub struct Module {
+    /// struct and union types defined in this module
+    pub records: Vec<ValueType>,
+
     /// global variables in this module
     pub global_variables: Vec<GlobalPtr>,
 
@@ -31,6 +34,7 @@ pub struct Module {
 impl Module {
     pub fn new(mem_pool: ObjPtr<IRBuilder>) -> Self {
         Self {
+            records: Vec::new(),
             functions: Vec::new(),
             mem_pool,
             global_variables: Vec::new(),
@@ -39,6 +43,9 @@ impl Module {
 
     pub fn gen_llvm_ir(&self) -> String {
         let mut ir = String::new();
+        for record in self.records.iter() {
+            ir.push_str(&gen_record_llvm_ir(record));
+        }
         for global in self.global_variables.iter() {
             ir.push_str(&global.gen_llvm_ir());
         }
@@ -48,3 +55,32 @@ impl Module {
         ir
     }
 }
+
+/// Union is emitted as its most aligned member, padded to its size.
+fn gen_record_llvm_ir(ty: &ValueType) -> String {
+    let fields = match ty {
+        ValueType::Struct(record) => record
+            .fields()
+            .iter()
+            .map(|(_, ty)| ty.to_string())
+            .collect::<Vec<_>>(),
+        ValueType::Union(record) => {
+            let member = record
+                .fields()
+                .iter()
+                .map(|(_, ty)| ty)
+                .rev()
+                .max_by_key(|ty| ty.align());
+            match member {
+                Some(member) if ty.size() > member.size() => vec![
+                    member.to_string(),
+                    format!("[{} x i8]", ty.size() - member.size()),
+                ],
+                Some(member) => vec![member.to_string()],
+                None => vec![],
+            }
+        }
+        _ => return String::new(),
+    };
+    format!("{} = type {{ {} }}\n", ty, fields.join(", "))
+}
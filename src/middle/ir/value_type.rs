@@ -14,11 +14,14 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+
 use anyhow::{anyhow, Context, Result};
 
 use crate::context;
 
-use super::Constant;
+use super::{Constant, Operand};
 
 /// Represent the type of a value.
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -26,10 +29,74 @@ pub enum ValueType {
     Void,
     SignedChar,
     Int,
+    UnsignedInt,
+    Long,
+    UnsignedLong,
     Float,
+    Double,
     Bool,
     Array(Box<ValueType>, usize),
     Pointer(Box<ValueType>),
+    Struct(Record),
+    Union(Record),
+}
+
+/// Fields of a struct or union, identified by its name.
+/// Fields are defined after the record is created,
+/// so that a field can point to the record itself.
+#[derive(Clone)]
+pub struct Record {
+    pub name: String,
+    fields: Arc<OnceLock<Vec<(String, ValueType)>>>,
+}
+
+impl Record {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            fields: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Define fields of the record.
+    /// Fails if fields are already defined.
+    pub fn define(&self, fields: Vec<(String, ValueType)>) -> Result<()> {
+        self.fields
+            .set(fields)
+            .map_err(|_| anyhow!("`{}` is defined twice", self.name))
+            .with_context(|| context!())
+    }
+
+    /// Get fields of the record, empty if not defined yet.
+    pub fn fields(&self) -> &[(String, ValueType)] {
+        self.fields.get().map_or(&[], |fields| fields.as_slice())
+    }
+
+    /// Get index of field with given name.
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields().iter().position(|(id, _)| id == name)
+    }
+}
+
+impl std::fmt::Debug for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Records are unique by name in a module.
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Record {}
+
+impl Hash for Record {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
 }
 
 impl std::fmt::Display for ValueType {
@@ -37,11 +104,15 @@ impl std::fmt::Display for ValueType {
         match self {
             ValueType::Void => write!(f, "void"),
             ValueType::SignedChar => write!(f, "i8"),
-            ValueType::Int => write!(f, "i32"),
+            ValueType::Int | ValueType::UnsignedInt => write!(f, "i32"),
+            ValueType::Long | ValueType::UnsignedLong => write!(f, "i64"),
             ValueType::Float => write!(f, "float"),
+            ValueType::Double => write!(f, "double"),
             ValueType::Bool => write!(f, "i1"),
             ValueType::Array(one_type, size) => write!(f, "[{} x {}]", size, one_type),
             ValueType::Pointer(pointer) => write!(f, "{}*", pointer),
+            ValueType::Struct(record) => write!(f, "%struct.{}", record.name),
+            ValueType::Union(record) => write!(f, "%union.{}", record.name),
         }
     }
 }
@@ -56,12 +127,64 @@ impl ValueType {
     pub fn is_basic_type(&self) -> bool {
         matches!(
             self,
-            ValueType::Void | ValueType::Int | ValueType::Float | ValueType::Bool
+            ValueType::Void
+                | ValueType::Int
+                | ValueType::UnsignedInt
+                | ValueType::Long
+                | ValueType::UnsignedLong
+                | ValueType::Float
+                | ValueType::Double
+                | ValueType::Bool
         )
     }
 
     pub fn is_num(&self) -> bool {
-        matches!(self, ValueType::Bool | ValueType::Int | ValueType::Float)
+        self.is_integer() || self.is_float()
+    }
+
+    /// Check if this type is an integer type, including boolean and char.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            ValueType::Bool
+                | ValueType::SignedChar
+                | ValueType::Int
+                | ValueType::UnsignedInt
+                | ValueType::Long
+                | ValueType::UnsignedLong
+        )
+    }
+
+    /// Check if this type is a floating point type.
+    pub fn is_float(&self) -> bool {
+        matches!(self, ValueType::Float | ValueType::Double)
+    }
+
+    /// Check if this type is an unsigned integer type.
+    /// Boolean is unsigned, so it is zero-extended when widened.
+    pub fn is_unsigned(&self) -> bool {
+        matches!(
+            self,
+            ValueType::Bool | ValueType::UnsignedInt | ValueType::UnsignedLong
+        )
+    }
+
+    /// Get unsigned integer type of the same width, or itself if there is none.
+    pub fn to_unsigned(&self) -> ValueType {
+        match self {
+            ValueType::Int => ValueType::UnsignedInt,
+            ValueType::Long => ValueType::UnsignedLong,
+            _ => self.clone(),
+        }
+    }
+
+    /// Get signed integer type of the same width, or itself if there is none.
+    pub fn to_signed(&self) -> ValueType {
+        match self {
+            ValueType::UnsignedInt => ValueType::Int,
+            ValueType::UnsignedLong => ValueType::Long,
+            _ => self.clone(),
+        }
     }
 
     pub fn is_pointer(&self) -> bool {
@@ -72,11 +195,71 @@ impl ValueType {
         matches!(self, ValueType::Array(_, _))
     }
 
-    /// Get size of this value type.
+    pub fn is_record(&self) -> bool {
+        matches!(self, ValueType::Struct(_) | ValueType::Union(_))
+    }
+
+    /// Check if this type is a struct or union, or an array of them.
+    pub fn has_record(&self) -> bool {
+        match self {
+            ValueType::Array(element_type, _) => element_type.has_record(),
+            _ => self.is_record(),
+        }
+    }
+
+    /// Get size of this value type in bytes.
+    /// Struct and union are padded to a multiple of their alignment.
     pub fn size(&self) -> usize {
         match self {
+            ValueType::Void => 0,
+            ValueType::SignedChar => 1,
+            // Boolean in memory is loaded and stored as a word
+            ValueType::Bool | ValueType::Int | ValueType::UnsignedInt | ValueType::Float => 4,
+            ValueType::Long | ValueType::UnsignedLong | ValueType::Double => 8,
+            ValueType::Pointer(_) => 8,
             ValueType::Array(element_type, dim) => *dim * element_type.size(),
-            _ => 1,
+            ValueType::Struct(record) => {
+                let end = record
+                    .fields()
+                    .iter()
+                    .fold(0, |end, (_, ty)| align_to(end, ty.align()) + ty.size());
+                align_to(end, self.align())
+            }
+            ValueType::Union(record) => {
+                let end = record.fields().iter().map(|(_, ty)| ty.size()).max();
+                align_to(end.unwrap_or(0), self.align())
+            }
+        }
+    }
+
+    /// Get alignment of this value type in bytes.
+    pub fn align(&self) -> usize {
+        match self {
+            ValueType::Array(element_type, _) => element_type.align(),
+            ValueType::Struct(record) | ValueType::Union(record) => record
+                .fields()
+                .iter()
+                .map(|(_, ty)| ty.align())
+                .max()
+                .unwrap_or(1),
+            _ => self.size().max(1),
+        }
+    }
+
+    /// Get offset of the field at `index` in bytes.
+    /// All members of a union start at offset 0.
+    pub fn field_offset(&self, index: usize) -> Option<usize> {
+        match self {
+            ValueType::Struct(record) => {
+                let fields = record.fields();
+                let (_, ty) = fields.get(index)?;
+                let end = fields[..index]
+                    .iter()
+                    .fold(0, |end, (_, ty)| align_to(end, ty.align()) + ty.size());
+                Some(align_to(end, ty.align()))
+            }
+            ValueType::Union(record) => record.fields().get(index).map(|_| 0),
+            _ => None,
         }
     }
 
@@ -90,6 +273,18 @@ impl ValueType {
         }
     }
 
+    /// Get type selected by a non-initial `getelementptr` index into this type.
+    /// Field of a struct is selected by a constant index.
+    pub fn get_indexed_type(&self, index: &Operand) -> Option<&ValueType> {
+        match (self, index) {
+            (ValueType::Struct(record), Operand::Constant(Constant::Int(i))) => {
+                record.fields().get(*i as usize).map(|(_, ty)| ty)
+            }
+            (ValueType::Struct(_) | ValueType::Union(_), _) => None,
+            _ => self.get_sub_type(),
+        }
+    }
+
     /// Get base type of the value type.
     /// Base type is i32 / f32 for array.
     pub fn get_base_type(&self) -> ValueType {
@@ -107,13 +302,18 @@ impl ValueType {
                 Err(anyhow!("Cannot convert void type to constant")).with_context(|| context!())
             }
             ValueType::Int => Ok(Constant::Int(0)),
+            ValueType::UnsignedInt => Ok(Constant::UnsignedInt(0)),
+            ValueType::Long => Ok(Constant::Long(0)),
+            ValueType::UnsignedLong => Ok(Constant::UnsignedLong(0)),
             ValueType::SignedChar => Ok(Constant::SignedChar(0)),
             ValueType::Float => Ok(Constant::Float(0.0)),
+            ValueType::Double => Ok(Constant::Double(0.0)),
             ValueType::Bool => Ok(Constant::Bool(false)),
             ValueType::Pointer(_) => {
                 Err(anyhow!("Cannot convert pointer type to constant")).with_context(|| context!())
             }
             ValueType::Array(ty, _) => Ok(Constant::Zero(*ty.clone())),
+            ValueType::Struct(_) | ValueType::Union(_) => Ok(Constant::Zero(self.clone())),
         }
     }
 
@@ -121,10 +321,15 @@ impl ValueType {
     /// Higher is more precise.
     pub fn to_precision_level(&self) -> i32 {
         match self {
-            // All boolean should be converted to int when applying `+` and etc.
+            // All boolean and char should be converted to int when applying `+` and etc.
             ValueType::Bool => 1,
+            ValueType::SignedChar => 1,
             ValueType::Int => 1,
-            ValueType::Float => 2,
+            ValueType::UnsignedInt => 2,
+            ValueType::Long => 3,
+            ValueType::UnsignedLong => 4,
+            ValueType::Float => 5,
+            ValueType::Double => 6,
             _ => 0,
         }
     }
@@ -133,7 +338,11 @@ impl ValueType {
     pub fn from_precision_level(level: i32) -> Self {
         match level {
             1 => ValueType::Int,
-            2 => ValueType::Float,
+            2 => ValueType::UnsignedInt,
+            3 => ValueType::Long,
+            4 => ValueType::UnsignedLong,
+            5 => ValueType::Float,
+            6 => ValueType::Double,
             _ => ValueType::Void,
         }
     }
@@ -151,3 +360,8 @@ impl ValueType {
         }
     }
 }
+
+/// Round `offset` up to a multiple of `align`.
+fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
@@ -23,6 +23,7 @@ use crate::utils::frame_map::FrameMap;
 pub struct FunctionKit<'a> {
     pub env: FrameMap<'a, String, Value>,
     pub fun_env: FrameMap<'a, String, FunPtr>,
+    pub ty_env: FrameMap<'a, String, ValueType>,
     pub program: &'a mut middle::Program,
     pub exit: Option<BBPtr>,
     pub break_to: Option<BBPtr>,
@@ -37,6 +38,7 @@ pub struct FunctionKit<'a> {
 pub struct FunctionContext<'a> {
     pub env: FrameMap<'a, String, Value>,
     pub fun_env: FrameMap<'a, String, FunPtr>,
+    pub ty_env: FrameMap<'a, String, ValueType>,
     pub program: &'a mut middle::Program,
     pub counter: &'a mut usize,
 }
@@ -60,6 +62,7 @@ impl<'a> FunctionKit<'a> {
         FunctionKit {
             env: ctx.env,
             fun_env: ctx.fun_env,
+            ty_env: ctx.ty_env,
             program: ctx.program,
             exit: routing.exit,
             break_to: routing.break_to,
@@ -82,6 +85,7 @@ impl<'a> FunctionKit<'a> {
             FunctionContext {
                 env: self.env.branch(),
                 fun_env: self.fun_env.branch(),
+                ty_env: self.ty_env.branch(),
                 program: self.program,
                 counter: self.counter,
             },
@@ -20,308 +20,17 @@ use crate::middle::ir::instruction::misc_inst::{FCmpOp, ICmpOp};
 use crate::middle::ir::{Constant, ValueType};
 use crate::middle::irgen::function_kit::FunctionKit;
 use crate::middle::irgen::value::Value;
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, Context, Result};
 
 impl<'a> FunctionKit<'a> {
     /// Generate a binary expression
     pub fn gen_binary(&mut self, head: &Expr, tail: &[(BinaryOp, Expr)]) -> anyhow::Result<Value> {
-        let Some(mut exit) = self.exit else {
-            return Err(anyhow!("exit block can't be appended")).with_context(|| context!());
-        };
-
         // Apply operation by iteration
         let mut lhs_val = self.gen_expr(head)?;
         for (op, rhs) in tail {
             lhs_val = match op {
-                BinaryOp::Add => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add "add" instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst = self.program.mem_pool.get_add(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst = self.program.mem_pool.get_fadd(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`+` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Sub => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add "sub" instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst = self.program.mem_pool.get_sub(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst = self.program.mem_pool.get_fsub(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`-` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Mul => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add "mul" instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst = self.program.mem_pool.get_mul(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst = self.program.mem_pool.get_fmul(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`*` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Div => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add "div" instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst = self.program.mem_pool.get_sdiv(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst = self.program.mem_pool.get_fdiv(lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`/` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Mod => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-
-                    // Load operand as integers
-                    let lop = lhs_val.load(ValueType::Int, self)?;
-                    let rop = rhs_val.load(ValueType::Int, self)?;
-
-                    // Add "signed rem" instruction, operand is the result of the instruction
-                    let inst = self.program.mem_pool.get_srem(lop, rop);
-                    exit.push_back(inst);
-                    Ok(Value::ReadOnly(inst.into()))
-                }
-                // Bitwise operation on int is not required
-                BinaryOp::Shr => Err(anyhow!("`>>` not supported")).with_context(|| context!()),
-                BinaryOp::Shl => Err(anyhow!("`<<` not supported")).with_context(|| context!()),
-                BinaryOp::BitAnd => Err(anyhow!("`&` not supported")).with_context(|| context!()),
-                BinaryOp::BitOr => Err(anyhow!("`|` not supported")).with_context(|| context!()),
-                BinaryOp::BitXor => Err(anyhow!("`^` not supported")).with_context(|| context!()),
-                BinaryOp::Gt => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add compare instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_icmp(ICmpOp::Sgt, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_fcmp(FCmpOp::Ogt, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`>` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Lt => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add compare instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_icmp(ICmpOp::Slt, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_fcmp(FCmpOp::Olt, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`<` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Ge => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add compare instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_icmp(ICmpOp::Sge, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_fcmp(FCmpOp::Oge, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`>=` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Le => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add compare instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_icmp(ICmpOp::Sle, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_fcmp(FCmpOp::Ole, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`<=` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Eq => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add compare instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst = self.program.mem_pool.get_icmp(ICmpOp::Eq, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_fcmp(FCmpOp::Oeq, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`==` for NaN")).with_context(|| context!()),
-                    }
-                }
-                BinaryOp::Ne => {
-                    // Generate arguments and get type to cast
-                    let rhs_val = self.gen_expr(rhs)?;
-                    let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
-
-                    // Load operand as maximum type
-                    let lop = lhs_val.load(max_ty.clone(), self)?;
-                    let rop = rhs_val.load(max_ty.clone(), self)?;
-
-                    // Add compare instruction, operand is the result of the instruction
-                    match max_ty {
-                        ValueType::Int => {
-                            let inst = self.program.mem_pool.get_icmp(ICmpOp::Ne, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        ValueType::Float => {
-                            let inst =
-                                self.program
-                                    .mem_pool
-                                    .get_fcmp(FCmpOp::One, max_ty, lop, rop);
-                            exit.push_back(inst);
-                            Ok(Value::ReadOnly(inst.into()))
-                        }
-                        _ => Err(anyhow!("`!=` for NaN")).with_context(|| context!()),
-                    }
-                }
+                // Left operand of comma is evaluated only for side effects
+                BinaryOp::Comma => self.gen_expr(rhs),
                 BinaryOp::And => {
                     // Allocate basic blocks
                     let alt_name: String = self.unique_name("alt");
@@ -386,8 +95,478 @@ impl<'a> FunctionKit<'a> {
                     final_entry.push_back(inst);
                     Ok(Value::ReadOnly(inst.into()))
                 }
+                _ => {
+                    let rhs_val = self.gen_expr(rhs)?;
+                    self.gen_binary_op(op, lhs_val, rhs_val)
+                }
             }?;
         }
         Ok(lhs_val)
     }
+
+    /// Apply binary operator to evaluated operands, except `&&`, `||` and `,`.
+    /// Operands are evaluated before, so instructions go to the block where evaluation ends.
+    pub fn gen_binary_op(
+        &mut self,
+        op: &BinaryOp,
+        lhs_val: Value,
+        rhs_val: Value,
+    ) -> Result<Value> {
+        let Some(mut exit) = self.exit else {
+            return Err(anyhow!("exit block can't be appended")).with_context(|| context!());
+        };
+
+        // Pointer arithmetic and comparison, with pointer on the left
+        if is_address(&lhs_val) {
+            return self.gen_pointer_binary(op, lhs_val, rhs_val);
+        }
+
+        match op {
+            BinaryOp::Add => {
+                // Integer plus pointer is the same as pointer plus integer
+                if is_address(&rhs_val) {
+                    return self.gen_pointer_binary(op, rhs_val, lhs_val);
+                }
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add "add" instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::Int
+                    | ValueType::UnsignedInt
+                    | ValueType::Long
+                    | ValueType::UnsignedLong => {
+                        let inst = self.program.mem_pool.get_add(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self.program.mem_pool.get_fadd(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`+` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Sub => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add "sub" instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::Int
+                    | ValueType::UnsignedInt
+                    | ValueType::Long
+                    | ValueType::UnsignedLong => {
+                        let inst = self.program.mem_pool.get_sub(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self.program.mem_pool.get_fsub(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`-` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Mul => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add "mul" instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::Int
+                    | ValueType::UnsignedInt
+                    | ValueType::Long
+                    | ValueType::UnsignedLong => {
+                        let inst = self.program.mem_pool.get_mul(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self.program.mem_pool.get_fmul(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`*` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Div => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add "div" instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::UnsignedInt | ValueType::UnsignedLong => {
+                        let inst = self.program.mem_pool.get_udiv(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Int | ValueType::Long => {
+                        let inst = self.program.mem_pool.get_sdiv(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self.program.mem_pool.get_fdiv(lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`/` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Mod => {
+                // Load operand as integers of maximum type
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+                let int_ty = if max_ty.is_integer() {
+                    max_ty
+                } else {
+                    ValueType::Int
+                };
+                let lop = lhs_val.load(int_ty.clone(), self)?;
+                let rop = rhs_val.load(int_ty.clone(), self)?;
+
+                // Add "rem" instruction by signedness, operand is the result of the instruction
+                let inst = if int_ty.is_unsigned() {
+                    self.program.mem_pool.get_urem(lop, rop)
+                } else {
+                    self.program.mem_pool.get_srem(lop, rop)
+                };
+                exit.push_back(inst);
+                Ok(Value::ReadOnly(inst.into()))
+            }
+            BinaryOp::Shr | BinaryOp::Shl => {
+                // Load operand as integers, result has the promoted type of left operand
+                let int_ty = lhs_val.get_type().max_with(&ValueType::Int);
+                let int_ty = if int_ty.is_integer() {
+                    int_ty
+                } else {
+                    ValueType::Int
+                };
+                let lop = lhs_val.load(int_ty.clone(), self)?;
+                let rop = rhs_val.load(int_ty.clone(), self)?;
+
+                // Shift right is arithmetic for signed integers, and logical for unsigned
+                let inst = match op {
+                    BinaryOp::Shr if int_ty.is_unsigned() => {
+                        self.program.mem_pool.get_lshr(lop, rop)
+                    }
+                    BinaryOp::Shr => self.program.mem_pool.get_ashr(lop, rop),
+                    _ => self.program.mem_pool.get_shl(lop, rop),
+                };
+                exit.push_back(inst);
+                Ok(Value::ReadOnly(inst.into()))
+            }
+            // Bitwise operation on int is not required
+            BinaryOp::BitAnd => Err(anyhow!("`&` not supported")).with_context(|| context!()),
+            BinaryOp::BitOr => Err(anyhow!("`|` not supported")).with_context(|| context!()),
+            BinaryOp::BitXor => Err(anyhow!("`^` not supported")).with_context(|| context!()),
+            BinaryOp::Gt => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add compare instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::UnsignedInt | ValueType::UnsignedLong => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Ugt, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Int | ValueType::Long => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Sgt, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_fcmp(FCmpOp::Ogt, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`>` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Lt => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add compare instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::UnsignedInt | ValueType::UnsignedLong => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Ult, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Int | ValueType::Long => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Slt, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_fcmp(FCmpOp::Olt, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`<` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Ge => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add compare instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::UnsignedInt | ValueType::UnsignedLong => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Uge, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Int | ValueType::Long => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Sge, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_fcmp(FCmpOp::Oge, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`>=` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Le => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add compare instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::UnsignedInt | ValueType::UnsignedLong => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Ule, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Int | ValueType::Long => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_icmp(ICmpOp::Sle, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_fcmp(FCmpOp::Ole, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`<=` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Eq => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add compare instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::Int
+                    | ValueType::UnsignedInt
+                    | ValueType::Long
+                    | ValueType::UnsignedLong => {
+                        let inst = self.program.mem_pool.get_icmp(ICmpOp::Eq, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_fcmp(FCmpOp::Oeq, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`==` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::Ne => {
+                let max_ty = lhs_val.get_type().max_with(&rhs_val.get_type());
+
+                // Load operand as maximum type
+                let lop = lhs_val.load(max_ty.clone(), self)?;
+                let rop = rhs_val.load(max_ty.clone(), self)?;
+
+                // Add compare instruction, operand is the result of the instruction
+                match max_ty {
+                    ValueType::Int
+                    | ValueType::UnsignedInt
+                    | ValueType::Long
+                    | ValueType::UnsignedLong => {
+                        let inst = self.program.mem_pool.get_icmp(ICmpOp::Ne, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    ValueType::Float | ValueType::Double => {
+                        let inst = self
+                            .program
+                            .mem_pool
+                            .get_fcmp(FCmpOp::One, max_ty, lop, rop);
+                        exit.push_back(inst);
+                        Ok(Value::ReadOnly(inst.into()))
+                    }
+                    _ => Err(anyhow!("`!=` for NaN")).with_context(|| context!()),
+                }
+            }
+            BinaryOp::And | BinaryOp::Or | BinaryOp::Comma => {
+                Err(anyhow!("`{:?}` needs unevaluated operand", op)).with_context(|| context!())
+            }
+        }
+    }
+
+    /// Generate a binary expression with pointer `lhs`.
+    /// Pointer can be shifted by an integer, or subtracted and compared with another pointer.
+    fn gen_pointer_binary(&mut self, op: &BinaryOp, lhs: Value, rhs: Value) -> Result<Value> {
+        let Some(mut exit) = self.exit else {
+            return Err(anyhow!("exit block can't be appended")).with_context(|| context!());
+        };
+
+        // Load pointer, array decays to pointer here
+        let (lop, lty) = lhs.load_uncast(self)?;
+        let ValueType::Pointer(element_type) = lty.clone() else {
+            return Err(anyhow!("{} is not a pointer", lty)).with_context(|| context!());
+        };
+
+        // Pointer with pointer
+        if is_address(&rhs) {
+            let (rop, rty) = rhs.load_uncast(self)?;
+            if lty != rty {
+                return Err(anyhow!("`{:?}` between {} and {}", op, lty, rty))
+                    .with_context(|| context!());
+            }
+            let cmp_op = match op {
+                BinaryOp::Sub => {
+                    // Difference of addresses is divided by element size
+                    let size = element_type.size();
+                    if size == 0 {
+                        return Err(anyhow!("`-` between {}", lty)).with_context(|| context!());
+                    }
+                    let lhs_int = self.program.mem_pool.get_ptrtoi(lop);
+                    let rhs_int = self.program.mem_pool.get_ptrtoi(rop);
+                    let diff = self
+                        .program
+                        .mem_pool
+                        .get_sub(lhs_int.into(), rhs_int.into());
+                    let inst = self
+                        .program
+                        .mem_pool
+                        .get_sdiv(diff.into(), Constant::Int(size as i32).into());
+                    exit.push_back(lhs_int);
+                    exit.push_back(rhs_int);
+                    exit.push_back(diff);
+                    exit.push_back(inst);
+                    return Ok(Value::ReadOnly(inst.into()));
+                }
+                BinaryOp::Eq => ICmpOp::Eq,
+                BinaryOp::Ne => ICmpOp::Ne,
+                BinaryOp::Lt => ICmpOp::Slt,
+                BinaryOp::Le => ICmpOp::Sle,
+                BinaryOp::Gt => ICmpOp::Sgt,
+                BinaryOp::Ge => ICmpOp::Sge,
+                _ => {
+                    return Err(anyhow!("`{:?}` between {} and {}", op, lty, rty))
+                        .with_context(|| context!())
+                }
+            };
+            let inst = self.program.mem_pool.get_icmp(cmp_op, lty, lop, rop);
+            exit.push_back(inst);
+            return Ok(Value::ReadOnly(inst.into()));
+        }
+
+        // Pointer with integer offset
+        let offset = rhs.load(ValueType::Int, self)?;
+        let offset = match op {
+            BinaryOp::Add => offset,
+            BinaryOp::Sub => {
+                let inst = self
+                    .program
+                    .mem_pool
+                    .get_sub(Constant::Int(0).into(), offset);
+                exit.push_back(inst);
+                inst.into()
+            }
+            _ => {
+                return Err(anyhow!("`{:?}` between {} and integer", op, lty))
+                    .with_context(|| context!())
+            }
+        };
+        let inst = self
+            .program
+            .mem_pool
+            .get_getelementptr(*element_type, lop, vec![offset]);
+        exit.push_back(inst);
+        Ok(Value::ReadOnly(inst.into()))
+    }
+}
+
+/// Check if value is a pointer, or an array that decays to pointer.
+fn is_address(val: &Value) -> bool {
+    matches!(val, Value::ReadOnly(_) | Value::ReadWrite(_))
+        && matches!(val.get_type(), ValueType::Pointer(_) | ValueType::Array(..))
 }
@@ -16,40 +16,102 @@
 
 use crate::context;
 use crate::frontend::Expr;
-use crate::middle::ir::Constant;
+use crate::middle::ir::{Constant, ValueType};
+use crate::utils::frame_map::FrameMap;
 use anyhow::{anyhow, Context, Result};
 
 use super::gen_type::gen_type;
 
 /// Generate constant expression
-pub fn gen_const(expr: &Expr) -> Result<Constant> {
+pub fn gen_const(expr: &Expr, ty_env: &FrameMap<String, ValueType>) -> Result<Constant> {
     match expr {
-        Expr::Array(ls) => Ok(Constant::Array(
-            ls.iter().map(gen_const).collect::<anyhow::Result<_, _>>()?,
+        Expr::Array(ls, _) => Ok(Constant::Array(
+            ls.iter()
+                .map(|x| gen_const(x, ty_env))
+                .collect::<anyhow::Result<_, _>>()?,
         )),
-        Expr::Zero(ty) => Ok(Constant::Zero(gen_type(ty)?)),
-        Expr::Int(x) => Ok(Constant::Int(*x)),
-        Expr::Float(x) => Ok(Constant::Float(*x)),
-        Expr::String(str) => {
-            let mut vec = vec![];
+        Expr::Zero(ty, _) => Ok(Constant::Zero(gen_type(ty, ty_env)?)),
+        Expr::Int(x, _) => Ok(Constant::Int(*x)),
+        Expr::Float(x, _) => Ok(Constant::Float(*x)),
+        Expr::String(str, _) => Ok(gen_string(str)),
+        _ => Err(anyhow!("expression {:?} is not constant", expr)).with_context(|| context!()),
+    }
+}
 
-            // Add trailing zero to bytes, pad bytes to multiple of 4
-            let mut bytes = str.as_bytes().to_vec();
-            bytes.push(0);
-            while bytes.len() % 4 != 0 {
-                bytes.push(0);
-            }
+/// Generate bytes of a string literal, with trailing zero.
+pub fn gen_string(str: &str) -> Constant {
+    let mut bytes: Vec<_> = str.bytes().map(|b| Constant::SignedChar(b as i8)).collect();
+    bytes.push(Constant::SignedChar(0));
+    Constant::Array(bytes)
+}
 
-            // Convert to little indian
-            for i in 0..(bytes.len() / 4) {
-                let mut val: u32 = bytes[i * 4 + 3] as u32;
-                val = val * 256 + bytes[i * 4 + 2] as u32;
-                val = val * 256 + bytes[i * 4 + 1] as u32;
-                val = val * 256 + bytes[i * 4] as u32;
-                vec.push(Constant::Int(val as i32));
+/// Shape a constant to the type it initializes.
+/// Struct is initialized from an array of its fields, missing fields are zero.
+pub fn shape_const(constant: Constant, ty: &ValueType) -> Result<Constant> {
+    match (ty, constant) {
+        (ValueType::Struct(_) | ValueType::Union(_), Constant::Zero(_)) => {
+            Ok(Constant::Zero(ty.clone()))
+        }
+        (ValueType::Struct(record), Constant::Array(arr)) => {
+            let mut arr = arr.into_iter();
+            let fields = record
+                .fields()
+                .iter()
+                .map(|(_, field)| match (arr.next(), field) {
+                    (Some(item), _) => shape_const(item, field),
+                    (None, ValueType::Int | ValueType::Float | ValueType::Bool) => {
+                        field.default_initializer()
+                    }
+                    (None, _) => Ok(Constant::Zero(field.clone())),
+                })
+                .collect::<Result<_>>()?;
+            Ok(Constant::Struct(record.clone(), fields))
+        }
+        (ValueType::Array(element_type, _), Constant::Array(arr)) if element_type.has_record() => {
+            Ok(Constant::Array(
+                arr.into_iter()
+                    .map(|item| shape_const(item, element_type))
+                    .collect::<Result<_>>()?,
+            ))
+        }
+        (ValueType::Array(element_type, len), Constant::Array(arr))
+            if element_type.get_base_type() == ValueType::SignedChar =>
+        {
+            // Char array is filled with zero, and string literal can drop its trailing zero
+            if arr.len() > *len && arr[*len..].iter().any(|c| *c != Constant::SignedChar(0)) {
+                return Err(anyhow!("too many initializers for {}", ty))
+                    .with_context(|| context!());
+            }
+            let mut arr = arr
+                .into_iter()
+                .take(*len)
+                .map(|item| shape_const(item, element_type))
+                .collect::<Result<Vec<_>>>()?;
+            while arr.len() < *len {
+                arr.push(shape_const(
+                    Constant::Zero(*element_type.clone()),
+                    element_type,
+                )?);
             }
-            Ok(Constant::Array(vec))
+            Ok(Constant::Array(arr))
         }
-        _ => Err(anyhow!("expression {:?} is not constant", expr)).with_context(|| context!()),
+        (ValueType::SignedChar, Constant::Zero(_)) => Ok(Constant::SignedChar(0)),
+        (ValueType::SignedChar, constant @ (Constant::Int(_) | Constant::Bool(_))) => {
+            Ok(constant.cast(ty))
+        }
+        (ValueType::Array(element_type, _), Constant::Array(arr)) => Ok(Constant::Array(
+            arr.into_iter()
+                .map(|item| shape_const(item, element_type))
+                .collect::<Result<_>>()?,
+        )),
+        (ty, Constant::Zero(_)) if ty.is_num() => ty.default_initializer(),
+        // Literals are 32-bit, widen them to the initialized type
+        (ty, constant @ (Constant::Int(_) | Constant::Float(_))) if ty.is_num() => {
+            Ok(constant.cast(ty))
+        }
+        (ValueType::Struct(_) | ValueType::Union(_), constant) => {
+            Err(anyhow!("{} can't initialize {}", constant, ty)).with_context(|| context!())
+        }
+        (_, constant) => Ok(constant),
     }
 }
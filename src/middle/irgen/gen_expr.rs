@@ -21,17 +21,16 @@ use crate::middle::irgen::function_kit::FunctionKit;
 use crate::middle::irgen::value::Value;
 use anyhow::{anyhow, Context};
 
-use super::gen_const::gen_const;
-use super::gen_library_function::is_argument_const;
+use super::gen_const::gen_string;
 
 impl<'a> FunctionKit<'a> {
     /// Generate an expression as a statement into the program
     pub fn gen_expr(&mut self, expr: &Expr) -> anyhow::Result<Value> {
-        let Some(mut exit) = self.exit else {
+        if self.exit.is_none() {
             return Err(anyhow!("exit block can't be appended")).with_context(|| context!());
-        };
+        }
         match expr {
-            Expr::Var(x) => {
+            Expr::Var(x, _) => {
                 // Ensure variable is defined
                 let Some(operand) = self.env.get(x) else {
                     return Err(anyhow!("variable not defined")).with_context(|| context!());
@@ -40,12 +39,12 @@ impl<'a> FunctionKit<'a> {
                 // Clone the operand and return, this clones the underlying value or InstPtr
                 Ok(operand.clone())
             }
-            Expr::Array(ls) => Ok(Value::Array(
+            Expr::Array(ls, _) => Ok(Value::Array(
                 ls.iter()
                     .map(|x| self.gen_expr(x))
                     .collect::<anyhow::Result<_, _>>()?,
             )),
-            Expr::Index(x, v) => {
+            Expr::Index(x, v, _) => {
                 // Load index as integer
                 let ix = self.gen_expr(v)?.load(ValueType::Int, self)?;
 
@@ -53,11 +52,34 @@ impl<'a> FunctionKit<'a> {
                 self.gen_expr(x)?
                     .getelementptr(self, vec![Constant::Int(0).into(), ix])
             }
-            Expr::Int(x) => Ok(Constant::Int(*x).into()),
-            Expr::Float(x) => Ok(Constant::Float(*x).into()),
-            Expr::Call(func, args) => {
+            Expr::Field(x, name, _) => self.gen_expr(x)?.member(self, name),
+            Expr::Arrow(x, name, _) => {
+                // Load pointer to record, and get its member
+                let (ptr, ty) = self.gen_expr(x)?.load_uncast(self)?;
+                if !ty.is_pointer() {
+                    return Err(anyhow!("{} is not a pointer to record", ty))
+                        .with_context(|| context!());
+                }
+                Value::ReadWrite(ptr).member(self, name)
+            }
+            Expr::Int(x, _) => Ok(Constant::Int(*x).into()),
+            Expr::Float(x, _) => Ok(Constant::Float(*x).into()),
+            Expr::String(x, _) => {
+                // String literal is a read-only char array in global constant
+                let constant = gen_string(x);
+                let name = self.unique_name("str");
+                let gvar = self.program.mem_pool.new_global_variable(
+                    name,
+                    constant.get_type(),
+                    false,
+                    constant,
+                );
+                self.program.module.global_variables.push(gvar);
+                Ok(Value::ReadWrite(gvar.into()))
+            }
+            Expr::Call(func, args, _) => {
                 // Ensure function is a defined variable
-                let Expr::Var(func_name) = *func.clone() else {
+                let Expr::Var(func_name, _) = *func.clone() else {
                     return Err(anyhow!("function is not variable")).with_context(|| context!());
                 };
                 let Some(func_ptr) = self.fun_env.get(&func_name).copied() else {
@@ -65,7 +87,7 @@ impl<'a> FunctionKit<'a> {
                         .with_context(|| context!());
                 };
 
-                // Generate arguments
+                // Generate arguments, variadic arguments like in `putf` are not converted
                 let mut operands = Vec::new();
                 if func_ptr.params.len() == args.len() {
                     for (param, arg) in func_ptr.params.iter().zip(args.iter()) {
@@ -73,33 +95,44 @@ impl<'a> FunctionKit<'a> {
                         operands.push(arg);
                     }
                 } else {
-                    for (i, arg) in args.iter().enumerate() {
-                        // Support constant argument only for dynamic library functions like `putf`
-                        let arg = if is_argument_const(&func_name, i) {
-                            let constant = gen_const(arg)?;
-                            let name = self.unique_name("format");
-                            let gvar = self.program.mem_pool.new_global_variable(
-                                name,
-                                constant.get_type(),
-                                false,
-                                constant,
-                            );
-                            self.program.module.global_variables.push(gvar);
-                            Value::ReadWrite(gvar.into()).load_uncast(self)?.0
-                        } else {
-                            self.gen_expr(arg)?.load_uncast(self)?.0
-                        };
+                    for arg in args.iter() {
+                        let arg = self.gen_expr(arg)?.load_uncast(self)?.0;
                         operands.push(arg);
                     }
                 }
 
-                // Call the function
+                // Call the function, arguments may have moved the exit block
                 let inst = self.program.mem_pool.get_call(func_ptr, operands);
-                exit.push_back(inst);
+                self.exit.unwrap().push_back(inst);
                 Ok(Value::ReadOnly(inst.into()))
             }
-            Expr::Unary(op, expr) => self.gen_unary(op, expr),
-            Expr::Binary(head, tail) => self.gen_binary(head, tail),
+            Expr::Unary(op, expr, _) => self.gen_unary(op, expr),
+            Expr::Binary(head, tail, _) => self.gen_binary(head, tail),
+            Expr::Assign(lhs, op, rhs, _) => {
+                // Generate right hand side before left hand side, like assignment statement
+                let rhs = self.gen_expr(rhs)?;
+                let lhs = self.gen_expr(lhs)?;
+
+                // Combine with old value for compound assignment
+                let val = match op {
+                    Some(op) => {
+                        let old = Value::ReadOnly(lhs.clone().load_uncast(self)?.0);
+                        self.gen_binary_op(op, old, rhs)?
+                    }
+                    None => rhs,
+                };
+
+                // Record is assigned as a whole and evaluates to itself,
+                // otherwise assignment evaluates to the value stored
+                let ty = lhs.get_type();
+                if matches!(ty, ValueType::Struct(_) | ValueType::Union(_)) {
+                    lhs.clone().assign(self, val)?;
+                    return Ok(lhs);
+                }
+                let val = Value::ReadOnly(val.load(ty, self)?);
+                lhs.assign(self, val.clone())?;
+                Ok(val)
+            }
             _ => Err(anyhow!("expr {:?} can't be translated to middle", expr))
                 .with_context(|| context!()),
         }
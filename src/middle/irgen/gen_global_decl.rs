@@ -20,28 +20,28 @@ use crate::middle::irgen::program_kit::ProgramKit;
 use crate::middle::irgen::value::Value;
 use anyhow::{anyhow, Context};
 
-use super::gen_const::gen_const;
-use super::gen_type::gen_type;
+use super::gen_const::{gen_const, shape_const};
+use super::gen_type::{gen_record, gen_type};
 
 impl<'a> ProgramKit<'a> {
     /// Generate a global declaration into the program
     /// Fails when declaration does not have a name
     pub fn gen_global_decl(&mut self, decl: &Decl) -> anyhow::Result<()> {
         match decl {
-            Decl::Var(ty, name, val) | Decl::Const(ty, name, val) => {
+            Decl::Var(ty, name, val, _) | Decl::Const(ty, name, val, _) => {
                 // Get variable type
-                let value_type = gen_type(ty)?;
+                let value_type = gen_type(ty, &self.ty_env)?;
 
                 // Get if value is global variable or constant
                 let is_global_variable: bool = match decl {
-                    Decl::Var(_, _, _) => true,
-                    Decl::Const(_, _, _) => false,
+                    Decl::Var(..) => true,
+                    Decl::Const(..) => false,
                     _ => false,
                 };
 
                 // Get initializer
                 let initializer = match val {
-                    Some(v) => gen_const(v)?,
+                    Some(v) => shape_const(gen_const(v, &self.ty_env)?, &value_type)?,
                     None => value_type.default_initializer()?,
                 };
 
@@ -61,16 +61,32 @@ impl<'a> ProgramKit<'a> {
                 self.program.module.global_variables.push(global_val);
                 Ok(())
             }
-            Decl::Func(Type::Function(return_ty, params), id, _) => {
+            Decl::Func(Type::Function(return_ty, params), id, body, _) => {
                 // Get function type
-                let fty = gen_type(return_ty)?;
+                let fty = gen_type(return_ty, &self.ty_env)?;
+                if fty.is_record() {
+                    return Err(anyhow!("function `{}` can't return {}", id, fty))
+                        .with_context(|| context!());
+                }
+
+                // Redeclaration shares the function, which takes parameter names from definition
+                let redecl = self.fun_env.get(id).copied();
+                if redecl.is_some() && body.is_none() {
+                    return Ok(());
+                }
 
                 // Create function
-                let mut fun_ptr = self.program.mem_pool.new_function(id.clone(), fty.clone());
+                let mut fun_ptr = redecl
+                    .unwrap_or_else(|| self.program.mem_pool.new_function(id.clone(), fty.clone()));
+                fun_ptr.params.clear();
 
                 // Generate parameters
                 for param in params.iter() {
-                    let value_type = gen_type(&param.ty)?;
+                    let value_type = gen_type(&param.ty, &self.ty_env)?;
+                    if value_type.is_record() {
+                        return Err(anyhow!("{} should be passed by pointer", value_type))
+                            .with_context(|| context!());
+                    }
                     let param = self
                         .program
                         .mem_pool
@@ -78,14 +94,17 @@ impl<'a> ProgramKit<'a> {
                     fun_ptr.params.push(param);
                 }
 
-                // Add function to environment
-                self.fun_env.insert(id.clone(), fun_ptr);
-
-                // Add function to program
-                self.program.module.functions.push(fun_ptr);
+                // Add function to environment and program
+                if redecl.is_none() {
+                    self.fun_env.insert(id.clone(), fun_ptr);
+                    self.program.module.functions.push(fun_ptr);
+                }
                 Ok(())
             }
-            Decl::Stack(ls) => {
+            Decl::Struct(..) | Decl::Union(..) => {
+                gen_record(decl, &mut self.ty_env, &mut self.program.module.records)
+            }
+            Decl::Stack(ls, _) => {
                 for l in ls.iter() {
                     self.gen_global_decl(l)?;
                 }
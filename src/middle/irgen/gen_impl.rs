@@ -27,7 +27,7 @@ impl<'a> ProgramKit<'a> {
     /// Generate an implementation into the program
     pub fn gen_impl(&mut self, decl: &Decl) -> anyhow::Result<()> {
         match decl {
-            Decl::Func(_, id, Some(stmt)) => {
+            Decl::Func(_, id, Some(stmt), _) => {
                 // Get function and its type
                 let mut fun_ptr = self.fun_env.get(id).copied().ok_or(MiddleError::GenError)?;
                 let fty = fun_ptr.return_type.clone();
@@ -62,6 +62,7 @@ impl<'a> ProgramKit<'a> {
                     FunctionContext {
                         env: self.env.branch(),
                         fun_env: self.fun_env.branch(),
+                        ty_env: self.ty_env.branch(),
                         program: self.program,
                         counter: &mut counter,
                     },
@@ -14,21 +14,21 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::frontend::Decl;
-use crate::middle::ir::Operand;
+use crate::frontend::{Decl, Expr};
+use crate::middle::ir::{Operand, ValueType};
 use crate::middle::irgen::function_kit::FunctionKit;
 use crate::{context, middle::ir::Constant};
 use anyhow::{anyhow, Context};
 
-use super::gen_const::gen_const;
-use super::gen_type::gen_type;
+use super::gen_const::{gen_const, gen_string, shape_const};
+use super::gen_type::{gen_record, gen_type};
 use super::value::{alloc, Value};
 
 impl<'a> FunctionKit<'a> {
     /// Generate a declaration as a statement into the program
     pub fn gen_inner_decl(&mut self, decl: &Decl) -> anyhow::Result<()> {
         match decl {
-            Decl::Const(raw_ty, id, op) => {
+            Decl::Const(raw_ty, id, op, _) => {
                 // Make sure constant has an initializer
                 let Some(expr) = op else {
                     return Err(anyhow!("const declaration must have an initializer"))
@@ -36,14 +36,14 @@ impl<'a> FunctionKit<'a> {
                 };
 
                 // Translate type
-                let value_type = gen_type(raw_ty)?;
+                let value_type = gen_type(raw_ty, &self.ty_env)?;
 
                 // Generate constant value
-                let initializer = gen_const(expr)?;
+                let initializer = shape_const(gen_const(expr, &self.ty_env)?, &value_type)?;
 
-                // If constant is an array, collapse it and store into global variable
+                // If constant is an array or record, collapse it and store into global variable
                 let val = match initializer {
-                    Constant::Array(_) => {
+                    Constant::Array(_) | Constant::Struct(..) | Constant::Zero(_) => {
                         let name = self.unique_name(id);
                         let gvar = self.program.mem_pool.new_global_variable(
                             name,
@@ -61,16 +61,29 @@ impl<'a> FunctionKit<'a> {
                 self.env.insert(id.clone(), val);
                 Ok(())
             }
-            Decl::Var(raw_ty, id, op) => {
+            Decl::Var(raw_ty, id, op, _) => {
                 // Allocate space for variable, add to environment
-                let ty = gen_type(raw_ty)?;
+                let ty = gen_type(raw_ty, &self.ty_env)?;
                 let lhs = alloc(ty.clone(), self);
                 self.env.insert(id.clone(), lhs.clone());
 
                 // Assign to the variable if it is defined
                 if let Some(expr) = op {
-                    // Generate expression as variable type
-                    let rhs = self.gen_expr(expr)?;
+                    // Generate expression as variable type,
+                    // string literal initializes char array element by element
+                    let rhs = match (&ty, expr) {
+                        (ValueType::Array(element_type, _), Expr::String(x, _))
+                            if **element_type == ValueType::SignedChar =>
+                        {
+                            let Constant::Array(bytes) = shape_const(gen_string(x), &ty)? else {
+                                return Err(anyhow!("string is not an array"))
+                                    .with_context(|| context!());
+                            };
+                            let bytes = bytes.into_iter().map(|b| Value::ReadOnly(b.into()));
+                            Value::Array(bytes.collect())
+                        }
+                        _ => self.gen_expr(expr)?,
+                    };
 
                     // Memset 0 if `rhs` is array
                     if let Value::Array(_) = rhs {
@@ -88,7 +101,7 @@ impl<'a> FunctionKit<'a> {
                             vec![
                                 ptr.clone(),
                                 Operand::Constant(Constant::SignedChar(0)),
-                                Operand::Constant(Constant::Int(ty.size() as i32 * 4)),
+                                Operand::Constant(Constant::Int(ty.size() as i32)),
                                 Operand::Constant(Constant::Bool(false)),
                             ],
                         );
@@ -100,7 +113,10 @@ impl<'a> FunctionKit<'a> {
                 };
                 Ok(())
             }
-            Decl::Stack(decls) => {
+            Decl::Struct(..) | Decl::Union(..) => {
+                gen_record(decl, &mut self.ty_env, &mut self.program.module.records)
+            }
+            Decl::Stack(decls, _) => {
                 // Generate each declaration
                 for decl in decls.iter() {
                     self.gen_inner_decl(decl)?;
@@ -82,7 +82,3 @@ impl<'a> ProgramKit<'a> {
         );
     }
 }
-
-pub fn is_argument_const(func_name: &str, index: usize) -> bool {
-    func_name == "putf" && index == 0
-}
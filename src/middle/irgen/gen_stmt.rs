@@ -17,7 +17,7 @@
 use anyhow::{anyhow, Context, Result};
 
 use crate::context;
-use crate::frontend::Stmt;
+use crate::frontend::{Expr, Stmt};
 use crate::middle::ir::ValueType;
 
 use super::function_kit::FunctionKit;
@@ -29,12 +29,12 @@ impl<'a> FunctionKit<'a> {
             return Err(anyhow!("exit block can't be appended")).with_context(|| context!());
         };
         match stmt {
-            Stmt::Nothing => (),
-            Stmt::Decl(decl) => {
+            Stmt::Nothing(_) => (),
+            Stmt::Decl(decl, _) => {
                 // Generate declaration
                 self.gen_inner_decl(decl)?;
             }
-            Stmt::Expr(opt_lhs, expr) => {
+            Stmt::Expr(opt_lhs, expr, _) => {
                 // Generate expression
                 let rhs = self.gen_expr(expr)?;
 
@@ -43,7 +43,7 @@ impl<'a> FunctionKit<'a> {
                     self.gen_expr(lhs)?.assign(self, rhs)?;
                 }
             }
-            Stmt::If(cond, then, alt) => {
+            Stmt::If(cond, then, alt, _) => {
                 // Allocate basic blocks
                 let cond_name = self.unique_name("cond");
                 let cond_entry = self.program.mem_pool.new_basicblock(cond_name);
@@ -91,7 +91,7 @@ impl<'a> FunctionKit<'a> {
                 // Exit is final block
                 self.exit = Some(final_entry);
             }
-            Stmt::While(cond, body) => {
+            Stmt::While(cond, body, _) => {
                 // Allocate basic blocks
                 let cond_name = self.unique_name("cond");
                 let cond_entry = self.program.mem_pool.new_basicblock(cond_name);
@@ -126,7 +126,7 @@ impl<'a> FunctionKit<'a> {
                 // Exit is final block
                 self.exit = Some(final_entry);
             }
-            Stmt::DoWhile(body, cond) => {
+            Stmt::DoWhile(body, cond, _) => {
                 // Allocate basic blocks
                 let body_name = self.unique_name("body");
                 let body_entry = self.program.mem_pool.new_basicblock(body_name);
@@ -161,7 +161,147 @@ impl<'a> FunctionKit<'a> {
                 // Exit is final block
                 self.exit = Some(final_entry);
             }
-            Stmt::Break => {
+            Stmt::For(init, cond, step, body, _) => {
+                // Initializer is in an encapsulated scope, create a new FunctionKit for it
+                let mut kit = self.gen_function_kit(self.exit, self.break_to, self.continue_to);
+                let Some(mut init_exit) = kit.gen_stmt(init)?.exit else {
+                    return Err(anyhow!("for initializer has no exit")).with_context(|| context!());
+                };
+
+                // Allocate basic blocks
+                let cond_name = kit.unique_name("cond");
+                let cond_entry = kit.program.mem_pool.new_basicblock(cond_name);
+                let body_name = kit.unique_name("body");
+                let body_entry = kit.program.mem_pool.new_basicblock(body_name);
+                let step_name = kit.unique_name("step");
+                let step_entry = kit.program.mem_pool.new_basicblock(step_name);
+                let final_name = kit.unique_name("final");
+                let final_entry = kit.program.mem_pool.new_basicblock(final_name);
+
+                // Redirect initializer exit to condition block
+                init_exit.set_true_bb(cond_entry);
+                init_exit.push_back(kit.program.mem_pool.get_br(None));
+
+                // Add statements and br to body block, continue goes to step block
+                let body_exit = kit
+                    .gen_function_kit(Some(body_entry), Some(final_entry), Some(step_entry))
+                    .gen_stmt(body)?
+                    .exit;
+                if let Some(mut body_exit) = body_exit {
+                    body_exit.push_back(kit.program.mem_pool.get_br(None));
+                    body_exit.set_true_bb(step_entry);
+                }
+
+                // Add step and br to step block
+                let step_exit = kit
+                    .gen_function_kit(Some(step_entry), None, None)
+                    .gen_stmt(step)?
+                    .exit;
+                if let Some(mut step_exit) = step_exit {
+                    step_exit.push_back(kit.program.mem_pool.get_br(None));
+                    step_exit.set_true_bb(cond_entry);
+                }
+
+                // Add condition and br to condition block, loop forever without condition
+                kit.exit = Some(cond_entry);
+                if let Some(cond) = cond {
+                    let operand = kit.gen_expr(cond)?.load(ValueType::Bool, &mut kit)?;
+                    if let Some(mut cond_exit) = kit.exit {
+                        cond_exit.push_back(kit.program.mem_pool.get_br(Some(operand)));
+                        cond_exit.set_true_bb(body_entry);
+                        cond_exit.set_false_bb(final_entry);
+                    }
+                } else {
+                    let mut cond_exit = cond_entry;
+                    cond_exit.push_back(kit.program.mem_pool.get_br(None));
+                    cond_exit.set_true_bb(body_entry);
+                }
+
+                // Exit is final block
+                self.exit = Some(final_entry);
+            }
+            Stmt::Switch(cond, arms, _) => {
+                // Add condition to current block
+                let operand = self.gen_expr(cond)?.load(ValueType::Int, self)?;
+                let Some(mut cond_exit) = self.exit else {
+                    return Err(anyhow!("switch condition has no exit"))
+                        .with_context(|| context!());
+                };
+
+                // Allocate basic blocks
+                let mut arm_entries = Vec::new();
+                for (label, _) in arms.iter() {
+                    let arm_name =
+                        self.unique_name(if label.is_some() { "case" } else { "default" });
+                    arm_entries.push(self.program.mem_pool.new_basicblock(arm_name));
+                }
+                let final_name = self.unique_name("final");
+                let final_entry = self.program.mem_pool.new_basicblock(final_name);
+
+                // Collect case values, jump to final block if there's no default arm
+                let mut cases = Vec::new();
+                let mut case_entries = Vec::new();
+                let mut default_entry = None;
+                for ((label, _), entry) in arms.iter().zip(arm_entries.iter()) {
+                    match label {
+                        Some(Expr::Int(x, _)) => {
+                            if cases.contains(x) {
+                                return Err(anyhow!("duplicate case value {}", x))
+                                    .with_context(|| context!());
+                            }
+                            cases.push(*x);
+                            case_entries.push(*entry);
+                        }
+                        Some(label) => {
+                            return Err(anyhow!("case label {:?} is not constant", label))
+                                .with_context(|| context!());
+                        }
+                        None => {
+                            if default_entry.replace(*entry).is_some() {
+                                return Err(anyhow!("multiple default labels in one switch"))
+                                    .with_context(|| context!());
+                            }
+                        }
+                    }
+                }
+
+                // Add switch to condition block, default block is the first successor
+                // Switch without case is a plain jump
+                if cases.is_empty() {
+                    cond_exit.push_back(self.program.mem_pool.get_br(None));
+                    cond_exit.set_true_bb(default_entry.unwrap_or(final_entry));
+                } else {
+                    cond_exit.push_back(self.program.mem_pool.get_switch(operand, cases));
+                    cond_exit.push_succ_bb(default_entry.unwrap_or(final_entry));
+                    for entry in case_entries {
+                        cond_exit.push_succ_bb(entry);
+                    }
+                }
+
+                // All arms share a scope, create a new FunctionKit for it
+                // Break goes to final block, and continue is retained
+                let mut kit = self.gen_function_kit(None, Some(final_entry), self.continue_to);
+                for (ix, (_, stmts)) in arms.iter().enumerate() {
+                    // Add statements to arm block
+                    kit.exit = Some(arm_entries[ix]);
+                    for stmt in stmts.iter() {
+                        if kit.exit.is_some() {
+                            kit.gen_stmt(stmt)?;
+                        }
+                    }
+
+                    // Fall through to next arm, or final block for the last arm
+                    if let Some(mut arm_exit) = kit.exit {
+                        let next = arm_entries.get(ix + 1).copied().unwrap_or(final_entry);
+                        arm_exit.push_back(kit.program.mem_pool.get_br(None));
+                        arm_exit.set_true_bb(next);
+                    }
+                }
+
+                // Exit is final block
+                self.exit = Some(final_entry);
+            }
+            Stmt::Break(_) => {
                 // Add br instruction to exit block
                 let br = self.program.mem_pool.get_br(None);
                 exit.push_back(br);
@@ -178,7 +318,7 @@ impl<'a> FunctionKit<'a> {
                 // Exit block can't be appended further
                 self.exit = None;
             }
-            Stmt::Continue => {
+            Stmt::Continue(_) => {
                 // Add br instruction to exit block
                 let br = self.program.mem_pool.get_br(None);
                 exit.push_back(br);
@@ -195,7 +335,7 @@ impl<'a> FunctionKit<'a> {
                 // Exit block can't be appended further
                 self.exit = None;
             }
-            Stmt::Return(expr) => {
+            Stmt::Return(expr, _) => {
                 // Assign return value source to destination if possible
                 if let (Some(expr), Some(return_dst)) = (expr, self.return_value.clone()) {
                     let return_src = self.gen_expr(expr)?;
@@ -212,7 +352,7 @@ impl<'a> FunctionKit<'a> {
                     self.exit = None;
                 }
             }
-            Stmt::Block(stmts) => {
+            Stmt::Block(stmts, _) => {
                 // A block is an encapsulated scope, create a new FunctionKit for it
                 let mut kit = self.gen_function_kit(self.exit, self.break_to, self.continue_to);
 
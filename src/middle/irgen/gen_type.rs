@@ -18,27 +18,100 @@ use anyhow::{anyhow, Context, Result};
 
 use crate::{
     context,
-    frontend::Type,
-    middle::ir::{Constant, ValueType},
+    frontend::{Decl, Type},
+    middle::ir::{Constant, Record, ValueType},
+    utils::frame_map::FrameMap,
 };
 
 use super::gen_const::gen_const;
 
-/// Translate a frontend type to IR value type
-pub fn gen_type(ty: &Type) -> Result<ValueType> {
+/// Translate a frontend type to IR value type.
+/// Struct and union are looked up by tag in `ty_env`.
+pub fn gen_type(ty: &Type, ty_env: &FrameMap<String, ValueType>) -> Result<ValueType> {
     match ty {
         Type::Void => Ok(ValueType::Void),
         Type::Int => Ok(ValueType::Int),
+        Type::UnsignedInt => Ok(ValueType::UnsignedInt),
+        Type::Long => Ok(ValueType::Long),
+        Type::UnsignedLong => Ok(ValueType::UnsignedLong),
         Type::Float => Ok(ValueType::Float),
+        Type::Double => Ok(ValueType::Double),
         Type::Bool => Ok(ValueType::Bool),
-        Type::Pointer(ty) => Ok(ValueType::Pointer(Box::new(gen_type(ty)?))),
+        Type::Char => Ok(ValueType::SignedChar),
+        Type::Pointer(ty) => Ok(ValueType::Pointer(Box::new(gen_type(ty, ty_env)?))),
         Type::Array(ty, index_expr) => {
-            let index_constant = gen_const(index_expr)?;
+            let index_constant = gen_const(index_expr, ty_env)?;
             let Constant::Int(index) = index_constant else {
                 return Err(anyhow!("index is not an integer")).with_context(|| context!());
             };
-            Ok(ValueType::Array(Box::new(gen_type(ty)?), index as usize))
+            Ok(ValueType::Array(
+                Box::new(gen_type(ty, ty_env)?),
+                index as usize,
+            ))
         }
+        Type::Struct(tag) | Type::Union(tag) => match (ty, ty_env.get(tag)) {
+            (Type::Struct(_), Some(record @ ValueType::Struct(_)))
+            | (Type::Union(_), Some(record @ ValueType::Union(_))) => Ok(record.clone()),
+            (_, Some(_)) => Err(anyhow!("`{}` is defined as wrong kind of tag", tag))
+                .with_context(|| context!()),
+            (_, None) => Err(anyhow!("`{}` is not defined", tag)).with_context(|| context!()),
+        },
         _ => Err(anyhow!("type {:?} can't translate to middle", ty)).with_context(|| context!()),
     }
 }
+
+/// Define a struct or union declaration in `ty_env`, and add it to `records`.
+/// Record gets a unique name in module if its tag is shadowed.
+pub fn gen_record(
+    decl: &Decl,
+    ty_env: &mut FrameMap<String, ValueType>,
+    records: &mut Vec<ValueType>,
+) -> Result<()> {
+    let (Decl::Struct(tag, fields, _) | Decl::Union(tag, fields, _)) = decl else {
+        return Err(anyhow!("{:?} is not a record", decl)).with_context(|| context!());
+    };
+
+    // Find a name not used by other records
+    let is_used = |name: &str| {
+        records.iter().any(|ty| match ty {
+            ValueType::Struct(record) | ValueType::Union(record) => record.name == name,
+            _ => false,
+        })
+    };
+    let mut name = tag.clone();
+    let mut counter = 0;
+    while is_used(&name) {
+        counter += 1;
+        name = format!("{}.{}", tag, counter);
+    }
+
+    // Add record to environment before defining fields,
+    // so that a field can point to the record itself
+    let record = Record::new(name);
+    let ty = match decl {
+        Decl::Struct(..) => ValueType::Struct(record.clone()),
+        _ => ValueType::Union(record.clone()),
+    };
+    ty_env.insert(tag.clone(), ty.clone());
+
+    // Define fields, record can not contain itself
+    let mut members = Vec::new();
+    for field in fields.iter() {
+        let Some(id) = &field.id else {
+            return Err(anyhow!("field of `{}` must have a name", tag)).with_context(|| context!());
+        };
+        let field_type = gen_type(&field.ty, ty_env)?;
+        let mut base_type = &field_type;
+        while let ValueType::Array(element_type, _) = base_type {
+            base_type = element_type;
+        }
+        if *base_type == ty {
+            return Err(anyhow!("field `{}` has incomplete type {}", id, ty))
+                .with_context(|| context!());
+        }
+        members.push((id.clone(), field_type));
+    }
+    record.define(members)?;
+    records.push(ty);
+    Ok(())
+}
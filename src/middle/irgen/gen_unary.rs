@@ -15,7 +15,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::context;
-use crate::frontend::{Expr, UnaryOp};
+use crate::frontend::{BinaryOp, Expr, UnaryOp};
 use crate::middle::ir::{Constant, ValueType};
 use crate::middle::irgen::function_kit::FunctionKit;
 use crate::middle::irgen::value::Value;
@@ -24,41 +24,40 @@ use anyhow::{anyhow, Context};
 impl<'a> FunctionKit<'a> {
     /// Generate a unary expression
     pub fn gen_unary(&mut self, op: &UnaryOp, expr: &Expr) -> anyhow::Result<Value> {
+        // Generate argument, which may end in another block
+        let val = self.gen_expr(expr)?;
         let Some(mut exit) = self.exit else {
             return Err(anyhow!("exit block can't be appended")).with_context(|| context!());
         };
 
-        // Generate argument
-        let val = self.gen_expr(expr)?;
-
         // Calculate type for operator polymorphism
         let ty = val.get_type();
 
         // Apply operation
         match op {
             UnaryOp::Neg => {
-                // Return 0 - x
+                // Return 0 - x, char is promoted to int
+                let ty = promote(ty);
                 let operand = val.load(ty.clone(), self)?;
                 match ty {
-                    ValueType::Int => {
-                        let inst = self
-                            .program
-                            .mem_pool
-                            .get_sub(Constant::Int(0).into(), operand);
+                    ValueType::Int
+                    | ValueType::UnsignedInt
+                    | ValueType::Long
+                    | ValueType::UnsignedLong => {
+                        let zero = ty.default_initializer()?;
+                        let inst = self.program.mem_pool.get_sub(zero.into(), operand);
                         exit.push_back(inst);
                         Ok(Value::ReadOnly(inst.into()))
                     }
-                    ValueType::Float => {
-                        let inst = self
-                            .program
-                            .mem_pool
-                            .get_fsub(Constant::Float(0.0).into(), operand);
+                    ValueType::Float | ValueType::Double => {
+                        let zero = ty.default_initializer()?;
+                        let inst = self.program.mem_pool.get_fsub(zero.into(), operand);
                         exit.push_back(inst);
                         Ok(Value::ReadOnly(inst.into()))
                     }
                     ValueType::Bool => {
                         // Convert to int and then make negative
-                        let zext = self.program.mem_pool.get_zext(operand);
+                        let zext = self.program.mem_pool.get_zext(operand, ValueType::Int);
                         let sub = self
                             .program
                             .mem_pool
@@ -71,12 +70,11 @@ impl<'a> FunctionKit<'a> {
                 }
             }
             UnaryOp::Pos => {
-                // Return operand directly
+                // Return operand directly, char is promoted to int
+                let ty = promote(ty);
                 let operand = val.load(ty.clone(), self)?;
                 match ty {
-                    ValueType::Int | ValueType::Float | ValueType::Bool => {
-                        Ok(Value::ReadOnly(operand))
-                    }
+                    ty if ty.is_num() => Ok(Value::ReadOnly(operand)),
                     _ => Err(anyhow!("`+` for NaN")).with_context(|| context!()),
                 }
             }
@@ -92,6 +90,55 @@ impl<'a> FunctionKit<'a> {
                 exit.push_back(inst);
                 Ok(Value::ReadOnly(inst.into()))
             }
+            UnaryOp::AddrOf => {
+                let Value::ReadWrite(ptr) = val else {
+                    return Err(anyhow!("cannot take address of rvalue"))
+                        .with_context(|| context!());
+                };
+
+                // Address is taken with GEP like array decaying to pointer,
+                // so that allocated variable is always addressed through GEP
+                let inst =
+                    self.program
+                        .mem_pool
+                        .get_getelementptr(ty, ptr, vec![Constant::Int(0).into()]);
+                exit.push_back(inst);
+                Ok(Value::ReadOnly(inst.into()))
+            }
+            UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec => {
+                // Load old value and calculate new value, pointer steps by element
+                let old = Value::ReadOnly(val.clone().load_uncast(self)?.0);
+                let step = match op {
+                    UnaryOp::PreInc | UnaryOp::PostInc => BinaryOp::Add,
+                    _ => BinaryOp::Sub,
+                };
+                let new = self.gen_binary_op(&step, old.clone(), Constant::Int(1).into())?;
+
+                // Store new value, pre-operators evaluate to the stored value
+                let new = Value::ReadOnly(new.load(ty, self)?);
+                val.assign(self, new.clone())?;
+                match op {
+                    UnaryOp::PreInc | UnaryOp::PreDec => Ok(new),
+                    _ => Ok(old),
+                }
+            }
+            UnaryOp::Deref => {
+                // Pointed value can be assigned to
+                let (ptr, ptr_ty) = val.load_uncast(self)?;
+                if !ptr_ty.is_pointer() {
+                    return Err(anyhow!("cannot dereference {}", ptr_ty))
+                        .with_context(|| context!());
+                }
+                Ok(Value::ReadWrite(ptr))
+            }
         }
     }
 }
+
+/// Promote char to int for arithmetic, other types are unchanged
+fn promote(ty: ValueType) -> ValueType {
+    match ty {
+        ValueType::SignedChar => ValueType::Int,
+        ty => ty,
+    }
+}
@@ -39,6 +39,7 @@ pub fn gen(program: &frontend::Program) -> Result<middle::Program> {
         program: &mut result,
         env: FrameMap::new(),
         fun_env: FrameMap::new(),
+        ty_env: FrameMap::new(),
     }
     .gen(program)?;
     Ok(result)
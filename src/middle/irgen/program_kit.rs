@@ -16,7 +16,7 @@
 
 use anyhow::Result;
 
-use crate::middle::ir::FunPtr;
+use crate::middle::ir::{FunPtr, ValueType};
 use crate::middle::irgen::value::Value;
 use crate::utils::frame_map::FrameMap;
 use crate::{frontend, middle};
@@ -25,6 +25,7 @@ use crate::{frontend, middle};
 pub struct ProgramKit<'a> {
     pub env: FrameMap<'a, String, Value>,
     pub fun_env: FrameMap<'a, String, FunPtr>,
+    pub ty_env: FrameMap<'a, String, ValueType>,
     pub program: &'a mut middle::Program,
 }
 
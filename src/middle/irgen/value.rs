@@ -14,6 +14,7 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::cmp::Ordering;
 use std::collections::VecDeque;
 
 use anyhow::{anyhow, Context, Result};
@@ -99,6 +100,11 @@ impl Value {
                         // So the `value_type` is changed to reference accordingly
                         (inst, ValueType::Pointer((*element_type.clone()).into()))
                     }
+                    ValueType::Struct(_) | ValueType::Union(_) => {
+                        // Record is accessed through its fields only
+                        return Err(anyhow!("{} is not loadable", value_type))
+                            .with_context(|| context!());
+                    }
                     _ => (
                         // This load changes `element_type**` to `element_type*
                         kit.program.mem_pool.get_load(value_type.clone(), pointer),
@@ -119,6 +125,12 @@ impl Value {
     pub fn load(self, target: ValueType, kit: &mut FunctionKit) -> Result<Operand> {
         let (uncast_operand, loaded_type) = self.load_uncast(kit)?;
 
+        // Pointer can not be converted from or to number
+        if loaded_type.is_pointer() != target.is_pointer() {
+            return Err(anyhow!("cannot load from {} to {}", loaded_type, target))
+                .with_context(|| context!());
+        }
+
         // Return directly if base type matches
         if loaded_type.get_base_type() == target.get_base_type() {
             return Ok(uncast_operand);
@@ -126,54 +138,102 @@ impl Value {
 
         // Convert type if not match
         match (loaded_type, target) {
-            (ValueType::Int, ValueType::Float) => {
+            (ValueType::Bool, target) if target.is_integer() => {
                 // Direct convert
-                let inst = kit.program.mem_pool.get_itofp(uncast_operand);
+                let inst = kit.program.mem_pool.get_zext(uncast_operand, target);
                 kit.exit.unwrap().push_back(inst);
                 Ok(inst.into())
             }
-            (ValueType::Float, ValueType::Int) => {
-                // Direct convert
-                let inst = kit.program.mem_pool.get_fptoi(uncast_operand);
+            (ValueType::Bool, target) => {
+                // Convert to int first, and then convert to target
+                let inst = kit
+                    .program
+                    .mem_pool
+                    .get_zext(uncast_operand, ValueType::Int);
                 kit.exit.unwrap().push_back(inst);
-                Ok(inst.into())
+                Value::ReadOnly(inst.into()).load(target, kit)
             }
-            (ValueType::Bool, ValueType::Int) => {
-                // Direct convert
-                let inst = kit.program.mem_pool.get_zext(uncast_operand);
+            (ValueType::SignedChar, target) => {
+                // Sign extend to int first, and then convert to target
+                let inst = kit
+                    .program
+                    .mem_pool
+                    .get_sext(uncast_operand, ValueType::Int);
                 kit.exit.unwrap().push_back(inst);
-                Ok(inst.into())
+                Value::ReadOnly(inst.into()).load(target, kit)
             }
-            (ValueType::Bool, ValueType::Float) => {
-                // Convert to int first and then float
-                let inst = kit.program.mem_pool.get_zext(uncast_operand);
-                kit.exit.unwrap().push_back(inst);
-                let inst = kit.program.mem_pool.get_itofp(inst.into());
-                kit.exit.unwrap().push_back(inst);
-                Ok(inst.into())
-            }
-            (ValueType::Int, ValueType::Bool) => {
-                // Direct convert
+            (ty, ValueType::Bool) if ty.is_integer() => {
+                // Compare with 0
                 let inst = kit.program.mem_pool.get_icmp(
                     ICmpOp::Ne,
-                    ValueType::Int,
+                    ty.clone(),
                     uncast_operand,
-                    Constant::Int(0).into(),
+                    ty.default_initializer()?.into(),
                 );
                 kit.exit.unwrap().push_back(inst);
                 Ok(inst.into())
             }
-            (ValueType::Float, ValueType::Bool) => {
+            (ty, ValueType::Bool) if ty.is_float() => {
                 // Compare with 0.0 (NaN is treated as true)
                 let inst = kit.program.mem_pool.get_fcmp(
                     FCmpOp::Une,
-                    ValueType::Float,
+                    ty.clone(),
                     uncast_operand,
-                    Constant::Float(0.0).into(),
+                    ty.default_initializer()?.into(),
                 );
                 kit.exit.unwrap().push_back(inst);
                 Ok(inst.into())
             }
+            (ty, target) if ty.is_integer() && target.is_integer() => {
+                // Extend by signedness of source, truncate, or reinterpret in the same width
+                let inst = match ty.size().cmp(&target.size()) {
+                    Ordering::Less if ty.is_unsigned() => {
+                        kit.program.mem_pool.get_zext(uncast_operand, target)
+                    }
+                    Ordering::Less => kit.program.mem_pool.get_sext(uncast_operand, target),
+                    Ordering::Greater => kit.program.mem_pool.get_trunc(uncast_operand, target),
+                    Ordering::Equal => kit.program.mem_pool.get_bitcast(uncast_operand, target),
+                };
+                kit.exit.unwrap().push_back(inst);
+                Ok(inst.into())
+            }
+            (ty, target) if ty.is_integer() && target.is_float() => {
+                // Direct convert
+                let inst = kit.program.mem_pool.get_itofp(uncast_operand, target);
+                kit.exit.unwrap().push_back(inst);
+                Ok(inst.into())
+            }
+            (ty, target) if ty.is_float() && target.is_integer() && target.size() < 4 => {
+                // Convert to int first, and then truncate
+                let operand = Value::ReadOnly(uncast_operand).load(ValueType::Int, kit)?;
+                let inst = kit.program.mem_pool.get_trunc(operand, target);
+                kit.exit.unwrap().push_back(inst);
+                Ok(inst.into())
+            }
+            (ty, target) if ty.is_float() && target.is_integer() => {
+                // Direct convert
+                let inst = kit.program.mem_pool.get_fptoi(uncast_operand, target);
+                kit.exit.unwrap().push_back(inst);
+                Ok(inst.into())
+            }
+            (ValueType::Float, ValueType::Double) => {
+                // Direct convert
+                let inst = kit
+                    .program
+                    .mem_pool
+                    .get_fpext(uncast_operand, ValueType::Double);
+                kit.exit.unwrap().push_back(inst);
+                Ok(inst.into())
+            }
+            (ValueType::Double, ValueType::Float) => {
+                // Direct convert
+                let inst = kit
+                    .program
+                    .mem_pool
+                    .get_fptrunc(uncast_operand, ValueType::Float);
+                kit.exit.unwrap().push_back(inst);
+                Ok(inst.into())
+            }
             (ty, target) => {
                 Err(anyhow!("cannot load from {} to {}", ty, target)).with_context(|| context!())
             }
@@ -236,6 +296,64 @@ impl Value {
         }
     }
 
+    /// Get member of a struct or union by name
+    ///
+    /// Struct field is selected by `getelementptr` with constant index,
+    /// while union member shares the pointer and only changes its type.
+    pub fn member(self, kit: &mut FunctionKit, name: &str) -> Result<Value> {
+        let value_type = self.get_type();
+        let Value::ReadWrite(pointer) = self else {
+            return Err(anyhow!("can't get member `{}` from operand", name))
+                .with_context(|| context!());
+        };
+        let (ValueType::Struct(record) | ValueType::Union(record)) = &value_type else {
+            return Err(anyhow!("{} has no member `{}`", value_type, name))
+                .with_context(|| context!());
+        };
+        let Some(index) = record.field_index(name) else {
+            return Err(anyhow!("{} has no member `{}`", value_type, name))
+                .with_context(|| context!());
+        };
+        let gep = match value_type {
+            ValueType::Struct(_) => kit.program.mem_pool.get_getelementptr(
+                value_type.clone(),
+                pointer,
+                vec![Constant::Int(0).into(), Constant::Int(index as i32).into()],
+            ),
+            _ => kit.program.mem_pool.get_getelementptr(
+                record.fields()[index].1.clone(),
+                pointer,
+                vec![Constant::Int(0).into()],
+            ),
+        };
+        kit.exit.unwrap().push_back(gep);
+        Ok(Value::ReadWrite(gep.into()))
+    }
+
+    /// Copy a struct, union or array from `val` element by element
+    fn copy_from(self, kit: &mut FunctionKit, val: Value) -> Result<()> {
+        match self.get_type() {
+            ValueType::Struct(record) | ValueType::Union(record) => {
+                // Overlapping union members are copied from the same bytes
+                for (name, _) in record.fields().iter() {
+                    let dst = self.clone().member(kit, name)?;
+                    let src = val.clone().member(kit, name)?;
+                    dst.assign(kit, src)?;
+                }
+            }
+            ValueType::Array(_, len) => {
+                for i in 0..len {
+                    let index = vec![Constant::Int(0).into(), Constant::Int(i as i32).into()];
+                    let dst = self.clone().getelementptr(kit, index.clone())?;
+                    let src = val.clone().getelementptr(kit, index)?;
+                    dst.assign(kit, src)?;
+                }
+            }
+            ty => return Err(anyhow!("can't copy {}", ty)).with_context(|| context!()),
+        }
+        Ok(())
+    }
+
     /// Assign a value to this value
     pub fn assign(self, kit: &mut FunctionKit, val: Value) -> Result<()> {
         let target = self.get_type();
@@ -244,10 +362,16 @@ impl Value {
         if let Value::Array(arr) = val {
             // Iterate all sub-pointers
             for (i, elem) in arr.into_iter().enumerate() {
-                let sub_ptr = self.clone().getelementptr(
-                    kit,
-                    vec![Constant::Int(0).into(), Constant::Int(i as i32).into()],
-                )?;
+                let sub_ptr = match &target {
+                    // Union is initialized through its first member
+                    ValueType::Union(record) if i == 0 && !record.fields().is_empty() => {
+                        self.clone().member(kit, &record.fields()[0].0)?
+                    }
+                    _ => self.clone().getelementptr(
+                        kit,
+                        vec![Constant::Int(0).into(), Constant::Int(i as i32).into()],
+                    )?,
+                };
 
                 // Assign element to sub-pointer
                 sub_ptr.assign(kit, elem)?;
@@ -255,6 +379,11 @@ impl Value {
             return Ok(());
         }
 
+        // If target is record, or array of record, copy each element separately
+        if let (true, Value::ReadWrite(_)) = (target.has_record(), &val) {
+            return self.copy_from(kit, val);
+        }
+
         // Otherwise load element
         match self {
             Value::ReadOnly(_) => Err(anyhow!("cannot assign operand")).with_context(|| context!()),
@@ -0,0 +1,119 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::middle::ir::{
+    instruction::{
+        misc_inst::{FCmpOp, ICmpOp},
+        InstType,
+    },
+    ValueType,
+};
+
+/// Untyped literal, converted to a constant once its type is known.
+#[derive(Clone, Debug)]
+pub enum Literal {
+    Int(i64),
+    Hex(u64),
+    Float(f64),
+    Bool(bool),
+    Zero,
+    /// Elements of an array or fields of a struct, each with its type.
+    Aggregate(Vec<(ValueType, Literal)>),
+}
+
+/// Reference to a value in instruction operand.
+#[derive(Clone, Debug)]
+pub enum Value {
+    Local(String),
+    Global(String),
+    Literal(Literal),
+}
+
+pub enum InstKind {
+    /// Binary operation of the same type, such as `add` and `fmul`.
+    Binary(InstType, ValueType, Value, Value),
+    ICmp(ICmpOp, ValueType, Value, Value),
+    FCmp(FCmpOp, ValueType, Value, Value),
+    /// Conversion from `(type, value)` to the target type, such as `zext` and `sitofp`.
+    Cast(String, (ValueType, Value), ValueType),
+    Alloca(ValueType, usize),
+    Load(ValueType, Value),
+    Store((ValueType, Value), Value),
+    GetElementPtr(ValueType, Value, Vec<(ValueType, Value)>),
+    Phi(ValueType, Vec<(Value, String)>),
+    Call(String, Vec<(ValueType, Value)>),
+    Ret(Option<(ValueType, Value)>),
+    /// Branch with optional condition, to one or two labels.
+    Br(Option<Value>, Vec<String>),
+    /// Switch on a value, to the default label and the label of each case.
+    Switch((ValueType, Value), String, Vec<(i32, String)>),
+}
+
+pub struct Inst {
+    pub name: Option<String>,
+    pub kind: InstKind,
+    pub line: usize,
+}
+
+impl Inst {
+    /// Labels this instruction jumps to, in the order of successors.
+    pub fn targets(&self) -> Vec<&str> {
+        match &self.kind {
+            InstKind::Br(_, labels) => labels.iter().map(String::as_str).collect(),
+            InstKind::Switch(_, default, cases) => std::iter::once(default.as_str())
+                .chain(cases.iter().map(|(_, label)| label.as_str()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_terminator(&self) -> bool {
+        matches!(
+            self.kind,
+            InstKind::Ret(_) | InstKind::Br(..) | InstKind::Switch(..)
+        )
+    }
+}
+
+pub struct Block {
+    pub name: String,
+    pub insts: Vec<Inst>,
+    pub line: usize,
+}
+
+pub struct Func {
+    pub name: String,
+    pub return_type: ValueType,
+    pub params: Vec<(ValueType, String)>,
+    /// Blocks of a defined function, `None` for a declaration.
+    pub blocks: Option<Vec<Block>>,
+    pub line: usize,
+}
+
+pub struct Global {
+    pub name: String,
+    pub value_type: ValueType,
+    pub variable_or_constant: bool,
+    pub initializer: Literal,
+    pub line: usize,
+}
+
+/// Parsed module, with record types already resolved into `ValueType`.
+pub struct Module {
+    pub records: Vec<(ValueType, Vec<ValueType>)>,
+    pub globals: Vec<Global>,
+    pub funcs: Vec<Func>,
+}
@@ -0,0 +1,409 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+
+use crate::middle::{
+    ir::{
+        instruction::{downcast_mut, misc_inst::Phi, InstType},
+        BBPtr, Constant, FunPtr, GlobalPtr, InstPtr, Operand, ValueType,
+    },
+    Program,
+};
+
+use super::ast::*;
+
+/// Build a program from parsed module.
+pub fn build(module: Module) -> Result<Program> {
+    let mut program = Program::new();
+    let mut globals = HashMap::new();
+    let mut funcs = HashMap::new();
+
+    // Define records
+    for (ty, mut fields) in module.records {
+        match &ty {
+            ValueType::Struct(record) => record.define(index_fields(fields))?,
+            ValueType::Union(record) => {
+                // Union is printed as its most aligned member padded to its size,
+                // widen the padding to the whole union so that size and alignment are kept
+                if let [member, ValueType::Array(element, padding)] = fields.as_mut_slice() {
+                    if **element == ValueType::SignedChar {
+                        *padding += member.size();
+                    }
+                }
+                record.define(index_fields(fields))?
+            }
+            _ => unreachable!(),
+        }
+        program.module.records.push(ty);
+    }
+
+    // Create global variables
+    for global in module.globals {
+        let initializer = constant(&global.value_type, &global.initializer)
+            .map_err(|e| anyhow!("line {}: {}", global.line, e))?;
+        let gvar = program.mem_pool.new_global_variable(
+            global.name.clone(),
+            global.value_type,
+            global.variable_or_constant,
+            initializer,
+        );
+        if globals.insert(global.name.clone(), gvar).is_some() {
+            return Err(anyhow!(
+                "line {}: @{} is defined twice",
+                global.line,
+                global.name
+            ));
+        }
+        program.module.global_variables.push(gvar);
+    }
+
+    // Declare all functions before building bodies, as calls can refer to later functions
+    for func in module.funcs.iter() {
+        let mut fun_ptr = program
+            .mem_pool
+            .new_function(func.name.clone(), func.return_type.clone());
+        for (i, (ty, name)) in func.params.iter().enumerate() {
+            let name = if name.is_empty() {
+                format!("p{}", i)
+            } else {
+                name.clone()
+            };
+            let param = program.mem_pool.new_parameter(name, ty.clone());
+            fun_ptr.params.push(param);
+        }
+        if funcs.insert(func.name.clone(), fun_ptr).is_some() {
+            return Err(anyhow!(
+                "line {}: @{} is defined twice",
+                func.line,
+                func.name
+            ));
+        }
+        program.module.functions.push(fun_ptr);
+    }
+
+    // Build function bodies
+    for func in module.funcs {
+        let Some(blocks) = func.blocks else {
+            continue;
+        };
+        FunctionBuilder {
+            program: &mut program,
+            globals: &globals,
+            funcs: &funcs,
+            fun: funcs[&func.name],
+            bbs: HashMap::new(),
+            values: HashMap::new(),
+        }
+        .build(blocks)?;
+    }
+    Ok(program)
+}
+
+/// Record fields are not named in IR, so they are named by their index.
+fn index_fields(fields: Vec<ValueType>) -> Vec<(String, ValueType)> {
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, ty)| (i.to_string(), ty))
+        .collect()
+}
+
+/// Convert a literal to constant of given type.
+fn constant(ty: &ValueType, literal: &Literal) -> Result<Constant> {
+    let constant = match (ty, literal) {
+        (_, Literal::Zero) => Constant::Zero(ty.clone()),
+        (ValueType::Bool, Literal::Bool(b)) => Constant::Bool(*b),
+        (ValueType::Bool, Literal::Int(i)) => Constant::Bool(*i != 0),
+        (ValueType::SignedChar, Literal::Int(i)) => Constant::SignedChar(*i as i8),
+        (ValueType::Int | ValueType::UnsignedInt, Literal::Int(i)) => Constant::Int(*i as i32),
+        (ValueType::Long | ValueType::UnsignedLong, Literal::Int(i)) => Constant::Long(*i),
+        // Float is written in bits of double, like LLVM does
+        (ValueType::Float, Literal::Hex(h)) => Constant::Float(f64::from_bits(*h) as f32),
+        (ValueType::Float, Literal::Float(f)) => Constant::Float(*f as f32),
+        (ValueType::Double, Literal::Hex(h)) => Constant::Double(f64::from_bits(*h)),
+        (ValueType::Double, Literal::Float(f)) => Constant::Double(*f),
+        (ValueType::Array(element, size), Literal::Aggregate(items)) if items.len() == *size => {
+            let items = items
+                .iter()
+                .map(|(item_ty, item)| {
+                    if item_ty != element.as_ref() {
+                        return Err(anyhow!("{} element in {}", item_ty, ty));
+                    }
+                    constant(item_ty, item)
+                })
+                .collect::<Result<_>>()?;
+            Constant::Array(items)
+        }
+        (ValueType::Struct(record), Literal::Aggregate(items))
+            if items.len() == record.fields().len() =>
+        {
+            let items = items
+                .iter()
+                .map(|(item_ty, item)| constant(item_ty, item))
+                .collect::<Result<_>>()?;
+            Constant::Struct(record.clone(), items)
+        }
+        _ => return Err(anyhow!("invalid literal for {}", ty)),
+    };
+    Ok(constant)
+}
+
+struct FunctionBuilder<'a> {
+    program: &'a mut Program,
+    globals: &'a HashMap<String, GlobalPtr>,
+    funcs: &'a HashMap<String, FunPtr>,
+    fun: FunPtr,
+    bbs: HashMap<String, BBPtr>,
+    /// Parameters and named instructions defined so far.
+    values: HashMap<String, Operand>,
+}
+
+impl<'a> FunctionBuilder<'a> {
+    fn build(mut self, blocks: Vec<Block>) -> Result<()> {
+        // Create blocks
+        for block in blocks.iter() {
+            let bb = self.program.mem_pool.new_basicblock(block.name.clone());
+            if self.bbs.insert(block.name.clone(), bb).is_some() {
+                return Err(anyhow!(
+                    "line {}: %{} is defined twice",
+                    block.line,
+                    block.name
+                ));
+            }
+        }
+
+        // Link blocks with terminators
+        let mut exit = None;
+        for block in blocks.iter() {
+            let mut bb = self.bbs[&block.name];
+            let Some(term) = block.insts.last().filter(|inst| inst.is_terminator()) else {
+                return Err(anyhow!(
+                    "line {}: %{} is not terminated",
+                    block.line,
+                    block.name
+                ));
+            };
+            for target in term.targets() {
+                bb.push_succ_bb(self.block(target, term.line)?);
+            }
+            if exit.is_none() && matches!(term.kind, InstKind::Ret(_)) {
+                exit = Some(bb);
+            }
+        }
+        let last = self.bbs[&blocks.last().unwrap().name];
+        self.fun.entry = Some(self.bbs[&blocks[0].name]);
+        self.fun.exit = Some(exit.unwrap_or(last));
+        for param in self.fun.params.iter() {
+            self.values.insert(param.name.clone(), (*param).into());
+        }
+
+        // Build reachable blocks in reverse postorder, so that values are defined before use
+        // except for phis, then build unreachable blocks in order of appearance
+        let mut order = self.fun.rpo_iter().collect::<Vec<_>>();
+        let reachable = order.iter().copied().collect::<HashSet<_>>();
+        order.extend(
+            blocks
+                .iter()
+                .map(|block| self.bbs[&block.name])
+                .filter(|bb| !reachable.contains(bb)),
+        );
+        let blocks = blocks
+            .into_iter()
+            .map(|block| (self.bbs[&block.name], block))
+            .collect::<HashMap<_, _>>();
+        let mut phis = Vec::new();
+        for mut bb in order {
+            for inst in blocks[&bb].insts.iter() {
+                let inst_ptr = self.inst(inst)?;
+                bb.push_back(inst_ptr);
+                if let InstKind::Phi(ty, incoming) = &inst.kind {
+                    phis.push((inst_ptr, ty, incoming, inst.line));
+                }
+                if let Some(name) = &inst.name {
+                    if self.values.insert(name.clone(), inst_ptr.into()).is_some() {
+                        return Err(anyhow!("line {}: %{} is defined twice", inst.line, name));
+                    }
+                }
+            }
+        }
+
+        // Fill incoming values of phis, which can refer to values defined later
+        for (mut phi, ty, incoming, line) in phis {
+            for (value, label) in incoming {
+                let op = self.operand(ty, value, line)?;
+                let bb = self.block(label, line)?;
+                downcast_mut::<Phi>(phi.as_mut().as_mut()).add_incoming_value(op, bb);
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&self, name: &str, line: usize) -> Result<BBPtr> {
+        self.bbs
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!("line {}: undefined label %{}", line, name))
+    }
+
+    fn operand(&self, ty: &ValueType, value: &Value, line: usize) -> Result<Operand> {
+        match value {
+            Value::Local(name) => self
+                .values
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("line {}: undefined value %{}", line, name)),
+            Value::Global(name) => self
+                .globals
+                .get(name)
+                .map(|gvar| (*gvar).into())
+                .ok_or_else(|| anyhow!("line {}: undefined global @{}", line, name)),
+            Value::Literal(literal) => constant(ty, literal)
+                .map(Operand::Constant)
+                .map_err(|e| anyhow!("line {}: {}", line, e)),
+        }
+    }
+
+    fn typed_operand(&self, (ty, value): &(ValueType, Value), line: usize) -> Result<Operand> {
+        self.operand(ty, value, line)
+    }
+
+    fn inst(&mut self, inst: &Inst) -> Result<InstPtr> {
+        let line = inst.line;
+        let ptr_type = ValueType::Pointer(ValueType::Void.into());
+        let inst_ptr = match &inst.kind {
+            InstKind::Binary(op, ty, lhs, rhs) => {
+                let lhs = self.operand(ty, lhs, line)?;
+                let rhs = self.operand(ty, rhs, line)?;
+                let mem_pool = &mut self.program.mem_pool;
+                match op {
+                    InstType::Add => mem_pool.get_add(lhs, rhs),
+                    InstType::FAdd => mem_pool.get_fadd(lhs, rhs),
+                    InstType::Sub => mem_pool.get_sub(lhs, rhs),
+                    InstType::FSub => mem_pool.get_fsub(lhs, rhs),
+                    InstType::Mul => mem_pool.get_mul(lhs, rhs),
+                    InstType::FMul => mem_pool.get_fmul(lhs, rhs),
+                    InstType::UDiv => mem_pool.get_udiv(lhs, rhs),
+                    InstType::SDiv => mem_pool.get_sdiv(lhs, rhs),
+                    InstType::FDiv => mem_pool.get_fdiv(lhs, rhs),
+                    InstType::URem => mem_pool.get_urem(lhs, rhs),
+                    InstType::SRem => mem_pool.get_srem(lhs, rhs),
+                    InstType::Shl => mem_pool.get_shl(lhs, rhs),
+                    InstType::LShr => mem_pool.get_lshr(lhs, rhs),
+                    InstType::AShr => mem_pool.get_ashr(lhs, rhs),
+                    InstType::And => mem_pool.get_and(lhs, rhs),
+                    InstType::Or => mem_pool.get_or(lhs, rhs),
+                    InstType::Xor => mem_pool.get_xor(lhs, rhs),
+                    _ => unreachable!(),
+                }
+            }
+            InstKind::ICmp(op, ty, lhs, rhs) => {
+                let lhs = self.operand(ty, lhs, line)?;
+                let rhs = self.operand(ty, rhs, line)?;
+                self.program.mem_pool.get_icmp(*op, ty.clone(), lhs, rhs)
+            }
+            InstKind::FCmp(op, ty, lhs, rhs) => {
+                let lhs = self.operand(ty, lhs, line)?;
+                let rhs = self.operand(ty, rhs, line)?;
+                self.program.mem_pool.get_fcmp(*op, ty.clone(), lhs, rhs)
+            }
+            InstKind::Cast(op, src, ty) => {
+                let src = self.typed_operand(src, line)?;
+                let ty = ty.clone();
+                let mem_pool = &mut self.program.mem_pool;
+                match op.as_str() {
+                    "zext" => mem_pool.get_zext(src, ty),
+                    "sext" => mem_pool.get_sext(src, ty),
+                    "trunc" => mem_pool.get_trunc(src, ty),
+                    "fpext" => mem_pool.get_fpext(src, ty),
+                    "fptrunc" => mem_pool.get_fptrunc(src, ty),
+                    "bitcast" => mem_pool.get_bitcast(src, ty),
+                    "sitofp" | "uitofp" => mem_pool.get_itofp(src, ty),
+                    "fptosi" => mem_pool.get_fptoi(src, ty),
+                    "fptoui" => mem_pool.get_fptoi(src, ty.to_unsigned()),
+                    "ptrtoint" if ty == ValueType::Int => mem_pool.get_ptrtoi(src),
+                    _ => return Err(anyhow!("line {}: unsupported {} to {}", line, op, ty)),
+                }
+            }
+            InstKind::Alloca(ty, num_elements) => {
+                self.program.mem_pool.get_alloca(ty.clone(), *num_elements)
+            }
+            InstKind::Load(ty, ptr) => {
+                let ptr = self.operand(&ptr_type, ptr, line)?;
+                self.program.mem_pool.get_load(ty.clone(), ptr)
+            }
+            InstKind::Store(value, ptr) => {
+                let value = self.typed_operand(value, line)?;
+                let ptr = self.operand(&ptr_type, ptr, line)?;
+                self.program.mem_pool.get_store(value, ptr)
+            }
+            InstKind::GetElementPtr(element_type, ptr, index) => {
+                let ptr = self.operand(&ptr_type, ptr, line)?;
+                let index = index
+                    .iter()
+                    .map(|index| self.typed_operand(index, line))
+                    .collect::<Result<Vec<_>>>()?;
+                if index.is_empty() {
+                    return Err(anyhow!("line {}: getelementptr without index", line));
+                }
+                let mut ty = element_type;
+                for i in &index[1..] {
+                    ty = ty
+                        .get_indexed_type(i)
+                        .ok_or_else(|| anyhow!("line {}: invalid index {} into {}", line, i, ty))?;
+                }
+                self.program
+                    .mem_pool
+                    .get_getelementptr(element_type.clone(), ptr, index)
+            }
+            InstKind::Phi(ty, _) => self.program.mem_pool.get_phi(ty.clone(), vec![]),
+            InstKind::Call(name, args) => {
+                let func = self
+                    .funcs
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| anyhow!("line {}: undefined function @{}", line, name))?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.typed_operand(arg, line))
+                    .collect::<Result<_>>()?;
+                self.program.mem_pool.get_call(func, args)
+            }
+            InstKind::Ret(value) => {
+                let value = match value {
+                    Some(value) => Some(self.typed_operand(value, line)?),
+                    None => None,
+                };
+                self.program.mem_pool.get_ret(value)
+            }
+            InstKind::Br(cond, _) => {
+                let cond = match cond {
+                    Some(cond) => Some(self.operand(&ValueType::Bool, cond, line)?),
+                    None => None,
+                };
+                self.program.mem_pool.get_br(cond)
+            }
+            InstKind::Switch(cond, _, cases) => {
+                let cond = self.typed_operand(cond, line)?;
+                let cases = cases.iter().map(|(case, _)| *case).collect();
+                self.program.mem_pool.get_switch(cond, cases)
+            }
+        };
+        Ok(inst_ptr)
+    }
+}
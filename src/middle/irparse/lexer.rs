@@ -0,0 +1,138 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+
+/// Kind of IR token.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TokenKind {
+    /// `%name`, a local value, block label or record type.
+    Local(String),
+    /// `@name`, a global variable or function.
+    Global(String),
+    /// Keyword, primitive type or bare label name.
+    Word(String),
+    Int(i64),
+    /// `0x...`, bits of a floating point number.
+    Hex(u64),
+    Float(f64),
+    Punct(char),
+}
+
+/// IR token with the line it starts at.
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub line: usize,
+}
+
+impl Token {
+    pub fn is_punct(&self, punct: char) -> bool {
+        self.kind == TokenKind::Punct(punct)
+    }
+
+    pub fn is_word(&self, word: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word(w) if w == word)
+    }
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Local(name) => write!(f, "%{}", name),
+            TokenKind::Global(name) => write!(f, "@{}", name),
+            TokenKind::Word(word) => write!(f, "{}", word),
+            TokenKind::Int(i) => write!(f, "{}", i),
+            TokenKind::Hex(h) => write!(f, "0x{:x}", h),
+            TokenKind::Float(fl) => write!(f, "{}", fl),
+            TokenKind::Punct(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '$' | '-')
+}
+
+/// Split IR text into tokens, skipping whitespace and `;` comments.
+pub fn tokenize(code: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    for (index, text) in code.lines().enumerate() {
+        let line = index + 1;
+        let mut pos = 0;
+        while let Some(c) = text[pos..].chars().next() {
+            let start = pos;
+            let kind = match c {
+                ';' => break,
+                c if c.is_whitespace() => {
+                    pos += c.len_utf8();
+                    continue;
+                }
+                '%' | '@' => {
+                    pos = skip_while(text, pos + 1, is_name_char);
+                    let name = text[start + 1..pos].to_string();
+                    if name.is_empty() {
+                        return Err(anyhow!("line {}: expected name after `{}`", line, c));
+                    }
+                    if c == '%' {
+                        TokenKind::Local(name)
+                    } else {
+                        TokenKind::Global(name)
+                    }
+                }
+                c if c.is_ascii_digit() || c == '-' => {
+                    pos = skip_while(text, pos, |c| {
+                        c.is_ascii_alphanumeric() || "-+.".contains(c)
+                    });
+                    let literal = &text[start..pos];
+                    number(literal)
+                        .ok_or_else(|| anyhow!("line {}: invalid number `{}`", line, literal))?
+                }
+                c if is_name_char(c) => {
+                    pos = skip_while(text, pos, is_name_char);
+                    TokenKind::Word(text[start..pos].to_string())
+                }
+                '=' | ',' | '(' | ')' | '[' | ']' | '{' | '}' | '*' | ':' => {
+                    pos += 1;
+                    TokenKind::Punct(c)
+                }
+                c => return Err(anyhow!("line {}: unexpected character `{}`", line, c)),
+            };
+            tokens.push(Token { kind, line });
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns end of the longest run of characters satisfying `pred` from `pos`.
+fn skip_while(text: &str, pos: usize, pred: impl Fn(char) -> bool) -> usize {
+    text[pos..]
+        .find(|c: char| !pred(c))
+        .map_or(text.len(), |len| pos + len)
+}
+
+/// Parse an integer, hexadecimal bits or decimal floating point literal.
+fn number(literal: &str) -> Option<TokenKind> {
+    if let Some(hex) = literal.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok().map(TokenKind::Hex)
+    } else if let Ok(i) = literal.parse() {
+        Some(TokenKind::Int(i))
+    } else {
+        literal.parse().ok().map(TokenKind::Float)
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+
+use crate::context;
+
+use super::Program;
+
+mod ast;
+mod builder;
+mod lexer;
+mod parser;
+
+/// Parse middle IR from the LLVM IR subset emitted by `Module::gen_llvm_ir`.
+///
+/// Values are resolved by name, so hand-written IR can use any name for values and blocks,
+/// and instructions are renamed by their new id once built.
+/// Integer types are parsed as signed, as LLVM integers carry no signedness.
+/// The parsed program is not verified, use `analysis::verifier` if needed.
+pub fn parse(code: &str) -> Result<Program> {
+    let tokens = lexer::tokenize(code).with_context(|| context!())?;
+    let module = parser::Parser::new(tokens)
+        .module()
+        .with_context(|| context!())?;
+    builder::build(module).with_context(|| context!())
+}
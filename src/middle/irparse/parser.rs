@@ -0,0 +1,599 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::middle::ir::{
+    instruction::{
+        misc_inst::{FCmpOp, ICmpOp},
+        InstType,
+    },
+    Record, ValueType,
+};
+
+use super::{
+    ast::*,
+    lexer::{Token, TokenKind},
+};
+
+const BINARY_OPS: [InstType; 17] = [
+    InstType::Add,
+    InstType::FAdd,
+    InstType::Sub,
+    InstType::FSub,
+    InstType::Mul,
+    InstType::FMul,
+    InstType::UDiv,
+    InstType::SDiv,
+    InstType::FDiv,
+    InstType::URem,
+    InstType::SRem,
+    InstType::Shl,
+    InstType::LShr,
+    InstType::AShr,
+    InstType::And,
+    InstType::Or,
+    InstType::Xor,
+];
+
+const ICMP_OPS: [ICmpOp; 10] = [
+    ICmpOp::Eq,
+    ICmpOp::Ne,
+    ICmpOp::Slt,
+    ICmpOp::Sle,
+    ICmpOp::Sgt,
+    ICmpOp::Sge,
+    ICmpOp::Ult,
+    ICmpOp::Ule,
+    ICmpOp::Ugt,
+    ICmpOp::Uge,
+];
+
+const FCMP_OPS: [FCmpOp; 16] = [
+    FCmpOp::Oeq,
+    FCmpOp::Ogt,
+    FCmpOp::Oge,
+    FCmpOp::Olt,
+    FCmpOp::Ole,
+    FCmpOp::One,
+    FCmpOp::Ord,
+    FCmpOp::Ueq,
+    FCmpOp::Ugt,
+    FCmpOp::Uge,
+    FCmpOp::Ult,
+    FCmpOp::Ule,
+    FCmpOp::Une,
+    FCmpOp::Uno,
+    FCmpOp::False,
+    FCmpOp::True,
+];
+
+const CAST_OPS: [&str; 11] = [
+    "zext", "sext", "trunc", "fpext", "fptrunc", "bitcast", "sitofp", "uitofp", "fptosi", "fptoui",
+    "ptrtoint",
+];
+
+/// Linkage and wrapping flags, which carry no meaning in middle IR.
+const IGNORED_WORDS: [&str; 6] = ["dso_local", "internal", "private", "external", "nsw", "nuw"];
+
+/// Recursive descent parser from tokens to `ast::Module`.
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    records: HashMap<String, ValueType>,
+}
+
+impl Parser {
+    /// Create a parser, declaring every `%struct.*` and `%union.*` type defined in tokens,
+    /// so that types can refer to records defined later.
+    pub fn new(tokens: Vec<Token>) -> Self {
+        let mut records = HashMap::new();
+        for window in tokens.windows(3) {
+            if let [Token {
+                kind: TokenKind::Local(name),
+                ..
+            }, eq, ty] = window
+            {
+                if !eq.is_punct('=') || !ty.is_word("type") {
+                    continue;
+                }
+                if let Some(record) = name.strip_prefix("struct.") {
+                    let ty = ValueType::Struct(Record::new(record.to_string()));
+                    records.insert(name.clone(), ty);
+                } else if let Some(record) = name.strip_prefix("union.") {
+                    let ty = ValueType::Union(Record::new(record.to_string()));
+                    records.insert(name.clone(), ty);
+                }
+            }
+        }
+        Self {
+            tokens,
+            pos: 0,
+            records,
+        }
+    }
+
+    pub fn module(&mut self) -> Result<Module> {
+        let mut module = Module {
+            records: Vec::new(),
+            globals: Vec::new(),
+            funcs: Vec::new(),
+        };
+        while let Some(token) = self.peek() {
+            match &token.kind {
+                TokenKind::Local(name) => {
+                    let name = name.clone();
+                    self.pos += 1;
+                    self.expect_punct('=')?;
+                    self.expect_word("type")?;
+                    let fields = self.aggregate('{', '}', Self::ty)?;
+                    module.records.push((self.records[&name].clone(), fields));
+                }
+                TokenKind::Global(_) => module.globals.push(self.global()?),
+                TokenKind::Word(w) if w == "declare" || w == "define" => {
+                    module.funcs.push(self.func()?)
+                }
+                _ => return Err(self.error("expected global, function or type definition")),
+            }
+        }
+        Ok(module)
+    }
+
+    fn global(&mut self) -> Result<Global> {
+        let line = self.line();
+        let name = self.global_name()?;
+        self.expect_punct('=')?;
+        self.skip_ignored();
+        let variable_or_constant = if self.eat_word("global") {
+            true
+        } else if self.eat_word("constant") {
+            false
+        } else {
+            return Err(self.error("expected `global` or `constant`"));
+        };
+        let value_type = self.ty()?;
+        let initializer = self.literal()?;
+        if self.eat_punct(',') {
+            self.expect_word("align")?;
+            self.int()?;
+        }
+        Ok(Global {
+            name,
+            value_type,
+            variable_or_constant,
+            initializer,
+            line,
+        })
+    }
+
+    fn func(&mut self) -> Result<Func> {
+        let line = self.line();
+        let is_define = self.eat_word("define");
+        if !is_define {
+            self.expect_word("declare")?;
+        }
+        self.skip_ignored();
+        let return_type = self.ty()?;
+        let name = self.global_name()?;
+        let params = self.aggregate('(', ')', |p| {
+            let ty = p.ty()?;
+            let name = match p.peek().map(|t| &t.kind) {
+                Some(TokenKind::Local(name)) => name.clone(),
+                _ => String::new(),
+            };
+            if !name.is_empty() {
+                p.pos += 1;
+            }
+            Ok((ty, name))
+        })?;
+        let blocks = if is_define {
+            self.expect_punct('{')?;
+            let mut blocks = Vec::new();
+            while !self.eat_punct('}') {
+                blocks.push(self.block(blocks.is_empty())?);
+            }
+            Some(blocks)
+        } else {
+            None
+        };
+        Ok(Func {
+            name,
+            return_type,
+            params,
+            blocks,
+            line,
+        })
+    }
+
+    fn block(&mut self, is_first: bool) -> Result<Block> {
+        let line = self.line();
+        let name = match self.label_def() {
+            Some(name) => name,
+            // Label of entry block can be omitted
+            None if is_first => "entry".to_string(),
+            None => return Err(self.error("expected label")),
+        };
+        let mut insts = Vec::new();
+        while self.peek().is_some_and(|t| !t.is_punct('}')) && !self.at_label_def() {
+            insts.push(self.inst()?);
+        }
+        Ok(Block { name, insts, line })
+    }
+
+    fn at_label_def(&self) -> bool {
+        matches!(self.peek().map(|t| &t.kind), Some(TokenKind::Word(_)))
+            && self
+                .tokens
+                .get(self.pos + 1)
+                .is_some_and(|t| t.is_punct(':'))
+    }
+
+    fn label_def(&mut self) -> Option<String> {
+        if !self.at_label_def() {
+            return None;
+        }
+        let TokenKind::Word(name) = self.tokens[self.pos].kind.clone() else {
+            unreachable!()
+        };
+        self.pos += 2;
+        Some(name)
+    }
+
+    fn inst(&mut self) -> Result<Inst> {
+        let line = self.line();
+        let name = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Local(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                self.expect_punct('=')?;
+                Some(name)
+            }
+            _ => None,
+        };
+        let op = self.word()?;
+        let kind = if let Some(ty) = BINARY_OPS.iter().find(|ty| ty.to_string() == op) {
+            self.skip_ignored();
+            let value_type = self.ty()?;
+            let lhs = self.value()?;
+            self.expect_punct(',')?;
+            InstKind::Binary(*ty, value_type, lhs, self.value()?)
+        } else if op == "icmp" || op == "fcmp" {
+            let pred = self.word()?;
+            let icmp = ICMP_OPS.iter().find(|p| p.to_string() == pred);
+            let fcmp = FCMP_OPS.iter().find(|p| p.to_string() == pred);
+            let comp_type = self.ty()?;
+            let lhs = self.value()?;
+            self.expect_punct(',')?;
+            let rhs = self.value()?;
+            match (op.as_str(), icmp, fcmp) {
+                ("icmp", Some(pred), _) => InstKind::ICmp(*pred, comp_type, lhs, rhs),
+                ("fcmp", _, Some(pred)) => InstKind::FCmp(*pred, comp_type, lhs, rhs),
+                _ => {
+                    return Err(anyhow!(
+                        "line {}: unknown {} predicate `{}`",
+                        line,
+                        op,
+                        pred
+                    ))
+                }
+            }
+        } else if CAST_OPS.contains(&op.as_str()) {
+            let src = self.typed_value()?;
+            self.expect_word("to")?;
+            InstKind::Cast(op, src, self.ty()?)
+        } else {
+            match op.as_str() {
+                "alloca" => {
+                    let ty = self.ty()?;
+                    let mut num_elements = 1;
+                    if self.eat_punct(',') {
+                        self.ty()?;
+                        num_elements = self.int()? as usize;
+                    }
+                    InstKind::Alloca(ty, num_elements)
+                }
+                "load" => {
+                    let ty = self.ty()?;
+                    self.expect_punct(',')?;
+                    let (_, ptr) = self.typed_value()?;
+                    InstKind::Load(ty, ptr)
+                }
+                "store" => {
+                    let value = self.typed_value()?;
+                    self.expect_punct(',')?;
+                    let (_, ptr) = self.typed_value()?;
+                    InstKind::Store(value, ptr)
+                }
+                "getelementptr" => {
+                    self.eat_word("inbounds");
+                    let element_type = self.ty()?;
+                    self.expect_punct(',')?;
+                    let (_, ptr) = self.typed_value()?;
+                    let mut index = Vec::new();
+                    while self.eat_punct(',') {
+                        index.push(self.typed_value()?);
+                    }
+                    InstKind::GetElementPtr(element_type, ptr, index)
+                }
+                "phi" => {
+                    let ty = self.ty()?;
+                    let mut incoming = Vec::new();
+                    loop {
+                        self.expect_punct('[')?;
+                        let value = self.value()?;
+                        self.expect_punct(',')?;
+                        incoming.push((value, self.local_name()?));
+                        self.expect_punct(']')?;
+                        if !self.eat_punct(',') {
+                            break;
+                        }
+                    }
+                    InstKind::Phi(ty, incoming)
+                }
+                "call" => {
+                    self.ty()?;
+                    let func = self.global_name()?;
+                    InstKind::Call(func, self.aggregate('(', ')', Self::typed_value)?)
+                }
+                "ret" if self.eat_word("void") => InstKind::Ret(None),
+                "ret" => InstKind::Ret(Some(self.typed_value()?)),
+                "br" if self.peek().is_some_and(|t| t.is_word("label")) => {
+                    InstKind::Br(None, vec![self.label()?])
+                }
+                "br" => {
+                    let (_, cond) = self.typed_value()?;
+                    self.expect_punct(',')?;
+                    let true_bb = self.label()?;
+                    self.expect_punct(',')?;
+                    InstKind::Br(Some(cond), vec![true_bb, self.label()?])
+                }
+                "switch" => {
+                    let cond = self.typed_value()?;
+                    self.expect_punct(',')?;
+                    let default = self.label()?;
+                    self.expect_punct('[')?;
+                    let mut cases = Vec::new();
+                    while !self.eat_punct(']') {
+                        self.ty()?;
+                        let case = self.int()? as i32;
+                        self.expect_punct(',')?;
+                        cases.push((case, self.label()?));
+                    }
+                    InstKind::Switch(cond, default, cases)
+                }
+                _ => return Err(anyhow!("line {}: unknown instruction `{}`", line, op)),
+            }
+        };
+        Ok(Inst { name, kind, line })
+    }
+
+    fn ty(&mut self) -> Result<ValueType> {
+        let token = self.next()?;
+        let mut ty = match &token.kind {
+            TokenKind::Word(w) => match w.as_str() {
+                "void" => ValueType::Void,
+                "i1" => ValueType::Bool,
+                "i8" => ValueType::SignedChar,
+                "i32" => ValueType::Int,
+                "i64" => ValueType::Long,
+                "float" => ValueType::Float,
+                "double" => ValueType::Double,
+                // Opaque pointer, only meaningful where pointee type is given elsewhere
+                "ptr" => ValueType::Pointer(ValueType::Void.into()),
+                _ => return Err(anyhow!("line {}: unknown type `{}`", token.line, w)),
+            },
+            TokenKind::Local(name) => self
+                .records
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("line {}: undefined type `%{}`", token.line, name))?,
+            TokenKind::Punct('[') => {
+                let size = self.int()? as usize;
+                self.expect_word("x")?;
+                let element = self.ty()?;
+                self.expect_punct(']')?;
+                ValueType::Array(element.into(), size)
+            }
+            kind => {
+                return Err(anyhow!(
+                    "line {}: expected type, found `{}`",
+                    token.line,
+                    kind
+                ))
+            }
+        };
+        while self.eat_punct('*') {
+            ty = ValueType::Pointer(ty.into());
+        }
+        Ok(ty)
+    }
+
+    fn literal(&mut self) -> Result<Literal> {
+        let token = self.peek().ok_or_else(|| self.error("expected literal"))?;
+        let literal = match &token.kind {
+            TokenKind::Int(i) => Literal::Int(*i),
+            TokenKind::Hex(h) => Literal::Hex(*h),
+            TokenKind::Float(f) => Literal::Float(*f),
+            TokenKind::Word(w) if w == "true" => Literal::Bool(true),
+            TokenKind::Word(w) if w == "false" => Literal::Bool(false),
+            TokenKind::Word(w) if w == "zeroinitializer" => Literal::Zero,
+            TokenKind::Punct('[') => return Ok(Literal::Aggregate(self.typed_literals('[', ']')?)),
+            TokenKind::Punct('{') => return Ok(Literal::Aggregate(self.typed_literals('{', '}')?)),
+            kind => {
+                return Err(anyhow!(
+                    "line {}: expected literal, found `{}`",
+                    token.line,
+                    kind
+                ))
+            }
+        };
+        self.pos += 1;
+        Ok(literal)
+    }
+
+    fn typed_literals(&mut self, open: char, close: char) -> Result<Vec<(ValueType, Literal)>> {
+        self.aggregate(open, close, |p| Ok((p.ty()?, p.literal()?)))
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::Local(name)) => {
+                self.pos += 1;
+                Ok(Value::Local(name))
+            }
+            Some(TokenKind::Global(name)) => {
+                self.pos += 1;
+                Ok(Value::Global(name))
+            }
+            _ => Ok(Value::Literal(self.literal()?)),
+        }
+    }
+
+    fn typed_value(&mut self) -> Result<(ValueType, Value)> {
+        Ok((self.ty()?, self.value()?))
+    }
+
+    /// `label %name` in branch instructions.
+    fn label(&mut self) -> Result<String> {
+        self.expect_word("label")?;
+        self.local_name()
+    }
+
+    /// Items separated by `,` and enclosed in `open` and `close`.
+    fn aggregate<T>(
+        &mut self,
+        open: char,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.expect_punct(open)?;
+        let mut items = Vec::new();
+        if self.eat_punct(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if self.eat_punct(close) {
+                return Ok(items);
+            }
+            self.expect_punct(',')?;
+        }
+    }
+
+    fn skip_ignored(&mut self) {
+        while IGNORED_WORDS.iter().any(|w| self.eat_word(w)) {}
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn line(&self) -> usize {
+        self.peek()
+            .or(self.tokens.last())
+            .map_or(0, |token| token.line)
+    }
+
+    fn error(&self, msg: &str) -> anyhow::Error {
+        match self.peek() {
+            Some(token) => anyhow!("line {}: {}, found `{}`", token.line, msg, token.kind),
+            None => anyhow!("line {}: {}, found end of input", self.line(), msg),
+        }
+    }
+
+    fn eat_punct(&mut self, punct: char) -> bool {
+        let matched = self.peek().is_some_and(|t| t.is_punct(punct));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect_punct(&mut self, punct: char) -> Result<()> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", punct)))
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let matched = self.peek().is_some_and(|t| t.is_word(word));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<()> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", word)))
+        }
+    }
+
+    fn word(&mut self) -> Result<String> {
+        match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::Word(w)) => {
+                self.pos += 1;
+                Ok(w)
+            }
+            _ => Err(self.error("expected keyword")),
+        }
+    }
+
+    fn int(&mut self) -> Result<i64> {
+        match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::Int(i)) => {
+                self.pos += 1;
+                Ok(i)
+            }
+            _ => Err(self.error("expected integer")),
+        }
+    }
+
+    fn local_name(&mut self) -> Result<String> {
+        match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::Local(name)) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected local name")),
+        }
+    }
+
+    fn global_name(&mut self) -> Result<String> {
+        match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::Global(name)) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected global name")),
+        }
+    }
+}
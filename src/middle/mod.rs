@@ -17,11 +17,12 @@
 use crate::{/* errors::MiddleError, */ frontend, utils::mem::ObjPtr};
 use anyhow::Context;
 use ir::ir_builder::IRBuilder;
-use transform::ultimate_pass;
+use transform::{pass_manager, ultimate_pass};
 
 pub mod analysis;
 pub mod ir;
 pub mod irgen;
+pub mod irparse;
 pub mod transform;
 
 use std::pin::Pin;
@@ -46,6 +47,12 @@ pub fn optimize(program: &mut Program) {
     ultimate_pass::optimize_program(program).unwrap();
 }
 
+/// Optimize program with a user-specified pipeline like `mem2reg,func_inline,licm`.
+pub fn optimize_with_passes(program: &mut Program, passes: &str) -> Result<()> {
+    pass_manager::optimize_program(program, passes).with_context(|| context!())?;
+    Ok(())
+}
+
 impl Default for Program {
     fn default() -> Self {
         Self::new()
@@ -17,7 +17,7 @@
 use anyhow::{Ok, Result};
 
 use crate::middle::{
-    ir::{BBPtr, FunPtr},
+    ir::{instruction::InstType, BBPtr, FunPtr},
     Program,
 };
 
@@ -65,7 +65,11 @@ impl<'a> BlockFuse<'a> {
         let Some(mut pred) = bb.get_pred_bb().first().cloned() else {
             return Ok(false);
         };
-        if pred.get_succ_bb().len() == 1 && bb.get_pred_bb().len() == 1 {
+        // Switch with only default target has an operand, it's not fused here
+        if pred.get_succ_bb().len() == 1
+            && bb.get_pred_bb().len() == 1
+            && pred.get_last_inst().get_type() == InstType::Br
+        {
             // Last instruction is "br", move the rest to successor block
             for inst in pred.iter_rev().skip(1) {
                 bb.push_front(inst);
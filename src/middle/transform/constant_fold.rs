@@ -22,7 +22,7 @@ use crate::middle::ir::instruction::misc_inst::FCmpOp;
 use crate::middle::ir::instruction::misc_inst::ICmp;
 use crate::middle::ir::instruction::misc_inst::ICmpOp;
 use crate::middle::{
-    ir::{instruction::InstType, Constant, InstPtr, Operand},
+    ir::{instruction::InstType, Constant, InstPtr, Operand, ValueType},
     Program,
 };
 
@@ -68,213 +68,256 @@ impl<'a> ConstantFold<'a> {
     }
 
     fn constant_fold_inst(&mut self, mut inst: InstPtr) -> Result<bool> {
-        match inst.get_type() {
-            InstType::Add | InstType::FAdd => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs + rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        let operands = inst
+            .get_operand()
+            .iter()
+            .map(|op| match op {
+                Operand::Constant(c) => Some(c.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(result) = operands.and_then(|operands| fold_constant(inst, operands)) {
+            inst.replace_self(&result.into());
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// Evaluate instruction with constant operands.
+/// Returns `None` if instruction can't be evaluated at compile time.
+pub fn fold_constant(inst: InstPtr, operands: Vec<Constant>) -> Option<Constant> {
+    match inst.get_type() {
+        InstType::Add | InstType::FAdd => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs + rhs;
+                return Some(result);
             }
-            InstType::Sub | InstType::FSub => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs - rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::Sub | InstType::FSub => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs - rhs;
+                return Some(result);
             }
-            InstType::Mul | InstType::FMul => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs * rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::Mul | InstType::FMul => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs * rhs;
+                return Some(result);
             }
-            InstType::UDiv => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let lhs: u32 = lhs.into();
-                    let rhs: u32 = rhs.into();
-                    let result = lhs / rhs;
-                    inst.replace_self(&Operand::Constant(result.into()));
-                    return Ok(true);
+        }
+        InstType::UDiv => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                if is_zero(&rhs) {
+                    return None;
                 }
+                let ty = lhs.get_type();
+                let unsigned_ty = ty.to_unsigned();
+                let result = (lhs.cast(&unsigned_ty) / rhs.cast(&unsigned_ty)).cast(&ty);
+                return Some(result);
             }
-            InstType::SDiv | InstType::FDiv => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs / rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
+        }
+        InstType::SDiv => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                if is_zero(&rhs) {
+                    return None;
                 }
+                let ty = lhs.get_type();
+                let signed_ty = ty.to_signed();
+                let result = (lhs.cast(&signed_ty) / rhs.cast(&signed_ty)).cast(&ty);
+                return Some(result);
             }
-            InstType::URem | InstType::SRem => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs % rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::FDiv => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs / rhs;
+                return Some(result);
             }
-            InstType::Shl => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs << rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
+        }
+        InstType::URem => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                if is_zero(&rhs) {
+                    return None;
                 }
+                let ty = lhs.get_type();
+                let unsigned_ty = ty.to_unsigned();
+                let result = (lhs.cast(&unsigned_ty) % rhs.cast(&unsigned_ty)).cast(&ty);
+                return Some(result);
             }
-            InstType::AShr => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs >> rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
+        }
+        InstType::SRem => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                if is_zero(&rhs) {
+                    return None;
                 }
+                let ty = lhs.get_type();
+                let signed_ty = ty.to_signed();
+                let result = (lhs.cast(&signed_ty) % rhs.cast(&signed_ty)).cast(&ty);
+                return Some(result);
             }
-            InstType::And => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs & rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::Shl => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs << rhs;
+                return Some(result);
             }
-            InstType::Or => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs | rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::AShr => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let ty = lhs.get_type();
+                let result = (lhs.cast(&ty.to_signed()) >> rhs).cast(&ty);
+                return Some(result);
             }
-            InstType::Xor => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs ^ rhs;
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::LShr => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let ty = lhs.get_type();
+                let result = (lhs.cast(&ty.to_unsigned()) >> rhs).cast(&ty);
+                return Some(result);
             }
-            InstType::ZextTo | InstType::ItoFp | InstType::FpToI => {
-                let src = inst.get_operand()[0].clone();
-                if let Operand::Constant(src) = src {
-                    let result = src.cast(&inst.get_value_type());
-                    inst.replace_self(&result.into());
-                    return Ok(true);
-                }
+        }
+        InstType::And => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs & rhs;
+                return Some(result);
             }
-            InstType::SextTo => {
-                let src = inst.get_operand()[0].clone();
-                if let Operand::Constant(Constant::Bool(b)) = src {
-                    let result = if b { -1 } else { 0 };
-                    inst.replace_self(&Operand::Constant(result.into()));
-                    return Ok(true);
-                }
+        }
+        InstType::Or => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs | rhs;
+                return Some(result);
             }
-            InstType::ICmp => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                let cmp_inst = downcast_ref::<ICmp>(inst.as_ref().as_ref());
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = match cmp_inst.op {
-                        ICmpOp::Eq => lhs == rhs,
-                        ICmpOp::Ne => lhs != rhs,
-                        ICmpOp::Slt => lhs < rhs,
-                        ICmpOp::Sle => lhs <= rhs,
-                        ICmpOp::Sgt => lhs > rhs,
-                        ICmpOp::Sge => lhs >= rhs,
-                        ICmpOp::Ult => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs < rhs
-                        }
-                        ICmpOp::Ule => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs <= rhs
-                        }
-                        ICmpOp::Ugt => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs > rhs
-                        }
-                        ICmpOp::Uge => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs >= rhs
-                        }
-                    };
-                    inst.replace_self(&Operand::Constant(result.into()));
-                    return Ok(true);
-                }
+        }
+        InstType::Xor => {
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = lhs ^ rhs;
+                return Some(result);
+            }
+        }
+        InstType::ItoFp
+        | InstType::FpToI
+        | InstType::Trunc
+        | InstType::FpExt
+        | InstType::FpTrunc
+        | InstType::BitCast => {
+            if let Ok([src]) = <[Constant; 1]>::try_from(operands) {
+                let result = src.cast(&inst.get_value_type());
+                return Some(result);
             }
-            InstType::FCmp => {
-                let lhs = inst.get_operand()[0].clone();
-                let rhs = inst.get_operand()[1].clone();
-                let cmp_inst = downcast_ref::<FCmp>(inst.as_ref().as_ref());
-                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = match cmp_inst.op {
-                        FCmpOp::False => false,
-                        FCmpOp::True => true,
-                        FCmpOp::Oeq => lhs == rhs,
-                        FCmpOp::One => lhs != rhs,
-                        FCmpOp::Olt => lhs < rhs,
-                        FCmpOp::Ole => lhs <= rhs,
-                        FCmpOp::Ogt => lhs > rhs,
-                        FCmpOp::Oge => lhs >= rhs,
-                        FCmpOp::Ueq => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
-                            lhs == rhs || (lhs.is_nan() && rhs.is_nan())
-                        }
-                        FCmpOp::Une => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
-                            lhs.is_nan() || rhs.is_nan() || lhs != rhs
-                        }
-                        FCmpOp::Ult => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
-                            lhs < rhs || (lhs.is_nan() && !rhs.is_nan())
-                        }
-                        FCmpOp::Ule => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
-                            lhs <= rhs || (lhs.is_nan() && !rhs.is_nan())
-                        }
-                        FCmpOp::Ugt => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
-                            lhs > rhs || (!lhs.is_nan() && rhs.is_nan())
-                        }
-                        FCmpOp::Uge => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
-                            lhs >= rhs || (!lhs.is_nan() && rhs.is_nan())
-                        }
-                        _ => todo!(),
-                    };
-                    inst.replace_self(&Operand::Constant(result.into()));
-                    return Ok(true);
+        }
+        InstType::ZextTo => {
+            if let Ok([src]) = <[Constant; 1]>::try_from(operands) {
+                let unsigned_ty = src.get_type().to_unsigned();
+                let result = src.cast(&unsigned_ty).cast(&inst.get_value_type());
+                return Some(result);
+            }
+        }
+        InstType::SextTo => {
+            if let Ok([src]) = <[Constant; 1]>::try_from(operands) {
+                if let Constant::Bool(b) = src {
+                    let result = Constant::Int(if b { -1 } else { 0 }).cast(&inst.get_value_type());
+                    return Some(result);
                 }
+                let signed_ty = src.get_type().to_signed();
+                let result = src.cast(&signed_ty).cast(&inst.get_value_type());
+                return Some(result);
             }
-            _ => (),
         }
-        Ok(false)
+        InstType::ICmp => {
+            let cmp_inst = downcast_ref::<ICmp>(inst.as_ref().as_ref());
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = match cmp_inst.op {
+                    ICmpOp::Eq => lhs == rhs,
+                    ICmpOp::Ne => lhs != rhs,
+                    ICmpOp::Slt => {
+                        let signed_ty = lhs.get_type().to_signed();
+                        lhs.cast(&signed_ty) < rhs.cast(&signed_ty)
+                    }
+                    ICmpOp::Sle => {
+                        let signed_ty = lhs.get_type().to_signed();
+                        lhs.cast(&signed_ty) <= rhs.cast(&signed_ty)
+                    }
+                    ICmpOp::Sgt => {
+                        let signed_ty = lhs.get_type().to_signed();
+                        lhs.cast(&signed_ty) > rhs.cast(&signed_ty)
+                    }
+                    ICmpOp::Sge => {
+                        let signed_ty = lhs.get_type().to_signed();
+                        lhs.cast(&signed_ty) >= rhs.cast(&signed_ty)
+                    }
+                    ICmpOp::Ult => {
+                        let unsigned_ty = lhs.get_type().to_unsigned();
+                        lhs.cast(&unsigned_ty) < rhs.cast(&unsigned_ty)
+                    }
+                    ICmpOp::Ule => {
+                        let unsigned_ty = lhs.get_type().to_unsigned();
+                        lhs.cast(&unsigned_ty) <= rhs.cast(&unsigned_ty)
+                    }
+                    ICmpOp::Ugt => {
+                        let unsigned_ty = lhs.get_type().to_unsigned();
+                        lhs.cast(&unsigned_ty) > rhs.cast(&unsigned_ty)
+                    }
+                    ICmpOp::Uge => {
+                        let unsigned_ty = lhs.get_type().to_unsigned();
+                        lhs.cast(&unsigned_ty) >= rhs.cast(&unsigned_ty)
+                    }
+                };
+                return Some(result.into());
+            }
+        }
+        InstType::FCmp => {
+            let cmp_inst = downcast_ref::<FCmp>(inst.as_ref().as_ref());
+            if let Ok([lhs, rhs]) = <[Constant; 2]>::try_from(operands) {
+                let result = match cmp_inst.op {
+                    FCmpOp::False => false,
+                    FCmpOp::True => true,
+                    FCmpOp::Oeq => lhs == rhs,
+                    FCmpOp::One => lhs != rhs,
+                    FCmpOp::Olt => lhs < rhs,
+                    FCmpOp::Ole => lhs <= rhs,
+                    FCmpOp::Ogt => lhs > rhs,
+                    FCmpOp::Oge => lhs >= rhs,
+                    FCmpOp::Ueq => {
+                        let lhs: f64 = lhs.into();
+                        let rhs: f64 = rhs.into();
+                        lhs == rhs || (lhs.is_nan() && rhs.is_nan())
+                    }
+                    FCmpOp::Une => {
+                        let lhs: f64 = lhs.into();
+                        let rhs: f64 = rhs.into();
+                        lhs.is_nan() || rhs.is_nan() || lhs != rhs
+                    }
+                    FCmpOp::Ult => {
+                        let lhs: f64 = lhs.into();
+                        let rhs: f64 = rhs.into();
+                        lhs < rhs || (lhs.is_nan() && !rhs.is_nan())
+                    }
+                    FCmpOp::Ule => {
+                        let lhs: f64 = lhs.into();
+                        let rhs: f64 = rhs.into();
+                        lhs <= rhs || (lhs.is_nan() && !rhs.is_nan())
+                    }
+                    FCmpOp::Ugt => {
+                        let lhs: f64 = lhs.into();
+                        let rhs: f64 = rhs.into();
+                        lhs > rhs || (!lhs.is_nan() && rhs.is_nan())
+                    }
+                    FCmpOp::Uge => {
+                        let lhs: f64 = lhs.into();
+                        let rhs: f64 = rhs.into();
+                        lhs >= rhs || (!lhs.is_nan() && rhs.is_nan())
+                    }
+                    _ => todo!(),
+                };
+                return Some(result.into());
+            }
+        }
+        _ => (),
     }
+    None
+}
+
+/// Check if integer constant is zero, division by it can't be folded.
+fn is_zero(c: &Constant) -> bool {
+    c.clone().cast(&ValueType::Long) == Constant::Long(0)
 }
@@ -14,11 +14,13 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashSet;
+
 use anyhow::Result;
 
 use crate::middle::analysis::effect_analysis::EffectAnalysis;
 use crate::middle::ir::instruction::InstType;
-use crate::middle::ir::{InstPtr, Operand};
+use crate::middle::ir::{FunPtr, InstPtr, Operand};
 use crate::middle::Program;
 
 use super::Transform;
@@ -54,6 +56,7 @@ impl<'a> Transform for DeadCodeElim<'a> {
                     changed |= self.dead_code_elim_inst(inst)?;
                 }
             }
+            changed |= self.dead_phi_elim(*func)?;
         }
 
         // Global variable does not require revisit, remove unused variables at the end
@@ -90,10 +93,53 @@ impl<'a> DeadCodeElim<'a> {
         Ok(true)
     }
 
+    /// Remove phis that are only used by each other, like variables declared in loop body
+    /// and carried around by mem2reg, which are never removed one at a time.
+    fn dead_phi_elim(&mut self, func: FunPtr) -> Result<bool> {
+        let phis = func
+            .po_iter()
+            .flat_map(|bb| bb.iter().filter(|inst| inst.get_type() == InstType::Phi))
+            .collect::<HashSet<_>>();
+
+        // Phis used by other instructions are live, and so are their operands
+        let mut worklist = phis
+            .iter()
+            .filter(|phi| phi.get_user().iter().any(|user| !phis.contains(user)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut live = worklist.iter().cloned().collect::<HashSet<_>>();
+        while let Some(phi) = worklist.pop() {
+            for op in phi.get_operand() {
+                if let Operand::Instruction(inst) = op {
+                    if phis.contains(inst) && live.insert(*inst) {
+                        worklist.push(*inst);
+                    }
+                }
+            }
+        }
+
+        // Remove dead phis together, then operands they kept alive
+        let mut dead = phis.difference(&live).cloned().collect::<Vec<_>>();
+        dead.sort();
+        let mut operands = Vec::new();
+        for phi in dead.iter_mut() {
+            operands.extend(phi.get_operand().iter().cloned());
+            phi.remove_self();
+        }
+        for op in operands {
+            if let Operand::Instruction(inst) = op {
+                if inst.get_parent_bb().is_some() {
+                    self.dead_code_elim_inst(inst)?;
+                }
+            }
+        }
+        Ok(!dead.is_empty())
+    }
+
     fn has_side_effect(&mut self, inst: InstPtr) -> bool {
         matches!(
             inst.get_type(),
-            InstType::Store | InstType::Ret | InstType::Br
+            InstType::Store | InstType::Ret | InstType::Br | InstType::Switch
         ) || self.effect_analysis.has_effect(inst)
     }
 }
@@ -0,0 +1,183 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::middle::{
+    analysis::call_graph::CallGraph,
+    ir::{instruction::InstType, BBPtr, Constant, FunPtr, GlobalPtr, InstPtr, Operand},
+    Program,
+};
+
+use super::Transform;
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let mut call_graph = CallGraph::new(program);
+    DeadGlobalElim::new(program, &mut call_graph).run_and_log()
+}
+
+/// Module level dead code elimination.
+/// Removes functions that are never called and global variables that are never used,
+/// turns read-only globals into constants, and moves globals only used by `main` into it.
+pub struct DeadGlobalElim<'a> {
+    program: &'a mut Program,
+    call_graph: &'a mut CallGraph,
+}
+
+impl<'a> Transform for DeadGlobalElim<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "dead_global_elim".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        // Without `main`, any function may be called from outside
+        let Some(main) = self.program.module.functions.iter().find(|f| f.is_main()) else {
+            return Ok(false);
+        };
+        let main = *main;
+        let mut changed = self.remove_dead_funcs();
+
+        // Simplify globals, globals without users are removed afterwards
+        let main_blocks = main.dfs_iter().collect::<HashSet<_>>();
+        for global in self.program.module.global_variables.clone() {
+            changed |= self.process_global(global, main, &main_blocks);
+        }
+
+        // Remove unused globals
+        let len = self.program.module.global_variables.len();
+        self.program
+            .module
+            .global_variables
+            .retain(|global| !global.get_user().is_empty());
+        changed |= len != self.program.module.global_variables.len();
+        Ok(changed)
+    }
+}
+
+impl<'a> DeadGlobalElim<'a> {
+    pub fn new(program: &'a mut Program, call_graph: &'a mut CallGraph) -> Self {
+        Self {
+            program,
+            call_graph,
+        }
+    }
+
+    /// Remove functions not called by others until unchanged,
+    /// because removing a function may leave its callees uncalled.
+    fn remove_dead_funcs(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            let dead = self
+                .program
+                .module
+                .functions
+                .iter()
+                .filter(|func| !func.is_lib() && !func.is_main())
+                .filter(|func| self.call_graph.get_called_by(**func).is_empty())
+                .cloned()
+                .collect::<Vec<FunPtr>>();
+            if dead.is_empty() {
+                return changed;
+            }
+            for func in dead {
+                // Detach instructions, so that globals no longer count them as users
+                for bb in func.dfs_iter() {
+                    for mut inst in bb.iter() {
+                        inst.remove_self();
+                    }
+                }
+                self.call_graph.remove(func);
+                self.program.module.functions.retain(|&f| f != func);
+            }
+            changed = true;
+        }
+    }
+
+    /// Simplify global variable, return changed or not.
+    fn process_global(
+        &mut self,
+        mut global: GlobalPtr,
+        main: FunPtr,
+        main_blocks: &HashSet<BBPtr>,
+    ) -> bool {
+        let users = global.get_user().to_vec();
+        if users.is_empty() {
+            return false;
+        }
+
+        // Global that is never written keeps its initializer
+        if users.iter().all(|user| is_read_only(*user)) {
+            if is_scalar(&global) {
+                let value: Operand = global.initializer.clone().into();
+                for mut user in users {
+                    user.replace_self(&value);
+                }
+                return true;
+            }
+            if global.variable_or_constant {
+                global.variable_or_constant = false;
+                return true;
+            }
+            return false;
+        }
+
+        // Scalar global only used in `main` lives as long as `main`, make it local
+        let local = users.iter().all(|user| {
+            user.get_parent_bb()
+                .is_some_and(|bb| main_blocks.contains(&bb))
+                && match user.get_type() {
+                    InstType::Load => true,
+                    InstType::Store => user.get_operand()[0] != Operand::Global(global),
+                    _ => false,
+                }
+        });
+        if is_scalar(&global) && local {
+            let Some(mut entry) = main.entry else {
+                return false;
+            };
+            let mem_pool = &mut self.program.mem_pool;
+            let mut alloca = mem_pool.get_alloca(global.value_type.clone(), 1);
+            let store = mem_pool.get_store(global.initializer.clone().into(), alloca.into());
+            entry.push_front(alloca);
+            alloca.insert_after(store);
+            for mut user in users {
+                user.replace_operand(&Operand::Global(global), &alloca.into());
+            }
+            return true;
+        }
+        false
+    }
+}
+
+/// Check if global is a scalar with a constant initializer.
+fn is_scalar(global: &GlobalPtr) -> bool {
+    global.value_type.is_num() && !matches!(global.initializer, Constant::Zero(_))
+}
+
+/// Check if pointer used by instruction is only read from.
+fn is_read_only(inst: InstPtr) -> bool {
+    match inst.get_type() {
+        InstType::Load => true,
+        InstType::GetElementPtr => inst.get_user().iter().all(|user| is_read_only(*user)),
+        _ => false,
+    }
+}
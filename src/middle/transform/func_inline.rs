@@ -14,15 +14,19 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, Context, Result};
 
 use crate::{
     backend::from_self::downcast_ref,
+    config::CONFIG,
     context,
     middle::{
-        analysis::call_graph::{CallEdge, CallGraph},
+        analysis::{
+            call_graph::{CallEdge, CallGraph},
+            loop_tools::LoopForest,
+        },
         ir::{
             instruction::{
                 downcast_mut,
@@ -36,7 +40,7 @@ use crate::{
     utils::paral_counter::ParalCounter,
 };
 
-use super::Transform;
+use super::{loop_depth::LoopDepthTracer, Transform};
 
 pub fn optimize_program(program: &mut Program) -> Result<bool> {
     let mut call_graph = CallGraph::new(program);
@@ -49,6 +53,8 @@ pub struct FuncInline<'a> {
     program: &'a mut Program,
     call_graph: &'a mut CallGraph,
     counter: ParalCounter,
+    /// Number of instructions added by inlining
+    growth: usize,
 }
 
 impl<'a> Transform for FuncInline<'a> {
@@ -61,32 +67,17 @@ impl<'a> Transform for FuncInline<'a> {
     }
 
     fn run(&mut self) -> Result<bool> {
-        let mut whole_changed = false;
-        loop {
-            let mut changed = false;
-            for func in self.program.module.functions.clone() {
-                // Do not process library function
-                if func.is_lib() {
-                    continue;
-                }
-
-                // If functions calls other functions, do not process it
-                if !self.call_graph.get_calls(func).is_empty() {
-                    continue;
-                }
-
-                // Process function
-                changed |= self.process_func(func)?;
-                whole_changed |= changed;
+        let mut changed = false;
 
-                // Update call graph
-                self.call_graph.remove(func);
-            }
-            if !changed {
-                break;
+        // Process callee before caller, so that callee is already optimized when inlined
+        for scc in self.call_graph.get_sccs() {
+            // Recursive function can't be inlined completely, do not process it
+            if self.call_graph.is_recursive(&scc) {
+                continue;
             }
+            changed |= self.process_func(scc[0])?;
         }
-        Ok(whole_changed)
+        Ok(changed)
     }
 }
 
@@ -100,24 +91,76 @@ impl<'a> FuncInline<'a> {
             program,
             call_graph,
             counter,
+            growth: 0,
         }
     }
 
     fn process_func(&mut self, func: FunPtr) -> Result<bool> {
-        let mut changed = false;
+        let mut calls = self
+            .call_graph
+            .get_called_by(func)
+            .into_iter()
+            .collect::<Vec<_>>();
+        calls.sort_by_key(|call| call.inst.get_id());
+        if calls.is_empty() {
+            return Ok(false);
+        }
 
-        // Eliminate call to func
-        for call in self.call_graph.get_called_by(func) {
-            changed |= self.process_call(call)?;
+        // Loop depth of call sites is used by cost model
+        let callers = calls.iter().map(|call| call.caller).collect::<HashSet<_>>();
+        for caller in callers {
+            trace_loop_depth(caller)?;
+        }
+
+        // Eliminate call to func if it's cheap enough
+        let size = func_size(func);
+        let only_call = calls.len() == 1;
+        let mut changed = false;
+        let mut all_inlined = true;
+        for call in calls {
+            if only_call || self.should_inline(call, size) {
+                changed |= self.process_call(call)?;
+                if !only_call {
+                    self.growth += size;
+                }
+            } else {
+                all_inlined = false;
+            }
         }
 
         // Delete func to reduce code size
-        if changed {
+        if all_inlined {
             self.program.module.functions.retain(|&f| f != func);
+            self.call_graph.remove(func);
         }
         Ok(changed)
     }
 
+    /// Decide if a call site should be inlined with cost model.
+    /// Cost is callee size minus the overhead of call and instructions
+    /// that may be folded by constant arguments.
+    /// Threshold doubles for each loop surrounding the call site.
+    fn should_inline(&self, edge: CallEdge, size: usize) -> bool {
+        if self.growth + size > CONFIG.inline_growth_budget {
+            return false;
+        }
+        let params = edge.callee.params.iter();
+        let args = edge.inst.get_operand().iter();
+        let const_bonus = params
+            .zip(args)
+            .filter(|(_, arg)| arg.is_const())
+            .map(|(param, _)| param.get_user().len() * CONST_ARG_BONUS)
+            .sum::<usize>();
+        let benefit = CALL_COST + edge.callee.params.len() + const_bonus;
+        let depth = edge
+            .inst
+            .get_parent_bb()
+            .map_or(0, |bb| bb.depth)
+            .min(MAX_DEPTH);
+        let threshold = CONFIG.inline_threshold << depth;
+        size.saturating_sub(benefit) <= threshold
+    }
+
     fn process_call(&mut self, edge: CallEdge) -> Result<bool> {
         let mut inst = edge.inst;
         let call = downcast_ref::<Call>(inst.as_ref().as_ref());
@@ -128,9 +171,30 @@ impl<'a> FuncInline<'a> {
         let arg_map = params.zip(args).collect();
 
         // Mirror function, focus on interface basic blocks
-        let new_fun = self.mirror_func(edge.callee, arg_map)?;
+        let counter = &self.counter;
+        let new_fun = mirror_func(self.program, edge.callee, arg_map, |name| {
+            format!("{}_inline{}", name, counter.get_id().unwrap())
+        })?;
+
+        // Calls in mirrored function are now called by caller
+        self.call_graph.remove_edge(edge);
+        for bb in new_fun.dfs_iter() {
+            for inst in bb.iter() {
+                if inst.get_type() == InstType::Call {
+                    let callee = downcast_ref::<Call>(inst.as_ref().as_ref()).func;
+                    if !callee.is_lib() {
+                        self.call_graph.add_edge(CallEdge {
+                            inst,
+                            caller: edge.caller,
+                            callee,
+                        });
+                    }
+                }
+            }
+        }
+
         let mut before_entry = call.get_parent_bb().unwrap();
-        let after_exit = self.split_block_at(before_entry, inst)?;
+        let after_exit = self.split_block_at(edge.caller, before_entry, inst)?;
         let fun_entry = new_fun
             .entry
             .ok_or_else(|| anyhow!("function `{}` has no entry", new_fun.name))
@@ -166,8 +230,13 @@ impl<'a> FuncInline<'a> {
 
     /// Split given basic block at the position of given instruction.
     /// Given instruction and instruction afterwards will be put to exit block.
-    /// Returns new exit block.
-    fn split_block_at(&mut self, mut entry: BBPtr, inst: InstPtr) -> Result<BBPtr> {
+    /// Returns new exit block, which is also the exit of `func` if `entry` was.
+    fn split_block_at(
+        &mut self,
+        mut func: FunPtr,
+        mut entry: BBPtr,
+        inst: InstPtr,
+    ) -> Result<BBPtr> {
         let exit_name = self.unique_name("split", &entry.name);
         let mut exit = self.program.mem_pool.new_basicblock(exit_name);
         let mut split = false;
@@ -182,120 +251,148 @@ impl<'a> FuncInline<'a> {
             }
         }
 
-        // Replace `entry` with `entry -> exit`
+        // Replace `entry` with `entry -> exit`, return instruction is moved to exit
         entry.replace_exit(exit);
+        if func.exit == Some(entry) {
+            func.exit = Some(exit);
+        }
 
         // Return created block
         Ok(exit)
     }
 
-    /// Mirror a function with given mapping.
-    /// The function should not be added to program, please wire entry and exit to existing function.
-    fn mirror_func(&mut self, func: FunPtr, arg_map: HashMap<ParaPtr, Operand>) -> Result<FunPtr> {
-        let func_entry = func
-            .entry
-            .ok_or_else(|| anyhow!("function `{}` has no entry", func.name))
-            .with_context(|| context!())?;
-        let func_exit = func
-            .exit
-            .ok_or_else(|| anyhow!("function `{}` has no exit", func.name))
-            .with_context(|| context!())?;
+    fn unique_name(&mut self, meta: &str, base_name: &str) -> String {
+        format!("{}_{}{}", base_name, meta, self.counter.get_id().unwrap())
+    }
+}
 
-        // Initialize inst and block mapping and new function
-        let mut inst_map: HashMap<InstPtr, InstPtr> = HashMap::new();
-        let mut block_map: HashMap<BBPtr, BBPtr> = HashMap::new();
-        let mut new_fun = self
-            .program
-            .mem_pool
-            .new_function(String::new(), func.return_type.clone());
-
-        // Copy blocks and instructions
-        for bb in func.dfs_iter() {
-            let name = self.unique_name("inline", &bb.name);
-            let mut new_bb = self.program.mem_pool.new_basicblock(name);
-            block_map.insert(bb, new_bb);
-            for inst in bb.iter() {
-                let new_inst = self
-                    .program
-                    .mem_pool
-                    .copy_instruction(inst.as_ref().as_ref());
-                inst_map.insert(inst, new_inst);
-                new_bb.push_back(new_inst);
-            }
+/// Mirror a function with given mapping, naming each copied block with `rename`.
+/// The function is not added to program, either wire its entry and exit to existing function,
+/// or assign name and parameters to it and add it to program.
+pub fn mirror_func(
+    program: &mut Program,
+    func: FunPtr,
+    arg_map: HashMap<ParaPtr, Operand>,
+    mut rename: impl FnMut(&str) -> String,
+) -> Result<FunPtr> {
+    let func_entry = func
+        .entry
+        .ok_or_else(|| anyhow!("function `{}` has no entry", func.name))
+        .with_context(|| context!())?;
+    let func_exit = func
+        .exit
+        .ok_or_else(|| anyhow!("function `{}` has no exit", func.name))
+        .with_context(|| context!())?;
+
+    // Initialize inst and block mapping and new function
+    let mut inst_map: HashMap<InstPtr, InstPtr> = HashMap::new();
+    let mut block_map: HashMap<BBPtr, BBPtr> = HashMap::new();
+    let mut new_fun = program
+        .mem_pool
+        .new_function(String::new(), func.return_type.clone());
+
+    // Copy blocks and instructions
+    for bb in func.dfs_iter() {
+        let mut new_bb = program.mem_pool.new_basicblock(rename(&bb.name));
+        block_map.insert(bb, new_bb);
+        for inst in bb.iter() {
+            let new_inst = program.mem_pool.copy_instruction(inst.as_ref().as_ref());
+            inst_map.insert(inst, new_inst);
+            new_bb.push_back(new_inst);
         }
+    }
 
-        // Set entry and exit for new function
-        new_fun.entry = block_map.get(&func_entry).cloned();
-        new_fun.exit = block_map.get(&func_exit).cloned();
+    let origin_map: HashMap<BBPtr, BBPtr> = block_map.iter().map(|(k, v)| (*v, *k)).collect();
 
-        // Copy operands from old instruction to new instruction,
-        // replace operands to local instruction and inlined argument
-        for bb in func.dfs_iter() {
-            for inst in bb.iter() {
-                let mut new_inst = inst_map
-                    .get(&inst)
-                    .cloned()
-                    .ok_or_else(|| anyhow!("instruction not found in inst_map: {}", inst))
-                    .with_context(|| context!())?;
-                if inst.get_type() == InstType::Phi {
-                    let inst = downcast_ref::<Phi>(inst.as_ref().as_ref());
-                    let new_inst = downcast_mut::<Phi>(new_inst.as_mut().as_mut());
-
-                    // Replace operand for phi instruction
-                    for (old_op, old_bb) in inst.get_incoming_values().iter() {
-                        let new_bb = block_map
-                            .get(old_bb)
-                            .cloned()
-                            .ok_or_else(|| anyhow!("bb not found in block_map: {}", old_bb.name))
-                            .with_context(|| context!())?;
-                        if let Operand::Instruction(old_op) = old_op {
-                            let new_op = inst_map.get(old_op).cloned().unwrap();
-                            new_inst.add_incoming_value(new_op.into(), new_bb);
-                        } else if let Operand::Parameter(old_op) = old_op {
-                            let new_op = arg_map.get(old_op).cloned().unwrap();
-                            new_inst.add_incoming_value(new_op, new_bb);
-                        } else {
-                            // Copy operands manually because `copy_instruction` does not copy them
-                            new_inst.add_incoming_value(old_op.clone(), new_bb);
-                        }
+    // Set entry and exit for new function
+    new_fun.entry = block_map.get(&func_entry).cloned();
+    new_fun.exit = block_map.get(&func_exit).cloned();
+
+    // Assign mapped basic blocks to successor
+    for bb in func.dfs_iter() {
+        let mut new_bb = block_map.get(&bb).cloned().unwrap();
+        for succ in bb.get_succ_bb() {
+            let new_succ = block_map.get(succ).cloned().unwrap();
+            new_bb.push_succ_bb(new_succ);
+        }
+    }
+
+    // Copy operands from old instruction to new instruction,
+    // replace operands to local instruction and inlined argument
+    for bb in func.dfs_iter() {
+        for inst in bb.iter() {
+            let mut new_inst = inst_map
+                .get(&inst)
+                .cloned()
+                .ok_or_else(|| anyhow!("instruction not found in inst_map: {}", inst))
+                .with_context(|| context!())?;
+            if inst.get_type() == InstType::Phi {
+                let inst = downcast_ref::<Phi>(inst.as_ref().as_ref());
+                let new_inst = downcast_mut::<Phi>(new_inst.as_mut().as_mut());
+
+                // Replace operand for phi instruction,
+                // incoming values follow predecessor order of the mirrored block
+                let new_preds = block_map[&bb].get_pred_bb().clone();
+                for new_bb in new_preds {
+                    let old_bb = origin_map[&new_bb];
+                    let old_op = inst
+                        .get_incoming_value(old_bb)
+                        .ok_or_else(|| anyhow!("incoming value not found: {}", old_bb.name))
+                        .with_context(|| context!())?;
+                    if let Operand::Instruction(old_op) = old_op {
+                        let new_op = inst_map.get(old_op).cloned().unwrap();
+                        new_inst.add_incoming_value(new_op.into(), new_bb);
+                    } else if let Operand::Parameter(old_op) = old_op {
+                        let new_op = arg_map.get(old_op).cloned().unwrap();
+                        new_inst.add_incoming_value(new_op, new_bb);
+                    } else {
+                        // Copy operands manually because `copy_instruction` does not copy them
+                        new_inst.add_incoming_value(old_op.clone(), new_bb);
                     }
-                } else {
-                    // Replace operand for normal instruction
-                    for old_op in inst.get_operand().iter() {
-                        if let Operand::Instruction(old_op) = old_op {
-                            let new_op = inst_map.get(old_op).cloned().unwrap();
-                            new_inst.add_operand(new_op.into());
-                        } else if let Operand::Parameter(old_op) = old_op {
-                            let new_op = arg_map.get(old_op).cloned().unwrap();
-                            new_inst.add_operand(new_op);
-                        } else {
-                            // Copy operands manually because `copy_instruction` does not copy them
-                            new_inst.add_operand(old_op.clone());
-                        }
+                }
+            } else {
+                // Replace operand for normal instruction
+                for old_op in inst.get_operand().iter() {
+                    if let Operand::Instruction(old_op) = old_op {
+                        let new_op = inst_map.get(old_op).cloned().unwrap();
+                        new_inst.add_operand(new_op.into());
+                    } else if let Operand::Parameter(old_op) = old_op {
+                        let new_op = arg_map.get(old_op).cloned().unwrap();
+                        new_inst.add_operand(new_op);
+                    } else {
+                        // Copy operands manually because `copy_instruction` does not copy them
+                        new_inst.add_operand(old_op.clone());
                     }
                 }
             }
         }
+    }
 
-        // Assign mapped basic blocks to successor
-        for bb in func.dfs_iter() {
-            let mut new_bb = block_map.get(&bb).cloned().unwrap();
-            let succ_bb = bb.get_succ_bb();
-            if !succ_bb.is_empty() {
-                let new_succ = block_map.get(&succ_bb[0]).cloned().unwrap();
-                new_bb.set_true_bb(new_succ);
-            }
-            if succ_bb.len() >= 2 {
-                let new_succ = block_map.get(&succ_bb[1]).cloned().unwrap();
-                new_bb.set_false_bb(new_succ);
-            }
-        }
+    // Return new function
+    Ok(new_fun)
+}
 
-        // Return new function
-        Ok(new_fun)
-    }
+/// Cost of call instruction itself, besides passing arguments
+const CALL_COST: usize = 5;
 
-    fn unique_name(&mut self, meta: &str, base_name: &str) -> String {
-        format!("{}_{}{}", base_name, meta, self.counter.get_id().unwrap())
+/// Bonus for each use of a constant argument
+const CONST_ARG_BONUS: usize = 4;
+
+/// Max loop depth to consider in cost model
+const MAX_DEPTH: usize = 3;
+
+/// Get number of instructions in function.
+pub fn func_size(func: FunPtr) -> usize {
+    func.dfs_iter().map(|bb| bb.iter().count()).sum()
+}
+
+/// Recompute loop depth of basic blocks in function.
+fn trace_loop_depth(func: FunPtr) -> Result<()> {
+    for mut bb in func.dfs_iter() {
+        bb.depth = 0;
+    }
+    if let Some(forest) = LoopForest::make_forest(func) {
+        LoopDepthTracer::run(&forest)?;
     }
+    Ok(())
 }
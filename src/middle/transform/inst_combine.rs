@@ -28,7 +28,7 @@ use crate::{
                 misc_inst::{FCmp, FCmpOp, ICmp, ICmpOp},
                 InstType,
             },
-            BBPtr, Constant, FunPtr, InstPtr, Operand,
+            BBPtr, Constant, FunPtr, InstPtr, Operand, ValueType,
         },
         Program,
     },
@@ -131,6 +131,7 @@ impl<'a> SymbolicEval<'a> {
             if let Operand::Instruction(lhs) = lhs {
                 if lhs.get_type() == InstType::GetElementPtr
                     && lhs.get_operand().last() == Some(&Operand::Constant(Constant::Int(0)))
+                    && Self::is_gep_last_stride(inst, lhs)
                 {
                     let lhs_lhs = lhs.get_operand()[0].clone();
                     let mut indexes = lhs.get_operand()[1..].to_vec();
@@ -150,6 +151,23 @@ impl<'a> SymbolicEval<'a> {
         Ok(false)
     }
 
+    /// Check if the last index of `lhs` strides over the element type of `inst`,
+    /// rather than selecting a struct field or a union member.
+    fn is_gep_last_stride(inst: InstPtr, lhs: InstPtr) -> bool {
+        let outer = downcast_ref::<GetElementPtr>(inst.as_ref().as_ref());
+        let inner = downcast_ref::<GetElementPtr>(lhs.as_ref().as_ref());
+        let indexes = &lhs.get_operand()[1..];
+        let mut ty = Some(&inner.element_type);
+        for index in &indexes[1..] {
+            ty = match ty {
+                Some(ValueType::Struct(_)) => return false,
+                Some(ty) => ty.get_indexed_type(index),
+                None => return false,
+            };
+        }
+        ty == Some(&outer.element_type)
+    }
+
     /// Constant folding.
     /// If changed, original instruction is removed.
     fn constant_fold(&mut self, mut inst: InstPtr) -> Result<bool> {
@@ -186,14 +204,25 @@ impl<'a> SymbolicEval<'a> {
                 let lhs = inst.get_operand()[0].clone();
                 let rhs = inst.get_operand()[1].clone();
                 if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let lhs: u32 = lhs.into();
-                    let rhs: u32 = rhs.into();
-                    let result = lhs / rhs;
-                    inst.replace_self(&Operand::Constant(result.into()));
+                    let ty = lhs.get_type();
+                    let unsigned_ty = ty.to_unsigned();
+                    let result = (lhs.cast(&unsigned_ty) / rhs.cast(&unsigned_ty)).cast(&ty);
+                    inst.replace_self(&result.into());
                     return Ok(true);
                 }
             }
-            InstType::SDiv | InstType::FDiv => {
+            InstType::SDiv => {
+                let lhs = inst.get_operand()[0].clone();
+                let rhs = inst.get_operand()[1].clone();
+                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
+                    let ty = lhs.get_type();
+                    let signed_ty = ty.to_signed();
+                    let result = (lhs.cast(&signed_ty) / rhs.cast(&signed_ty)).cast(&ty);
+                    inst.replace_self(&result.into());
+                    return Ok(true);
+                }
+            }
+            InstType::FDiv => {
                 let lhs = inst.get_operand()[0].clone();
                 let rhs = inst.get_operand()[1].clone();
                 if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
@@ -202,11 +231,24 @@ impl<'a> SymbolicEval<'a> {
                     return Ok(true);
                 }
             }
-            InstType::URem | InstType::SRem => {
+            InstType::URem => {
+                let lhs = inst.get_operand()[0].clone();
+                let rhs = inst.get_operand()[1].clone();
+                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
+                    let ty = lhs.get_type();
+                    let unsigned_ty = ty.to_unsigned();
+                    let result = (lhs.cast(&unsigned_ty) % rhs.cast(&unsigned_ty)).cast(&ty);
+                    inst.replace_self(&result.into());
+                    return Ok(true);
+                }
+            }
+            InstType::SRem => {
                 let lhs = inst.get_operand()[0].clone();
                 let rhs = inst.get_operand()[1].clone();
                 if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs % rhs;
+                    let ty = lhs.get_type();
+                    let signed_ty = ty.to_signed();
+                    let result = (lhs.cast(&signed_ty) % rhs.cast(&signed_ty)).cast(&ty);
                     inst.replace_self(&result.into());
                     return Ok(true);
                 }
@@ -224,7 +266,18 @@ impl<'a> SymbolicEval<'a> {
                 let lhs = inst.get_operand()[0].clone();
                 let rhs = inst.get_operand()[1].clone();
                 if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
-                    let result = lhs >> rhs;
+                    let ty = lhs.get_type();
+                    let result = (lhs.cast(&ty.to_signed()) >> rhs).cast(&ty);
+                    inst.replace_self(&result.into());
+                    return Ok(true);
+                }
+            }
+            InstType::LShr => {
+                let lhs = inst.get_operand()[0].clone();
+                let rhs = inst.get_operand()[1].clone();
+                if let (Operand::Constant(lhs), Operand::Constant(rhs)) = (lhs, rhs) {
+                    let ty = lhs.get_type();
+                    let result = (lhs.cast(&ty.to_unsigned()) >> rhs).cast(&ty);
                     inst.replace_self(&result.into());
                     return Ok(true);
                 }
@@ -256,7 +309,12 @@ impl<'a> SymbolicEval<'a> {
                     return Ok(true);
                 }
             }
-            InstType::ZextTo | InstType::ItoFp | InstType::FpToI => {
+            InstType::ItoFp
+            | InstType::FpToI
+            | InstType::Trunc
+            | InstType::FpExt
+            | InstType::FpTrunc
+            | InstType::BitCast => {
                 let src = inst.get_operand()[0].clone();
                 if let Operand::Constant(src) = src {
                     let result = src.cast(&inst.get_value_type());
@@ -264,11 +322,26 @@ impl<'a> SymbolicEval<'a> {
                     return Ok(true);
                 }
             }
+            InstType::ZextTo => {
+                let src = inst.get_operand()[0].clone();
+                if let Operand::Constant(src) = src {
+                    let unsigned_ty = src.get_type().to_unsigned();
+                    let result = src.cast(&unsigned_ty).cast(&inst.get_value_type());
+                    inst.replace_self(&result.into());
+                    return Ok(true);
+                }
+            }
             InstType::SextTo => {
                 let src = inst.get_operand()[0].clone();
                 if let Operand::Constant(Constant::Bool(b)) = src {
-                    let result = if b { -1 } else { 0 };
-                    inst.replace_self(&Operand::Constant(result.into()));
+                    let result = Constant::Int(if b { -1 } else { 0 }).cast(&inst.get_value_type());
+                    inst.replace_self(&result.into());
+                    return Ok(true);
+                }
+                if let Operand::Constant(src) = src {
+                    let signed_ty = src.get_type().to_signed();
+                    let result = src.cast(&signed_ty).cast(&inst.get_value_type());
+                    inst.replace_self(&result.into());
                     return Ok(true);
                 }
             }
@@ -280,29 +353,37 @@ impl<'a> SymbolicEval<'a> {
                     let result = match cmp_inst.op {
                         ICmpOp::Eq => lhs == rhs,
                         ICmpOp::Ne => lhs != rhs,
-                        ICmpOp::Slt => lhs < rhs,
-                        ICmpOp::Sle => lhs <= rhs,
-                        ICmpOp::Sgt => lhs > rhs,
-                        ICmpOp::Sge => lhs >= rhs,
+                        ICmpOp::Slt => {
+                            let signed_ty = lhs.get_type().to_signed();
+                            lhs.cast(&signed_ty) < rhs.cast(&signed_ty)
+                        }
+                        ICmpOp::Sle => {
+                            let signed_ty = lhs.get_type().to_signed();
+                            lhs.cast(&signed_ty) <= rhs.cast(&signed_ty)
+                        }
+                        ICmpOp::Sgt => {
+                            let signed_ty = lhs.get_type().to_signed();
+                            lhs.cast(&signed_ty) > rhs.cast(&signed_ty)
+                        }
+                        ICmpOp::Sge => {
+                            let signed_ty = lhs.get_type().to_signed();
+                            lhs.cast(&signed_ty) >= rhs.cast(&signed_ty)
+                        }
                         ICmpOp::Ult => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs < rhs
+                            let unsigned_ty = lhs.get_type().to_unsigned();
+                            lhs.cast(&unsigned_ty) < rhs.cast(&unsigned_ty)
                         }
                         ICmpOp::Ule => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs <= rhs
+                            let unsigned_ty = lhs.get_type().to_unsigned();
+                            lhs.cast(&unsigned_ty) <= rhs.cast(&unsigned_ty)
                         }
                         ICmpOp::Ugt => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs > rhs
+                            let unsigned_ty = lhs.get_type().to_unsigned();
+                            lhs.cast(&unsigned_ty) > rhs.cast(&unsigned_ty)
                         }
                         ICmpOp::Uge => {
-                            let lhs: u32 = lhs.into();
-                            let rhs: u32 = rhs.into();
-                            lhs >= rhs
+                            let unsigned_ty = lhs.get_type().to_unsigned();
+                            lhs.cast(&unsigned_ty) >= rhs.cast(&unsigned_ty)
                         }
                     };
                     inst.replace_self(&Operand::Constant(result.into()));
@@ -324,33 +405,33 @@ impl<'a> SymbolicEval<'a> {
                         FCmpOp::Ogt => lhs > rhs,
                         FCmpOp::Oge => lhs >= rhs,
                         FCmpOp::Ueq => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
+                            let lhs: f64 = lhs.into();
+                            let rhs: f64 = rhs.into();
                             lhs == rhs || (lhs.is_nan() && rhs.is_nan())
                         }
                         FCmpOp::Une => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
+                            let lhs: f64 = lhs.into();
+                            let rhs: f64 = rhs.into();
                             lhs.is_nan() || rhs.is_nan() || lhs != rhs
                         }
                         FCmpOp::Ult => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
+                            let lhs: f64 = lhs.into();
+                            let rhs: f64 = rhs.into();
                             lhs < rhs || (lhs.is_nan() && !rhs.is_nan())
                         }
                         FCmpOp::Ule => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
+                            let lhs: f64 = lhs.into();
+                            let rhs: f64 = rhs.into();
                             lhs <= rhs || (lhs.is_nan() && !rhs.is_nan())
                         }
                         FCmpOp::Ugt => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
+                            let lhs: f64 = lhs.into();
+                            let rhs: f64 = rhs.into();
                             lhs > rhs || (!lhs.is_nan() && rhs.is_nan())
                         }
                         FCmpOp::Uge => {
-                            let lhs: f32 = lhs.into();
-                            let rhs: f32 = rhs.into();
+                            let lhs: f64 = lhs.into();
+                            let rhs: f64 = rhs.into();
                             lhs >= rhs || (!lhs.is_nan() && rhs.is_nan())
                         }
                         _ => todo!(),
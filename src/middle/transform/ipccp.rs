@@ -0,0 +1,328 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    backend::from_self::downcast_ref,
+    config::CONFIG,
+    context,
+    middle::{
+        analysis::{
+            call_graph::{CallEdge, CallGraph},
+            effect_analysis::EffectAnalysis,
+        },
+        ir::{
+            instruction::{
+                downcast_mut,
+                misc_inst::{Call, ICmp, ICmpOp},
+                InstType,
+            },
+            Constant, FunPtr, Operand, ParaPtr, ValueType,
+        },
+        Program,
+    },
+    utils::paral_counter::ParalCounter,
+};
+
+use super::{
+    func_inline::{func_size, mirror_func},
+    Transform,
+};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let mut call_graph = CallGraph::new(program);
+    let effect_analysis = EffectAnalysis::new(program);
+    let counter = ParalCounter::new(0, usize::MAX);
+    IPCCP::new(program, &mut call_graph, &effect_analysis, counter).run_and_log()
+}
+
+/// Interprocedural constant propagation.
+/// Propagates constant arguments into callee, cloning callee when call sites disagree,
+/// then propagates constant return values and non-negative return facts back to callers.
+#[allow(clippy::upper_case_acronyms)]
+pub struct IPCCP<'a> {
+    program: &'a mut Program,
+    call_graph: &'a mut CallGraph,
+    effect_analysis: &'a EffectAnalysis,
+    counter: ParalCounter,
+    /// Number of instructions added by cloning
+    growth: usize,
+}
+
+impl<'a> Transform for IPCCP<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "ipccp".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        let mut changed = false;
+
+        // Process caller before callee, so that propagated constants flow further down
+        for scc in self.call_graph.get_sccs().into_iter().rev() {
+            // Mutually recursive functions pass arguments around, do not process them
+            if scc.len() > 1 {
+                continue;
+            }
+            changed |= self.process_args(scc[0])?;
+        }
+
+        // Propagate facts of return value to callers
+        for func in self.program.module.functions.clone() {
+            if func.is_lib() {
+                continue;
+            }
+            changed |= self.process_ret(func)?;
+            changed |= self.process_range(func);
+        }
+        Ok(changed)
+    }
+}
+
+impl<'a> IPCCP<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        call_graph: &'a mut CallGraph,
+        effect_analysis: &'a EffectAnalysis,
+        counter: ParalCounter,
+    ) -> Self {
+        Self {
+            program,
+            call_graph,
+            effect_analysis,
+            counter,
+            growth: 0,
+        }
+    }
+
+    /// Propagate constant arguments of all call sites into function.
+    fn process_args(&mut self, func: FunPtr) -> Result<bool> {
+        let mut calls = self
+            .call_graph
+            .get_called_by(func)
+            .into_iter()
+            .collect::<Vec<_>>();
+        calls.sort_by_key(|call| call.inst.get_id());
+
+        // Self recursive call sites only pass arguments along
+        let (inner, outer): (Vec<_>, Vec<_>) =
+            calls.into_iter().partition(|call| call.caller == func);
+        if outer.is_empty() {
+            return Ok(false);
+        }
+
+        // Replace parameter with constant if all call sites agree on it
+        let mut changed = false;
+        for (i, param) in func.params.iter().enumerate() {
+            if param.get_user().is_empty() {
+                continue;
+            }
+            let Some(value) = get_common_arg(&outer, i) else {
+                continue;
+            };
+            let passed_along = inner.iter().all(|call| {
+                let arg = &call.inst.get_operand()[i];
+                *arg == Operand::Parameter(*param) || *arg == value
+            });
+            if passed_along {
+                replace_param(*param, &value);
+                changed = true;
+            }
+        }
+
+        // Clone function for call sites with other constant arguments,
+        // recursive function is not cloned because recursive call still targets the original
+        if inner.is_empty() {
+            changed |= self.specialize(func, outer)?;
+        }
+        Ok(changed)
+    }
+
+    /// Group call sites by constant arguments, and redirect each group to a specialized clone.
+    fn specialize(&mut self, func: FunPtr, calls: Vec<CallEdge>) -> Result<bool> {
+        let mut groups: Vec<(Vec<Option<Constant>>, Vec<CallEdge>)> = Vec::new();
+        for call in calls {
+            let key = func
+                .params
+                .iter()
+                .zip(call.inst.get_operand().iter())
+                .map(|(param, arg)| match arg {
+                    Operand::Constant(value) if !param.get_user().is_empty() => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if key.iter().all(Option::is_none) {
+                continue;
+            }
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, group)) => group.push(call),
+                None => groups.push((key, vec![call])),
+            }
+        }
+
+        // Clone function within budget
+        let size = func_size(func);
+        let mut changed = false;
+        for (key, group) in groups {
+            if self.growth + size > CONFIG.ipccp_clone_budget {
+                break;
+            }
+            self.growth += size;
+            let new_fun = self.clone_func(func, &key)?;
+            for mut edge in group {
+                let call = downcast_mut::<Call>(edge.inst.as_mut().as_mut());
+                call.func = new_fun;
+                self.call_graph.remove_edge(edge);
+                self.call_graph.add_edge(CallEdge {
+                    callee: new_fun,
+                    ..edge
+                });
+            }
+            changed = true;
+        }
+
+        // Delete function if all call sites are redirected
+        if changed && self.call_graph.get_called_by(func).is_empty() {
+            self.program.module.functions.retain(|&f| f != func);
+            self.call_graph.remove(func);
+        }
+        Ok(changed)
+    }
+
+    /// Clone function with parameters replaced by given constants, and add it to program.
+    /// Signature of the clone is unchanged, so that call sites only need to change callee.
+    fn clone_func(&mut self, func: FunPtr, key: &[Option<Constant>]) -> Result<FunPtr> {
+        let mut params = Vec::new();
+        let mut arg_map: HashMap<ParaPtr, Operand> = HashMap::new();
+        for (param, value) in func.params.iter().zip(key.iter()) {
+            let new_param = self
+                .program
+                .mem_pool
+                .new_parameter(param.name.clone(), param.value_type.clone());
+            params.push(new_param);
+            let arg = match value {
+                Some(value) => Operand::Constant(value.clone()),
+                None => Operand::Parameter(new_param),
+            };
+            arg_map.insert(*param, arg);
+        }
+
+        // Block names are local to function, keep them unchanged
+        let mut new_fun = mirror_func(self.program, func, arg_map, |name| name.to_string())?;
+        new_fun.name = format!("{}_spec{}", func.name, self.counter.get_id().unwrap());
+        new_fun.params = params;
+        self.program.module.functions.push(new_fun);
+
+        // Calls in cloned function are new edges of call graph
+        for bb in new_fun.dfs_iter() {
+            for inst in bb.iter() {
+                if inst.get_type() == InstType::Call {
+                    let callee = downcast_ref::<Call>(inst.as_ref().as_ref()).func;
+                    if !callee.is_lib() {
+                        self.call_graph.add_edge(CallEdge {
+                            inst,
+                            caller: new_fun,
+                            callee,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(new_fun)
+    }
+
+    /// Replace result of calls to function with its constant return value.
+    /// Calls are kept for their side effects, and removed later if they have none.
+    fn process_ret(&mut self, func: FunPtr) -> Result<bool> {
+        if func.return_type == ValueType::Void {
+            return Ok(false);
+        }
+        let exit = func
+            .exit
+            .ok_or_else(|| anyhow!("function `{}` has no exit", func.name))
+            .with_context(|| context!())?;
+        let value = exit.get_last_inst().get_operand()[0].clone();
+        if !value.is_const() {
+            return Ok(false);
+        }
+
+        let mut changed = false;
+        for edge in self.call_graph.get_called_by(func) {
+            let call: Operand = edge.inst.into();
+            let users = edge.inst.get_user().to_vec();
+            for mut user in users {
+                user.replace_operand(&call, &value);
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Fold sign comparison of non-negative values.
+    fn process_range(&mut self, func: FunPtr) -> bool {
+        let mut changed = false;
+        let zero = Operand::Constant(Constant::Int(0));
+        for bb in func.dfs_iter() {
+            for mut inst in bb.iter() {
+                if inst.get_type() != InstType::ICmp {
+                    continue;
+                }
+                let icmp = downcast_ref::<ICmp>(inst.as_ref().as_ref());
+                if icmp.comp_type != ValueType::Int {
+                    continue;
+                }
+                let (lhs, rhs) = (icmp.get_lhs(), icmp.get_rhs());
+                let is_non_negative = |op| self.effect_analysis.is_non_negative(op);
+                let result = match icmp.op {
+                    ICmpOp::Slt if *rhs == zero && is_non_negative(lhs) => false,
+                    ICmpOp::Sge if *rhs == zero && is_non_negative(lhs) => true,
+                    ICmpOp::Sgt if *lhs == zero && is_non_negative(rhs) => false,
+                    ICmpOp::Sle if *lhs == zero && is_non_negative(rhs) => true,
+                    _ => continue,
+                };
+                inst.replace_self(&Constant::Bool(result).into());
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Get the constant argument at given position if all call sites pass it.
+fn get_common_arg(calls: &[CallEdge], index: usize) -> Option<Operand> {
+    let value = calls.first()?.inst.get_operand()[index].clone();
+    if !value.is_const() {
+        return None;
+    }
+    calls
+        .iter()
+        .all(|call| call.inst.get_operand()[index] == value)
+        .then_some(value)
+}
+
+/// Replace all uses of parameter with given operand.
+fn replace_param(param: ParaPtr, value: &Operand) {
+    let users = param.get_user().to_vec();
+    for mut user in users {
+        user.replace_operand(&Operand::Parameter(param), value);
+    }
+}
@@ -23,9 +23,14 @@ use crate::middle::{
     analysis::{
         effect_analysis::EffectAnalysis,
         loop_tools::{LoopForest, LoopPtr},
+        scalar_evolution::ScalarEvolution,
     },
     ir::{
-        instruction::{downcast_ref, misc_inst::Call, InstType},
+        instruction::{
+            downcast_mut, downcast_ref,
+            misc_inst::{Call, Phi},
+            InstType,
+        },
         BBPtr, InstPtr,
     },
     IRBuilder,
@@ -40,6 +45,7 @@ pub struct LDCE<'a> {
     _ir_builder: &'a mut IRBuilderWraper,
     check_set: HashSet<InstPtr>,
     loop_bbs: HashSet<BBPtr>,
+    dead_loops: HashSet<LoopPtr>,
     effect_analysis: &'a EffectAnalysis,
 }
 
@@ -52,12 +58,15 @@ impl<'a> LDCE<'a> {
             _ir_builder,
             check_set: HashSet::new(),
             loop_bbs: HashSet::new(),
+            dead_loops: HashSet::new(),
             effect_analysis,
         }
     }
 
     pub fn run(&mut self, forest: &mut LoopForest) -> Result<()> {
-        loop_forest_post_order(forest, |x| self.ldce_one_loop(x))
+        loop_forest_post_order(forest, |x| self.ldce_one_loop(x))?;
+        forest.forest.retain(|lo| !self.dead_loops.contains(lo));
+        Ok(())
     }
 
     fn ldce_one_loop(&mut self, lo: LoopPtr) -> Result<()> {
@@ -70,7 +79,71 @@ impl<'a> LDCE<'a> {
             }
         }
 
-        lo.blocks.iter().try_for_each(|&x| self.ldce_one_bb(x))
+        lo.blocks.iter().try_for_each(|&x| self.ldce_one_bb(x))?;
+        if self.is_dead_loop(lo) {
+            self.delete_loop(lo);
+        }
+        Ok(())
+    }
+
+    /// Check if loop can be deleted as a whole. Loop should terminate, which is known by its
+    /// constant trip count, and none of its instructions has side effect or is used after loop.
+    /// Sub loops are visited first, so a loop with dead sub loops has them removed already.
+    fn is_dead_loop(&self, lo: LoopPtr) -> bool {
+        let Some(pre_header) = lo.pre_header else {
+            return false;
+        };
+        if !lo.sub_loops.is_empty()
+            || pre_header.get_succ_bb().len() != 1
+            || ScalarEvolution::new().get_trip_count(lo).is_none()
+        {
+            return false;
+        }
+        lo.blocks.iter().all(|bb| {
+            bb.iter().all(|inst| {
+                let is_terminator = matches!(inst.get_type(), InstType::Br | InstType::Switch);
+                (is_terminator || self.can_delete_inst(inst))
+                    && inst
+                        .get_user()
+                        .iter()
+                        .all(|user| lo.blocks.contains(&user.get_parent_bb().unwrap()))
+            })
+        })
+    }
+
+    /// Delete loop by jumping from pre-header to exit directly.
+    /// Trip count is only known when loop exits from header, so header is the only exiting block.
+    fn delete_loop(&mut self, mut lo: LoopPtr) {
+        let mut pre_header = lo.pre_header.unwrap();
+        let header = lo.head;
+        let mut exit = *header
+            .get_succ_bb()
+            .iter()
+            .find(|bb| !lo.is_in_loop(bb))
+            .unwrap();
+        pre_header.replace_succ_bb_only(header, exit);
+        for mut inst in exit.iter() {
+            if inst.get_type() == InstType::Phi {
+                let phi = downcast_mut::<Phi>(inst.as_mut().as_mut());
+                phi.replace_incoming_value(header, pre_header);
+            }
+        }
+        exit.remove_pred_bb(header);
+
+        // Remove blocks and instructions of loop
+        for mut bb in lo.blocks.iter().cloned() {
+            bb.remove_self();
+        }
+        for bb in lo.blocks.iter() {
+            for mut inst in bb.iter() {
+                inst.remove_self();
+            }
+        }
+        if let Some(mut parent) = lo.parent_loop {
+            parent.sub_loops.retain(|x| *x != lo);
+        }
+        lo.blocks.clear();
+        self.dead_loops.insert(lo);
     }
 
     fn ldce_one_bb(&mut self, bb: BBPtr) -> Result<()> {
@@ -123,7 +196,7 @@ impl<'a> LDCE<'a> {
         };
         let no_control_or_store = !matches!(
             inst.get_type(),
-            InstType::Br | InstType::Ret | InstType::Store
+            InstType::Br | InstType::Switch | InstType::Ret | InstType::Store
         );
         call_no_effect && no_control_or_store
     }
@@ -71,6 +71,7 @@ impl<'a, 'b> LICM<'a, 'b> {
         && !matches!(
             inst.get_type(),
             InstType::Br
+            | InstType::Switch
             | InstType::Alloca
             | InstType::Store
             | InstType::Call
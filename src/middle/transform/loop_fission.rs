@@ -0,0 +1,479 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::{
+    backend::from_self::downcast_ref,
+    middle::{
+        analysis::{
+            dominator_tree::DominatorTree,
+            effect_analysis::{Effect, EffectAnalysis},
+            loop_tools::{LoopForest, LoopPtr},
+        },
+        ir::{
+            instruction::{downcast_mut, misc_inst::Phi, InstType},
+            BBPtr, InstPtr, Operand,
+        },
+        Program,
+    },
+};
+
+use super::{
+    loop_interchange::{cond_of, get_candidate, is_pure, next_of},
+    loop_simplify,
+    loop_unroll::{map_operand, replace_use},
+    make_parallel::Candidate,
+    Transform,
+};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let mut changed = false;
+    let effect_analysis = EffectAnalysis::new(program);
+    for func in program.module.functions.clone() {
+        if func.is_lib() {
+            continue;
+        }
+        let Some(mut forest) = LoopForest::make_forest(func) else {
+            continue;
+        };
+        loop_simplify::LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+        let mut dom_tree = DominatorTree::new(func);
+        let bb_names = func.dfs_iter().map(|bb| bb.name.clone()).collect();
+        changed |= LoopFission::new(
+            program,
+            &mut forest,
+            &mut dom_tree,
+            &effect_analysis,
+            bb_names,
+        )
+        .run_and_log()?;
+    }
+    Ok(changed)
+}
+
+/// Distribute an innermost loop into a sequence of loops, so that statements carrying a
+/// dependence across iterations don't block parallelization of the others:
+///
+/// ```text
+/// while (i < n) { a[i] = a[i - 1] + 1; b[i] = c[i] * 2; i = i + 1; }
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// while (i < n) { a[i] = a[i - 1] + 1; i = i + 1; }
+/// while (i < n) { b[i] = c[i] * 2; i = i + 1; }
+/// ```
+///
+/// Instructions are partitioned by their operands and by memory accesses that may conflict
+/// across iterations. Accesses to the same address in the same iteration only order the parts.
+/// A loop is split only when it mixes parts that can run in parallel with parts that can't.
+pub struct LoopFission<'a> {
+    program: &'a mut Program,
+    loop_forest: &'a mut LoopForest,
+    dom_tree: &'a mut DominatorTree,
+    effect_analysis: &'a EffectAnalysis,
+    bb_names: HashSet<String>,
+}
+
+impl<'a> Transform for LoopFission<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "loop_fission".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        // Loops are disjoint, so plans are made before dominator tree gets outdated
+        let mut plans = Vec::new();
+        for lo in self.loop_forest.forest.clone() {
+            self.make_plans(lo, &mut plans);
+        }
+        for plan in plans.iter() {
+            self.distribute(plan);
+        }
+        Ok(!plans.is_empty())
+    }
+}
+
+/// A loop with a header and a single body block, and its instructions grouped by new loops.
+struct FissionPlan {
+    candidate: Candidate,
+    body: BBPtr,
+    /// Instructions except induction variable and control flow.
+    items: Vec<InstPtr>,
+    /// Instructions of each new loop, the first group is kept in the original loop.
+    /// Shared values can be in multiple groups.
+    groups: Vec<HashSet<InstPtr>>,
+}
+
+impl<'a> LoopFission<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        loop_forest: &'a mut LoopForest,
+        dom_tree: &'a mut DominatorTree,
+        effect_analysis: &'a EffectAnalysis,
+        bb_names: HashSet<String>,
+    ) -> Self {
+        Self {
+            program,
+            loop_forest,
+            dom_tree,
+            effect_analysis,
+            bb_names,
+        }
+    }
+
+    fn make_plans(&mut self, lo: LoopPtr, plans: &mut Vec<FissionPlan>) {
+        for sub_loop in lo.sub_loops.iter() {
+            self.make_plans(*sub_loop, plans);
+        }
+        if let Some(plan) = self.get_plan(lo) {
+            plans.push(plan);
+        }
+    }
+
+    /// Partition instructions of an innermost loop, returns `None` if it's not worth splitting.
+    fn get_plan(&mut self, lo: LoopPtr) -> Option<FissionPlan> {
+        if !lo.sub_loops.is_empty() || lo.blocks.len() != 2 {
+            return None;
+        }
+        let candidate = get_candidate(lo, self.dom_tree)?;
+        let header = lo.head;
+        let body = lo.blocks.iter().cloned().find(|bb| *bb != header)?;
+        if body.get_succ_bb() != &vec![header] || !header.get_succ_bb().contains(&body) {
+            return None;
+        }
+        let indvar = Operand::from(candidate.indvar);
+
+        // Induction variable and control flow are copied to every new loop
+        let control = [
+            candidate.indvar,
+            next_of(&candidate),
+            cond_of(&candidate),
+            header.get_last_inst(),
+            body.get_last_inst(),
+        ]
+        .into_iter()
+        .collect::<HashSet<_>>();
+        let items = header
+            .iter()
+            .chain(body.iter())
+            .filter(|inst| !control.contains(inst))
+            .collect::<Vec<_>>();
+        let index = items
+            .iter()
+            .enumerate()
+            .map(|(i, inst)| (*inst, i))
+            .collect::<HashMap<_, _>>();
+
+        // Pure values and loads of memory not written in the loop are copied to each part
+        // using them, other values stay with their operands, including recurrences through phis
+        let effect_of = |inst: &InstPtr| self.effect_analysis.inst_effect.get(inst);
+        let mut shared = HashSet::new();
+        for inst in items.iter() {
+            let read_only = inst.get_type() == InstType::Load
+                && items.iter().filter_map(effect_of).all(|other| {
+                    !other
+                        .def_range
+                        .can_alias(&effect_of(inst).unwrap().use_range)
+                });
+            let operands_shared = inst.get_operand().iter().all(|op| match op {
+                Operand::Instruction(op) => !index.contains_key(op) || shared.contains(op),
+                _ => true,
+            });
+            if (is_pure(*inst) || read_only) && operands_shared && !self.is_live(*inst, lo) {
+                shared.insert(*inst);
+            }
+        }
+        let mut parent = (0..items.len()).collect::<Vec<_>>();
+        for (i, inst) in items.iter().enumerate() {
+            for op in inst.get_operand() {
+                if let Operand::Instruction(op) = op {
+                    if let Some(j) = index.get(op) {
+                        if !shared.contains(op) {
+                            union(&mut parent, i, *j);
+                        }
+                    }
+                }
+            }
+        }
+
+        // IO keeps its order
+        let io = (0..items.len())
+            .filter(|i| self.effect_analysis.has_io(items[*i]))
+            .collect::<Vec<_>>();
+        for pair in io.windows(2) {
+            union(&mut parent, pair[0], pair[1]);
+        }
+
+        // Accesses conflicting across iterations stay together,
+        // those touching the same address in the same iteration only order the parts
+        let accesses = items
+            .iter()
+            .enumerate()
+            .filter(|(_, inst)| !shared.contains(*inst))
+            .filter_map(|(i, inst)| Some((i, effect_of(inst)?)))
+            .collect::<Vec<_>>();
+        let mut order = Vec::new();
+        for (k, (i, a)) in accesses.iter().enumerate() {
+            for (j, b) in accesses[k + 1..].iter() {
+                if can_conflict(a, b, &indvar) {
+                    union(&mut parent, *i, *j);
+                } else if can_alias(a, b) {
+                    order.push((*i, *j));
+                }
+            }
+        }
+
+        // Root of a part is its first instruction, merge parts until each order goes forward
+        loop {
+            let mut merged = false;
+            for (i, j) in order.iter() {
+                if find(&mut parent, *i) > find(&mut parent, *j) {
+                    union(&mut parent, *i, *j);
+                    merged = true;
+                }
+            }
+            if !merged {
+                break;
+            }
+        }
+        let mut parts: BTreeMap<usize, Vec<InstPtr>> = BTreeMap::new();
+        for (i, inst) in items.iter().enumerate() {
+            if !shared.contains(inst) {
+                parts.entry(find(&mut parent, i)).or_default().push(*inst);
+            }
+        }
+
+        // Group adjacent parts of the same kind, parts without effect are dead and left in place
+        let mut groups: Vec<(bool, HashSet<InstPtr>)> = Vec::new();
+        let mut dead = Vec::new();
+        for part in parts.into_values() {
+            if !part.iter().any(|inst| self.is_live(*inst, lo)) {
+                dead.extend(part);
+                continue;
+            }
+            let parallel = part.iter().all(|inst| inst.get_type() != InstType::Phi)
+                && is_parallel(&part, &indvar, self.effect_analysis);
+            match groups.last_mut() {
+                Some((kind, group)) if *kind == parallel => group.extend(part),
+                _ => groups.push((parallel, part.into_iter().collect())),
+            }
+        }
+        if groups.len() < 2 {
+            return None;
+        }
+        groups[0].1.extend(dead);
+        let groups = groups
+            .into_iter()
+            .map(|(_, mut group)| {
+                let mut worklist = group.iter().cloned().collect::<Vec<_>>();
+                while let Some(inst) = worklist.pop() {
+                    for op in inst.get_operand() {
+                        if let Operand::Instruction(op) = op {
+                            if shared.contains(op) && group.insert(*op) {
+                                worklist.push(*op);
+                            }
+                        }
+                    }
+                }
+                group
+            })
+            .collect();
+        Some(FissionPlan {
+            candidate,
+            body,
+            items,
+            groups,
+        })
+    }
+
+    /// Check if an instruction has effect, or is used after the loop.
+    fn is_live(&self, inst: InstPtr, lo: LoopPtr) -> bool {
+        inst.get_type() == InstType::Store
+            || self.effect_analysis.has_effect(inst)
+            || inst
+                .get_user()
+                .iter()
+                .any(|user| !lo.is_in_loop(&user.get_parent_bb().unwrap()))
+    }
+
+    /// Copy the loop once for each group after the first one, and chain the copies.
+    fn distribute(&mut self, plan: &FissionPlan) {
+        let candidate = &plan.candidate;
+        let header = candidate.lo.head;
+        let body = plan.body;
+        let pre_header = candidate.init_bb;
+        let exit = candidate.exit_bb;
+        let mut live_outs: HashMap<InstPtr, Operand> = HashMap::new();
+        let mut last_header = header;
+        for group in plan.groups[1..].iter() {
+            // Copy blocks and instructions
+            let name = self.unique_name(&pre_header.name);
+            let mut new_pre_header = self.program.mem_pool.new_basicblock(name);
+            let name = self.unique_name(&header.name);
+            let mut new_header = self.program.mem_pool.new_basicblock(name);
+            let name = self.unique_name(&body.name);
+            let mut new_body = self.program.mem_pool.new_basicblock(name);
+            let mut inst_map: HashMap<InstPtr, Operand> = HashMap::new();
+            for (bb, mut new_bb) in [(header, new_header), (body, new_body)] {
+                for inst in bb.iter() {
+                    let new_inst = self
+                        .program
+                        .mem_pool
+                        .copy_instruction(inst.as_ref().as_ref());
+                    inst_map.insert(inst, new_inst.into());
+                    new_bb.push_back(new_inst);
+                }
+            }
+
+            // Run the copy after the last loop
+            let br = self.program.mem_pool.get_br(None);
+            new_pre_header.push_back(br);
+            last_header.replace_succ_bb_only(exit, new_pre_header);
+            new_pre_header.push_succ_bb(new_header);
+            for succ in header.get_succ_bb().clone() {
+                new_header.push_succ_bb(if succ == body { new_body } else { exit });
+            }
+            new_body.push_succ_bb(new_header);
+
+            // Copy operands, incoming values of phi follow predecessor order of copied block
+            let origin_map: HashMap<BBPtr, BBPtr> =
+                HashMap::from([(new_pre_header, pre_header), (new_body, body)]);
+            for bb in [header, body] {
+                for inst in bb.iter() {
+                    let Operand::Instruction(mut new_inst) = inst_map[&inst].clone() else {
+                        unreachable!();
+                    };
+                    if inst.get_type() == InstType::Phi {
+                        let inst = downcast_ref::<Phi>(inst.as_ref().as_ref());
+                        let new_inst = downcast_mut::<Phi>(new_inst.as_mut().as_mut());
+                        for pred in new_header.get_pred_bb() {
+                            let op = inst.get_incoming_value(origin_map[pred]).unwrap();
+                            new_inst.add_incoming_value(map_operand(&inst_map, op), *pred);
+                        }
+                    } else {
+                        for op in inst.get_operand() {
+                            new_inst.add_operand(map_operand(&inst_map, op));
+                        }
+                    }
+                }
+            }
+
+            // Keep instructions of this group only
+            for inst in plan.items.iter().filter(|inst| !group.contains(inst)) {
+                let Operand::Instruction(mut new_inst) = inst_map[inst].clone() else {
+                    unreachable!();
+                };
+                new_inst.remove_self();
+            }
+            for inst in group.iter() {
+                if inst.get_type() == InstType::Phi {
+                    live_outs.insert(*inst, inst_map[inst].clone());
+                }
+            }
+            last_header = new_header;
+        }
+
+        // Values used after the loop come from the copy computing them
+        for (inst, new_inst) in live_outs {
+            let users = inst.get_user().to_vec();
+            for user in users {
+                if !candidate.lo.is_in_loop(&user.get_parent_bb().unwrap()) {
+                    replace_use(user, inst, new_inst.clone());
+                }
+            }
+        }
+        for mut phi in exit.iter() {
+            if phi.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+            let op = phi.get_incoming_value(header).unwrap().clone();
+            phi.remove_incoming_value(header.id);
+            phi.add_incoming_value(op, last_header);
+        }
+
+        // Original loop keeps the first group
+        for inst in plan
+            .items
+            .iter()
+            .filter(|inst| !plan.groups[0].contains(inst))
+        {
+            inst.clone().remove_self();
+        }
+    }
+
+    fn unique_name(&mut self, base_name: &str) -> String {
+        let mut id = 0;
+        loop {
+            let name = format!("{}_fission{}", base_name, id);
+            if self.bb_names.insert(name.clone()) {
+                return name;
+            }
+            id += 1;
+        }
+    }
+}
+
+/// Check if instructions can run in parallel iterations of `indvar`, the same way as
+/// `make_parallel`: there is no IO, and no memory access conflicts with another iteration.
+pub fn is_parallel(insts: &[InstPtr], indvar: &Operand, effect_analysis: &EffectAnalysis) -> bool {
+    if insts.iter().any(|inst| effect_analysis.has_io(*inst)) {
+        return false;
+    }
+    let effects = insts
+        .iter()
+        .filter_map(|inst| effect_analysis.inst_effect.get(inst))
+        .collect::<Vec<_>>();
+    effects
+        .iter()
+        .enumerate()
+        .all(|(i, a)| effects[i..].iter().all(|b| !can_conflict(a, b, indvar)))
+}
+
+/// Check if two effects may touch the same memory in different iterations of `indvar`.
+fn can_conflict(a: &Effect, b: &Effect, indvar: &Operand) -> bool {
+    a.def_range.can_conflict(&b.def_range, indvar)
+        || a.def_range.can_conflict(&b.use_range, indvar)
+        || a.use_range.can_conflict(&b.def_range, indvar)
+}
+
+/// Check if two effects may touch the same memory.
+fn can_alias(a: &Effect, b: &Effect) -> bool {
+    a.def_range.can_alias(&b.def_range)
+        || a.def_range.can_alias(&b.use_range)
+        || a.use_range.can_alias(&b.def_range)
+}
+
+/// Find root of a part, which is its smallest index.
+fn find(parent: &mut [usize], i: usize) -> usize {
+    if parent[i] != i {
+        parent[i] = find(parent, parent[i]);
+    }
+    parent[i]
+}
+
+fn union(parent: &mut [usize], i: usize, j: usize) {
+    let (i, j) = (find(parent, i), find(parent, j));
+    parent[i.max(j)] = i.min(j);
+}
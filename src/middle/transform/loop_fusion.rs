@@ -0,0 +1,489 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::{
+    backend::from_self::downcast_ref,
+    middle::{
+        analysis::{
+            dominator_tree::DominatorTree,
+            effect_analysis::EffectAnalysis,
+            loop_tools::{LoopForest, LoopPtr},
+            memory_ssa::{MemorySSA, Node},
+        },
+        ir::{
+            instruction::{downcast_mut, memory_op_inst::GetElementPtr, misc_inst::Phi, InstType},
+            BBPtr, InstPtr, Operand,
+        },
+        Program,
+    },
+};
+
+use super::{
+    loop_fission::is_parallel,
+    loop_interchange::{cond_of, get_candidate, get_loop_blocks, get_ptr, is_indexed_by, is_pure},
+    loop_simplify,
+    make_parallel::Candidate,
+    redundance_elim, Transform,
+};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let mut changed = false;
+
+    // Fusion changes loops and memory dependences, so analyses are rebuilt after each one
+    loop {
+        let mut forests = Vec::new();
+        for func in program.module.functions.clone() {
+            if func.is_lib() {
+                continue;
+            }
+            let Some(mut forest) = LoopForest::make_forest(func) else {
+                continue;
+            };
+            loop_simplify::LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+            forests.push((func, forest));
+        }
+        let effect_analysis = EffectAnalysis::new(program);
+        let memory_ssa = MemorySSA::new(program, &effect_analysis);
+        let mut fused = false;
+        for (func, mut forest) in forests {
+            let mut dom_tree = DominatorTree::new(func);
+            if LoopFusion::new(program, &mut forest, &mut dom_tree, &memory_ssa).run_and_log()? {
+                fused = true;
+                break;
+            }
+        }
+        if !fused {
+            break;
+        }
+        changed = true;
+
+        // Accesses to the same address in fused bodies share the address, so that
+        // fused loop is still recognized as parallel
+        redundance_elim::optimize_program(program)?;
+    }
+    Ok(changed)
+}
+
+/// Fuse adjacent loops running the same iterations:
+///
+/// ```text
+/// while (i < n) { a[i] = b[i] + 1; i = i + 1; }
+/// while (j < n) { c[j] = a[j] * 2; j = j + 1; }
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// while (i < n) { a[i] = b[i] + 1; c[i] = a[i] * 2; i = i + 1; }
+/// ```
+///
+/// Accesses of the two loops that may touch the same memory must use the same address indexed
+/// by the induction variable, so each iteration of the second loop only depends on the same
+/// iteration of the first one. Loops are not fused if only one of them can run in parallel.
+pub struct LoopFusion<'a> {
+    program: &'a mut Program,
+    loop_forest: &'a mut LoopForest,
+    dom_tree: &'a mut DominatorTree,
+    memory_ssa: &'a MemorySSA<'a>,
+}
+
+impl<'a> Transform for LoopFusion<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "loop_fusion".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        // Loop forest is outdated after fusion, so only fuse the first pair
+        let mut siblings = vec![self.loop_forest.forest.clone()];
+        let mut index = 0;
+        while index < siblings.len() {
+            for lo in siblings[index].clone() {
+                siblings.push(lo.sub_loops.clone());
+            }
+            index += 1;
+        }
+        for loops in siblings {
+            for first in loops.iter() {
+                for second in loops.iter().filter(|second| *second != first) {
+                    if let Some(pair) = self.get_pair(*first, *second) {
+                        self.fuse(&pair);
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Two loops where the second one directly follows the first one.
+struct FusionPair {
+    first: Candidate,
+    second: Candidate,
+    /// Blocks from exit of the first loop to pre_header of the second loop.
+    between: Vec<BBPtr>,
+}
+
+impl<'a> LoopFusion<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        loop_forest: &'a mut LoopForest,
+        dom_tree: &'a mut DominatorTree,
+        memory_ssa: &'a MemorySSA<'a>,
+    ) -> Self {
+        Self {
+            program,
+            loop_forest,
+            dom_tree,
+            memory_ssa,
+        }
+    }
+
+    /// Get two adjacent loops with the same iterations, and check their dependences.
+    fn get_pair(&mut self, first_lo: LoopPtr, second_lo: LoopPtr) -> Option<FusionPair> {
+        let first = get_candidate(first_lo, self.dom_tree)?;
+        let second = get_candidate(second_lo, self.dom_tree)?;
+        if first.delta != second.delta
+            || first.init_val != second.init_val
+            || first.exit_val != second.exit_val
+        {
+            return None;
+        }
+
+        // Only pure instructions are between two loops, they are moved before the first loop
+        let mut between = Vec::new();
+        let mut bb = first.exit_bb;
+        while bb != second_lo.head {
+            if bb.get_pred_bb().len() != 1 || bb.get_succ_bb().len() != 1 {
+                return None;
+            }
+            for inst in bb.iter() {
+                if inst != bb.get_last_inst()
+                    && (!is_pure(inst)
+                        || matches!(inst.get_type(), InstType::SDiv | InstType::SRem))
+                {
+                    return None;
+                }
+            }
+            between.push(bb);
+            bb = bb.get_succ_bb()[0];
+        }
+
+        // Loops exit only from headers, which contain only phis and control flow
+        let first_blocks = get_loop_blocks(first_lo);
+        let second_blocks = get_loop_blocks(second_lo);
+        for (candidate, blocks) in [(&first, &first_blocks), (&second, &second_blocks)] {
+            let header = candidate.lo.head;
+            if latch_of(candidate) == header {
+                return None;
+            }
+            for inst in header.iter() {
+                if inst.get_type() != InstType::Phi
+                    && inst != cond_of(candidate)
+                    && inst != candidate.exit
+                {
+                    return None;
+                }
+            }
+            for bb in blocks.iter().filter(|bb| **bb != header) {
+                if bb.get_succ_bb().iter().any(|succ| !blocks.contains(succ)) {
+                    return None;
+                }
+            }
+        }
+        let second_entry = second_lo
+            .head
+            .get_succ_bb()
+            .iter()
+            .cloned()
+            .find(|bb| *bb != second.exit_bb)?;
+        if second_entry.get_pred_bb().len() != 1 {
+            return None;
+        }
+
+        // Values of the first loop are not used before the end of the second loop
+        for bb in first_blocks.iter() {
+            for inst in bb.iter() {
+                for user in inst.get_user() {
+                    let user_bb = user.get_parent_bb().unwrap();
+                    if between.contains(&user_bb) || second_blocks.contains(&user_bb) {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        // Parallel loops are not fused with sequential ones
+        let effect_analysis = &*self.memory_ssa.effect_analysis;
+        if is_parallel_loop(&first, &first_blocks, effect_analysis)
+            != is_parallel_loop(&second, &second_blocks, effect_analysis)
+        {
+            return None;
+        }
+        let pair = FusionPair {
+            first,
+            second,
+            between,
+        };
+        self.check_dependence(&pair, &first_blocks, &second_blocks)
+            .then_some(pair)
+    }
+
+    /// Check if every access of the second loop only depends on the same iteration of the first loop.
+    fn check_dependence(
+        &self,
+        pair: &FusionPair,
+        first_blocks: &[BBPtr],
+        second_blocks: &[BBPtr],
+    ) -> bool {
+        let effect_analysis = &*self.memory_ssa.effect_analysis;
+        let accesses = |blocks: &[BBPtr]| {
+            blocks
+                .iter()
+                .flat_map(|bb| bb.iter())
+                .filter(|inst| {
+                    effect_analysis.inst_effect.contains_key(inst) || effect_analysis.has_io(*inst)
+                })
+                .collect::<Vec<_>>()
+        };
+        let first_accesses = accesses(first_blocks);
+        let second_accesses = accesses(second_blocks);
+
+        // IO of two loops can't be interleaved
+        if first_accesses
+            .iter()
+            .any(|inst| effect_analysis.has_io(*inst))
+            && second_accesses
+                .iter()
+                .any(|inst| effect_analysis.has_io(*inst))
+        {
+            return false;
+        }
+
+        for y in second_accesses.iter() {
+            let Some(ey) = effect_analysis.inst_effect.get(y) else {
+                continue;
+            };
+            let reads_first = self.reads_from(*y, first_blocks, second_blocks, &pair.between);
+            for x in first_accesses.iter() {
+                let Some(ex) = effect_analysis.inst_effect.get(x) else {
+                    continue;
+                };
+                let dependent = reads_first && ex.def_range.can_alias(&ey.use_range)
+                    || ex.use_range.can_alias(&ey.def_range)
+                    || ex.def_range.can_alias(&ey.def_range);
+                if dependent && !same_iteration(*x, *y, pair) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Check with MemorySSA if memory read by `inst` may be written in the first loop.
+    fn reads_from(
+        &self,
+        inst: InstPtr,
+        first_blocks: &[BBPtr],
+        second_blocks: &[BBPtr],
+        between: &[BBPtr],
+    ) -> bool {
+        let Some(node) = self.memory_ssa.get_inst_node(inst) else {
+            return false;
+        };
+        let Node::Normal(_, Some(use_node), _, _) = *node else {
+            return false;
+        };
+
+        // Walk up from the memory state read, until leaving the second loop
+        let mut worklist = vec![use_node];
+        let mut visited = HashSet::new();
+        while let Some(node) = worklist.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            let Some(bb) = self.memory_ssa.get_node_block(node) else {
+                continue;
+            };
+            if first_blocks.contains(&bb) {
+                return true;
+            }
+            if second_blocks.contains(&bb) || between.contains(&bb) {
+                worklist.extend(node.get_used_node());
+            }
+        }
+        false
+    }
+
+    /// Append body of the second loop to the first loop, and remove control flow of the second loop.
+    fn fuse(&mut self, pair: &FusionPair) {
+        let (first, second) = (&pair.first, &pair.second);
+        let pre_header = first.init_bb;
+        let mut header = first.lo.head;
+        let second_header = second.lo.head;
+        let exit = second.exit_bb;
+        let mut first_latch = latch_of(first);
+        let mut second_latch = latch_of(second);
+        let second_entry = second_header
+            .get_succ_bb()
+            .iter()
+            .cloned()
+            .find(|bb| *bb != exit)
+            .unwrap();
+
+        // Instructions between loops are moved before the first loop
+        let mut guard = pre_header.get_last_inst();
+        for bb in pair.between.iter() {
+            for inst in bb.iter().collect::<Vec<_>>() {
+                if inst != bb.get_last_inst() {
+                    guard.insert_before(inst);
+                }
+            }
+        }
+
+        // Second induction variable is the same as the first one
+        let Operand::Instruction(mut second_next) =
+            downcast_ref::<Phi>(second.indvar.as_ref().as_ref()).get_incoming_values()[1]
+                .0
+                .clone()
+        else {
+            unreachable!();
+        };
+        second.indvar.clone().replace_self(&first.indvar.into());
+        if second_next.get_user().is_empty() {
+            second_next.remove_self();
+        }
+
+        // Redirect `first_latch -> second_entry`, `second_latch -> header` and `header -> exit`
+        first_latch.replace_succ_bb_only(header, second_entry);
+        second_latch.replace_succ_bb_only(second_header, header);
+        header.replace_succ_bb_only(first.exit_bb, exit);
+        for mut phi in header.iter() {
+            if phi.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+            phi.replace_incoming_value(first_latch, second_latch);
+        }
+
+        // Move other phis of the second header
+        for mut inst in second_header.iter().collect::<Vec<_>>() {
+            if inst.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_mut::<Phi>(inst.as_mut().as_mut());
+            let init_val = phi.get_incoming_value(second.init_bb).unwrap().clone();
+            let next_val = phi.get_incoming_value(second_latch).unwrap().clone();
+            phi.remove_incoming_value(second.init_bb.id);
+            phi.remove_incoming_value(second_latch.id);
+            phi.add_incoming_value(init_val, pre_header);
+            phi.add_incoming_value(next_val, second_latch);
+            header.push_front(inst);
+        }
+        for mut phi in exit.iter() {
+            if phi.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+            let op = phi.get_incoming_value(second_header).unwrap().clone();
+            phi.remove_incoming_value(second_header.id);
+            phi.add_incoming_value(op, header);
+        }
+
+        // Remove blocks between loops and the second header
+        for mut bb in pair.between.iter().cloned().chain([second_header]) {
+            bb.remove_self();
+        }
+        for bb in pair.between.iter().chain([&second_header]) {
+            for mut inst in bb.iter().collect::<Vec<_>>() {
+                inst.remove_self();
+            }
+        }
+    }
+}
+
+/// Get the block jumping back to header of a loop.
+fn latch_of(candidate: &Candidate) -> BBPtr {
+    let phi = downcast_ref::<Phi>(candidate.indvar.as_ref().as_ref());
+    phi.get_incoming_values()[1].1
+}
+
+/// Check if a loop can be parallelized, with the induction variable as the only header phi.
+fn is_parallel_loop(
+    candidate: &Candidate,
+    blocks: &[BBPtr],
+    effect_analysis: &EffectAnalysis,
+) -> bool {
+    let phis = candidate
+        .lo
+        .head
+        .iter()
+        .filter(|inst| inst.get_type() == InstType::Phi)
+        .count();
+    let insts = blocks.iter().flat_map(|bb| bb.iter()).collect::<Vec<_>>();
+    phis == 1 && is_parallel(&insts, &candidate.indvar.into(), effect_analysis)
+}
+
+/// Check if two accesses of fused loops touch the same address only in the same iteration.
+fn same_iteration(x: InstPtr, y: InstPtr, pair: &FusionPair) -> bool {
+    let is_access = |inst: InstPtr| matches!(inst.get_type(), InstType::Load | InstType::Store);
+    if !is_access(x) || !is_access(y) {
+        return false;
+    }
+    let first_iv = Operand::from(pair.first.indvar);
+    let second_iv = Operand::from(pair.second.indvar);
+    let (x_ptr, y_ptr) = (get_ptr(x), get_ptr(y));
+    is_indexed_by(&x_ptr, &first_iv) && equivalent(&x_ptr, &y_ptr, &first_iv, &second_iv, 8)
+}
+
+/// Check if `b` computes the same value as `a` within `depth` instructions,
+/// when `b_iv` is replaced by `a_iv`.
+fn equivalent(a: &Operand, b: &Operand, a_iv: &Operand, b_iv: &Operand, depth: usize) -> bool {
+    if a == b || a == a_iv && b == b_iv {
+        return true;
+    }
+    let (Operand::Instruction(a), Operand::Instruction(b)) = (a, b) else {
+        return false;
+    };
+    if depth == 0
+        || a.get_type() != b.get_type()
+        || !is_pure(*a)
+        || a.get_type() == InstType::ICmp
+        || a.get_value_type() != b.get_value_type()
+    {
+        return false;
+    }
+    if a.get_type() == InstType::GetElementPtr {
+        let a_gep = downcast_ref::<GetElementPtr>(a.as_ref().as_ref());
+        let b_gep = downcast_ref::<GetElementPtr>(b.as_ref().as_ref());
+        if a_gep.element_type != b_gep.element_type {
+            return false;
+        }
+    }
+    let (a, b) = (a.get_operand(), b.get_operand());
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| equivalent(a, b, a_iv, b_iv, depth - 1))
+}
@@ -0,0 +1,609 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::{
+    backend::from_self::downcast_ref,
+    config::CONFIG,
+    middle::{
+        analysis::{
+            alias_analysis::EffectRange,
+            dominator_tree::DominatorTree,
+            loop_tools::{LoopForest, LoopPtr},
+        },
+        ir::{
+            instruction::{
+                downcast_mut,
+                memory_op_inst::{Load, Store},
+                misc_inst::{ICmpOp, Phi},
+                InstType,
+            },
+            BBPtr, Constant, InstPtr, Operand, ValueType,
+        },
+        Program,
+    },
+};
+
+use super::{loop_simplify, make_parallel::Candidate, Transform};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    run_with_tile_size(program, CONFIG.loop_tile_size)
+}
+
+/// Interchange loops, and tile them with `tile_size` inner iterations, 0 disables tiling.
+pub fn run_with_tile_size(program: &mut Program, tile_size: usize) -> Result<bool> {
+    let mut changed = false;
+    for func in program.module.functions.clone() {
+        if func.is_lib() {
+            continue;
+        }
+        let Some(mut forest) = LoopForest::make_forest(func) else {
+            continue;
+        };
+        loop_simplify::LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+        let mut dom_tree = DominatorTree::new(func);
+        let bb_names = func.dfs_iter().map(|bb| bb.name.clone()).collect();
+        changed |= LoopInterchange::new(program, &mut forest, &mut dom_tree, bb_names, tile_size)
+            .run_and_log()?;
+    }
+    Ok(changed)
+}
+
+/// Interchange perfectly nested loops so that array accesses become unit-stride,
+/// and optionally tile them.
+///
+/// A nest is handled only when both loops have a single `indvar < N` induction variable,
+/// the inner loop's bounds are invariant to the outer loop, and every store only aliases
+/// accesses to exactly the same address indexed by one of the induction variables.
+pub struct LoopInterchange<'a> {
+    program: &'a mut Program,
+    loop_forest: &'a mut LoopForest,
+    dom_tree: &'a mut DominatorTree,
+    bb_names: HashSet<String>,
+    tile_size: usize,
+}
+
+impl<'a> Transform for LoopInterchange<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "loop_interchange".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        let mut changed = false;
+        for lo in self.loop_forest.forest.clone() {
+            self.visit(lo, &mut changed)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// A perfectly nested pair of loops.
+struct Nest {
+    outer: Candidate,
+    inner: Candidate,
+    /// Pure instructions between the two loops, to be sunk into the inner loop.
+    sink: Vec<InstPtr>,
+    /// Loads and stores in the inner loop.
+    accesses: Vec<InstPtr>,
+}
+
+impl<'a> LoopInterchange<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        loop_forest: &'a mut LoopForest,
+        dom_tree: &'a mut DominatorTree,
+        bb_names: HashSet<String>,
+        tile_size: usize,
+    ) -> Self {
+        Self {
+            program,
+            loop_forest,
+            dom_tree,
+            bb_names,
+            tile_size,
+        }
+    }
+
+    /// Handle nests in post order, inner pairs first.
+    /// Returns true if the loop forest above `lo` is outdated by tiling.
+    fn visit(&mut self, lo: LoopPtr, changed: &mut bool) -> Result<bool> {
+        let mut outdated = false;
+        for sub_loop in lo.sub_loops.iter() {
+            outdated |= self.visit(*sub_loop, changed)?;
+        }
+        if outdated {
+            return Ok(true);
+        }
+        let [inner] = lo.sub_loops[..] else {
+            return Ok(false);
+        };
+        let Some(nest) = self.get_nest(lo, inner) else {
+            return Ok(false);
+        };
+        if stride_score(&nest) < 0 {
+            self.interchange(&nest);
+            *changed = true;
+        }
+        if self.tile_size > 0 && inner.sub_loops.is_empty() {
+            // Induction variables are rebuilt because interchange swaps their bounds
+            let Some(nest) = self.get_nest(lo, inner) else {
+                return Ok(false);
+            };
+            if self.tile(&nest, self.tile_size as i32) {
+                *changed = true;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Get a perfect nest with rectangular iteration space, and check its dependences.
+    fn get_nest(&mut self, outer_lo: LoopPtr, inner_lo: LoopPtr) -> Option<Nest> {
+        let outer = get_candidate(outer_lo, self.dom_tree)?;
+        let inner = get_candidate(inner_lo, self.dom_tree)?;
+        let outer_next = next_of(&outer);
+        let inner_next = next_of(&inner);
+
+        // Inner bounds should be calculated before outer loop
+        for op in [&inner.init_val, &inner.exit_val] {
+            if let Operand::Instruction(inst) = op {
+                if outer_lo.is_in_loop(&inst.get_parent_bb().unwrap()) {
+                    return None;
+                }
+            }
+        }
+
+        // Header phi should be the only phi, so that no value is carried across iterations
+        for lo in [outer_lo, inner_lo] {
+            if lo
+                .head
+                .iter()
+                .filter(|i| i.get_type() == InstType::Phi)
+                .count()
+                != 1
+            {
+                return None;
+            }
+        }
+
+        // Blocks between two loops are executed exactly once for each outer iteration.
+        // Blocks before inner loop contain pure instructions, blocks after it contain
+        // only loop control
+        let outer_control = [outer.indvar, outer_next, outer.exit, cond_of(&outer)];
+        if !outer_lo
+            .head
+            .iter()
+            .all(|inst| outer_control.contains(&inst))
+        {
+            return None;
+        }
+        let mut sink = Vec::new();
+        let mut visited = 0;
+        let mut bb = *outer_lo
+            .head
+            .get_succ_bb()
+            .iter()
+            .find(|bb| outer_lo.is_in_loop(bb))?;
+        while bb != inner_lo.head {
+            let [succ] = bb.get_succ_bb()[..] else {
+                return None;
+            };
+            if !outer_lo.blocks.contains(&bb)
+                || bb == outer_lo.head
+                || visited >= outer_lo.blocks.len()
+            {
+                return None;
+            }
+            for inst in bb.iter() {
+                if inst.get_type() == InstType::Br {
+                    continue;
+                }
+                if !is_pure(inst) {
+                    return None;
+                }
+                sink.push(inst);
+            }
+            visited += 1;
+            bb = succ;
+        }
+        let mut bb = inner.exit_bb;
+        while bb != outer_lo.head {
+            let [succ] = bb.get_succ_bb()[..] else {
+                return None;
+            };
+            if !outer_lo.blocks.contains(&bb) || visited >= outer_lo.blocks.len() {
+                return None;
+            }
+            if !bb
+                .iter()
+                .all(|inst| inst == outer_next || inst.get_type() == InstType::Br)
+            {
+                return None;
+            }
+            visited += 1;
+            bb = succ;
+        }
+        let next_bb = outer_next.get_parent_bb()?;
+        if visited + 1 != outer_lo.blocks.len() || !outer_lo.blocks.contains(&next_bb) {
+            return None;
+        }
+
+        // Inner loop only exits from its header
+        let inner_blocks = get_loop_blocks(inner_lo);
+        for bb in inner_blocks.iter() {
+            if *bb != inner_lo.head && bb.get_succ_bb().iter().any(|bb| !inner_lo.is_in_loop(bb)) {
+                return None;
+            }
+        }
+
+        // Induction variables and values between loops are only used by the nest
+        let in_inner = |user: &InstPtr| inner_lo.is_in_loop(&user.get_parent_bb().unwrap());
+        for inst in sink.iter().chain([&outer.indvar, &inner.indvar]) {
+            if !inst
+                .get_user()
+                .iter()
+                .all(|user| in_inner(user) || sink.contains(user) || outer_control.contains(user))
+            {
+                return None;
+            }
+        }
+        if outer_next.get_user() != [outer.indvar] || inner_next.get_user() != [inner.indvar] {
+            return None;
+        }
+
+        // Collect memory accesses, calls may touch anything
+        let mut accesses = Vec::new();
+        for bb in inner_blocks.iter() {
+            for inst in bb.iter() {
+                match inst.get_type() {
+                    InstType::Load | InstType::Store => accesses.push(inst),
+                    InstType::Call => return None,
+                    _ => (),
+                }
+            }
+        }
+
+        // Stores can only alias accesses of the same address, which are indexed by
+        // an induction variable, so that dependences are carried by at most one loop
+        let outer_iv = Operand::from(outer.indvar);
+        let inner_iv = Operand::from(inner.indvar);
+        for store in accesses.iter().filter(|i| i.get_type() == InstType::Store) {
+            let ptr = get_ptr(*store);
+            for access in accesses.iter() {
+                let other = get_ptr(*access);
+                if !EffectRange::from(ptr.clone()).can_alias(&EffectRange::from(other.clone())) {
+                    continue;
+                }
+                if !same_address(&ptr, &other)
+                    || !(is_indexed_by(&ptr, &outer_iv) || is_indexed_by(&ptr, &inner_iv))
+                {
+                    return None;
+                }
+            }
+        }
+
+        Some(Nest {
+            outer,
+            inner,
+            sink,
+            accesses,
+        })
+    }
+
+    /// Swap bounds and steps of the two loops, then swap uses of induction variables in body.
+    /// Iteration space is rectangular, so each loop keeps its own control flow.
+    fn interchange(&mut self, nest: &Nest) {
+        let outer = &nest.outer;
+        let inner = &nest.inner;
+
+        // Sink pure instructions between loops into inner body, where both indvars are available
+        let body_entry = inner
+            .indvar
+            .get_parent_bb()
+            .unwrap()
+            .get_succ_bb()
+            .iter()
+            .find(|bb| inner.lo.is_in_loop(bb))
+            .cloned()
+            .unwrap();
+        let mut first = body_entry
+            .iter()
+            .find(|inst| inst.get_type() != InstType::Phi)
+            .unwrap();
+        for inst in nest.sink.iter() {
+            first.insert_before(*inst);
+        }
+
+        // Swap uses in body, excluding inner loop control
+        let inner_next = next_of(inner);
+        let inner_control = [inner.indvar, inner_next, inner.exit, cond_of(inner)];
+        let outer_iv = Operand::from(outer.indvar);
+        let inner_iv = Operand::from(inner.indvar);
+        let swap = |op: &Operand| {
+            if op == &outer_iv {
+                Some(inner_iv.clone())
+            } else if op == &inner_iv {
+                Some(outer_iv.clone())
+            } else {
+                None
+            }
+        };
+        for bb in get_loop_blocks(inner.lo) {
+            for mut inst in bb.iter() {
+                if inner_control.contains(&inst) {
+                    continue;
+                }
+                for (index, op) in inst.get_operand().to_vec().iter().enumerate() {
+                    if let Some(new_op) = swap(op) {
+                        inst.set_operand(index, new_op);
+                    }
+                }
+            }
+        }
+
+        // Swap bounds and steps
+        for (from, to) in [(outer, inner), (inner, outer)] {
+            let mut indvar = from.indvar;
+            let phi = downcast_mut::<Phi>(indvar.as_mut().as_mut());
+            phi.replace_incoming_value_at(from.init_bb, to.init_val.clone());
+            next_of(from).set_operand(1, Constant::Int(to.delta).into());
+            cond_of(from).set_operand(1, to.exit_val.clone());
+        }
+    }
+
+    /// Tile inner loop with `size` iterations, and put the tile loop outside the nest:
+    ///
+    /// ```text
+    /// for (jj = j0; jj < N2; jj += size * d2)
+    ///   for (i = i0; i < N1; i += d1)
+    ///     for (j = jj; j < jj + size * d2; j += d2)
+    /// ```
+    ///
+    /// Only applies when inner trip count is a constant multiple of `size`.
+    fn tile(&mut self, nest: &Nest, size: i32) -> bool {
+        let outer = &nest.outer;
+        let inner = &nest.inner;
+        let (Operand::Constant(Constant::Int(init)), Operand::Constant(Constant::Int(exit))) =
+            (&inner.init_val, &inner.exit_val)
+        else {
+            return false;
+        };
+        let width = size.saturating_mul(inner.delta);
+        let Some(range) = exit.checked_sub(*init) else {
+            return false;
+        };
+        if inner.delta <= 0 || range <= width || range % width != 0 {
+            return false;
+        }
+        let exit_bb = outer.exit_bb;
+        if exit_bb.iter().any(|inst| inst.get_type() == InstType::Phi) {
+            return false;
+        }
+
+        // Tile header: jj = phi [j0, pre_header], [jj + width, tile_latch]
+        let mut header = outer.lo.head;
+        let mut pre_header = outer.init_bb;
+        let name = self.unique_name(&header.name);
+        let mut tile_header = self.program.mem_pool.new_basicblock(name);
+        let name = self.unique_name(&header.name);
+        let mut tile_latch = self.program.mem_pool.new_basicblock(name);
+        let mut tile_phi = self.program.mem_pool.get_phi(ValueType::Int, vec![]);
+        tile_header.push_back(tile_phi);
+        let tile_end = self
+            .program
+            .mem_pool
+            .get_add(tile_phi.into(), Constant::Int(width).into());
+        tile_header.push_back(tile_end);
+        let cond = self.program.mem_pool.get_icmp(
+            ICmpOp::Slt,
+            ValueType::Int,
+            tile_phi.into(),
+            inner.exit_val.clone(),
+        );
+        tile_header.push_back(cond);
+        let br = self.program.mem_pool.get_br(Some(cond.into()));
+        tile_header.push_back(br);
+        let br = self.program.mem_pool.get_br(None);
+        tile_latch.push_back(br);
+
+        // Wire edges, predecessors of tile header are [pre_header, tile_latch]
+        pre_header.replace_succ_bb_only(header, tile_header);
+        tile_header.set_true_bb(header);
+        header.replace_succ_bb_only(exit_bb, tile_latch);
+        tile_latch.push_succ_bb(tile_header);
+        tile_header.set_false_bb(exit_bb);
+
+        // Fill phi, outer loop now starts from tile header
+        let phi = downcast_mut::<Phi>(tile_phi.as_mut().as_mut());
+        phi.add_incoming_value(inner.init_val.clone(), pre_header);
+        phi.add_incoming_value(tile_end.into(), tile_latch);
+        let mut indvar = outer.indvar;
+        let phi = downcast_mut::<Phi>(indvar.as_mut().as_mut());
+        phi.remove_incoming_value(pre_header.id);
+        phi.add_incoming_value(outer.init_val.clone(), tile_header);
+
+        // Inner loop runs within the tile
+        let mut indvar = inner.indvar;
+        let phi = downcast_mut::<Phi>(indvar.as_mut().as_mut());
+        phi.replace_incoming_value_at(inner.init_bb, tile_phi.into());
+        cond_of(inner).set_operand(1, tile_end.into());
+        true
+    }
+
+    fn unique_name(&mut self, base_name: &str) -> String {
+        let mut id = 0;
+        loop {
+            let name = format!("{}_tile{}", base_name, id);
+            if self.bb_names.insert(name.clone()) {
+                return name;
+            }
+            id += 1;
+        }
+    }
+}
+
+/// Get induction variable of a loop exiting from header.
+pub fn get_candidate(lo: LoopPtr, dom_tree: &mut DominatorTree) -> Option<Candidate> {
+    lo.pre_header?;
+    let exit = lo.head.get_last_inst();
+    let candidate = Candidate::from_exit(exit, lo, dom_tree)?;
+    (candidate.delta > 0).then_some(candidate)
+}
+
+/// Get `indvar + delta` of an induction variable.
+pub fn next_of(candidate: &Candidate) -> InstPtr {
+    let phi = downcast_ref::<Phi>(candidate.indvar.as_ref().as_ref());
+    let Operand::Instruction(next) = phi.get_incoming_values()[1].0 else {
+        unreachable!();
+    };
+    next
+}
+
+/// Get `indvar < N` of an induction variable.
+pub fn cond_of(candidate: &Candidate) -> InstPtr {
+    let Operand::Instruction(cond) = candidate.exit.get_operand()[0] else {
+        unreachable!();
+    };
+    cond
+}
+
+/// Check if an instruction can be moved into a loop body without changing semantics.
+pub fn is_pure(inst: InstPtr) -> bool {
+    matches!(
+        inst.get_type(),
+        InstType::Add
+            | InstType::Sub
+            | InstType::Mul
+            | InstType::SDiv
+            | InstType::SRem
+            | InstType::Shl
+            | InstType::AShr
+            | InstType::LShr
+            | InstType::And
+            | InstType::Or
+            | InstType::Xor
+            | InstType::ZextTo
+            | InstType::SextTo
+            | InstType::ICmp
+            | InstType::GetElementPtr
+    )
+}
+
+/// Get blocks of a loop, including its sub loops.
+pub fn get_loop_blocks(lo: LoopPtr) -> Vec<BBPtr> {
+    let mut blocks = lo.blocks.iter().cloned().collect::<Vec<_>>();
+    for sub_loop in lo.sub_loops.iter() {
+        blocks.extend(get_loop_blocks(*sub_loop));
+    }
+    blocks.sort();
+    blocks
+}
+
+/// Get address of a load or store.
+pub fn get_ptr(inst: InstPtr) -> Operand {
+    if inst.get_type() == InstType::Load {
+        downcast_ref::<Load>(inst.as_ref().as_ref())
+            .get_ptr()
+            .clone()
+    } else {
+        downcast_ref::<Store>(inst.as_ref().as_ref())
+            .get_ptr()
+            .clone()
+    }
+}
+
+/// Check if two addresses are computed the same way.
+fn same_address(a: &Operand, b: &Operand) -> bool {
+    if a == b {
+        return true;
+    }
+    let (Operand::Instruction(a), Operand::Instruction(b)) = (a, b) else {
+        return false;
+    };
+    if a.get_type() != InstType::GetElementPtr || b.get_type() != InstType::GetElementPtr {
+        return false;
+    }
+    let (a, b) = (a.get_operand(), b.get_operand());
+    a.len() == b.len() && same_address(&a[0], &b[0]) && a[1..] == b[1..]
+}
+
+/// Check if a GEP chain has an index `iv` or `iv + c`, which differs for each iteration.
+pub fn is_indexed_by(op: &Operand, iv: &Operand) -> bool {
+    let Operand::Instruction(inst) = op else {
+        return false;
+    };
+    if inst.get_type() != InstType::GetElementPtr {
+        return false;
+    }
+    let operands = inst.get_operand();
+    operands[1..].iter().any(|index| {
+        if index == iv {
+            return true;
+        }
+        let Operand::Instruction(index) = index else {
+            return false;
+        };
+        matches!(index.get_type(), InstType::Add | InstType::Sub)
+            && &index.get_operand()[0] == iv
+            && matches!(index.get_operand()[1], Operand::Constant(_))
+    }) || is_indexed_by(&operands[0], iv)
+}
+
+/// Number of accesses made unit-stride by the inner induction variable, minus those by
+/// the outer one. Negative score means interchange is profitable.
+fn stride_score(nest: &Nest) -> i32 {
+    let outer_iv = Operand::from(nest.outer.indvar);
+    let inner_iv = Operand::from(nest.inner.indvar);
+    let mut score = 0;
+    for access in nest.accesses.iter() {
+        let Operand::Instruction(gep) = get_ptr(*access) else {
+            continue;
+        };
+        if gep.get_type() != InstType::GetElementPtr {
+            continue;
+        }
+        let last = gep.get_operand().last().unwrap();
+        if depends_on(last, &inner_iv, 8) {
+            score += 1;
+        } else if depends_on(last, &outer_iv, 8) {
+            score -= 1;
+        }
+    }
+    score
+}
+
+/// Check if an operand is computed from `iv` within `depth` instructions.
+fn depends_on(op: &Operand, iv: &Operand, depth: usize) -> bool {
+    if op == iv {
+        return true;
+    }
+    let Operand::Instruction(inst) = op else {
+        return false;
+    };
+    if depth == 0 || inst.get_type() == InstType::Phi {
+        return false;
+    }
+    inst.get_operand()
+        .iter()
+        .any(|op| depends_on(op, iv, depth - 1))
+}
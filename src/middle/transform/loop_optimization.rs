@@ -26,9 +26,15 @@ use crate::middle::{
     ir::FunPtr,
     Program,
 };
+use crate::utils::pass_instrument;
 use anyhow::{Ok, Result};
 
 pub fn optimize_program(program: &mut Program) -> Result<()> {
+    let ir = |program: &Program| program.module.gen_llvm_ir();
+    pass_instrument::run_pass("loop_optimization", "module", "ll", program, ir, run)
+}
+
+fn run(program: &mut Program) -> Result<()> {
     let effect_analysis = EffectAnalysis::new(program);
     let mut memory_ssa = MemorySSA::new(program, &effect_analysis);
     let mut func_loop_map = program
@@ -0,0 +1,521 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::{
+    backend::from_self::downcast_ref,
+    config::CONFIG,
+    cprintln,
+    middle::{
+        analysis::{
+            dominator_tree::DominatorTree,
+            loop_tools::{self, LoopForest, LoopPtr},
+            scalar_evolution::ScalarEvolution,
+        },
+        ir::{
+            instruction::{
+                downcast_mut,
+                misc_inst::{ICmpOp, Phi},
+                InstType,
+            },
+            BBPtr, Constant, FunPtr, InstPtr, Operand, ValueType,
+        },
+        Program,
+    },
+};
+
+use super::{loop_simplify, make_parallel::Candidate, Transform};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let mut changed = false;
+    for func in program.module.functions.clone() {
+        if func.is_lib() {
+            continue;
+        }
+        let Some(mut forest) = loop_tools::LoopForest::make_forest(func) else {
+            continue;
+        };
+        loop_simplify::LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+        let mut dom_tree = DominatorTree::new(func);
+        changed |= LoopUnroll::new(program, func, &mut forest, &mut dom_tree).run_and_log()?;
+    }
+    Ok(changed)
+}
+
+pub struct LoopUnroll<'a> {
+    program: &'a mut Program,
+    func: FunPtr,
+    loop_forest: &'a mut LoopForest,
+    dom_tree: &'a mut DominatorTree,
+    bb_names: HashSet<String>,
+}
+
+impl<'a> Transform for LoopUnroll<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "loop_unroll".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        // Only innermost loops are unrolled, so that unrolled loops never overlap.
+        // Plans are made before unrolling, when dominator tree is still valid.
+        let mut loops = Vec::new();
+        for lo in self.loop_forest.forest.iter() {
+            collect_innermost(*lo, &mut loops);
+        }
+        let plans = loops
+            .into_iter()
+            .filter_map(|lo| self.make_plan(lo))
+            .collect::<Vec<_>>();
+        let changed = !plans.is_empty();
+        for plan in plans {
+            match plan.kind {
+                UnrollKind::Full(trip_count) => self.unroll_full(&plan, trip_count)?,
+                UnrollKind::Partial(factor) => self.unroll_partial(&plan, factor)?,
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// How to unroll a loop.
+enum UnrollKind {
+    /// Replace loop with given number of body copies
+    Full(usize),
+    /// Unroll loop by given factor, and run the rest iterations with original loop
+    Partial(usize),
+}
+
+/// A loop to unroll. The loop should shape like:
+///
+/// ```llvm
+/// pre_header:
+///   br label %header
+/// header:
+///   %indvar = phi [init_val, pre_header], [%next, latch]
+///   ; other phi
+///   %cond = icmp slt %indvar, exit_val
+///   br i1 %cond, label %body_entry, label %exit
+/// ; body blocks, branching only to body blocks and header
+/// latch:
+///   %next = add %indvar, delta
+///   br label %header
+/// ```
+struct UnrollPlan {
+    candidate: Candidate,
+    pre_header: BBPtr,
+    header: BBPtr,
+    latch: BBPtr,
+    body_entry: BBPtr,
+    body: Vec<BBPtr>,
+    phis: Vec<InstPtr>,
+    kind: UnrollKind,
+}
+
+impl<'a> LoopUnroll<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        func: FunPtr,
+        loop_forest: &'a mut LoopForest,
+        dom_tree: &'a mut DominatorTree,
+    ) -> Self {
+        let bb_names = func.dfs_iter().map(|bb| bb.name.clone()).collect();
+        Self {
+            program,
+            func,
+            loop_forest,
+            dom_tree,
+            bb_names,
+        }
+    }
+
+    /// Check if loop is in canonical form, and decide how to unroll it.
+    fn make_plan(&mut self, lo: LoopPtr) -> Option<UnrollPlan> {
+        let pre_header = lo.pre_header?;
+        let header = lo.head;
+        let exit = header.get_last_inst();
+        let candidate = Candidate::from_exit(exit, lo, self.dom_tree)?;
+        if candidate.delta <= 0 {
+            return None;
+        }
+
+        // Header should jump into loop when condition is true, and exit otherwise
+        let [body_entry, exit_bb] = header.get_succ_bb()[..] else {
+            return None;
+        };
+        if !lo.is_in_loop(&body_entry) || exit_bb != candidate.exit_bb {
+            return None;
+        }
+
+        // Header should have exactly one back edge
+        let [pred, latch] = header.get_pred_bb()[..] else {
+            return None;
+        };
+        let latch = if pred == pre_header { latch } else { pred };
+        if !lo.is_in_loop(&latch) || !header.get_pred_bb().contains(&pre_header) {
+            return None;
+        }
+
+        // Header should contain only phi, condition and exit
+        let cond = exit.get_operand().first()?.clone();
+        let mut phis = Vec::new();
+        for inst in header.iter() {
+            if inst.get_type() == InstType::Phi {
+                let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+                if phi.get_incoming_value(pre_header).is_none()
+                    || phi.get_incoming_value(latch).is_none()
+                {
+                    return None;
+                }
+                phis.push(inst);
+            } else if Operand::Instruction(inst) == cond {
+                if inst.get_user().len() != 1 {
+                    return None;
+                }
+            } else if inst != exit {
+                cprintln!("[INFO] loop {} has complex header", header.name);
+                return None;
+            }
+        }
+
+        // Body can only exit through header, and body entry can't have phi
+        let body = self
+            .func
+            .rpo_iter()
+            .filter(|bb| *bb != header && lo.is_in_loop(bb))
+            .collect::<Vec<_>>();
+        for bb in body.iter() {
+            if bb.get_succ_bb().iter().any(|succ| !lo.is_in_loop(succ)) {
+                return None;
+            }
+        }
+        if body_entry.get_first_inst().get_type() == InstType::Phi {
+            return None;
+        }
+
+        // Decide unroll kind by size of body
+        let size = body.iter().map(|bb| bb.iter().count()).sum::<usize>();
+        let budget = CONFIG.loop_unroll_budget;
+        let kind = match ScalarEvolution::new().get_trip_count(lo) {
+            Some(trip_count) if trip_count * size <= budget => UnrollKind::Full(trip_count),
+            _ => {
+                let factor = CONFIG.loop_unroll_factor;
+                if factor < 2 || factor * size > budget {
+                    return None;
+                }
+
+                // Bound of unrolled loop `exit_val - (factor - 1) * delta` should not overflow
+                let delta = (factor as i32 - 1).checked_mul(candidate.delta)?;
+                if let Operand::Constant(Constant::Int(exit_val)) = candidate.exit_val {
+                    exit_val.checked_sub(delta)?;
+                }
+                UnrollKind::Partial(factor)
+            }
+        };
+
+        Some(UnrollPlan {
+            candidate,
+            pre_header,
+            header,
+            latch,
+            body_entry,
+            body,
+            phis,
+            kind,
+        })
+    }
+
+    /// Replace loop with copies of body, wired one after another.
+    fn unroll_full(&mut self, plan: &UnrollPlan, trip_count: usize) -> Result<()> {
+        let header = plan.header;
+        let mut values = self.init_values(plan);
+        let mut tail = plan.pre_header;
+        for _ in 0..trip_count {
+            let (block_map, next_values) = self.copy_body(plan, &values)?;
+            tail.replace_succ_bb_only(header, block_map[&plan.body_entry]);
+            tail = block_map[&plan.latch];
+            values = next_values;
+        }
+
+        // Wire last iteration to exit, replace header with it in exit phi.
+        // Incoming value from tail is appended, same as predecessor list.
+        let exit_bb = plan.candidate.exit_bb;
+        tail.replace_succ_bb_only(header, exit_bb);
+        for mut inst in exit_bb.iter() {
+            if inst.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_mut::<Phi>(inst.as_mut().as_mut());
+            let Some(op) = phi.get_incoming_value(header).cloned() else {
+                continue;
+            };
+            phi.remove_incoming_value(header.id);
+            phi.add_incoming_value(map_operand(&values, &op), tail);
+        }
+
+        // Replace header phi used after loop with value of last iteration
+        for phi in plan.phis.iter() {
+            let users = phi.get_user().to_vec();
+            for user in users {
+                if !plan.candidate.lo.is_in_loop(&user.get_parent_bb().unwrap()) {
+                    replace_use(user, *phi, values[phi].clone());
+                }
+            }
+        }
+
+        // Remove original loop
+        for mut bb in plan.body.iter().cloned().chain([header]) {
+            bb.remove_self();
+        }
+        for bb in plan.body.iter().chain([&header]) {
+            for mut inst in bb.iter().collect::<Vec<_>>() {
+                inst.remove_self();
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert a loop running `factor` iterations at a time before original loop.
+    /// Original loop runs the rest iterations.
+    fn unroll_partial(&mut self, plan: &UnrollPlan, factor: usize) -> Result<()> {
+        let header = plan.header;
+        let mut pre_header = plan.pre_header;
+        let name = self.unique_name(&header.name);
+        let mut unrolled_header = self.program.mem_pool.new_basicblock(name);
+
+        // Enter unrolled body if all `factor` iterations are in bound:
+        // indvar < exit_val - (factor - 1) * delta
+        // The subtraction can't overflow for constant exit_val (checked in `make_plan`),
+        // otherwise it's guarded before entering unrolled loop.
+        let delta = (factor as i32 - 1) * plan.candidate.delta;
+        let mut guard = None;
+        let limit: Operand = match plan.candidate.exit_val {
+            Operand::Constant(Constant::Int(exit_val)) => {
+                pre_header.replace_succ_bb_only(header, unrolled_header);
+                Constant::Int(exit_val - delta).into()
+            }
+            ref exit_val => {
+                let name = self.unique_name(&pre_header.name);
+                let mut guard_bb = self.program.mem_pool.new_basicblock(name);
+                let limit = self
+                    .program
+                    .mem_pool
+                    .get_sub(exit_val.clone(), Constant::Int(delta).into());
+                guard_bb.push_back(limit);
+                let no_overflow = self.program.mem_pool.get_icmp(
+                    ICmpOp::Sge,
+                    ValueType::Int,
+                    exit_val.clone(),
+                    Constant::Int(i32::MIN + delta).into(),
+                );
+                guard_bb.push_back(no_overflow);
+                let br = self.program.mem_pool.get_br(Some(no_overflow.into()));
+                guard_bb.push_back(br);
+                pre_header.replace_succ_bb_only(header, guard_bb);
+                guard_bb.set_true_bb(unrolled_header);
+                guard_bb.set_false_bb(header);
+                guard = Some(guard_bb);
+                limit.into()
+            }
+        };
+        let entry = guard.unwrap_or(pre_header);
+
+        // Create phi in unrolled header for each phi in header
+        let mut values = HashMap::new();
+        let mut unrolled_phis = Vec::new();
+        for phi in plan.phis.iter() {
+            let unrolled_phi = self.program.mem_pool.get_phi(phi.get_value_type(), vec![]);
+            unrolled_header.push_back(unrolled_phi);
+            values.insert(*phi, Operand::from(unrolled_phi));
+            unrolled_phis.push(unrolled_phi);
+        }
+        let cond = self.program.mem_pool.get_icmp(
+            ICmpOp::Slt,
+            ValueType::Int,
+            values[&plan.candidate.indvar].clone(),
+            limit,
+        );
+        unrolled_header.push_back(cond);
+        let br = self.program.mem_pool.get_br(Some(cond.into()));
+        unrolled_header.push_back(br);
+
+        // Copy body for `factor` times, the last one jumps back to unrolled header
+        let mut tail = unrolled_header;
+        for i in 0..factor {
+            let (block_map, next_values) = self.copy_body(plan, &values)?;
+            if i == 0 {
+                unrolled_header.set_true_bb(block_map[&plan.body_entry]);
+            } else {
+                tail.replace_succ_bb_only(header, block_map[&plan.body_entry]);
+            }
+            tail = block_map[&plan.latch];
+            values = next_values;
+        }
+        tail.replace_succ_bb_only(header, unrolled_header);
+        unrolled_header.set_false_bb(header);
+
+        // Fill phi in unrolled header, and make original loop start from unrolled header (or guard).
+        // Incoming values follow predecessor order: [entry, tail] and [latch, guard, unrolled_header].
+        for (mut phi, mut unrolled_phi) in plan.phis.iter().cloned().zip(unrolled_phis) {
+            let next = values[&phi].clone();
+            let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+            let init = phi.get_incoming_value(pre_header).unwrap().clone();
+            let unrolled = downcast_mut::<Phi>(unrolled_phi.as_mut().as_mut());
+            unrolled.add_incoming_value(init.clone(), entry);
+            unrolled.add_incoming_value(next, tail);
+            phi.remove_incoming_value(pre_header.id);
+            if let Some(guard) = guard {
+                phi.add_incoming_value(init, guard);
+            }
+            phi.add_incoming_value(unrolled_phi.into(), unrolled_header);
+        }
+        Ok(())
+    }
+
+    /// Get values of header phi in the first iteration.
+    fn init_values(&self, plan: &UnrollPlan) -> HashMap<InstPtr, Operand> {
+        plan.phis
+            .iter()
+            .map(|phi| {
+                let inc = downcast_ref::<Phi>(phi.as_ref().as_ref());
+                (
+                    *phi,
+                    inc.get_incoming_value(plan.pre_header).unwrap().clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Copy body blocks for an iteration, in which header phi have given values.
+    /// Copied latch still jumps to header, please redirect it.
+    /// Returns mapping from body blocks to copied blocks, and header phi values of next iteration.
+    #[allow(clippy::type_complexity)]
+    fn copy_body(
+        &mut self,
+        plan: &UnrollPlan,
+        values: &HashMap<InstPtr, Operand>,
+    ) -> Result<(HashMap<BBPtr, BBPtr>, HashMap<InstPtr, Operand>)> {
+        let mut block_map: HashMap<BBPtr, BBPtr> = HashMap::new();
+        let mut inst_map = values.clone();
+
+        // Copy blocks and instructions
+        for bb in plan.body.iter() {
+            let name = self.unique_name(&bb.name);
+            let mut new_bb = self.program.mem_pool.new_basicblock(name);
+            block_map.insert(*bb, new_bb);
+            for inst in bb.iter() {
+                let new_inst = self
+                    .program
+                    .mem_pool
+                    .copy_instruction(inst.as_ref().as_ref());
+                inst_map.insert(inst, new_inst.into());
+                new_bb.push_back(new_inst);
+            }
+        }
+
+        // Copy edges
+        for bb in plan.body.iter() {
+            let mut new_bb = block_map[bb];
+            for succ in bb.get_succ_bb() {
+                new_bb.push_succ_bb(block_map.get(succ).cloned().unwrap_or(*succ));
+            }
+        }
+
+        // Copy operands, replacing them with values in this iteration.
+        // Incoming values of phi follow predecessor order of copied block.
+        let origin_map = block_map
+            .iter()
+            .map(|(bb, new_bb)| (*new_bb, *bb))
+            .collect::<HashMap<_, _>>();
+        for bb in plan.body.iter() {
+            for inst in bb.iter() {
+                let Operand::Instruction(mut new_inst) = inst_map[&inst].clone() else {
+                    unreachable!();
+                };
+                if inst.get_type() == InstType::Phi {
+                    let inst = downcast_ref::<Phi>(inst.as_ref().as_ref());
+                    let new_inst = downcast_mut::<Phi>(new_inst.as_mut().as_mut());
+                    for pred in block_map[bb].get_pred_bb() {
+                        let op = inst.get_incoming_value(origin_map[pred]).unwrap();
+                        new_inst.add_incoming_value(map_operand(&inst_map, op), *pred);
+                    }
+                } else {
+                    for op in inst.get_operand() {
+                        new_inst.add_operand(map_operand(&inst_map, op));
+                    }
+                }
+            }
+        }
+
+        // Get header phi values for next iteration
+        let next_values = plan
+            .phis
+            .iter()
+            .map(|phi| {
+                let inc = downcast_ref::<Phi>(phi.as_ref().as_ref());
+                let op = inc.get_incoming_value(plan.latch).unwrap();
+                (*phi, map_operand(&inst_map, op))
+            })
+            .collect();
+        Ok((block_map, next_values))
+    }
+
+    /// Get a block name not used in current function.
+    fn unique_name(&mut self, base_name: &str) -> String {
+        let mut id = 0;
+        loop {
+            let name = format!("{}_unroll{}", base_name, id);
+            if self.bb_names.insert(name.clone()) {
+                return name;
+            }
+            id += 1;
+        }
+    }
+}
+
+/// Collect innermost loops in loop tree.
+fn collect_innermost(lo: LoopPtr, result: &mut Vec<LoopPtr>) {
+    if lo.sub_loops.is_empty() {
+        result.push(lo);
+    }
+    for sub_loop in lo.sub_loops.iter() {
+        collect_innermost(*sub_loop, result);
+    }
+}
+
+/// Map operand to its copy, operands defined outside of copied code are kept.
+pub fn map_operand(map: &HashMap<InstPtr, Operand>, op: &Operand) -> Operand {
+    match op {
+        Operand::Instruction(inst) => map.get(inst).cloned().unwrap_or_else(|| op.clone()),
+        _ => op.clone(),
+    }
+}
+
+/// Replace all use of `from` in `user` with `to`.
+pub fn replace_use(mut user: InstPtr, from: InstPtr, to: Operand) {
+    let from = Operand::Instruction(from);
+    for index in 0..user.get_operand().len() {
+        if user.get_operand()[index] == from {
+            user.set_operand(index, to.clone());
+        }
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::{
+    backend::from_self::downcast_ref,
+    config::CONFIG,
+    middle::{
+        analysis::{
+            dominator_tree::DominatorTree,
+            loop_tools::{LoopForest, LoopPtr},
+        },
+        ir::{
+            instruction::{downcast_mut, misc_inst::Phi, InstType},
+            BBPtr, Constant, FunPtr, InstPtr, Operand,
+        },
+        Program,
+    },
+};
+
+use super::{
+    loop_simplify,
+    loop_unroll::{map_operand, replace_use},
+    Transform,
+};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    optimize_program_with_growth(program, &mut HashMap::new())
+}
+
+/// Unswitch loops, adding number of cloned instructions of each function to `growth`.
+/// Repeated runs should share `growth`, so that they share the budget of each function.
+pub fn optimize_program_with_growth(
+    program: &mut Program,
+    growth: &mut HashMap<FunPtr, usize>,
+) -> Result<bool> {
+    let mut changed = false;
+    for func in program.module.functions.clone() {
+        if func.is_lib() {
+            continue;
+        }
+        let Some(mut forest) = LoopForest::make_forest(func) else {
+            continue;
+        };
+        loop_simplify::LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+        let growth = growth.entry(func).or_default();
+        changed |= LoopUnswitch::new(program, &mut forest, func, growth).run_and_log()?;
+    }
+    Ok(changed)
+}
+
+/// Move loop invariant conditions out of loop:
+///
+/// ```text
+/// while (...) { if (c) A else B }
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// if (c) while (...) { if (true) A else B }
+/// else while (...) { if (false) A else B }
+/// ```
+///
+/// Constant branches are then pruned by `inst_combine`, and left blocks are fused by `block_fuse`.
+/// Each function clones at most `CONFIG.loop_unswitch_budget` instructions in total,
+/// otherwise a loop with `k` invariant conditions would be cloned `2^k` times.
+pub struct LoopUnswitch<'a> {
+    program: &'a mut Program,
+    loop_forest: &'a mut LoopForest,
+    func: FunPtr,
+    bb_names: HashSet<String>,
+    /// Number of instructions cloned in this function
+    growth: &'a mut usize,
+}
+
+impl<'a> Transform for LoopUnswitch<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "loop_unswitch".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        let mut changed = false;
+        for lo in self.loop_forest.forest.clone() {
+            self.visit(lo, &mut changed);
+        }
+        Ok(changed)
+    }
+}
+
+impl<'a> LoopUnswitch<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        loop_forest: &'a mut LoopForest,
+        func: FunPtr,
+        growth: &'a mut usize,
+    ) -> Self {
+        let bb_names = func.dfs_iter().map(|bb| bb.name.clone()).collect();
+        Self {
+            program,
+            loop_forest,
+            func,
+            bb_names,
+            growth,
+        }
+    }
+
+    /// Unswitch inner loops first, because they are smaller to clone.
+    /// Returns true if the loop forest above `lo` is outdated by unswitching.
+    fn visit(&mut self, lo: LoopPtr, changed: &mut bool) -> bool {
+        let mut outdated = false;
+        for sub_loop in lo.sub_loops.iter() {
+            outdated |= self.visit(*sub_loop, changed);
+        }
+        if outdated {
+            return true;
+        }
+        if self.unswitch(lo) {
+            *changed = true;
+            return true;
+        }
+        false
+    }
+
+    /// Clone loop into two versions guarded by an invariant condition.
+    fn unswitch(&mut self, lo: LoopPtr) -> bool {
+        let Some(mut pre_header) = lo.pre_header else {
+            return false;
+        };
+        let blocks = get_loop_blocks(lo);
+        let size: usize = blocks.iter().map(|bb| bb.iter().count()).sum();
+        if *self.growth + size > CONFIG.loop_unswitch_budget {
+            return false;
+        }
+        let mut guard = pre_header.get_last_inst();
+        if guard.get_type() != InstType::Br || !guard.get_operand().is_empty() {
+            return false;
+        }
+
+        // Find a conditional branch with loop invariant condition
+        let in_loop = |inst: &InstPtr| lo.is_in_loop(&inst.get_parent_bb().unwrap());
+        let Some(br) = blocks.iter().map(|bb| bb.get_last_inst()).find(|br| {
+            if br.get_type() != InstType::Br {
+                return false;
+            }
+            match br.get_operand().first() {
+                Some(Operand::Instruction(inst)) => !in_loop(inst),
+                Some(Operand::Parameter(_)) => true,
+                _ => false,
+            }
+        }) else {
+            return false;
+        };
+
+        // Values used after loop need a phi to merge two versions, which is placed in the
+        // only exit block. The value should be available on every exit edge.
+        let mut exits = Vec::new();
+        for bb in blocks.iter() {
+            for succ in bb.get_succ_bb() {
+                if !lo.is_in_loop(succ) && !exits.contains(succ) {
+                    exits.push(*succ);
+                }
+            }
+        }
+        let is_exit_phi = |user: &InstPtr| {
+            user.get_type() == InstType::Phi && exits.contains(&user.get_parent_bb().unwrap())
+        };
+        let mut live_outs = Vec::new();
+        for bb in blocks.iter() {
+            for inst in bb.iter() {
+                if inst
+                    .get_user()
+                    .iter()
+                    .any(|user| !in_loop(user) && !is_exit_phi(user))
+                {
+                    live_outs.push(inst);
+                }
+            }
+        }
+        if !live_outs.is_empty() {
+            let [exit] = exits[..] else {
+                return false;
+            };
+            if !exit.get_pred_bb().iter().all(|pred| lo.is_in_loop(pred)) {
+                return false;
+            }
+            let mut dom_tree = DominatorTree::new(self.func);
+            for inst in live_outs.iter() {
+                let bb = inst.get_parent_bb().unwrap();
+                if !exit
+                    .get_pred_bb()
+                    .iter()
+                    .all(|pred| dom_tree.is_dominate(bb, *pred))
+                {
+                    return false;
+                }
+            }
+        }
+
+        // Copy blocks and instructions
+        *self.growth += size;
+        let mut block_map: HashMap<BBPtr, BBPtr> = HashMap::new();
+        let mut inst_map: HashMap<InstPtr, Operand> = HashMap::new();
+        for bb in blocks.iter() {
+            let name = self.unique_name(&bb.name);
+            let mut new_bb = self.program.mem_pool.new_basicblock(name);
+            block_map.insert(*bb, new_bb);
+            for inst in bb.iter() {
+                let new_inst = self
+                    .program
+                    .mem_pool
+                    .copy_instruction(inst.as_ref().as_ref());
+                inst_map.insert(inst, new_inst.into());
+                new_bb.push_back(new_inst);
+            }
+        }
+
+        // Copy edges
+        for bb in blocks.iter() {
+            let mut new_bb = block_map[bb];
+            for succ in bb.get_succ_bb() {
+                new_bb.push_succ_bb(block_map.get(succ).cloned().unwrap_or(*succ));
+            }
+        }
+
+        // Jump to the copy when condition is false, each version has its own pre_header
+        let header = lo.head;
+        let new_guard = self
+            .program
+            .mem_pool
+            .get_br(Some(br.get_operand()[0].clone()));
+        guard.insert_after(new_guard);
+        guard.remove_self();
+        let mut pre_headers = Vec::new();
+        for target in [header, block_map[&header]] {
+            let name = self.unique_name(&pre_header.name);
+            let mut bb = self.program.mem_pool.new_basicblock(name);
+            let br = self.program.mem_pool.get_br(None);
+            bb.push_back(br);
+            bb.push_succ_bb(target);
+            pre_headers.push(bb);
+        }
+        pre_header.replace_succ_bb_only(header, pre_headers[0]);
+        pre_header.set_false_bb(pre_headers[1]);
+        for mut phi in header.iter() {
+            if phi.get_type() != InstType::Phi {
+                break;
+            }
+            let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+            let op = phi.get_incoming_value(pre_header).unwrap().clone();
+            phi.remove_incoming_value(pre_header.id);
+            phi.add_incoming_value(op, pre_headers[0]);
+        }
+
+        // Copy operands, incoming values of phi follow predecessor order of copied block
+        let mut origin_map = block_map
+            .iter()
+            .map(|(bb, new_bb)| (*new_bb, *bb))
+            .collect::<HashMap<_, _>>();
+        origin_map.insert(pre_headers[1], pre_headers[0]);
+        for bb in blocks.iter() {
+            for inst in bb.iter() {
+                let Operand::Instruction(mut new_inst) = inst_map[&inst].clone() else {
+                    unreachable!();
+                };
+                if inst.get_type() == InstType::Phi {
+                    let inst = downcast_ref::<Phi>(inst.as_ref().as_ref());
+                    let new_inst = downcast_mut::<Phi>(new_inst.as_mut().as_mut());
+                    for pred in block_map[bb].get_pred_bb() {
+                        let op = inst.get_incoming_value(origin_map[pred]).unwrap();
+                        new_inst.add_incoming_value(map_operand(&inst_map, op), *pred);
+                    }
+                } else {
+                    for op in inst.get_operand() {
+                        new_inst.add_operand(map_operand(&inst_map, op));
+                    }
+                }
+            }
+        }
+
+        // Exit phis get incoming values from copied exiting blocks
+        for exit in exits.iter() {
+            for mut phi in exit.iter() {
+                if phi.get_type() != InstType::Phi {
+                    continue;
+                }
+                let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+                for pred in exit.get_pred_bb() {
+                    if let Some(origin) = origin_map.get(pred) {
+                        let op = phi.get_incoming_value(*origin).unwrap().clone();
+                        phi.add_incoming_value(map_operand(&inst_map, &op), *pred);
+                    }
+                }
+            }
+        }
+
+        // Merge live out values of two versions
+        for inst in live_outs {
+            let mut exit = exits[0];
+            let users = inst
+                .get_user()
+                .iter()
+                .filter(|user| !in_loop(user) && !is_exit_phi(user))
+                .cloned()
+                .collect::<Vec<_>>();
+            let incoming = exit
+                .get_pred_bb()
+                .iter()
+                .map(|pred| {
+                    let op = Operand::from(inst);
+                    if origin_map.contains_key(pred) {
+                        (map_operand(&inst_map, &op), *pred)
+                    } else {
+                        (op, *pred)
+                    }
+                })
+                .collect();
+            let phi = self
+                .program
+                .mem_pool
+                .get_phi(inst.get_value_type(), incoming);
+            exit.push_front(phi);
+            for user in users {
+                replace_use(user, inst, phi.into());
+            }
+        }
+
+        // Fix condition in each version
+        let Operand::Instruction(mut new_br) = inst_map[&br].clone() else {
+            unreachable!();
+        };
+        let mut br = br;
+        br.set_operand(0, Constant::Bool(true).into());
+        new_br.set_operand(0, Constant::Bool(false).into());
+        true
+    }
+
+    fn unique_name(&mut self, base_name: &str) -> String {
+        let mut id = 0;
+        loop {
+            let name = format!("{}_unswitch{}", base_name, id);
+            if self.bb_names.insert(name.clone()) {
+                return name;
+            }
+            id += 1;
+        }
+    }
+}
+
+/// Get blocks of a loop, including its sub loops.
+fn get_loop_blocks(lo: LoopPtr) -> Vec<BBPtr> {
+    let mut blocks = lo.blocks.iter().cloned().collect::<Vec<_>>();
+    for sub_loop in lo.sub_loops.iter() {
+        blocks.extend(get_loop_blocks(*sub_loop));
+    }
+    blocks.sort();
+    blocks
+}
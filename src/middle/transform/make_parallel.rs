@@ -199,6 +199,29 @@ impl<'a, const N_THREAD: i32> MakeParallel<'a, N_THREAD> {
             return Ok(());
         };
 
+        // Exit block should have only one pred
+        // TODO-PERF: this is for easy thread join implementation, but weakens optimization
+        if candidate.exit_bb.get_pred_bb().len() != 1 {
+            cprintln!(
+                "[INFO] loop {} fails because {} has multiple preds",
+                pre_header.name,
+                candidate.exit_bb.name
+            );
+            return Ok(());
+        }
+
+        // `indvar` should be the only phi in its block (other phi can be non-trivial)
+        for inst in candidate.indvar.get_parent_bb().unwrap().iter() {
+            if inst.get_type() == InstType::Phi && inst != candidate.indvar {
+                cprintln!(
+                    "[INFO] loop {} fails because {} has multiple phi",
+                    pre_header.name,
+                    inst.gen_llvm_ir()
+                );
+                return Ok(());
+            }
+        }
+
         // If effect range collides, then it can't be parallelized, check sub loops instead
         if self
             .get_loop_effect(lo, &candidate.indvar.into())?
@@ -492,15 +515,15 @@ fn merge_effect(a: &mut Effect, b: &Effect, indvar: &Operand) -> Result<bool> {
 /// init_val = 2
 /// init_bb = pre_header
 /// exit_val = 6
-struct Candidate {
-    lo: LoopPtr,
-    indvar: InstPtr,
-    exit: InstPtr,
-    delta: i32,
-    init_val: Operand,
-    init_bb: BBPtr,
-    exit_val: Operand,
-    exit_bb: BBPtr,
+pub struct Candidate {
+    pub lo: LoopPtr,
+    pub indvar: InstPtr,
+    pub exit: InstPtr,
+    pub delta: i32,
+    pub init_val: Operand,
+    pub init_bb: BBPtr,
+    pub exit_val: Operand,
+    pub exit_bb: BBPtr,
 }
 
 impl Candidate {
@@ -544,7 +567,7 @@ impl Candidate {
     /// Get induction variable from exit instruction.
     /// Exit instruction should shape like:
     /// `exit = br (indvar < N), loop, exit`
-    fn from_exit(exit: InstPtr, lo: LoopPtr, dom_tree: &mut DominatorTree) -> Option<Self> {
+    pub fn from_exit(exit: InstPtr, lo: LoopPtr, dom_tree: &mut DominatorTree) -> Option<Self> {
         let pre_header = lo.pre_header.unwrap();
         if exit.get_type() != InstType::Br {
             cprintln!(
@@ -562,17 +585,6 @@ impl Candidate {
             .iter()
             .find(|bb| !lo.is_in_loop(bb))?;
 
-        // Exit block should have only one pred
-        // TODO-PERF: this is for easy thread join implementation, but weakens optimization
-        if exit_bb.get_pred_bb().len() != 1 {
-            cprintln!(
-                "[INFO] loop {} fails because {} has multiple preds",
-                pre_header.name,
-                exit_bb.name
-            );
-            return None;
-        }
-
         // Condition should be `indvar < op`, get `indvar` from condition
         // TODO-PERF: use induction variable analysis to get `indvar` consistently
         let Operand::Instruction(cond) = exit.get_operand().first()? else {
@@ -623,7 +635,6 @@ impl Candidate {
         }
 
         // Indvar should be `phi [init_val, init_bb], [indvar + delta, next_bb]`
-        // `indvar` should be the only phi in its block (other phi can be non-trivial)
         // `init_bb` should be `pre_header`
         // `next_bb` should be in loop
         if indvar.get_type() != InstType::Phi {
@@ -670,6 +681,15 @@ impl Candidate {
             );
             return None;
         }
+        if next_val.get_operand()[0] != Operand::Instruction(*indvar) {
+            cprintln!(
+                "[INFO] loop {} fails because {} does not increase {}",
+                pre_header.name,
+                next_val.gen_llvm_ir(),
+                indvar.gen_llvm_ir()
+            );
+            return None;
+        }
         let Operand::Constant(Constant::Int(delta)) = next_val.get_operand()[1] else {
             cprintln!(
                 "[INFO] loop {} fails because {}'s second operand is not constant",
@@ -687,16 +707,6 @@ impl Candidate {
             );
             return None;
         }
-        for inst in indvar.get_parent_bb().unwrap().iter() {
-            if inst.get_type() == InstType::Phi && inst != *indvar {
-                cprintln!(
-                    "[INFO] loop {} fails because {} has multiple phi",
-                    pre_header.name,
-                    inst.gen_llvm_ir()
-                );
-                return None;
-            }
-        }
 
         // Construct induction variable
         Some(Self::new(
@@ -191,7 +191,7 @@ pub fn mem2reg(func: FunPtr, mem_pool: &mut Pin<Box<IRBuilder>>) -> Result<bool>
 
                     // Update only when store destination is a constant pointer
                     if let Operand::Instruction(variable) = store_ptr {
-                        if variable.get_type() == InstType::Alloca {
+                        if is_promotable(*variable) {
                             current_variable_value.insert(*variable, store_value.clone());
                             inst.remove_self();
                             changed = true;
@@ -204,7 +204,7 @@ pub fn mem2reg(func: FunPtr, mem_pool: &mut Pin<Box<IRBuilder>>) -> Result<bool>
 
                     // Replace only when load source is a constant pointer
                     if let Operand::Instruction(variable) = load_ptr {
-                        if variable.get_type() == InstType::Alloca {
+                        if is_promotable(*variable) {
                             let current_value =
                                 decide_variable_value(*variable, current_variable_value)?;
                             inst.replace_self(&current_value);
@@ -240,6 +240,19 @@ pub fn mem2reg(func: FunPtr, mem_pool: &mut Pin<Box<IRBuilder>>) -> Result<bool>
     )
 }
 
+/// Check if an "alloca" instruction can be promoted to registers,
+/// i.e. it's only loaded from or stored to, and its address does not escape
+fn is_promotable(variable: InstPtr) -> bool {
+    if variable.get_type() != InstType::Alloca {
+        return false;
+    }
+    variable.get_user().iter().all(|user| match user.get_type() {
+        InstType::Load => true,
+        InstType::Store => user.get_operand()[0] != Operand::Instruction(variable),
+        _ => false,
+    })
+}
+
 /// Insert empty "phi" for basic blocks starting from `entry`
 /// Returns a mapping from basic block to inserted "phi" instructions
 #[allow(unused)]
@@ -290,7 +303,7 @@ fn get_variable_to_phi_insertion(func: FunPtr) -> BTreeMap<InstPtr, BTreeSet<BBP
 
                 // Only insert "phi" when store destination is a constant pointer
                 if let Operand::Instruction(inst) = store_ptr {
-                    if inst.get_type() == InstType::Alloca {
+                    if is_promotable(*inst) {
                         store_positions.entry(*inst).or_default().insert(current_bb);
                     }
                 }
@@ -16,30 +16,45 @@
 
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 #[allow(unused)]
-use crate::{cprintln, utils::diff::diff};
+use crate::{
+    config::CONFIG,
+    cprintln,
+    utils::{diff::diff, pass_instrument},
+};
 
-use super::Program;
+use super::{analysis::verifier, Program};
 
 pub mod block_fuse;
 pub mod constant_fold;
 pub mod dead_code_elim;
+pub mod dead_global_elim;
 pub mod func_inline;
 pub mod inst_combine;
+pub mod ipccp;
 pub mod ldce;
 pub mod licm;
 pub mod load_elim;
 pub mod load_store_elim;
 pub mod loop_depth;
+pub mod loop_fission;
+pub mod loop_fusion;
+pub mod loop_interchange;
 pub mod loop_optimization;
 pub mod loop_simplify;
+pub mod loop_unroll;
+pub mod loop_unswitch;
 pub mod make_parallel;
 pub mod mem2reg;
+pub mod pass_manager;
 pub mod redundance_elim;
+pub mod sccp;
 pub mod sink_code;
 pub mod store_elim;
+pub mod strength_reduce;
+pub mod tail_recursion_elim;
 pub mod ultimate_pass;
 
 pub trait Transform {
@@ -51,18 +66,26 @@ pub trait Transform {
 
     #[allow(unused)]
     fn run_and_log(&mut self) -> Result<bool> {
-        let time_before = Instant::now();
         let program_before = self.get_program_mut().module.gen_llvm_ir();
+        pass_instrument::before_pass(&Self::name(), "module", "ll", || program_before.clone())?;
+        let time_before = Instant::now();
         let changed = self.run()?;
-        let elapsed = time_before.elapsed().as_micros();
+        let elapsed = time_before.elapsed();
         let program_after = self.get_program_mut().module.gen_llvm_ir();
         cprintln!(
             "## Pass {} {}\n\nTime elapsed = {} µs\n\nDiff:\n\n```diff\n{}```\n",
             Self::name(),
             if changed { "[CHANGED]" } else { "" },
-            elapsed,
+            elapsed.as_micros(),
             diff(&program_before, &program_after)
         );
+        pass_instrument::after_pass(&Self::name(), "module", "ll", changed, elapsed, || {
+            program_after
+        })?;
+        if CONFIG.verify_each_pass {
+            verifier::verify_program(self.get_program_mut())
+                .with_context(|| format!("IR is broken after pass {}", Self::name()))?;
+        }
         Ok(changed)
     }
 }
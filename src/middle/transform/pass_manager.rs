@@ -0,0 +1,328 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Context, Result};
+use lazy_static::lazy_static;
+
+use crate::{
+    context,
+    middle::{
+        analysis::{
+            analysis_manager::{Analysis, AnalysisManager},
+            loop_tools::LoopForest,
+            simple_gvn::SimpleGVN,
+        },
+        Program,
+    },
+    utils::pass_instrument,
+};
+
+use super::{
+    block_fuse::{self, BlockFuse},
+    constant_fold::{self, ConstantFold},
+    dead_code_elim::DeadCodeElim,
+    dead_global_elim::{self, DeadGlobalElim},
+    func_inline::{self, FuncInline},
+    inst_combine::{self, SymbolicEval},
+    ipccp::{self, IPCCP},
+    ldce::LDCE,
+    licm::LICM,
+    load_elim::{self, LoadElim},
+    loop_fission::{self, LoopFission},
+    loop_fusion::{self, LoopFusion},
+    loop_interchange::{self, LoopInterchange},
+    loop_simplify::LoopSimplifier,
+    loop_unroll::{self, LoopUnroll},
+    loop_unswitch::{self, LoopUnswitch},
+    make_parallel::{self, MakeParallel},
+    mem2reg::{self, Mem2Reg},
+    redundance_elim::RedundanceElim,
+    sccp::{self, SCCP},
+    sink_code::SinkCode,
+    store_elim::{self, StoreElim},
+    strength_reduce::{self, StrengthReduce},
+    tail_recursion_elim::{self, TailRecursionElim},
+    Transform,
+};
+
+type PassFn = fn(&mut Program, &mut AnalysisManager) -> Result<bool>;
+
+/// A pass that can be referred to by name in a pipeline string.
+pub struct PassInfo {
+    pub name: String,
+    run: PassFn,
+    /// Analyses still valid after the pass changes the program
+    preserved: &'static [Analysis],
+}
+
+lazy_static! {
+    /// All passes available to the pass manager.
+    /// Transforms are keyed by `Transform::name()`, loop passes by their module name.
+    static ref PASSES: Vec<PassInfo> = vec![
+        PassInfo {
+            name: Mem2Reg::name(),
+            run: |program, _| mem2reg::optimize_program(program),
+            preserved: &[Analysis::DominatorTree],
+        },
+        PassInfo {
+            name: TailRecursionElim::name(),
+            run: |program, _| tail_recursion_elim::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: FuncInline::name(),
+            run: |program, _| func_inline::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: SymbolicEval::name(),
+            run: |program, _| inst_combine::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: ConstantFold::name(),
+            run: |program, _| constant_fold::optimize_program(program),
+            preserved: &[Analysis::DominatorTree],
+        },
+        PassInfo {
+            name: SCCP::name(),
+            run: |program, _| sccp::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: IPCCP::name(),
+            run: |program, _| ipccp::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: DeadCodeElim::name(),
+            run: |program, analyses| {
+                let effect_analysis = analyses.effect_analysis(program);
+                DeadCodeElim::new(program, effect_analysis).run_and_log()
+            },
+            preserved: &[Analysis::DominatorTree],
+        },
+        PassInfo {
+            name: DeadGlobalElim::name(),
+            run: |program, _| dead_global_elim::optimize_program(program),
+            preserved: &[],
+        },
+        // Load and store elimination keep MemorySSA up to date by themselves
+        PassInfo {
+            name: LoadElim::name(),
+            run: |program, analyses| load_elim::optimize_program(program, analyses.memory_ssa(program)),
+            preserved: &[
+                Analysis::DominatorTree,
+                Analysis::EffectAnalysis,
+                Analysis::MemorySSA,
+            ],
+        },
+        PassInfo {
+            name: StoreElim::name(),
+            run: |program, analyses| store_elim::optimize_program(program, analyses.memory_ssa(program)),
+            preserved: &[
+                Analysis::DominatorTree,
+                Analysis::EffectAnalysis,
+                Analysis::MemorySSA,
+            ],
+        },
+        PassInfo {
+            name: RedundanceElim::name(),
+            run: |program, analyses| {
+                let mut dom_trees = std::mem::take(analyses.dom_trees());
+                let mut gvn = SimpleGVN::new(analyses.memory_ssa(program));
+                let changed = RedundanceElim::new(program, &mut gvn, &mut dom_trees).run_and_log();
+                *analyses.dom_trees() = dom_trees;
+                changed
+            },
+            preserved: &[Analysis::DominatorTree],
+        },
+        PassInfo {
+            name: BlockFuse::name(),
+            run: |program, _| block_fuse::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: SinkCode::name(),
+            run: |program, analyses| {
+                let mut dom_trees = std::mem::take(analyses.dom_trees());
+                let effect_analysis = analyses.effect_analysis(program);
+                let changed = SinkCode::new(program, effect_analysis, &mut dom_trees).run_and_log();
+                *analyses.dom_trees() = dom_trees;
+                changed
+            },
+            preserved: &[Analysis::DominatorTree],
+        },
+        PassInfo {
+            name: MakeParallel::<5>::name(),
+            run: |program, _| make_parallel::optimize_program::<5>(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: LoopUnroll::name(),
+            run: |program, _| loop_unroll::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: LoopUnswitch::name(),
+            run: |program, _| loop_unswitch::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: LoopInterchange::name(),
+            run: |program, _| loop_interchange::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: LoopFusion::name(),
+            run: |program, _| loop_fusion::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: LoopFission::name(),
+            run: |program, _| loop_fission::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: StrengthReduce::name(),
+            run: |program, _| strength_reduce::optimize_program(program),
+            preserved: &[],
+        },
+        PassInfo {
+            name: "loop_simplify".to_string(),
+            run: |program, _| run_loop_pass("loop_simplify", program, |_, _| Ok(())),
+            preserved: &[],
+        },
+        PassInfo {
+            name: "licm".to_string(),
+            run: |program, analyses| {
+                let memory_ssa = analyses.memory_ssa(program);
+                run_loop_pass("licm", program, |program, forest| {
+                    LICM::new(&mut program.mem_pool, memory_ssa).run(forest)
+                })
+            },
+            preserved: &[],
+        },
+        PassInfo {
+            name: "ldce".to_string(),
+            run: |program, analyses| {
+                let effect_analysis = analyses.effect_analysis(program);
+                run_loop_pass("ldce", program, |program, forest| {
+                    LDCE::new(&mut program.mem_pool, effect_analysis).run(forest)
+                })
+            },
+            preserved: &[],
+        },
+    ];
+}
+
+/// Run a loop pass on every function with loops.
+/// Loops are put in simplified form first, as loop passes rely on pre-headers.
+/// Loop passes don't report changes, so they are always considered changed.
+fn run_loop_pass<F>(name: &str, program: &mut Program, mut pass: F) -> Result<bool>
+where
+    F: FnMut(&mut Program, &mut LoopForest) -> Result<()>,
+{
+    let ir = |program: &Program| program.module.gen_llvm_ir();
+    pass_instrument::run_pass(name, "module", "ll", program, ir, |program| {
+        for func in program.module.functions.clone() {
+            if func.is_lib() {
+                continue;
+            }
+            let Some(mut forest) = LoopForest::make_forest(func) else {
+                continue;
+            };
+            LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+            pass(program, &mut forest)?;
+        }
+        Ok(())
+    })?;
+    Ok(true)
+}
+
+/// Find pass by name.
+pub fn get_pass(name: &str) -> Option<&'static PassInfo> {
+    PASSES.iter().find(|pass| pass.name == name)
+}
+
+/// Get names of all available passes.
+pub fn pass_names() -> Vec<String> {
+    PASSES.iter().map(|pass| pass.name.clone()).collect()
+}
+
+/// Parse a comma-separated pipeline string like `mem2reg,func_inline,licm`.
+/// Errors if any pass does not exist, so that nothing runs on a mistyped pipeline.
+pub fn parse_pipeline(pipeline: &str) -> Result<Vec<&'static PassInfo>> {
+    pipeline
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            get_pass(name)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown pass `{}`, available passes: {}",
+                        name,
+                        pass_names().join(", ")
+                    )
+                })
+                .with_context(|| context!())
+        })
+        .collect()
+}
+
+/// Run passes on program, sharing cached analyses between them.
+pub struct PassManager<'a> {
+    program: &'a mut Program,
+    analyses: AnalysisManager,
+}
+
+impl<'a> PassManager<'a> {
+    pub fn new(program: &'a mut Program) -> Self {
+        Self {
+            program,
+            analyses: AnalysisManager::new(),
+        }
+    }
+
+    /// Get cached analyses, mainly for inspection.
+    pub fn analyses(&self) -> &AnalysisManager {
+        &self.analyses
+    }
+
+    /// Run a single pass, and invalidate analyses it doesn't preserve if program is changed.
+    pub fn run_pass(&mut self, pass: &PassInfo) -> Result<bool> {
+        let changed = (pass.run)(self.program, &mut self.analyses)
+            .with_context(|| format!("failed to run pass {}", pass.name))?;
+        if changed {
+            self.analyses.invalidate(pass.preserved);
+        }
+        Ok(changed)
+    }
+
+    /// Run all passes in pipeline string in order, returns if any pass changed program.
+    pub fn run_pipeline(&mut self, pipeline: &str) -> Result<bool> {
+        let mut changed = false;
+        for pass in parse_pipeline(pipeline)? {
+            changed |= self.run_pass(pass)?;
+        }
+        Ok(changed)
+    }
+}
+
+pub fn optimize_program(program: &mut Program, pipeline: &str) -> Result<bool> {
+    PassManager::new(program).run_pipeline(pipeline)
+}
@@ -25,7 +25,7 @@ use crate::middle::{
         memory_ssa::MemorySSA,
         simple_gvn::{Expr, SimpleGVN},
     },
-    ir::{InstPtr, Operand, ValueType},
+    ir::{FunPtr, InstPtr, Operand, ValueType},
     Program,
 };
 
@@ -35,12 +35,14 @@ pub fn optimize_program(program: &mut Program) -> Result<bool> {
     let effect_analysis = EffectAnalysis::new(program);
     let memory_ssa = MemorySSA::new(program, &effect_analysis);
     let mut gvn = SimpleGVN::new(&memory_ssa);
-    RedundanceElim::new(program, &mut gvn).run_and_log()
+    let mut dom_trees = HashMap::new();
+    RedundanceElim::new(program, &mut gvn, &mut dom_trees).run_and_log()
 }
 
 pub struct RedundanceElim<'a> {
     program: &'a mut Program,
     gvn: &'a mut SimpleGVN<'a>,
+    dom_trees: &'a mut HashMap<FunPtr, DominatorTree>,
 }
 
 impl<'a> Transform for RedundanceElim<'a> {
@@ -59,7 +61,10 @@ impl<'a> Transform for RedundanceElim<'a> {
             if func.is_lib() {
                 continue;
             }
-            let mut dom_tree = DominatorTree::new(func);
+            let dom_tree = self
+                .dom_trees
+                .entry(func)
+                .or_insert_with(|| DominatorTree::new(func));
 
             // Implementation of Expr::Hash does not use it's mutable content,
             // so it's false positive according to:
@@ -101,7 +106,15 @@ impl<'a> Transform for RedundanceElim<'a> {
 }
 
 impl<'a> RedundanceElim<'a> {
-    pub fn new(program: &'a mut Program, gvn: &'a mut SimpleGVN<'a>) -> Self {
-        Self { program, gvn }
+    pub fn new(
+        program: &'a mut Program,
+        gvn: &'a mut SimpleGVN<'a>,
+        dom_trees: &'a mut HashMap<FunPtr, DominatorTree>,
+    ) -> Self {
+        Self {
+            program,
+            gvn,
+            dom_trees,
+        }
     }
 }
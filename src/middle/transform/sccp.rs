@@ -0,0 +1,326 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    backend::from_self::downcast_ref,
+    context,
+    middle::{
+        analysis::reachability::Reachability,
+        ir::{
+            instruction::{misc_inst::Phi, terminator_inst::Switch, InstType},
+            BBPtr, Constant, FunPtr, InstPtr, Operand,
+        },
+        Program,
+    },
+};
+
+use super::{constant_fold::fold_constant, Transform};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    SCCP::new(program).run_and_log()
+}
+
+/// Sparse conditional constant propagation.
+/// Propagates constants through SSA edges and phi, only along CFG edges that may execute.
+#[allow(clippy::upper_case_acronyms)]
+pub struct SCCP<'a> {
+    program: &'a mut Program,
+}
+
+impl<'a> Transform for SCCP<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "sccp".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        let mut changed = false;
+        for func in self.program.module.functions.clone() {
+            if func.is_lib() {
+                continue;
+            }
+            changed |= self.process_func(func)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Lattice value of an SSA value.
+#[derive(Clone, Debug, PartialEq)]
+enum Lattice {
+    /// Not yet known, may be any constant
+    Undef,
+    /// Always the given constant
+    Const(Constant),
+    /// Not a constant
+    Overdefined,
+}
+
+impl Lattice {
+    fn meet(self, other: Lattice) -> Lattice {
+        match (self, other) {
+            (Lattice::Undef, x) | (x, Lattice::Undef) => x,
+            (Lattice::Const(a), Lattice::Const(b)) if a == b => Lattice::Const(a),
+            _ => Lattice::Overdefined,
+        }
+    }
+}
+
+/// Solver state of a function.
+#[derive(Default)]
+struct Solver {
+    lattice: HashMap<InstPtr, Lattice>,
+    executable_edges: HashSet<(BBPtr, BBPtr)>,
+    executable_blocks: HashSet<BBPtr>,
+    cfg_worklist: Vec<(Option<BBPtr>, BBPtr)>,
+    ssa_worklist: Vec<InstPtr>,
+}
+
+impl Solver {
+    /// Compute lattice values and executable blocks of function.
+    fn solve(&mut self, entry: BBPtr) {
+        self.cfg_worklist.push((None, entry));
+        while !self.cfg_worklist.is_empty() || !self.ssa_worklist.is_empty() {
+            while let Some((from, to)) = self.cfg_worklist.pop() {
+                if let Some(from) = from {
+                    if !self.executable_edges.insert((from, to)) {
+                        continue;
+                    }
+                }
+
+                // Visit whole block for the first time, otherwise only phi may change
+                if self.executable_blocks.insert(to) {
+                    for inst in to.iter() {
+                        self.visit(inst);
+                    }
+                } else {
+                    for inst in to.iter() {
+                        if inst.get_type() != InstType::Phi {
+                            break;
+                        }
+                        self.visit(inst);
+                    }
+                }
+            }
+            while let Some(inst) = self.ssa_worklist.pop() {
+                let executable = inst
+                    .get_parent_bb()
+                    .is_some_and(|bb| self.executable_blocks.contains(&bb));
+                if executable {
+                    self.visit(inst);
+                }
+            }
+        }
+    }
+
+    /// Update lattice value of instruction, mark successors executable for `br` and `switch`.
+    fn visit(&mut self, inst: InstPtr) {
+        let bb = inst.get_parent_bb().unwrap();
+        if inst.get_type() == InstType::Br {
+            let succ = bb.get_succ_bb();
+            match inst.get_operand().first().map(|cond| self.get_value(cond)) {
+                None => self.cfg_worklist.push((Some(bb), succ[0])),
+                Some(Lattice::Const(Constant::Bool(cond))) => {
+                    let target = if cond { succ[0] } else { succ[1] };
+                    self.cfg_worklist.push((Some(bb), target));
+                }
+                Some(Lattice::Undef) => (),
+                Some(_) => {
+                    for target in succ {
+                        self.cfg_worklist.push((Some(bb), *target));
+                    }
+                }
+            }
+            return;
+        }
+        if inst.get_type() == InstType::Switch {
+            let succ = bb.get_succ_bb();
+            match self.get_value(&inst.get_operand()[0]) {
+                Lattice::Const(Constant::Int(cond)) => {
+                    let target = succ[switch_target(inst, cond)];
+                    self.cfg_worklist.push((Some(bb), target));
+                }
+                Lattice::Undef => (),
+                _ => {
+                    for target in succ {
+                        self.cfg_worklist.push((Some(bb), *target));
+                    }
+                }
+            }
+            return;
+        }
+
+        // Lower lattice value, and notify users on change
+        let value = self.evaluate(inst, bb);
+        let old = self.lattice.get(&inst).cloned().unwrap_or(Lattice::Undef);
+        let new = old.clone().meet(value);
+        if new != old {
+            self.lattice.insert(inst, new);
+            self.ssa_worklist.extend(inst.get_user().iter().cloned());
+        }
+    }
+
+    /// Evaluate lattice value of instruction with current knowledge.
+    fn evaluate(&self, inst: InstPtr, bb: BBPtr) -> Lattice {
+        if inst.get_type() == InstType::Phi {
+            let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+            return phi
+                .get_incoming_values()
+                .iter()
+                .filter(|(_, pred)| self.executable_edges.contains(&(*pred, bb)))
+                .fold(Lattice::Undef, |acc, (op, _)| acc.meet(self.get_value(op)));
+        }
+        let mut operands = Vec::new();
+        for op in inst.get_operand() {
+            match self.get_value(op) {
+                Lattice::Const(c) => operands.push(c),
+                other => return other,
+            }
+        }
+        match fold_constant(inst, operands) {
+            Some(c) => Lattice::Const(c),
+            None => Lattice::Overdefined,
+        }
+    }
+
+    fn get_value(&self, op: &Operand) -> Lattice {
+        match op {
+            Operand::Constant(c) => Lattice::Const(c.clone()),
+            Operand::Instruction(inst) => self.lattice.get(inst).cloned().unwrap_or(Lattice::Undef),
+            _ => Lattice::Overdefined,
+        }
+    }
+}
+
+/// Index of the successor `switch` jumps to when its condition is `value`.
+fn switch_target(switch: InstPtr, value: i32) -> usize {
+    downcast_ref::<Switch>(switch.as_ref().as_ref())
+        .get_cases()
+        .iter()
+        .position(|case| *case == value)
+        .map_or(0, |i| i + 1)
+}
+
+impl<'a> SCCP<'a> {
+    pub fn new(program: &'a mut Program) -> Self {
+        Self { program }
+    }
+
+    fn process_func(&mut self, func: FunPtr) -> Result<bool> {
+        let entry = func
+            .entry
+            .ok_or_else(|| anyhow!("function `{}` has no entry", func.name))
+            .with_context(|| context!())?;
+        let mut solver = Solver::default();
+        solver.solve(entry);
+        let mut changed = false;
+
+        // Make branch with constant condition unconditional
+        let blocks = func.dfs_iter().collect::<Vec<_>>();
+        for mut bb in blocks.iter().cloned() {
+            let mut br = bb.get_last_inst();
+            if br.get_type() != InstType::Br
+                || br.get_operand().is_empty()
+                || bb.get_succ_bb()[0] == bb.get_succ_bb()[1]
+            {
+                continue;
+            }
+            let Lattice::Const(Constant::Bool(cond)) = solver.get_value(&br.get_operand()[0])
+            else {
+                continue;
+            };
+            if cond {
+                bb.remove_false_bb();
+            } else {
+                bb.remove_true_bb();
+            }
+            let new_br = self.program.mem_pool.get_br(None);
+            br.insert_after(new_br);
+            br.remove_self();
+            changed = true;
+        }
+
+        // Make switch with constant condition unconditional
+        for mut bb in blocks.iter().cloned() {
+            let mut switch = bb.get_last_inst();
+            if switch.get_type() != InstType::Switch {
+                continue;
+            }
+            let Lattice::Const(Constant::Int(cond)) = solver.get_value(&switch.get_operand()[0])
+            else {
+                continue;
+            };
+            let target = bb.get_succ_bb()[switch_target(switch, cond)];
+
+            // Edges to the same block can not be told apart by phi, keep them as is
+            if bb
+                .get_succ_bb()
+                .iter()
+                .filter(|succ| **succ == target)
+                .count()
+                > 1
+            {
+                continue;
+            }
+            while let Some(other) = bb
+                .get_succ_bb()
+                .iter()
+                .find(|succ| **succ != target)
+                .cloned()
+            {
+                bb.remove_succ_bb(other);
+            }
+            let new_br = self.program.mem_pool.get_br(None);
+            switch.insert_after(new_br);
+            switch.remove_self();
+            changed = true;
+        }
+
+        // Remove blocks no longer reachable, which are also never executed
+        let reachability = Reachability::new(func);
+        let dead_blocks = blocks
+            .into_iter()
+            .filter(|bb| !reachability.is_reachable(*bb))
+            .collect::<Vec<_>>();
+        for mut bb in dead_blocks.iter().cloned() {
+            bb.remove_self();
+        }
+        for bb in dead_blocks.iter() {
+            for mut inst in bb.iter() {
+                inst.remove_self();
+            }
+            changed = true;
+        }
+
+        // Replace instructions with constant value
+        for bb in func.dfs_iter() {
+            for mut inst in bb.iter() {
+                if let Some(Lattice::Const(c)) = solver.lattice.get(&inst) {
+                    inst.replace_self(&c.clone().into());
+                    changed = true;
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
@@ -24,7 +24,7 @@ use crate::middle::analysis::dominator_tree::DominatorTree;
 use crate::middle::analysis::effect_analysis::EffectAnalysis;
 use crate::middle::ir::instruction::misc_inst::Phi;
 use crate::middle::ir::instruction::{downcast_mut, InstType};
-use crate::middle::ir::{BBPtr, InstPtr, Operand};
+use crate::middle::ir::{BBPtr, FunPtr, InstPtr, Operand};
 use crate::middle::Program;
 
 use super::Transform;
@@ -32,13 +32,15 @@ use super::Transform;
 #[allow(unused)]
 pub fn optimize_program(program: &mut Program) -> Result<bool> {
     let effect_analysis = EffectAnalysis::new(program);
-    SinkCode::new(program, &effect_analysis).run_and_log()
+    let mut dom_trees = HashMap::new();
+    SinkCode::new(program, &effect_analysis, &mut dom_trees).run_and_log()
 }
 
 #[allow(unused)]
 pub struct SinkCode<'a> {
     program: &'a mut Program,
     effect_analysis: &'a EffectAnalysis,
+    dom_trees: &'a mut HashMap<FunPtr, DominatorTree>,
 }
 
 #[allow(unused)]
@@ -57,12 +59,16 @@ impl<'a> Transform for SinkCode<'a> {
             if func.is_lib() {
                 continue;
             }
-            let mut dom_tree = DominatorTree::new(func);
+            let mut dom_tree = self
+                .dom_trees
+                .remove(&func)
+                .unwrap_or_else(|| DominatorTree::new(func));
             for bb in func.po_iter() {
                 for inst in bb.iter_rev() {
                     changed |= self.sink_inst(inst, &mut dom_tree)?;
                 }
             }
+            self.dom_trees.insert(func, dom_tree);
         }
         Ok(true)
     }
@@ -70,10 +76,15 @@ impl<'a> Transform for SinkCode<'a> {
 
 #[allow(unused)]
 impl<'a> SinkCode<'a> {
-    pub fn new(program: &'a mut Program, effect_analysis: &'a EffectAnalysis) -> Self {
+    pub fn new(
+        program: &'a mut Program,
+        effect_analysis: &'a EffectAnalysis,
+        dom_trees: &'a mut HashMap<FunPtr, DominatorTree>,
+    ) -> Self {
         Self {
             program,
             effect_analysis,
+            dom_trees,
         }
     }
 
@@ -215,7 +226,12 @@ impl<'a> SinkCode<'a> {
     fn is_fixed(&mut self, inst: InstPtr) -> bool {
         matches!(
             inst.get_type(),
-            InstType::Load | InstType::Store | InstType::Ret | InstType::Br | InstType::Phi
+            InstType::Load
+                | InstType::Store
+                | InstType::Ret
+                | InstType::Br
+                | InstType::Switch
+                | InstType::Phi
         ) || self.effect_analysis.has_effect(inst)
     }
 }
@@ -26,17 +26,17 @@ use super::Transform;
 
 pub fn optimize_program<'a>(
     program: &'a mut Program,
-    memory_ssa: &'a mut MemorySSA<'a>,
+    memory_ssa: &'a mut MemorySSA,
 ) -> Result<bool> {
     StoreElim::new(program, memory_ssa).run_and_log()
 }
 
-pub struct StoreElim<'a> {
+pub struct StoreElim<'a, 'b> {
     program: &'a mut Program,
-    memory_ssa: &'a mut MemorySSA<'a>,
+    memory_ssa: &'a mut MemorySSA<'b>,
 }
 
-impl<'a> Transform for StoreElim<'a> {
+impl<'a, 'b> Transform for StoreElim<'a, 'b> {
     fn get_program_mut(&mut self) -> &mut Program {
         self.program
     }
@@ -60,8 +60,8 @@ impl<'a> Transform for StoreElim<'a> {
     }
 }
 
-impl<'a> StoreElim<'a> {
-    pub fn new(program: &'a mut Program, memory_ssa: &'a mut MemorySSA<'a>) -> Self {
+impl<'a, 'b> StoreElim<'a, 'b> {
+    pub fn new(program: &'a mut Program, memory_ssa: &'a mut MemorySSA<'b>) -> Self {
         Self {
             program,
             memory_ssa,
@@ -104,7 +104,7 @@ impl<'a> StoreElim<'a> {
     fn can_delete_inst(&self, inst: InstPtr) -> bool {
         let no_io = !self.memory_ssa.effect_analysis.has_io(inst);
         let no_user = inst.get_user().is_empty();
-        let no_control = !matches!(inst.get_type(), InstType::Br | InstType::Ret);
+        let no_control = !matches!(inst.get_type(), InstType::Br | InstType::Switch | InstType::Ret);
         no_io && no_user && no_control
     }
 
@@ -0,0 +1,239 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+
+use crate::{
+    cprintln,
+    middle::{
+        analysis::{
+            loop_tools::{LoopForest, LoopPtr},
+            scalar_evolution::{Affine, ScalarEvolution, Scev},
+        },
+        ir::{
+            instruction::{downcast_mut, misc_inst::Phi, InstType},
+            BBPtr, Constant, InstPtr, Operand, ValueType,
+        },
+        Program,
+    },
+};
+
+use super::{loop_optimization::loop_forest_post_order, loop_simplify, Transform};
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let mut changed = false;
+    for func in program.module.functions.clone() {
+        if func.is_lib() {
+            continue;
+        }
+        let Some(mut forest) = LoopForest::make_forest(func) else {
+            continue;
+        };
+        loop_simplify::LoopSimplifier::new(&mut program.mem_pool).run(&mut forest)?;
+        changed |= StrengthReduce::new(program, &mut forest).run_and_log()?;
+    }
+    Ok(changed)
+}
+
+/// Induction variable strength reduction.
+/// Rewrites multiplied induction variables like `i * k + base` into phis increasing by `k`,
+/// and replaces uses of induction variables after loop with their exit values.
+/// Exit values are only computed for loops with constant trip count.
+pub struct StrengthReduce<'a> {
+    program: &'a mut Program,
+    loop_forest: &'a mut LoopForest,
+}
+
+impl<'a> Transform for StrengthReduce<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "strength_reduce".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        let mut loops = Vec::new();
+        loop_forest_post_order(self.loop_forest, |lo| {
+            loops.push(lo);
+            Ok(())
+        })?;
+        let mut changed = false;
+        for lo in loops {
+            changed |= self.reduce_indvars(lo);
+            changed |= self.replace_exit_values(lo);
+        }
+        Ok(changed)
+    }
+}
+
+impl<'a> StrengthReduce<'a> {
+    pub fn new(program: &'a mut Program, loop_forest: &'a mut LoopForest) -> Self {
+        Self {
+            program,
+            loop_forest,
+        }
+    }
+
+    /// Replace multiplied induction variables with new phis.
+    fn reduce_indvars(&mut self, lo: LoopPtr) -> bool {
+        let Some(pre_header) = lo.pre_header else {
+            return false;
+        };
+        let mut header = lo.head;
+        let [a, b] = header.get_pred_bb()[..] else {
+            return false;
+        };
+        let latch = if a == pre_header { b } else { a };
+
+        // Collect multiplications and their enclosing `+ base` with add recurrence
+        let mut scev = ScalarEvolution::new();
+        let mut targets: Vec<(InstPtr, Affine, Affine)> = Vec::new();
+        let mut blocks = lo.blocks.iter().cloned().collect::<Vec<_>>();
+        blocks.sort();
+        for bb in blocks {
+            for inst in bb.iter() {
+                if !matches!(inst.get_type(), InstType::Mul | InstType::Shl) {
+                    continue;
+                }
+                let mut target = inst;
+                while let [user] = target.get_user() {
+                    let derived = matches!(user.get_type(), InstType::Add | InstType::Sub)
+                        && matches!(
+                            scev.get_scev(lo, &(*user).into()),
+                            Some(Scev::AddRec { .. })
+                        );
+                    if !derived || !is_in_loop(lo, *user) {
+                        break;
+                    }
+                    target = *user;
+                }
+                let Some(Scev::AddRec { start, step }) = scev.get_scev(lo, &target.into()) else {
+                    continue;
+                };
+
+                // Value after loop is not the value of phi, leave it
+                if step.get_constant() == Some(0)
+                    || target.get_user().iter().any(|user| !is_in_loop(lo, *user))
+                    || targets.iter().any(|(t, _, _)| *t == target)
+                {
+                    continue;
+                }
+                targets.push((target, start, step));
+            }
+        }
+
+        // Create phi = [start, pre_header], [phi + step, latch] for each target
+        let changed = !targets.is_empty();
+        for (mut target, start, step) in targets {
+            let start = self.materialize(&start, pre_header);
+            let step = self.materialize(&step, pre_header);
+            let mut phi = self.program.mem_pool.get_phi(ValueType::Int, vec![]);
+            header.push_front(phi);
+            let next = self.program.mem_pool.get_add(phi.into(), step);
+            latch.get_last_inst().insert_before(next);
+            let inner = downcast_mut::<Phi>(phi.as_mut().as_mut());
+            for pred in header.get_pred_bb().clone() {
+                if pred == pre_header {
+                    inner.add_incoming_value(start.clone(), pred);
+                } else {
+                    inner.add_incoming_value(next.into(), pred);
+                }
+            }
+            target.replace_self(&phi.into());
+        }
+        changed
+    }
+
+    /// Replace uses of header values after loop with their values when loop exits.
+    /// Only loops with constant trip count are handled. Loops like `i < n` are skipped,
+    /// because the exit value of `{0,+,3}` is `3 * max(n, 0)`, which is not affine.
+    fn replace_exit_values(&mut self, lo: LoopPtr) -> bool {
+        let Some(pre_header) = lo.pre_header else {
+            return false;
+        };
+        let mut scev = ScalarEvolution::new();
+        if scev.get_trip_count(lo).is_none() {
+            cprintln!(
+                "[INFO] exit values of loop {} are kept because trip count is not constant",
+                lo.head.name
+            );
+            return false;
+        }
+        let mut changed = false;
+        for inst in lo.head.iter() {
+            let op: Operand = inst.into();
+            let outside = inst
+                .get_user()
+                .iter()
+                .filter(|user| !is_in_loop(lo, **user))
+                .cloned()
+                .collect::<Vec<_>>();
+            if outside.is_empty() || !matches!(scev.get_scev(lo, &op), Some(Scev::AddRec { .. })) {
+                continue;
+            }
+            let Some(value) = scev.get_final_value(lo, &op) else {
+                continue;
+            };
+
+            // Exit value only depends on values before loop, compute it in pre_header
+            let value = self.materialize(&value, pre_header);
+            for mut user in outside {
+                user.replace_operand(&op, &value);
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Compute affine expression at the end of given block.
+    fn materialize(&mut self, affine: &Affine, bb: BBPtr) -> Operand {
+        let mut terminator = bb.get_last_inst();
+        let mem_pool = &mut self.program.mem_pool;
+        let mut result: Option<Operand> = None;
+        for (op, coef) in affine.terms.iter() {
+            let mut term = op.clone();
+            if *coef != 1 {
+                let mul = mem_pool.get_mul(term, Constant::Int(*coef as i32).into());
+                terminator.insert_before(mul);
+                term = mul.into();
+            }
+            result = Some(match result {
+                Some(acc) => {
+                    let add = mem_pool.get_add(acc, term);
+                    terminator.insert_before(add);
+                    add.into()
+                }
+                None => term,
+            });
+        }
+        let constant: Operand = Constant::Int(affine.constant as i32).into();
+        match result {
+            Some(acc) if affine.constant != 0 => {
+                let add = mem_pool.get_add(acc, constant);
+                terminator.insert_before(add);
+                add.into()
+            }
+            Some(acc) => acc,
+            None => constant,
+        }
+    }
+}
+
+fn is_in_loop(lo: LoopPtr, inst: InstPtr) -> bool {
+    inst.get_parent_bb().is_some_and(|bb| lo.is_in_loop(&bb))
+}
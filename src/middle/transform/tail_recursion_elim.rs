@@ -0,0 +1,358 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    backend::from_self::downcast_ref,
+    context, cprintln,
+    middle::{
+        analysis::call_graph::CallGraph,
+        ir::{
+            instruction::{downcast_mut, misc_inst::Phi, InstType},
+            BBPtr, Constant, FunPtr, InstPtr, Operand, ValueType,
+        },
+        Program,
+    },
+};
+
+use super::Transform;
+
+pub fn optimize_program(program: &mut Program) -> Result<bool> {
+    let call_graph = CallGraph::new(program);
+    TailRecursionElim::new(program, &call_graph).run_and_log()
+}
+
+pub struct TailRecursionElim<'a> {
+    program: &'a mut Program,
+    call_graph: &'a CallGraph,
+}
+
+impl<'a> Transform for TailRecursionElim<'a> {
+    fn get_program_mut(&mut self) -> &mut Program {
+        self.program
+    }
+
+    fn name() -> String {
+        "tail_recursion_elim".to_string()
+    }
+
+    fn run(&mut self) -> Result<bool> {
+        let mut changed = false;
+        for func in self.program.module.functions.clone() {
+            if func.is_lib() {
+                continue;
+            }
+            changed |= self.process_func(func)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// A self call whose result is returned directly, or through an accumulator:
+///
+/// ```llvm
+/// bb:
+///   %call = call i32 @f(...)
+///   %acc = add i32 %x, %call ; only in accumulator-style tail call
+///   br label %exit
+/// exit:
+///   %ret = phi i32 [%acc, %bb], ...
+///   ret i32 %ret
+/// ```
+struct TailCall {
+    call: InstPtr,
+    /// Instruction accumulating call result with another operand
+    acc: Option<InstPtr>,
+    bb: BBPtr,
+}
+
+impl<'a> TailRecursionElim<'a> {
+    pub fn new(program: &'a mut Program, call_graph: &'a CallGraph) -> Self {
+        Self {
+            program,
+            call_graph,
+        }
+    }
+
+    /// Replace tail calls to function itself with a loop.
+    ///
+    /// ```llvm
+    /// new_entry:
+    ///   ; alloca moved from entry
+    ///   br label %entry
+    /// entry:
+    ///   %p = phi [%param, %new_entry], [%arg, %bb]
+    ///   %acc = phi [identity, %new_entry], [%acc_next, %bb]
+    ///   ...
+    /// bb:
+    ///   %acc_next = add %acc, %x
+    ///   br label %entry
+    /// exit:
+    ///   %res = add %acc, %ret
+    ///   ret %res
+    /// ```
+    fn process_func(&mut self, mut func: FunPtr) -> Result<bool> {
+        let tail_calls = self
+            .call_graph
+            .get_calls(func)
+            .into_iter()
+            .filter(|edge| edge.callee == func)
+            .filter_map(|edge| get_tail_call(func, edge.inst))
+            .collect::<Vec<_>>();
+        if tail_calls.is_empty() {
+            return Ok(false);
+        }
+
+        // Accumulator-style tail calls should accumulate with the same operation
+        let acc_types = tail_calls
+            .iter()
+            .filter_map(|tail_call| tail_call.acc.map(|inst| inst.get_type()))
+            .collect::<HashSet<_>>();
+        if acc_types.len() > 1 {
+            cprintln!("[INFO] {} has different accumulator", func.name);
+            return Ok(false);
+        }
+        let acc_type = acc_types.into_iter().next();
+
+        // Function should still return somewhere other than tail calls
+        let mut exit = func
+            .exit
+            .ok_or_else(|| anyhow!("function `{}` has no exit", func.name))
+            .with_context(|| context!())?;
+        let tail_bbs = tail_calls
+            .iter()
+            .map(|tail_call| tail_call.bb)
+            .collect::<HashSet<_>>();
+        if exit.get_pred_bb().iter().all(|bb| tail_bbs.contains(bb)) {
+            return Ok(false);
+        }
+
+        // Create new entry, move alloca to it so that they are not in loop
+        let mut entry = func
+            .entry
+            .ok_or_else(|| anyhow!("function `{}` has no entry", func.name))
+            .with_context(|| context!())?;
+        let name = unique_name(func, &entry.name);
+        let mut new_entry = self.program.mem_pool.new_basicblock(name);
+        for inst in entry.iter() {
+            if inst.get_type() == InstType::Alloca {
+                new_entry.push_back(inst);
+            }
+        }
+        new_entry.push_back(self.program.mem_pool.get_br(None));
+        new_entry.set_true_bb(entry);
+        func.entry = Some(new_entry);
+
+        // Create phi for parameters in old entry, in the order of parameters
+        let mut param_phis = Vec::new();
+        for param in func.params.iter().rev() {
+            let phi = self
+                .program
+                .mem_pool
+                .get_phi(param.value_type.clone(), vec![]);
+            entry.push_front(phi);
+            let users = param.get_user().to_vec();
+            for user in users {
+                replace_use(user, &Operand::Parameter(*param), &phi.into());
+            }
+            let mut phi_inst = phi;
+            let phi_inst = downcast_mut::<Phi>(phi_inst.as_mut().as_mut());
+            phi_inst.add_incoming_value(Operand::Parameter(*param), new_entry);
+            param_phis.push(phi);
+        }
+        param_phis.reverse();
+
+        // Create phi for accumulator
+        let acc_phi = acc_type.map(|ty| {
+            let identity = if ty == InstType::Add { 0 } else { 1 };
+            let phi = self.program.mem_pool.get_phi(
+                ValueType::Int,
+                vec![(Constant::Int(identity).into(), new_entry)],
+            );
+            entry.push_front(phi);
+            phi
+        });
+
+        // Replace each tail call with a jump to old entry
+        for tail_call in tail_calls {
+            let mut bb = tail_call.bb;
+            let mut call = tail_call.call;
+            let args = call.get_operand().to_vec();
+            let acc_next = match (acc_phi, tail_call.acc) {
+                (Some(acc_phi), Some(mut acc)) => {
+                    // Parameters in `x` are already replaced with phi
+                    let x = get_accumulated(acc, call);
+                    let acc_next = self.accumulate(acc_type.unwrap(), acc_phi.into(), x);
+                    call.insert_before(acc_next);
+                    acc.remove_self();
+                    Some(acc_next.into())
+                }
+                (Some(acc_phi), None) => Some(acc_phi.into()),
+                _ => None,
+            };
+            call.remove_self();
+
+            // Jump to old entry instead of exit
+            bb.replace_succ_bb_only(exit, entry);
+            exit.remove_pred_bb(bb);
+            for (mut phi, arg) in param_phis.iter().cloned().zip(args) {
+                let phi = downcast_mut::<Phi>(phi.as_mut().as_mut());
+                phi.add_incoming_value(arg, bb);
+            }
+            if let (Some(mut acc_phi), Some(acc_next)) = (acc_phi, acc_next) {
+                let acc_phi = downcast_mut::<Phi>(acc_phi.as_mut().as_mut());
+                acc_phi.add_incoming_value(acc_next, bb);
+            }
+        }
+
+        // Accumulate value returned elsewhere
+        if let Some(acc_phi) = acc_phi {
+            let mut ret = exit.get_last_inst();
+            let ret_val = ret.get_operand()[0].clone();
+            let res = self.accumulate(acc_type.unwrap(), acc_phi.into(), ret_val);
+            ret.insert_before(res);
+            ret.set_operand(0, res.into());
+        }
+        Ok(true)
+    }
+
+    /// Create instruction accumulating `x` to `acc`.
+    fn accumulate(&mut self, ty: InstType, acc: Operand, x: Operand) -> InstPtr {
+        if ty == InstType::Add {
+            self.program.mem_pool.get_add(acc, x)
+        } else {
+            self.program.mem_pool.get_mul(acc, x)
+        }
+    }
+}
+
+/// Check if given self call is a tail call.
+fn get_tail_call(func: FunPtr, call: InstPtr) -> Option<TailCall> {
+    let bb = call.get_parent_bb()?;
+    let exit = func.exit?;
+    if bb == exit || bb.get_succ_bb().as_slice() != [exit] {
+        return None;
+    }
+
+    // Exit block should only contain phi and return
+    let ret = exit.get_last_inst();
+    if exit
+        .iter()
+        .any(|inst| inst != ret && inst.get_type() != InstType::Phi)
+    {
+        return None;
+    }
+
+    // Get value returned when jumping from `bb`
+    let ret_val = match ret.get_operand().first() {
+        Some(Operand::Instruction(inst)) if inst.get_parent_bb() == Some(exit) => {
+            let phi = downcast_ref::<Phi>(inst.as_ref().as_ref());
+            Some(phi.get_incoming_value(bb)?.clone())
+        }
+        op => op.cloned(),
+    };
+
+    // Instructions after call should be the accumulator (optional) and `br`
+    let next = call.get_next()?;
+    let acc = if next == bb.get_last_inst() {
+        if ret_val
+            .as_ref()
+            .is_some_and(|val| *val != Operand::Instruction(call))
+        {
+            return None;
+        }
+        None
+    } else {
+        if next.get_next()? != bb.get_last_inst()
+            || !matches!(next.get_type(), InstType::Add | InstType::Mul)
+            || next.get_value_type() != ValueType::Int
+            || ret_val != Some(Operand::Instruction(next))
+            || next.get_user().len() != 1
+        {
+            return None;
+        }
+        let call_count = next
+            .get_operand()
+            .iter()
+            .filter(|op| **op == Operand::Instruction(call))
+            .count();
+        if call_count != 1 {
+            return None;
+        }
+        Some(next)
+    };
+    // Call result should be used only by accumulator or return
+    if call.get_user().len() != ret_val.is_some() as usize {
+        return None;
+    }
+
+    // Local memory should not be passed to callee, as it's reused after elimination
+    if call.get_operand().iter().any(is_local_memory) {
+        cprintln!("[INFO] {} passes local memory to itself", func.name);
+        return None;
+    }
+    Some(TailCall { call, acc, bb })
+}
+
+/// Get operand accumulated with call result.
+fn get_accumulated(acc: InstPtr, call: InstPtr) -> Operand {
+    acc.get_operand()
+        .iter()
+        .find(|op| **op != Operand::Instruction(call))
+        .cloned()
+        .unwrap()
+}
+
+/// Check if operand points to memory allocated in function.
+fn is_local_memory(op: &Operand) -> bool {
+    let mut op = op.clone();
+    while let Operand::Instruction(inst) = op {
+        match inst.get_type() {
+            InstType::Alloca => return true,
+            InstType::GetElementPtr => op = inst.get_operand()[0].clone(),
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Get a block name not used in function.
+fn unique_name(func: FunPtr, base_name: &str) -> String {
+    let names = func
+        .dfs_iter()
+        .map(|bb| bb.name.clone())
+        .collect::<HashSet<_>>();
+    let mut id = 0;
+    loop {
+        let name = format!("{}_tre{}", base_name, id);
+        if !names.contains(&name) {
+            return name;
+        }
+        id += 1;
+    }
+}
+
+/// Replace all use of `from` in `user` with `to`.
+fn replace_use(mut user: InstPtr, from: &Operand, to: &Operand) {
+    for index in 0..user.get_operand().len() {
+        if user.get_operand()[index] == *from {
+            user.set_operand(index, to.clone());
+        }
+    }
+}
@@ -14,33 +14,73 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
 use anyhow::Result;
 
 use crate::{config::CONFIG, middle::Program};
 
 use super::{
-    block_fuse, dead_code_elim, func_inline, inst_combine, load_store_elim, loop_optimization,
-    make_parallel, mem2reg, redundance_elim, sink_code,
+    block_fuse, dead_code_elim, dead_global_elim, func_inline, inst_combine, ipccp,
+    load_store_elim, loop_fission, loop_fusion, loop_interchange, loop_optimization, loop_unroll,
+    loop_unswitch, make_parallel, mem2reg, redundance_elim, sccp, sink_code, strength_reduce,
+    tail_recursion_elim,
 };
 
 pub fn optimize_program(program: &mut Program) -> Result<bool> {
     mem2reg::optimize_program(program)?;
     main_loop(program)?;
+
+    // Interchange sinks values hoisted out of inner loop, hoist them again
+    if loop_interchange::optimize_program(program)? {
+        loop_optimization::optimize_program(program)?;
+    }
+
+    // Fuse loops over the same iterations, then clean up replaced induction variables
+    if loop_fusion::optimize_program(program)? {
+        eval_and_prune(program)?;
+        block_fuse::optimize_program(program)?;
+    }
     if CONFIG.open_auto_parallel {
+        // Split sequential parts out of loops, so the rest can be parallelized
+        loop_fission::optimize_program(program)?;
         make_parallel::optimize_program::<5>(program)?;
     }
+
+    // Parallelization expects a single phi in loop header, so reduce induction variables after it,
+    // then remove loop computations that are only used by replaced exit values
+    if strength_reduce::optimize_program(program)? {
+        loop_optimization::optimize_program(program)?;
+    }
+    loop_unroll::optimize_program(program)?;
     eval_and_prune(program)?;
     sink_code::optimize_program(program)?;
     Ok(true)
 }
 
 pub fn main_loop(program: &mut Program) -> Result<bool> {
+    let mut unswitch_growth = HashMap::new();
     loop {
         let mut changed = false;
 
+        // Turn self tail calls into loops, so that they can be inlined
+        changed |= tail_recursion_elim::optimize_program(program)?;
+
         // Inline functions
         changed |= func_inline::optimize_program(program)?;
 
+        // Remove dead globals, localized globals are then promoted to registers
+        if dead_global_elim::optimize_program(program)? {
+            mem2reg::optimize_program(program)?;
+            changed = true;
+        }
+
+        // Propagate constants along feasible edges
+        changed |= sccp::optimize_program(program)?;
+
+        // Propagate constants across functions
+        changed |= ipccp::optimize_program(program)?;
+
         // Simplify code
         changed |= eval_and_prune(program)?;
 
@@ -52,6 +92,10 @@ pub fn main_loop(program: &mut Program) -> Result<bool> {
         // TODO remove inst_combine in loop_optimization
         loop_optimization::optimize_program(program)?;
 
+        // Clone loops for invariant conditions, dead arms are pruned in the next round.
+        // Budget is shared by all rounds, because each round can clone the clones again.
+        changed |= loop_unswitch::optimize_program_with_growth(program, &mut unswitch_growth)?;
+
         // Fuse blocks
         changed |= block_fuse::optimize_program(program)?;
 
@@ -30,3 +30,5 @@ pub mod vec_util;
 pub mod diff;
 
 pub mod traverse;
+
+pub mod pass_instrument;
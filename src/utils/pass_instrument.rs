@@ -0,0 +1,221 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runtime pass instrumentation: IR dumps around named passes and timing report.
+//! Unlike `cprintln!`, this is controlled by command line switches, not features.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+
+use crate::context;
+
+lazy_static! {
+    static ref INSTRUMENT: Mutex<Option<PassInstrument>> = Mutex::new(None);
+}
+
+/// Pass name that matches every pass in `print_before` and `print_after`.
+pub const ALL_PASSES: &str = "all";
+
+/// One execution of a pass.
+pub struct PassRecord {
+    pub name: String,
+    pub changed: bool,
+    pub elapsed: Duration,
+}
+
+#[derive(Default)]
+pub struct PassInstrument {
+    /// Dump IR before these passes
+    pub print_before: Vec<String>,
+    /// Dump IR after these passes
+    pub print_after: Vec<String>,
+    /// Directory to write dumps into
+    pub print_dir: PathBuf,
+    /// Record time of each pass
+    pub time_passes: bool,
+    records: Vec<PassRecord>,
+    dump_count: usize,
+}
+
+impl PassInstrument {
+    pub fn new(
+        print_before: Vec<String>,
+        print_after: Vec<String>,
+        print_dir: PathBuf,
+        time_passes: bool,
+    ) -> Self {
+        Self {
+            print_before,
+            print_after,
+            print_dir,
+            time_passes,
+            ..Default::default()
+        }
+    }
+
+    /// Check if instrumentation does nothing, so it needs not to be installed.
+    pub fn is_empty(&self) -> bool {
+        self.print_before.is_empty() && self.print_after.is_empty() && !self.time_passes
+    }
+
+    /// Write IR of `unit` (a module or function) to a new file in dump directory.
+    /// Files are numbered in the order of dumping, so a sorted listing follows the pipeline.
+    fn dump(&mut self, when: &str, pass: &str, unit: &str, ext: &str, ir: String) -> Result<()> {
+        fs::create_dir_all(&self.print_dir).with_context(|| context!())?;
+        let file_name = format!("{:04}-{}-{}-{}.{}", self.dump_count, when, pass, unit, ext);
+        self.dump_count += 1;
+        fs::write(self.print_dir.join(file_name), ir).with_context(|| context!())
+    }
+
+    /// Add an execution of pass to timing report.
+    pub fn record(&mut self, name: &str, changed: bool, elapsed: Duration) {
+        self.records.push(PassRecord {
+            name: name.to_string(),
+            changed,
+            elapsed,
+        });
+    }
+
+    /// Get all recorded executions of passes, in order of finishing.
+    pub fn records(&self) -> &[PassRecord] {
+        &self.records
+    }
+
+    /// Summarize records per pass, in order of first execution.
+    pub fn report(&self) -> String {
+        let mut summary: Vec<(&str, Duration, usize, usize)> = Vec::new();
+        for record in self.records.iter() {
+            let index = match summary.iter().position(|s| s.0 == record.name) {
+                Some(index) => index,
+                None => {
+                    summary.push((&record.name, Duration::ZERO, 0, 0));
+                    summary.len() - 1
+                }
+            };
+            summary[index].1 += record.elapsed;
+            summary[index].2 += 1;
+            summary[index].3 += record.changed as usize;
+        }
+        let total: Duration = self.records.iter().map(|r| r.elapsed).sum();
+        let share = |elapsed: Duration| {
+            if total.is_zero() {
+                0.0
+            } else {
+                elapsed.as_secs_f64() / total.as_secs_f64() * 100.0
+            }
+        };
+
+        let mut report = String::from("===== Pass execution timing report =====\n");
+        report += &format!(
+            "{:>12} {:>7} {:>6} {:>8}  {}\n",
+            "Time (ms)", "Share", "Runs", "Changed", "Pass"
+        );
+        let mut line = |name: &str, elapsed: Duration, runs: usize, changed: usize| {
+            report += &format!(
+                "{:>12.3} {:>6.1}% {:>6} {:>8}  {}\n",
+                elapsed.as_secs_f64() * 1000.0,
+                share(elapsed),
+                runs,
+                changed,
+                name
+            );
+        };
+        for (name, elapsed, runs, changed) in summary {
+            line(name, elapsed, runs, changed);
+        }
+        let changed = self.records.iter().filter(|r| r.changed).count();
+        line("Total", total, self.records.len(), changed);
+        report
+    }
+}
+
+/// Install instrumentation globally, it's used by all passes run afterwards.
+pub fn install(instrument: PassInstrument) {
+    *INSTRUMENT.lock().unwrap() = Some(instrument);
+}
+
+/// Remove global instrumentation, returns it for reporting.
+pub fn uninstall() -> Option<PassInstrument> {
+    INSTRUMENT.lock().unwrap().take()
+}
+
+pub fn is_enabled() -> bool {
+    INSTRUMENT.lock().unwrap().is_some()
+}
+
+/// Dump IR if `pass` is in `print_before`. `ir` is only called when dumping.
+pub fn before_pass(pass: &str, unit: &str, ext: &str, ir: impl FnOnce() -> String) -> Result<()> {
+    if let Some(instrument) = INSTRUMENT.lock().unwrap().as_mut() {
+        if matches(&instrument.print_before, pass) {
+            instrument.dump("before", pass, unit, ext, ir())?;
+        }
+    }
+    Ok(())
+}
+
+/// Record execution of pass, and dump IR if `pass` is in `print_after`.
+/// `ir` is only called when dumping.
+pub fn after_pass(
+    pass: &str,
+    unit: &str,
+    ext: &str,
+    changed: bool,
+    elapsed: Duration,
+    ir: impl FnOnce() -> String,
+) -> Result<()> {
+    if let Some(instrument) = INSTRUMENT.lock().unwrap().as_mut() {
+        if instrument.time_passes {
+            instrument.record(pass, changed, elapsed);
+        }
+        if matches(&instrument.print_after, pass) {
+            instrument.dump("after", pass, unit, ext, ir())?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a pass that can't report changes, and instrument it if enabled.
+/// Whether it changes `target` is decided by comparing the dumped IR.
+pub fn run_pass<T>(
+    pass: &str,
+    unit: &str,
+    ext: &str,
+    target: &mut T,
+    ir: impl Fn(&T) -> String,
+    run: impl FnOnce(&mut T) -> Result<()>,
+) -> Result<()> {
+    if !is_enabled() {
+        return run(target);
+    }
+    let ir_before = ir(target);
+    before_pass(pass, unit, ext, || ir_before.clone())?;
+    let time_before = Instant::now();
+    run(target)?;
+    let elapsed = time_before.elapsed();
+    let ir_after = ir(target);
+    after_pass(pass, unit, ext, ir_before != ir_after, elapsed, || ir_after)
+}
+
+fn matches(passes: &[String], pass: &str) -> bool {
+    passes.iter().any(|p| p == pass || p == ALL_PASSES)
+}
@@ -19,3 +19,345 @@ mod test_gep_from_self {
     #[test]
     fn _is_int_test() {}
 }
+
+mod test_switch_from_self {
+    use compiler::{backend, frontend, middle};
+    use insta::assert_snapshot;
+
+    fn gen_asm(code: &str) -> String {
+        let program = frontend::parse(code).unwrap();
+        let program = middle::r#gen(&program).unwrap();
+        let program = backend::from_self::gen_from_self(&program).unwrap();
+        let func = program.modules[0].funcs.iter().find(|f| f.name() == "f");
+        func.unwrap().gen_asm()
+    }
+
+    #[test]
+    fn test_dense_switch() {
+        let code = r#"
+            int f(int x) {
+                switch (x) {
+                    case 1: return 10;
+                    case 2: return 20;
+                    case 3: return 30;
+                    case 5: return 50;
+                }
+                return 0;
+            }
+        "#;
+        assert_snapshot!(gen_asm(code), @r###"
+        .text
+        .align	3
+        .globl	f
+        .type	f, @function
+        f:
+        .Lf_entry:
+        mv x32,a0
+        store x32,[8-16]
+        load x33,[8-16]
+        li x34,1
+        sub x35,x33,x34
+        li x36,5
+        sltu x37,x35,x36
+        beq x37,zero,.Lf_final4
+        j .Lf_entry_switch1
+        .Lf_final4:
+        li x43,0
+        store x43,[0-8]
+        j .Lf_exit
+        .Lf_case0:
+        li x44,10
+        store x44,[0-8]
+        j .Lf_exit
+        .Lf_case1:
+        li x45,20
+        store x45,[0-8]
+        j .Lf_exit
+        .Lf_case2:
+        li x46,30
+        store x46,[0-8]
+        j .Lf_exit
+        .Lf_case3:
+        li x47,50
+        store x47,[0-8]
+        j .Lf_exit
+        .Lf_exit:
+        load x48,[0-8]
+        mv a0,x48
+        ret
+        .Lf_entry_switch1:
+        slli x38,x35,2
+        lla x39,.Lf_entry_switch1_table
+        add x40,x39,x38
+        lw x41,0(x40)
+        add x42,x39,x41
+        jr x42
+        .size	f, .-f
+        .section	.rodata
+        .align	2
+        .Lf_entry_switch1_table:
+        .word	.Lf_case0-.Lf_entry_switch1_table
+        .word	.Lf_case1-.Lf_entry_switch1_table
+        .word	.Lf_case2-.Lf_entry_switch1_table
+        .word	.Lf_final4-.Lf_entry_switch1_table
+        .word	.Lf_case3-.Lf_entry_switch1_table
+        "###);
+    }
+
+    #[test]
+    fn test_sparse_switch() {
+        let code = r#"
+            int f(int x) {
+                switch (x) {
+                    case 1: return 10;
+                    case 100: return 20;
+                    case 1000: return 30;
+                    case -7: return 40;
+                }
+                return 0;
+            }
+        "#;
+        assert_snapshot!(gen_asm(code), @r###"
+        .text
+        .align	3
+        .globl	f
+        .type	f, @function
+        f:
+        .Lf_entry:
+        mv x32,a0
+        store x32,[8-16]
+        load x33,[8-16]
+        li x34,100
+        blt x33,x34,.Lf_entry_switch1
+        j .Lf_entry_switch2
+        .Lf_final4:
+        li x39,0
+        store x39,[0-8]
+        j .Lf_exit
+        .Lf_case0:
+        li x40,10
+        store x40,[0-8]
+        j .Lf_exit
+        .Lf_case1:
+        li x41,20
+        store x41,[0-8]
+        j .Lf_exit
+        .Lf_case2:
+        li x42,30
+        store x42,[0-8]
+        j .Lf_exit
+        .Lf_case3:
+        li x43,40
+        store x43,[0-8]
+        j .Lf_exit
+        .Lf_exit:
+        load x44,[0-8]
+        mv a0,x44
+        ret
+        .Lf_entry_switch1:
+        li x35,-7
+        beq x33,x35,.Lf_case3
+        j .Lf_entry_switch3
+        .Lf_entry_switch2:
+        li x37,100
+        beq x33,x37,.Lf_case1
+        j .Lf_entry_switch4
+        .Lf_entry_switch3:
+        li x36,1
+        beq x33,x36,.Lf_case0
+        j .Lf_final4
+        .Lf_entry_switch4:
+        li x38,1000
+        beq x33,x38,.Lf_case2
+        j .Lf_final4
+        .size	f, .-f
+        "###);
+    }
+}
+
+mod test_struct_from_self {
+    use compiler::{backend, frontend, middle};
+    use insta::assert_snapshot;
+
+    fn gen_asm(code: &str) -> String {
+        let program = frontend::parse(code).unwrap();
+        let program = middle::r#gen(&program).unwrap();
+        let program = backend::from_self::gen_from_self(&program).unwrap();
+        let func = program.modules[0].funcs.iter().find(|f| f.name() == "f");
+        func.unwrap().gen_asm()
+    }
+
+    #[test]
+    fn test_field_offset() {
+        let code = r#"
+            struct P { int x; struct P *next; float y; };
+            void f(struct P p[]) {
+                p[1].next->y = p[0].x;
+            }
+        "#;
+        assert_snapshot!(gen_asm(code), @r###"
+        .text
+        .align	3
+        .globl	f
+        .type	f, @function
+        f:
+        .Lf_entry:
+        mv x32,a0
+        store x32,[0-8]
+        load x33,[0-8]
+        li x34,0
+        muli x35,x34,24
+        add x36,zero,x35
+        add x37,x33,x36
+        li x38,0
+        muli x39,x38,24
+        add x40,zero,x39
+        addi x41,x40,0
+        add x42,x37,x41
+        load x43,[0-8]
+        li x44,1
+        muli x45,x44,24
+        add x46,zero,x45
+        add x47,x43,x46
+        li x48,0
+        muli x49,x48,24
+        add x50,zero,x49
+        addi x51,x50,8
+        add x52,x47,x51
+        ld x53,0(x52)
+        li x54,0
+        muli x55,x54,24
+        add x56,zero,x55
+        addi x57,x56,16
+        add x58,x53,x57
+        lw x59,0(x42)
+        fcvt.s.w f32,x59
+        fsw f32,0(x58)
+        j .Lf_exit
+        .Lf_exit:
+        ret
+        .size	f, .-f
+        "###);
+    }
+    #[test]
+    fn test_char_load_store() {
+        let code = r#"
+            char f(char *s) {
+                s[1] = s[0];
+                return s[0] + 1;
+            }
+        "#;
+        assert_snapshot!(gen_asm(code), @r###"
+        .text
+        .align	3
+        .globl	f
+        .type	f, @function
+        f:
+        .Lf_entry:
+        mv x32,a0
+        store x32,[0-8]
+        load x33,[0-8]
+        li x34,0
+        muli x35,x34,1
+        add x36,zero,x35
+        add x37,x33,x36
+        load x38,[0-8]
+        li x39,1
+        muli x40,x39,1
+        add x41,zero,x40
+        add x42,x38,x41
+        lb x43,0(x37)
+        sb x43,0(x42)
+        load x44,[0-8]
+        li x45,0
+        muli x46,x45,1
+        add x47,zero,x46
+        add x48,x44,x47
+        lb x49,0(x48)
+        addiw x50,x49,1
+        slli x51,x50,56
+        srai x51,x51,56
+        store x51,[8-16]
+        j .Lf_exit
+        .Lf_exit:
+        load x52,[8-16]
+        mv a0,x52
+        ret
+        .size	f, .-f
+        "###);
+    }
+}
+
+mod test_wide_from_self {
+    use compiler::{backend, frontend, middle};
+    use insta::assert_snapshot;
+
+    fn gen_asm(code: &str) -> String {
+        let program = frontend::parse(code).unwrap();
+        let program = middle::r#gen(&program).unwrap();
+        let program = backend::from_self::gen_from_self(&program).unwrap();
+        let func = program.modules[0].funcs.iter().find(|f| f.name() == "f");
+        func.unwrap().gen_asm()
+    }
+
+    #[test]
+    fn test_long_double() {
+        let code = r#"
+            double g;
+            double f(long long *a) {
+                a[1] = a[0] * a[0];
+                return g * a[1] + 0.5;
+            }
+        "#;
+        assert_snapshot!(gen_asm(code), @r###"
+        .text
+        .align	3
+        .globl	f
+        .type	f, @function
+        f:
+        .Lf_entry:
+        mv x32,a0
+        store x32,[0-8]
+        load x33,[0-8]
+        li x34,0
+        muli x35,x34,8
+        add x36,zero,x35
+        add x37,x33,x36
+        load x38,[0-8]
+        li x39,0
+        muli x40,x39,8
+        add x41,zero,x40
+        add x42,x38,x41
+        ld x43,0(x37)
+        ld x44,0(x42)
+        mul x45,x43,x44
+        load x46,[0-8]
+        li x47,1
+        muli x48,x47,8
+        add x49,zero,x48
+        add x50,x46,x49
+        sd x45,0(x50)
+        load x51,[0-8]
+        li x52,1
+        muli x53,x52,8
+        add x54,zero,x53
+        add x55,x51,x54
+        lla x56,g
+        fld f32,0(x56)
+        ld x57,0(x55)
+        fcvt.d.l f33,x57
+        fmul.d f34,f32,f33
+        lla x58,_fc_3fe0000000000000
+        flw f35,0(x58)
+        fcvt.d.s f36,f35
+        fadd.d f37,f34,f36
+        store f37,[8-16]
+        j .Lf_exit
+        .Lf_exit:
+        load f38,[8-16]
+        fmv.d fa0,f38
+        ret
+        .size	f, .-f
+        "###);
+    }
+}
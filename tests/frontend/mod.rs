@@ -15,3 +15,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod parse;
+mod preprocess;
+mod sema;
@@ -17,7 +17,7 @@
 // Unit tests
 #[cfg(test)]
 pub mod tests_parse {
-    use insta::assert_debug_snapshot;
+    use insta::{assert_debug_snapshot, assert_snapshot};
 
     use compiler::{errors::FrontendError, frontend::*};
 
@@ -48,12 +48,16 @@ pub mod tests_parse {
                                             Some(
                                                 Int(
                                                     0,
+                                                    41..42,
                                                 ),
                                             ),
+                                            34..43,
                                         ),
                                     ],
+                                    20..53,
                                 ),
                             ),
+                            13..53,
                         ),
                     ],
                 }
@@ -63,6 +67,7 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
@@ -114,9 +119,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Int(
                                                         3,
+                                                        43..44,
                                                     ),
                                                 ),
+                                                38..44,
                                             ),
+                                            34..45,
                                         ),
                                         Decl(
                                             Var(
@@ -125,9 +133,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Int(
                                                         64206,
+                                                        67..73,
                                                     ),
                                                 ),
+                                                62..73,
                                             ),
+                                            58..74,
                                         ),
                                         Decl(
                                             Var(
@@ -136,9 +147,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Int(
                                                         47828,
+                                                        96..102,
                                                     ),
                                                 ),
+                                                91..102,
                                             ),
+                                            87..103,
                                         ),
                                         Decl(
                                             Var(
@@ -147,9 +161,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Int(
                                                         511,
+                                                        125..129,
                                                     ),
                                                 ),
+                                                120..129,
                                             ),
+                                            116..130,
                                         ),
                                         Decl(
                                             Var(
@@ -158,9 +175,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         3.7,
+                                                        154..157,
                                                     ),
                                                 ),
+                                                149..157,
                                             ),
+                                            143..158,
                                         ),
                                         Decl(
                                             Var(
@@ -169,9 +189,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         2.0,
+                                                        182..184,
                                                     ),
                                                 ),
+                                                177..184,
                                             ),
+                                            171..185,
                                         ),
                                         Decl(
                                             Var(
@@ -180,9 +203,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         0.9,
+                                                        209..211,
                                                     ),
                                                 ),
+                                                204..211,
                                             ),
+                                            198..212,
                                         ),
                                         Decl(
                                             Var(
@@ -191,9 +217,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         23000.0,
+                                                        236..242,
                                                     ),
                                                 ),
+                                                231..242,
                                             ),
+                                            225..243,
                                         ),
                                         Decl(
                                             Var(
@@ -202,9 +231,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         5e-10,
+                                                        267..273,
                                                     ),
                                                 ),
+                                                262..273,
                                             ),
+                                            256..274,
                                         ),
                                         Decl(
                                             Var(
@@ -213,9 +245,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         1000.0,
+                                                        298..301,
                                                     ),
                                                 ),
+                                                293..301,
                                             ),
+                                            287..302,
                                         ),
                                         Decl(
                                             Var(
@@ -224,9 +259,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         20000.0,
+                                                        326..330,
                                                     ),
                                                 ),
+                                                321..330,
                                             ),
+                                            315..331,
                                         ),
                                         Decl(
                                             Var(
@@ -235,9 +273,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         5.0,
+                                                        355..359,
                                                     ),
                                                 ),
+                                                350..359,
                                             ),
+                                            344..360,
                                         ),
                                         Decl(
                                             Var(
@@ -246,9 +287,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         15.0,
+                                                        384..392,
                                                     ),
                                                 ),
+                                                379..392,
                                             ),
+                                            373..393,
                                         ),
                                         Decl(
                                             Var(
@@ -257,9 +301,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         1.078125,
+                                                        417..425,
                                                     ),
                                                 ),
+                                                412..425,
                                             ),
+                                            406..426,
                                         ),
                                         Decl(
                                             Var(
@@ -268,9 +315,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         120.0,
+                                                        450..455,
                                                     ),
                                                 ),
+                                                445..455,
                                             ),
+                                            439..456,
                                         ),
                                         Decl(
                                             Var(
@@ -279,9 +329,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         120.0,
+                                                        480..485,
                                                     ),
                                                 ),
+                                                475..485,
                                             ),
+                                            469..486,
                                         ),
                                         Decl(
                                             Var(
@@ -290,9 +343,12 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         96.0,
+                                                        510..516,
                                                     ),
                                                 ),
+                                                505..516,
                                             ),
+                                            499..517,
                                         ),
                                         Decl(
                                             Var(
@@ -301,13 +357,18 @@ pub mod tests_parse {
                                                 Some(
                                                     Float(
                                                         6.5,
+                                                        541..547,
                                                     ),
                                                 ),
+                                                536..547,
                                             ),
+                                            530..548,
                                         ),
                                     ],
+                                    20..558,
                                 ),
                             ),
+                            13..558,
                         ),
                     ],
                 }
@@ -317,6 +378,7 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
@@ -337,8 +399,10 @@ pub mod tests_parse {
                             Some(
                                 Int(
                                     3,
+                                    8..9,
                                 ),
                             ),
+                            4..9,
                         ),
                     ],
                 }
@@ -348,6 +412,7 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
@@ -383,20 +448,27 @@ pub mod tests_parse {
                                                 Some(
                                                     Int(
                                                         3,
+                                                        42..43,
                                                     ),
                                                 ),
+                                                38..43,
                                             ),
+                                            34..44,
                                         ),
                                         Return(
                                             Some(
                                                 Var(
                                                     "n",
+                                                    64..65,
                                                 ),
                                             ),
+                                            57..66,
                                         ),
                                     ],
+                                    20..76,
                                 ),
                             ),
+                            13..76,
                         ),
                     ],
                 }
@@ -406,6 +478,7 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
@@ -447,28 +520,37 @@ pub mod tests_parse {
                                                 Int,
                                                 "a",
                                                 None,
+                                                60..61,
                                             ),
+                                            56..62,
                                         ),
                                         Expr(
                                             Some(
                                                 Var(
                                                     "a",
+                                                    75..76,
                                                 ),
                                             ),
                                             Int(
                                                 5,
+                                                79..80,
                                             ),
+                                            75..81,
                                         ),
                                         Return(
                                             Some(
                                                 Var(
                                                     "a",
+                                                    231..232,
                                                 ),
                                             ),
+                                            224..233,
                                         ),
                                     ],
+                                    42..243,
                                 ),
                             ),
+                            36..243,
                         ),
                     ],
                 }
@@ -478,6 +560,7 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
@@ -487,6 +570,7 @@ pub mod tests_parse {
         let code = r#"
         #include "sylib.h"
         #define MAX 100
+        int a[MAX];
         "#;
         match parse(code) {
             Ok(result) => {
@@ -495,17 +579,28 @@ pub mod tests_parse {
                     @r###"
                 Program {
                     module: [
-                        Stack(
-                            [],
-                        ),
-                        Const(
-                            Int,
-                            "MAX",
-                            Some(
+                        Var(
+                            Array(
+                                Int,
                                 Int(
                                     100,
+                                    0..0,
                                 ),
                             ),
+                            "a",
+                            Some(
+                                Zero(
+                                    Array(
+                                        Int,
+                                        Int(
+                                            100,
+                                            0..0,
+                                        ),
+                                    ),
+                                    0..0,
+                                ),
+                            ),
+                            15..23,
                         ),
                     ],
                 }
@@ -515,6 +610,7 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
@@ -553,12 +649,120 @@ pub mod tests_parse {
                                             Some(
                                                 Int(
                                                     3,
+                                                    185..186,
+                                                ),
+                                            ),
+                                            178..187,
+                                        ),
+                                    ],
+                                    63..214,
+                                ),
+                            ),
+                            57..214,
+                        ),
+                    ],
+                }
+                "###
+                )
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_for() {
+        let code = r#"
+        void f() {
+            for (int i = 0; i < 2; i = i + 1) ;
+        }"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Func(
+                            Function(
+                                Void,
+                                [],
+                            ),
+                            "f",
+                            Some(
+                                Block(
+                                    [
+                                        For(
+                                            Decl(
+                                                Var(
+                                                    Int,
+                                                    "i",
+                                                    Some(
+                                                        Int(
+                                                            0,
+                                                            45..46,
+                                                        ),
+                                                    ),
+                                                    41..46,
                                                 ),
+                                                37..47,
                                             ),
+                                            Some(
+                                                Binary(
+                                                    Var(
+                                                        "i",
+                                                        48..49,
+                                                    ),
+                                                    [
+                                                        (
+                                                            Lt,
+                                                            Int(
+                                                                2,
+                                                                52..53,
+                                                            ),
+                                                        ),
+                                                    ],
+                                                    48..53,
+                                                ),
+                                            ),
+                                            Expr(
+                                                Some(
+                                                    Var(
+                                                        "i",
+                                                        55..56,
+                                                    ),
+                                                ),
+                                                Binary(
+                                                    Var(
+                                                        "i",
+                                                        59..60,
+                                                    ),
+                                                    [
+                                                        (
+                                                            Add,
+                                                            Int(
+                                                                1,
+                                                                63..64,
+                                                            ),
+                                                        ),
+                                                    ],
+                                                    59..64,
+                                                ),
+                                                55..64,
+                                            ),
+                                            Nothing(
+                                                66..67,
+                                            ),
+                                            32..67,
                                         ),
                                     ],
+                                    18..77,
                                 ),
                             ),
+                            14..77,
                         ),
                     ],
                 }
@@ -568,7 +772,736 @@ pub mod tests_parse {
             Err(err) => match err {
                 FrontendError::ParseError(s) => panic!("{}", s),
                 FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_switch() {
+        let code = r#"
+        void f(int x) {
+            switch (x) { case 1: break; default: ; }
+        }"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Func(
+                            Function(
+                                Void,
+                                [
+                                    TypedIdent {
+                                        ty: Int,
+                                        id: Some(
+                                            "x",
+                                        ),
+                                    },
+                                ],
+                            ),
+                            "f",
+                            Some(
+                                Block(
+                                    [
+                                        Switch(
+                                            Var(
+                                                "x",
+                                                45..46,
+                                            ),
+                                            [
+                                                (
+                                                    Some(
+                                                        Int(
+                                                            1,
+                                                            55..56,
+                                                        ),
+                                                    ),
+                                                    [
+                                                        Break(
+                                                            58..64,
+                                                        ),
+                                                    ],
+                                                ),
+                                                (
+                                                    None,
+                                                    [
+                                                        Nothing(
+                                                            74..75,
+                                                        ),
+                                                    ],
+                                                ),
+                                            ],
+                                            37..77,
+                                        ),
+                                    ],
+                                    23..87,
+                                ),
+                            ),
+                            14..87,
+                        ),
+                    ],
+                }
+                "###
+                );
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_struct() {
+        let code = r#"
+        struct P { int x, y[2]; } p;
+        int f(struct P *q) { return q->y[1] + p.x; }"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Stack(
+                            [
+                                Struct(
+                                    "P",
+                                    [
+                                        TypedIdent {
+                                            ty: Int,
+                                            id: Some(
+                                                "x",
+                                            ),
+                                        },
+                                        TypedIdent {
+                                            ty: Array(
+                                                Int,
+                                                Int(
+                                                    2,
+                                                    0..0,
+                                                ),
+                                            ),
+                                            id: Some(
+                                                "y",
+                                            ),
+                                        },
+                                    ],
+                                    9..34,
+                                ),
+                                Var(
+                                    Struct(
+                                        "P",
+                                    ),
+                                    "p",
+                                    Some(
+                                        Zero(
+                                            Struct(
+                                                "P",
+                                            ),
+                                            0..0,
+                                        ),
+                                    ),
+                                    35..36,
+                                ),
+                            ],
+                            9..36,
+                        ),
+                        Func(
+                            Function(
+                                Int,
+                                [
+                                    TypedIdent {
+                                        ty: Pointer(
+                                            Struct(
+                                                "P",
+                                            ),
+                                        ),
+                                        id: Some(
+                                            "q",
+                                        ),
+                                    },
+                                ],
+                            ),
+                            "f",
+                            Some(
+                                Block(
+                                    [
+                                        Return(
+                                            Some(
+                                                Binary(
+                                                    Index(
+                                                        Arrow(
+                                                            Var(
+                                                                "q",
+                                                                74..75,
+                                                            ),
+                                                            "y",
+                                                            74..78,
+                                                        ),
+                                                        Int(
+                                                            1,
+                                                            79..80,
+                                                        ),
+                                                        74..81,
+                                                    ),
+                                                    [
+                                                        (
+                                                            Add,
+                                                            Field(
+                                                                Var(
+                                                                    "p",
+                                                                    84..85,
+                                                                ),
+                                                                "x",
+                                                                84..87,
+                                                            ),
+                                                        ),
+                                                    ],
+                                                    74..87,
+                                                ),
+                                            ),
+                                            67..88,
+                                        ),
+                                    ],
+                                    65..90,
+                                ),
+                            ),
+                            50..90,
+                        ),
+                    ],
+                }
+                "###
+                );
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_pointer() {
+        let code = r#"
+        int f(int *p, int *q) { *p = *(q + 1); return &p[1] - q; }"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Func(
+                            Function(
+                                Int,
+                                [
+                                    TypedIdent {
+                                        ty: Pointer(
+                                            Int,
+                                        ),
+                                        id: Some(
+                                            "p",
+                                        ),
+                                    },
+                                    TypedIdent {
+                                        ty: Pointer(
+                                            Int,
+                                        ),
+                                        id: Some(
+                                            "q",
+                                        ),
+                                    },
+                                ],
+                            ),
+                            "f",
+                            Some(
+                                Block(
+                                    [
+                                        Expr(
+                                            Some(
+                                                Unary(
+                                                    Deref,
+                                                    Var(
+                                                        "p",
+                                                        34..35,
+                                                    ),
+                                                    33..35,
+                                                ),
+                                            ),
+                                            Unary(
+                                                Deref,
+                                                Binary(
+                                                    Var(
+                                                        "q",
+                                                        40..41,
+                                                    ),
+                                                    [
+                                                        (
+                                                            Add,
+                                                            Int(
+                                                                1,
+                                                                44..45,
+                                                            ),
+                                                        ),
+                                                    ],
+                                                    40..45,
+                                                ),
+                                                38..45,
+                                            ),
+                                            33..47,
+                                        ),
+                                        Return(
+                                            Some(
+                                                Binary(
+                                                    Unary(
+                                                        AddrOf,
+                                                        Index(
+                                                            Var(
+                                                                "p",
+                                                                56..57,
+                                                            ),
+                                                            Int(
+                                                                1,
+                                                                58..59,
+                                                            ),
+                                                            56..60,
+                                                        ),
+                                                        55..60,
+                                                    ),
+                                                    [
+                                                        (
+                                                            Sub,
+                                                            Var(
+                                                                "q",
+                                                                63..64,
+                                                            ),
+                                                        ),
+                                                    ],
+                                                    55..64,
+                                                ),
+                                            ),
+                                            48..65,
+                                        ),
+                                    ],
+                                    31..67,
+                                ),
+                            ),
+                            13..67,
+                        ),
+                    ],
+                }
+                "###
+                );
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_compound_assign() {
+        let code = r#"
+        int x, y, a, b, i, j;
+        void g(int p, int q);
+        void f() { x++; --y; a += b = 2; i = 0, j = 1; g(a, b); }"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Stack(
+                            [
+                                Var(
+                                    Int,
+                                    "x",
+                                    Some(
+                                        Int(
+                                            0,
+                                            0..0,
+                                        ),
+                                    ),
+                                    13..14,
+                                ),
+                                Var(
+                                    Int,
+                                    "y",
+                                    Some(
+                                        Int(
+                                            0,
+                                            0..0,
+                                        ),
+                                    ),
+                                    16..17,
+                                ),
+                                Var(
+                                    Int,
+                                    "a",
+                                    Some(
+                                        Int(
+                                            0,
+                                            0..0,
+                                        ),
+                                    ),
+                                    19..20,
+                                ),
+                                Var(
+                                    Int,
+                                    "b",
+                                    Some(
+                                        Int(
+                                            0,
+                                            0..0,
+                                        ),
+                                    ),
+                                    22..23,
+                                ),
+                                Var(
+                                    Int,
+                                    "i",
+                                    Some(
+                                        Int(
+                                            0,
+                                            0..0,
+                                        ),
+                                    ),
+                                    25..26,
+                                ),
+                                Var(
+                                    Int,
+                                    "j",
+                                    Some(
+                                        Int(
+                                            0,
+                                            0..0,
+                                        ),
+                                    ),
+                                    28..29,
+                                ),
+                            ],
+                            9..29,
+                        ),
+                        Func(
+                            Function(
+                                Void,
+                                [
+                                    TypedIdent {
+                                        ty: Int,
+                                        id: Some(
+                                            "p",
+                                        ),
+                                    },
+                                    TypedIdent {
+                                        ty: Int,
+                                        id: Some(
+                                            "q",
+                                        ),
+                                    },
+                                ],
+                            ),
+                            "g",
+                            None,
+                            44..59,
+                        ),
+                        Func(
+                            Function(
+                                Void,
+                                [],
+                            ),
+                            "f",
+                            Some(
+                                Block(
+                                    [
+                                        Expr(
+                                            None,
+                                            Unary(
+                                                PostInc,
+                                                Var(
+                                                    "x",
+                                                    80..81,
+                                                ),
+                                                80..83,
+                                            ),
+                                            80..84,
+                                        ),
+                                        Expr(
+                                            None,
+                                            Unary(
+                                                PreDec,
+                                                Var(
+                                                    "y",
+                                                    87..88,
+                                                ),
+                                                85..88,
+                                            ),
+                                            85..89,
+                                        ),
+                                        Expr(
+                                            None,
+                                            Assign(
+                                                Var(
+                                                    "a",
+                                                    90..91,
+                                                ),
+                                                Some(
+                                                    Add,
+                                                ),
+                                                Assign(
+                                                    Var(
+                                                        "b",
+                                                        95..96,
+                                                    ),
+                                                    None,
+                                                    Int(
+                                                        2,
+                                                        99..100,
+                                                    ),
+                                                    95..100,
+                                                ),
+                                                90..100,
+                                            ),
+                                            90..101,
+                                        ),
+                                        Expr(
+                                            None,
+                                            Binary(
+                                                Assign(
+                                                    Var(
+                                                        "i",
+                                                        102..103,
+                                                    ),
+                                                    None,
+                                                    Int(
+                                                        0,
+                                                        106..107,
+                                                    ),
+                                                    102..107,
+                                                ),
+                                                [
+                                                    (
+                                                        Comma,
+                                                        Assign(
+                                                            Var(
+                                                                "j",
+                                                                109..110,
+                                                            ),
+                                                            None,
+                                                            Int(
+                                                                1,
+                                                                113..114,
+                                                            ),
+                                                            109..114,
+                                                        ),
+                                                    ),
+                                                ],
+                                                102..114,
+                                            ),
+                                            102..115,
+                                        ),
+                                        Expr(
+                                            None,
+                                            Call(
+                                                Var(
+                                                    "g",
+                                                    116..117,
+                                                ),
+                                                [
+                                                    Var(
+                                                        "a",
+                                                        118..119,
+                                                    ),
+                                                    Var(
+                                                        "b",
+                                                        121..122,
+                                                    ),
+                                                ],
+                                                116..123,
+                                            ),
+                                            116..124,
+                                        ),
+                                    ],
+                                    78..126,
+                                ),
+                            ),
+                            74..126,
+                        ),
+                    ],
+                }
+                "###
+                );
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_char_string() {
+        let code = r#"
+        char s[8] = "a\"b\\\n";
+        int c = '\'' + '\x41' + '\101';"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Var(
+                            Array(
+                                Char,
+                                Int(
+                                    8,
+                                    0..0,
+                                ),
+                            ),
+                            "s",
+                            Some(
+                                String(
+                                    "a\"b\\\n",
+                                    21..31,
+                                ),
+                            ),
+                            14..31,
+                        ),
+                        Var(
+                            Int,
+                            "c",
+                            Some(
+                                Int(
+                                    169,
+                                    49..71,
+                                ),
+                            ),
+                            45..71,
+                        ),
+                    ],
+                }
+                "###
+                );
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
+            },
+        }
+    }
+
+    #[test]
+    fn test_wide_types() {
+        let code = r#"
+        unsigned u;
+        unsigned long long int a;
+        long long b;
+        long c;
+        double d;"#;
+        match parse(code) {
+            Ok(result) => {
+                assert_debug_snapshot!(
+                    result,
+                    @r###"
+                Program {
+                    module: [
+                        Var(
+                            UnsignedInt,
+                            "u",
+                            Some(
+                                Int(
+                                    0,
+                                    0..0,
+                                ),
+                            ),
+                            18..19,
+                        ),
+                        Var(
+                            UnsignedLong,
+                            "a",
+                            Some(
+                                Int(
+                                    0,
+                                    0..0,
+                                ),
+                            ),
+                            52..53,
+                        ),
+                        Var(
+                            Long,
+                            "b",
+                            Some(
+                                Int(
+                                    0,
+                                    0..0,
+                                ),
+                            ),
+                            73..74,
+                        ),
+                        Var(
+                            Long,
+                            "c",
+                            Some(
+                                Int(
+                                    0,
+                                    0..0,
+                                ),
+                            ),
+                            89..90,
+                        ),
+                        Var(
+                            Double,
+                            "d",
+                            Some(
+                                Float(
+                                    0.0,
+                                    0..0,
+                                ),
+                            ),
+                            107..108,
+                        ),
+                    ],
+                }
+                "###
+                );
+            }
+            Err(err) => match err {
+                FrontendError::ParseError(s) => panic!("{}", s),
+                FrontendError::OptimizeError => panic!("optimize error"),
+                err => panic!("{:?}", err),
             },
         }
     }
+
+    #[test]
+    fn test_error_location() {
+        let code = r#"
+int main() {
+    int x = 1
+    return x;
+}
+"#;
+        let Err(FrontendError::ParseError(diag)) = parse(code) else {
+            panic!("code should not parse");
+        };
+        assert_eq!((diag.line, diag.column), (4, 5));
+        assert_snapshot!(diag, @r###"
+        error: expected `;`
+         --> 4:5
+          |
+        4 |     return x;
+          |     ^
+        "###);
+    }
 }
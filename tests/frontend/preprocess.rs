@@ -0,0 +1,148 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Unit tests
+#[cfg(test)]
+pub mod tests_preprocess {
+    use std::fs;
+
+    use insta::assert_snapshot;
+
+    use compiler::errors::FrontendError;
+    use compiler::frontend::parse_file;
+    use compiler::frontend::preprocess::preprocess;
+
+    /// Preprocessed text of `code`, with blank lines removed.
+    fn expand(code: &str) -> String {
+        let (text, _) = preprocess(code).unwrap();
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_define() {
+        let code = r#"
+        #define N 10
+        #define SQ(x) ((x) * (x))
+        #define CAT(a, b) a ## b
+        #define STR(x) #x
+        #define LOG(fmt, ...) putf(fmt, __VA_ARGS__)
+        #define SELF SELF + 1
+        #define LONG(a, \
+                     b) a + b
+        int CAT(arr, N)[SQ(N + 1)];
+        LOG(STR(a  "b"), N, SELF);
+        x = LONG(1,
+                 2) + __LINE__;
+        #undef N
+        N;
+        "#;
+        assert_snapshot!(expand(code), @r###"
+        int  arrN [ (( 10  + 1) * ( 10  + 1)) ];
+        putf( "a \"b\"" ,   10 ,  SELF + 1 ) ;
+        x =  1 +                   2  +  12 ;
+        N;
+        "###);
+    }
+
+    #[test]
+    fn test_condition() {
+        let code = r#"
+        #define A 2
+        #if defined(A) && A * 2 == 4
+        one;
+        #if B
+        two;
+        #elif !defined B
+        three;
+        #else
+        four;
+        #endif
+        #elif 1
+        five;
+        #endif
+        #ifndef A
+        six;
+        #else
+        seven;
+        #endif
+        "#;
+        assert_snapshot!(expand(code), @r###"
+        one;
+        three;
+        seven;
+        "###);
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let inc = dir.path().join("inc");
+        fs::create_dir(&inc).unwrap();
+        fs::write(
+            inc.join("common.h"),
+            "#pragma once\n#define N 4\nint get(int a[], int i);\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("util.h"),
+            "#include <common.h>\nint get(int a[], int i) { return a[i]; }\n",
+        )
+        .unwrap();
+        let main = dir.path().join("main.c");
+        fs::write(
+            &main,
+            "#include \"util.h\"\n#include <common.h>\nint main() {\n    int a[N] = {};\n    return get(a, 0) + x;\n}\n",
+        )
+        .unwrap();
+
+        // Undeclared `x` is reported at its line in `main.c`
+        let err = parse_file(&main, vec![inc]).unwrap_err();
+        let FrontendError::SemanticErrors(errs) = err else {
+            panic!("{:?}", err);
+        };
+        let diag = errs[0].diagnostic().unwrap();
+        assert_eq!(diag.file.as_deref(), Some(main.to_str().unwrap()));
+        assert_eq!(diag.line, 5);
+        assert_eq!(diag.column, 24);
+    }
+
+    #[test]
+    fn test_error() {
+        let code = "int a;\n#if 1\nint b;\n";
+        let err = preprocess(code).unwrap_err();
+        assert_snapshot!(err.diagnostic().unwrap(), @r###"
+        error: unterminated conditional directive
+         --> 2:1
+          |
+        2 | #if 1
+          | ^^^^^
+        "###);
+
+        let code = "#include \"missing.h\"\n";
+        let err = preprocess(code).unwrap_err();
+        assert_snapshot!(err.diagnostic().unwrap(), @r###"
+        error: cannot find include file `missing.h`
+         --> 1:1
+          |
+        1 | #include "missing.h"
+          | ^^^^^^^^^^^^^^^^^^^^
+        "###);
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Unit tests
+#[cfg(test)]
+pub mod tests_sema {
+    use insta::assert_snapshot;
+
+    use compiler::{errors::FrontendError, frontend::*};
+
+    /// Parse and check `code`, returning all semantic errors.
+    fn sema_errors(code: &str) -> Vec<FrontendError> {
+        match parse(code) {
+            Ok(_) => vec![],
+            Err(FrontendError::SemanticErrors(errs)) => errs,
+            Err(err) => panic!("{:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_valid_program() {
+        let code = r#"
+        const int N = 4;
+        int a[N];
+        int f(int x, int y[]);
+        int main() {
+            int i = 0;
+            while (i < N) {
+                if (i == 2) {
+                    i = i + 1;
+                    continue;
+                }
+                a[i] = f(i, a);
+                i = i + 1;
+            }
+            putf("%d", a[0]);
+            return g();
+        }
+        int f(int x, int y[]) {
+            return x + y[0];
+        }
+        int g() {
+            return getint();
+        }
+        "#;
+        assert!(sema_errors(code).is_empty());
+    }
+
+    #[test]
+    fn test_all_errors_reported() {
+        let code = r#"
+int f(int x) {
+    return x;
+}
+int main() {
+    const int c = 1;
+    c = 2;
+    break;
+    f(1, 2);
+    y = h();
+    {
+        int y = 0;
+        continue;
+    }
+    return y;
+}
+"#;
+        let errs = sema_errors(code);
+        let kinds: Vec<String> = errs.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "assign to constant",
+                "break outside loop or switch",
+                "argument count mismatch",
+                "undeclared identifier",
+                "undeclared identifier",
+                "continue outside loop",
+                "undeclared identifier",
+            ]
+        );
+        assert!(matches!(
+            &errs[2],
+            FrontendError::ArgumentCountMismatch(name, 1, 2, _) if name == "f"
+        ));
+        let rendered = errs
+            .iter()
+            .filter_map(FrontendError::diagnostic)
+            .map(|diag| diag.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_snapshot!(rendered, @r###"
+        error: cannot assign to constant `c`
+         --> 7:5
+          |
+        7 |     c = 2;
+          |     ^
+        error: `break` outside of a loop or switch
+         --> 8:5
+          |
+        8 |     break;
+          |     ^^^^^^
+        error: function `f` takes 1 argument(s) but 2 were supplied
+         --> 9:5
+          |
+        9 |     f(1, 2);
+          |     ^^^^^^^
+        error: cannot find value `y` in this scope
+          --> 10:5
+           |
+        10 |     y = h();
+           |     ^
+        error: cannot find function `h` in this scope
+          --> 10:9
+           |
+        10 |     y = h();
+           |         ^
+        error: `continue` outside of a loop
+          --> 13:9
+           |
+        13 |         continue;
+           |         ^^^^^^^^^
+        error: cannot find value `y` in this scope
+          --> 15:12
+           |
+        15 |     return y;
+           |            ^
+        "###);
+    }
+
+    #[test]
+    fn test_address_of_rvalue() {
+        let code = r#"
+int main() {
+    int x = 0;
+    int *p = &x;
+    *p = 1;
+    p = &(x + 1);
+    return *p;
+}
+"#;
+        let errs = sema_errors(code);
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(&errs[0], FrontendError::AddressOfRvalue(_)));
+    }
+}
@@ -23,7 +23,7 @@ pub mod tests_effect_analysis {
         middle::{
             analysis::effect_analysis::EffectAnalysis,
             irgen::gen,
-            transform::{constant_fold, dead_code_elim, inst_combine, mem2reg},
+            transform::{constant_fold, dead_code_elim, inst_combine, mem2reg, ultimate_pass},
         },
     };
 
@@ -243,4 +243,106 @@ pub mod tests_effect_analysis {
 
         "###);
     }
+
+    #[test]
+    fn test_non_negative_ret() {
+        let code = r#"
+        int count(int n) {
+            int c = 0;
+            while (n > 0) {
+                c = c + 1;
+                n = n / 2;
+            }
+            return c;
+        }
+        int twice(int n) {
+            return count(n) * 2;
+        }
+        int neg(int n) {
+            return 0 - count(n);
+        }
+        int rec(int n) {
+            if (n == 0) return 0;
+            return rec(n - 1) + 1;
+        }
+        int hash(int n) {
+            int h = 1, i = 0;
+            while (i < n) {
+                h = h * 100003 + 7;
+                i = i + 1;
+            }
+            return h;
+        }
+        int flag(int n) {
+            return n > 0;
+        }
+        int slot(int n) {
+            return flag(n) % 8 * 3 + 1;
+        }
+        int main() {
+            return twice(getint()) + neg(getint()) + rec(getint()) + hash(getint()) + slot(getint());
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let effect_analysis = EffectAnalysis::new(&program);
+        let mut names = effect_analysis
+            .non_negative_ret
+            .iter()
+            .map(|func| func.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_snapshot!(names.join(", "), @r###"
+        flag, slot
+        "###);
+    }
+
+    #[test]
+    fn test_non_negative_ret_outdated_exit() {
+        let code = r#"
+        int main() {
+            if (getint()) return 1;
+            return 2;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+
+        // Exit ends with `br` instead of `ret`, returns are still found
+        let mut main = program.module.functions.last().cloned().unwrap();
+        main.exit = main.entry;
+        let effect_analysis = EffectAnalysis::new(&program);
+        assert!(effect_analysis.non_negative_ret.contains(&main));
+    }
+
+    #[test]
+    fn test_inline_in_exit() {
+        // Second round of inlining puts calls to `f` into the fused exit block of `main`
+        let code = format!(
+            r#"
+            int f(int x) {{
+                int s = 0;
+                while (x > 0) {{
+                    s = s + x % 7;
+                    x = x / 2;
+                }}
+                if (0) {{
+                    {}
+                }}
+                return s;
+            }}
+            int main() {{
+                int a = f(getint());
+                int b = f(getint());
+                return a + b;
+            }}
+            "#,
+            "x = x * 3 + 1;\n".repeat(40)
+        );
+        let parsed = parse(&code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        ultimate_pass::optimize_program(&mut program).unwrap();
+    }
 }
@@ -16,3 +16,5 @@
 
 mod effect_analysis;
 mod memory_ssa;
+mod scalar_evolution;
+mod verifier;
@@ -0,0 +1,139 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_scalar_evolution {
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::{
+                loop_tools::{LoopForest, LoopPtr},
+                scalar_evolution::ScalarEvolution,
+            },
+            irgen::gen,
+            transform::mem2reg,
+            Program,
+        },
+    };
+
+    /// Dump scalar evolution of all loops in main function.
+    fn dump_loops(program: &Program) -> String {
+        fn dump(lo: LoopPtr, scev: &mut ScalarEvolution, res: &mut String) {
+            *res += &scev.dump(lo);
+            for sub_loop in lo.sub_loops.iter() {
+                dump(*sub_loop, scev, res);
+            }
+        }
+        let main = program
+            .module
+            .functions
+            .iter()
+            .find(|func| func.is_main())
+            .unwrap();
+        let forest = LoopForest::make_forest(*main).unwrap();
+        let mut scev = ScalarEvolution::new();
+        let mut res = String::new();
+        for lo in forest.forest.iter() {
+            dump(*lo, &mut scev, &mut res);
+        }
+        res
+    }
+
+    #[test]
+    fn test_add_rec() {
+        let code = r#"
+        int a[100];
+        int main() {
+            int i = 3;
+            int n = getint();
+            while (i < 50) {
+                a[i * 2 + n] = i - n;
+                i = i + 4;
+            }
+            return i;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert_snapshot!(dump_loops(&program), @r###"
+        loop cond0:
+            %Sub_16 = {-1 * %call_8 + 3,+,4}
+            %Mul_18 = {6,+,8}
+            %Add_20 = {%call_8 + 6,+,8}
+            %Add_24 = {7,+,4}
+            %phi_33 = {3,+,4}
+            trip count: 12
+            final %phi_33 = 51
+        "###);
+    }
+
+    #[test]
+    fn test_nested() {
+        let code = r#"
+        int main() {
+            int i = 10;
+            int s = 0;
+            while (i > 0) {
+                int j = 0;
+                while (j <= i) {
+                    s = s + j;
+                    j = j + 1;
+                }
+                i = i - 2;
+            }
+            return s;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert_snapshot!(dump_loops(&program), @r###"
+        loop cond0:
+            %phi_41 = {10,+,-2}
+            %Sub_32 = {8,+,-2}
+            trip count: 5
+            final %phi_41 = 0
+        loop cond3:
+            %Add_24 = {1,+,1}
+            %phi_45 = {0,+,1}
+            trip count: unknown
+        "###);
+    }
+
+    #[test]
+    fn test_not_affine() {
+        let code = r#"
+        int main() {
+            int i = 1;
+            int k = 0;
+            while (i != 64) {
+                i = i * 2;
+                k = k + i;
+            }
+            return k;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert_snapshot!(dump_loops(&program), @r###"
+        loop cond0:
+            trip count: unknown
+        "###);
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+pub mod tests_verifier {
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            ir::{instruction::InstType, InstPtr},
+            irgen::gen,
+            transform::{mem2reg, ultimate_pass},
+        },
+    };
+
+    /// Find the first instruction of given type in `main`.
+    fn find_inst(program: &compiler::middle::Program, ty: InstType) -> InstPtr {
+        let main = program
+            .module
+            .functions
+            .iter()
+            .find(|f| f.is_main())
+            .unwrap();
+        main.dfs_iter()
+            .flat_map(|bb| bb.iter())
+            .find(|inst| inst.get_type() == ty)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_optimized_program() {
+        let code = r#"
+        int a[10];
+        int f(int x) {
+            int s = 0;
+            while (x > 0) {
+                s = s + a[x % 10];
+                x = x - 1;
+            }
+            return s;
+        }
+        int main() {
+            int i = 0;
+            while (i < 10) {
+                a[i] = i * i;
+                i = i + 1;
+            }
+            if (getint() > 3) {
+                return f(20);
+            }
+            return 0;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        verify_program(&program).unwrap();
+        ultimate_pass::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+    }
+
+    #[test]
+    fn test_use_before_def() {
+        let code = r#"
+        int main() {
+            int a = getint();
+            return a + 1;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let mut call = find_inst(&program, InstType::Call);
+        let add = find_inst(&program, InstType::Add);
+        call.insert_before(add);
+        let err = verify_program(&program).unwrap_err();
+        assert_snapshot!(err.root_cause(), @r###"
+        @main: %call_6 does not dominate its use in %Add_9
+        "###);
+    }
+
+    #[test]
+    fn test_inst_after_terminator() {
+        let code = r#"
+        int main() {
+            int a = getint();
+            return a + 1;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let mut ret = find_inst(&program, InstType::Ret);
+        let add = find_inst(&program, InstType::Add);
+        ret.insert_after(add);
+        let err = verify_program(&program).unwrap_err();
+        assert_snapshot!(err.root_cause(), @r###"
+        @main: %ret_4 is not at the end of %exit
+        @main: block %exit is not terminated
+        @main: %Add_9 does not dominate its use in %ret_4
+        "###);
+    }
+
+    #[test]
+    fn test_outdated_exit() {
+        let code = r#"
+        int main() {
+            int a = getint();
+            return a + 1;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let mut main = program.module.functions.last().cloned().unwrap();
+        main.exit = main.entry;
+        let err = verify_program(&program).unwrap_err();
+        assert_snapshot!(err.root_cause(), @r###"
+        @main: exit %entry ends with br instead of ret
+        "###);
+    }
+}
@@ -401,6 +401,614 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_for() {
+        let code = r#"
+            int main() {
+                int s = 0;
+                for (int i = 0; i < 10; i = i + 1) {
+                    if (i == 5) continue;
+                    s = s + i;
+                }
+                return s;
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        store i32 0, ptr %alloca_5
+        %alloca_7 = alloca i32
+        store i32 0, ptr %alloca_7
+        br label %cond0
+
+        cond0:
+        %load_33 = load i32, ptr %alloca_7
+        %icmp_34 = icmp slt i32 %load_33, 10
+        br i1 %icmp_34, label %body1, label %final3
+
+        body1:
+        br label %cond4
+
+        final3:
+        %load_36 = load i32, ptr %alloca_5
+        store i32 %load_36, ptr %alloca_2
+        br label %exit
+
+        cond4:
+        %load_19 = load i32, ptr %alloca_7
+        %icmp_20 = icmp eq i32 %load_19, 5
+        br i1 %icmp_20, label %then5, label %alt6
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+        then5:
+        br label %step2
+
+        alt6:
+        br label %final7
+
+        step2:
+        %load_29 = load i32, ptr %alloca_7
+        %Add_30 = add i32 %load_29, 1
+        store i32 %Add_30, ptr %alloca_7
+        br label %cond0
+
+        final7:
+        %load_24 = load i32, ptr %alloca_5
+        %load_25 = load i32, ptr %alloca_7
+        %Add_26 = add i32 %load_24, %load_25
+        store i32 %Add_26, ptr %alloca_5
+        br label %step2
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_for_without_clause() {
+        let code = r#"
+            int main() {
+                int i = 0;
+                for (;;) {
+                    i = i + 1;
+                    if (i > 10) break;
+                }
+                return i;
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        store i32 0, ptr %alloca_5
+        br label %cond0
+
+        cond0:
+        br label %body1
+
+        body1:
+        %load_12 = load i32, ptr %alloca_5
+        %Add_13 = add i32 %load_12, 1
+        store i32 %Add_13, ptr %alloca_5
+        br label %cond4
+
+        cond4:
+        %load_20 = load i32, ptr %alloca_5
+        %icmp_21 = icmp sgt i32 %load_20, 10
+        br i1 %icmp_21, label %then5, label %alt6
+
+        then5:
+        br label %final3
+
+        alt6:
+        br label %final7
+
+        final3:
+        %load_28 = load i32, ptr %alloca_5
+        store i32 %load_28, ptr %alloca_2
+        br label %exit
+
+        final7:
+        br label %step2
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+        step2:
+        br label %cond0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_struct() {
+        let code = r#"
+            struct P { int x; float y; };
+            union U { int i; float f; };
+            struct P g = {1};
+            int main() {
+                struct P a = {2, 3.0};
+                union U u;
+                a = g;
+                u.f = a.y;
+                return a.x + u.i;
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        %struct.P = type { i32, float }
+        %union.U = type { i32 }
+        @g = dso_local global %struct.P { i32 1, float 0x0000000000000000 }
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca %struct.P
+        call void @llvm.memset.p0.i32(%struct.P* %alloca_5, i8 0, i32 8, i1 false)
+        %getelementptr_7 = getelementptr %struct.P, ptr %alloca_5, i32 0, i32 0
+        store i32 2, ptr %getelementptr_7
+        %getelementptr_9 = getelementptr %struct.P, ptr %alloca_5, i32 0, i32 1
+        store float 0x4008000000000000, ptr %getelementptr_9
+        %alloca_11 = alloca %union.U
+        %getelementptr_12 = getelementptr %struct.P, ptr %alloca_5, i32 0, i32 0
+        %getelementptr_13 = getelementptr %struct.P, ptr @g, i32 0, i32 0
+        %load_14 = load i32, ptr %getelementptr_13
+        store i32 %load_14, ptr %getelementptr_12
+        %getelementptr_16 = getelementptr %struct.P, ptr %alloca_5, i32 0, i32 1
+        %getelementptr_17 = getelementptr %struct.P, ptr @g, i32 0, i32 1
+        %load_18 = load float, ptr %getelementptr_17
+        store float %load_18, ptr %getelementptr_16
+        %getelementptr_20 = getelementptr %struct.P, ptr %alloca_5, i32 0, i32 1
+        %getelementptr_21 = getelementptr float, ptr %alloca_11, i32 0
+        %load_22 = load float, ptr %getelementptr_20
+        store float %load_22, ptr %getelementptr_21
+        %getelementptr_24 = getelementptr %struct.P, ptr %alloca_5, i32 0, i32 0
+        %getelementptr_25 = getelementptr i32, ptr %alloca_11, i32 0
+        %load_26 = load i32, ptr %getelementptr_24
+        %load_27 = load i32, ptr %getelementptr_25
+        %Add_28 = add i32 %load_26, %load_27
+        store i32 %Add_28, ptr %alloca_2
+        br label %exit
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_char_string() {
+        let code = r#"
+            char g = 'g';
+            int main() {
+                char s[4] = "ab";
+                char *p = "cd";
+                s[0] = s[1] + 1;
+                return p[1] + g;
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        @g = dso_local global i8 103
+        @str0 = dso_local constant [3 x i8] [i8 99, i8 100, i8 0]
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca [4 x i8]
+        call void @llvm.memset.p0.i32([4 x i8]* %alloca_5, i8 0, i32 4, i1 false)
+        %getelementptr_7 = getelementptr [4 x i8], ptr %alloca_5, i32 0, i32 0
+        store i8 97, ptr %getelementptr_7
+        %getelementptr_9 = getelementptr [4 x i8], ptr %alloca_5, i32 0, i32 1
+        store i8 98, ptr %getelementptr_9
+        %getelementptr_11 = getelementptr [4 x i8], ptr %alloca_5, i32 0, i32 2
+        store i8 0, ptr %getelementptr_11
+        %getelementptr_13 = getelementptr [4 x i8], ptr %alloca_5, i32 0, i32 3
+        store i8 0, ptr %getelementptr_13
+        %alloca_15 = alloca i8*
+        %getelementptr_16 = getelementptr [3 x i8], ptr @str0, i32 0, i32 0
+        store i8* %getelementptr_16, ptr %alloca_15
+        %getelementptr_18 = getelementptr [4 x i8], ptr %alloca_5, i32 0, i32 1
+        %load_19 = load i8, ptr %getelementptr_18
+        %sext_20 = sext i8 %load_19 to i32
+        %Add_21 = add i32 %sext_20, 1
+        %getelementptr_22 = getelementptr [4 x i8], ptr %alloca_5, i32 0, i32 0
+        %trunc_23 = trunc i32 %Add_21 to i8
+        store i8 %trunc_23, ptr %getelementptr_22
+        %load_25 = load i8*, ptr %alloca_15
+        %getelementptr_26 = getelementptr i8, ptr %load_25, i32 1
+        %load_27 = load i8, ptr %getelementptr_26
+        %sext_28 = sext i8 %load_27 to i32
+        %load_29 = load i8, ptr @g
+        %sext_30 = sext i8 %load_29 to i32
+        %Add_31 = add i32 %sext_28, %sext_30
+        store i32 %Add_31, ptr %alloca_2
+        br label %exit
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_compound_assign() {
+        let code = r#"
+            int main() {
+                int i, s = 0;
+                for (i = 0; i < 4; i++)
+                    s += i;
+                int x = s--, y;
+                y = (x <<= 1, ++x);
+                return s * (x = y);
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_6 = alloca i32
+        store i32 0, ptr %alloca_6
+        store i32 0, ptr %alloca_5
+        br label %cond0
+
+        cond0:
+        %load_23 = load i32, ptr %alloca_5
+        %icmp_24 = icmp slt i32 %load_23, 4
+        br i1 %icmp_24, label %body1, label %final3
+
+        body1:
+        %load_14 = load i32, ptr %alloca_6
+        %load_15 = load i32, ptr %alloca_5
+        %Add_16 = add i32 %load_14, %load_15
+        store i32 %Add_16, ptr %alloca_6
+        br label %step2
+
+        final3:
+        %alloca_26 = alloca i32
+        %load_27 = load i32, ptr %alloca_6
+        %Sub_28 = sub i32 %load_27, 1
+        store i32 %Sub_28, ptr %alloca_6
+        store i32 %load_27, ptr %alloca_26
+        %alloca_31 = alloca i32
+        %load_32 = load i32, ptr %alloca_26
+        %Shl_33 = shl i32 %load_32, 1
+        store i32 %Shl_33, ptr %alloca_26
+        %load_35 = load i32, ptr %alloca_26
+        %Add_36 = add i32 %load_35, 1
+        store i32 %Add_36, ptr %alloca_26
+        store i32 %Add_36, ptr %alloca_31
+        %load_39 = load i32, ptr %alloca_31
+        store i32 %load_39, ptr %alloca_26
+        %load_41 = load i32, ptr %alloca_6
+        %Mul_42 = mul i32 %load_41, %load_39
+        store i32 %Mul_42, ptr %alloca_2
+        br label %exit
+
+        step2:
+        %load_19 = load i32, ptr %alloca_5
+        %Add_20 = add i32 %load_19, 1
+        store i32 %Add_20, ptr %alloca_5
+        br label %cond0
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_pointer_arith() {
+        let code = r#"
+            int sum(int *p, int *end) {
+                int s = 0;
+                while (p < end) {
+                    s = s + *p;
+                    p = p + 1;
+                }
+                return s;
+            }
+            int main() {
+                int a[4] = {1, 2, 3, 4};
+                int x = 0;
+                int *px = &x;
+                *px = sum(a, a + 4);
+                return x + (a + 3 - a);
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @sum(i32* %p, i32* %end) {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32*
+        store i32* %p, ptr %alloca_5
+        %alloca_7 = alloca i32*
+        store i32* %end, ptr %alloca_7
+        %alloca_9 = alloca i32
+        store i32 0, ptr %alloca_9
+        br label %cond0
+
+        cond0:
+        %load_24 = load i32*, ptr %alloca_5
+        %load_25 = load i32*, ptr %alloca_7
+        %icmp_26 = icmp slt i32* %load_24, %load_25
+        br i1 %icmp_26, label %body1, label %final2
+
+        body1:
+        %load_15 = load i32*, ptr %alloca_5
+        %load_16 = load i32, ptr %alloca_9
+        %load_17 = load i32, ptr %load_15
+        %Add_18 = add i32 %load_16, %load_17
+        store i32 %Add_18, ptr %alloca_9
+        %load_20 = load i32*, ptr %alloca_5
+        %getelementptr_21 = getelementptr i32, ptr %load_20, i32 1
+        store i32* %getelementptr_21, ptr %alloca_5
+        br label %cond0
+
+        final2:
+        %load_28 = load i32, ptr %alloca_9
+        store i32 %load_28, ptr %alloca_2
+        br label %exit
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+
+        }
+        define i32 @main() {
+        entry:
+        %alloca_33 = alloca i32
+        %alloca_36 = alloca [4 x i32]
+        call void @llvm.memset.p0.i32([4 x i32]* %alloca_36, i8 0, i32 16, i1 false)
+        %getelementptr_38 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 0
+        store i32 1, ptr %getelementptr_38
+        %getelementptr_40 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 1
+        store i32 2, ptr %getelementptr_40
+        %getelementptr_42 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 2
+        store i32 3, ptr %getelementptr_42
+        %getelementptr_44 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 3
+        store i32 4, ptr %getelementptr_44
+        %alloca_46 = alloca i32
+        store i32 0, ptr %alloca_46
+        %alloca_48 = alloca i32*
+        %getelementptr_49 = getelementptr i32, ptr %alloca_46, i32 0
+        store i32* %getelementptr_49, ptr %alloca_48
+        %getelementptr_51 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 0
+        %getelementptr_52 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 0
+        %getelementptr_53 = getelementptr i32, ptr %getelementptr_52, i32 4
+        %call_54 = call i32 @sum(i32* %getelementptr_51, i32* %getelementptr_53)
+        %load_55 = load i32*, ptr %alloca_48
+        store i32 %call_54, ptr %load_55
+        %getelementptr_57 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 0
+        %getelementptr_58 = getelementptr i32, ptr %getelementptr_57, i32 3
+        %getelementptr_59 = getelementptr [4 x i32], ptr %alloca_36, i32 0, i32 0
+        %ptrtoi_60 = ptrtoint i32* %getelementptr_58 to i32
+        %ptrtoi_61 = ptrtoint i32* %getelementptr_59 to i32
+        %Sub_62 = sub i32 %ptrtoi_60, %ptrtoi_61
+        %SDiv_63 = sdiv i32 %Sub_62, 4
+        %load_64 = load i32, ptr %alloca_46
+        %Add_65 = add i32 %load_64, %SDiv_63
+        store i32 %Add_65, ptr %alloca_33
+        br label %exit
+
+        exit:
+        %load_34 = load i32, ptr %alloca_33
+        ret i32 %load_34
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_switch() {
+        let code = r#"
+            int main() {
+                int x = getint();
+                int r = 0;
+                switch (x) {
+                    case 1: r = 10; break;
+                    case 2: r = 20;
+                    case 3: r = r + 30; break;
+                    default: r = -1;
+                }
+                return r;
+            }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %call_6 = call i32 @getint()
+        store i32 %call_6, ptr %alloca_5
+        %alloca_8 = alloca i32
+        store i32 0, ptr %alloca_8
+        %load_10 = load i32, ptr %alloca_5
+        switch i32 %load_10, label %default3 [ i32 1, label %case0 i32 2, label %case1 i32 3, label %case2 ]
+
+        default3:
+        %Sub_25 = sub i32 0, 1
+        store i32 %Sub_25, ptr %alloca_8
+        br label %final4
+
+        case0:
+        store i32 10, ptr %alloca_8
+        br label %final4
+
+        case1:
+        store i32 20, ptr %alloca_8
+        br label %case2
+
+        case2:
+        %load_21 = load i32, ptr %alloca_8
+        %Add_22 = add i32 %load_21, 30
+        store i32 %Add_22, ptr %alloca_8
+        br label %final4
+
+        final4:
+        %load_28 = load i32, ptr %alloca_8
+        store i32 %load_28, ptr %alloca_2
+        br label %exit
+
+        exit:
+        %load_3 = load i32, ptr %alloca_2
+        ret i32 %load_3
+
+
+        }
+        "###);
+    }
+
     #[test]
     fn test_control_flow() {
         let code = r#"
@@ -1946,7 +2554,7 @@ mod tests {
         let result = gen(&program).unwrap();
         let llvm_ir = result.module.gen_llvm_ir();
         assert_snapshot!(llvm_ir, @r###"
-        @format0 = dso_local constant [2 x i32] [i32 540876920, i32 25637]
+        @str0 = dso_local constant [7 x i8] [i8 120, i8 32, i8 61, i8 32, i8 37, i8 100, i8 0]
         declare i32 @getint()
         declare i32 @getch()
         declare float @getfloat()
@@ -1969,9 +2577,9 @@ mod tests {
         %alloca_5 = alloca i32
         %call_6 = call i32 @getint()
         store i32 %call_6, ptr %alloca_5
-        %getelementptr_8 = getelementptr [2 x i32], ptr @format0, i32 0, i32 0
+        %getelementptr_8 = getelementptr [7 x i8], ptr @str0, i32 0, i32 0
         %load_9 = load i32, ptr %alloca_5
-        call void @putf(i32* %getelementptr_8, i32 %load_9)
+        call void @putf(i8* %getelementptr_8, i32 %load_9)
         store i32 0, ptr %alloca_2
         br label %exit
 
@@ -2194,7 +2802,6 @@ mod tests {
         let result = gen(&program).unwrap();
         let llvm_ir = result.module.gen_llvm_ir();
         assert_snapshot!(llvm_ir, @r###"
-        @len = dso_local constant i32 20
         declare i32 @getint()
         declare i32 @getch()
         declare float @getfloat()
@@ -2217,13 +2824,12 @@ mod tests {
         br label %cond0
 
         cond0:
-        %load_10 = load i32, ptr @len
-        %icmp_11 = icmp eq i32 %load_10, 20
-        br i1 %icmp_11, label %then1, label %alt2
+        %icmp_10 = icmp eq i32 20, 20
+        br i1 %icmp_10, label %then1, label %alt2
 
         then1:
-        %alloca_13 = alloca [20 x i32]
-        call void @llvm.memset.p0.i32([20 x i32]* %alloca_13, i8 0, i32 80, i1 false)
+        %alloca_12 = alloca [20 x i32]
+        call void @llvm.memset.p0.i32([20 x i32]* %alloca_12, i8 0, i32 80, i1 false)
         br label %final3
 
         alt2:
@@ -2258,7 +2864,6 @@ mod tests {
         let result = gen(&program).unwrap();
         let llvm_ir = result.module.gen_llvm_ir();
         assert_snapshot!(llvm_ir, @r###"
-        @len = dso_local constant i32 20
         declare i32 @getint()
         declare i32 @getch()
         declare float @getfloat()
@@ -2338,6 +2943,138 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_wide_types() {
+        let code = r#"
+        long long big[2] = {1, 2};
+        long long sum(int *a, int n) {
+            long long s = 0;
+            for (int i = 0; i < n; i += 1) s += a[i];
+            return s;
+        }
+        unsigned half(unsigned x) {
+            return x / 2 + (x > 7);
+        }
+        double scale(float f) {
+            double d = f;
+            return d * 2;
+        }
+        "#;
+        let program = parse(code).unwrap();
+        let result = gen(&program).unwrap();
+        let llvm_ir = result.module.gen_llvm_ir();
+        assert_snapshot!(llvm_ir, @r###"
+        @big = dso_local global [2 x i64] [i64 1, i64 2]
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i64 @sum(i32* %a, i32 %n) {
+        entry:
+        %alloca_2 = alloca i64
+        %alloca_5 = alloca i32*
+        store i32* %a, ptr %alloca_5
+        %alloca_7 = alloca i32
+        store i32 %n, ptr %alloca_7
+        %alloca_9 = alloca i64
+        %sext_10 = sext i32 0 to i64
+        store i64 %sext_10, ptr %alloca_9
+        %alloca_12 = alloca i32
+        store i32 0, ptr %alloca_12
+        br label %cond0
+
+        cond0:
+        %load_32 = load i32, ptr %alloca_12
+        %load_33 = load i32, ptr %alloca_7
+        %icmp_34 = icmp slt i32 %load_32, %load_33
+        br i1 %icmp_34, label %body1, label %final3
+
+        body1:
+        %load_19 = load i32, ptr %alloca_12
+        %load_20 = load i32*, ptr %alloca_5
+        %getelementptr_21 = getelementptr i32, ptr %load_20, i32 %load_19
+        %load_22 = load i64, ptr %alloca_9
+        %load_23 = load i32, ptr %getelementptr_21
+        %sext_24 = sext i32 %load_23 to i64
+        %Add_25 = add i64 %load_22, %sext_24
+        store i64 %Add_25, ptr %alloca_9
+        br label %step2
+
+        final3:
+        %load_36 = load i64, ptr %alloca_9
+        store i64 %load_36, ptr %alloca_2
+        br label %exit
+
+        step2:
+        %load_28 = load i32, ptr %alloca_12
+        %Add_29 = add i32 %load_28, 1
+        store i32 %Add_29, ptr %alloca_12
+        br label %cond0
+
+        exit:
+        %load_3 = load i64, ptr %alloca_2
+        ret i64 %load_3
+
+
+        }
+        define i32 @half(i32 %x) {
+        entry:
+        %alloca_41 = alloca i32
+        %alloca_44 = alloca i32
+        store i32 %x, ptr %alloca_44
+        %load_46 = load i32, ptr %alloca_44
+        %bitcast_47 = bitcast i32 2 to i32
+        %UDiv_48 = udiv i32 %load_46, %bitcast_47
+        %load_49 = load i32, ptr %alloca_44
+        %bitcast_50 = bitcast i32 7 to i32
+        %icmp_51 = icmp ugt i32 %load_49, %bitcast_50
+        %zext_52 = zext i1 %icmp_51 to i32
+        %Add_53 = add i32 %UDiv_48, %zext_52
+        store i32 %Add_53, ptr %alloca_41
+        br label %exit
+
+        exit:
+        %load_42 = load i32, ptr %alloca_41
+        ret i32 %load_42
+
+
+        }
+        define double @scale(float %f) {
+        entry:
+        %alloca_58 = alloca double
+        %alloca_61 = alloca float
+        store float %f, ptr %alloca_61
+        %alloca_63 = alloca double
+        %load_64 = load float, ptr %alloca_61
+        %fpext_65 = fpext float %load_64 to double
+        store double %fpext_65, ptr %alloca_63
+        %load_67 = load double, ptr %alloca_63
+        %itofp_68 = sitofp i32 2 to double
+        %FMul_69 = fmul double %load_67, %itofp_68
+        store double %FMul_69, ptr %alloca_58
+        br label %exit
+
+        exit:
+        %load_59 = load double, ptr %alloca_58
+        ret double %load_59
+
+
+        }
+        "###);
+    }
+
     #[test]
     fn test_multi_zero_array() {
         let code = r#"
@@ -0,0 +1,199 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+pub mod tests_irparse {
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            irparse,
+            transform::{constant_fold, dead_code_elim, mem2reg},
+        },
+    };
+
+    fn parse_error(code: &str) -> String {
+        irparse::parse(code).err().unwrap().root_cause().to_string()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let code = r#"
+        struct P { int x; float y; };
+        union U { int i; char c[6]; };
+        struct P p = {1, 2.0};
+        union U u;
+        int a[2][3] = {{1, 2, 3}, {4}};
+        double d = 1.5;
+        int f(int *p, float q) {
+            switch (p[0]) {
+                case 1: return 2;
+                case 5: return 3;
+            }
+            return q;
+        }
+        int main() {
+            int i = 0, s = 0;
+            while (i < 10 && d < 100.0) {
+                s = s + f(a[1], p.y) + u.c[i % 6];
+                d = d * 2.0;
+                i = i + 1;
+            }
+            return s;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+
+        // Parsed program is printed the same, except for instruction ids
+        let llvm_ir = program.module.gen_llvm_ir();
+        let reparsed = irparse::parse(&llvm_ir).unwrap();
+        verify_program(&reparsed).unwrap();
+        let strip_id = |ir: &str| {
+            regex::Regex::new(r"_\d+")
+                .unwrap()
+                .replace_all(ir, "")
+                .to_string()
+        };
+        let llvm_ir_reparsed = reparsed.module.gen_llvm_ir();
+        assert_eq!(strip_id(&llvm_ir), strip_id(&llvm_ir_reparsed));
+
+        // Printing is a fixed point once ids are assigned by parser
+        let twice = irparse::parse(&llvm_ir_reparsed).unwrap();
+        assert_eq!(llvm_ir_reparsed, twice.module.gen_llvm_ir());
+    }
+
+    #[test]
+    fn test_hand_written() {
+        let code = r#"
+        @n = dso_local global i32 10
+
+        declare void @putint(i32)
+
+        define i32 @main() {
+        entry:
+            %n = load i32, ptr @n
+            br label %cond
+
+        ; Body appears before its dominator, and phi refers to later values
+        body:
+            %sum.next = add i32 %sum, %i
+            %i.next = add i32 %i, 1
+            br label %cond
+
+        cond:
+            %i = phi i32 [0, %entry], [%i.next, %body]
+            %sum = phi i32 [0, %entry], [%sum.next, %body]
+            %c = icmp slt i32 %i, %n
+            br i1 %c, label %body, label %exit
+
+        exit:
+            call void @putint(i32 %sum)
+            ret i32 0
+        }
+        "#;
+        let program = irparse::parse(code).unwrap();
+        verify_program(&program).unwrap();
+        assert_snapshot!(program.module.gen_llvm_ir(), @r###"
+        @n = dso_local global i32 10
+        declare void @putint(i32 %p0)
+        define i32 @main() {
+        entry:
+        %load_4 = load i32, ptr @n
+        br label %cond
+
+        cond:
+        %phi_6 = phi i32 [0, %entry], [%Add_13, %body]
+        %phi_7 = phi i32 [0, %entry], [%Add_12, %body]
+        %icmp_8 = icmp slt i32 %phi_6, %load_4
+        br i1 %icmp_8, label %body, label %exit
+
+        body:
+        %Add_12 = add i32 %phi_7, %phi_6
+        %Add_13 = add i32 %phi_6, 1
+        br label %cond
+
+        exit:
+        call void @putint(i32 %phi_7)
+        ret i32 0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_pass_from_ir() {
+        let code = r#"
+        define i32 @main() {
+            %a = add i32 1, 2
+            %b = mul i32 %a, 3
+            %unused = sub i32 %b, 1
+            ret i32 %b
+        }
+        "#;
+        let mut program = irparse::parse(code).unwrap();
+        constant_fold::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        assert_snapshot!(program.module.gen_llvm_ir(), @r###"
+        define i32 @main() {
+        entry:
+        ret i32 9
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let undefined_value = r#"
+        define i32 @main() {
+        entry:
+            %a = add i32 %b, 1
+            ret i32 %a
+        }
+        "#;
+        assert_snapshot!(parse_error(undefined_value), @r###"
+        line 4: undefined value %b
+        "###);
+
+        let unterminated = r#"
+        define void @main() {
+        entry:
+            %a = alloca i32
+        }
+        "#;
+        assert_snapshot!(parse_error(unterminated), @r###"
+        line 3: %entry is not terminated
+        "###);
+
+        let unknown_type = r#"
+        define void @main() {
+        entry:
+            %a = alloca i17
+            ret void
+        }
+        "#;
+        assert_snapshot!(parse_error(unknown_type), @r###"
+        line 4: unknown type `i17`
+        "###);
+    }
+}
@@ -16,4 +16,5 @@
 
 mod analysis;
 mod irgen;
+mod irparse;
 mod transform;
@@ -309,6 +309,99 @@ pub mod tests_dce {
         "###);
     }
 
+    #[test]
+    fn test_dce_phi_cycle() {
+        let code = r#"
+        int main() {
+            int i = 0;
+            int s = 0;
+            while (i < 10) {
+                int t = 0;
+                int j = 0;
+                while (j < 10) {
+                    t = j;
+                    s = s + j;
+                    j = j + 1;
+                }
+                i = i + 1;
+            }
+            return s;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        [-] %alloca_7 = alloca i32
+        br label %cond0
+
+        cond0:
+        [-] %phi_49 = phi i32 [0, %entry], [%phi_50, %final5]
+        [-] %phi_47 = phi i32 [0, %entry], [%phi_48, %final5]
+        %phi_45 = phi i32 [0, %entry], [%phi_46, %final5]
+        %phi_44 = phi i32 [0, %entry], [%Add_35, %final5]
+        %icmp_39 = icmp slt i32 %phi_44, 10
+        br i1 %icmp_39, label %body1, label %final2
+
+        body1:
+        [-] %alloca_13 = alloca i32
+        [-] %alloca_15 = alloca i32
+        br label %cond3
+
+        final2:
+        br label %exit
+
+        cond3:
+        %phi_50 = phi i32 [0, %body1], [%Add_28, %body4]
+        [-] %phi_48 = phi i32 [0, %body1], [%phi_50, %body4]
+        %phi_46 = phi i32 [%phi_45, %body1], [%Add_25, %body4]
+        %icmp_32 = icmp slt i32 %phi_50, 10
+        br i1 %icmp_32, label %body4, label %final5
+
+        exit:
+        ret i32 %phi_45
+
+        body4:
+        %Add_25 = add i32 %phi_46, %phi_50
+        %Add_28 = add i32 %phi_50, 1
+        br label %cond3
+
+        final5:
+        %Add_35 = add i32 %phi_44, 1
+        br label %cond0
+
+
+        }
+        "###);
+    }
+
     #[test]
     fn test_dce_1() {
         let code = r#"
@@ -0,0 +1,287 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_dead_global_elim {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{dead_global_elim, mem2reg},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_dead_func() {
+        let code = r#"
+        int unused = 7;
+        int g = 1;
+        int leaf(int x) {
+            return x * unused;
+        }
+        int dead(int x) {
+            return leaf(x) + 1;
+        }
+        void live() {
+            g = g + 1;
+        }
+        int main() {
+            live();
+            return g;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(dead_global_elim::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        [-] @unused = dso_local global i32 7
+        @g = dso_local global i32 1
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        [-] define i32 @leaf(i32 %x) {
+        [-] entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        [-] %load_8 = load i32, ptr @unused
+        [-] %Mul_9 = mul i32 %x, %load_8
+        [-] br label %exit
+        [-] 
+        [-] exit:
+        [-] ret i32 %Mul_9
+        [-] 
+        [-] 
+        [-] }
+        [-] define i32 @dead(i32 %x) {
+        [-] entry:
+        [-] %alloca_14 = alloca i32
+        [-] %alloca_17 = alloca i32
+        [-] %call_20 = call i32 @leaf(i32 %x)
+        [-] %Add_21 = add i32 %call_20, 1
+        [-] br label %exit
+        [-] 
+        [-] exit:
+        [-] ret i32 %Add_21
+        [-] 
+        [-] 
+        [-] }
+        define void @live() {
+        entry:
+        %load_27 = load i32, ptr @g
+        %Add_28 = add i32 %load_27, 1
+        store i32 %Add_28, ptr @g
+        br label %exit
+
+        exit:
+        ret void
+
+
+        }
+        define i32 @main() {
+        entry:
+        %alloca_33 = alloca i32
+        call void @live()
+        %load_37 = load i32, ptr @g
+        br label %exit
+
+        exit:
+        ret i32 %load_37
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_read_only() {
+        let code = r#"
+        int scale = 3;
+        int tab[4] = {1, 2, 3, 4};
+        int f(int i) {
+            return tab[i] * scale;
+        }
+        int main() {
+            return f(getint()) + scale;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(dead_global_elim::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        [-] @scale = dso_local global i32 3
+        [-] @tab = dso_local global [4 x i32] [i32 1, i32 2, i32 3, i32 4]
+        [+] @tab = dso_local constant [4 x i32] [i32 1, i32 2, i32 3, i32 4]
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @f(i32 %i) {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %getelementptr_8 = getelementptr [4 x i32], ptr @tab, i32 0, i32 %i
+        %load_9 = load i32, ptr %getelementptr_8
+        [-] %load_10 = load i32, ptr @scale
+        [-] %Mul_11 = mul i32 %load_9, %load_10
+        [+] %Mul_11 = mul i32 %load_9, 3
+        br label %exit
+
+        exit:
+        ret i32 %Mul_11
+
+
+        }
+        define i32 @main() {
+        entry:
+        %alloca_16 = alloca i32
+        %call_19 = call i32 @getint()
+        %call_20 = call i32 @f(i32 %call_19)
+        [-] %load_21 = load i32, ptr @scale
+        [-] %Add_22 = add i32 %call_20, %load_21
+        [+] %Add_22 = add i32 %call_20, 3
+        br label %exit
+
+        exit:
+        ret i32 %Add_22
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_localize() {
+        let code = r#"
+        int cnt = 5;
+        int main() {
+            int i = 0;
+            while (i < getint()) {
+                cnt = cnt + i;
+                i = i + 1;
+            }
+            return cnt;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization, localized global is promoted by mem2reg
+        assert!(dead_global_elim::optimize_program(&mut program).unwrap());
+        mem2reg::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        [-] @cnt = dso_local global i32 5
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [+] %alloca_27 = alloca i32
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        br label %cond0
+
+        cond0:
+        [+] %phi_29 = phi i32 [5, %entry], [%Add_13, %body1]
+        %phi_26 = phi i32 [0, %entry], [%Add_16, %body1]
+        %call_19 = call i32 @getint()
+        %icmp_21 = icmp slt i32 %phi_26, %call_19
+        br i1 %icmp_21, label %body1, label %final2
+
+        body1:
+        [-] %load_11 = load i32, ptr @cnt
+        [-] %Add_13 = add i32 %load_11, %phi_26
+        [-] store i32 %Add_13, ptr @cnt
+        [+] %Add_13 = add i32 %phi_29, %phi_26
+        %Add_16 = add i32 %phi_26, 1
+        br label %cond0
+
+        final2:
+        [-] %load_23 = load i32, ptr @cnt
+        br label %exit
+
+        exit:
+        [-] ret i32 %load_23
+        [+] ret i32 %phi_29
+
+
+        }
+        "###);
+    }
+}
@@ -21,10 +21,13 @@ pub mod tests_func_inline {
     use compiler::{
         frontend::parse,
         middle::{
+            analysis::verifier::verify_program,
+            ir::instruction::InstType,
             irgen::gen,
             transform::{
                 block_fuse, constant_fold, dead_code_elim, func_inline, inst_combine, mem2reg,
             },
+            Program,
         },
         utils::diff::diff,
     };
@@ -280,4 +283,116 @@ pub mod tests_func_inline {
         }
         "###);
     }
+
+    #[test]
+    fn test_non_leaf() {
+        let code = r#"
+        int sq(int x) {
+            return x * x;
+        }
+        int f(int x) {
+            return sq(x) + sq(x + 1);
+        }
+        int main() {
+            return f(getint());
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert!(func_inline::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        assert_eq!(func_names(&program), ["main"]);
+    }
+
+    #[test]
+    fn test_call_in_exit() {
+        let code = r#"
+        int f(int x) {
+            int s = 0;
+            while (x > 0) {
+                s = s + x;
+                x = x - 1;
+            }
+            return s;
+        }
+        int main() {
+            return f(getint());
+        }
+        "#;
+
+        // Blocks of main are fused into exit, so the call is split from the return
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        block_fuse::optimize_program(&mut program).unwrap();
+        let main = program.module.functions.last().cloned().unwrap();
+        assert_eq!(main.entry, main.exit);
+        assert!(func_inline::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        assert_eq!(main.exit.unwrap().get_last_inst().get_type(), InstType::Ret);
+    }
+
+    #[test]
+    fn test_recursive() {
+        let code = r#"
+        int g(int x) {
+            return x - 1;
+        }
+        int f(int x) {
+            if (x < 2) return x;
+            return f(g(x)) + f(x - 2);
+        }
+        int main() {
+            return f(getint()) + f(getint());
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert!(func_inline::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        assert_eq!(func_names(&program), ["f", "main"]);
+    }
+
+    #[test]
+    fn test_loop_depth() {
+        // Function too large to inline outside loop, but cheap enough in loop
+        let body = "x = x * 3 + 1;\n".repeat(40);
+        let code = format!(
+            r#"
+            int f(int x) {{
+                {}
+                return x;
+            }}
+            int main() {{
+                int i = 0, s = f(getint());
+                while (i < 10) {{
+                    s = s + f(i);
+                    i = i + 1;
+                }}
+                return s;
+            }}
+            "#,
+            body
+        );
+        let parsed = parse(&code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert!(func_inline::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        assert_eq!(func_names(&program), ["f", "main"]);
+        let main = program.module.gen_llvm_ir();
+        assert_eq!(main.matches("call i32 @f").count(), 1);
+    }
+
+    fn func_names(program: &Program) -> Vec<String> {
+        program
+            .module
+            .functions
+            .iter()
+            .filter(|func| !func.is_lib())
+            .map(|func| func.name.clone())
+            .collect()
+    }
 }
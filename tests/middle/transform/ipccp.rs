@@ -0,0 +1,474 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_ipccp {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{ipccp, mem2reg},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_common_arg() {
+        let code = r#"
+        int f(int x, int m) {
+            return x * 2 % m;
+        }
+        int main() {
+            return f(getint(), 7) + f(getint(), 7);
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(ipccp::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @f(i32 %x, i32 %m) {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_7 = alloca i32
+        %Mul_10 = mul i32 %x, 2
+        [-] %SRem_12 = srem i32 %Mul_10, %m
+        [+] %SRem_12 = srem i32 %Mul_10, 7
+        br label %exit
+
+        exit:
+        ret i32 %SRem_12
+
+
+        }
+        define i32 @main() {
+        entry:
+        %alloca_17 = alloca i32
+        %call_20 = call i32 @getint()
+        %call_21 = call i32 @f(i32 %call_20, i32 7)
+        %call_22 = call i32 @getint()
+        %call_23 = call i32 @f(i32 %call_22, i32 7)
+        %Add_24 = add i32 %call_21, %call_23
+        br label %exit
+
+        exit:
+        ret i32 %Add_24
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_specialize() {
+        let code = r#"
+        int f(int x, int k) {
+            int i = 0;
+            while (i < k) {
+                x = x * 3 + i;
+                i = i + 1;
+            }
+            return x;
+        }
+        int main() {
+            return f(getint(), 2) + f(getint(), 5) + f(getint(), 2);
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(ipccp::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        [-] define i32 @f(i32 %x, i32 %k) {
+        [+] define i32 @main() {
+        entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        [-] %alloca_7 = alloca i32
+        [-] %alloca_9 = alloca i32
+        [+] %alloca_33 = alloca i32
+        [+] %call_36 = call i32 @getint()
+        [+] %call_37 = call i32 @f_spec0(i32 %call_36, i32 2)
+        [+] %call_38 = call i32 @getint()
+        [+] %call_39 = call i32 @f_spec1(i32 %call_38, i32 5)
+        [+] %Add_40 = add i32 %call_37, %call_39
+        [+] %call_41 = call i32 @getint()
+        [+] %call_42 = call i32 @f_spec0(i32 %call_41, i32 2)
+        [+] %Add_43 = add i32 %Add_40, %call_42
+        [+] br label %exit
+        [+] 
+        [+] exit:
+        [+] ret i32 %Add_43
+        [+] 
+        [+] 
+        [+] }
+        [+] define i32 @f_spec0(i32 %x, i32 %k) {
+        [+] entry:
+        [+] %alloca_49 = alloca i32
+        [+] %alloca_50 = alloca i32
+        [+] %alloca_51 = alloca i32
+        [+] %alloca_52 = alloca i32
+        br label %cond0
+
+        cond0:
+        [-] %phi_47 = phi i32 [0, %entry], [%Add_21, %body1]
+        [-] %phi_46 = phi i32 [%x, %entry], [%Add_18, %body1]
+        [-] %icmp_26 = icmp slt i32 %phi_47, %k
+        [-] br i1 %icmp_26, label %body1, label %final2
+        [+] %phi_55 = phi i32 [0, %entry], [%Add_66, %body1]
+        [+] %phi_56 = phi i32 [%x, %entry], [%Add_65, %body1]
+        [+] %icmp_57 = icmp slt i32 %phi_55, 2
+        [+] br i1 %icmp_57, label %body1, label %final2
+
+        body1:
+        [-] %Mul_16 = mul i32 %phi_46, 3
+        [-] %Add_18 = add i32 %Mul_16, %phi_47
+        [-] %Add_21 = add i32 %phi_47, 1
+        [+] %Mul_64 = mul i32 %phi_56, 3
+        [+] %Add_65 = add i32 %Mul_64, %phi_55
+        [+] %Add_66 = add i32 %phi_55, 1
+        br label %cond0
+
+        final2:
+        br label %exit
+
+        exit:
+        [-] ret i32 %phi_46
+        [+] ret i32 %phi_56
+
+
+        }
+        [-] define i32 @main() {
+        [+] define i32 @f_spec1(i32 %x, i32 %k) {
+        entry:
+        [-] %alloca_33 = alloca i32
+        [-] %call_36 = call i32 @getint()
+        [-] %call_37 = call i32 @f(i32 %call_36, i32 2)
+        [-] %call_38 = call i32 @getint()
+        [-] %call_39 = call i32 @f(i32 %call_38, i32 5)
+        [-] %Add_40 = add i32 %call_37, %call_39
+        [-] %call_41 = call i32 @getint()
+        [-] %call_42 = call i32 @f(i32 %call_41, i32 2)
+        [-] %Add_43 = add i32 %Add_40, %call_42
+        [+] %alloca_69 = alloca i32
+        [+] %alloca_70 = alloca i32
+        [+] %alloca_71 = alloca i32
+        [+] %alloca_72 = alloca i32
+        [+] br label %cond0
+        [+] 
+        [+] cond0:
+        [+] %phi_75 = phi i32 [0, %entry], [%Add_86, %body1]
+        [+] %phi_76 = phi i32 [%x, %entry], [%Add_85, %body1]
+        [+] %icmp_77 = icmp slt i32 %phi_75, 5
+        [+] br i1 %icmp_77, label %body1, label %final2
+        [+] 
+        [+] body1:
+        [+] %Mul_84 = mul i32 %phi_76, 3
+        [+] %Add_85 = add i32 %Mul_84, %phi_75
+        [+] %Add_86 = add i32 %phi_75, 1
+        [+] br label %cond0
+        [+] 
+        [+] final2:
+        br label %exit
+
+        exit:
+        [-] ret i32 %Add_43
+        [+] ret i32 %phi_76
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_ret_and_range() {
+        let code = r#"
+        int version() {
+            putint(1);
+            return 3;
+        }
+        int flag(int n) {
+            return n > 0;
+        }
+        int slot(int n) {
+            return flag(n) % 8 * 3 + 1;
+        }
+        int main() {
+            int a = version();
+            if (slot(getint()) < 0) {
+                a = a + 1;
+            }
+            return a;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(ipccp::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @version() {
+        entry:
+        %alloca_2 = alloca i32
+        call void @putint(i32 1)
+        br label %exit
+
+        exit:
+        ret i32 3
+
+
+        }
+        define i32 @flag(i32 %n) {
+        entry:
+        %alloca_10 = alloca i32
+        %alloca_13 = alloca i32
+        %icmp_16 = icmp sgt i32 %n, 0
+        %zext_17 = zext i1 %icmp_16 to i32
+        br label %exit
+
+        exit:
+        ret i32 %zext_17
+
+
+        }
+        define i32 @slot(i32 %n) {
+        entry:
+        %alloca_22 = alloca i32
+        %alloca_25 = alloca i32
+        %call_28 = call i32 @flag(i32 %n)
+        %SRem_29 = srem i32 %call_28, 8
+        %Mul_30 = mul i32 %SRem_29, 3
+        %Add_31 = add i32 %Mul_30, 1
+        br label %exit
+
+        exit:
+        ret i32 %Add_31
+
+
+        }
+        define i32 @main() {
+        entry:
+        %alloca_36 = alloca i32
+        %alloca_39 = alloca i32
+        %call_40 = call i32 @version()
+        br label %cond0
+
+        cond0:
+        %call_47 = call i32 @getint()
+        %call_48 = call i32 @slot(i32 %call_47)
+        [-] %icmp_49 = icmp slt i32 %call_48, 0
+        [-] br i1 %icmp_49, label %then1, label %alt2
+        [+] br i1 false, label %then1, label %alt2
+
+        then1:
+        [-] %Add_52 = add i32 %call_40, 1
+        [+] %Add_52 = add i32 3, 1
+        br label %final3
+
+        alt2:
+        br label %final3
+
+        final3:
+        [-] %phi_59 = phi i32 [%Add_52, %then1], [%call_40, %alt2]
+        [+] %phi_59 = phi i32 [%Add_52, %then1], [3, %alt2]
+        br label %exit
+
+        exit:
+        ret i32 %phi_59
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_range_wrapping() {
+        let code = r#"
+        int hash(int n) {
+            int h = 1, i = 0;
+            while (i < n) {
+                h = h * 100003 + 7;
+                i = i + 1;
+            }
+            return h;
+        }
+        int main() {
+            if (hash(getint()) < 0) {
+                putint(1);
+            } else {
+                putint(0);
+            }
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization, `hash` may wrap to negative
+        ipccp::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @hash(i32 %n) {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_7 = alloca i32
+        %alloca_9 = alloca i32
+        br label %cond0
+
+        cond0:
+        %phi_51 = phi i32 [0, %entry], [%Add_20, %body1]
+        %phi_50 = phi i32 [1, %entry], [%Add_17, %body1]
+        %icmp_25 = icmp slt i32 %phi_51, %n
+        br i1 %icmp_25, label %body1, label %final2
+
+        body1:
+        %Mul_16 = mul i32 %phi_50, 100003
+        %Add_17 = add i32 %Mul_16, 7
+        %Add_20 = add i32 %phi_51, 1
+        br label %cond0
+
+        final2:
+        br label %exit
+
+        exit:
+        ret i32 %phi_50
+
+
+        }
+        define i32 @main() {
+        entry:
+        %alloca_32 = alloca i32
+        br label %cond0
+
+        cond0:
+        %call_40 = call i32 @getint()
+        %call_41 = call i32 @hash(i32 %call_40)
+        %icmp_42 = icmp slt i32 %call_41, 0
+        br i1 %icmp_42, label %then1, label %alt2
+
+        then1:
+        call void @putint(i32 1)
+        br label %final3
+
+        alt2:
+        call void @putint(i32 0)
+        br label %final3
+
+        final3:
+        br label %exit
+
+        exit:
+        ret i32 0
+
+
+        }
+        "###);
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_loop_fission {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{loop_fission, mem2reg, redundance_elim},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_distribute() {
+        let code = r#"
+        int a[100];
+        int b[100];
+        int c[100];
+        int main() {
+            int i = 1;
+            while (i < 100) {
+                a[i] = a[i - 1] + c[i];
+                b[i] = c[i] * 2;
+                i = i + 1;
+            }
+            return a[99] + b[99];
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        redundance_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_fission::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @a = dso_local global [100 x i32] zeroinitializer
+        @b = dso_local global [100 x i32] zeroinitializer
+        @c = dso_local global [100 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        br label %cond0
+
+        cond0:
+        %phi_43 = phi i32 [1, %entry], [%Add_30, %body1]
+        %icmp_34 = icmp slt i32 %phi_43, 100
+        [-] br i1 %icmp_34, label %body1, label %final2
+        [+] br i1 %icmp_34, label %body1, label %entry_fission0
+
+        body1:
+        %Sub_12 = sub i32 %phi_43, 1
+        %getelementptr_13 = getelementptr [100 x i32], ptr @a, i32 0, i32 %Sub_12
+        %getelementptr_15 = getelementptr [100 x i32], ptr @c, i32 0, i32 %phi_43
+        %load_16 = load i32, ptr %getelementptr_13
+        %load_17 = load i32, ptr %getelementptr_15
+        %Add_18 = add i32 %load_16, %load_17
+        %getelementptr_20 = getelementptr [100 x i32], ptr @a, i32 0, i32 %phi_43
+        store i32 %Add_18, ptr %getelementptr_20
+        [-] %Mul_25 = mul i32 %load_17, 2
+        [-] %getelementptr_27 = getelementptr [100 x i32], ptr @b, i32 0, i32 %phi_43
+        [-] store i32 %Mul_25, ptr %getelementptr_27
+        %Add_30 = add i32 %phi_43, 1
+        br label %cond0
+        [+] 
+        [+] entry_fission0:
+        [+] br label %cond0_fission0
+        [+] 
+        [+] cond0_fission0:
+        [+] %phi_47 = phi i32 [1, %entry_fission0], [%Add_61, %body1_fission0]
+        [+] %icmp_48 = icmp slt i32 %phi_47, 100
+        [+] br i1 %icmp_48, label %body1_fission0, label %final2
+        [+] 
+        [+] body1_fission0:
+        [+] %getelementptr_52 = getelementptr [100 x i32], ptr @c, i32 0, i32 %phi_47
+        [+] %load_54 = load i32, ptr %getelementptr_52
+        [+] %Mul_58 = mul i32 %load_54, 2
+        [+] %getelementptr_59 = getelementptr [100 x i32], ptr @b, i32 0, i32 %phi_47
+        [+] store i32 %Mul_58, ptr %getelementptr_59
+        [+] %Add_61 = add i32 %phi_47, 1
+        [+] br label %cond0_fission0
+
+        final2:
+        %getelementptr_36 = getelementptr [100 x i32], ptr @a, i32 0, i32 99
+        %getelementptr_37 = getelementptr [100 x i32], ptr @b, i32 0, i32 99
+        %load_38 = load i32, ptr %getelementptr_36
+        %load_39 = load i32, ptr %getelementptr_37
+        %Add_40 = add i32 %load_38, %load_39
+        br label %exit
+
+        exit:
+        ret i32 %Add_40
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_sequential() {
+        let code = r#"
+        int a[100];
+        int b[100];
+        int main() {
+            int i = 1;
+            while (i < 100) {
+                a[i] = a[i - 1] + b[i - 1];
+                b[i] = a[i] * 2;
+                i = i + 1;
+            }
+            return a[99];
+        }
+        "#;
+
+        // Both statements are in the same recurrence, so loop is kept
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        redundance_elim::optimize_program(&mut program).unwrap();
+        assert!(!loop_fission::optimize_program(&mut program).unwrap());
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_loop_fusion {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{dead_code_elim, loop_fusion, mem2reg},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_fuse() {
+        let code = r#"
+        int a[100];
+        int b[100];
+        int main() {
+            int n = getint();
+            int i = 0;
+            int s = 0;
+            while (i < 100) {
+                a[i] = i * n;
+                s = s + i;
+                i = i + 1;
+            }
+            int m = n * 2;
+            i = 0;
+            int t = 0;
+            while (i < 100) {
+                b[i] = a[i] + m;
+                t = t + b[i];
+                i = i + 1;
+            }
+            return s + t;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_fusion::optimize_program(&mut program).unwrap());
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @a = dso_local global [100 x i32] zeroinitializer
+        @b = dso_local global [100 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %call_6 = call i32 @getint()
+        [+] %Mul_35 = mul i32 %call_6, 2
+        br label %cond0
+
+        cond0:
+        [-] %phi_72 = phi i32 [0, %entry], [%Add_24, %body1]
+        [-] %phi_70 = phi i32 [0, %entry], [%Add_27, %body1]
+        [+] %phi_73 = phi i32 [0, %entry], [%Add_56, %body4]
+        [+] %phi_72 = phi i32 [0, %entry], [%Add_24, %body4]
+        [+] %phi_70 = phi i32 [0, %entry], [%Add_27, %body4]
+        %icmp_31 = icmp slt i32 %phi_70, 100
+        [-] br i1 %icmp_31, label %body1, label %final2
+        [+] br i1 %icmp_31, label %body1, label %final5
+
+        body1:
+        %Mul_18 = mul i32 %phi_70, %call_6
+        %getelementptr_20 = getelementptr [100 x i32], ptr @a, i32 0, i32 %phi_70
+        store i32 %Mul_18, ptr %getelementptr_20
+        %Add_24 = add i32 %phi_72, %phi_70
+        %Add_27 = add i32 %phi_70, 1
+        [-] br label %cond0
+        [+] br label %body4
+
+        [-] final2:
+        [-] %Mul_35 = mul i32 %call_6, 2
+        [-] br label %cond3
+        [+] final5:
+        [+] %Add_67 = add i32 %phi_72, %phi_73
+        [+] br label %exit
+
+        [-] cond3:
+        [-] %phi_73 = phi i32 [0, %final2], [%Add_56, %body4]
+        [-] %phi_71 = phi i32 [0, %final2], [%Add_59, %body4]
+        [-] %icmp_63 = icmp slt i32 %phi_71, 100
+        [-] br i1 %icmp_63, label %body4, label %final5
+        [-] 
+        body4:
+        [-] %getelementptr_45 = getelementptr [100 x i32], ptr @a, i32 0, i32 %phi_71
+        [-] %load_46 = load i32, ptr %getelementptr_45
+        [+] %load_46 = load i32, ptr %getelementptr_20
+        %Add_48 = add i32 %load_46, %Mul_35
+        [-] %getelementptr_50 = getelementptr [100 x i32], ptr @b, i32 0, i32 %phi_71
+        [+] %getelementptr_50 = getelementptr [100 x i32], ptr @b, i32 0, i32 %phi_70
+        store i32 %Add_48, ptr %getelementptr_50
+        [-] %getelementptr_53 = getelementptr [100 x i32], ptr @b, i32 0, i32 %phi_71
+        [-] %load_55 = load i32, ptr %getelementptr_53
+        [+] %load_55 = load i32, ptr %getelementptr_50
+        %Add_56 = add i32 %phi_73, %load_55
+        [-] %Add_59 = add i32 %phi_71, 1
+        [-] br label %cond3
+        [-] 
+        [-] final5:
+        [-] %Add_67 = add i32 %phi_72, %phi_73
+        [-] br label %exit
+        [+] br label %cond0
+
+        exit:
+        ret i32 %Add_67
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_dependence() {
+        let code = r#"
+        int a[101];
+        int b[100];
+        int main() {
+            int i = 0;
+            while (i < 100) {
+                a[i] = i;
+                i = i + 1;
+            }
+            i = 0;
+            while (i < 100) {
+                b[i] = a[i + 1];
+                i = i + 1;
+            }
+            return b[50];
+        }
+        "#;
+
+        // Second loop reads elements written in later iterations of the first loop
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        assert!(!loop_fusion::optimize_program(&mut program).unwrap());
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_loop_interchange {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{constant_fold, dead_code_elim, loop_interchange, mem2reg},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_interchange() {
+        let code = r#"
+        int a[16][32];
+        int main() {
+            int n = getint();
+            int j = 0;
+            while (j < 32) {
+                int i = 0;
+                while (i < 16) {
+                    a[i][j] = a[i][j] + n;
+                    i = i + 1;
+                }
+                j = j + 1;
+            }
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_interchange::run_with_tile_size(&mut program, 0).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @a = dso_local global [16 x [32 x i32]] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %call_6 = call i32 @getint()
+        br label %cond0
+
+        cond0:
+        %phi_48 = phi i32 [0, %entry], [%Add_40, %final5]
+        [-] %icmp_44 = icmp slt i32 %phi_48, 32
+        [+] %icmp_44 = icmp slt i32 %phi_48, 16
+        br i1 %icmp_44, label %body1, label %final2
+
+        body1:
+        br label %cond3
+
+        final2:
+        br label %exit
+
+        cond3:
+        %phi_50 = phi i32 [0, %body1], [%Add_33, %body4]
+        [-] %icmp_37 = icmp slt i32 %phi_50, 16
+        [+] %icmp_37 = icmp slt i32 %phi_50, 32
+        br i1 %icmp_37, label %body4, label %final5
+
+        exit:
+        ret i32 0
+
+        body4:
+        [-] %getelementptr_22 = getelementptr [16 x [32 x i32]], ptr @a, i32 0, i32 %phi_50
+        [-] %getelementptr_23 = getelementptr [32 x i32], ptr %getelementptr_22, i32 0, i32 %phi_48
+        [+] %getelementptr_22 = getelementptr [16 x [32 x i32]], ptr @a, i32 0, i32 %phi_48
+        [+] %getelementptr_23 = getelementptr [32 x i32], ptr %getelementptr_22, i32 0, i32 %phi_50
+        %load_24 = load i32, ptr %getelementptr_23
+        %Add_26 = add i32 %load_24, %call_6
+        [-] %getelementptr_29 = getelementptr [16 x [32 x i32]], ptr @a, i32 0, i32 %phi_50
+        [-] %getelementptr_30 = getelementptr [32 x i32], ptr %getelementptr_29, i32 0, i32 %phi_48
+        [+] %getelementptr_29 = getelementptr [16 x [32 x i32]], ptr @a, i32 0, i32 %phi_48
+        [+] %getelementptr_30 = getelementptr [32 x i32], ptr %getelementptr_29, i32 0, i32 %phi_50
+        store i32 %Add_26, ptr %getelementptr_30
+        %Add_33 = add i32 %phi_50, 1
+        br label %cond3
+
+        final5:
+        %Add_40 = add i32 %phi_48, 1
+        br label %cond0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_dependence() {
+        let code = r#"
+        int a[16][32];
+        int main() {
+            int j = 0;
+            while (j < 31) {
+                int i = 1;
+                while (i < 16) {
+                    a[i][j] = a[i - 1][j + 1] + 1;
+                    i = i + 1;
+                }
+                j = j + 1;
+            }
+            return 0;
+        }
+        "#;
+
+        // Interchange reverses dependence from a[i - 1][j + 1] to a[i][j]
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        assert!(!loop_interchange::run_with_tile_size(&mut program, 4).unwrap());
+    }
+
+    #[test]
+    fn test_tile_overflow() {
+        let code = r#"
+        int main() {
+            int i = 0;
+            while (i < 8) {
+                int j = -5;
+                while (j < 2147483647) {
+                    j = j + 1;
+                }
+                i = i + 1;
+            }
+            return 0;
+        }
+        "#;
+
+        // Inner range 2147483647 - (-5) overflows, so the nest is not tiled
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        constant_fold::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        assert!(!loop_interchange::run_with_tile_size(&mut program, 4).unwrap());
+    }
+
+    #[test]
+    fn test_tile() {
+        let code = r#"
+        int a[16][32];
+        int b[32];
+        int main() {
+            int i = 0;
+            while (i < 16) {
+                int j = 0;
+                while (j < 32) {
+                    a[i][j] = b[j] + i;
+                    j = j + 1;
+                }
+                i = i + 1;
+            }
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_interchange::run_with_tile_size(&mut program, 8).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @a = dso_local global [16 x [32 x i32]] zeroinitializer
+        @b = dso_local global [32 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [-] br label %cond0
+        [+] br label %cond0_tile0
+
+        [+] cond0_tile0:
+        [+] %phi_48 = phi i32 [0, %entry], [%Add_49, %cond0_tile1]
+        [+] %Add_49 = add i32 %phi_48, 8
+        [+] %icmp_50 = icmp slt i32 %phi_48, 32
+        [+] br i1 %icmp_50, label %cond0, label %final2
+        [+] 
+        cond0:
+        [-] %phi_43 = phi i32 [0, %entry], [%Add_35, %final5]
+        [+] %phi_43 = phi i32 [%Add_35, %final5], [0, %cond0_tile0]
+        %icmp_39 = icmp slt i32 %phi_43, 16
+        [-] br i1 %icmp_39, label %body1, label %final2
+        [+] br i1 %icmp_39, label %body1, label %cond0_tile1
+
+        [-] body1:
+        [-] br label %cond3
+        [-] 
+        final2:
+        br label %exit
+
+        [-] cond3:
+        [-] %phi_45 = phi i32 [0, %body1], [%Add_28, %body4]
+        [-] %icmp_32 = icmp slt i32 %phi_45, 32
+        [-] br i1 %icmp_32, label %body4, label %final5
+        [+] body1:
+        [+] br label %cond3
+
+        [+] cond0_tile1:
+        [+] br label %cond0_tile0
+        [+] 
+        exit:
+        ret i32 0
+        [+] 
+        [+] cond3:
+        [+] %phi_45 = phi i32 [%phi_48, %body1], [%Add_28, %body4]
+        [+] %icmp_32 = icmp slt i32 %phi_45, %Add_49
+        [+] br i1 %icmp_32, label %body4, label %final5
+
+        body4:
+        %getelementptr_18 = getelementptr [32 x i32], ptr @b, i32 0, i32 %phi_45
+        %load_19 = load i32, ptr %getelementptr_18
+        %Add_21 = add i32 %load_19, %phi_43
+        %getelementptr_24 = getelementptr [16 x [32 x i32]], ptr @a, i32 0, i32 %phi_43
+        %getelementptr_25 = getelementptr [32 x i32], ptr %getelementptr_24, i32 0, i32 %phi_45
+        store i32 %Add_21, ptr %getelementptr_25
+        %Add_28 = add i32 %phi_45, 1
+        br label %cond3
+
+        final5:
+        %Add_35 = add i32 %phi_43, 1
+        br label %cond0
+
+
+        }
+        "###);
+    }
+}
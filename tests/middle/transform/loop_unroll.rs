@@ -0,0 +1,386 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_loop_unroll {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{constant_fold, dead_code_elim, loop_unroll, mem2reg},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_full_unroll() {
+        let code = r#"
+        int A[3];
+        int main() {
+            int i = 0;
+            while (i < 3) {
+                A[i] = i;
+                i = i + 1;
+            }
+            return i;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_unroll::optimize_program(&mut program).unwrap());
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @A = dso_local global [3 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        [-] br label %cond0
+        [+] br label %body1_unroll0
+
+        [-] cond0:
+        [-] %phi_25 = phi i32 [0, %entry], [%Add_16, %body1]
+        [-] %icmp_20 = icmp slt i32 %phi_25, 3
+        [-] br i1 %icmp_20, label %body1, label %final2
+        [+] body1_unroll0:
+        [+] %getelementptr_27 = getelementptr [3 x i32], ptr @A, i32 0, i32 0
+        [+] store i32 0, ptr %getelementptr_27
+        [+] %Add_29 = add i32 0, 1
+        [+] br label %body1_unroll1
+
+        [-] body1:
+        [-] %getelementptr_12 = getelementptr [3 x i32], ptr @A, i32 0, i32 %phi_25
+        [-] store i32 %phi_25, ptr %getelementptr_12
+        [-] %Add_16 = add i32 %phi_25, 1
+        [-] br label %cond0
+        [+] body1_unroll1:
+        [+] %getelementptr_32 = getelementptr [3 x i32], ptr @A, i32 0, i32 %Add_29
+        [+] store i32 %Add_29, ptr %getelementptr_32
+        [+] %Add_34 = add i32 %Add_29, 1
+        [+] br label %body1_unroll2
+
+        [+] body1_unroll2:
+        [+] %getelementptr_37 = getelementptr [3 x i32], ptr @A, i32 0, i32 %Add_34
+        [+] store i32 %Add_34, ptr %getelementptr_37
+        [+] %Add_39 = add i32 %Add_34, 1
+        [+] br label %final2
+        [+] 
+        final2:
+        br label %exit
+
+        exit:
+        [-] ret i32 %phi_25
+        [+] ret i32 %Add_39
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_partial_unroll() {
+        let code = r#"
+        int A[100];
+        int main() {
+            int i = 0;
+            int n = getint();
+            int s = 0;
+            while (i < n) {
+                s = s + A[i];
+                i = i + 2;
+            }
+            return s;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_unroll::optimize_program(&mut program).unwrap());
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @A = dso_local global [100 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        [-] %alloca_7 = alloca i32
+        %call_8 = call i32 @getint()
+        [-] %alloca_10 = alloca i32
+        [-] br label %cond0
+        [+] br label %entry_unroll0
+
+        [+] entry_unroll0:
+        [+] %Sub_37 = sub i32 %call_8, 6
+        [+] %icmp_38 = icmp sge i32 %call_8, -2147483642
+        [+] br i1 %icmp_38, label %cond0_unroll0, label %cond0
+        [+] 
+        [+] cond0_unroll0:
+        [+] %phi_40 = phi i32 [0, %entry_unroll0], [%Add_65, %body1_unroll3]
+        [+] %phi_41 = phi i32 [0, %entry_unroll0], [%Add_66, %body1_unroll3]
+        [+] %icmp_42 = icmp slt i32 %phi_41, %Sub_37
+        [+] br i1 %icmp_42, label %body1_unroll0, label %cond0
+        [+] 
+        cond0:
+        [-] %phi_34 = phi i32 [0, %entry], [%Add_20, %body1]
+        [-] %phi_33 = phi i32 [0, %entry], [%Add_23, %body1]
+        [+] %phi_34 = phi i32 [%Add_20, %body1], [0, %entry_unroll0], [%phi_40, %cond0_unroll0]
+        [+] %phi_33 = phi i32 [%Add_23, %body1], [0, %entry_unroll0], [%phi_41, %cond0_unroll0]
+        %icmp_28 = icmp slt i32 %phi_33, %call_8
+        br i1 %icmp_28, label %body1, label %final2
+
+        [+] body1_unroll0:
+        [+] %getelementptr_45 = getelementptr [100 x i32], ptr @A, i32 0, i32 %phi_41
+        [+] %load_46 = load i32, ptr %getelementptr_45
+        [+] %Add_47 = add i32 %phi_40, %load_46
+        [+] %Add_48 = add i32 %phi_41, 2
+        [+] br label %body1_unroll1
+        [+] 
+        body1:
+        %getelementptr_17 = getelementptr [100 x i32], ptr @A, i32 0, i32 %phi_33
+        %load_19 = load i32, ptr %getelementptr_17
+        %Add_20 = add i32 %phi_34, %load_19
+        %Add_23 = add i32 %phi_33, 2
+        br label %cond0
+
+        final2:
+        br label %exit
+
+        [+] body1_unroll1:
+        [+] %getelementptr_51 = getelementptr [100 x i32], ptr @A, i32 0, i32 %Add_48
+        [+] %load_52 = load i32, ptr %getelementptr_51
+        [+] %Add_53 = add i32 %Add_47, %load_52
+        [+] %Add_54 = add i32 %Add_48, 2
+        [+] br label %body1_unroll2
+        [+] 
+        exit:
+        ret i32 %phi_34
+        [+] 
+        [+] body1_unroll2:
+        [+] %getelementptr_57 = getelementptr [100 x i32], ptr @A, i32 0, i32 %Add_54
+        [+] %load_58 = load i32, ptr %getelementptr_57
+        [+] %Add_59 = add i32 %Add_53, %load_58
+        [+] %Add_60 = add i32 %Add_54, 2
+        [+] br label %body1_unroll3
+        [+] 
+        [+] body1_unroll3:
+        [+] %getelementptr_63 = getelementptr [100 x i32], ptr @A, i32 0, i32 %Add_60
+        [+] %load_64 = load i32, ptr %getelementptr_63
+        [+] %Add_65 = add i32 %Add_59, %load_64
+        [+] %Add_66 = add i32 %Add_60, 2
+        [+] br label %cond0_unroll0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_partial_unroll_near_int_max() {
+        let code = r#"
+        int main() {
+            int n = getint();
+            int i = n - 5;
+            int s = 0;
+            while (i < n) {
+                s = s + 1;
+                i = i + 1;
+            }
+            return s;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Bound of unrolled loop is `n - 3` instead of `i + 3`, which overflows when `n` is INT_MAX
+        assert!(loop_unroll::optimize_program(&mut program).unwrap());
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        %call_6 = call i32 @getint()
+        [-] %alloca_8 = alloca i32
+        %Sub_10 = sub i32 %call_6, 5
+        [-] %alloca_12 = alloca i32
+        [-] br label %cond0
+        [+] br label %entry_unroll0
+
+        [+] entry_unroll0:
+        [+] %Sub_36 = sub i32 %call_6, 3
+        [+] %icmp_37 = icmp sge i32 %call_6, -2147483645
+        [+] br i1 %icmp_37, label %cond0_unroll0, label %cond0
+        [+] 
+        [+] cond0_unroll0:
+        [+] %phi_39 = phi i32 [0, %entry_unroll0], [%Add_56, %body1_unroll3]
+        [+] %phi_40 = phi i32 [%Sub_10, %entry_unroll0], [%Add_57, %body1_unroll3]
+        [+] %icmp_41 = icmp slt i32 %phi_40, %Sub_36
+        [+] br i1 %icmp_41, label %body1_unroll0, label %cond0
+        [+] 
+        cond0:
+        [-] %phi_33 = phi i32 [0, %entry], [%Add_19, %body1]
+        [-] %phi_32 = phi i32 [%Sub_10, %entry], [%Add_22, %body1]
+        [+] %phi_33 = phi i32 [%Add_19, %body1], [0, %entry_unroll0], [%phi_39, %cond0_unroll0]
+        [+] %phi_32 = phi i32 [%Add_22, %body1], [%Sub_10, %entry_unroll0], [%phi_40, %cond0_unroll0]
+        %icmp_27 = icmp slt i32 %phi_32, %call_6
+        br i1 %icmp_27, label %body1, label %final2
+
+        [+] body1_unroll0:
+        [+] %Add_44 = add i32 %phi_39, 1
+        [+] %Add_45 = add i32 %phi_40, 1
+        [+] br label %body1_unroll1
+        [+] 
+        body1:
+        %Add_19 = add i32 %phi_33, 1
+        %Add_22 = add i32 %phi_32, 1
+        br label %cond0
+
+        final2:
+        br label %exit
+
+        [+] body1_unroll1:
+        [+] %Add_48 = add i32 %Add_44, 1
+        [+] %Add_49 = add i32 %Add_45, 1
+        [+] br label %body1_unroll2
+        [+] 
+        exit:
+        ret i32 %phi_33
+        [+] 
+        [+] body1_unroll2:
+        [+] %Add_52 = add i32 %Add_48, 1
+        [+] %Add_53 = add i32 %Add_49, 1
+        [+] br label %body1_unroll3
+        [+] 
+        [+] body1_unroll3:
+        [+] %Add_56 = add i32 %Add_52, 1
+        [+] %Add_57 = add i32 %Add_53, 1
+        [+] br label %cond0_unroll0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_partial_unroll_bound_underflow() {
+        let code = r#"
+        int main() {
+            int i = getint();
+            while (i < -2147483647) {
+                i = i + 1;
+            }
+            return i;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        constant_fold::optimize_program(&mut program).unwrap();
+
+        // Bound of unrolled loop `-2147483647 - 3` underflows, so loop is not unrolled
+        assert!(!loop_unroll::optimize_program(&mut program).unwrap());
+    }
+
+    #[test]
+    fn test_not_counted_loop() {
+        let code = r#"
+        int main() {
+            int i = 1;
+            int n = getint();
+            while (i < n) {
+                i = i * 2;
+            }
+            return i;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert!(!loop_unroll::optimize_program(&mut program).unwrap());
+    }
+}
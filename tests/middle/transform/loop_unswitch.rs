@@ -0,0 +1,324 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_loop_unswitch {
+
+    use std::collections::HashMap;
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        config::CONFIG,
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{
+                block_fuse, dead_code_elim, inst_combine, loop_optimization, loop_unswitch, mem2reg,
+            },
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_unswitch() {
+        let code = r#"
+        int a[100];
+        int main() {
+            int n = getint();
+            int c = n > 5;
+            int i = 0;
+            while (i < 100) {
+                if (c) {
+                    a[i] = 1;
+                } else {
+                    a[i] = 2;
+                }
+                i = i + 1;
+            }
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        loop_optimization::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization, dead arms are pruned afterwards
+        assert!(loop_unswitch::optimize_program(&mut program).unwrap());
+        inst_combine::optimize_program(&mut program).unwrap();
+        block_fuse::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @a = dso_local global [100 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %call_6 = call i32 @getint()
+        %icmp_10 = icmp sgt i32 %call_6, 5
+        %zext_11 = zext i1 %icmp_10 to i32
+        %icmp_25 = icmp ne i32 %zext_11, 0
+        [+] br i1 %icmp_25, label %entry_unswitch0, label %entry_unswitch1
+        [+] 
+        [+] entry_unswitch0:
+        br label %cond0
+
+        [+] entry_unswitch1:
+        [+] br label %cond0_unswitch0
+        [+] 
+        cond0:
+        [-] %phi_44 = phi i32 [0, %entry], [%Add_36, %final6]
+        [+] %phi_44 = phi i32 [%Add_36, %final6], [0, %entry_unswitch0]
+        %icmp_40 = icmp slt i32 %phi_44, 100
+        [-] br i1 %icmp_40, label %body1, label %final2
+        [+] br i1 %icmp_40, label %final6, label %exit
+
+        [-] body1:
+        [-] br label %cond3
+        [+] cond0_unswitch0:
+        [+] %phi_52 = phi i32 [%Add_58, %final6_unswitch0], [0, %entry_unswitch1]
+        [+] %icmp_53 = icmp slt i32 %phi_52, 100
+        [+] br i1 %icmp_53, label %final6_unswitch0, label %exit
+
+        [-] final2:
+        [-] br label %exit
+        [-] 
+        [-] cond3:
+        [-] br i1 %icmp_25, label %then4, label %alt5
+        [-] 
+        [-] exit:
+        [-] ret i32 0
+        [-] 
+        [-] then4:
+        [+] final6:
+        %getelementptr_28 = getelementptr [100 x i32], ptr @a, i32 0, i32 %phi_44
+        store i32 1, ptr %getelementptr_28
+        [-] br label %final6
+        [-] 
+        [-] alt5:
+        [-] %getelementptr_32 = getelementptr [100 x i32], ptr @a, i32 0, i32 %phi_44
+        [-] store i32 2, ptr %getelementptr_32
+        [-] br label %final6
+        [-] 
+        [-] final6:
+        %Add_36 = add i32 %phi_44, 1
+        br label %cond0
+        [+] 
+        [+] exit:
+        [+] ret i32 0
+        [+] 
+        [+] final6_unswitch0:
+        [+] %getelementptr_46 = getelementptr [100 x i32], ptr @a, i32 0, i32 %phi_52
+        [+] store i32 2, ptr %getelementptr_46
+        [+] %Add_58 = add i32 %phi_52, 1
+        [+] br label %cond0_unswitch0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_live_out() {
+        let code = r#"
+        int main() {
+            int n = getint();
+            int i = 0;
+            int s = 0;
+            while (i < n) {
+                if (n > 10) {
+                    s = s + i;
+                }
+                i = i + 1;
+            }
+            return s;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        loop_optimization::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(loop_unswitch::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %call_6 = call i32 @getint()
+        %icmp_22 = icmp sgt i32 %call_6, 10
+        [+] br i1 %icmp_22, label %entry_unswitch0, label %entry_unswitch1
+        [+] 
+        [+] entry_unswitch0:
+        br label %cond0
+
+        [+] entry_unswitch1:
+        [+] br label %cond0_unswitch0
+        [+] 
+        cond0:
+        [-] %phi_42 = phi i32 [0, %entry], [%phi_43, %final6]
+        [-] %phi_41 = phi i32 [0, %entry], [%Add_31, %final6]
+        [+] %phi_42 = phi i32 [%phi_43, %final6], [0, %entry_unswitch0]
+        [+] %phi_41 = phi i32 [%Add_31, %final6], [0, %entry_unswitch0]
+        %icmp_36 = icmp slt i32 %phi_41, %call_6
+        br i1 %icmp_36, label %body1, label %final2
+
+        [+] cond0_unswitch0:
+        [+] %phi_49 = phi i32 [%phi_56, %final6_unswitch0], [0, %entry_unswitch1]
+        [+] %phi_50 = phi i32 [%Add_57, %final6_unswitch0], [0, %entry_unswitch1]
+        [+] %icmp_51 = icmp slt i32 %phi_50, %call_6
+        [+] br i1 %icmp_51, label %body1_unswitch0, label %final2
+        [+] 
+        body1:
+        br label %cond3
+
+        final2:
+        [+] %phi_67 = phi i32 [%phi_42, %cond0], [%phi_49, %cond0_unswitch0]
+        br label %exit
+
+        [+] body1_unswitch0:
+        [+] br label %cond3_unswitch0
+        [+] 
+        cond3:
+        [-] br i1 %icmp_22, label %then4, label %alt5
+        [+] br i1 true, label %then4, label %alt5
+
+        exit:
+        [-] ret i32 %phi_42
+        [+] ret i32 %phi_67
+
+        [+] cond3_unswitch0:
+        [+] br i1 false, label %then4_unswitch0, label %alt5_unswitch0
+        [+] 
+        then4:
+        %Add_26 = add i32 %phi_42, %phi_41
+        br label %final6
+
+        alt5:
+        br label %final6
+
+        [+] then4_unswitch0:
+        [+] %Add_60 = add i32 %phi_49, %phi_50
+        [+] br label %final6_unswitch0
+        [+] 
+        [+] alt5_unswitch0:
+        [+] br label %final6_unswitch0
+        [+] 
+        final6:
+        %phi_43 = phi i32 [%Add_26, %then4], [%phi_42, %alt5]
+        %Add_31 = add i32 %phi_41, 1
+        br label %cond0
+        [+] 
+        [+] final6_unswitch0:
+        [+] %phi_56 = phi i32 [%phi_49, %alt5_unswitch0], [%Add_60, %then4_unswitch0]
+        [+] %Add_57 = add i32 %phi_50, 1
+        [+] br label %cond0_unswitch0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_budget_across_runs() {
+        let mut conds = String::new();
+        for k in 0..8 {
+            conds += &format!("if (n > {}) {{ s = s + {}; }}\n", k, k + 1);
+        }
+        let code = format!(
+            r#"
+            int main() {{
+                int n = getint();
+                int s = 0;
+                int i = 0;
+                while (i < 100) {{
+                    {}
+                    i = i + 1;
+                }}
+                return s;
+            }}
+            "#,
+            conds
+        );
+        let parsed = parse(&code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        loop_optimization::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let size = |program: &compiler::middle::Program| -> usize {
+            let main = program
+                .module
+                .functions
+                .iter()
+                .find(|f| f.is_main())
+                .unwrap();
+            main.dfs_iter().map(|bb| bb.iter().count()).sum()
+        };
+        let size_before = size(&program);
+
+        // Unswitch until nothing changes, like main loop of optimizer.
+        // Without shared budget, each of 2^8 versions would be cloned.
+        let mut growth = HashMap::new();
+        while loop_unswitch::optimize_program_with_growth(&mut program, &mut growth).unwrap() {
+            inst_combine::optimize_program(&mut program).unwrap();
+            block_fuse::optimize_program(&mut program).unwrap();
+            verify_program(&program).unwrap();
+        }
+        assert!(growth.values().all(|g| *g <= CONFIG.loop_unswitch_budget));
+        assert!(size(&program) <= size_before + CONFIG.loop_unswitch_budget);
+    }
+}
@@ -17,11 +17,22 @@
 mod block_fuse;
 mod constant_fold;
 mod dead_code_elim;
+mod dead_global_elim;
 mod func_inline;
+mod ipccp;
 mod load_elim;
+mod loop_fission;
+mod loop_fusion;
+mod loop_interchange;
 mod loop_optimization;
+mod loop_unroll;
+mod loop_unswitch;
 mod make_parallel;
 mod mem2reg;
+mod pass_manager;
 mod redundance_elim;
+mod sccp;
 mod store_elim;
+mod strength_reduce;
 mod symbolic_eval;
+mod tail_recursion_elim;
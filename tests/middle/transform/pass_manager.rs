@@ -0,0 +1,154 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+pub mod tests_pass_manager {
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::{analysis_manager::Analysis, verifier::verify_program},
+            irgen::gen,
+            transform::pass_manager::{self, PassManager},
+            Program,
+        },
+    };
+
+    fn gen_program(code: &str) -> Program {
+        let parsed = parse(code).unwrap();
+        gen(&parsed).unwrap()
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let code = r#"
+        int a[10];
+        int main() {
+            int i = 0, n = getint();
+            while (i < 10) {
+                a[i] = n * 2;
+                i = i + 1;
+            }
+            return a[3];
+        }
+        "#;
+        let mut program = gen_program(code);
+        pass_manager::optimize_program(
+            &mut program,
+            "mem2reg,licm,symbolic_eval,dead_code_elim,block_fuse",
+        )
+        .unwrap();
+        verify_program(&program).unwrap();
+        let llvm_ir = program.module.gen_llvm_ir();
+        let main = &llvm_ir[llvm_ir.find("define i32 @main").unwrap()..];
+        assert_snapshot!(main, @r###"
+        define i32 @main() {
+        entry:
+        %call_8 = call i32 @getint()
+        %Mul_15 = mul i32 %call_8, 2
+        br label %cond0
+
+        cond0:
+        %phi_30 = phi i32 [0, %entry], [%Add_20, %body1]
+        %icmp_24 = icmp slt i32 %phi_30, 10
+        br i1 %icmp_24, label %body1, label %exit
+
+        body1:
+        %getelementptr_17 = getelementptr [10 x i32], ptr @a, i32 0, i32 %phi_30
+        store i32 %Mul_15, ptr %getelementptr_17
+        %Add_20 = add i32 %phi_30, 1
+        br label %cond0
+
+        exit:
+        %getelementptr_26 = getelementptr [10 x i32], ptr @a, i32 0, i32 3
+        %load_27 = load i32, ptr %getelementptr_26
+        ret i32 %load_27
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_all_passes() {
+        let code = r#"
+        int g = 3;
+        int f(int x) {
+            return x * g;
+        }
+        int main() {
+            int i = 0, s = 0;
+            while (i < 100) {
+                s = s + f(i);
+                i = i + 1;
+            }
+            g = s;
+            putint(g);
+            return 0;
+        }
+        "#;
+        let mut program = gen_program(code);
+        let pipeline = pass_manager::pass_names().join(",");
+        pass_manager::optimize_program(&mut program, &pipeline).unwrap();
+        verify_program(&program).unwrap();
+    }
+
+    #[test]
+    fn test_analysis_cache() {
+        let code = r#"
+        int a = 1;
+        int main() {
+            int b = a;
+            a = 2;
+            a = b + 3;
+            return a;
+        }
+        "#;
+        let mut program = gen_program(code);
+        let mut manager = PassManager::new(&mut program);
+
+        // Load and store elimination share one MemorySSA
+        manager
+            .run_pipeline("load_elim,store_elim,load_elim")
+            .unwrap();
+        assert_eq!(manager.analyses().build_count(Analysis::EffectAnalysis), 1);
+        assert_eq!(manager.analyses().build_count(Analysis::MemorySSA), 1);
+
+        // Changing the program invalidates MemorySSA
+        assert!(manager.run_pipeline("constant_fold").unwrap());
+        assert!(!manager.analyses().is_cached(Analysis::MemorySSA));
+        manager.run_pipeline("load_elim").unwrap();
+        assert_eq!(manager.analyses().build_count(Analysis::EffectAnalysis), 2);
+        assert_eq!(manager.analyses().build_count(Analysis::MemorySSA), 2);
+
+        // Dominator trees survive passes that keep the CFG
+        manager.run_pipeline("sink_code").unwrap();
+        assert!(manager.analyses().is_cached(Analysis::DominatorTree));
+        assert!(!manager.analyses().is_cached(Analysis::EffectAnalysis));
+    }
+
+    #[test]
+    fn test_unknown_pass() {
+        let mut program = gen_program("int main() { return 0; }");
+        let err = pass_manager::optimize_program(&mut program, "mem2reg,gvn")
+            .err()
+            .unwrap();
+        assert_snapshot!(err.root_cause(), @r###"
+        unknown pass `gvn`, available passes: mem2reg, tail_recursion_elim, func_inline, symbolic_eval, constant_fold, sccp, ipccp, dead_code_elim, dead_global_elim, load_elim, store_elim, redundance_elim, block_fuse, sink_code, make_parallel, loop_unroll, loop_unswitch, loop_interchange, loop_fusion, loop_fission, strength_reduce, loop_simplify, licm, ldce
+        "###);
+    }
+}
@@ -0,0 +1,350 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_sccp {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{mem2reg, sccp},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_phi_constant() {
+        let code = r#"
+        int main() {
+            int x = 1;
+            int y;
+            if (x > 0) {
+                y = 2;
+            } else {
+                y = 3;
+            }
+            return y + x;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(sccp::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_7 = alloca i32
+        br label %cond0
+
+        cond0:
+        [-] %icmp_14 = icmp sgt i32 1, 0
+        [-] br i1 %icmp_14, label %then1, label %alt2
+        [+] br label %then1
+
+        then1:
+        br label %final3
+
+        [-] alt2:
+        [-] br label %final3
+        [-] 
+        final3:
+        [-] %phi_25 = phi i32 [2, %then1], [3, %alt2]
+        [-] %Add_22 = add i32 %phi_25, 1
+        br label %exit
+
+        exit:
+        [-] ret i32 %Add_22
+        [+] ret i32 3
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_loop_constant() {
+        let code = r#"
+        int main() {
+            int i = 0;
+            int x = 5;
+            while (i < getint()) {
+                if (x != 5) {
+                    x = x + 1;
+                }
+                i = i + 1;
+            }
+            return x;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(sccp::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_7 = alloca i32
+        br label %cond0
+
+        cond0:
+        [-] %phi_38 = phi i32 [5, %entry], [%phi_39, %final6]
+        %phi_37 = phi i32 [0, %entry], [%Add_27, %final6]
+        %call_30 = call i32 @getint()
+        %icmp_32 = icmp slt i32 %phi_37, %call_30
+        br i1 %icmp_32, label %body1, label %final2
+
+        body1:
+        br label %cond3
+
+        final2:
+        br label %exit
+
+        cond3:
+        [-] %icmp_19 = icmp ne i32 %phi_38, 5
+        [-] br i1 %icmp_19, label %then4, label %alt5
+        [+] br label %alt5
+
+        exit:
+        [-] ret i32 %phi_38
+        [+] ret i32 5
+
+        [-] then4:
+        [-] %Add_22 = add i32 %phi_38, 1
+        [-] br label %final6
+        [-] 
+        alt5:
+        br label %final6
+
+        final6:
+        [-] %phi_39 = phi i32 [%Add_22, %then4], [%phi_38, %alt5]
+        %Add_27 = add i32 %phi_37, 1
+        br label %cond0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_switch_phi() {
+        let code = r#"
+        int main() {
+            int c = getint(), x = getint(), y = 0;
+            if (c) {
+                switch (x) {
+                    case 1: y = 5; break;
+                    case 2: y = 7; break;
+                }
+            }
+            putint(y);
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        sccp::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %call_6 = call i32 @getint()
+        %alloca_8 = alloca i32
+        %call_9 = call i32 @getint()
+        %alloca_11 = alloca i32
+        br label %cond0
+
+        cond0:
+        %icmp_19 = icmp ne i32 %call_6, 0
+        br i1 %icmp_19, label %then1, label %alt2
+
+        then1:
+        switch i32 %call_9, label %final6 [ i32 1, label %case4 i32 2, label %case5 ]
+
+        alt2:
+        br label %final3
+
+        final6:
+        %phi_37 = phi i32 [0, %then1], [5, %case4], [7, %case5]
+        br label %final3
+
+        case4:
+        br label %final6
+
+        case5:
+        br label %final6
+
+        final3:
+        %phi_36 = phi i32 [%phi_37, %final6], [0, %alt2]
+        call void @putint(i32 %phi_36)
+        br label %exit
+
+        exit:
+        ret i32 0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_switch_constant() {
+        let code = r#"
+        int main() {
+            int x = 2, y = 0;
+            switch (x) {
+                case 1: y = 5; break;
+                case 2: y = 7; break;
+                default: y = 9;
+            }
+            return y;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(sccp::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_7 = alloca i32
+        [-] switch i32 2, label %default2 [ i32 1, label %case0 i32 2, label %case1 ]
+        [+] br label %case1
+
+        [-] default2:
+        [-] br label %final3
+        [-] 
+        [-] case0:
+        [-] br label %final3
+        [-] 
+        case1:
+        br label %final3
+
+        final3:
+        [-] %phi_24 = phi i32 [9, %default2], [5, %case0], [7, %case1]
+        br label %exit
+
+        exit:
+        [-] ret i32 %phi_24
+        [+] ret i32 7
+
+
+        }
+        "###);
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_strength_reduce {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{dead_code_elim, loop_optimization, mem2reg, strength_reduce},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_derived_indvar() {
+        let code = r#"
+        int a[300];
+        int main() {
+            int n = getint();
+            int i = 0;
+            while (i < n) {
+                a[i * 3 + 2] = i;
+                i = i + 1;
+            }
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(strength_reduce::optimize_program(&mut program).unwrap());
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        @a = dso_local global [300 x i32] zeroinitializer
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        [-] %alloca_2 = alloca i32
+        [-] %alloca_5 = alloca i32
+        %call_6 = call i32 @getint()
+        [-] %alloca_8 = alloca i32
+        br label %cond0
+
+        cond0:
+        [+] %phi_31 = phi i32 [2, %entry], [%Add_32, %body1]
+        %phi_30 = phi i32 [0, %entry], [%Add_21, %body1]
+        %icmp_26 = icmp slt i32 %phi_30, %call_6
+        br i1 %icmp_26, label %body1, label %final2
+
+        body1:
+        [-] %Mul_15 = mul i32 %phi_30, 3
+        [-] %Add_16 = add i32 %Mul_15, 2
+        [-] %getelementptr_17 = getelementptr [300 x i32], ptr @a, i32 0, i32 %Add_16
+        [+] %getelementptr_17 = getelementptr [300 x i32], ptr @a, i32 0, i32 %phi_31
+        store i32 %phi_30, ptr %getelementptr_17
+        %Add_21 = add i32 %phi_30, 1
+        [+] %Add_32 = add i32 %phi_31, 3
+        br label %cond0
+
+        final2:
+        br label %exit
+
+        exit:
+        ret i32 0
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_exit_value() {
+        let code = r#"
+        int main() {
+            int n = getint();
+            int i = 0;
+            int s = 0;
+            int k = 7;
+            while (i < 100) {
+                s = s + 3;
+                k = k + n;
+                i = i + 1;
+            }
+            return s + k;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(strength_reduce::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %call_6 = call i32 @getint()
+        %alloca_8 = alloca i32
+        %alloca_10 = alloca i32
+        %alloca_12 = alloca i32
+        [+] %Mul_40 = mul i32 %call_6, 100
+        [+] %Add_41 = add i32 %Mul_40, 7
+        br label %cond0
+
+        cond0:
+        %phi_39 = phi i32 [7, %entry], [%Add_23, %body1]
+        %phi_38 = phi i32 [0, %entry], [%Add_19, %body1]
+        %phi_37 = phi i32 [0, %entry], [%Add_26, %body1]
+        %icmp_30 = icmp slt i32 %phi_37, 100
+        br i1 %icmp_30, label %body1, label %final2
+
+        body1:
+        %Add_19 = add i32 %phi_38, 3
+        %Add_23 = add i32 %phi_39, %call_6
+        %Add_26 = add i32 %phi_37, 1
+        br label %cond0
+
+        final2:
+        [-] %Add_34 = add i32 %phi_38, %phi_39
+        [+] %Add_34 = add i32 300, %Add_41
+        br label %exit
+
+        exit:
+        ret i32 %Add_34
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_exit_value_unknown_trip_count() {
+        let code = r#"
+        int main() {
+            int n = getint();
+            int i = 0;
+            int s = 0;
+            while (i < n) {
+                s = s + 3;
+                i = i + 1;
+            }
+            return s;
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+
+        // Exit value `3 * max(n, 0)` is not affine, so it's kept
+        assert!(!strength_reduce::optimize_program(&mut program).unwrap());
+    }
+
+    #[test]
+    fn test_dead_loop() {
+        let code = r#"
+        int main() {
+            int j = 0;
+            int t = 0;
+            while (j < 100) {
+                t = t + 3;
+                j = j + 1;
+            }
+            putint(t);
+            return 0;
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization, loop is deleted once its exit values are replaced
+        assert!(strength_reduce::optimize_program(&mut program).unwrap());
+        loop_optimization::optimize_program(&mut program).unwrap();
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @main() {
+        entry:
+        %alloca_2 = alloca i32
+        %alloca_5 = alloca i32
+        %alloca_7 = alloca i32
+        [-] br label %cond0
+        [+] br label %final2
+
+        [-] cond0:
+        [-] %phi_28 = phi i32 [0, %entry], [%Add_14, %body1]
+        [-] %phi_27 = phi i32 [0, %entry], [%Add_17, %body1]
+        [-] %icmp_21 = icmp slt i32 %phi_27, 100
+        [-] br i1 %icmp_21, label %body1, label %final2
+        [-] 
+        [-] body1:
+        [-] %Add_14 = add i32 %phi_28, 3
+        [-] %Add_17 = add i32 %phi_27, 1
+        [-] br label %cond0
+        [-] 
+        final2:
+        [-] call void @putint(i32 %phi_28)
+        [+] call void @putint(i32 300)
+        br label %exit
+
+        exit:
+        ret i32 0
+
+
+        }
+        "###);
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+#[cfg(test)]
+pub mod tests_tail_recursion_elim {
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{
+            analysis::verifier::verify_program,
+            irgen::gen,
+            transform::{dead_code_elim, mem2reg, tail_recursion_elim},
+        },
+        utils::diff::diff,
+    };
+
+    #[test]
+    fn test_tail_call() {
+        let code = r#"
+        int gcd(int a, int b) {
+            if (b == 0) return a;
+            return gcd(b, a % b);
+        }
+        int main() {
+            return gcd(1071, 462);
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(tail_recursion_elim::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @gcd(i32 %a, i32 %b) {
+        [+] entry_tre0:
+        [+] br label %entry
+        [+] 
+        entry:
+        [+] %phi_40 = phi i32 [%a, %entry_tre0], [%phi_39, %final3]
+        [+] %phi_39 = phi i32 [%b, %entry_tre0], [%SRem_24, %final3]
+        br label %cond0
+
+        cond0:
+        [-] %icmp_15 = icmp eq i32 %b, 0
+        [+] %icmp_15 = icmp eq i32 %phi_39, 0
+        br i1 %icmp_15, label %then1, label %alt2
+
+        then1:
+        br label %exit
+
+        alt2:
+        br label %final3
+
+        exit:
+        [-] %phi_36 = phi i32 [%a, %then1], [%call_25, %final3]
+        [-] ret i32 %phi_36
+        [+] ret i32 %phi_40
+
+        final3:
+        [-] %SRem_24 = srem i32 %a, %b
+        [-] %call_25 = call i32 @gcd(i32 %b, i32 %SRem_24)
+        [-] br label %exit
+        [+] %SRem_24 = srem i32 %phi_40, %phi_39
+        [+] br label %entry
+
+
+        }
+        define i32 @main() {
+        entry:
+        %call_33 = call i32 @gcd(i32 1071, i32 462)
+        br label %exit
+
+        exit:
+        ret i32 %call_33
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_accumulator() {
+        let code = r#"
+        int sum(int n) {
+            if (n == 0) return 0;
+            return n + sum(n - 1);
+        }
+        int main() {
+            return sum(10);
+        }
+        "#;
+
+        // Check before optimization
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        dead_code_elim::optimize_program(&mut program).unwrap();
+        let llvm_before = program.module.gen_llvm_ir();
+
+        // Check after optimization
+        assert!(tail_recursion_elim::optimize_program(&mut program).unwrap());
+        verify_program(&program).unwrap();
+        let llvm_after = program.module.gen_llvm_ir();
+        assert_snapshot!(diff(&llvm_before, &llvm_after), @r###"
+        declare i32 @getint()
+        declare i32 @getch()
+        declare float @getfloat()
+        declare void @putint(i32 %p0)
+        declare void @putch(i32 %p0)
+        declare void @putfloat(float %p0)
+        declare i32 @getarray(i32* %p0)
+        declare i32 @getfarray(float* %p0)
+        declare void @putarray(i32 %p0, i32* %p1)
+        declare void @putfarray(i32 %p0, float* %p1)
+        declare void @_sysy_starttime(i32 %p0)
+        declare void @_sysy_stoptime(i32 %p0)
+        declare i32 @thrd_create(i32 %p0)
+        declare void @thrd_join()
+        declare void @putf()
+        declare void @llvm.memset.p0.i32(i32* %p0, i8 %p1, i32 %p2, i1 %p3)
+        define i32 @sum(i32 %n) {
+        [+] entry_tre0:
+        [+] br label %entry
+        [+] 
+        entry:
+        [+] %phi_37 = phi i32 [0, %entry_tre0], [%Add_38, %final3]
+        [+] %phi_36 = phi i32 [%n, %entry_tre0], [%Sub_19, %final3]
+        br label %cond0
+
+        cond0:
+        [-] %icmp_13 = icmp eq i32 %n, 0
+        [+] %icmp_13 = icmp eq i32 %phi_36, 0
+        br i1 %icmp_13, label %then1, label %alt2
+
+        then1:
+        br label %exit
+
+        alt2:
+        br label %final3
+
+        exit:
+        [-] %phi_33 = phi i32 [0, %then1], [%Add_22, %final3]
+        [-] ret i32 %phi_33
+        [+] %Add_39 = add i32 %phi_37, 0
+        [+] ret i32 %Add_39
+
+        final3:
+        [-] %Sub_19 = sub i32 %n, 1
+        [-] %call_20 = call i32 @sum(i32 %Sub_19)
+        [-] %Add_22 = add i32 %n, %call_20
+        [-] br label %exit
+        [+] %Sub_19 = sub i32 %phi_36, 1
+        [+] %Add_38 = add i32 %phi_37, %phi_36
+        [+] br label %entry
+
+
+        }
+        define i32 @main() {
+        entry:
+        %call_30 = call i32 @sum(i32 10)
+        br label %exit
+
+        exit:
+        ret i32 %call_30
+
+
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_not_tail_call() {
+        let code = r#"
+        int f(int n) {
+            if (n == 0) return 0;
+            return f(n - 1) - n;
+        }
+        int g(int n, int b[]) {
+            int a[2] = {};
+            if (n == 0) return b[0];
+            a[0] = n;
+            return g(n - 1, a);
+        }
+        int main() {
+            int b[1] = {};
+            return f(10) + g(3, b);
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        mem2reg::optimize_program(&mut program).unwrap();
+        assert!(!tail_recursion_elim::optimize_program(&mut program).unwrap());
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2024 Duskphantom Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+extern crate compiler;
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use insta::assert_snapshot;
+
+    use compiler::{
+        frontend::parse,
+        middle::{irgen::gen, transform::pass_manager},
+        utils::pass_instrument::{self, PassInstrument},
+    };
+
+    #[test]
+    fn test_dump_and_record() {
+        let code = r#"
+        int a[10];
+        int main() {
+            int i = 0, n = getint();
+            while (i < 10) {
+                a[i] = n * 2;
+                i = i + 1;
+            }
+            return a[3];
+        }
+        "#;
+        let parsed = parse(code).unwrap();
+        let mut program = gen(&parsed).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        pass_instrument::install(PassInstrument::new(
+            vec!["mem2reg".to_string()],
+            vec!["all".to_string()],
+            dir.path().to_path_buf(),
+            true,
+        ));
+        pass_manager::optimize_program(&mut program, "mem2reg,licm,constant_fold").unwrap();
+        let instrument = pass_instrument::uninstall().unwrap();
+
+        // Each pass is recorded once, and loop passes detect changes by comparing IR
+        let records = instrument
+            .records()
+            .iter()
+            .map(|r| format!("{} {}", r.name, r.changed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            ["mem2reg true", "licm true", "constant_fold false"]
+        );
+
+        // Dumps are numbered in order of passes
+        let mut dumps = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        dumps.sort();
+        assert_snapshot!(dumps.join("\n"), @r###"
+        0000-before-mem2reg-module.ll
+        0001-after-mem2reg-module.ll
+        0002-after-licm-module.ll
+        0003-after-constant_fold-module.ll
+        "###);
+        let after_licm = std::fs::read_to_string(dir.path().join(&dumps[2])).unwrap();
+        assert_eq!(after_licm, program.module.gen_llvm_ir());
+    }
+
+    #[test]
+    fn test_report() {
+        let mut instrument = PassInstrument::default();
+        instrument.record("mem2reg", true, Duration::from_micros(1500));
+        instrument.record("dead_code_elim", false, Duration::from_micros(250));
+        instrument.record("reg_alloc", true, Duration::from_micros(2000));
+        instrument.record("dead_code_elim", true, Duration::from_micros(1250));
+        assert_snapshot!(instrument.report(), @r###"
+        ===== Pass execution timing report =====
+           Time (ms)   Share   Runs  Changed  Pass
+               1.500   30.0%      1        1  mem2reg
+               1.500   30.0%      2        1  dead_code_elim
+               2.000   40.0%      1        1  reg_alloc
+               5.000  100.0%      4        3  Total
+        "###);
+    }
+}